@@ -0,0 +1,218 @@
+//! Message throughput of the public API, run with `cargo bench --bench throughput [filter]`.
+//!
+//! Every benchmark runs on a current-thread and on a multi-thread runtime, one iteration is
+//! one message, so that criterion reports the throughput in messages per second.
+
+use criterion::{criterion_group, criterion_main, BenchmarkGroup, Criterion, Throughput};
+use elliot::{ActorRef, ActorRefGone, ActorSystem, Behaviors, ReplyTo, SpawnOptions};
+use std::{
+    future::Future,
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{Duration, Instant},
+};
+use tokio::runtime::{Builder, Runtime};
+
+struct Ping {
+    count: u64,
+    reply: ActorRef<Pong>,
+}
+
+struct Pong {
+    count: u64,
+    reply: ActorRef<Ping>,
+}
+
+async fn pong(this: ActorRef<Ping>, ping: Ping) -> Result<(), ActorRefGone<Pong>> {
+    ping.reply.tell(Pong {
+        count: ping.count,
+        reply: this,
+    })
+}
+
+async fn ping(this: ActorRef<Pong>, pong: Pong) -> Option<Result<(), ActorRefGone<Ping>>> {
+    let count = pong.count.checked_sub(1)?;
+    Some(pong.reply.tell(Ping { count, reply: this }))
+}
+
+/// Two actors that send one message back and forth.
+async fn ping_pong(messages: u64) -> Duration {
+    let system = ActorSystem::new();
+    let pinger = system.spawn("ping", ping);
+    let ponger = system.spawn("pong", pong);
+    let start = Instant::now();
+    let _ = pinger.tell(Pong {
+        count: messages.div_ceil(2),
+        reply: ponger,
+    });
+    pinger.wait_for_stop().await;
+    start.elapsed()
+}
+
+/// One sender that tells an actor as fast as it can.
+async fn tell(messages: u64) -> Duration {
+    let system = ActorSystem::new();
+    let sink = system.spawn("sink", move |n: u64| async move {
+        if n + 1 == messages {
+            Behaviors::Stopped
+        } else {
+            Behaviors::Same
+        }
+    });
+    let start = Instant::now();
+    for n in 0..messages {
+        let _ = sink.tell(n);
+    }
+    sink.wait_for_stop().await;
+    start.elapsed()
+}
+
+/// One asker that waits for every reply before it asks again.
+async fn ask(asks: u64) -> Duration {
+    let system = ActorSystem::new();
+    let echo = system.spawn("echo", |reply: ReplyTo<u64>| async move {
+        let _ = reply.reply(42);
+    });
+    let start = Instant::now();
+    for _ in 0..asks {
+        let _ = echo.ask(|reply| reply).await;
+    }
+    start.elapsed()
+}
+
+/// One sender that waits for space in a bounded mailbox.
+async fn bounded(messages: u64) -> Duration {
+    let system = ActorSystem::new();
+    let sink = system.spawn_bounded("sink", 1024, move |n: u64| async move {
+        if n + 1 == messages {
+            Behaviors::Stopped
+        } else {
+            Behaviors::Same
+        }
+    });
+    let start = Instant::now();
+    for n in 0..messages {
+        let _ = sink.send(n).await;
+    }
+    sink.wait_for_stop().await;
+    start.elapsed()
+}
+
+/// Many senders that tell one actor, with a sharded mailbox of `lanes`.
+async fn contended(messages: u64, lanes: usize) -> Duration {
+    const SENDERS: u64 = 64;
+    let system = ActorSystem::new();
+    let left = AtomicUsize::new(messages as usize);
+    let sink = system.spawn_with(
+        "sink",
+        SpawnOptions::default().lanes(lanes),
+        move |(): ()| {
+            let done = left.fetch_sub(1, Ordering::Relaxed) == 1;
+            async move {
+                if done {
+                    Behaviors::Stopped
+                } else {
+                    Behaviors::Same
+                }
+            }
+        },
+    );
+    let start = Instant::now();
+    for sender in 0..SENDERS {
+        let sink = sink.clone();
+        let per_sender = messages / SENDERS + u64::from(sender < messages % SENDERS);
+        drop(tokio::spawn(async move {
+            for _ in 0..per_sender {
+                let _ = sink.tell(());
+            }
+        }));
+    }
+    sink.wait_for_stop().await;
+    start.elapsed()
+}
+
+fn current_thread() -> Runtime {
+    Builder::new_current_thread().build().unwrap()
+}
+
+fn multi_thread() -> Runtime {
+    Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+}
+
+/// Benchmark `run` with as many messages as criterion asks for, on `runtime`.
+fn messages<F, Fut>(
+    group: &mut BenchmarkGroup<'_, criterion::measurement::WallTime>,
+    name: &str,
+    runtime: &Runtime,
+    run: F,
+) where
+    F: Fn(u64) -> Fut,
+    Fut: Future<Output = Duration>,
+{
+    let _ = group.bench_function(name, |b| {
+        b.iter_custom(|messages| runtime.block_on(run(messages.max(1))));
+    });
+}
+
+fn runtimes() -> [(&'static str, Runtime); 2] {
+    [
+        ("current_thread", current_thread()),
+        ("multi_thread", multi_thread()),
+    ]
+}
+
+fn pingpong_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("pingpong");
+    let _ = group.throughput(Throughput::Elements(1));
+    for (name, runtime) in runtimes() {
+        messages(&mut group, name, &runtime, ping_pong);
+    }
+    group.finish();
+}
+
+fn tell_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("tell");
+    let _ = group.throughput(Throughput::Elements(1));
+    for (name, runtime) in runtimes() {
+        messages(&mut group, name, &runtime, tell);
+    }
+    group.finish();
+}
+
+fn ask_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("ask");
+    let _ = group.throughput(Throughput::Elements(1));
+    for (name, runtime) in runtimes() {
+        messages(&mut group, name, &runtime, ask);
+    }
+    group.finish();
+}
+
+fn mailbox_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("mailbox");
+    let _ = group.throughput(Throughput::Elements(1));
+    for (name, runtime) in runtimes() {
+        messages(&mut group, &format!("bounded/{name}"), &runtime, bounded);
+    }
+    let runtime = multi_thread();
+    for lanes in [1, 4] {
+        messages(
+            &mut group,
+            &format!("contended/{lanes}_lanes/multi_thread"),
+            &runtime,
+            |messages| contended(messages, lanes),
+        );
+    }
+    group.finish();
+}
+
+criterion_group!(
+    benches,
+    pingpong_benches,
+    tell_benches,
+    ask_benches,
+    mailbox_benches
+);
+criterion_main!(benches);
@@ -1,21 +1,145 @@
 use std::{
+    future::{poll_fn, Future},
+    marker::PhantomData,
     ops::{Deref, DerefMut},
-    sync::Arc,
+    sync::{Arc, Weak},
+    time::Duration,
 };
 
-use crate::ActorRefGone;
-use tokio::sync::mpsc;
+use crate::{
+    adapter::{Sender, WeakSender},
+    behavior,
+    cell::ActorCell,
+    conflate,
+    envelope::{Envelope, Meta},
+    mailbox::{LaneSenders, Lanes, Sizer},
+    replace, schedule, sequence, throttle, ActorPath, ActorRefGone, ActorSystem, Behavior, Effect,
+    Rate, ScheduledTell, Sequenced, Signal, TrySendError,
+};
+use tokio::sync::{mpsc, oneshot};
 
 pub struct ActorRef<T> {
-    pub(crate) tx: mpsc::UnboundedSender<T>,
+    /// The lane of the mailbox that this ref sends to, see [`SpawnOptions::lanes`](crate::SpawnOptions::lanes).
+    pub(crate) tx: Sender<T>,
+    pub(crate) cell: Arc<ActorCell>,
+    pub(crate) sizer: Sizer<T>,
+    pub(crate) lanes: Lanes<T>,
+    /// A place in the mailbox that was reserved by [`Sink::poll_ready`](futures_util::Sink::poll_ready).
+    pub(crate) reserved: bool,
 }
 
 impl<T> ActorRef<T> {
     pub fn tell(&self, msg: T) -> Result<(), ActorRefGone<T>> {
-        if let Err(e) = self.tx.send(msg) {
-            return Err(ActorRefGone(e.0));
+        self.deliver(msg, Meta::capture(None))
+    }
+
+    /// Like [`ActorRef::tell`], but the message and the messages that result from it carry `correlation_id` in their [`Meta`].
+    pub fn tell_correlated(
+        &self,
+        msg: T,
+        correlation_id: impl Into<Arc<str>>,
+    ) -> Result<(), ActorRefGone<T>> {
+        self.deliver(msg, Meta::capture(Some(correlation_id.into())))
+    }
+
+    /// Like [`ActorRef::tell`], but the message is a dead letter instead of being handled
+    /// if it is still in the mailbox `ttl` from now.
+    pub fn tell_with_ttl(&self, msg: T, ttl: Duration) -> Result<(), ActorRefGone<T>> {
+        let mut meta = Meta::capture(None);
+        meta.expires_at = meta.enqueued_at.checked_add(ttl);
+        self.deliver(msg, meta)
+    }
+
+    pub(crate) fn deliver(&self, msg: T, meta: Meta) -> Result<(), ActorRefGone<T>> {
+        let size = self.size_of(&msg);
+        self.deliver_sized(msg, meta, size)
+    }
+
+    fn deliver_sized(&self, msg: T, meta: Meta, size: usize) -> Result<(), ActorRefGone<T>> {
+        if let Sender::Adapted(adapter) = &self.tx {
+            return adapter.deliver(msg, meta);
+        }
+        self.cell.enqueued(size);
+        self.deliver_enqueued(msg, meta, size)
+    }
+
+    /// Send a message that has already been counted in the mailbox, see [`ActorCell::try_enqueue`].
+    ///
+    /// The message is no longer counted if the actor is gone.
+    pub(crate) fn deliver_enqueued(
+        &self,
+        msg: T,
+        meta: Meta,
+        size: usize,
+    ) -> Result<(), ActorRefGone<T>> {
+        match &self.tx {
+            Sender::Lane(tx) => tx.send(Envelope { msg, meta, size }).map_err(|e| {
+                self.cell.dequeued(size);
+                ActorRefGone(e.0.msg)
+            }),
+            Sender::Adapted(adapter) => adapter.deliver_enqueued(msg, meta, size),
         }
-        Ok(())
+    }
+
+    /// Send a message into a place in the mailbox that was counted without its bytes.
+    pub(crate) fn deliver_reserved(&self, msg: T) -> Result<(), ActorRefGone<T>> {
+        let size = self.size_of(&msg);
+        self.cell.enqueued_bytes(size);
+        self.deliver_enqueued(msg, Meta::capture(None), size)
+    }
+
+    fn size_of(&self, msg: &T) -> usize {
+        self.sizer.get().map_or(0, |sizer| sizer(msg))
+    }
+
+    /// Wait until the mailbox has room for the message, then send it.
+    ///
+    /// Only actors that are spawned with [`ActorSystem::spawn_bounded`] or
+    /// [`ActorSystem::spawn_bounded_by_size`] make the sender wait,
+    /// for all other actors this is the same as [`ActorRef::tell`].
+    pub async fn send(&self, msg: T) -> Result<(), ActorRefGone<T>> {
+        let size = self.size_of(&msg);
+        let capacity = self.cell.capacity();
+        if capacity == usize::MAX && size == 0 {
+            return self.deliver_sized(msg, Meta::capture(None), size);
+        }
+        tokio::select! {
+            () = poll_fn(|cx| self.cell.poll_enqueue(capacity, size, cx)) => {}
+            () = self.tx.closed() => return Err(ActorRefGone(msg)),
+        }
+        self.deliver_enqueued(msg, Meta::capture(None), size)
+    }
+
+    /// Like [`ActorRef::send`], but rejects the message instead of waiting while the mailbox is full.
+    pub fn try_send(&self, msg: T) -> Result<(), TrySendError<T>> {
+        if self.is_alive() == false {
+            return Err(TrySendError::ActorRefGone(msg));
+        }
+        let size = self.size_of(&msg);
+        if self.cell.try_enqueue(self.cell.capacity(), size) == false {
+            return Err(TrySendError::Full(msg));
+        }
+        self.deliver_enqueued(msg, Meta::capture(None), size)
+            .map_err(|ActorRefGone(msg)| TrySendError::ActorRefGone(msg))
+    }
+
+    /// Stop the actor once it has handled its current message.
+    ///
+    /// The request overtakes the messages that are waiting in the mailbox, they are not handled.
+    pub fn stop(&self) {
+        self.cell.request_stop();
+    }
+
+    /// This ref without its message type, see [`AnyActorRef`](crate::AnyActorRef).
+    pub fn to_any(&self) -> crate::AnyActorRef
+    where
+        T: Send + 'static,
+    {
+        self.clone().into()
+    }
+
+    pub fn path(&self) -> &ActorPath {
+        &self.cell.path
     }
 
     pub fn is_alive(&self) -> bool {
@@ -25,34 +149,264 @@ impl<T> ActorRef<T> {
     pub async fn wait_for_stop(&self) {
         self.tx.closed().await
     }
+
+    /// A ref that does not keep the actor alive.
+    pub fn downgrade(&self) -> WeakActorRef<T> {
+        let tx = match &self.lanes {
+            Some(lanes) => WeakSender::Lane(lanes.first().downgrade()),
+            None => self.tx.downgrade(),
+        };
+        WeakActorRef {
+            tx,
+            cell: Arc::clone(&self.cell),
+            sizer: Arc::clone(&self.sizer),
+            lanes: self.lanes.as_ref().map(Arc::downgrade),
+        }
+    }
+
+    pub(crate) fn same_actor(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.cell, &other.cell)
+    }
+}
+
+/// A ref to an actor that does not prevent the actor from stopping once all [`ActorRef`]s are gone.
+pub struct WeakActorRef<T> {
+    tx: WeakSender<T>,
+    pub(crate) cell: Arc<ActorCell>,
+    sizer: Sizer<T>,
+    /// Weak, so that the lanes close once all [`ActorRef`]s are gone.
+    lanes: Option<Weak<LaneSenders<T>>>,
+}
+
+impl<T> WeakActorRef<T> {
+    /// Returns `None` if the actor has stopped, or is stopping because there are no more refs to it.
+    pub fn upgrade(&self) -> Option<ActorRef<T>> {
+        let tx = self.tx.upgrade()?;
+        self.cell.referenced();
+        Some(ActorRef {
+            tx,
+            cell: Arc::clone(&self.cell),
+            sizer: Arc::clone(&self.sizer),
+            lanes: self.lanes.as_ref().and_then(Weak::upgrade),
+            reserved: false,
+        })
+    }
+
+    pub fn path(&self) -> &ActorPath {
+        &self.cell.path
+    }
+
+    /// A ref that rejects all messages, for when the actor can no longer be reached.
+    fn gone(&self) -> ActorRef<T> {
+        let (tx, _) = mpsc::unbounded_channel();
+        let tx = Sender::Lane(tx);
+        self.cell.referenced();
+        ActorRef {
+            tx,
+            cell: Arc::clone(&self.cell),
+            sizer: Arc::clone(&self.sizer),
+            lanes: None,
+            reserved: false,
+        }
+    }
+}
+
+impl<T: Send + 'static> ActorRef<T> {
+    /// Deliver the message after the given delay.
+    ///
+    /// The returned handle can be used to cancel the delivery.
+    pub fn tell_after(&self, msg: T, delay: Duration) -> ScheduledTell<T> {
+        schedule::tell_after(self, msg, delay)
+    }
+
+    /// Continue the actor with `behavior` from the next message on, e.g. to reconfigure it.
+    ///
+    /// The replacement overtakes the waiting messages, the current message is handled by the old
+    /// behavior. Actors that are spawned with [`ActorSystem::spawn_local`] keep their handler.
+    pub fn replace_behavior<B, Args>(&self, behavior: B)
+    where
+        B: Behavior<T, Args>,
+        Args: 'static,
+    {
+        self.cell.replace_behavior(replace::replacement(behavior));
+    }
+
+    /// Stop the actor and take the messages that are waiting in its mailbox.
+    ///
+    /// Like [`ActorRef::stop`], the request is sent right away and overtakes the waiting messages,
+    /// the current message is handled first. The mailbox is closed at once, so no message is lost
+    /// in between. Returns no messages if the actor has already stopped.
+    pub fn drain(&self) -> impl Future<Output = Vec<T>> + Send + 'static {
+        let envelopes = self.drain_envelopes();
+        async move {
+            let envelopes = envelopes.await;
+            envelopes.into_iter().map(|envelope| envelope.msg).collect()
+        }
+    }
+
+    /// Stop the actor and tell the messages that are waiting in its mailbox to `successor`,
+    /// e.g. to replace an actor with a new version of its behavior.
+    ///
+    /// See [`ActorRef::drain`], the messages keep their [`Meta`]. If the successor has stopped,
+    /// the messages that it did not receive are returned.
+    pub fn hand_over(
+        &self,
+        successor: &ActorRef<T>,
+    ) -> impl Future<Output = Result<(), ActorRefGone<Vec<T>>>> + Send + 'static {
+        let envelopes = self.drain_envelopes();
+        let successor = successor.clone();
+        async move {
+            let mut envelopes = envelopes.await.into_iter();
+            while let Some(Envelope { msg, meta, .. }) = envelopes.next() {
+                if let Err(ActorRefGone(msg)) = successor.deliver(msg, meta) {
+                    let rest = envelopes.map(|envelope| envelope.msg);
+                    return Err(ActorRefGone(std::iter::once(msg).chain(rest).collect()));
+                }
+            }
+            Ok(())
+        }
+    }
+
+    fn drain_envelopes(&self) -> impl Future<Output = Vec<Envelope<T>>> {
+        let (drained, envelopes) = oneshot::channel();
+        self.cell.request_drain(drained);
+        async move { envelopes.await.unwrap_or_default() }
+    }
+
+    /// A ref that delivers at most at the given rate to this actor.
+    pub fn throttled(&self, rate: Rate) -> ActorRef<T> {
+        throttle::throttled(self, rate)
+    }
+
+    /// A ref that delivers at most one message per `window` to this actor,
+    /// only the latest message of each window is delivered.
+    pub fn conflated(&self, window: Duration) -> ActorRef<T> {
+        conflate::conflated(self, window, |_, latest| latest)
+    }
+
+    /// A ref that delivers at most one message per `window` to this actor,
+    /// all messages of each window are combined using `merge`.
+    pub fn conflated_with<F>(&self, window: Duration, merge: F) -> ActorRef<T>
+    where
+        F: FnMut(T, T) -> T + Send + 'static,
+    {
+        conflate::conflated(self, window, merge)
+    }
+
+    /// A ref that delivers the messages of every [`SequencedRef`](crate::SequencedRef) to this actor
+    /// in the order in which they were stamped.
+    ///
+    /// Messages that arrive early wait for the ones before them, retries of delivered messages are dropped.
+    pub fn sequenced(&self) -> ActorRef<Sequenced<T>> {
+        sequence::sequenced(self)
+    }
 }
 
 #[derive(Debug)]
 pub struct ActorContext<T> {
-    // TODO weak, and ref = Arc(channel)
-    this: ActorRef<T>,
+    pub(crate) this: WeakActorRef<T>,
     // TODO: add handle somehow
-    name: Arc<str>,
+    system: ActorSystem,
 }
 
 impl<T> ActorContext<T> {
-    pub(crate) fn new(this: ActorRef<T>, name: Arc<str>) -> Self {
-        Self { this, name }
+    pub(crate) fn new(this: WeakActorRef<T>, system: ActorSystem) -> Self {
+        Self { this, system }
+    }
+
+    pub fn system(&self) -> &ActorSystem {
+        &self.system
     }
 
+    /// A ref to this actor.
+    ///
+    /// Once all other refs are gone, the returned ref keeps the actor alive while it handles the
+    /// messages that are still waiting. After that the actor stops and the ref does not accept
+    /// new messages.
     pub fn this(&self) -> ActorRef<T> {
-        self.this.clone()
+        self.this.upgrade().unwrap_or_else(|| self.this.gone())
     }
 
     pub fn name(&self) -> &str {
-        &*self.name
+        self.this.path().name()
+    }
+
+    pub fn path(&self) -> &ActorPath {
+        self.this.path()
+    }
+
+    /// Spawn a child of this actor with a generated unique name like `$a-42`,
+    /// e.g. for a short-lived worker.
+    pub fn spawn_anonymous<U: Send + 'static, A, Args>(&self, behavior: A) -> ActorRef<U>
+    where
+        A: Behavior<U, Args>,
+    {
+        let path = self.path().child(&self.system.inner.anonymous_name());
+        if let Some(effects) = self.this.cell.effects() {
+            return effects.spawned(path);
+        }
+        behavior::actor_of(&self.system, path, behavior)
+    }
+
+    /// Receive [`Signal::Terminated`] once `target` has stopped.
+    ///
+    /// Like [`ActorSystem::watch`], but without a message type for the notification.
+    pub fn watch<U>(&self, target: &ActorRef<U>) {
+        if let Some(effects) = self.this.cell.effects() {
+            effects.record(Effect::Watched(target.path().clone()));
+            return;
+        }
+        let cell = Arc::clone(&self.this.cell);
+        self.system.watch_with(target, move |terminated| {
+            cell.signal(Signal::Terminated(terminated))
+        });
+    }
+
+    /// Receive [`Signal::ReceiveTimeout`] whenever no message or signal has arrived within `timeout`,
+    /// `None` disables the timeout again.
+    pub fn set_receive_timeout(&self, timeout: impl Into<Option<Duration>>) {
+        let timeout = timeout.into();
+        if let Some(effects) = self.this.cell.effects() {
+            effects.record(Effect::ReceiveTimeout(timeout));
+        }
+        self.this.cell.set_receive_timeout(timeout);
     }
 }
 
 impl<T> Clone for ActorRef<T> {
+    fn clone(&self) -> Self {
+        self.cell.referenced();
+        // the clones of a sharded ref are spread over the lanes
+        let tx = match &self.lanes {
+            Some(lanes) => Sender::Lane(lanes.next()),
+            None => self.tx.clone(),
+        };
+        Self {
+            tx,
+            cell: Arc::clone(&self.cell),
+            sizer: Arc::clone(&self.sizer),
+            lanes: self.lanes.clone(),
+            reserved: false,
+        }
+    }
+}
+
+impl<T> Drop for ActorRef<T> {
+    fn drop(&mut self) {
+        if self.reserved {
+            self.cell.dequeued(0);
+        }
+        self.cell.unreferenced();
+    }
+}
+
+impl<T> Clone for WeakActorRef<T> {
     fn clone(&self) -> Self {
         Self {
             tx: self.tx.clone(),
+            cell: Arc::clone(&self.cell),
+            sizer: Arc::clone(&self.sizer),
+            lanes: self.lanes.clone(),
         }
     }
 }
@@ -61,22 +415,42 @@ impl<T> Clone for ActorContext<T> {
     fn clone(&self) -> Self {
         Self {
             this: self.this.clone(),
-            name: Arc::clone(&self.name),
+            system: self.system.clone(),
         }
     }
 }
 
 impl<T> std::fmt::Debug for ActorRef<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        f.debug_struct("ActorRef").finish_non_exhaustive()
+        f.debug_struct("ActorRef")
+            .field("path", self.path())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> std::fmt::Debug for WeakActorRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("WeakActorRef")
+            .field("path", self.path())
+            .finish_non_exhaustive()
     }
 }
 
+/// Publish and subscribe to events of type `T` on the system bus.
 #[derive(Debug)]
-pub struct SystemBus<T>(pub(crate) Option<T>);
+pub struct SystemBus<T> {
+    pub(crate) system: ActorSystem,
+    pub(crate) _event: PhantomData<fn(T)>,
+}
 
-impl<T> SystemBus<T> {
-    pub async fn publish(&self, _msg: T) {}
+impl<T: Clone + Send + 'static> SystemBus<T> {
+    pub async fn publish(&self, msg: T) {
+        let _ = self.system.publish(msg);
+    }
+
+    pub fn subscribe(&self, subscriber: &ActorRef<T>) {
+        self.system.subscribe(subscriber);
+    }
 }
 
 #[derive(Debug)]
@@ -0,0 +1,166 @@
+use crate::{envelope::Envelope, ActorRef, ActorRefGone, Meta, WeakActorRef};
+use std::{future::Future, pin::Pin, sync::Arc};
+use tokio::sync::mpsc;
+
+/// Where a ref sends its messages, a lane of the mailbox of its actor or an adapter
+/// that converts them for an actor of another message type, see [`ActorRef::accepting`].
+pub(crate) enum Sender<T> {
+    Lane(mpsc::UnboundedSender<Envelope<T>>),
+    Adapted(Arc<dyn Adapter<T>>),
+}
+
+/// The [`Sender`] of a [`WeakActorRef`].
+pub(crate) enum WeakSender<T> {
+    Lane(mpsc::WeakUnboundedSender<Envelope<T>>),
+    Adapted(Arc<dyn WeakAdapter<T>>),
+}
+
+pub(crate) trait Adapter<U>: Send + Sync {
+    fn deliver(&self, msg: U, meta: Meta) -> Result<(), ActorRefGone<U>>;
+    /// See [`ActorRef::deliver_enqueued`].
+    fn deliver_enqueued(&self, msg: U, meta: Meta, size: usize) -> Result<(), ActorRefGone<U>>;
+    fn is_closed(&self) -> bool;
+    fn closed(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>>;
+    fn downgrade(&self) -> Arc<dyn WeakAdapter<U>>;
+}
+
+pub(crate) trait WeakAdapter<U>: Send + Sync {
+    fn upgrade(&self) -> Option<Arc<dyn Adapter<U>>>;
+}
+
+struct Adapted<T, U> {
+    target: ActorRef<T>,
+    convert: fn(U) -> T,
+}
+
+struct WeakAdapted<T, U> {
+    target: WeakActorRef<T>,
+    convert: fn(U) -> T,
+}
+
+impl<T: Send + 'static, U: 'static> Adapter<U> for Adapted<T, U> {
+    fn deliver(&self, msg: U, meta: Meta) -> Result<(), ActorRefGone<U>> {
+        if self.target.is_alive() == false {
+            return Err(ActorRefGone(msg));
+        }
+        // the converted message can not be given back if the actor stops in the meantime
+        if let Err(ActorRefGone(msg)) = self.target.deliver((self.convert)(msg), meta) {
+            self.target.cell.dead_letter_with(msg);
+        }
+        Ok(())
+    }
+
+    fn deliver_enqueued(&self, msg: U, meta: Meta, size: usize) -> Result<(), ActorRefGone<U>> {
+        if self.target.is_alive() == false {
+            self.target.cell.dequeued(size);
+            return Err(ActorRefGone(msg));
+        }
+        if let Err(ActorRefGone(msg)) =
+            self.target
+                .deliver_enqueued((self.convert)(msg), meta, size)
+        {
+            self.target.cell.dead_letter_with(msg);
+        }
+        Ok(())
+    }
+
+    fn is_closed(&self) -> bool {
+        self.target.is_alive() == false
+    }
+
+    fn closed(&self) -> Pin<Box<dyn Future<Output = ()> + Send + '_>> {
+        Box::pin(self.target.wait_for_stop())
+    }
+
+    fn downgrade(&self) -> Arc<dyn WeakAdapter<U>> {
+        Arc::new(WeakAdapted {
+            target: self.target.downgrade(),
+            convert: self.convert,
+        })
+    }
+}
+
+impl<T: Send + 'static, U: 'static> WeakAdapter<U> for WeakAdapted<T, U> {
+    fn upgrade(&self) -> Option<Arc<dyn Adapter<U>>> {
+        let target = self.target.upgrade()?;
+        Some(Arc::new(Adapted {
+            target,
+            convert: self.convert,
+        }))
+    }
+}
+
+impl<T> Sender<T> {
+    pub(crate) fn is_closed(&self) -> bool {
+        match self {
+            Self::Lane(tx) => tx.is_closed(),
+            Self::Adapted(adapter) => adapter.is_closed(),
+        }
+    }
+
+    pub(crate) async fn closed(&self) {
+        match self {
+            Self::Lane(tx) => tx.closed().await,
+            Self::Adapted(adapter) => adapter.closed().await,
+        }
+    }
+
+    pub(crate) fn downgrade(&self) -> WeakSender<T> {
+        match self {
+            Self::Lane(tx) => WeakSender::Lane(tx.downgrade()),
+            Self::Adapted(adapter) => WeakSender::Adapted(adapter.downgrade()),
+        }
+    }
+}
+
+impl<T> WeakSender<T> {
+    pub(crate) fn upgrade(&self) -> Option<Sender<T>> {
+        match self {
+            Self::Lane(tx) => tx.upgrade().map(Sender::Lane),
+            Self::Adapted(adapter) => adapter.upgrade().map(Sender::Adapted),
+        }
+    }
+}
+
+impl<T: Send + 'static> ActorRef<T> {
+    /// A ref for messages of type `U`, which are converted into messages of this actor when they are told.
+    ///
+    /// Senders can depend on a narrow message type that they own, while the actor receives a wider one.
+    /// The ref sends directly to the mailbox of this actor, there is no actor in between.
+    /// A message that is told while the actor stops can end up with its dead letters.
+    pub fn accepting<U>(&self) -> ActorRef<U>
+    where
+        T: From<U>,
+        U: 'static,
+    {
+        self.cell.referenced();
+        ActorRef {
+            tx: Sender::Adapted(Arc::new(Adapted {
+                target: self.clone(),
+                convert: T::from,
+            })),
+            cell: Arc::clone(&self.cell),
+            sizer: Arc::default(),
+            lanes: None,
+            reserved: false,
+        }
+    }
+}
+
+impl<T> Clone for Sender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Lane(tx) => Self::Lane(tx.clone()),
+            Self::Adapted(adapter) => Self::Adapted(Arc::clone(adapter)),
+        }
+    }
+}
+
+impl<T> Clone for WeakSender<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Lane(tx) => Self::Lane(tx.clone()),
+            Self::Adapted(adapter) => Self::Adapted(Arc::clone(adapter)),
+        }
+    }
+}
@@ -0,0 +1,118 @@
+use crate::{ActorPath, ActorRef, AnyTellError};
+use std::{
+    any::{self, Any, TypeId},
+    sync::Arc,
+};
+
+/// A message of any type, as sent through an [`AnyActorRef`].
+pub type AnyMessage = Box<dyn Any + Send>;
+
+/// A ref that hides the message type of its actor, so that refs of different types can be kept together.
+///
+/// Messages are checked against the type of the actor when they are told. An actor that opts into
+/// dynamic messages by receiving [`AnyMessage`]s gets every message as is, and downcasts it itself.
+#[derive(Clone)]
+pub struct AnyActorRef {
+    inner: Arc<dyn Erased>,
+}
+
+trait Erased: Send + Sync {
+    fn tell(&self, msg: AnyMessage) -> Result<(), AnyTellError>;
+    fn accepts(&self, message_type: TypeId) -> bool;
+    fn message_type(&self) -> &'static str;
+    fn path(&self) -> &ActorPath;
+    fn is_alive(&self) -> bool;
+    fn stop(&self);
+    fn as_any(&self) -> &dyn Any;
+}
+
+impl<T: Send + 'static> Erased for ActorRef<T> {
+    fn tell(&self, msg: AnyMessage) -> Result<(), AnyTellError> {
+        // an actor of `AnyMessage` takes the box itself
+        let msg = if TypeId::of::<T>() == TypeId::of::<AnyMessage>() {
+            Box::new(msg)
+        } else {
+            msg
+        };
+        match msg.downcast::<T>() {
+            Ok(msg) => ActorRef::tell(self, *msg)
+                .map_err(|gone| AnyTellError::ActorRefGone(Box::new(gone.0))),
+            Err(msg) => Err(AnyTellError::WrongType(msg)),
+        }
+    }
+
+    fn accepts(&self, message_type: TypeId) -> bool {
+        TypeId::of::<T>() == message_type || TypeId::of::<T>() == TypeId::of::<AnyMessage>()
+    }
+
+    fn message_type(&self) -> &'static str {
+        any::type_name::<T>()
+    }
+
+    fn path(&self) -> &ActorPath {
+        ActorRef::path(self)
+    }
+
+    fn is_alive(&self) -> bool {
+        ActorRef::is_alive(self)
+    }
+
+    fn stop(&self) {
+        ActorRef::stop(self);
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+}
+
+impl AnyActorRef {
+    /// Send `msg` if the actor receives its type, the message is returned otherwise.
+    pub fn tell(&self, msg: AnyMessage) -> Result<(), AnyTellError> {
+        self.inner.tell(msg)
+    }
+
+    /// Returns `true` if the actor receives messages of type `M`.
+    pub fn accepts<M: 'static>(&self) -> bool {
+        self.inner.accepts(TypeId::of::<M>())
+    }
+
+    /// The name of the message type of the actor.
+    pub fn message_type(&self) -> &'static str {
+        self.inner.message_type()
+    }
+
+    /// The typed ref, if the actor receives messages of type `T`.
+    pub fn downcast<T: Send + 'static>(&self) -> Option<ActorRef<T>> {
+        self.inner.as_any().downcast_ref::<ActorRef<T>>().cloned()
+    }
+
+    pub fn path(&self) -> &ActorPath {
+        self.inner.path()
+    }
+
+    pub fn is_alive(&self) -> bool {
+        self.inner.is_alive()
+    }
+
+    pub fn stop(&self) {
+        self.inner.stop();
+    }
+}
+
+impl<T: Send + 'static> From<ActorRef<T>> for AnyActorRef {
+    fn from(actor: ActorRef<T>) -> Self {
+        Self {
+            inner: Arc::new(actor),
+        }
+    }
+}
+
+impl std::fmt::Debug for AnyActorRef {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AnyActorRef")
+            .field("path", self.path())
+            .field("message_type", &self.message_type())
+            .finish()
+    }
+}
@@ -0,0 +1,367 @@
+use crate::{runtime::Runtime, ActorRef, AskError};
+use std::{
+    future::{poll_fn, Future},
+    pin::Pin,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    task::{Context, Poll, Wake, Waker},
+    thread::{self, Thread},
+    time::Duration,
+};
+use tokio::sync::{oneshot, Semaphore};
+
+/// The reply channel of an ask, to be included in the message that is sent to the actor.
+#[derive(Debug)]
+pub struct ReplyTo<R> {
+    tx: oneshot::Sender<R>,
+}
+
+impl<R> ReplyTo<R> {
+    /// Send the reply, returns the reply if the asker is no longer waiting for it.
+    pub fn reply(self, reply: R) -> Result<(), R> {
+        self.tx.send(reply)
+    }
+
+    /// Returns `true` if the asker is no longer waiting for a reply.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Wait until the asker is no longer waiting for a reply, e.g. because the ask was cancelled,
+    /// so that the work for the reply can be abandoned in a `tokio::select!`.
+    pub async fn closed(&mut self) {
+        self.tx.closed().await;
+    }
+}
+
+fn reply_channel<R>() -> (ReplyTo<R>, oneshot::Receiver<R>) {
+    let (tx, rx) = oneshot::channel();
+    (ReplyTo { tx }, rx)
+}
+
+impl<T> ActorRef<T> {
+    /// Send a message that contains a [`ReplyTo`] and wait for the reply.
+    pub async fn ask<R, F>(&self, msg: F) -> Result<R, AskError>
+    where
+        F: FnOnce(ReplyTo<R>) -> T,
+    {
+        let (reply_to, rx) = reply_channel();
+        self.tell(msg(reply_to))
+            .map_err(|_| AskError::ActorRefGone)?;
+        rx.await.map_err(|_| AskError::NoReply)
+    }
+
+    /// Like [`ActorRef::ask`], but fails if there is no reply within the timeout.
+    pub async fn ask_timeout<R, F>(&self, msg: F, timeout: Duration) -> Result<R, AskError>
+    where
+        F: FnOnce(ReplyTo<R>) -> T,
+    {
+        within(&*self.cell.runtime(), timeout, self.ask(msg)).await
+    }
+
+    /// Like [`ActorRef::ask`], but fails once `token` is cancelled, so that many asks can be
+    /// cancelled at once, e.g. when the request that they serve is aborted.
+    ///
+    /// The actor sees the cancellation through [`ReplyTo::is_closed`] and [`ReplyTo::closed`].
+    #[cfg(feature = "cancellation")]
+    pub async fn ask_cancellable<R, F>(
+        &self,
+        msg: F,
+        token: &tokio_util::sync::CancellationToken,
+    ) -> Result<R, AskError>
+    where
+        F: FnOnce(ReplyTo<R>) -> T,
+    {
+        tokio::select! {
+            reply = self.ask(msg) => reply,
+            () = token.cancelled() => Err(AskError::Cancelled),
+        }
+    }
+
+    /// Like [`ActorRef::ask_timeout`], but blocks the current thread while waiting for the reply.
+    ///
+    /// This is for threads that are not driven by an async runtime, e.g. synchronous code
+    /// that calls into an actor system. It does not need a runtime, but it must not be
+    /// called from async code, as it would block the executor.
+    pub fn ask_blocking<R, F>(&self, msg: F, timeout: Duration) -> Result<R, AskError>
+    where
+        F: FnOnce(ReplyTo<R>) -> T,
+    {
+        let (reply_to, rx) = reply_channel();
+        self.tell(msg(reply_to))
+            .map_err(|_| AskError::ActorRefGone)?;
+        let reply = block_until(rx, std::time::Instant::now() + timeout);
+        reply
+            .ok_or(AskError::Timeout)?
+            .map_err(|_| AskError::NoReply)
+    }
+}
+
+/// Caps the asks to an actor that wait for their reply at the same time,
+/// so that a slow actor does not pile up an unbounded number of waiting askers under load.
+///
+/// Asks beyond `max_in_flight` wait for an earlier ask to finish, and once `max_queued` asks
+/// are waiting, further asks fail with [`AskError::Overloaded`] right away.
+/// An ask is in flight until its reply arrives or the asker gives up on it.
+///
+/// All clones share the same limits.
+pub struct AskPool<T> {
+    actor: ActorRef<T>,
+    permits: Arc<Semaphore>,
+    queued: Arc<AtomicUsize>,
+    max_in_flight: usize,
+    max_queued: usize,
+}
+
+impl<T> AskPool<T> {
+    pub fn new(actor: ActorRef<T>, max_in_flight: usize) -> Self {
+        let max_in_flight = max_in_flight.clamp(1, Semaphore::MAX_PERMITS);
+        Self {
+            actor,
+            permits: Arc::new(Semaphore::new(max_in_flight)),
+            queued: Arc::new(AtomicUsize::new(0)),
+            max_in_flight,
+            max_queued: usize::MAX,
+        }
+    }
+
+    /// Let at most `max_queued` asks wait for an ask in flight to finish, `0` rejects every ask
+    /// beyond `max_in_flight`. By default, all asks wait.
+    pub fn with_max_queued(mut self, max_queued: usize) -> Self {
+        self.max_queued = max_queued;
+        self
+    }
+
+    /// The number of asks that wait for their reply.
+    pub fn in_flight(&self) -> usize {
+        self.max_in_flight - self.permits.available_permits()
+    }
+
+    /// Like [`ActorRef::ask`], but waits for an ask in flight to finish if there are
+    /// `max_in_flight` of them, or fails with [`AskError::Overloaded`] if too many asks wait already.
+    pub async fn ask<R, F>(&self, msg: F) -> Result<R, AskError>
+    where
+        F: FnOnce(ReplyTo<R>) -> T,
+    {
+        let _permit = match self.permits.try_acquire() {
+            Ok(permit) => permit,
+            Err(_) => {
+                let queued = Queued::new(&self.queued);
+                if queued.position >= self.max_queued {
+                    return Err(AskError::Overloaded);
+                }
+                let permit = self.permits.acquire().await;
+                drop(queued);
+                permit.map_err(|_| AskError::Overloaded)?
+            }
+        };
+        self.actor.ask(msg).await
+    }
+
+    /// Like [`AskPool::ask`], but fails if there is no reply within the timeout,
+    /// which includes the time that the ask waits for its turn.
+    pub async fn ask_timeout<R, F>(&self, msg: F, timeout: Duration) -> Result<R, AskError>
+    where
+        F: FnOnce(ReplyTo<R>) -> T,
+    {
+        within(&*self.actor.cell.runtime(), timeout, self.ask(msg)).await
+    }
+
+    pub fn actor(&self) -> &ActorRef<T> {
+        &self.actor
+    }
+}
+
+impl<T> Clone for AskPool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: self.actor.clone(),
+            permits: Arc::clone(&self.permits),
+            queued: Arc::clone(&self.queued),
+            max_in_flight: self.max_in_flight,
+            max_queued: self.max_queued,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for AskPool<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("AskPool")
+            .field("actor", &self.actor)
+            .field("in_flight", &self.in_flight())
+            .field("max_in_flight", &self.max_in_flight)
+            .field("max_queued", &self.max_queued)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Counts an ask of an [`AskPool`] as queued until it is dropped,
+/// also when the asker gives up while the ask waits for its turn.
+struct Queued<'a> {
+    queued: &'a AtomicUsize,
+    /// The number of asks that were queued before this one.
+    position: usize,
+}
+
+impl<'a> Queued<'a> {
+    fn new(queued: &'a AtomicUsize) -> Self {
+        let position = queued.fetch_add(1, Ordering::SeqCst);
+        Self { queued, position }
+    }
+}
+
+impl Drop for Queued<'_> {
+    fn drop(&mut self) {
+        let _ = self.queued.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// Wait for `reply` for at most `timeout`, sleeping on `runtime`,
+/// so that asks time out on any executor.
+async fn within<R>(
+    runtime: &dyn Runtime,
+    timeout: Duration,
+    reply: impl Future<Output = Result<R, AskError>>,
+) -> Result<R, AskError> {
+    let sleep = runtime.sleep(timeout);
+    tokio::select! {
+        biased;
+        reply = reply => reply,
+        () = sleep => Err(AskError::Timeout),
+    }
+}
+
+/// Unparks the blocked thread when the reply arrives.
+struct ThreadWaker(Thread);
+
+impl Wake for ThreadWaker {
+    fn wake(self: Arc<Self>) {
+        self.0.unpark();
+    }
+}
+
+/// Poll `fut` on the current thread, returns `None` if it is not ready before the deadline.
+pub(crate) fn block_until<F: Future + Unpin>(
+    mut fut: F,
+    deadline: std::time::Instant,
+) -> Option<F::Output> {
+    let waker = Waker::from(Arc::new(ThreadWaker(thread::current())));
+    let mut cx = Context::from_waker(&waker);
+    loop {
+        if let Poll::Ready(output) = Pin::new(&mut fut).poll(&mut cx) {
+            return Some(output);
+        }
+        let now = std::time::Instant::now();
+        if now >= deadline {
+            return None;
+        }
+        thread::park_timeout(deadline - now);
+    }
+}
+
+/// Ask all actors and wait for their replies, for at most `timeout`.
+///
+/// The results are in the same order as the refs.
+pub async fn ask_all<'a, T, R, I, F>(
+    refs: I,
+    mut msg: F,
+    timeout: Duration,
+) -> Vec<Result<R, AskError>>
+where
+    T: 'a,
+    I: IntoIterator<Item = &'a ActorRef<T>>,
+    F: FnMut(ReplyTo<R>) -> T,
+{
+    let mut runtime = None;
+    let (mut replies, mut pending): (Vec<_>, Vec<_>) = refs
+        .into_iter()
+        .map(|actor| {
+            let _ = runtime.get_or_insert_with(|| actor.cell.runtime());
+            let (reply_to, rx) = reply_channel();
+            match actor.tell(msg(reply_to)) {
+                Ok(()) => (None, Some(rx)),
+                Err(_) => (Some(Err(AskError::ActorRefGone)), None),
+            }
+        })
+        .unzip();
+    let Some(runtime) = runtime else {
+        return Vec::new();
+    };
+
+    let all_replies = poll_fn(|cx| {
+        let mut done = true;
+        for (reply, slot) in replies.iter_mut().zip(&mut pending) {
+            let Some(rx) = slot else {
+                continue;
+            };
+            match Pin::new(rx).poll(cx) {
+                Poll::Ready(result) => {
+                    *reply = Some(result.map_err(|_| AskError::NoReply));
+                    *slot = None;
+                }
+                Poll::Pending => done = false,
+            }
+        }
+        if done {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    });
+    let sleep = runtime.sleep(timeout);
+    tokio::select! {
+        biased;
+        () = all_replies => {}
+        () = sleep => {}
+    }
+
+    replies
+        .into_iter()
+        .map(|reply| reply.unwrap_or(Err(AskError::Timeout)))
+        .collect()
+}
+
+/// Ask all actors and return the first reply, waiting for at most `timeout`.
+///
+/// Fails only if none of the actors replies in time.
+pub async fn ask_any<'a, T, R, I, F>(refs: I, mut msg: F, timeout: Duration) -> Result<R, AskError>
+where
+    T: 'a,
+    I: IntoIterator<Item = &'a ActorRef<T>>,
+    F: FnMut(ReplyTo<R>) -> T,
+{
+    let mut runtime = None;
+    let mut pending = refs
+        .into_iter()
+        .filter_map(|actor| {
+            let _ = runtime.get_or_insert_with(|| actor.cell.runtime());
+            let (reply_to, rx) = reply_channel();
+            actor.tell(msg(reply_to)).ok().map(|()| Some(rx))
+        })
+        .collect::<Vec<_>>();
+    let Some(runtime) = runtime.filter(|_| pending.is_empty() == false) else {
+        return Err(AskError::ActorRefGone);
+    };
+
+    let first_reply = poll_fn(|cx| {
+        for slot in &mut pending {
+            let Some(rx) = slot else {
+                continue;
+            };
+            match Pin::new(rx).poll(cx) {
+                Poll::Ready(Ok(reply)) => return Poll::Ready(Ok(reply)),
+                Poll::Ready(Err(_)) => *slot = None,
+                Poll::Pending => {}
+            }
+        }
+        if pending.iter().all(Option::is_none) {
+            Poll::Ready(Err(AskError::NoReply))
+        } else {
+            Poll::Pending
+        }
+    });
+
+    within(&*runtime, timeout, first_reply).await
+}
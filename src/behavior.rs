@@ -1,16 +1,28 @@
 use crate::{
-    ActorContext, ActorRef, ActorRefGone, BoxErr, Error, NoActorRef, State, Stopped, SystemBus,
+    child::{Failure, Supervisor},
+    compose::{OrElse, Redeliver},
+    crash_dump::DebugMessages,
+    envelope::{self, Envelope},
+    intercept::Handled,
+    mailbox::{self, Mailbox, Next},
+    replace::Replacement,
+    runtime,
+    signal::{OnSignal, Signal},
+    ActorContext, ActorCrashed, ActorPath, ActorRef, ActorRefGone, ActorStarted, ActorSystem,
+    BoxErr, ChildEscalated, Error, LifecycleEvent, Lifetime, NoActorRef, SpawnOptions, State,
+    Stopped, Supervision, SystemBus,
 };
 use pin_project_lite::pin_project;
 use std::{
     error::Error as StdError,
+    fmt::Debug,
     future::Future,
     marker::PhantomData,
     pin::Pin,
     sync::Arc,
     task::{Context, Poll},
 };
-use tokio::sync::mpsc;
+use tokio::runtime::Handle;
 
 #[derive(Debug, Copy, Clone)]
 pub enum Behaviors {
@@ -26,15 +38,104 @@ pub enum Behaviors {
     Stopped,
 }
 
-pub(crate) fn actor_of<T: Send + 'static, N, A, Args>(name: N, behavior: A) -> ActorRef<T>
+pub(crate) fn actor_of<T: Send + 'static, A, Args>(
+    system: &ActorSystem,
+    path: ActorPath,
+    behavior: A,
+) -> ActorRef<T>
 where
-    N: Into<Arc<str>>,
     A: Behavior<T, Args>,
 {
-    let (tx, rx) = mpsc::unbounded_channel();
-    let this = ActorRef { tx };
-    let context = ActorContext::new(this.clone(), name.into());
-    let _handle = tokio::spawn(async move { receive(context, rx, behavior).await });
+    actor_on(
+        system,
+        None,
+        SpawnOptions::default(),
+        Supervisor::system(),
+        path,
+        behavior,
+    )
+}
+
+/// Like [`actor_of`], but on `runtime` instead of the runtime of the system.
+pub(crate) fn actor_on<T: Send + 'static, A, Args>(
+    system: &ActorSystem,
+    runtime: Option<Handle>,
+    options: SpawnOptions,
+    mut supervisor: Supervisor<T>,
+    path: ActorPath,
+    behavior: A,
+) -> ActorRef<T>
+where
+    A: Behavior<T, Args>,
+{
+    let context_system = system.clone();
+    let system = Arc::clone(&system.inner);
+    let cell = system.register(path.clone());
+    if let Some(capacity) = system.mailbox_capacity_for(&path) {
+        cell.set_capacity(capacity);
+    }
+    let (this, mailbox) = mailbox::sharded(Arc::clone(&cell), options.lanes);
+    system.selectable(&this);
+    let context = ActorContext::new(this.downgrade(), context_system);
+    system.publish_lifecycle(LifecycleEvent::Started(ActorStarted { path: path.clone() }));
+    let keep_alive = (options.lifetime == Lifetime::Detached).then(|| this.clone());
+    let task = {
+        let system = Arc::clone(&system);
+        async move {
+            let res = receive(context, mailbox, behavior, &mut supervisor).await;
+            drop(keep_alive);
+            system.deregister(&cell);
+            if let Err(e) = &res {
+                let event = LifecycleEvent::terminated(path, e, &cell.crash_dump);
+                if let LifecycleEvent::Crashed(crashed) = &event {
+                    if supervisor.escalated {
+                        system.escalate(crashed);
+                    } else {
+                        system.signal_parent(crashed);
+                    }
+                }
+                system.publish_lifecycle(event);
+            }
+        }
+    };
+    system.spawn_actor(runtime, &this.cell, task);
+    this
+}
+
+/// Like [`actor_of`], but the actor is driven on the current thread and `handler` need not be `Send`.
+pub(crate) fn local_actor_of<T, F, Fut>(
+    system: &ActorSystem,
+    path: ActorPath,
+    handler: F,
+) -> ActorRef<T>
+where
+    T: Send + 'static,
+    F: FnMut(T) -> Fut + 'static,
+    Fut: Future + 'static,
+    Fut::Output: IntoResult<T>,
+{
+    let context_system = system.clone();
+    let system = Arc::clone(&system.inner);
+    let cell = system.register(path.clone());
+    if let Some(capacity) = system.mailbox_capacity_for(&path) {
+        cell.set_capacity(capacity);
+    }
+    let (this, mailbox) = mailbox::channel(Arc::clone(&cell));
+    system.selectable(&this);
+    let context = ActorContext::new(this.downgrade(), context_system);
+    system.publish_lifecycle(LifecycleEvent::Started(ActorStarted { path: path.clone() }));
+    let task = {
+        let system = Arc::clone(&system);
+        async move {
+            let res = receive_local(context, mailbox, handler).await;
+            system.deregister(&cell);
+            if let Err(e) = &res {
+                let event = LifecycleEvent::terminated(path, e, &cell.crash_dump);
+                system.publish_lifecycle(event);
+            }
+        }
+    };
+    runtime::spawn_local(this.path().as_str(), Box::pin(task));
     this
 }
 
@@ -42,6 +143,67 @@ pub trait Behavior<T, Args = ()>: Send + Sync + Sized + 'static {
     type F: Future<Output = Result<Behaviors, Error<T>>> + Send;
 
     fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F;
+
+    /// Retry the messages that this behavior leaves unhandled against `other`.
+    ///
+    /// A message is unhandled if the behavior returns [`Behaviors::Unhandled`] or
+    /// fails with [`ActorRefGone`]. Messages are cloned, so that they can be retried.
+    fn or_else<B, ArgsB>(self, other: B) -> OrElse<Self, B, Args, ArgsB>
+    where
+        B: Behavior<T, ArgsB>,
+    {
+        OrElse::new(self, other)
+    }
+
+    /// Put the messages that crash the behavior back into the mailbox, at most `max_redeliveries` times,
+    /// for handlers with transient failures.
+    ///
+    /// Redelivered messages queue up behind the messages that are already waiting.
+    /// Once a message has been redelivered `max_redeliveries` times, the next crash goes to the
+    /// supervision of the system. Messages are cloned, so that they can be redelivered.
+    fn redeliver(self, max_redeliveries: u32) -> Redeliver<Self, Args>
+    where
+        T: Clone,
+    {
+        Redeliver::new(self, max_redeliveries)
+    }
+
+    /// Handle a [`Signal`], `None` if the behavior ignores signals, which is the default.
+    fn receive_signal(&self, context: &ActorContext<T>, signal: Signal) -> Option<Handled<T>> {
+        let _ = (context, signal);
+        None
+    }
+
+    /// Handle the [`Signal`]s of the actor with `handler`.
+    ///
+    /// Signals reach only the outermost behavior, so attach the handler last,
+    /// e.g. after [`Behavior::or_else`].
+    fn on_signal<H, Fut>(self, handler: H) -> OnSignal<Self, H, Args>
+    where
+        H: Fn(&ActorContext<T>, Signal) -> Fut + Send + Sync + 'static,
+        Fut: Future + Send + 'static,
+        Fut::Output: IntoResult<T>,
+    {
+        OnSignal::new(self, handler)
+    }
+
+    /// Render `msg` for the crash dump, `None` names it by its type, which is the default.
+    fn describe(&self, msg: &T) -> Option<Arc<str>> {
+        let _ = msg;
+        None
+    }
+
+    /// Render the messages with [`Debug`] for the crash dump, see
+    /// [`ActorSystemBuilder::crash_dump`](crate::ActorSystemBuilder::crash_dump).
+    ///
+    /// Like signals, this reaches only the outermost behavior, or the behavior inside of
+    /// [`Behavior::on_signal`].
+    fn debug_messages(self) -> DebugMessages<Self, Args>
+    where
+        T: Debug,
+    {
+        DebugMessages::new(self)
+    }
 }
 
 pin_project! {
@@ -70,63 +232,216 @@ where
 
 async fn receive<B, T, Args>(
     context: ActorContext<T>,
-    mut rx: mpsc::UnboundedReceiver<T>,
+    rx: Mailbox<T>,
     behavior: B,
+    supervisor: &mut Supervisor<T>,
 ) -> Result<(), Error<T>>
 where
+    T: 'static,
     B: Behavior<T, Args>,
 {
+    let (mut rx, mut behavior) =
+        match receive_until_stopped(&context, rx, &behavior, supervisor).await {
+            Until::Stopped(res) => return post_stop(&context, &behavior, res).await,
+            Until::Replaced(rx, replacement) => (rx, replacement),
+        };
     loop {
-        let msg = match receive_next(&mut rx).await {
-            Some(msg) => msg,
-            None => return Err(Error::NoActorRef(NoActorRef)),
+        match receive_until_stopped(&context, rx, &behavior, supervisor).await {
+            Until::Stopped(res) => return post_stop(&context, &behavior, res).await,
+            Until::Replaced(next, replacement) => (rx, behavior) = (next, replacement),
+        }
+    }
+}
+
+async fn post_stop<B, T, Args>(
+    context: &ActorContext<T>,
+    behavior: &B,
+    res: Result<(), Error<T>>,
+) -> Result<(), Error<T>>
+where
+    B: Behavior<T, Args>,
+{
+    if let Some(post_stop) = behavior.receive_signal(context, Signal::PostStop) {
+        let _ = post_stop.await;
+    }
+    res
+}
+
+/// How [`receive_until_stopped`] ends.
+enum Until<T> {
+    Stopped(Result<(), Error<T>>),
+    /// The behavior is replaced, the mailbox continues with the replacement.
+    Replaced(Mailbox<T>, Replacement<T>),
+}
+
+async fn receive_until_stopped<B, T, Args>(
+    context: &ActorContext<T>,
+    mut rx: Mailbox<T>,
+    behavior: &B,
+    supervisor: &mut Supervisor<T>,
+) -> Until<T>
+where
+    B: Behavior<T, Args>,
+{
+    let system = &context.system().inner;
+    loop {
+        let (handled, is_signal) = match rx.recv_next(|timeout| system.sleep(timeout)).await {
+            Next::Message(Envelope { msg, meta, .. }) => {
+                rx.cell().crash_dump.record(|| {
+                    behavior
+                        .describe(&msg)
+                        .unwrap_or_else(|| std::any::type_name::<T>().into())
+                });
+                #[cfg(feature = "prometheus")]
+                let started = std::time::Instant::now();
+                let handled =
+                    envelope::scope(context.path(), meta, || behavior.receive(context, msg)).await;
+                #[cfg(feature = "prometheus")]
+                rx.cell().metrics.handled(started.elapsed());
+                (handled, false)
+            }
+            Next::Signal(signal) => match behavior.receive_signal(context, signal) {
+                Some(handled) => (handled.await, true),
+                None => continue,
+            },
+            Next::Stop => return Until::Stopped(stop(rx)),
+            Next::Replace(replacement) => return Until::Replaced(rx, replacement),
+            Next::Escalate(crashed) => (Err(escalated(crashed)), true),
+            Next::Closed => return Until::Stopped(Err(Error::NoActorRef(NoActorRef))),
         };
-        let handled = behavior.receive(&context, msg).await;
+        if handled.is_ok() && is_signal == false {
+            supervisor.on_success();
+        }
         match handled {
             Ok(behavior) => match behavior {
-                Behaviors::Empty => return empty_behavor(rx).await.map_err(Error::NoActorRef),
-                Behaviors::Ignore => return ignore_behavor(rx).await.map_err(Error::NoActorRef),
+                Behaviors::Empty => return Until::Stopped(empty_behavor(rx).await),
+                Behaviors::Ignore => return Until::Stopped(ignore_behavor(rx).await),
                 Behaviors::Same => {}
+                // signals are not letters
+                Behaviors::Unhandled if is_signal => {}
                 Behaviors::Unhandled => {
-                    // TODO: dead letters / unhandled bus
-                }
-                Behaviors::Stopped => {
-                    drop(rx);
-                    return Err(Error::Stopped(Stopped));
+                    rx.cell().dead_letter();
                 }
+                Behaviors::Stopped => return Until::Stopped(stop(rx)),
             },
-            Err(err) => return Err(err),
+            Err(err @ (Error::Unhandled(_) | Error::Crashed(_))) => {
+                let failure = supervisor.on_failure(system.supervision);
+                if let Failure::Stop = failure {
+                    rx.cell().stopping();
+                    return Until::Stopped(Err(err));
+                }
+                match err {
+                    Error::Unhandled(ActorRefGone(msg)) => rx.dead_letter(msg),
+                    _ => rx.cell().dead_letter(),
+                }
+                if let Failure::Restart(replacement) = failure {
+                    return Until::Replaced(rx, replacement);
+                }
+            }
+            Err(err) => {
+                rx.cell().stopping();
+                return Until::Stopped(Err(err));
+            }
         }
     }
 }
 
-async fn receive_next<T>(rx: &mut mpsc::UnboundedReceiver<T>) -> Option<T> {
-    let msg = rx.try_recv();
-    match msg {
-        Ok(msg) => Some(msg),
-        Err(mpsc::error::TryRecvError::Disconnected) => None,
-        Err(_) => rx.recv().await,
-    }
+/// The failure of a parent whose child has escalated its failure.
+fn escalated<T>(crashed: ActorCrashed) -> Error<T> {
+    Error::Crashed(Box::new(ChildEscalated(crashed)))
 }
 
-async fn empty_behavor<T>(mut rx: mpsc::UnboundedReceiver<T>) -> Result<(), NoActorRef> {
+async fn receive_local<T, F, Fut>(
+    context: ActorContext<T>,
+    mut rx: Mailbox<T>,
+    mut handler: F,
+) -> Result<(), Error<T>>
+where
+    F: FnMut(T) -> Fut,
+    Fut: Future,
+    Fut::Output: IntoResult<T>,
+{
+    let system = &context.system().inner;
     loop {
-        let msg = match receive_next(&mut rx).await {
-            Some(msg) => msg,
-            None => return Err(NoActorRef),
+        let Envelope { msg, meta, .. } = match rx.recv_next(|timeout| system.sleep(timeout)).await {
+            Next::Message(envelope) => envelope,
+            // there is no handler for signals
+            Next::Signal(_) => continue,
+            Next::Stop => return stop(rx),
+            // local handlers are not replaced
+            Next::Replace(_) => continue,
+            Next::Escalate(crashed) => {
+                rx.cell().stopping();
+                return Err(escalated(crashed));
+            }
+            Next::Closed => return Err(Error::NoActorRef(NoActorRef)),
         };
-        // TODO: dead letters / unhandled bus
-        drop(msg);
+        rx.cell()
+            .crash_dump
+            .record(|| std::any::type_name::<T>().into());
+        let handled = envelope::scope(context.path(), meta, || handler(msg))
+            .await
+            .into_result();
+        match handled {
+            Ok(Behaviors::Empty) => return empty_behavor(rx).await,
+            Ok(Behaviors::Ignore) => return ignore_behavor(rx).await,
+            Ok(Behaviors::Same) => {}
+            Ok(Behaviors::Unhandled) => rx.cell().dead_letter(),
+            Ok(Behaviors::Stopped) => return stop(rx),
+            Err(Error::Unhandled(ActorRefGone(msg)))
+                if context.system().inner.supervision == Supervision::Resume =>
+            {
+                rx.dead_letter(msg);
+            }
+            Err(Error::Crashed(_)) if context.system().inner.supervision == Supervision::Resume => {
+                rx.cell().dead_letter();
+            }
+            Err(err) => {
+                rx.cell().stopping();
+                return Err(err);
+            }
+        }
     }
 }
 
-async fn ignore_behavor<T>(mut rx: mpsc::UnboundedReceiver<T>) -> Result<(), NoActorRef> {
+fn stop<T>(rx: Mailbox<T>) -> Result<(), Error<T>> {
+    rx.cell().stopping();
+    drop(rx);
+    Err(Error::Stopped(Stopped))
+}
+
+async fn empty_behavor<T>(mut rx: Mailbox<T>) -> Result<(), Error<T>> {
     loop {
-        let msg = match receive_next(&mut rx).await {
+        let msg = match recv_unhandled(&mut rx).await? {
             Some(msg) => msg,
-            None => return Err(NoActorRef),
+            None => continue,
         };
-        drop(msg);
+        rx.dead_letter(msg);
+    }
+}
+
+async fn ignore_behavor<T>(mut rx: Mailbox<T>) -> Result<(), Error<T>> {
+    loop {
+        drop(recv_unhandled(&mut rx).await?);
+    }
+}
+
+/// The next message for a behavior that handles neither messages nor signals,
+/// `None` for ignored signals.
+async fn recv_unhandled<T>(rx: &mut Mailbox<T>) -> Result<Option<T>, Error<T>> {
+    rx.cell().set_receive_timeout(None);
+    match rx.recv_next(|_| Box::pin(std::future::pending())).await {
+        Next::Message(envelope) => Ok(Some(envelope.msg)),
+        Next::Signal(_) | Next::Replace(_) => Ok(None),
+        Next::Escalate(crashed) => {
+            rx.cell().stopping();
+            Err(escalated(crashed))
+        }
+        Next::Stop => {
+            rx.cell().stopping();
+            Err(Error::Stopped(Stopped))
+        }
+        Next::Closed => Err(Error::NoActorRef(NoActorRef)),
     }
 }
 
@@ -153,8 +468,11 @@ impl<T, S: Default> FromContext<T> for State<S> {
 }
 
 impl<T, E> FromContext<T> for SystemBus<E> {
-    fn from_context(_context: &ActorContext<T>) -> Self {
-        Self(None)
+    fn from_context(context: &ActorContext<T>) -> Self {
+        Self {
+            system: context.system().clone(),
+            _event: PhantomData,
+        }
     }
 }
 
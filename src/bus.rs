@@ -0,0 +1,82 @@
+use crate::ActorRef;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::Mutex,
+};
+
+/// Subscribers of the system bus, keyed by the type of the events.
+#[derive(Debug, Default)]
+pub(crate) struct Bus {
+    subscribers: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+}
+
+impl Bus {
+    pub(crate) fn subscribe<E: Send + 'static>(&self, subscriber: ActorRef<E>) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers
+            .entry(TypeId::of::<E>())
+            .or_insert_with(|| Box::new(Vec::<ActorRef<E>>::new()))
+            .downcast_mut::<Vec<ActorRef<E>>>()
+            .expect("bus subscribers are keyed by their type")
+            .push(subscriber);
+    }
+
+    pub(crate) fn unsubscribe<E: Send + 'static>(&self, subscriber: &ActorRef<E>) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        if let Some(subscribers) = subscribers
+            .get_mut(&TypeId::of::<E>())
+            .and_then(|s| s.downcast_mut::<Vec<ActorRef<E>>>())
+        {
+            subscribers.retain(|s| s.same_actor(subscriber) == false);
+        }
+    }
+
+    /// Drop all subscribers.
+    pub(crate) fn clear(&self) {
+        let subscribers = std::mem::take(&mut *self.subscribers.lock().unwrap());
+        drop(subscribers);
+    }
+
+    /// Send the event to all subscribers, returns the number of subscribers that received it.
+    pub(crate) fn publish<E: Clone + Send + 'static>(&self, event: E) -> usize {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let Some(subscribers) = subscribers
+            .get_mut(&TypeId::of::<E>())
+            .and_then(|s| s.downcast_mut::<Vec<ActorRef<E>>>())
+        else {
+            return 0;
+        };
+        subscribers.retain(|s| s.tell(event.clone()).is_ok());
+        subscribers.len()
+    }
+}
+
+/// Encoded events that a bridge has published on the bus and that are still on their way
+/// through it, so that the bridge does not send them back to where they came from.
+#[cfg(any(feature = "nats", feature = "redis"))]
+#[derive(Debug, Default)]
+pub(crate) struct Echoes {
+    pending: Mutex<HashMap<Vec<u8>, usize>>,
+}
+
+#[cfg(any(feature = "nats", feature = "redis"))]
+impl Echoes {
+    /// The event is about to be published on the bus.
+    pub(crate) fn expect(&self, payload: Vec<u8>) {
+        *self.pending.lock().unwrap().entry(payload).or_default() += 1;
+    }
+
+    /// Returns `true` if the event came from the bridge, and forgets about it.
+    pub(crate) fn is_echo(&self, payload: &[u8]) -> bool {
+        let mut pending = self.pending.lock().unwrap();
+        let Some(count) = pending.get_mut(payload) else {
+            return false;
+        };
+        *count -= 1;
+        if *count == 0 {
+            let _ = pending.remove(payload);
+        }
+        true
+    }
+}
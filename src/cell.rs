@@ -0,0 +1,475 @@
+use crate::{
+    crash_dump::CrashDump,
+    lifecycle::DeadLetterValues,
+    mailbox::{Drained, SystemMessage},
+    replace::Replacement,
+    runtime::{self, Instant, Runtime},
+    testkit::Effects,
+    ActorCrashed, ActorPath, DeadLetter, Signal,
+};
+use std::{
+    future::Future,
+    sync::{
+        atomic::{AtomicU64, AtomicU8, AtomicUsize, Ordering},
+        Arc, Mutex, OnceLock, Weak,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use tokio::sync::{broadcast, mpsc, watch, Notify};
+
+/// Bookkeeping that is shared between all refs of an actor and the system.
+#[derive(Debug)]
+pub(crate) struct ActorCell {
+    pub(crate) id: u64,
+    pub(crate) path: ActorPath,
+    started: Instant,
+    state: AtomicU8,
+    mailbox_len: AtomicUsize,
+    /// The number of [`ActorRef`](crate::ActorRef)s, the actor stops once they are gone.
+    refs: AtomicUsize,
+    dead_letters: AtomicUsize,
+    /// The messages that are being handled plus the delayed messages that are pending.
+    busy: AtomicUsize,
+    /// Counts whenever the actor is done with something that kept it busy.
+    activity: AtomicU64,
+    /// Wakes the callers of `run_until_idle`, `None` for cells that are not tracked by the system.
+    activity_events: Option<Arc<Activity>>,
+    /// Where the dead letters are published, `None` for cells that are not tracked by the system.
+    dead_letter_events: Option<broadcast::Sender<DeadLetter>>,
+    /// Where the messages of dead letters are offered, `None` like `dead_letter_events`.
+    dead_letter_values: Option<Arc<DeadLetterValues>>,
+    stopped: watch::Sender<bool>,
+    /// The bound of a bounded mailbox, `usize::MAX` for unbounded mailboxes.
+    capacity: AtomicUsize,
+    /// The bound of the approximate bytes in the mailbox, `usize::MAX` for mailboxes without one.
+    byte_budget: AtomicUsize,
+    /// The approximate bytes of the messages in the mailbox, as measured when they are told.
+    mailbox_bytes: AtomicUsize,
+    /// Senders that wait for the mailbox to drain, see [`ActorCell::poll_enqueue`].
+    waiting: AtomicUsize,
+    wakers: Mutex<Vec<Waker>>,
+    /// Where the system messages of the actor are sent, set once the mailbox is created.
+    signals: OnceLock<mpsc::UnboundedSender<SystemMessage>>,
+    /// The receive timeout in nanoseconds, `u64::MAX` for none.
+    /// Read for every message, so it is not behind a lock.
+    receive_timeout: AtomicU64,
+    /// Records what the actor does instead of doing it, set for the inboxes of a
+    /// [`BehaviorTestKit`](crate::BehaviorTestKit).
+    effects: OnceLock<Weak<Effects>>,
+    /// The runtime that drives the actor, set once it is spawned.
+    runtime: OnceLock<Arc<dyn Runtime>>,
+    pub(crate) crash_dump: CrashDump,
+    #[cfg(feature = "stream")]
+    pub(crate) outputs: crate::stream::Outputs,
+    #[cfg(feature = "prometheus")]
+    pub(crate) metrics: crate::prometheus::ActorMetrics,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ActorState {
+    /// The actor is processing messages.
+    Running,
+    /// The actor has decided to stop and will not process any more messages.
+    Stopping,
+}
+
+/// Wakes the callers of [`ActorSystem::run_until_idle`](crate::ActorSystem::run_until_idle)
+/// whenever an actor is done with something or stops, instead of letting them poll.
+#[derive(Debug, Default)]
+pub(crate) struct Activity {
+    waiting: AtomicUsize,
+    notify: Notify,
+}
+
+impl Activity {
+    #[inline]
+    pub(crate) fn changed(&self) {
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            self.notify.notify_waiters();
+        }
+    }
+
+    /// Wait until `done` returns `true`, checking it again after every change.
+    pub(crate) async fn wait_until(&self, mut done: impl FnMut() -> bool) {
+        let _ = self.waiting.fetch_add(1, Ordering::SeqCst);
+        loop {
+            let changed = self.notify.notified();
+            tokio::pin!(changed);
+            // registered before the check, so that a change in between is not missed
+            let _ = changed.as_mut().enable();
+            if done() {
+                break;
+            }
+            changed.await;
+        }
+        let _ = self.waiting.fetch_sub(1, Ordering::SeqCst);
+    }
+}
+
+/// A snapshot of a live actor, as returned by [`ActorSystem::actors`](crate::ActorSystem::actors).
+#[derive(Clone, Debug)]
+pub struct ActorInfo {
+    pub path: ActorPath,
+    /// Number of messages waiting in the actors mailbox.
+    pub mailbox_len: usize,
+    /// Approximate bytes of the waiting messages, only measured for actors that are spawned with
+    /// [`ActorSystem::spawn_bounded_by_size`](crate::ActorSystem::spawn_bounded_by_size).
+    pub mailbox_bytes: usize,
+    pub uptime: Duration,
+    pub state: ActorState,
+    /// Number of messages that this actor did not handle.
+    pub dead_letters: usize,
+}
+
+impl ActorCell {
+    pub(crate) fn new(
+        id: u64,
+        path: ActorPath,
+        dead_letter_events: Option<broadcast::Sender<DeadLetter>>,
+        dead_letter_values: Option<Arc<DeadLetterValues>>,
+        activity_events: Option<Arc<Activity>>,
+        crash_dump: usize,
+    ) -> Self {
+        Self {
+            id,
+            path,
+            started: Instant::now(),
+            state: AtomicU8::new(ActorState::Running as u8),
+            mailbox_len: AtomicUsize::new(0),
+            refs: AtomicUsize::new(0),
+            dead_letters: AtomicUsize::new(0),
+            busy: AtomicUsize::new(0),
+            activity: AtomicU64::new(0),
+            activity_events,
+            dead_letter_events,
+            dead_letter_values,
+            stopped: watch::Sender::new(false),
+            capacity: AtomicUsize::new(usize::MAX),
+            byte_budget: AtomicUsize::new(usize::MAX),
+            mailbox_bytes: AtomicUsize::new(0),
+            waiting: AtomicUsize::new(0),
+            wakers: Mutex::new(Vec::new()),
+            signals: OnceLock::new(),
+            receive_timeout: AtomicU64::new(u64::MAX),
+            effects: OnceLock::new(),
+            runtime: OnceLock::new(),
+            crash_dump: CrashDump::new(crash_dump),
+            #[cfg(feature = "stream")]
+            outputs: crate::stream::Outputs::default(),
+            #[cfg(feature = "prometheus")]
+            metrics: crate::prometheus::ActorMetrics::default(),
+        }
+    }
+
+    /// A cell for helpers like relays, that are not tracked by the system.
+    pub(crate) fn unregistered(path: ActorPath) -> Self {
+        Self::new(u64::MAX, path, None, None, None, 0)
+    }
+
+    pub(crate) fn set_signals(&self, signals: mpsc::UnboundedSender<SystemMessage>) {
+        let _ = self.signals.set(signals);
+    }
+
+    pub(crate) fn referenced(&self) {
+        let _ = self.refs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// A ref is dropped, the mailbox is told when it was the last one.
+    pub(crate) fn unreferenced(&self) {
+        if self.refs.fetch_sub(1, Ordering::AcqRel) == 1 {
+            self.system_message(SystemMessage::Unreferenced);
+        }
+    }
+
+    pub(crate) fn is_referenced(&self) -> bool {
+        self.refs.load(Ordering::Acquire) > 0
+    }
+
+    /// Send `signal` to the actor, it is lost if the actor has stopped.
+    pub(crate) fn signal(&self, signal: Signal) {
+        self.system_message(SystemMessage::Signal(signal));
+    }
+
+    pub(crate) fn request_stop(&self) {
+        self.system_message(SystemMessage::Stop);
+    }
+
+    pub(crate) fn escalate(&self, crashed: ActorCrashed) {
+        self.system_message(SystemMessage::Escalate(crashed));
+    }
+
+    pub(crate) fn replace_behavior<T: 'static>(&self, replacement: Replacement<T>) {
+        self.system_message(SystemMessage::Replace(Box::new(replacement)));
+    }
+
+    pub(crate) fn request_drain<T: Send + 'static>(&self, drained: Drained<T>) {
+        self.system_message(SystemMessage::Drain(Box::new(drained)));
+    }
+
+    fn system_message(&self, msg: SystemMessage) {
+        if let Some(signals) = self.signals.get() {
+            let _ = signals.send(msg);
+        }
+    }
+
+    pub(crate) fn set_receive_timeout(&self, timeout: Option<Duration>) {
+        let nanos = timeout.map_or(u64::MAX, |timeout| {
+            u64::try_from(timeout.as_nanos()).map_or(u64::MAX - 1, |nanos| nanos.min(u64::MAX - 1))
+        });
+        self.receive_timeout.store(nanos, Ordering::Relaxed);
+    }
+
+    #[inline]
+    pub(crate) fn receive_timeout(&self) -> Option<Duration> {
+        match self.receive_timeout.load(Ordering::Relaxed) {
+            u64::MAX => None,
+            nanos => Some(Duration::from_nanos(nanos)),
+        }
+    }
+
+    pub(crate) fn set_effects(&self, effects: Weak<Effects>) {
+        let _ = self.effects.set(effects);
+    }
+
+    /// The effects of the test kit that created this cell, `None` for actors.
+    pub(crate) fn effects(&self) -> Option<Arc<Effects>> {
+        self.effects.get().and_then(Weak::upgrade)
+    }
+
+    pub(crate) fn set_runtime(&self, runtime: Arc<dyn Runtime>) {
+        let _ = self.runtime.set(runtime);
+    }
+
+    /// The runtime that drives the actor, for the tasks and timers that work on its behalf.
+    ///
+    /// Cells that are not spawned use the current tokio runtime.
+    pub(crate) fn runtime(&self) -> Arc<dyn Runtime> {
+        self.runtime.get().cloned().unwrap_or_else(runtime::default)
+    }
+
+    /// Spawn a task that works on behalf of the actor, named after it.
+    pub(crate) fn spawn_task(&self, task: impl Future<Output = ()> + Send + 'static) {
+        self.runtime().spawn(self.path.as_str(), Box::pin(task));
+    }
+
+    /// A message of `size` bytes has been told.
+    #[inline]
+    pub(crate) fn enqueued(&self, size: usize) {
+        let _ = self.mailbox_len.fetch_add(1, Ordering::SeqCst);
+        self.enqueued_bytes(size);
+    }
+
+    /// A message of `size` bytes has been told into a place that was counted without its bytes.
+    #[inline]
+    pub(crate) fn enqueued_bytes(&self, size: usize) {
+        if size > 0 {
+            let _ = self.mailbox_bytes.fetch_add(size, Ordering::SeqCst);
+        }
+    }
+
+    /// The actor has taken a message, or a delayed message has been scheduled.
+    #[inline]
+    pub(crate) fn busy(&self) {
+        let _ = self.busy.fetch_add(1, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub(crate) fn done(&self) {
+        // counted first, so that a check in between sees either the activity or the busy actor
+        let _ = self.activity.fetch_add(1, Ordering::SeqCst);
+        let _ = self.busy.fetch_sub(1, Ordering::SeqCst);
+        if let Some(activity) = &self.activity_events {
+            activity.changed();
+        }
+    }
+
+    pub(crate) fn activity(&self) -> u64 {
+        self.activity.load(Ordering::SeqCst)
+    }
+
+    /// No messages are waiting, being handled, or delayed.
+    pub(crate) fn is_idle(&self) -> bool {
+        self.busy.load(Ordering::SeqCst) == 0 && self.mailbox_len.load(Ordering::SeqCst) == 0
+    }
+
+    #[inline]
+    pub(crate) fn dequeued(&self, size: usize) {
+        if size > 0 {
+            let _ = self.mailbox_bytes.fetch_sub(size, Ordering::SeqCst);
+        }
+        let _ = self.mailbox_len.fetch_sub(1, Ordering::SeqCst);
+        if self.waiting.load(Ordering::SeqCst) > 0 {
+            self.wake_senders();
+        }
+        // e.g. a message that could not be delivered after all
+        if let Some(activity) = &self.activity_events {
+            activity.changed();
+        }
+    }
+
+    fn wake_senders(&self) {
+        let wakers = {
+            let mut wakers = self.wakers.lock().unwrap();
+            self.waiting.store(0, Ordering::SeqCst);
+            std::mem::take(&mut *wakers)
+        };
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    pub(crate) fn set_capacity(&self, capacity: usize) {
+        self.capacity.store(capacity, Ordering::Relaxed);
+    }
+
+    pub(crate) fn capacity(&self) -> usize {
+        self.capacity.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn set_byte_budget(&self, budget: usize) {
+        self.byte_budget.store(budget, Ordering::Relaxed);
+    }
+
+    /// Count a message of `size` bytes as enqueued if a mailbox with a bound of `capacity` messages
+    /// has room for it, the message must then be sent with [`ActorRef::deliver_enqueued`](crate::ActorRef).
+    ///
+    /// The room is reserved atomically, so that concurrent senders cannot overshoot the bounds.
+    /// An empty mailbox always has room, so that a message that is larger than the byte budget
+    /// can still be sent.
+    pub(crate) fn try_enqueue(&self, capacity: usize, size: usize) -> bool {
+        let Ok(len) = self
+            .mailbox_len
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |len| {
+                (len < capacity).then_some(len + 1)
+            })
+        else {
+            return false;
+        };
+        if size == 0 {
+            return true;
+        }
+        let budget = self.byte_budget.load(Ordering::Relaxed);
+        let fits = self
+            .mailbox_bytes
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |bytes| {
+                let bytes = bytes.saturating_add(size);
+                (budget == usize::MAX || len == 0 || bytes <= budget).then_some(bytes)
+            });
+        if fits.is_err() {
+            // give the place back, a sender might have waited for it
+            self.dequeued(0);
+            return false;
+        }
+        true
+    }
+
+    /// Ready once a message of `size` bytes has been counted as enqueued, see [`ActorCell::try_enqueue`].
+    pub(crate) fn poll_enqueue(
+        &self,
+        capacity: usize,
+        size: usize,
+        cx: &mut Context<'_>,
+    ) -> Poll<()> {
+        if self.try_enqueue(capacity, size) {
+            return Poll::Ready(());
+        }
+        {
+            let mut wakers = self.wakers.lock().unwrap();
+            if wakers.iter().any(|w| w.will_wake(cx.waker())) == false {
+                wakers.push(cx.waker().clone());
+            }
+            self.waiting.store(wakers.len(), Ordering::SeqCst);
+        }
+        // a message might have been taken before the waker was registered
+        if self.try_enqueue(capacity, size) {
+            return Poll::Ready(());
+        }
+        Poll::Pending
+    }
+
+    pub(crate) fn mailbox_len(&self) -> usize {
+        self.mailbox_len.load(Ordering::Relaxed)
+    }
+
+    pub(crate) fn dead_letter(&self) {
+        let _ = self.dead_letters.fetch_add(1, Ordering::Relaxed);
+        if let Some(events) = &self.dead_letter_events {
+            // no subscribers is not an error
+            let _ = events.send(DeadLetter {
+                recipient: self.path.clone(),
+                message: (),
+            });
+        }
+    }
+
+    /// Like [`ActorCell::dead_letter`], but the message can be recovered with
+    /// [`ActorSystem::dead_letters_of`](crate::ActorSystem::dead_letters_of).
+    pub(crate) fn dead_letter_with<T: Send + 'static>(&self, message: T) {
+        self.dead_letter();
+        if let Some(values) = &self.dead_letter_values {
+            values.offer(&self.path, message);
+        }
+    }
+
+    pub(crate) fn stopping(&self) {
+        self.state
+            .store(ActorState::Stopping as u8, Ordering::Release);
+    }
+
+    /// The mailbox has been dropped, no more messages will be processed.
+    pub(crate) fn stopped(&self) {
+        let _ = self.stopped.send_replace(true);
+        // waiting senders find out that the actor is gone
+        self.wake_senders();
+        #[cfg(feature = "stream")]
+        self.outputs.close();
+    }
+
+    pub(crate) fn is_stopped(&self) -> bool {
+        *self.stopped.borrow()
+    }
+
+    /// Wait until the actor has stopped, without keeping it alive.
+    pub(crate) async fn wait_for_stop(&self) {
+        let mut stopped = self.stopped.subscribe();
+        let _ = stopped.wait_for(|stopped| *stopped).await;
+    }
+
+    pub(crate) fn state(&self) -> ActorState {
+        match self.state.load(Ordering::Acquire) {
+            s if s == ActorState::Running as u8 => ActorState::Running,
+            _ => ActorState::Stopping,
+        }
+    }
+
+    pub(crate) fn info(&self) -> ActorInfo {
+        ActorInfo {
+            path: self.path.clone(),
+            mailbox_len: self.mailbox_len(),
+            mailbox_bytes: self.mailbox_bytes.load(Ordering::Relaxed),
+            uptime: self.started.elapsed(),
+            state: self.state(),
+            dead_letters: self.dead_letters.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// A place in the mailbox that was counted with [`ActorCell::try_enqueue`] and is given back
+/// unless a message is sent into it.
+pub(crate) struct Reserved<'a>(&'a ActorCell);
+
+impl<'a> Reserved<'a> {
+    pub(crate) fn new(cell: &'a ActorCell) -> Self {
+        Self(cell)
+    }
+
+    /// A message is sent into the place, it is given back once the message is received.
+    pub(crate) fn keep(self) {
+        std::mem::forget(self);
+    }
+}
+
+impl Drop for Reserved<'_> {
+    fn drop(&mut self) {
+        self.0.dequeued(0);
+    }
+}
@@ -0,0 +1,157 @@
+use crate::{
+    behavior,
+    replace::{self, Replacement},
+    ActorContext, ActorRef, Behavior, SpawnOptions, Supervision,
+};
+
+/// How a child that is spawned with [`ActorContext::spawn_child_with`] handles its failures,
+/// instead of the supervision of the system.
+///
+/// A failure is a handler that crashes or fails with [`ActorRefGone`](crate::ActorRefGone).
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ChildOptions {
+    on_failure: OnFailure,
+    escalate_after: Option<u32>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum OnFailure {
+    Stop,
+    Resume,
+    Restart,
+}
+
+impl ChildOptions {
+    /// The child stops, and its parent receives [`Signal::ChildFailed`](crate::Signal::ChildFailed).
+    pub fn stop_on_failure() -> Self {
+        Self::on_failure(OnFailure::Stop)
+    }
+
+    /// The message that failed is a dead letter and the child continues with the next message.
+    pub fn resume_on_failure() -> Self {
+        Self::on_failure(OnFailure::Resume)
+    }
+
+    /// The message that failed is a dead letter and the child continues with the next message,
+    /// with a fresh clone of the behavior that it was spawned with.
+    pub fn restart_on_failure() -> Self {
+        Self::on_failure(OnFailure::Restart)
+    }
+
+    fn on_failure(on_failure: OnFailure) -> Self {
+        Self {
+            on_failure,
+            escalate_after: None,
+        }
+    }
+
+    /// Stop the child on its `failures`th failure in a row and fail the parent with
+    /// [`ChildEscalated`](crate::ChildEscalated), so that the failure is handled by the supervision of the parent.
+    /// A message that the child handles starts the count again.
+    pub fn escalate_after(mut self, failures: u32) -> Self {
+        self.escalate_after = Some(failures.max(1));
+        self
+    }
+}
+
+/// What an actor does when its behavior fails.
+pub(crate) enum Failure<T> {
+    Stop,
+    Resume,
+    Restart(Replacement<T>),
+}
+
+/// Decides how an actor handles its failures, by the supervision of the system
+/// unless the actor is a child with [`ChildOptions`].
+pub(crate) struct Supervisor<T> {
+    options: Option<ChildOptions>,
+    restart: Option<Box<dyn Fn() -> Replacement<T> + Send>>,
+    failures: u32,
+    /// The actor stopped with a failure that goes to its parent.
+    pub(crate) escalated: bool,
+}
+
+impl<T> Supervisor<T> {
+    pub(crate) fn system() -> Self {
+        Self {
+            options: None,
+            restart: None,
+            failures: 0,
+            escalated: false,
+        }
+    }
+
+    pub(crate) fn on_failure(&mut self, supervision: Supervision) -> Failure<T> {
+        let Some(options) = self.options else {
+            return match supervision {
+                Supervision::Stop => Failure::Stop,
+                Supervision::Resume => Failure::Resume,
+            };
+        };
+        self.failures += 1;
+        if options
+            .escalate_after
+            .is_some_and(|after| self.failures >= after)
+        {
+            self.escalated = true;
+            return Failure::Stop;
+        }
+        match (options.on_failure, &self.restart) {
+            (OnFailure::Stop, _) => Failure::Stop,
+            (OnFailure::Resume, _) | (OnFailure::Restart, None) => Failure::Resume,
+            (OnFailure::Restart, Some(restart)) => Failure::Restart(restart()),
+        }
+    }
+
+    /// A message was handled, so the failures before it are no longer in a row.
+    pub(crate) fn on_success(&mut self) {
+        self.failures = 0;
+    }
+}
+
+impl<T> ActorContext<T> {
+    /// Spawn a child of this actor, named `name` below the path of this actor.
+    ///
+    /// The child fails like all actors of the system, see [`ActorContext::spawn_child_with`]
+    /// to choose how it handles its failures.
+    pub fn spawn_child<U: Send + 'static, A, Args>(&self, name: &str, behavior: A) -> ActorRef<U>
+    where
+        A: Behavior<U, Args>,
+    {
+        if let Some(effects) = self.this.cell.effects() {
+            return effects.spawned(self.path().child(name));
+        }
+        behavior::actor_of(self.system(), self.path().child(name), behavior)
+    }
+
+    /// Like [`ActorContext::spawn_child`], but the child handles its failures as configured by `options`.
+    pub fn spawn_child_with<U: Send + 'static, A, Args>(
+        &self,
+        name: &str,
+        behavior: A,
+        options: ChildOptions,
+    ) -> ActorRef<U>
+    where
+        A: Behavior<U, Args> + Clone,
+        Args: 'static,
+    {
+        if let Some(effects) = self.this.cell.effects() {
+            return effects.spawned(self.path().child(name));
+        }
+        let spawned = behavior.clone();
+        let supervisor = Supervisor {
+            options: Some(options),
+            restart: Some(Box::new(move || replace::replacement(spawned.clone()))),
+            failures: 0,
+            escalated: false,
+        };
+        behavior::actor_on(
+            self.system(),
+            None,
+            SpawnOptions::default(),
+            supervisor,
+            self.path().child(name),
+            behavior,
+        )
+    }
+}
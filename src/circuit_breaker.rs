@@ -0,0 +1,188 @@
+use crate::{ActorSystem, CircuitBreakerError};
+use std::{
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum CircuitState {
+    /// Calls are allowed, failures are counted.
+    Closed,
+    /// Calls are rejected until the reset timeout has passed.
+    Open,
+    /// A single trial call is allowed to decide whether to close or to open again.
+    HalfOpen,
+}
+
+/// Published on the system bus whenever a [`CircuitBreaker`] changes its state.
+#[derive(Clone, Debug)]
+pub struct CircuitBreakerTransition {
+    pub name: Arc<str>,
+    pub from: CircuitState,
+    pub to: CircuitState,
+}
+
+/// Protects calls to a failing resource by rejecting them for a while after too many failures.
+///
+/// The breaker opens after `max_failures` consecutive failures and rejects all calls.
+/// After `reset_timeout`, it lets a single trial call through, which either closes
+/// the breaker again or re-opens it for another `reset_timeout`.
+///
+/// All clones share the same state.
+#[derive(Clone, Debug)]
+pub struct CircuitBreaker {
+    name: Arc<str>,
+    max_failures: u32,
+    reset_timeout: Duration,
+    call_timeout: Option<Duration>,
+    system: Option<ActorSystem>,
+    state: Arc<Mutex<Breaker>>,
+}
+
+#[derive(Debug)]
+struct Breaker {
+    state: CircuitState,
+    failures: u32,
+    opened_at: Instant,
+    trial_in_flight: bool,
+}
+
+impl CircuitBreaker {
+    pub fn new(name: impl Into<Arc<str>>, max_failures: u32, reset_timeout: Duration) -> Self {
+        Self {
+            name: name.into(),
+            max_failures: max_failures.max(1),
+            reset_timeout,
+            call_timeout: None,
+            system: None,
+            state: Arc::new(Mutex::new(Breaker {
+                state: CircuitState::Closed,
+                failures: 0,
+                opened_at: Instant::now(),
+                trial_in_flight: false,
+            })),
+        }
+    }
+
+    /// Count calls that take longer than `timeout` in [`CircuitBreaker::call`] as failures.
+    pub fn with_call_timeout(mut self, timeout: Duration) -> Self {
+        self.call_timeout = Some(timeout);
+        self
+    }
+
+    /// Publish a [`CircuitBreakerTransition`] on the system bus for every state change.
+    pub fn publish_transitions(mut self, system: &ActorSystem) -> Self {
+        self.system = Some(system.clone());
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn state(&self) -> CircuitState {
+        self.state.lock().unwrap().state
+    }
+
+    /// Ask for permission to make a call.
+    ///
+    /// If this returns `true`, the outcome of the call must be reported with
+    /// [`CircuitBreaker::success`] or [`CircuitBreaker::failure`].
+    pub fn allow(&self) -> bool {
+        let mut breaker = self.state.lock().unwrap();
+        match breaker.state {
+            CircuitState::Closed => true,
+            CircuitState::Open if breaker.opened_at.elapsed() >= self.reset_timeout => {
+                breaker.trial_in_flight = true;
+                self.transition(breaker, CircuitState::HalfOpen);
+                true
+            }
+            CircuitState::Open => false,
+            CircuitState::HalfOpen if breaker.trial_in_flight => false,
+            CircuitState::HalfOpen => {
+                breaker.trial_in_flight = true;
+                true
+            }
+        }
+    }
+
+    pub fn success(&self) {
+        let mut breaker = self.state.lock().unwrap();
+        breaker.failures = 0;
+        breaker.trial_in_flight = false;
+        if breaker.state != CircuitState::Closed {
+            self.transition(breaker, CircuitState::Closed);
+        }
+    }
+
+    pub fn failure(&self) {
+        let mut breaker = self.state.lock().unwrap();
+        breaker.failures = breaker.failures.saturating_add(1);
+        breaker.trial_in_flight = false;
+        let open = match breaker.state {
+            CircuitState::Closed => breaker.failures >= self.max_failures,
+            CircuitState::Open => false,
+            CircuitState::HalfOpen => true,
+        };
+        if open {
+            breaker.opened_at = Instant::now();
+            self.transition(breaker, CircuitState::Open);
+        }
+    }
+
+    /// Run the call if the breaker allows it and record its outcome.
+    ///
+    /// Dropping the returned future before it completes counts as a failure.
+    pub async fn call<F, O, E>(&self, call: F) -> Result<O, CircuitBreakerError<E>>
+    where
+        F: Future<Output = Result<O, E>>,
+    {
+        if self.allow() == false {
+            return Err(CircuitBreakerError::Open);
+        }
+
+        let mut outcome = Outcome {
+            breaker: self,
+            success: false,
+        };
+        let result = match self.call_timeout {
+            Some(timeout) => match tokio::time::timeout(timeout, call).await {
+                Ok(result) => result.map_err(CircuitBreakerError::Failed),
+                Err(_) => Err(CircuitBreakerError::Timeout),
+            },
+            None => call.await.map_err(CircuitBreakerError::Failed),
+        };
+        outcome.success = result.is_ok();
+        result
+    }
+
+    fn transition(&self, mut breaker: std::sync::MutexGuard<'_, Breaker>, to: CircuitState) {
+        let from = std::mem::replace(&mut breaker.state, to);
+        drop(breaker);
+        if let Some(system) = &self.system {
+            let _ = system.publish(CircuitBreakerTransition {
+                name: Arc::clone(&self.name),
+                from,
+                to,
+            });
+        }
+    }
+}
+
+/// Records the outcome of a call when dropped.
+struct Outcome<'a> {
+    breaker: &'a CircuitBreaker,
+    success: bool,
+}
+
+impl Drop for Outcome<'_> {
+    fn drop(&mut self) {
+        if self.success {
+            self.breaker.success();
+        } else {
+            self.breaker.failure();
+        }
+    }
+}
@@ -0,0 +1,800 @@
+use crate::{
+    behavior, failure_detector::NewDetector, ActorPath, ActorRef, ActorSystem, Behaviors, BoxErr,
+    FailureDetector, MessageCodec, PhiAccrualFailureDetector, VersionError, VersionedCodec,
+};
+use std::{
+    collections::{
+        hash_map::{Entry, RandomState},
+        HashMap,
+    },
+    hash::{BuildHasher, Hasher},
+    io,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+use tokio::{
+    net::ToSocketAddrs,
+    task::JoinHandle,
+    time::{Instant, MissedTickBehavior},
+};
+
+mod crdt;
+mod pubsub;
+mod receptionist;
+mod replicator;
+mod router;
+mod sharding;
+
+pub use crdt::{Counter, Crdt, CrdtCodec, LwwMap, OrSet};
+pub use pubsub::DistributedTopic;
+pub use receptionist::ClusterReceptionist;
+pub use replicator::{Consistency, Replicate};
+pub use router::ClusterRouter;
+pub use sharding::Sharding;
+
+const GOSSIP_PATH: &str = "/system/cluster";
+
+/// How a node takes part in a cluster, see [`ActorSystem::join_cluster`].
+#[derive(Clone)]
+pub struct ClusterSettings {
+    seeds: Vec<String>,
+    gossip_interval: Duration,
+    phi_threshold: f64,
+    remove_after: Duration,
+    forget_after: Duration,
+    failure_detector: Option<NewDetector>,
+    downing: Option<Downing>,
+    roles: Vec<Arc<str>>,
+}
+
+/// How a partition of the cluster is resolved, see [`ClusterSettings::downing`].
+///
+/// Every side of the partition decides on its own, from its view of the members, whether it
+/// keeps running and removes the unreachable members, or downs itself. All members need to use
+/// the same strategy, so that at most one side survives.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Downing {
+    /// Keep the side with more than half of the members, on a tie the side with the lowest address.
+    KeepMajority,
+    /// Keep the side with at least this many members, which should be more than half of the cluster.
+    StaticQuorum(usize),
+    /// Keep the side with the member that has been in the cluster the longest.
+    KeepOldest,
+}
+
+impl ClusterSettings {
+    /// Join the cluster through the nodes at `seeds`, given as `host:port`.
+    ///
+    /// The first node of a cluster can use its own address as the only seed, or no seeds at all.
+    pub fn new<S: Into<String>>(seeds: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            seeds: seeds.into_iter().map(Into::into).collect(),
+            gossip_interval: Duration::from_secs(1),
+            phi_threshold: 8.0,
+            remove_after: Duration::from_secs(10),
+            forget_after: Duration::from_secs(300),
+            failure_detector: None,
+            downing: None,
+            roles: Vec::new(),
+        }
+    }
+
+    /// How often the membership is gossiped to a random member, defaults to 1 second.
+    pub fn gossip_interval(mut self, gossip_interval: Duration) -> Self {
+        self.gossip_interval = gossip_interval;
+        self
+    }
+
+    /// The phi above which a member is considered unreachable, defaults to 8.
+    pub fn phi_threshold(mut self, phi_threshold: f64) -> Self {
+        self.phi_threshold = phi_threshold;
+        self
+    }
+
+    /// How long a member can be unreachable before it is removed, defaults to 10 seconds.
+    ///
+    /// With a [downing](Self::downing) strategy, this is how long no member may have become
+    /// reachable or unreachable before the strategy decides.
+    pub fn remove_after(mut self, remove_after: Duration) -> Self {
+        self.remove_after = remove_after;
+        self
+    }
+
+    /// How long removed members are remembered, so that the gossip of members that have not
+    /// noticed the removal yet does not bring them back, defaults to 5 minutes.
+    pub fn forget_after(mut self, forget_after: Duration) -> Self {
+        self.forget_after = forget_after;
+        self
+    }
+
+    /// Resolve partitions with `downing`, instead of every side removing the members it cannot reach.
+    ///
+    /// Without a strategy, both sides of a partition keep running on their own, each with their
+    /// own shards. A node that has been downed leaves the cluster and does not come back,
+    /// see [`Cluster::is_down`].
+    pub fn downing(mut self, downing: Downing) -> Self {
+        self.downing = Some(downing);
+        self
+    }
+
+    /// Watch every member with a detector from `new`, instead of a [`PhiAccrualFailureDetector`]
+    /// with the [`phi_threshold`](Self::phi_threshold).
+    ///
+    /// The detectors receive a heartbeat whenever the gossip brings news of the member.
+    pub fn failure_detector<D: FailureDetector + 'static>(
+        mut self,
+        new: impl Fn() -> D + Send + Sync + 'static,
+    ) -> Self {
+        self.failure_detector = Some(Arc::new(move || Box::new(new())));
+        self
+    }
+
+    /// The roles of this node, e.g. to only route to some members with a [`ClusterRouter`].
+    pub fn roles<R: Into<Arc<str>>>(mut self, roles: impl IntoIterator<Item = R>) -> Self {
+        self.roles = roles.into_iter().map(Into::into).collect();
+        self
+    }
+
+    fn new_detector(&self) -> Box<dyn FailureDetector> {
+        match &self.failure_detector {
+            Some(new) => new(),
+            None => Box::new(
+                PhiAccrualFailureDetector::new(self.gossip_interval, self.gossip_interval * 3)
+                    .threshold(self.phi_threshold),
+            ),
+        }
+    }
+}
+
+impl std::fmt::Debug for ClusterSettings {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClusterSettings")
+            .field("seeds", &self.seeds)
+            .field("gossip_interval", &self.gossip_interval)
+            .field("phi_threshold", &self.phi_threshold)
+            .field("remove_after", &self.remove_after)
+            .field("forget_after", &self.forget_after)
+            .field("downing", &self.downing)
+            .field("roles", &self.roles)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Changes of the cluster membership, as seen by this node.
+///
+/// The events are published on the [system bus](ActorSystem::subscribe).
+/// `MemberUp` is published again when an unreachable member becomes reachable.
+/// A node that is [down](Cluster::is_down) publishes `MemberRemoved` with its own address.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ClusterEvent {
+    MemberUp(Arc<str>),
+    MemberUnreachable(Arc<str>),
+    MemberRemoved(Arc<str>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum MemberStatus {
+    Up,
+    Unreachable,
+}
+
+/// A node of the cluster, identified by the address that it is bound to.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Member {
+    pub address: Arc<str>,
+    pub status: MemberStatus,
+    pub roles: Vec<Arc<str>>,
+}
+
+/// The membership of this system in a cluster.
+///
+/// The node gossips for as long as this handle is alive, dropping it without
+/// [leaving](Cluster::leave) looks like a crash to the other members.
+pub struct Cluster {
+    address: Arc<str>,
+    membership: Arc<Mutex<Membership>>,
+    _gossip: ActorRef<Gossip>,
+    ticker: JoinHandle<()>,
+}
+
+impl ActorSystem {
+    /// Bind to `addr` and join the cluster through the seeds in `settings`.
+    ///
+    /// Members gossip their view of the cluster and detect failures with a [`FailureDetector`],
+    /// a [phi accrual failure detector](PhiAccrualFailureDetector) by default. Changes are published as [`ClusterEvent`]s.
+    pub async fn join_cluster(
+        &self,
+        addr: impl ToSocketAddrs,
+        settings: ClusterSettings,
+    ) -> io::Result<Cluster> {
+        let address: Arc<str> = self.bind(addr).await?.to_string().into();
+        self.register_codec(GossipCodec::new());
+
+        let joined = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        let membership = Arc::new(Mutex::new(Membership {
+            address: Arc::clone(&address),
+            heartbeat: 0,
+            joined,
+            changed_at: Instant::now(),
+            down: false,
+            nodes: HashMap::new(),
+            peers: HashMap::new(),
+            settings,
+            system: self.clone(),
+        }));
+
+        let gossip = behavior::actor_of(self, ActorPath::system("cluster"), {
+            let membership = Arc::clone(&membership);
+            let system = self.clone();
+            move |gossip: Gossip| {
+                let events = membership.lock().unwrap().merge(gossip, Instant::now());
+                for event in events {
+                    let _ = system.publish(event);
+                }
+                async { Behaviors::Same }
+            }
+        });
+        self.expose(&gossip)
+            .expect("the gossip codec is registered");
+
+        let interval = membership.lock().unwrap().settings.gossip_interval;
+        let ticker = tokio::spawn({
+            let membership = Arc::clone(&membership);
+            let system = self.clone();
+            async move {
+                let mut interval = tokio::time::interval(interval);
+                interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+                loop {
+                    let now = interval.tick().await;
+                    let (events, gossip, targets) = membership.lock().unwrap().tick(now);
+                    for event in events {
+                        let _ = system.publish(event);
+                    }
+                    for target in targets {
+                        let _ = target.tell(gossip.clone());
+                    }
+                }
+            }
+        });
+
+        Ok(Cluster {
+            address,
+            membership,
+            _gossip: gossip,
+            ticker,
+        })
+    }
+}
+
+impl Cluster {
+    /// The address of this node, as seen by the other members.
+    pub fn address(&self) -> &str {
+        &self.address
+    }
+
+    /// All members that have not been removed, including this node, sorted by address.
+    ///
+    /// Empty once this node is down.
+    pub fn members(&self) -> Vec<Member> {
+        self.membership.lock().unwrap().members()
+    }
+
+    /// Whether the [downing](ClusterSettings::downing) strategy has removed this node,
+    /// because it ended up on the side of a partition that does not survive.
+    ///
+    /// A node that is down no longer gossips and hosts no shards, the system has to join again
+    /// with a new address.
+    pub fn is_down(&self) -> bool {
+        self.membership.lock().unwrap().down
+    }
+
+    /// Tell all members that this node leaves, they remove it without waiting for the failure detector.
+    pub fn leave(self) {
+        let mut membership = self.membership.lock().unwrap();
+        let gossip = Gossip {
+            entries: vec![GossipEntry {
+                address: Arc::clone(&self.address),
+                heartbeat: membership.heartbeat + 1,
+                joined: membership.joined,
+                roles: membership.settings.roles.clone(),
+                removed: true,
+                left: true,
+            }],
+        };
+        let targets = membership.members_with(|status| status.is_removed() == false);
+        for target in targets {
+            if let Some(peer) = membership.peer(&target) {
+                let _ = peer.tell(gossip.clone());
+            }
+        }
+    }
+}
+
+impl Drop for Cluster {
+    fn drop(&mut self) {
+        self.ticker.abort();
+    }
+}
+
+/// The view of the cluster of one node.
+struct Membership {
+    address: Arc<str>,
+    heartbeat: u64,
+    /// When this node joined, in milliseconds since the epoch.
+    joined: u64,
+    /// When a member has last become reachable or unreachable.
+    changed_at: Instant,
+    down: bool,
+    nodes: HashMap<Arc<str>, Node>,
+    peers: HashMap<Arc<str>, ActorRef<Gossip>>,
+    settings: ClusterSettings,
+    system: ActorSystem,
+}
+
+struct Node {
+    heartbeat: u64,
+    joined: u64,
+    roles: Vec<Arc<str>>,
+    status: NodeStatus,
+    detector: Box<dyn FailureDetector>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum NodeStatus {
+    Up,
+    Unreachable(Instant),
+    /// Kept as a tombstone, so that gossip from members that have not noticed yet does not revive it,
+    /// until it is forgotten after [`ClusterSettings::forget_after`].
+    Removed {
+        at: Instant,
+        /// Whether the member has left on its own, instead of being removed as unreachable.
+        left: bool,
+    },
+}
+
+impl NodeStatus {
+    fn is_removed(self) -> bool {
+        matches!(self, Self::Removed { .. })
+    }
+}
+
+impl Membership {
+    /// Bump the own heartbeat, check the failure detectors and pick the members to gossip to.
+    fn tick(&mut self, now: Instant) -> (Vec<ClusterEvent>, Gossip, Vec<ActorRef<Gossip>>) {
+        if self.down {
+            return (Vec::new(), self.gossip(), Vec::new());
+        }
+        self.heartbeat += 1;
+
+        let mut events = Vec::new();
+        for (address, node) in &mut self.nodes {
+            match node.status {
+                NodeStatus::Up => {
+                    if node.detector.is_available(now) == false {
+                        node.status = NodeStatus::Unreachable(now);
+                        events.push(ClusterEvent::MemberUnreachable(Arc::clone(address)));
+                    }
+                }
+                NodeStatus::Unreachable(since) => {
+                    if self.settings.downing.is_none()
+                        && now.duration_since(since) >= self.settings.remove_after
+                    {
+                        node.status = NodeStatus::Removed {
+                            at: now,
+                            left: false,
+                        };
+                        events.push(ClusterEvent::MemberRemoved(Arc::clone(address)));
+                    }
+                }
+                NodeStatus::Removed { .. } => {}
+            }
+        }
+        self.forget(now);
+        if events.is_empty() == false {
+            self.changed_at = now;
+        }
+        if let Some(downing) = self.settings.downing {
+            if now.duration_since(self.changed_at) >= self.settings.remove_after {
+                events.extend(self.resolve(downing, now));
+            }
+        }
+        if self.down {
+            return (events, self.gossip(), Vec::new());
+        }
+
+        // prefer reachable members, unreachable ones are only gossiped to when nobody else is left
+        let mut candidates = self.members_with(|status| status == NodeStatus::Up);
+        if candidates.is_empty() {
+            candidates = self.members_with(|status| status.is_removed() == false);
+        }
+        let targets = if candidates.is_empty() {
+            self.settings
+                .seeds
+                .iter()
+                .filter(|seed| **seed != *self.address)
+                .map(|seed| Arc::from(seed.as_str()))
+                .collect()
+        } else {
+            let pick = RandomState::new().build_hasher().finish() as usize % candidates.len();
+            vec![candidates.swap_remove(pick)]
+        };
+        let targets = targets
+            .into_iter()
+            .filter_map(|target: Arc<str>| self.peer(&target))
+            .collect();
+
+        (events, self.gossip(), targets)
+    }
+
+    /// Take in the view of another member, returns the changes to the own view.
+    fn merge(&mut self, gossip: Gossip, now: Instant) -> Vec<ClusterEvent> {
+        let mut events = Vec::new();
+        if self.down {
+            return events;
+        }
+        for entry in gossip.entries {
+            if entry.address == self.address {
+                continue;
+            }
+            match self.nodes.entry(Arc::clone(&entry.address)) {
+                Entry::Vacant(vacant) => {
+                    if entry.removed {
+                        continue;
+                    }
+                    let mut detector = self.settings.new_detector();
+                    detector.heartbeat(now);
+                    let _ = vacant.insert(Node {
+                        heartbeat: entry.heartbeat,
+                        joined: entry.joined,
+                        roles: entry.roles,
+                        status: NodeStatus::Up,
+                        detector,
+                    });
+                    events.push(ClusterEvent::MemberUp(entry.address));
+                }
+                Entry::Occupied(mut occupied) => {
+                    let node = occupied.get_mut();
+                    if node.status.is_removed() {
+                        continue;
+                    }
+                    if entry.removed {
+                        // a member that was removed as unreachable is unreachable here as well,
+                        // even if the failure detector of this node has not noticed yet
+                        if entry.left == false && node.status == NodeStatus::Up {
+                            events
+                                .push(ClusterEvent::MemberUnreachable(Arc::clone(&entry.address)));
+                        }
+                        node.status = NodeStatus::Removed {
+                            at: now,
+                            left: entry.left,
+                        };
+                        events.push(ClusterEvent::MemberRemoved(entry.address));
+                    } else if entry.heartbeat > node.heartbeat {
+                        node.heartbeat = entry.heartbeat;
+                        node.detector.heartbeat(now);
+                        if let NodeStatus::Unreachable(_) = node.status {
+                            node.status = NodeStatus::Up;
+                            events.push(ClusterEvent::MemberUp(entry.address));
+                        }
+                    }
+                }
+            }
+        }
+        if events.is_empty() == false {
+            self.changed_at = now;
+        }
+        events
+    }
+
+    /// Resolve a partition that has been stable for long enough, either by removing
+    /// the unreachable members or by downing this node.
+    fn resolve(&mut self, downing: Downing, now: Instant) -> Vec<ClusterEvent> {
+        let unreachable = self.members_with(|status| matches!(status, NodeStatus::Unreachable(_)));
+        if unreachable.is_empty() {
+            return Vec::new();
+        }
+        let reachable = self.members_with(|status| status == NodeStatus::Up).len() + 1;
+        let members = reachable + unreachable.len();
+        let keep = match downing {
+            Downing::KeepMajority => {
+                let lowest = self.addresses().into_iter().min();
+                reachable * 2 > members
+                    || (reachable * 2 == members
+                        && lowest.is_some_and(|lowest| unreachable.contains(&lowest) == false))
+            }
+            Downing::StaticQuorum(quorum) => reachable >= quorum,
+            Downing::KeepOldest => {
+                let oldest = self
+                    .nodes
+                    .iter()
+                    .filter(|(_, node)| node.status.is_removed() == false)
+                    .map(|(address, node)| (node.joined, address))
+                    .chain(Some((self.joined, &self.address)))
+                    .min()
+                    .map(|(_, address)| Arc::clone(address));
+                oldest.is_some_and(|oldest| unreachable.contains(&oldest) == false)
+            }
+        };
+
+        if keep == false {
+            self.down = true;
+            return vec![ClusterEvent::MemberRemoved(Arc::clone(&self.address))];
+        }
+        unreachable
+            .into_iter()
+            .map(|address| {
+                if let Some(node) = self.nodes.get_mut(&address) {
+                    node.status = NodeStatus::Removed {
+                        at: now,
+                        left: false,
+                    };
+                }
+                ClusterEvent::MemberRemoved(address)
+            })
+            .collect()
+    }
+
+    fn members(&self) -> Vec<Member> {
+        if self.down {
+            return Vec::new();
+        }
+        let mut members = self
+            .nodes
+            .iter()
+            .filter_map(|(address, node)| {
+                let status = match node.status {
+                    NodeStatus::Up => MemberStatus::Up,
+                    NodeStatus::Unreachable(_) => MemberStatus::Unreachable,
+                    NodeStatus::Removed { .. } => return None,
+                };
+                Some(Member {
+                    address: Arc::clone(address),
+                    status,
+                    roles: node.roles.clone(),
+                })
+            })
+            .chain(Some(Member {
+                address: Arc::clone(&self.address),
+                status: MemberStatus::Up,
+                roles: self.settings.roles.clone(),
+            }))
+            .collect::<Vec<_>>();
+        members.sort_by(|a, b| a.address.cmp(&b.address));
+        members
+    }
+
+    /// The addresses of all members that have not been removed, including this node.
+    fn addresses(&self) -> Vec<Arc<str>> {
+        self.members()
+            .into_iter()
+            .map(|member| member.address)
+            .collect()
+    }
+
+    /// Forget the tombstones of members that were removed long enough ago.
+    fn forget(&mut self, now: Instant) {
+        let forget_after = self.settings.forget_after;
+        self.nodes.retain(|_, node| match node.status {
+            NodeStatus::Removed { at, .. } => now.duration_since(at) < forget_after,
+            _ => true,
+        });
+        let nodes = &self.nodes;
+        self.peers.retain(|address, _| nodes.contains_key(address));
+    }
+
+    fn members_with(&self, status: impl Fn(NodeStatus) -> bool) -> Vec<Arc<str>> {
+        self.nodes
+            .iter()
+            .filter(|(_, node)| status(node.status))
+            .map(|(address, _)| Arc::clone(address))
+            .collect()
+    }
+
+    fn gossip(&self) -> Gossip {
+        let entries = self
+            .nodes
+            .iter()
+            .map(|(address, node)| GossipEntry {
+                address: Arc::clone(address),
+                heartbeat: node.heartbeat,
+                joined: node.joined,
+                roles: node.roles.clone(),
+                removed: node.status.is_removed(),
+                left: matches!(node.status, NodeStatus::Removed { left: true, .. }),
+            })
+            .chain(Some(GossipEntry {
+                address: Arc::clone(&self.address),
+                heartbeat: self.heartbeat,
+                joined: self.joined,
+                roles: self.settings.roles.clone(),
+                removed: false,
+                left: false,
+            }))
+            .collect();
+        Gossip { entries }
+    }
+
+    fn peer(&mut self, address: &Arc<str>) -> Option<ActorRef<Gossip>> {
+        if let Some(peer) = self.peers.get(address) {
+            return Some(peer.clone());
+        }
+        let peer = self
+            .system
+            .remote_ref::<Gossip>(&format!("elliot://{address}{GOSSIP_PATH}"))
+            .ok()?;
+        let _ = self.peers.insert(Arc::clone(address), peer.clone());
+        Some(peer)
+    }
+}
+
+/// The view of the cluster that nodes exchange, the highest heartbeat seen for every member.
+#[derive(Clone, Debug)]
+struct Gossip {
+    entries: Vec<GossipEntry>,
+}
+
+#[derive(Clone, Debug)]
+struct GossipEntry {
+    address: Arc<str>,
+    heartbeat: u64,
+    joined: u64,
+    roles: Vec<Arc<str>>,
+    removed: bool,
+    /// Whether the member has left on its own, only set for removed members.
+    left: bool,
+}
+
+/// The version of the gossip format, see [`GossipCodec`].
+const GOSSIP_VERSION: u32 = 2;
+
+/// Tags the gossip with the version of its format, so that nodes can read the gossip of older nodes.
+///
+/// Nodes from before the tag send untagged gossip of version 1.
+struct GossipCodec(VersionedCodec<Gossip>);
+
+impl GossipCodec {
+    fn new() -> Self {
+        let versioned = VersionedCodec::new("elliot.cluster.Gossip", GOSSIP_VERSION, GossipV2)
+            .expect("the name fits into the tag")
+            .upcaster(1, GossipV1, |gossip| gossip);
+        Self(versioned)
+    }
+}
+
+impl MessageCodec<Gossip> for GossipCodec {
+    fn encode(&self, msg: &Gossip) -> Result<Vec<u8>, BoxErr> {
+        self.0.encode(msg)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Gossip, BoxErr> {
+        self.0.decode(bytes).or_else(|e| match e.downcast_ref() {
+            Some(VersionError::Untagged | VersionError::WrongType { .. }) => GossipV1.decode(bytes),
+            _ => Err(e),
+        })
+    }
+}
+
+/// `[count u32]` followed by `[address_len u16][address][heartbeat u64][removed u8]` for every entry.
+///
+/// Members of this version have no roles and count as the youngest, because it is not known
+/// when they joined.
+struct GossipV1;
+
+impl MessageCodec<Gossip> for GossipV1 {
+    fn encode(&self, msg: &Gossip) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u32::try_from(msg.entries.len())?.to_be_bytes());
+        for entry in &msg.entries {
+            bytes.extend_from_slice(&u16::try_from(entry.address.len())?.to_be_bytes());
+            bytes.extend_from_slice(entry.address.as_bytes());
+            bytes.extend_from_slice(&entry.heartbeat.to_be_bytes());
+            bytes.push(u8::from(entry.removed));
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<Gossip, BoxErr> {
+        let count = u32::from_be_bytes(take(&mut bytes, 4)?.try_into()?);
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let address = string(&mut bytes)?;
+            let heartbeat = u64::from_be_bytes(take(&mut bytes, 8)?.try_into()?);
+            let removed = take(&mut bytes, 1)?[0] != 0;
+            entries.push(GossipEntry {
+                address,
+                heartbeat,
+                joined: u64::MAX,
+                roles: Vec::new(),
+                removed,
+                left: false,
+            });
+        }
+        if bytes.is_empty() == false {
+            return Err("trailing bytes after the gossip".into());
+        }
+        Ok(Gossip { entries })
+    }
+}
+
+/// `[count u32]` followed by `[address_len u16][address][heartbeat u64][joined u64][role count u16]
+/// [role_len u16][role]...[removed u8]` for every entry, where `removed` is 1 for members that were
+/// removed as unreachable and 2 for members that have left.
+struct GossipV2;
+
+impl MessageCodec<Gossip> for GossipV2 {
+    fn encode(&self, msg: &Gossip) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u32::try_from(msg.entries.len())?.to_be_bytes());
+        for entry in &msg.entries {
+            bytes.extend_from_slice(&u16::try_from(entry.address.len())?.to_be_bytes());
+            bytes.extend_from_slice(entry.address.as_bytes());
+            bytes.extend_from_slice(&entry.heartbeat.to_be_bytes());
+            bytes.extend_from_slice(&entry.joined.to_be_bytes());
+            bytes.extend_from_slice(&u16::try_from(entry.roles.len())?.to_be_bytes());
+            for role in &entry.roles {
+                bytes.extend_from_slice(&u16::try_from(role.len())?.to_be_bytes());
+                bytes.extend_from_slice(role.as_bytes());
+            }
+            bytes.push(u8::from(entry.removed) + u8::from(entry.left));
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<Gossip, BoxErr> {
+        let count = u32::from_be_bytes(take(&mut bytes, 4)?.try_into()?);
+        let mut entries = Vec::new();
+        for _ in 0..count {
+            let address = string(&mut bytes)?;
+            let heartbeat = u64::from_be_bytes(take(&mut bytes, 8)?.try_into()?);
+            let joined = u64::from_be_bytes(take(&mut bytes, 8)?.try_into()?);
+            let roles = u16::from_be_bytes(take(&mut bytes, 2)?.try_into()?);
+            let roles = (0..roles)
+                .map(|_| string(&mut bytes))
+                .collect::<Result<_, _>>()?;
+            let removed = take(&mut bytes, 1)?[0];
+            entries.push(GossipEntry {
+                address,
+                heartbeat,
+                joined,
+                roles,
+                removed: removed != 0,
+                left: removed == 2,
+            });
+        }
+        Ok(Gossip { entries })
+    }
+}
+
+impl std::fmt::Debug for Cluster {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Cluster")
+            .field("address", &self.address)
+            .finish_non_exhaustive()
+    }
+}
+
+fn string(bytes: &mut &[u8]) -> Result<Arc<str>, BoxErr> {
+    let len = u16::from_be_bytes(take(bytes, 2)?.try_into()?);
+    Ok(std::str::from_utf8(take(bytes, usize::from(len))?)?.into())
+}
+
+/// A hash that is the same on every node, unlike the randomly seeded std hashers.
+fn fnv1a(parts: &[&[u8]]) -> u64 {
+    let mut hash = 0xcbf2_9ce4_8422_2325_u64;
+    for byte in parts.iter().flat_map(|part| part.iter()) {
+        hash ^= u64::from(*byte);
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    hash
+}
+
+fn take<'a>(bytes: &mut &'a [u8], len: usize) -> Result<&'a [u8], BoxErr> {
+    if bytes.len() < len {
+        return Err("truncated message".into());
+    }
+    let (head, tail) = bytes.split_at(len);
+    *bytes = tail;
+    Ok(head)
+}
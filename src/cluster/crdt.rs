@@ -0,0 +1,372 @@
+use super::take;
+use crate::{BoxErr, MessageCodec};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::Arc,
+    time::SystemTime,
+};
+
+/// A replicated data type, whose replicas converge by merging their states in any order.
+///
+/// `merge` must be commutative, associative and idempotent. Changes are made on the replica
+/// of one node, identified by the address of the node, see [`Replicate::update`](crate::Replicate::update).
+pub trait Crdt: Clone + Send + 'static {
+    fn merge(&mut self, other: &Self);
+}
+
+/// A counter that every node can increment and decrement, a PN-counter.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Counter {
+    increments: BTreeMap<Arc<str>, u64>,
+    decrements: BTreeMap<Arc<str>, u64>,
+}
+
+impl Counter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn value(&self) -> i64 {
+        let increments = self.increments.values().sum::<u64>();
+        let decrements = self.decrements.values().sum::<u64>();
+        increments as i64 - decrements as i64
+    }
+
+    pub fn increment(&mut self, replica: &str, n: u64) {
+        *self.increments.entry(replica.into()).or_default() += n;
+    }
+
+    pub fn decrement(&mut self, replica: &str, n: u64) {
+        *self.decrements.entry(replica.into()).or_default() += n;
+    }
+}
+
+impl Crdt for Counter {
+    fn merge(&mut self, other: &Self) {
+        merge_max(&mut self.increments, &other.increments);
+        merge_max(&mut self.decrements, &other.decrements);
+    }
+}
+
+fn merge_max(counts: &mut BTreeMap<Arc<str>, u64>, other: &BTreeMap<Arc<str>, u64>) {
+    for (replica, count) in other {
+        let own = counts.entry(Arc::clone(replica)).or_default();
+        *own = (*own).max(*count);
+    }
+}
+
+/// A unique add of an element, the replica that added it and its count of adds.
+type Dot = (Arc<str>, u64);
+
+/// A set that every node can add elements to and remove elements from, an observed-remove set.
+///
+/// A remove only removes the adds that the replica has seen, concurrent adds win.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OrSet<T> {
+    elements: BTreeMap<T, BTreeSet<Dot>>,
+    /// The highest count of adds seen from every replica.
+    clock: BTreeMap<Arc<str>, u64>,
+}
+
+impl<T: Ord + Clone> OrSet<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, replica: &str, element: T) {
+        let count = self.clock.entry(replica.into()).or_default();
+        *count += 1;
+        let dot = (Arc::from(replica), *count);
+        let _ = self.elements.entry(element).or_default().insert(dot);
+    }
+
+    /// Returns whether the element was in the set.
+    pub fn remove(&mut self, element: &T) -> bool {
+        self.elements.remove(element).is_some()
+    }
+
+    pub fn contains(&self, element: &T) -> bool {
+        self.elements.contains_key(element)
+    }
+
+    /// The elements in order.
+    pub fn iter(&self) -> impl Iterator<Item = &T> {
+        self.elements.keys()
+    }
+
+    pub fn len(&self) -> usize {
+        self.elements.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.elements.is_empty()
+    }
+
+    fn seen(&self, (replica, count): &Dot) -> bool {
+        self.clock.get(replica).is_some_and(|seen| seen >= count)
+    }
+}
+
+impl<T: Ord + Clone + Send + 'static> Crdt for OrSet<T> {
+    fn merge(&mut self, other: &Self) {
+        let mut elements = BTreeMap::new();
+        let all = self.elements.keys().chain(other.elements.keys());
+        for element in all.cloned().collect::<BTreeSet<_>>() {
+            let empty = BTreeSet::new();
+            let own = self.elements.get(&element).unwrap_or(&empty);
+            let theirs = other.elements.get(&element).unwrap_or(&empty);
+            // a dot that only one side has is either new to the other side or removed by it
+            let dots = own
+                .iter()
+                .filter(|dot| theirs.contains(*dot) || other.seen(dot) == false)
+                .chain(
+                    theirs
+                        .iter()
+                        .filter(|dot| own.contains(*dot) == false && self.seen(dot) == false),
+                )
+                .cloned()
+                .collect::<BTreeSet<_>>();
+            if dots.is_empty() == false {
+                let _ = elements.insert(element, dots);
+            }
+        }
+        self.elements = elements;
+        merge_max(&mut self.clock, &other.clock);
+    }
+}
+
+impl<T> Default for OrSet<T> {
+    fn default() -> Self {
+        Self {
+            elements: BTreeMap::new(),
+            clock: BTreeMap::new(),
+        }
+    }
+}
+
+/// A map where the last write to a key wins, by wall clock time.
+///
+/// Writes at the same millisecond are ordered by the address of their replica.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct LwwMap<K, V> {
+    /// Removed keys keep their timestamp, without a value.
+    entries: BTreeMap<K, (u64, Arc<str>, Option<V>)>,
+}
+
+impl<K: Ord + Clone, V: Clone> LwwMap<K, V> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn insert(&mut self, replica: &str, key: K, value: V) {
+        self.write(replica, key, Some(value));
+    }
+
+    pub fn remove(&mut self, replica: &str, key: K) {
+        self.write(replica, key, None);
+    }
+
+    pub fn get(&self, key: &K) -> Option<&V> {
+        self.entries
+            .get(key)
+            .and_then(|(_, _, value)| value.as_ref())
+    }
+
+    /// The entries in the order of their keys.
+    pub fn iter(&self) -> impl Iterator<Item = (&K, &V)> {
+        self.entries
+            .iter()
+            .filter_map(|(key, (_, _, value))| Some((key, value.as_ref()?)))
+    }
+
+    pub fn len(&self) -> usize {
+        self.iter().count()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// A write always wins over the write it replaces, even if the clock went backwards.
+    fn write(&mut self, replica: &str, key: K, value: Option<V>) {
+        let now = SystemTime::now()
+            .duration_since(SystemTime::UNIX_EPOCH)
+            .map_or(0, |since| since.as_millis() as u64);
+        let timestamp = match self.entries.get(&key) {
+            Some((timestamp, _, _)) => now.max(timestamp + 1),
+            None => now,
+        };
+        let _ = self.entries.insert(key, (timestamp, replica.into(), value));
+    }
+}
+
+impl<K: Ord + Clone + Send + 'static, V: Clone + Send + 'static> Crdt for LwwMap<K, V> {
+    fn merge(&mut self, other: &Self) {
+        for (key, theirs) in &other.entries {
+            match self.entries.get(key) {
+                Some(own) if (own.0, &own.1) >= (theirs.0, &theirs.1) => {}
+                _ => {
+                    let _ = self.entries.insert(key.clone(), theirs.clone());
+                }
+            }
+        }
+    }
+}
+
+impl<K, V> Default for LwwMap<K, V> {
+    fn default() -> Self {
+        Self {
+            entries: BTreeMap::new(),
+        }
+    }
+}
+
+/// Encodes the built-in CRDTs, with the elements of sets and the keys and values of maps encoded by `C`.
+///
+/// Counters have no elements, any `C` will do, e.g. `CrdtCodec::new(())`.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct CrdtCodec<C> {
+    elements: C,
+}
+
+impl<C> CrdtCodec<C> {
+    pub fn new(elements: C) -> Self {
+        Self { elements }
+    }
+}
+
+impl<C: Send + Sync + 'static> MessageCodec<Counter> for CrdtCodec<C> {
+    fn encode(&self, msg: &Counter) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        put_counts(&mut bytes, &msg.increments)?;
+        put_counts(&mut bytes, &msg.decrements)?;
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<Counter, BoxErr> {
+        Ok(Counter {
+            increments: counts(&mut bytes)?,
+            decrements: counts(&mut bytes)?,
+        })
+    }
+}
+
+impl<T, C> MessageCodec<OrSet<T>> for CrdtCodec<C>
+where
+    T: Ord + 'static,
+    C: MessageCodec<T>,
+{
+    fn encode(&self, msg: &OrSet<T>) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        put_counts(&mut bytes, &msg.clock)?;
+        bytes.extend_from_slice(&u32::try_from(msg.elements.len())?.to_be_bytes());
+        for (element, dots) in &msg.elements {
+            put_bytes(&mut bytes, &self.elements.encode(element)?)?;
+            bytes.extend_from_slice(&u32::try_from(dots.len())?.to_be_bytes());
+            for (replica, count) in dots {
+                put_str(&mut bytes, replica)?;
+                bytes.extend_from_slice(&count.to_be_bytes());
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<OrSet<T>, BoxErr> {
+        let clock = counts(&mut bytes)?;
+        let mut elements = BTreeMap::new();
+        for _ in 0..u32(&mut bytes)? {
+            let element = self.elements.decode(byte_slice(&mut bytes)?)?;
+            let dots = (0..u32(&mut bytes)?)
+                .map(|_| Ok((string(&mut bytes)?, u64(&mut bytes)?)))
+                .collect::<Result<_, BoxErr>>()?;
+            let _ = elements.insert(element, dots);
+        }
+        Ok(OrSet { elements, clock })
+    }
+}
+
+impl<K, V, C> MessageCodec<LwwMap<K, V>> for CrdtCodec<C>
+where
+    K: Ord + 'static,
+    V: 'static,
+    C: MessageCodec<K> + MessageCodec<V>,
+{
+    fn encode(&self, msg: &LwwMap<K, V>) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u32::try_from(msg.entries.len())?.to_be_bytes());
+        for (key, (timestamp, replica, value)) in &msg.entries {
+            put_bytes(&mut bytes, &self.elements.encode(key)?)?;
+            bytes.extend_from_slice(&timestamp.to_be_bytes());
+            put_str(&mut bytes, replica)?;
+            match value {
+                Some(value) => {
+                    bytes.push(1);
+                    put_bytes(&mut bytes, &self.elements.encode(value)?)?;
+                }
+                None => bytes.push(0),
+            }
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<LwwMap<K, V>, BoxErr> {
+        let mut entries = BTreeMap::new();
+        for _ in 0..u32(&mut bytes)? {
+            let key = self.elements.decode(byte_slice(&mut bytes)?)?;
+            let timestamp = u64(&mut bytes)?;
+            let replica = string(&mut bytes)?;
+            let value = match take(&mut bytes, 1)?[0] {
+                0 => None,
+                _ => Some(self.elements.decode(byte_slice(&mut bytes)?)?),
+            };
+            let _ = entries.insert(key, (timestamp, replica, value));
+        }
+        Ok(LwwMap { entries })
+    }
+}
+
+/// `[count u32]` followed by `[replica_len u16][replica][count u64]` for every replica.
+fn put_counts(bytes: &mut Vec<u8>, counts: &BTreeMap<Arc<str>, u64>) -> Result<(), BoxErr> {
+    bytes.extend_from_slice(&u32::try_from(counts.len())?.to_be_bytes());
+    for (replica, count) in counts {
+        put_str(bytes, replica)?;
+        bytes.extend_from_slice(&count.to_be_bytes());
+    }
+    Ok(())
+}
+
+fn counts(bytes: &mut &[u8]) -> Result<BTreeMap<Arc<str>, u64>, BoxErr> {
+    (0..u32(bytes)?)
+        .map(|_| Ok((string(bytes)?, u64(bytes)?)))
+        .collect()
+}
+
+fn put_str(bytes: &mut Vec<u8>, s: &str) -> Result<(), BoxErr> {
+    bytes.extend_from_slice(&u16::try_from(s.len())?.to_be_bytes());
+    bytes.extend_from_slice(s.as_bytes());
+    Ok(())
+}
+
+fn put_bytes(bytes: &mut Vec<u8>, payload: &[u8]) -> Result<(), BoxErr> {
+    bytes.extend_from_slice(&u32::try_from(payload.len())?.to_be_bytes());
+    bytes.extend_from_slice(payload);
+    Ok(())
+}
+
+fn string(bytes: &mut &[u8]) -> Result<Arc<str>, BoxErr> {
+    let len = u16::from_be_bytes(take(bytes, 2)?.try_into()?);
+    Ok(std::str::from_utf8(take(bytes, usize::from(len))?)?.into())
+}
+
+fn byte_slice<'a>(bytes: &mut &'a [u8]) -> Result<&'a [u8], BoxErr> {
+    let len = u32(bytes)?;
+    take(bytes, len as usize)
+}
+
+fn u32(bytes: &mut &[u8]) -> Result<u32, BoxErr> {
+    Ok(u32::from_be_bytes(take(bytes, 4)?.try_into()?))
+}
+
+fn u64(bytes: &mut &[u8]) -> Result<u64, BoxErr> {
+    Ok(u64::from_be_bytes(take(bytes, 8)?.try_into()?))
+}
@@ -0,0 +1,125 @@
+use super::{Cluster, MemberStatus, Membership};
+use crate::{behavior, ActorPath, ActorRef, ActorSystem, Behaviors, RemoteError};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// A topic that is shared by all nodes of a [`Cluster`].
+///
+/// Events that are published on any node are sent once to every other member that is up,
+/// and every node hands them to its local subscribers. Nodes that have not started the topic
+/// drop the events. Delivery is at most once, like every remote message.
+///
+/// The event type needs a [codec](ActorSystem::register_codec) on all nodes.
+/// There should only be one topic per name and node, clone it to publish from multiple places.
+pub struct DistributedTopic<T> {
+    inner: Arc<TopicInner<T>>,
+}
+
+struct TopicInner<T> {
+    path: ActorPath,
+    address: Arc<str>,
+    membership: Arc<Mutex<Membership>>,
+    system: ActorSystem,
+    subscribers: Arc<Mutex<Vec<ActorRef<T>>>>,
+    nodes: Mutex<HashMap<Arc<str>, ActorRef<T>>>,
+    _inbound: ActorRef<T>,
+}
+
+impl Cluster {
+    /// Start the topic `name` on this node.
+    pub fn topic<T: Clone + Send + 'static>(
+        &self,
+        name: &str,
+    ) -> Result<DistributedTopic<T>, RemoteError> {
+        let system = self.membership.lock().unwrap().system.clone();
+        let path = ActorPath::system("pubsub").child(name);
+        let subscribers = Arc::new(Mutex::new(Vec::new()));
+        let inbound = behavior::actor_of(&system, path.clone(), {
+            let subscribers = Arc::clone(&subscribers);
+            move |event: T| {
+                fan_out(&subscribers, event);
+                async { Behaviors::Same }
+            }
+        });
+        system.expose(&inbound)?;
+        Ok(DistributedTopic {
+            inner: Arc::new(TopicInner {
+                path,
+                address: Arc::clone(&self.address),
+                membership: Arc::clone(&self.membership),
+                system,
+                subscribers,
+                nodes: Mutex::new(HashMap::new()),
+                _inbound: inbound,
+            }),
+        })
+    }
+}
+
+impl<T: Clone + Send + 'static> DistributedTopic<T> {
+    /// Send `event` to the subscribers on all nodes.
+    pub fn publish(&self, event: T) {
+        let inner = &*self.inner;
+        let members = inner.membership.lock().unwrap().members();
+        let mut nodes = inner.nodes.lock().unwrap();
+        for member in members {
+            if member.status != MemberStatus::Up || member.address == inner.address {
+                continue;
+            }
+            let node = match nodes.get(&member.address) {
+                Some(node) => node,
+                None => {
+                    let uri = format!("elliot://{}{}", member.address, inner.path);
+                    let Ok(node) = inner.system.remote_ref::<T>(&uri) else {
+                        continue;
+                    };
+                    nodes.entry(member.address).or_insert(node)
+                }
+            };
+            let _ = node.tell(event.clone());
+        }
+        fan_out(&inner.subscribers, event);
+    }
+
+    /// Receive the events that are published on any node.
+    pub fn subscribe(&self, subscriber: &ActorRef<T>) {
+        self.inner
+            .subscribers
+            .lock()
+            .unwrap()
+            .push(subscriber.clone());
+    }
+
+    pub fn unsubscribe(&self, subscriber: &ActorRef<T>) {
+        self.inner
+            .subscribers
+            .lock()
+            .unwrap()
+            .retain(|s| s.same_actor(subscriber) == false);
+    }
+}
+
+fn fan_out<T: Clone>(subscribers: &Mutex<Vec<ActorRef<T>>>, event: T) {
+    subscribers
+        .lock()
+        .unwrap()
+        .retain(|s| s.tell(event.clone()).is_ok());
+}
+
+impl<T> Clone for DistributedTopic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for DistributedTopic<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DistributedTopic")
+            .field("path", &self.inner.path)
+            .finish_non_exhaustive()
+    }
+}
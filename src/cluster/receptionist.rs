@@ -0,0 +1,392 @@
+use super::{take, Cluster, ClusterEvent, MemberStatus, Membership};
+use crate::{
+    behavior, runtime::AbortHandle, ActorPath, ActorRef, ActorSystem, Behaviors, BoxErr, Listing,
+    ListingUpdate, MessageCodec, RemoteError, ServiceKey,
+};
+use std::{
+    collections::{HashMap, HashSet},
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+
+/// The services under one [`ServiceKey`] on all nodes of a [`Cluster`].
+///
+/// Services are registered with the [receptionist](ActorSystem::receptionist) of their node,
+/// every node shares them with the other members that have started the receptionist for the key.
+/// The services of unreachable members are left out until the members are reachable again.
+///
+/// The message type needs a [codec](ActorSystem::register_codec) on all nodes.
+/// There should only be one receptionist per key and node, clone it to use it from multiple places.
+pub struct ClusterReceptionist<T> {
+    inner: Arc<ReceptionistInner<T>>,
+}
+
+struct ReceptionistInner<T> {
+    key: ServiceKey<T>,
+    system: ActorSystem,
+    state: Arc<Mutex<State<T>>>,
+    _inbound: ActorRef<Registrations>,
+    events: ActorRef<ClusterEvent>,
+    local: AbortHandle,
+}
+
+/// The services of the other members, and who listens to the changes.
+struct State<T> {
+    address: Arc<str>,
+    path: ActorPath,
+    membership: Arc<Mutex<Membership>>,
+    system: ActorSystem,
+    nodes: HashMap<Arc<str>, ActorRef<Registrations>>,
+    remote: HashMap<Arc<str>, Vec<ActorRef<T>>>,
+    unreachable: HashSet<Arc<str>>,
+    subscribers: Vec<mpsc::UnboundedSender<ListingUpdate<T>>>,
+}
+
+/// The services that a member has registered under a key, replacing the ones it has sent before.
+#[derive(Clone, Debug)]
+struct Registrations {
+    address: Arc<str>,
+    /// The sender has just started and asks for the services of the receiver.
+    request: bool,
+    paths: Vec<Arc<str>>,
+}
+
+impl Cluster {
+    /// Start the receptionist for `key` on this node.
+    pub fn receptionist<T: Send + 'static>(
+        &self,
+        key: &ServiceKey<T>,
+    ) -> Result<ClusterReceptionist<T>, RemoteError> {
+        let system = self.membership.lock().unwrap().system.clone();
+        if system.codec::<T>().is_none() {
+            return Err(RemoteError::NoCodec(std::any::type_name::<T>()));
+        }
+        system.register_codec(RegistrationsCodec);
+        let path = ActorPath::system("receptionist").child(key.id());
+        let state = Arc::new(Mutex::new(State {
+            address: Arc::clone(&self.address),
+            path: path.clone(),
+            membership: Arc::clone(&self.membership),
+            system: system.clone(),
+            nodes: HashMap::new(),
+            remote: HashMap::new(),
+            unreachable: HashSet::new(),
+            subscribers: Vec::new(),
+        }));
+
+        let inbound = behavior::actor_of(&system, path, {
+            let (state, key) = (Arc::clone(&state), key.clone());
+            move |registrations: Registrations| {
+                let mut state = state.lock().unwrap();
+                if registrations.request {
+                    let local = local_paths(&state.system, &key);
+                    state.send(&registrations.address, local, false);
+                }
+                state.replace(registrations.address, &registrations.paths);
+                async { Behaviors::Same }
+            }
+        });
+        system.expose(&inbound)?;
+
+        let events = system.spawn_anonymous({
+            let (state, key) = (Arc::clone(&state), key.clone());
+            move |event: ClusterEvent| {
+                let mut state = state.lock().unwrap();
+                match event {
+                    ClusterEvent::MemberUp(address) => {
+                        state.reachable(&address);
+                        let local = local_paths(&state.system, &key);
+                        state.send(&address, local, false);
+                    }
+                    ClusterEvent::MemberUnreachable(address) => state.unreachable(address),
+                    ClusterEvent::MemberRemoved(address) => {
+                        state.replace(Arc::clone(&address), &[]);
+                        let _ = state.unreachable.remove(&address);
+                        let _ = state.nodes.remove(&address);
+                    }
+                }
+                async { Behaviors::Same }
+            }
+        });
+        system.subscribe(&events);
+
+        // the services of this node are shared whenever they change
+        let mut listing = system.receptionist().subscribe(key);
+        let local = system.inner.spawn_abortable({
+            let (state, key, system) = (Arc::clone(&state), key.clone(), system.clone());
+            async move {
+                while let Some(update) = listing.recv().await {
+                    if let ListingUpdate::Registered(service) = &update {
+                        let _ = system.expose(service);
+                    }
+                    let mut state = state.lock().unwrap();
+                    let local = local_paths(&system, &key);
+                    for member in state.members() {
+                        state.send(&member, local.clone(), false);
+                    }
+                }
+            }
+        });
+
+        // ask the members that have started before this node for their services
+        {
+            let mut state = state.lock().unwrap();
+            let local = local_paths(&system, key);
+            for member in state.members() {
+                state.send(&member, local.clone(), true);
+            }
+        }
+
+        Ok(ClusterReceptionist {
+            inner: Arc::new(ReceptionistInner {
+                key: key.clone(),
+                system,
+                state,
+                _inbound: inbound,
+                events,
+                local,
+            }),
+        })
+    }
+}
+
+impl<T: Send + 'static> ClusterReceptionist<T> {
+    pub fn key(&self) -> &ServiceKey<T> {
+        &self.inner.key
+    }
+
+    /// Register `service` with the receptionist of this node, which shares it with the cluster.
+    pub fn register(&self, service: &ActorRef<T>) {
+        self.inner
+            .system
+            .receptionist()
+            .register(&self.inner.key, service);
+    }
+
+    pub fn deregister(&self, service: &ActorRef<T>) {
+        self.inner
+            .system
+            .receptionist()
+            .deregister(&self.inner.key, service);
+    }
+
+    /// The services of this node and of all reachable members, sorted by their path.
+    pub fn find(&self) -> Vec<ActorRef<T>> {
+        let mut services = self.inner.system.receptionist().find(&self.inner.key);
+        let state = self.inner.state.lock().unwrap();
+        services.extend(
+            state
+                .remote
+                .iter()
+                .filter(|(address, _)| state.unreachable.contains(*address) == false)
+                .flat_map(|(_, services)| services.iter().cloned()),
+        );
+        drop(state);
+        services.sort_by(|a, b| a.path().as_str().cmp(b.path().as_str()));
+        services
+    }
+
+    /// Receive the changes of the services on all nodes, starting with the services that are currently found.
+    ///
+    /// Services of a member that becomes unreachable are deregistered, and registered again
+    /// when the member is reachable again.
+    pub fn subscribe(&self) -> Listing<T> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut state = self.inner.state.lock().unwrap();
+        for (address, services) in &state.remote {
+            if state.unreachable.contains(address) == false {
+                for service in services {
+                    let _ = tx.send(ListingUpdate::Registered(service.clone()));
+                }
+            }
+        }
+        state.subscribers.push(tx.clone());
+        drop(state);
+
+        // the updates of this node come straight from its receptionist
+        let mut local = self.inner.system.receptionist().subscribe(&self.inner.key);
+        self.inner.system.inner.spawn_task(async move {
+            while let Some(update) = local.recv().await {
+                if tx.send(update).is_err() {
+                    break;
+                }
+            }
+        });
+        Listing { rx }
+    }
+}
+
+impl<T> ClusterReceptionist<T> {
+    pub(super) fn membership(&self) -> Arc<Mutex<Membership>> {
+        Arc::clone(&self.inner.state.lock().unwrap().membership)
+    }
+}
+
+impl<T: Send + 'static> State<T> {
+    /// The other members that are up.
+    fn members(&self) -> Vec<Arc<str>> {
+        self.membership
+            .lock()
+            .unwrap()
+            .members()
+            .into_iter()
+            .filter(|member| member.status == MemberStatus::Up && member.address != self.address)
+            .map(|member| member.address)
+            .collect()
+    }
+
+    /// Send the services of this node to the member at `address`, always through the same ref to keep them in order.
+    fn send(&mut self, address: &Arc<str>, paths: Vec<Arc<str>>, request: bool) {
+        let node = match self.nodes.get(address) {
+            Some(node) => node,
+            None => {
+                let uri = format!("elliot://{address}{}", self.path);
+                let Ok(node) = self.system.remote_ref::<Registrations>(&uri) else {
+                    return;
+                };
+                self.nodes.entry(Arc::clone(address)).or_insert(node)
+            }
+        };
+        let _ = node.tell(Registrations {
+            address: Arc::clone(&self.address),
+            request,
+            paths,
+        });
+    }
+
+    /// Take the services that the member at `address` has sent.
+    fn replace(&mut self, address: Arc<str>, paths: &[Arc<str>]) {
+        let known = self.remote.remove(&address).unwrap_or_default();
+        let uri = |path: &str| format!("elliot://{address}{path}");
+        let reachable = self.unreachable.contains(&address) == false;
+        let mut services = Vec::new();
+        for service in known {
+            if paths
+                .iter()
+                .any(|path| *service.path().as_str() == uri(path))
+            {
+                services.push(service);
+            } else if reachable {
+                self.notify(&ListingUpdate::Deregistered(service.path().clone()));
+            }
+        }
+        for path in paths {
+            if services.iter().any(|s| *s.path().as_str() == uri(path)) {
+                continue;
+            }
+            let Ok(service) = self.system.remote_ref::<T>(&uri(path)) else {
+                continue;
+            };
+            if reachable {
+                self.notify(&ListingUpdate::Registered(service.clone()));
+            }
+            services.push(service);
+        }
+        if services.is_empty() == false {
+            let _ = self.remote.insert(address, services);
+        }
+    }
+
+    fn unreachable(&mut self, address: Arc<str>) {
+        if let Some(services) = self.remote.get(&address) {
+            let updates = services
+                .iter()
+                .map(|service| ListingUpdate::Deregistered(service.path().clone()))
+                .collect::<Vec<_>>();
+            for update in &updates {
+                self.notify(update);
+            }
+        }
+        let _ = self.unreachable.insert(address);
+    }
+
+    fn reachable(&mut self, address: &Arc<str>) {
+        if self.unreachable.remove(address) == false {
+            return;
+        }
+        if let Some(services) = self.remote.get(address) {
+            let updates = services
+                .iter()
+                .map(|service| ListingUpdate::Registered(service.clone()))
+                .collect::<Vec<_>>();
+            for update in &updates {
+                self.notify(update);
+            }
+        }
+    }
+
+    fn notify(&mut self, update: &ListingUpdate<T>) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(update.clone()).is_ok());
+    }
+}
+
+fn local_paths<T: Send + 'static>(system: &ActorSystem, key: &ServiceKey<T>) -> Vec<Arc<str>> {
+    system
+        .receptionist()
+        .find(key)
+        .iter()
+        .map(|service| Arc::from(service.path().as_str()))
+        .collect()
+}
+
+impl<T> Drop for ReceptionistInner<T> {
+    fn drop(&mut self) {
+        self.local.abort();
+        self.system.unsubscribe(&self.events);
+        self.events.stop();
+    }
+}
+
+/// `[address_len u16][address][request u8][count u32]` followed by `[path_len u16][path]` for every path.
+struct RegistrationsCodec;
+
+impl MessageCodec<Registrations> for RegistrationsCodec {
+    fn encode(&self, msg: &Registrations) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u16::try_from(msg.address.len())?.to_be_bytes());
+        bytes.extend_from_slice(msg.address.as_bytes());
+        bytes.push(u8::from(msg.request));
+        bytes.extend_from_slice(&u32::try_from(msg.paths.len())?.to_be_bytes());
+        for path in &msg.paths {
+            bytes.extend_from_slice(&u16::try_from(path.len())?.to_be_bytes());
+            bytes.extend_from_slice(path.as_bytes());
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<Registrations, BoxErr> {
+        fn string(bytes: &mut &[u8]) -> Result<Arc<str>, BoxErr> {
+            let len = u16::from_be_bytes(take(bytes, 2)?.try_into()?);
+            Ok(std::str::from_utf8(take(bytes, usize::from(len))?)?.into())
+        }
+
+        let address = string(&mut bytes)?;
+        let request = take(&mut bytes, 1)?[0] != 0;
+        let count = u32::from_be_bytes(take(&mut bytes, 4)?.try_into()?);
+        let paths = (0..count)
+            .map(|_| string(&mut bytes))
+            .collect::<Result<_, _>>()?;
+        Ok(Registrations {
+            address,
+            request,
+            paths,
+        })
+    }
+}
+
+impl<T> Clone for ClusterReceptionist<T> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ClusterReceptionist<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ClusterReceptionist")
+            .field("key", &self.inner.key)
+            .finish_non_exhaustive()
+    }
+}
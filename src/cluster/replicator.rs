@@ -0,0 +1,546 @@
+use super::{fnv1a, take, Cluster, MemberStatus, Membership};
+use crate::{
+    behavior,
+    runtime::{Runtime, Task},
+    ActorPath, ActorRef, ActorSystem, Behaviors, BoxErr, ConsistencyTimeout, Crdt, MessageCodec,
+    RemoteError, ReplyTo,
+};
+use std::{
+    collections::{hash_map::Entry, hash_map::RandomState, HashMap},
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+use tokio::sync::oneshot;
+
+type Modify<D> = Box<dyn FnOnce(&mut D, &str) + Send>;
+
+/// How many replicas, including the one of this node, a read or a write waits for.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Consistency {
+    /// Only the replica of this node, writes reach the other members in the background.
+    Local,
+    /// A majority of the members that are up, or fail after the timeout.
+    Majority(Duration),
+    /// All members that are up, or fail after the timeout.
+    All(Duration),
+}
+
+/// The messages of a [replicator](Cluster::replicator), best sent with [`ActorRef::ask`].
+pub enum Replicate<D> {
+    /// Read the value of `key`, merged from the replicas of the consistency, `None` if none of them has a value.
+    Get {
+        key: String,
+        consistency: Consistency,
+        reply: ReplyTo<Result<Option<D>, ConsistencyTimeout>>,
+    },
+    /// Change the value of `key` on this node, starting from the default value, and reply with
+    /// the changed value once it is written to the replicas of the consistency.
+    ///
+    /// `modify` gets the address of this node, which identifies the replica that makes the change.
+    /// It changes a copy of the value, which is merged into the replica, so that the replica can
+    /// take the values of other members in the meantime.
+    /// A timeout does not undo the change, it still reaches the other members in the background.
+    Update {
+        key: String,
+        consistency: Consistency,
+        modify: Modify<D>,
+        reply: ReplyTo<Result<D, ConsistencyTimeout>>,
+    },
+}
+
+impl<D> Replicate<D> {
+    pub fn get(
+        key: impl Into<String>,
+        consistency: Consistency,
+        reply: ReplyTo<Result<Option<D>, ConsistencyTimeout>>,
+    ) -> Self {
+        Self::Get {
+            key: key.into(),
+            consistency,
+            reply,
+        }
+    }
+
+    pub fn update(
+        key: impl Into<String>,
+        consistency: Consistency,
+        modify: impl FnOnce(&mut D, &str) + Send + 'static,
+        reply: ReplyTo<Result<D, ConsistencyTimeout>>,
+    ) -> Self {
+        Self::Update {
+            key: key.into(),
+            consistency,
+            modify: Box::new(modify),
+            reply,
+        }
+    }
+}
+
+/// The values of one replicator on this node, and the reads and writes that wait for other members.
+struct Replicas<D> {
+    address: Arc<str>,
+    path: ActorPath,
+    membership: Arc<Mutex<Membership>>,
+    system: ActorSystem,
+    codec: Arc<dyn MessageCodec<D>>,
+    values: HashMap<String, D>,
+    nodes: HashMap<Arc<str>, ActorRef<Replication>>,
+    pending: HashMap<u64, Pending>,
+    next_id: u64,
+}
+
+struct Pending {
+    /// The answers that are still missing.
+    needed: usize,
+    done: oneshot::Sender<()>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Kind {
+    Write,
+    WriteAck,
+    Read,
+    ReadReply,
+    /// The hashes of all values, to find the values that differ.
+    Digest,
+}
+
+/// What the replicators of one name on different nodes tell each other.
+#[derive(Clone, Debug)]
+struct Replication {
+    address: Arc<str>,
+    kind: Kind,
+    /// The read or write that is answered, `0` for writes that need no answer.
+    id: u64,
+    key: String,
+    value: Option<Vec<u8>>,
+}
+
+impl Cluster {
+    /// Start the replicator `name` for values of type `D` on this node.
+    ///
+    /// Every key has a replica of its value on every node that has started the replicator with the same name,
+    /// the replicas are updated locally and converge by merging, see [`Crdt`]. Changes are sent to the other
+    /// members right away. To repair lost messages and to bring new members up to date, the hashes of all values
+    /// are gossiped to a random member periodically, and the values that differ are exchanged.
+    /// How many replicas a read or write waits for is its [`Consistency`].
+    ///
+    /// `D` needs a [codec](crate::ActorSystem::register_codec) on all nodes, e.g. a [`CrdtCodec`](crate::CrdtCodec).
+    /// The replicator stops when the returned ref is dropped.
+    pub fn replicator<D: Crdt + Default>(
+        &self,
+        name: &str,
+    ) -> Result<ActorRef<Replicate<D>>, RemoteError> {
+        let system = self.membership.lock().unwrap().system.clone();
+        let codec = system
+            .codec::<D>()
+            .ok_or(RemoteError::NoCodec(std::any::type_name::<D>()))?;
+        system.register_codec(ReplicationCodec);
+        let path = ActorPath::system("replicator").child(name);
+        let replicas = Arc::new(Mutex::new(Replicas {
+            address: Arc::clone(&self.address),
+            path: path.clone(),
+            membership: Arc::clone(&self.membership),
+            system: system.clone(),
+            codec,
+            values: HashMap::new(),
+            nodes: HashMap::new(),
+            pending: HashMap::new(),
+            next_id: 1,
+        }));
+
+        let inbound = behavior::actor_of(&system, path, {
+            let replicas = Arc::clone(&replicas);
+            move |replication: Replication| {
+                replicas.lock().unwrap().receive(replication);
+                async { Behaviors::Same }
+            }
+        });
+        system.expose(&inbound)?;
+
+        let interval = self.membership.lock().unwrap().settings.gossip_interval;
+        let runtime = Arc::clone(system.inner.runtime());
+        system.inner.spawn_task(gossip(
+            Arc::downgrade(&replicas),
+            interval,
+            Arc::clone(&runtime),
+        ));
+
+        let path = inbound.path().child("$replicator");
+        Ok(behavior::actor_of(
+            &system,
+            path,
+            move |msg: Replicate<D>| {
+                // the replicator keeps the inbound actor alive
+                let _ = &inbound;
+                match msg {
+                    Replicate::Get {
+                        key,
+                        consistency,
+                        reply,
+                    } => {
+                        let request =
+                            replicas
+                                .lock()
+                                .unwrap()
+                                .request(Kind::Read, &key, consistency);
+                        let replicas = Arc::clone(&replicas);
+                        let timer = timeout(&*runtime, consistency);
+                        runtime.spawn(
+                            "elliot-replicator",
+                            Box::pin(async move {
+                                let done = wait(&replicas, request, timer).await;
+                                let value = replicas.lock().unwrap().values.get(&key).cloned();
+                                let _ = reply.reply(done.map(|()| value));
+                            }),
+                        );
+                    }
+                    Replicate::Update {
+                        key,
+                        consistency,
+                        modify,
+                        reply,
+                    } => {
+                        let (address, mut value) = {
+                            let state = replicas.lock().unwrap();
+                            let value = state.values.get(&key).cloned().unwrap_or_default();
+                            (Arc::clone(&state.address), value)
+                        };
+                        modify(&mut value, &address);
+                        let mut state = replicas.lock().unwrap();
+                        let value = match state.values.entry(key.clone()) {
+                            Entry::Occupied(mut current) => {
+                                current.get_mut().merge(&value);
+                                current.get().clone()
+                            }
+                            Entry::Vacant(current) => current.insert(value).clone(),
+                        };
+                        let request = state.request(Kind::Write, &key, consistency);
+                        drop(state);
+                        let replicas = Arc::clone(&replicas);
+                        let timer = timeout(&*runtime, consistency);
+                        runtime.spawn(
+                            "elliot-replicator",
+                            Box::pin(async move {
+                                let done = wait(&replicas, request, timer).await;
+                                let _ = reply.reply(done.map(|()| value));
+                            }),
+                        );
+                    }
+                }
+                async { Behaviors::Same }
+            },
+        ))
+    }
+}
+
+/// The timeout of `consistency` and the timer that elapses after it, `None` for local consistency.
+fn timeout(runtime: &dyn Runtime, consistency: Consistency) -> Option<(Duration, Task)> {
+    match consistency {
+        Consistency::Local => None,
+        Consistency::Majority(timeout) | Consistency::All(timeout) => {
+            Some((timeout, runtime.sleep(timeout)))
+        }
+    }
+}
+
+/// Wait for the answers of a request, gives up on them after the timeout.
+async fn wait<D>(
+    replicas: &Mutex<Replicas<D>>,
+    request: Option<(u64, oneshot::Receiver<()>)>,
+    timeout: Option<(Duration, Task)>,
+) -> Result<(), ConsistencyTimeout> {
+    let (Some((id, done)), Some((timeout, elapsed))) = (request, timeout) else {
+        return Ok(());
+    };
+    let answered = tokio::select! {
+        answered = done => answered.is_ok(),
+        () = elapsed => false,
+    };
+    if answered {
+        return Ok(());
+    }
+    let _ = replicas.lock().unwrap().pending.remove(&id);
+    Err(ConsistencyTimeout(timeout))
+}
+
+/// Send the digest of all values to a random member, until the replicator is gone.
+async fn gossip<D: Crdt>(
+    replicas: Weak<Mutex<Replicas<D>>>,
+    interval: Duration,
+    runtime: Arc<dyn Runtime>,
+) {
+    loop {
+        runtime.sleep(interval).await;
+        let Some(replicas) = replicas.upgrade() else {
+            return;
+        };
+        let mut replicas = replicas.lock().unwrap();
+        let mut members = replicas.members();
+        if members.is_empty() || replicas.values.is_empty() {
+            continue;
+        }
+        let pick = RandomState::new().build_hasher().finish() as usize % members.len();
+        let member = members.swap_remove(pick);
+        let mut digest = Vec::new();
+        for (key, hash) in replicas.digest() {
+            digest.extend_from_slice(&(key.len() as u16).to_be_bytes());
+            digest.extend_from_slice(key.as_bytes());
+            digest.extend_from_slice(&hash.to_be_bytes());
+        }
+        replicas.send(&member, Kind::Digest, 0, String::new(), Some(digest));
+    }
+}
+
+impl<D: Crdt> Replicas<D> {
+    /// The other members that are up.
+    fn members(&self) -> Vec<Arc<str>> {
+        self.membership
+            .lock()
+            .unwrap()
+            .members()
+            .into_iter()
+            .filter(|member| member.status == MemberStatus::Up && member.address != self.address)
+            .map(|member| member.address)
+            .collect()
+    }
+
+    /// Send a read or the written value of `key` to all other members, returns what to wait for if the consistency needs answers.
+    fn request(
+        &mut self,
+        kind: Kind,
+        key: &str,
+        consistency: Consistency,
+    ) -> Option<(u64, oneshot::Receiver<()>)> {
+        let members = self.members();
+        let needed = match consistency {
+            Consistency::Local => 0,
+            // this node is part of the majority
+            Consistency::Majority(_) => members.len().div_ceil(2),
+            Consistency::All(_) => members.len(),
+        };
+        let value = match kind {
+            Kind::Write => self.encode(key),
+            _ if needed == 0 => return None,
+            _ => None,
+        };
+        let id = if needed == 0 {
+            0
+        } else {
+            self.next_id += 1;
+            self.next_id
+        };
+        for member in &members {
+            self.send(member, kind, id, key.to_owned(), value.clone());
+        }
+        if needed == 0 {
+            return None;
+        }
+        let (done, rx) = oneshot::channel();
+        let _ = self.pending.insert(id, Pending { needed, done });
+        Some((id, rx))
+    }
+
+    fn receive(&mut self, replication: Replication) {
+        let Replication {
+            address,
+            kind,
+            id,
+            key,
+            value,
+        } = replication;
+        match kind {
+            Kind::Write => {
+                if let Some(value) = value {
+                    self.merge(key.clone(), &value);
+                }
+                if id != 0 {
+                    self.send(&address, Kind::WriteAck, id, key, None);
+                }
+            }
+            Kind::Read => {
+                let value = self.encode(&key);
+                self.send(&address, Kind::ReadReply, id, key, value);
+            }
+            Kind::ReadReply => {
+                if let Some(value) = value {
+                    self.merge(key, &value);
+                }
+                self.answered(id);
+            }
+            Kind::WriteAck => self.answered(id),
+            Kind::Digest => {
+                let Some(Ok(mut theirs)) = value.as_deref().map(decode_digest) else {
+                    return;
+                };
+                // both sides merge the values that differ
+                for (key, hash) in self.digest() {
+                    match theirs.remove(&key) {
+                        Some(theirs) if theirs == hash => {}
+                        Some(_) => {
+                            let value = self.encode(&key);
+                            self.send(&address, Kind::Write, 0, key.clone(), value);
+                            self.send(&address, Kind::Read, 0, key, None);
+                        }
+                        None => {
+                            let value = self.encode(&key);
+                            self.send(&address, Kind::Write, 0, key, value);
+                        }
+                    }
+                }
+                for key in theirs.into_keys() {
+                    self.send(&address, Kind::Read, 0, key, None);
+                }
+            }
+        }
+    }
+
+    /// The keys with the hashes of their encoded values.
+    ///
+    /// Equal values can have different encodings, which only makes them look different.
+    fn digest(&self) -> Vec<(String, u64)> {
+        self.values
+            .keys()
+            .filter_map(|key| {
+                let encoded = self.encode(key)?;
+                (key.len() <= usize::from(u16::MAX)).then(|| (key.clone(), fnv1a(&[&encoded])))
+            })
+            .collect()
+    }
+
+    fn answered(&mut self, id: u64) {
+        let Entry::Occupied(mut pending) = self.pending.entry(id) else {
+            return;
+        };
+        pending.get_mut().needed -= 1;
+        if pending.get().needed == 0 {
+            let _ = pending.remove().done.send(());
+        }
+    }
+
+    fn merge(&mut self, key: String, bytes: &[u8]) {
+        let Ok(other) = self.codec.decode(bytes) else {
+            return;
+        };
+        match self.values.entry(key) {
+            Entry::Occupied(mut value) => value.get_mut().merge(&other),
+            Entry::Vacant(value) => {
+                let _ = value.insert(other);
+            }
+        }
+    }
+
+    fn encode(&self, key: &str) -> Option<Vec<u8>> {
+        let value = self.values.get(key)?;
+        self.codec.encode(value).ok()
+    }
+
+    /// Send to the member at `address`, always through the same ref to keep the messages in order.
+    fn send(
+        &mut self,
+        address: &Arc<str>,
+        kind: Kind,
+        id: u64,
+        key: String,
+        value: Option<Vec<u8>>,
+    ) {
+        let node = match self.nodes.get(address) {
+            Some(node) => node,
+            None => {
+                let uri = format!("elliot://{address}{}", self.path);
+                let Ok(node) = self.system.remote_ref::<Replication>(&uri) else {
+                    return;
+                };
+                self.nodes.entry(Arc::clone(address)).or_insert(node)
+            }
+        };
+        let _ = node.tell(Replication {
+            address: Arc::clone(&self.address),
+            kind,
+            id,
+            key,
+            value,
+        });
+    }
+}
+
+impl<D> std::fmt::Debug for Replicate<D> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (name, key, consistency) = match self {
+            Self::Get {
+                key, consistency, ..
+            } => ("Get", key, consistency),
+            Self::Update {
+                key, consistency, ..
+            } => ("Update", key, consistency),
+        };
+        f.debug_struct(name)
+            .field("key", key)
+            .field("consistency", consistency)
+            .finish_non_exhaustive()
+    }
+}
+
+/// `[key_len u16][key][hash u64]` for every key of a digest.
+fn decode_digest(mut bytes: &[u8]) -> Result<HashMap<String, u64>, BoxErr> {
+    let mut digest = HashMap::new();
+    while bytes.is_empty() == false {
+        let len = u16::from_be_bytes(take(&mut bytes, 2)?.try_into()?);
+        let key = std::str::from_utf8(take(&mut bytes, usize::from(len))?)?.to_owned();
+        let hash = u64::from_be_bytes(take(&mut bytes, 8)?.try_into()?);
+        let _ = digest.insert(key, hash);
+    }
+    Ok(digest)
+}
+
+/// `[address_len u16][address][kind u8][id u64][key_len u16][key]`, followed by the value if there is one.
+struct ReplicationCodec;
+
+impl MessageCodec<Replication> for ReplicationCodec {
+    fn encode(&self, msg: &Replication) -> Result<Vec<u8>, BoxErr> {
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&u16::try_from(msg.address.len())?.to_be_bytes());
+        bytes.extend_from_slice(msg.address.as_bytes());
+        bytes.push(msg.kind as u8);
+        bytes.extend_from_slice(&msg.id.to_be_bytes());
+        bytes.extend_from_slice(&u16::try_from(msg.key.len())?.to_be_bytes());
+        bytes.extend_from_slice(msg.key.as_bytes());
+        if let Some(value) = &msg.value {
+            bytes.extend_from_slice(value);
+        }
+        Ok(bytes)
+    }
+
+    fn decode(&self, mut bytes: &[u8]) -> Result<Replication, BoxErr> {
+        fn string<'a>(bytes: &mut &'a [u8]) -> Result<&'a str, BoxErr> {
+            let len = u16::from_be_bytes(take(bytes, 2)?.try_into()?);
+            Ok(std::str::from_utf8(take(bytes, usize::from(len))?)?)
+        }
+
+        let address = string(&mut bytes)?.into();
+        let kind = match take(&mut bytes, 1)?[0] {
+            0 => Kind::Write,
+            1 => Kind::WriteAck,
+            2 => Kind::Read,
+            3 => Kind::ReadReply,
+            4 => Kind::Digest,
+            kind => return Err(format!("unknown replication kind {kind}").into()),
+        };
+        let id = u64::from_be_bytes(take(&mut bytes, 8)?.try_into()?);
+        let key = string(&mut bytes)?.to_owned();
+        let value = match kind {
+            Kind::Write | Kind::ReadReply | Kind::Digest if bytes.is_empty() == false => {
+                Some(bytes.to_vec())
+            }
+            _ => None,
+        };
+        Ok(Replication {
+            address,
+            kind,
+            id,
+            key,
+            value,
+        })
+    }
+}
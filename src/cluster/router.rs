@@ -0,0 +1,136 @@
+use super::{ClusterEvent, ClusterReceptionist, Membership};
+use crate::{remote::parse_uri, router::Selector, ActorRef, Behaviors, Strategy};
+use std::sync::{Arc, Mutex, Weak};
+
+/// Routes messages to the services that are registered under one key on the members of a [`Cluster`](super::Cluster).
+///
+/// The routees are the services that the [`ClusterReceptionist`] of the key finds, they are updated
+/// when services are registered and when members join, become unreachable or are removed.
+/// With a [role](ClusterRouter::role), only the services on members with that role are routees.
+#[derive(Clone, Debug)]
+pub struct ClusterRouter {
+    strategy: Strategy,
+    role: Option<Arc<str>>,
+}
+
+struct Routing<T> {
+    routees: Vec<ActorRef<T>>,
+    selector: Selector,
+}
+
+/// Recomputes the routees, until the router is gone.
+struct Refresh<T> {
+    receptionist: ClusterReceptionist<T>,
+    membership: Arc<Mutex<Membership>>,
+    role: Option<Arc<str>>,
+    routing: Weak<Mutex<Routing<T>>>,
+}
+
+impl ClusterRouter {
+    pub fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            role: None,
+        }
+    }
+
+    /// Only route to services on members that have `role`, see [`ClusterSettings::roles`](super::ClusterSettings::roles).
+    pub fn role(mut self, role: impl Into<Arc<str>>) -> Self {
+        self.role = Some(role.into());
+        self
+    }
+
+    /// Start the router on this node, messages sent to the returned ref are forwarded to one of the services of `receptionist`.
+    ///
+    /// Messages are unhandled while there are no routees. The router stops when the returned ref is dropped.
+    pub fn start<T: Send + 'static>(self, receptionist: &ClusterReceptionist<T>) -> ActorRef<T> {
+        let membership = receptionist.membership();
+        let system = membership.lock().unwrap().system.clone();
+        let routing = Arc::new(Mutex::new(Routing {
+            routees: Vec::new(),
+            selector: Selector::new(self.strategy),
+        }));
+        let refresh = Refresh {
+            receptionist: receptionist.clone(),
+            membership,
+            role: self.role,
+            routing: Arc::downgrade(&routing),
+        };
+        let _ = refresh.run();
+
+        // the roles of new members are only known from the gossip
+        let events = system.spawn_anonymous({
+            let refresh = refresh.clone();
+            move |_: ClusterEvent| {
+                let behavior = if refresh.run() {
+                    Behaviors::Same
+                } else {
+                    Behaviors::Stopped
+                };
+                async move { behavior }
+            }
+        });
+        system.subscribe(&events);
+
+        let mut listing = receptionist.subscribe();
+        system.inner.spawn_task(async move {
+            while listing.recv().await.is_some() {
+                if refresh.run() == false {
+                    return;
+                }
+            }
+        });
+
+        system.spawn_anonymous(move |msg: T| {
+            let mut routing = routing.lock().unwrap();
+            let Routing { routees, selector } = &mut *routing;
+            let behavior = match selector.route(routees, msg) {
+                Ok(()) => Behaviors::Same,
+                Err(_) => Behaviors::Unhandled,
+            };
+            async move { behavior }
+        })
+    }
+}
+
+impl<T: Send + 'static> Refresh<T> {
+    /// Returns `false` once the router is gone.
+    fn run(&self) -> bool {
+        let Some(routing) = self.routing.upgrade() else {
+            return false;
+        };
+        let membership = self.membership.lock().unwrap();
+        let address = Arc::clone(&membership.address);
+        let members = membership.members();
+        drop(membership);
+
+        let routees = self
+            .receptionist
+            .find()
+            .into_iter()
+            .filter(|service| {
+                let Some(role) = &self.role else {
+                    return true;
+                };
+                let node = parse_uri(service.path().as_str())
+                    .map_or(&*address, |(authority, _)| authority);
+                members
+                    .iter()
+                    .any(|member| &*member.address == node && member.roles.contains(role))
+            })
+            .collect();
+        routing.lock().unwrap().routees = routees;
+        true
+    }
+}
+
+impl<T> Clone for Refresh<T> {
+    fn clone(&self) -> Self {
+        Self {
+            receptionist: self.receptionist.clone(),
+            membership: Arc::clone(&self.membership),
+            role: self.role.clone(),
+            routing: Weak::clone(&self.routing),
+        }
+    }
+}
@@ -0,0 +1,267 @@
+use super::{fnv1a, Cluster, ClusterEvent, Membership};
+use crate::{behavior, ActorPath, ActorRef, ActorSystem, Behavior, Behaviors, RemoteError};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+use tokio::time::{Instant, MissedTickBehavior};
+
+type EntityId<T> = Box<dyn Fn(&T) -> String + Send + Sync>;
+type Spawn<T> = Box<dyn Fn(&ActorSystem, ActorPath, &str) -> ActorRef<T> + Send + Sync>;
+
+/// Entity actors that are spread over the nodes of a [`Cluster`].
+///
+/// Every message names the entity it is for, the entity id is mapped to one of a fixed number of shards
+/// and every shard is owned by exactly one node. Entities are spawned on demand on the owning node
+/// and passivated, i.e. stopped, when they did not receive a message for a while.
+///
+/// When nodes join or are removed, shards move to their new owner and the entities of the shards
+/// that moved away are stopped. Entities that need to keep their state across moves should be
+/// [event sourced](crate::EventSourced).
+///
+/// The message type needs a [codec](ActorSystem::register_codec) on all nodes.
+pub struct Sharding<T> {
+    name: String,
+    entity_id: EntityId<T>,
+    spawn: Spawn<T>,
+    shards: u32,
+    passivate_after: Duration,
+}
+
+impl<T: Send + 'static> Sharding<T> {
+    /// Shard entities of type `name`, which must be the same on all nodes.
+    ///
+    /// `entity_id` extracts the entity from a message and `entity` creates the behavior for a new entity.
+    pub fn new<K, F, A, Args>(name: impl Into<String>, entity_id: K, entity: F) -> Self
+    where
+        K: Fn(&T) -> String + Send + Sync + 'static,
+        F: Fn(&str) -> A + Send + Sync + 'static,
+        A: Behavior<T, Args>,
+    {
+        let spawn = move |system: &ActorSystem, path: ActorPath, id: &str| {
+            behavior::actor_of(system, path, entity(id))
+        };
+        Self {
+            name: name.into(),
+            entity_id: Box::new(entity_id),
+            spawn: Box::new(spawn),
+            shards: 100,
+            passivate_after: Duration::from_secs(120),
+        }
+    }
+
+    /// The number of shards, which must be the same on all nodes, defaults to 100.
+    pub fn shards(mut self, shards: u32) -> Self {
+        self.shards = shards.max(1);
+        self
+    }
+
+    /// Stop entities that did not receive a message for `passivate_after`, defaults to 2 minutes.
+    pub fn passivate_after(mut self, passivate_after: Duration) -> Self {
+        self.passivate_after = passivate_after;
+        self
+    }
+
+    /// Start the shard region of this node, messages sent to the returned ref are routed to their entity.
+    ///
+    /// The region stops, together with its entities, when the returned ref is dropped.
+    pub fn start(self, cluster: &Cluster) -> Result<ActorRef<T>, RemoteError> {
+        let system = cluster.membership.lock().unwrap().system.clone();
+        let path = ActorPath::system("sharding").child(&self.name);
+        let region = Arc::new(Mutex::new(Region {
+            sharding: self,
+            path: path.clone(),
+            membership: Arc::clone(&cluster.membership),
+            system: system.clone(),
+            entities: HashMap::new(),
+            regions: HashMap::new(),
+        }));
+
+        // messages from other nodes are always delivered locally, even if this node does not agree
+        // that it owns the shard, so that different views of the membership cannot bounce messages
+        let inbound = behavior::actor_of(&system, path, {
+            let region = Arc::clone(&region);
+            move |this: ActorRef<T>, msg: T| {
+                let mut region = region.lock().unwrap();
+                // a node that is down has no shards
+                if region.is_down() {
+                    this.cell.dead_letter_with(msg);
+                } else {
+                    region.deliver(msg);
+                }
+                async { Behaviors::Same }
+            }
+        });
+        system.expose(&inbound)?;
+
+        let topology = behavior::actor_of(&system, inbound.path().child("$topology"), {
+            let region = Arc::downgrade(&region);
+            move |_: ClusterEvent| {
+                let behavior = match region.upgrade() {
+                    Some(region) => {
+                        region.lock().unwrap().rebalance();
+                        Behaviors::Same
+                    }
+                    None => Behaviors::Stopped,
+                };
+                async move { behavior }
+            }
+        });
+        system.subscribe(&topology);
+
+        let passivate_after = region.lock().unwrap().sharding.passivate_after;
+        let _handle = tokio::spawn(passivate(Arc::downgrade(&region), passivate_after));
+
+        let path = inbound.path().child("$region");
+        Ok(behavior::actor_of(
+            &system,
+            path,
+            move |this: ActorRef<T>, msg: T| {
+                // the region keeps the inbound actor alive
+                let _ = &inbound;
+                if let Err(msg) = region.lock().unwrap().route(msg) {
+                    this.cell.dead_letter_with(msg);
+                }
+                async { Behaviors::Same }
+            },
+        ))
+    }
+}
+
+/// Stop idle entities, until the region is gone.
+async fn passivate<T: Send + 'static>(region: Weak<Mutex<Region<T>>>, passivate_after: Duration) {
+    let mut interval = tokio::time::interval((passivate_after / 2).max(Duration::from_millis(1)));
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    loop {
+        let now = interval.tick().await;
+        let Some(region) = region.upgrade() else {
+            return;
+        };
+        region
+            .lock()
+            .unwrap()
+            .entities
+            .retain(|_, entity| now.duration_since(entity.last_message) < passivate_after);
+    }
+}
+
+/// The entities and shards of one node.
+struct Region<T> {
+    sharding: Sharding<T>,
+    path: ActorPath,
+    membership: Arc<Mutex<Membership>>,
+    system: ActorSystem,
+    entities: HashMap<String, Entity<T>>,
+    regions: HashMap<Arc<str>, ActorRef<T>>,
+}
+
+struct Entity<T> {
+    actor: ActorRef<T>,
+    shard: u32,
+    last_message: Instant,
+}
+
+impl<T: Send + 'static> Region<T> {
+    /// Deliver `msg` to the owner of its shard, hands it back if there is none.
+    fn route(&mut self, msg: T) -> Result<(), T> {
+        let shard = self.shard(&(self.sharding.entity_id)(&msg));
+        // a node that is down has no shards
+        let Some(owner) = self.owner(shard) else {
+            return Err(msg);
+        };
+        if owner == self.address() {
+            self.deliver(msg);
+            return Ok(());
+        }
+        match self.region(&owner) {
+            Some(region) => region.tell(msg).map_err(|gone| gone.0),
+            None => Err(msg),
+        }
+    }
+
+    fn deliver(&mut self, msg: T) {
+        let id = (self.sharding.entity_id)(&msg);
+        let now = Instant::now();
+        let mut msg = msg;
+        for _ in 0..2 {
+            if self.entities.contains_key(&id) == false {
+                let actor = (self.sharding.spawn)(&self.system, self.path.child(&id), &id);
+                let entity = Entity {
+                    actor,
+                    shard: self.shard(&id),
+                    last_message: now,
+                };
+                let _ = self.entities.insert(id.clone(), entity);
+            }
+            let entity = self.entities.get_mut(&id).expect("entity is spawned");
+            entity.last_message = now;
+            match entity.actor.tell(msg) {
+                Ok(()) => return,
+                // the entity stopped by itself, start a new one
+                Err(gone) => {
+                    msg = gone.0;
+                    let _ = self.entities.remove(&id);
+                }
+            }
+        }
+    }
+
+    /// Stop the entities of the shards that are now owned by another node.
+    fn rebalance(&mut self) {
+        let address = self.address();
+        let members = self.membership.lock().unwrap().addresses();
+        self.entities
+            .retain(|_, entity| owner(&members, entity.shard).as_ref() == Some(&address));
+    }
+
+    fn shard(&self, entity_id: &str) -> u32 {
+        (fnv1a(&[entity_id.as_bytes()]) % u64::from(self.sharding.shards)) as u32
+    }
+
+    fn owner(&self, shard: u32) -> Option<Arc<str>> {
+        let members = self.membership.lock().unwrap().addresses();
+        owner(&members, shard)
+    }
+
+    fn address(&self) -> Arc<str> {
+        Arc::clone(&self.membership.lock().unwrap().address)
+    }
+
+    fn is_down(&self) -> bool {
+        self.membership.lock().unwrap().down
+    }
+
+    fn region(&mut self, address: &Arc<str>) -> Option<ActorRef<T>> {
+        if let Some(region) = self.regions.get(address) {
+            return Some(region.clone());
+        }
+        let region = self
+            .system
+            .remote_ref::<T>(&format!("elliot://{address}{}", self.path))
+            .ok()?;
+        let _ = self.regions.insert(Arc::clone(address), region.clone());
+        Some(region)
+    }
+}
+
+/// Rendezvous hashing: the member with the highest hash for the shard owns it,
+/// so that only the shards of a joining or leaving member move.
+/// `None` if there are no members, because the own node is down.
+fn owner(members: &[Arc<str>], shard: u32) -> Option<Arc<str>> {
+    let shard = shard.to_be_bytes();
+    members
+        .iter()
+        .max_by_key(|member| fnv1a(&[&shard, member.as_bytes()]))
+        .map(Arc::clone)
+}
+
+impl<T> std::fmt::Debug for Sharding<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Sharding")
+            .field("name", &self.name)
+            .field("shards", &self.shards)
+            .field("passivate_after", &self.passivate_after)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,240 @@
+use crate::{ActorSystem, BoxErr, VersionError};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+/// Turns messages into bytes and back, for messages that leave the process.
+///
+/// Local actors never need a codec, messages are only encoded when they are
+/// sent to another system.
+pub trait MessageCodec<T>: Send + Sync + 'static {
+    fn encode(&self, msg: &T) -> Result<Vec<u8>, BoxErr>;
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, BoxErr>;
+}
+
+/// Encodes messages as JSON with `serde_json`.
+#[cfg(feature = "json")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct JsonCodec;
+
+#[cfg(feature = "json")]
+impl<T> MessageCodec<T> for JsonCodec
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + 'static,
+{
+    fn encode(&self, msg: &T) -> Result<Vec<u8>, BoxErr> {
+        Ok(serde_json::to_vec(msg)?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, BoxErr> {
+        Ok(serde_json::from_slice(bytes)?)
+    }
+}
+
+/// Encodes messages with `bincode`, using its standard configuration.
+#[cfg(feature = "bincode")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct BincodeCodec;
+
+#[cfg(feature = "bincode")]
+impl<T> MessageCodec<T> for BincodeCodec
+where
+    T: serde::Serialize + serde::de::DeserializeOwned + 'static,
+{
+    fn encode(&self, msg: &T) -> Result<Vec<u8>, BoxErr> {
+        Ok(bincode::serde::encode_to_vec(
+            msg,
+            bincode::config::standard(),
+        )?)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, BoxErr> {
+        let (msg, _) = bincode::serde::decode_from_slice(bytes, bincode::config::standard())?;
+        Ok(msg)
+    }
+}
+
+/// Encodes protobuf messages with `prost`.
+#[cfg(feature = "prost")]
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProstCodec;
+
+#[cfg(feature = "prost")]
+impl<T> MessageCodec<T> for ProstCodec
+where
+    T: prost::Message + Default + 'static,
+{
+    fn encode(&self, msg: &T) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.encode_to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, BoxErr> {
+        Ok(T::decode(bytes)?)
+    }
+}
+
+type Upcaster<T> = Box<dyn Fn(&[u8]) -> Result<T, BoxErr> + Send + Sync>;
+
+/// Tags every message with the name of its type and a schema version, and migrates messages
+/// of older versions when they are decoded.
+///
+/// Messages are encoded with the codec of the current version. Messages from nodes that still run
+/// an older version, or events that have been persisted by one, are decoded with the codec of
+/// their version and turned into the current type by its upcaster. Messages of a version without
+/// an upcaster, like messages from a newer version, fail with a [`VersionError`].
+///
+/// The tag is `[name len: u16][name][version: u32]` in front of the encoded message.
+pub struct VersionedCodec<T> {
+    name: Arc<str>,
+    version: u32,
+    codec: Arc<dyn MessageCodec<T>>,
+    upcasters: HashMap<u32, Upcaster<T>>,
+}
+
+impl<T: 'static> VersionedCodec<T> {
+    /// Encode and decode the current `version` of the messages named `name` with `codec`.
+    ///
+    /// Fails if `name` is longer than 65535 bytes.
+    pub fn new(
+        name: impl Into<Arc<str>>,
+        version: u32,
+        codec: impl MessageCodec<T>,
+    ) -> Result<Self, VersionError> {
+        let name = name.into();
+        if name.len() > usize::from(u16::MAX) {
+            return Err(VersionError::NameTooLong(name.len()));
+        }
+        Ok(Self {
+            name,
+            version,
+            codec: Arc::new(codec),
+            upcasters: HashMap::new(),
+        })
+    }
+
+    /// Decode messages of the older `version` as `O` with `codec` and migrate them with `upcast`.
+    pub fn upcaster<O: 'static>(
+        mut self,
+        version: u32,
+        codec: impl MessageCodec<O>,
+        upcast: impl Fn(O) -> T + Send + Sync + 'static,
+    ) -> Self {
+        let upcaster = move |bytes: &[u8]| Ok(upcast(codec.decode(bytes)?));
+        let _ = self.upcasters.insert(version, Box::new(upcaster));
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    pub fn version(&self) -> u32 {
+        self.version
+    }
+}
+
+impl<T: 'static> MessageCodec<T> for VersionedCodec<T> {
+    fn encode(&self, msg: &T) -> Result<Vec<u8>, BoxErr> {
+        let payload = self.codec.encode(msg)?;
+        let mut bytes = Vec::with_capacity(6 + self.name.len() + payload.len());
+        bytes.extend_from_slice(&(self.name.len() as u16).to_be_bytes());
+        bytes.extend_from_slice(self.name.as_bytes());
+        bytes.extend_from_slice(&self.version.to_be_bytes());
+        bytes.extend_from_slice(&payload);
+        Ok(bytes)
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<T, BoxErr> {
+        let (name, version, payload) = untag(bytes).ok_or(VersionError::Untagged)?;
+        if name != self.name.as_bytes() {
+            return Err(VersionError::WrongType {
+                expected: self.name.to_string(),
+                found: String::from_utf8_lossy(name).into_owned(),
+            }
+            .into());
+        }
+        if version == self.version {
+            return self.codec.decode(payload);
+        }
+        let upcaster =
+            self.upcasters
+                .get(&version)
+                .ok_or_else(|| VersionError::UnknownVersion {
+                    name: self.name.to_string(),
+                    version,
+                })?;
+        upcaster(payload)
+    }
+}
+
+/// Split a tagged message into the type name, the version, and the encoded message.
+fn untag(bytes: &[u8]) -> Option<(&[u8], u32, &[u8])> {
+    let (len, rest) = bytes.split_first_chunk::<2>()?;
+    let len = usize::from(u16::from_be_bytes(*len));
+    if rest.len() < len {
+        return None;
+    }
+    let (name, rest) = rest.split_at(len);
+    let (version, payload) = rest.split_first_chunk::<4>()?;
+    Some((name, u32::from_be_bytes(*version), payload))
+}
+
+/// The codecs of a system, one per message type.
+#[derive(Default)]
+pub(crate) struct Codecs {
+    codecs: Mutex<HashMap<TypeId, Box<dyn Any + Send + Sync>>>,
+}
+
+impl Codecs {
+    pub(crate) fn get<T: 'static>(&self) -> Option<Arc<dyn MessageCodec<T>>> {
+        self.codecs
+            .lock()
+            .unwrap()
+            .get(&TypeId::of::<T>())
+            .and_then(|codec| codec.downcast_ref::<Arc<dyn MessageCodec<T>>>())
+            .cloned()
+    }
+}
+
+impl ActorSystem {
+    /// Use `codec` for all messages of type `T` that are sent to or received from other systems,
+    /// replacing any previously registered codec for `T`.
+    pub fn register_codec<T: 'static>(&self, codec: impl MessageCodec<T>) {
+        let codec: Arc<dyn MessageCodec<T>> = Arc::new(codec);
+        let _ = self
+            .inner
+            .codecs
+            .codecs
+            .lock()
+            .unwrap()
+            .insert(TypeId::of::<T>(), Box::new(codec));
+    }
+
+    /// The registered codec for messages of type `T`.
+    pub fn codec<T: 'static>(&self) -> Option<Arc<dyn MessageCodec<T>>> {
+        self.inner.codecs.get()
+    }
+}
+
+impl<T> std::fmt::Debug for VersionedCodec<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let mut upcasters = self.upcasters.keys().collect::<Vec<_>>();
+        upcasters.sort_unstable();
+        f.debug_struct("VersionedCodec")
+            .field("name", &self.name)
+            .field("version", &self.version)
+            .field("upcasters", &upcasters)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for Codecs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Codecs")
+            .field("types", &self.codecs.lock().unwrap().len())
+            .finish()
+    }
+}
@@ -0,0 +1,227 @@
+use crate::{
+    behavior::{FromContext, IntoResult},
+    runtime::Instant,
+    ActorContext, Behavior, Behaviors, Error, Meta,
+};
+use std::{future::Future, marker::PhantomData, pin::Pin, sync::Arc};
+
+type Handled<T> = Pin<Box<dyn Future<Output = Result<Behaviors, Error<T>>> + Send>>;
+
+/// A behavior that retries the messages that the first behavior did not handle against the second,
+/// created with [`Behavior::or_else`].
+pub struct OrElse<A, B, ArgsA, ArgsB> {
+    behaviors: Arc<(A, B)>,
+    _args: PhantomData<fn(ArgsA, ArgsB)>,
+}
+
+impl<A, B, ArgsA, ArgsB> OrElse<A, B, ArgsA, ArgsB> {
+    pub(crate) fn new(first: A, second: B) -> Self {
+        Self {
+            behaviors: Arc::new((first, second)),
+            _args: PhantomData,
+        }
+    }
+}
+
+impl<T, A, B, ArgsA, ArgsB> Behavior<T> for OrElse<A, B, ArgsA, ArgsB>
+where
+    T: Clone + Send + 'static,
+    A: Behavior<T, ArgsA>,
+    B: Behavior<T, ArgsB>,
+    ArgsA: 'static,
+    ArgsB: 'static,
+{
+    type F = Handled<T>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        let retry = msg.clone();
+        let first = self.behaviors.0.receive(context, msg);
+        let behaviors = Arc::clone(&self.behaviors);
+        let context = context.clone();
+        Box::pin(async move {
+            match first.await {
+                Ok(Behaviors::Unhandled) | Err(Error::Unhandled(_)) => {
+                    behaviors.1.receive(&context, retry).await
+                }
+                handled => handled,
+            }
+        })
+    }
+}
+
+/// A behavior that redelivers the messages that crash it, created with [`Behavior::redeliver`].
+pub struct Redeliver<B, Args> {
+    behavior: B,
+    max_redeliveries: u32,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl<B, Args> Redeliver<B, Args> {
+    pub(crate) fn new(behavior: B, max_redeliveries: u32) -> Self {
+        Self {
+            behavior,
+            max_redeliveries,
+            _args: PhantomData,
+        }
+    }
+}
+
+impl<T, B, Args> Behavior<T> for Redeliver<B, Args>
+where
+    T: Clone + Send + 'static,
+    B: Behavior<T, Args>,
+    Args: 'static,
+{
+    type F = Handled<T>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        let retry = msg.clone();
+        let handled = self.behavior.receive(context, msg);
+        let max_redeliveries = self.max_redeliveries;
+        let context = context.clone();
+        Box::pin(async move {
+            match handled.await {
+                Err(Error::Crashed(e)) => {
+                    let meta = Meta::from_context(&context);
+                    if meta.redeliveries >= max_redeliveries {
+                        return Err(Error::Crashed(e));
+                    }
+                    let meta = Meta {
+                        enqueued_at: Instant::now(),
+                        redeliveries: meta.redeliveries + 1,
+                        ..meta
+                    };
+                    match context.this().deliver(retry, meta) {
+                        Ok(()) => Ok(Behaviors::Same),
+                        Err(_) => Err(Error::Crashed(e)),
+                    }
+                }
+                handled => handled,
+            }
+        })
+    }
+}
+
+/// A behavior that handles only the messages for which a function returns a handler,
+/// created with [`Behaviors::receive_match`].
+pub struct ReceiveMatch<F> {
+    handler: F,
+}
+
+/// A behavior that handles only the messages that match a predicate,
+/// created with [`Behaviors::receive_if`].
+pub struct ReceiveIf<P, B, Args> {
+    predicate: P,
+    behavior: B,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl Behaviors {
+    /// A behavior like a partial function: `handler` returns the handling of the messages
+    /// that it matches and `None` for all other messages, which are unhandled.
+    ///
+    /// Unhandled messages can be passed on to other behaviors with [`Behavior::or_else`].
+    ///
+    /// ```rust
+    /// use elliot::{ActorSystem, Behavior, Behaviors};
+    ///
+    /// #[derive(Clone)]
+    /// enum Msg {
+    ///     Greet(String),
+    ///     Leave(String),
+    /// }
+    ///
+    /// # async fn example() {
+    /// let greetings = Behaviors::receive_match(|msg: Msg| match msg {
+    ///     Msg::Greet(name) => Some(async move { println!("Hello, {name}") }),
+    ///     _ => None,
+    /// });
+    /// let farewells = Behaviors::receive_match(|msg: Msg| match msg {
+    ///     Msg::Leave(name) => Some(async move { println!("Bye, {name}") }),
+    ///     _ => None,
+    /// });
+    /// # let _ = ActorSystem::new().spawn("greeter", greetings.or_else(farewells));
+    /// # }
+    /// ```
+    pub fn receive_match<T, F, Fut>(handler: F) -> ReceiveMatch<F>
+    where
+        F: Fn(T) -> Option<Fut> + Send + Sync + 'static,
+    {
+        ReceiveMatch { handler }
+    }
+
+    /// Handle the messages that match `predicate` with `behavior`, all other messages are unhandled.
+    pub fn receive_if<T, P, B, Args>(predicate: P, behavior: B) -> ReceiveIf<P, B, Args>
+    where
+        P: Fn(&T) -> bool + Send + Sync + 'static,
+        B: Behavior<T, Args>,
+    {
+        ReceiveIf {
+            predicate,
+            behavior,
+            _args: PhantomData,
+        }
+    }
+}
+
+impl<T, F, Fut> Behavior<T> for ReceiveMatch<F>
+where
+    T: Send + 'static,
+    F: Fn(T) -> Option<Fut> + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: IntoResult<T>,
+{
+    type F = Handled<T>;
+
+    fn receive(&self, _context: &ActorContext<T>, msg: T) -> Self::F {
+        match (self.handler)(msg) {
+            Some(handled) => Box::pin(async move { handled.await.into_result() }),
+            None => Box::pin(async { Ok(Behaviors::Unhandled) }),
+        }
+    }
+}
+
+impl<T, P, B, Args> Behavior<T> for ReceiveIf<P, B, Args>
+where
+    T: Send + 'static,
+    P: Fn(&T) -> bool + Send + Sync + 'static,
+    B: Behavior<T, Args>,
+    B::F: 'static,
+    Args: 'static,
+{
+    type F = Handled<T>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        if (self.predicate)(&msg) {
+            Box::pin(self.behavior.receive(context, msg))
+        } else {
+            Box::pin(async { Ok(Behaviors::Unhandled) })
+        }
+    }
+}
+
+impl<F> std::fmt::Debug for ReceiveMatch<F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReceiveMatch").finish_non_exhaustive()
+    }
+}
+
+impl<P, B, Args> std::fmt::Debug for ReceiveIf<P, B, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReceiveIf").finish_non_exhaustive()
+    }
+}
+
+impl<A, B, ArgsA, ArgsB> std::fmt::Debug for OrElse<A, B, ArgsA, ArgsB> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OrElse").finish_non_exhaustive()
+    }
+}
+
+impl<B, Args> std::fmt::Debug for Redeliver<B, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Redeliver")
+            .field("max_redeliveries", &self.max_redeliveries)
+            .finish_non_exhaustive()
+    }
+}
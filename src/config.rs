@@ -0,0 +1,102 @@
+use crate::{ActorSystemBuilder, ConfigError, Supervision};
+use serde::Deserialize;
+use std::{collections::BTreeMap, path::Path, str::FromStr, time::Duration};
+use tokio::runtime::Handle;
+
+/// The configuration of an [`ActorSystem`](crate::ActorSystem), loaded from TOML.
+///
+/// All settings are optional, settings that are missing keep their defaults.
+/// The settings of single actors are keyed by their path.
+///
+/// ```toml
+/// name = "orders"
+/// mailbox_capacity = 1000
+/// supervision = "resume"
+/// dead_letter_capacity = 256
+/// scheduler_resolution_ms = 10
+///
+/// [remote]
+/// bind = "0.0.0.0:7100"
+///
+/// [actors."/user/importer"]
+/// mailbox_capacity = 10
+/// dispatcher = "blocking"
+/// ```
+///
+/// Apply the configuration with [`ActorSystemBuilder::config`], the runtimes for the dispatchers
+/// are registered with [`ActorSystemBuilder::dispatcher`]. Use [`ActorSystemBuilder::try_build`]
+/// to handle dispatchers that are not registered.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActorSystemConfig {
+    pub name: Option<String>,
+    pub mailbox_capacity: Option<usize>,
+    pub supervision: Option<Supervision>,
+    pub dead_letter_capacity: Option<usize>,
+    pub scheduler_resolution_ms: Option<u64>,
+    pub remote: RemoteConfig,
+    pub actors: BTreeMap<String, ActorConfig>,
+}
+
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct RemoteConfig {
+    /// The address for [`ActorSystem::bind_configured`](crate::ActorSystem::bind_configured).
+    pub bind: Option<String>,
+}
+
+/// The settings of a single actor, they take precedence over the system-wide settings.
+#[derive(Clone, Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct ActorConfig {
+    pub mailbox_capacity: Option<usize>,
+    /// The name of the runtime that the actor runs on.
+    pub dispatcher: Option<String>,
+}
+
+impl ActorSystemConfig {
+    pub fn from_path(path: impl AsRef<Path>) -> Result<Self, ConfigError> {
+        std::fs::read_to_string(path)?.parse()
+    }
+}
+
+impl FromStr for ActorSystemConfig {
+    type Err = ConfigError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(toml::from_str(s)?)
+    }
+}
+
+impl ActorSystemBuilder {
+    /// Apply the settings of `config`, replacing the settings that it contains.
+    pub fn config(mut self, config: ActorSystemConfig) -> Self {
+        if let Some(name) = config.name {
+            self = self.name(name);
+        }
+        if let Some(capacity) = config.mailbox_capacity {
+            self = self.mailbox_capacity(capacity);
+        }
+        if let Some(supervision) = config.supervision {
+            self = self.supervision(supervision);
+        }
+        if let Some(capacity) = config.dead_letter_capacity {
+            self = self.dead_letter_capacity(capacity);
+        }
+        if let Some(resolution) = config.scheduler_resolution_ms {
+            self = self.scheduler_resolution(Duration::from_millis(resolution));
+        }
+        #[cfg(feature = "remote")]
+        if let Some(bind) = config.remote.bind {
+            self.bind = Some(bind);
+        }
+        self.actors.extend(config.actors);
+        self
+    }
+
+    /// Run the actors that are configured with the dispatcher `name` on `runtime`.
+    pub fn dispatcher(mut self, name: impl Into<String>, runtime: Handle) -> Self {
+        let _ = self.dispatchers.insert(name.into(), runtime);
+        self
+    }
+}
@@ -0,0 +1,45 @@
+use crate::{cell::ActorCell, mailbox, ActorRef};
+use std::{sync::Arc, time::Duration};
+
+pub(crate) fn conflated<T, F>(target: &ActorRef<T>, window: Duration, mut merge: F) -> ActorRef<T>
+where
+    T: Send + 'static,
+    F: FnMut(T, T) -> T + Send + 'static,
+{
+    let cell = ActorCell::unregistered(target.path().child("$conflate"));
+    cell.set_runtime(target.cell.runtime());
+    let (this, mut mailbox) = mailbox::channel(Arc::new(cell));
+    let target = target.clone();
+    let runtime = this.cell.runtime();
+    this.cell.spawn_task(async move {
+        loop {
+            let first = tokio::select! {
+                msg = mailbox.recv() => msg,
+                () = target.wait_for_stop() => None,
+            };
+            let Some(mut pending) = first else {
+                return;
+            };
+
+            let mut window = runtime.sleep(window);
+            let mut closed = false;
+            loop {
+                tokio::select! {
+                    msg = mailbox.recv() => match msg {
+                        Some(msg) => pending = merge(pending, msg),
+                        None => {
+                            closed = true;
+                            break;
+                        }
+                    },
+                    () = &mut window => break,
+                }
+            }
+
+            if target.tell(pending).is_err() || closed {
+                return;
+            }
+        }
+    });
+    this
+}
@@ -0,0 +1,71 @@
+use crate::{behavior, ActorPath, ActorRef, ActorSystem};
+use std::{fmt::Display, sync::Arc};
+use tokio::{
+    io::{AsyncBufRead, AsyncBufReadExt, AsyncWrite, AsyncWriteExt, BufReader},
+    sync::Mutex,
+};
+
+impl ActorSystem {
+    /// Tell every line from stdin to `target`, without the line ending.
+    ///
+    /// Reading stops at the end of stdin or when the target stops, stdin keeps the target alive until then.
+    pub fn spawn_stdin(&self, target: &ActorRef<String>) {
+        self.spawn_lines(BufReader::new(tokio::io::stdin()), target);
+    }
+
+    /// Like [`ActorSystem::spawn_stdin`], but reading the lines from `reader`.
+    pub fn spawn_lines<R>(&self, reader: R, target: &ActorRef<String>)
+    where
+        R: AsyncBufRead + Unpin + Send + 'static,
+    {
+        let target = target.clone();
+        let _handle = tokio::spawn(async move {
+            let mut lines = reader.lines();
+            // errors while reading end the input as well
+            while let Ok(Some(line)) = lines.next_line().await {
+                if target.tell(line).is_err() {
+                    return;
+                }
+            }
+        });
+    }
+
+    /// Spawn an actor as `name` that writes every message to stdout, as one line.
+    pub fn spawn_stdout<T, N>(&self, name: N) -> ActorRef<T>
+    where
+        T: Display + Send + 'static,
+        N: Into<Arc<str>>,
+    {
+        self.spawn_writer(name, tokio::io::stdout())
+    }
+
+    /// Spawn an actor as `name` that writes every message to stderr, as one line.
+    pub fn spawn_stderr<T, N>(&self, name: N) -> ActorRef<T>
+    where
+        T: Display + Send + 'static,
+        N: Into<Arc<str>>,
+    {
+        self.spawn_writer(name, tokio::io::stderr())
+    }
+
+    /// Like [`ActorSystem::spawn_stdout`], but writing the lines to `writer`.
+    ///
+    /// Every line is flushed right away. Failing to write crashes the actor.
+    pub fn spawn_writer<T, N, W>(&self, name: N, writer: W) -> ActorRef<T>
+    where
+        T: Display + Send + 'static,
+        N: Into<Arc<str>>,
+        W: AsyncWrite + Unpin + Send + 'static,
+    {
+        let writer = Arc::new(Mutex::new(writer));
+        behavior::actor_of(self, ActorPath::user(&name.into()), move |msg: T| {
+            let line = format!("{msg}\n");
+            let writer = Arc::clone(&writer);
+            async move {
+                let mut writer = writer.lock().await;
+                writer.write_all(line.as_bytes()).await?;
+                writer.flush().await
+            }
+        })
+    }
+}
@@ -0,0 +1,77 @@
+use crate::{intercept::Handled, ActorContext, Behavior, Signal};
+use std::{collections::VecDeque, fmt::Debug, marker::PhantomData, sync::Arc, sync::Mutex};
+
+/// The last messages of an actor, for the reports of [`ActorCrashed`](crate::ActorCrashed).
+#[derive(Debug)]
+pub(crate) struct CrashDump {
+    capacity: usize,
+    recent: Mutex<VecDeque<Arc<str>>>,
+}
+
+impl CrashDump {
+    pub(crate) fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            recent: Mutex::new(VecDeque::with_capacity(capacity)),
+        }
+    }
+
+    /// Remember the message rendered by `describe`, which is only called if messages are kept.
+    pub(crate) fn record(&self, describe: impl FnOnce() -> Arc<str>) {
+        if self.capacity == 0 {
+            return;
+        }
+        let mut recent = self.recent.lock().unwrap();
+        if recent.len() == self.capacity {
+            let _ = recent.pop_front();
+        }
+        recent.push_back(describe());
+    }
+
+    /// The kept messages, oldest first.
+    pub(crate) fn recent(&self) -> Arc<[Arc<str>]> {
+        self.recent.lock().unwrap().iter().cloned().collect()
+    }
+}
+
+/// A behavior that dumps its messages with [`Debug`], created with [`Behavior::debug_messages`].
+pub struct DebugMessages<B, Args> {
+    behavior: B,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl<B, Args> DebugMessages<B, Args> {
+    pub(crate) fn new(behavior: B) -> Self {
+        Self {
+            behavior,
+            _args: PhantomData,
+        }
+    }
+}
+
+impl<T, B, Args> Behavior<T> for DebugMessages<B, Args>
+where
+    T: Debug + Send + 'static,
+    B: Behavior<T, Args>,
+    Args: 'static,
+{
+    type F = B::F;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        self.behavior.receive(context, msg)
+    }
+
+    fn receive_signal(&self, context: &ActorContext<T>, signal: Signal) -> Option<Handled<T>> {
+        self.behavior.receive_signal(context, signal)
+    }
+
+    fn describe(&self, msg: &T) -> Option<Arc<str>> {
+        Some(format!("{msg:?}").into())
+    }
+}
+
+impl<B, Args> Debug for DebugMessages<B, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("DebugMessages").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,170 @@
+use crate::runtime::{Instant, Runtime};
+use crate::{behavior, ActorContext, ActorPath, ActorRef, ActorSystem, Behavior, Behaviors, Error};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    future::{ready, Ready},
+    hash::Hash,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+type KeyFn<T, K> = Box<dyn Fn(&T) -> K + Send + Sync>;
+type SpawnFn<T, K> = Box<dyn Fn(&ActorSystem, ActorPath, &K) -> ActorRef<T> + Send + Sync>;
+
+/// A behavior that routes every message to the actor for its key, spawning one on the first message.
+///
+/// Entities are spawned as children of the manager, named after their key. With
+/// [`passivate_after`](EntityManager::passivate_after), entities that have been idle for that long
+/// are stopped and spawned again when the next message for them arrives.
+///
+/// ```rust
+/// use elliot::{ActorSystem, Behaviors, EntityManager};
+/// use std::time::Duration;
+///
+/// struct Login {
+///     user: u32,
+/// }
+///
+/// # async fn example() {
+/// let system = ActorSystem::new();
+/// let sessions = EntityManager::new(
+///     |msg: &Login| msg.user,
+///     |user: &u32| {
+///         let user = *user;
+///         move |_: Login| {
+///             println!("user {user} logged in");
+///             async { Behaviors::Same }
+///         }
+///     },
+/// )
+/// .passivate_after(Duration::from_secs(600));
+///
+/// let sessions = system.spawn("sessions", sessions);
+/// # }
+/// ```
+pub struct EntityManager<T, K> {
+    key: KeyFn<T, K>,
+    spawn: SpawnFn<T, K>,
+    passivate_after: Option<Duration>,
+    entities: Arc<Mutex<Entities<T, K>>>,
+}
+
+struct Entities<T, K> {
+    running: HashMap<K, Entity<T>>,
+    passivating: bool,
+}
+
+struct Entity<T> {
+    actor: ActorRef<T>,
+    last_message: Instant,
+}
+
+impl<T, K> EntityManager<T, K>
+where
+    T: Send + 'static,
+    K: Hash + Eq,
+{
+    pub fn new<KF, F, A, Args>(key: KF, entity: F) -> Self
+    where
+        KF: Fn(&T) -> K + Send + Sync + 'static,
+        F: Fn(&K) -> A + Send + Sync + 'static,
+        A: Behavior<T, Args>,
+    {
+        let spawn = move |system: &ActorSystem, path: ActorPath, key: &K| {
+            behavior::actor_of(system, path, entity(key))
+        };
+        Self {
+            key: Box::new(key),
+            spawn: Box::new(spawn),
+            passivate_after: None,
+            entities: Arc::new(Mutex::new(Entities {
+                running: HashMap::new(),
+                passivating: false,
+            })),
+        }
+    }
+
+    /// Stop entities that did not receive a message for `passivate_after`.
+    pub fn passivate_after(mut self, passivate_after: Duration) -> Self {
+        self.passivate_after = Some(passivate_after);
+        self
+    }
+}
+
+/// Stop idle entities, until the manager is gone.
+async fn passivate<T, K>(
+    entities: Weak<Mutex<Entities<T, K>>>,
+    passivate_after: Duration,
+    runtime: Arc<dyn Runtime>,
+) {
+    let interval = (passivate_after / 2).max(Duration::from_millis(1));
+    loop {
+        runtime.sleep(interval).await;
+        let now = Instant::now();
+        let Some(entities) = entities.upgrade() else {
+            return;
+        };
+        entities
+            .lock()
+            .unwrap()
+            .running
+            .retain(|_, entity| now.duration_since(entity.last_message) < passivate_after);
+    }
+}
+
+impl<T, K> Behavior<T> for EntityManager<T, K>
+where
+    T: Send + 'static,
+    K: Hash + Eq + Clone + Display + Send + Sync + 'static,
+{
+    type F = Ready<Result<Behaviors, Error<T>>>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        let key = (self.key)(&msg);
+        let now = Instant::now();
+        let mut entities = self.entities.lock().unwrap();
+
+        if let Some(passivate_after) = self.passivate_after {
+            if entities.passivating == false {
+                entities.passivating = true;
+                let weak = Arc::downgrade(&self.entities);
+                let cell = &context.this.cell;
+                cell.spawn_task(passivate(weak, passivate_after, cell.runtime()));
+            }
+        }
+
+        let mut msg = msg;
+        for _ in 0..2 {
+            if entities.running.contains_key(&key) == false {
+                let path = context.path().child(&key.to_string());
+                let entity = Entity {
+                    actor: (self.spawn)(context.system(), path, &key),
+                    last_message: now,
+                };
+                let _ = entities.running.insert(key.clone(), entity);
+            }
+            let entity = entities.running.get_mut(&key).expect("entity is running");
+            entity.last_message = now;
+            match entity.actor.tell(msg) {
+                Ok(()) => break,
+                // the entity stopped by itself, start a new one
+                Err(gone) => {
+                    msg = gone.0;
+                    let _ = entities.running.remove(&key);
+                }
+            }
+        }
+
+        ready(Ok(Behaviors::Same))
+    }
+}
+
+impl<T, K> std::fmt::Debug for EntityManager<T, K> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EntityManager")
+            .field("passivate_after", &self.passivate_after)
+            .field("running", &self.entities.lock().unwrap().running.len())
+            .finish_non_exhaustive()
+    }
+}
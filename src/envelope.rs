@@ -0,0 +1,120 @@
+use crate::{behavior::FromContext, runtime::Instant, ActorContext, ActorPath};
+use std::{future::Future, sync::Arc};
+
+/// The metadata of the message that is being handled.
+///
+/// Use `Meta` as an argument of a behavior to get the metadata of every message.
+/// Messages that are told while a message is handled carry its correlation id along,
+/// so that all messages that result from one request can be related to each other.
+///
+/// ```rust
+/// use elliot::{ActorSystem, Meta};
+///
+/// # async fn example() {
+/// let system = ActorSystem::new();
+/// let actor = system.spawn("latency", |meta: Meta, msg: String| async move {
+///     println!("{msg} waited {:?} in the mailbox", meta.enqueued_at.elapsed());
+/// });
+/// actor.tell_correlated(String::from("hello"), "request-1").unwrap();
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Meta {
+    /// When the message was told.
+    pub enqueued_at: Instant,
+    /// The actor that told the message, `None` for messages from outside of an actor.
+    pub sender: Option<ActorPath>,
+    pub correlation_id: Option<Arc<str>>,
+    /// When the message goes stale, it is a dead letter if it is still in the mailbox by then,
+    /// see [`ActorRef::tell_with_ttl`](crate::ActorRef::tell_with_ttl).
+    pub expires_at: Option<Instant>,
+    /// How often the message has been redelivered after it crashed the actor,
+    /// see [`Behavior::redeliver`](crate::Behavior::redeliver).
+    pub redeliveries: u32,
+    /// The authenticated identity of the system that sent the message, for messages from other systems,
+    /// see [`ActorSystem::set_authenticator`](crate::ActorSystem::set_authenticator).
+    pub peer: Option<Arc<str>>,
+    /// The OpenTelemetry context of the teller, which is the current context while the message is handled.
+    #[cfg(feature = "opentelemetry")]
+    pub otel_context: opentelemetry::Context,
+}
+
+pub(crate) struct Envelope<T> {
+    pub(crate) msg: T,
+    pub(crate) meta: Meta,
+    /// The approximate bytes of `msg`, see [`ActorSystem::spawn_bounded_by_size`](crate::ActorSystem::spawn_bounded_by_size).
+    pub(crate) size: usize,
+}
+
+struct Current {
+    path: ActorPath,
+    meta: Meta,
+}
+
+tokio::task_local! {
+    static CURRENT: Current;
+}
+
+impl Meta {
+    /// The metadata for a message that is told right now, by the actor that is handling a message, if any.
+    pub(crate) fn capture(correlation_id: Option<Arc<str>>) -> Self {
+        let current = CURRENT
+            .try_with(|current| (current.path.clone(), current.meta.correlation_id.clone()))
+            .ok();
+        let (sender, inherited) = match current {
+            Some((path, correlation_id)) => (Some(path), correlation_id),
+            None => (None, None),
+        };
+        Self {
+            enqueued_at: Instant::now(),
+            sender,
+            correlation_id: correlation_id.or(inherited),
+            expires_at: None,
+            redeliveries: 0,
+            peer: None,
+            #[cfg(feature = "opentelemetry")]
+            otel_context: opentelemetry::Context::current(),
+        }
+    }
+
+    #[inline]
+    pub(crate) fn is_expired(&self) -> bool {
+        self.expires_at
+            .is_some_and(|expires_at| Instant::now() >= expires_at)
+    }
+}
+
+/// Run the handler that `receive` creates with `meta` as the metadata of the current message.
+pub(crate) async fn scope<F: Future>(
+    path: &ActorPath,
+    meta: Meta,
+    receive: impl FnOnce() -> F,
+) -> F::Output {
+    let current = Current {
+        path: path.clone(),
+        meta,
+    };
+    #[cfg(feature = "opentelemetry")]
+    let otel_context = current.meta.otel_context.clone();
+    // the arguments of the behavior are extracted in the same scope, before the handler is first polled,
+    // so that the metadata is not copied for every message
+    let handler = async move {
+        let handler = {
+            #[cfg(feature = "opentelemetry")]
+            let _guard = otel_context.clone().attach();
+            receive()
+        };
+        #[cfg(feature = "opentelemetry")]
+        let handler = opentelemetry::context::FutureExt::with_context(handler, otel_context);
+        handler.await
+    };
+    CURRENT.scope(current, handler).await
+}
+
+impl<T> FromContext<T> for Meta {
+    fn from_context(_context: &ActorContext<T>) -> Self {
+        CURRENT
+            .try_with(|current| current.meta.clone())
+            .unwrap_or_else(|_| Self::capture(None))
+    }
+}
@@ -1,6 +1,8 @@
 use std::{
+    any::Any,
     error::Error as StdError,
     fmt::{Debug, Display},
+    time::Duration,
 };
 
 pub type BoxErr = Box<dyn StdError + Send + Sync + 'static>;
@@ -21,6 +23,141 @@ pub struct Stopped;
 #[derive(Clone)]
 pub struct ActorRefGone<T>(pub T);
 
+/// The message of [`ActorRef::try_send`](crate::ActorRef::try_send) was rejected.
+#[derive(Clone)]
+pub enum TrySendError<T> {
+    /// The mailbox is at its bound of messages or bytes.
+    Full(T),
+    /// The actor has stopped.
+    ActorRefGone(T),
+}
+
+/// The message of [`AnyActorRef::tell`](crate::AnyActorRef::tell) was rejected.
+pub enum AnyTellError {
+    /// The actor does not receive messages of this type.
+    WrongType(Box<dyn Any + Send>),
+    /// The actor has stopped.
+    ActorRefGone(Box<dyn Any + Send>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum AskError {
+    /// The message could not be delivered because the actor has stopped.
+    ActorRefGone,
+    /// The actor dropped the [`ReplyTo`](crate::ReplyTo) without replying.
+    NoReply,
+    /// There was no reply within the timeout.
+    Timeout,
+    /// Too many asks wait for their turn in an [`AskPool`](crate::AskPool).
+    Overloaded,
+    /// The ask was cancelled before the reply arrived.
+    Cancelled,
+}
+
+/// A message was not handled within the timeout of [`with_timeout`](crate::intercept::with_timeout).
+#[derive(Copy, Clone, Debug)]
+pub struct HandlerTimeout(pub Duration);
+
+/// Not enough replicas answered a read or write of a [replicator](crate::Cluster::replicator) within the timeout.
+#[cfg(feature = "cluster")]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct ConsistencyTimeout(pub Duration);
+
+/// A child has escalated its failure to its parent, see
+/// [`ChildOptions::escalate_after`](crate::ChildOptions::escalate_after).
+#[derive(Clone, Debug)]
+pub struct ChildEscalated(pub crate::ActorCrashed);
+
+/// Another actor of the system is already spawned with this name.
+#[derive(Clone, Debug)]
+pub struct NameTaken(pub String);
+
+/// An actor is configured with a dispatcher that has not been registered with
+/// [`ActorSystemBuilder::dispatcher`](crate::ActorSystemBuilder::dispatcher).
+#[cfg(feature = "config")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnknownDispatcher {
+    pub dispatcher: String,
+    pub path: String,
+}
+
+#[derive(Clone, Debug)]
+pub struct InvalidSchedule(pub(crate) String);
+
+#[derive(Clone, Debug)]
+pub struct InvalidActorPath(pub(crate) String);
+
+#[derive(Clone, Debug)]
+pub enum CircuitBreakerError<E> {
+    /// The call was rejected because the circuit breaker is open.
+    Open,
+    /// The call did not complete within the call timeout.
+    Timeout,
+    /// The call itself failed.
+    Failed(E),
+}
+
+#[cfg(feature = "config")]
+#[derive(Debug)]
+pub enum ConfigError {
+    /// The configuration file could not be read.
+    Io(std::io::Error),
+    /// The configuration is not valid TOML or has unknown or invalid settings.
+    Parse(toml::de::Error),
+}
+
+#[cfg(feature = "remote")]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum RemoteError {
+    /// The uri is not of the form `elliot://host:port/path`.
+    InvalidUri(String),
+    /// There is no codec registered for the message type.
+    NoCodec(&'static str),
+}
+
+/// Why a [`VersionedCodec`](crate::VersionedCodec) could not be created or could not decode a message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum VersionError {
+    /// The type name does not fit into the tag, which allows up to 65535 bytes.
+    NameTooLong(usize),
+    /// The message has no type name and version, or they are cut off.
+    Untagged,
+    /// The message is of another type.
+    WrongType { expected: String, found: String },
+    /// There is no upcaster for the version of the message, e.g. because it is newer.
+    UnknownVersion { name: String, version: u32 },
+}
+
+#[cfg(feature = "nats")]
+#[derive(Debug)]
+pub enum NatsError {
+    /// There is no codec registered for the message type.
+    NoCodec(&'static str),
+    /// Subscribing to the subject failed.
+    Subscribe(async_nats::SubscribeError),
+}
+
+#[cfg(feature = "redis")]
+#[derive(Debug)]
+pub enum RedisBridgeError {
+    /// There is no codec registered for the event type.
+    NoCodec(&'static str),
+    /// Subscribing to the channel failed.
+    Redis(redis::RedisError),
+}
+
+impl Display for ChildEscalated {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Child {} failed: {}",
+            self.0.path, self.0.reason
+        ))
+    }
+}
+
+impl StdError for ChildEscalated {}
+
 impl Display for NoActorRef {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.pad("No actor refs are alive, stopping actor")
@@ -37,6 +174,224 @@ impl Display for Stopped {
 
 impl StdError for Stopped {}
 
+impl Display for AskError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::ActorRefGone => f.pad("The recipient ActorRef is no longer available"),
+            Self::NoReply => f.pad("The actor did not reply"),
+            Self::Timeout => f.pad("The ask timed out"),
+            Self::Overloaded => f.pad("Too many asks are waiting for the actor"),
+            Self::Cancelled => f.pad("The ask was cancelled"),
+        }
+    }
+}
+
+impl StdError for AskError {}
+
+impl Display for HandlerTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "The message was not handled within {:?}",
+            self.0
+        ))
+    }
+}
+
+impl StdError for HandlerTimeout {}
+
+#[cfg(feature = "cluster")]
+impl Display for ConsistencyTimeout {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "Not enough replicas answered within {:?}",
+            self.0
+        ))
+    }
+}
+
+#[cfg(feature = "cluster")]
+impl StdError for ConsistencyTimeout {}
+
+impl Display for NameTaken {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "An actor with the name `{}` is already spawned",
+            self.0
+        ))
+    }
+}
+
+impl StdError for NameTaken {}
+
+#[cfg(feature = "config")]
+impl Display for UnknownDispatcher {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!(
+            "The dispatcher `{}` of {} is not registered",
+            self.dispatcher, self.path
+        ))
+    }
+}
+
+#[cfg(feature = "config")]
+impl StdError for UnknownDispatcher {}
+
+impl Display for InvalidSchedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Invalid schedule: {}", self.0))
+    }
+}
+
+impl StdError for InvalidSchedule {}
+
+impl Display for InvalidActorPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_fmt(format_args!("Invalid actor path: `{}`", self.0))
+    }
+}
+
+impl StdError for InvalidActorPath {}
+
+#[cfg(feature = "config")]
+impl Display for ConfigError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Io(e) => f.write_fmt(format_args!("Could not read the configuration: {e}")),
+            Self::Parse(e) => f.write_fmt(format_args!("Invalid configuration: {e}")),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl StdError for ConfigError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Parse(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<std::io::Error> for ConfigError {
+    fn from(e: std::io::Error) -> Self {
+        Self::Io(e)
+    }
+}
+
+#[cfg(feature = "config")]
+impl From<toml::de::Error> for ConfigError {
+    fn from(e: toml::de::Error) -> Self {
+        Self::Parse(e)
+    }
+}
+
+#[cfg(feature = "remote")]
+impl Display for RemoteError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::InvalidUri(uri) => f.write_fmt(format_args!("Invalid remote actor uri: {uri}")),
+            Self::NoCodec(ty) => f.write_fmt(format_args!("No codec registered for {ty}")),
+        }
+    }
+}
+
+#[cfg(feature = "remote")]
+impl StdError for RemoteError {}
+
+impl Display for VersionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NameTooLong(len) => f.write_fmt(format_args!(
+                "The type name has {len} bytes, at most {} fit into the tag",
+                u16::MAX
+            )),
+            Self::Untagged => f.write_str("The message has no version tag"),
+            Self::WrongType { expected, found } => f.write_fmt(format_args!(
+                "Expected a message of {expected}, got {found}"
+            )),
+            Self::UnknownVersion { name, version } => {
+                f.write_fmt(format_args!("No upcaster for version {version} of {name}"))
+            }
+        }
+    }
+}
+
+impl StdError for VersionError {}
+
+#[cfg(feature = "nats")]
+impl Display for NatsError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoCodec(ty) => f.write_fmt(format_args!("No codec registered for {ty}")),
+            Self::Subscribe(e) => f.write_fmt(format_args!("Could not subscribe: {e}")),
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+impl StdError for NatsError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::NoCodec(_) => None,
+            Self::Subscribe(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "nats")]
+impl From<async_nats::SubscribeError> for NatsError {
+    fn from(e: async_nats::SubscribeError) -> Self {
+        Self::Subscribe(e)
+    }
+}
+
+#[cfg(feature = "redis")]
+impl Display for RedisBridgeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::NoCodec(ty) => f.write_fmt(format_args!("No codec registered for {ty}")),
+            Self::Redis(e) => f.write_fmt(format_args!("Could not subscribe: {e}")),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl StdError for RedisBridgeError {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::NoCodec(_) => None,
+            Self::Redis(e) => Some(e),
+        }
+    }
+}
+
+#[cfg(feature = "redis")]
+impl From<redis::RedisError> for RedisBridgeError {
+    fn from(e: redis::RedisError) -> Self {
+        Self::Redis(e)
+    }
+}
+
+impl<E: Display> Display for CircuitBreakerError<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Open => f.pad("The circuit breaker is open"),
+            Self::Timeout => f.pad("The call timed out"),
+            Self::Failed(e) => Display::fmt(e, f),
+        }
+    }
+}
+
+impl<E: StdError + 'static> StdError for CircuitBreakerError<E> {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self {
+            Self::Failed(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
 impl<T> Debug for ActorRefGone<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("Unhandled").finish_non_exhaustive()
@@ -51,6 +406,64 @@ impl<T> Display for ActorRefGone<T> {
 
 impl<T> StdError for ActorRefGone<T> {}
 
+impl<T> TrySendError<T> {
+    /// The message that was rejected.
+    pub fn into_inner(self) -> T {
+        match self {
+            Self::Full(msg) | Self::ActorRefGone(msg) => msg,
+        }
+    }
+}
+
+impl<T> Debug for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => f.pad("Full(..)"),
+            Self::ActorRefGone(_) => f.pad("ActorRefGone(..)"),
+        }
+    }
+}
+
+impl<T> Display for TrySendError<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Full(_) => f.pad("The mailbox of the recipient is full"),
+            Self::ActorRefGone(_) => f.pad("The recipient ActorRef is no longer available"),
+        }
+    }
+}
+
+impl<T> StdError for TrySendError<T> {}
+
+impl AnyTellError {
+    /// The message that was rejected.
+    pub fn into_inner(self) -> Box<dyn Any + Send> {
+        match self {
+            Self::WrongType(msg) | Self::ActorRefGone(msg) => msg,
+        }
+    }
+}
+
+impl Debug for AnyTellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongType(_) => f.pad("WrongType(..)"),
+            Self::ActorRefGone(_) => f.pad("ActorRefGone(..)"),
+        }
+    }
+}
+
+impl Display for AnyTellError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::WrongType(_) => f.pad("The recipient does not receive messages of this type"),
+            Self::ActorRefGone(_) => f.pad("The recipient ActorRef is no longer available"),
+        }
+    }
+}
+
+impl StdError for AnyTellError {}
+
 impl<T> Debug for Error<T> {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
@@ -0,0 +1,114 @@
+use std::{collections::VecDeque, time::Duration};
+use tokio::time::Instant;
+
+const MAX_SAMPLES: usize = 200;
+
+/// Creates a detector for every monitored system.
+#[cfg(feature = "remote")]
+pub(crate) type NewDetector = std::sync::Arc<dyn Fn() -> Box<dyn FailureDetector> + Send + Sync>;
+
+/// Decides whether a monitored system is alive from the heartbeats that arrive from it.
+///
+/// Remoting and the cluster use it to watch other nodes, actors can use it for their own
+/// heartbeats, e.g. from external systems. The default is the [`PhiAccrualFailureDetector`].
+pub trait FailureDetector: Send {
+    /// Record a heartbeat that arrived at `now`.
+    fn heartbeat(&mut self, now: Instant);
+
+    /// Whether the monitored system is considered alive at `now`.
+    ///
+    /// Before the first heartbeat, the system is considered alive.
+    fn is_available(&self, now: Instant) -> bool;
+}
+
+/// A phi accrual failure detector, as described by Hayashibara et al.
+///
+/// Instead of a binary verdict, the detector reports how suspicious the silence since the
+/// last heartbeat is, based on the distribution of the intervals between previous heartbeats.
+/// A phi of 1 means a 10% chance that the node is still alive, 2 means 1%, 3 means 0.1%, and so on.
+#[derive(Clone, Debug)]
+pub struct PhiAccrualFailureDetector {
+    intervals: VecDeque<f64>,
+    min_std_dev: f64,
+    acceptable_pause: f64,
+    threshold: f64,
+    last_heartbeat: Option<Instant>,
+}
+
+impl PhiAccrualFailureDetector {
+    /// `first_heartbeat_estimate` seeds the interval distribution before any heartbeats have arrived,
+    /// `acceptable_pause` is added to the expected interval to tolerate occasional hiccups.
+    pub fn new(first_heartbeat_estimate: Duration, acceptable_pause: Duration) -> Self {
+        let estimate = first_heartbeat_estimate.as_secs_f64() * 1000.0;
+        let std_dev = estimate / 4.0;
+        Self {
+            intervals: VecDeque::from([estimate - std_dev, estimate + std_dev]),
+            min_std_dev: (estimate / 10.0).max(1.0),
+            acceptable_pause: acceptable_pause.as_secs_f64() * 1000.0,
+            threshold: 8.0,
+            last_heartbeat: None,
+        }
+    }
+
+    /// The phi above which [`FailureDetector::is_available`] considers the system
+    /// unavailable, defaults to 8.
+    pub fn threshold(mut self, threshold: f64) -> Self {
+        self.threshold = threshold;
+        self
+    }
+
+    /// The suspicion level at `now`, 0 if no heartbeat has arrived yet.
+    pub fn phi(&self, now: Instant) -> f64 {
+        let Some(last) = self.last_heartbeat else {
+            return 0.0;
+        };
+        let elapsed = now.duration_since(last).as_secs_f64() * 1000.0;
+        let samples = self.intervals.len() as f64;
+        let mean = self.intervals.iter().sum::<f64>() / samples;
+        let variance = self
+            .intervals
+            .iter()
+            .map(|interval| (interval - mean).powi(2))
+            .sum::<f64>()
+            / samples;
+        let std_dev = variance.sqrt().max(self.min_std_dev);
+        phi(elapsed, mean + self.acceptable_pause, std_dev)
+    }
+
+    /// Record a heartbeat that arrived at `now`.
+    pub fn heartbeat(&mut self, now: Instant) {
+        if let Some(last) = self.last_heartbeat.replace(now) {
+            if self.intervals.len() == MAX_SAMPLES {
+                let _ = self.intervals.pop_front();
+            }
+            self.intervals
+                .push_back(now.duration_since(last).as_secs_f64() * 1000.0);
+        }
+    }
+
+    /// Whether the phi at `now` is below `threshold`.
+    pub fn is_available(&self, now: Instant, threshold: f64) -> bool {
+        self.phi(now) < threshold
+    }
+}
+
+impl FailureDetector for PhiAccrualFailureDetector {
+    fn heartbeat(&mut self, now: Instant) {
+        PhiAccrualFailureDetector::heartbeat(self, now);
+    }
+
+    fn is_available(&self, now: Instant) -> bool {
+        PhiAccrualFailureDetector::is_available(self, now, self.threshold)
+    }
+}
+
+/// `-log10(1 - F(elapsed))` for a normal distribution, using a logistic approximation of the CDF.
+fn phi(elapsed: f64, mean: f64, std_dev: f64) -> f64 {
+    let y = (elapsed - mean) / std_dev;
+    let e = (-y * (1.5976 + 0.070566 * y * y)).exp();
+    if elapsed > mean {
+        -(e / (1.0 + e)).log10()
+    } else {
+        -(1.0 - 1.0 / (1.0 + e)).log10()
+    }
+}
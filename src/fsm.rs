@@ -0,0 +1,168 @@
+use crate::{ActorContext, Behavior, Behaviors, Error};
+use std::{
+    future::{ready, Ready},
+    sync::Mutex,
+};
+
+/// What an [`Fsm`] does after one of its state handlers has handled a message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Transition<S> {
+    /// Move to the given state, running the exit hook of the current state
+    /// and the enter hook of the new state, even when both are the same.
+    To(S),
+    /// Remain in the current state without running any hooks.
+    Stay,
+    /// Run the exit hook of the current state and stop the actor.
+    Stop,
+}
+
+type Handler<S, T> = Box<dyn Fn(T) -> Transition<S> + Send + Sync>;
+type Hook = Box<dyn Fn() + Send + Sync>;
+
+/// A behavior that is a finite state machine.
+///
+/// Each state has its own handler that decides on the [`Transition`] for the message.
+/// Messages that arrive in a state without a handler are unhandled.
+/// The enter hook of the initial state is not run.
+///
+/// ```rust
+/// use elliot::{ActorSystem, Fsm, Transition};
+///
+/// #[derive(Copy, Clone, Debug, PartialEq)]
+/// enum Door {
+///     Open,
+///     Closed,
+/// }
+///
+/// # async fn example() {
+/// let system = ActorSystem::new();
+/// let door = Fsm::new(Door::Closed)
+///     .when(Door::Closed, |msg: &str| match msg {
+///         "open" => Transition::To(Door::Open),
+///         _ => Transition::Stay,
+///     })
+///     .when(Door::Open, |msg: &str| match msg {
+///         "close" => Transition::To(Door::Closed),
+///         _ => Transition::Stay,
+///     })
+///     .on_enter(Door::Open, || println!("the door is open"));
+///
+/// let door = system.spawn("door", door);
+/// # }
+/// ```
+pub struct Fsm<S, T> {
+    current: Mutex<S>,
+    states: Vec<State<S, T>>,
+}
+
+struct State<S, T> {
+    state: S,
+    handler: Option<Handler<S, T>>,
+    on_enter: Option<Hook>,
+    on_exit: Option<Hook>,
+}
+
+impl<S: PartialEq, T> Fsm<S, T> {
+    pub fn new(initial: S) -> Self {
+        Self {
+            current: Mutex::new(initial),
+            states: Vec::new(),
+        }
+    }
+
+    /// Handle messages with `handler` while in `state`, replacing any previous handler.
+    pub fn when<F>(mut self, state: S, handler: F) -> Self
+    where
+        F: Fn(T) -> Transition<S> + Send + Sync + 'static,
+    {
+        self.state_mut(state).handler = Some(Box::new(handler));
+        self
+    }
+
+    /// Run `hook` whenever the machine transitions into `state`.
+    pub fn on_enter<F>(mut self, state: S, hook: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.state_mut(state).on_enter = Some(Box::new(hook));
+        self
+    }
+
+    /// Run `hook` whenever the machine transitions out of `state`.
+    pub fn on_exit<F>(mut self, state: S, hook: F) -> Self
+    where
+        F: Fn() + Send + Sync + 'static,
+    {
+        self.state_mut(state).on_exit = Some(Box::new(hook));
+        self
+    }
+
+    fn state_mut(&mut self, state: S) -> &mut State<S, T> {
+        let index = match self.states.iter().position(|s| s.state == state) {
+            Some(index) => index,
+            None => {
+                self.states.push(State {
+                    state,
+                    handler: None,
+                    on_enter: None,
+                    on_exit: None,
+                });
+                self.states.len() - 1
+            }
+        };
+        &mut self.states[index]
+    }
+
+    fn state(&self, state: &S) -> Option<&State<S, T>> {
+        self.states.iter().find(|s| &s.state == state)
+    }
+
+    fn exit(&self, state: &S) {
+        if let Some(on_exit) = self.state(state).and_then(|s| s.on_exit.as_ref()) {
+            on_exit();
+        }
+    }
+
+    fn enter(&self, state: &S) {
+        if let Some(on_enter) = self.state(state).and_then(|s| s.on_enter.as_ref()) {
+            on_enter();
+        }
+    }
+}
+
+impl<S, T> Behavior<T> for Fsm<S, T>
+where
+    S: PartialEq + Send + Sync + 'static,
+    T: Send + 'static,
+{
+    type F = Ready<Result<Behaviors, Error<T>>>;
+
+    fn receive(&self, _context: &ActorContext<T>, msg: T) -> Self::F {
+        let mut current = self.current.lock().unwrap();
+        let Some(handler) = self.state(&current).and_then(|s| s.handler.as_ref()) else {
+            return ready(Ok(Behaviors::Unhandled));
+        };
+        let handled = match handler(msg) {
+            Transition::Stay => Behaviors::Same,
+            Transition::To(next) => {
+                self.exit(&current);
+                *current = next;
+                self.enter(&current);
+                Behaviors::Same
+            }
+            Transition::Stop => {
+                self.exit(&current);
+                Behaviors::Stopped
+            }
+        };
+        ready(Ok(handled))
+    }
+}
+
+impl<S: std::fmt::Debug, T> std::fmt::Debug for Fsm<S, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Fsm")
+            .field("current", &self.current)
+            .finish_non_exhaustive()
+    }
+}
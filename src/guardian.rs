@@ -0,0 +1,105 @@
+use crate::{
+    cell::ActorCell,
+    mailbox::{self, Next},
+    system::SystemInner,
+    ActorPath, ActorRef,
+};
+use std::{
+    collections::HashMap,
+    convert::Infallible,
+    sync::{Arc, Mutex, Weak},
+};
+
+/// The messages of a guardian, the parent of the top-level actors below `/user` or `/system`.
+pub(crate) enum Guardian {
+    /// Stop all actors below the guardian and then the guardian.
+    Terminate,
+}
+
+/// The internal actors of the system, spawned with the first user actor and again with the first one
+/// after a terminate.
+#[derive(Debug, Default)]
+pub(crate) struct Internals {
+    pub(crate) scheduler: Mutex<Option<ActorRef<Infallible>>>,
+    pub(crate) bus: Mutex<Option<ActorRef<Infallible>>>,
+    pub(crate) dead_letters: Mutex<Option<ActorRef<Infallible>>>,
+}
+
+/// A guardian and the actors below it, which it stops once it is terminated.
+#[derive(Debug)]
+pub(crate) struct Guarded {
+    pub(crate) guardian: ActorRef<Guardian>,
+    /// The live actors below the guardian, by the id of their cell.
+    below: Arc<Mutex<HashMap<u64, Weak<ActorCell>>>>,
+}
+
+impl Guarded {
+    pub(crate) fn adopt(&self, cell: &Arc<ActorCell>) {
+        let _ = self
+            .below
+            .lock()
+            .unwrap()
+            .insert(cell.id, Arc::downgrade(cell));
+    }
+
+    pub(crate) fn release(&self, cell: &ActorCell) {
+        let _ = self.below.lock().unwrap().remove(&cell.id);
+    }
+}
+
+/// Spawn the guardian at `path`, `/user` or `/system`.
+///
+/// The guardian is not listed among the actors of the system and does not keep it alive.
+/// It receives the failures of its children as [`Signal::ChildFailed`](crate::Signal::ChildFailed),
+/// which have already been handled by their supervision.
+pub(crate) fn guardian(system: &SystemInner, path: ActorPath) -> Guarded {
+    let cell = ActorCell::unregistered(path);
+    let (this, mut mailbox) = mailbox::channel(Arc::new(cell));
+    let below = Arc::new(Mutex::new(HashMap::<u64, Weak<ActorCell>>::new()));
+    let actors = Arc::clone(&below);
+    system.spawn_actor(None, &this.cell, async move {
+        let Some(Guardian::Terminate) = mailbox.recv().await else {
+            return;
+        };
+        let actors = std::mem::take(&mut *actors.lock().unwrap())
+            .into_values()
+            .filter_map(|actor| actor.upgrade())
+            .collect::<Vec<_>>();
+        for actor in &actors {
+            actor.request_stop();
+        }
+        for actor in &actors {
+            actor.wait_for_stop().await;
+        }
+    });
+    Guarded {
+        guardian: this,
+        below,
+    }
+}
+
+/// Spawn an internal actor of the system below `/system` that owns a component of the system,
+/// which is shut down with `stop` once the actor is stopped.
+///
+/// The actor has no messages, it runs until it is stopped or the system is gone.
+pub(crate) fn internal(
+    system: &Arc<SystemInner>,
+    name: &str,
+    stop: fn(&SystemInner),
+) -> ActorRef<Infallible> {
+    let cell = system.register(ActorPath::system(name));
+    let (this, mut mailbox) = mailbox::channel(Arc::clone(&cell));
+    let weak = Arc::downgrade(system);
+    system.spawn_actor(None, &this.cell, async move {
+        while let Next::Message(_) | Next::Signal(_) | Next::Replace(_) = mailbox
+            .recv_next(|_| Box::pin(std::future::pending()))
+            .await
+        {}
+        drop(mailbox);
+        if let Some(system) = weak.upgrade() {
+            stop(&system);
+            system.deregister(&cell);
+        }
+    });
+    this
+}
@@ -0,0 +1,176 @@
+use crate::{
+    behavior::{FromContext, IntoResult},
+    intercept::Handled,
+    ActorContext, AnyMessage, Behavior, Behaviors,
+};
+use std::{
+    any::{type_name, TypeId},
+    future::Future,
+    marker::PhantomData,
+};
+
+/// The handler of one message type of [`Behaviors::handlers`].
+///
+/// Functions `Fn(M)` that return a future of a behavior output are handlers of `M`.
+/// Like behaviors, they can take the ref or the context of the actor before the message.
+pub trait Handler<Args>: Send + Sync + 'static {
+    /// The type of the messages that this handler handles.
+    type Msg: Send + 'static;
+
+    fn handle(&self, context: &ActorContext<AnyMessage>, msg: Self::Msg) -> Handled<AnyMessage>;
+}
+
+/// A tuple of [`Handler`]s, see [`Behaviors::handlers`].
+pub trait HandlerSet<Args>: Send + Sync + 'static {
+    /// Handle `msg` with the first handler of its type, the message is returned if there is none.
+    fn dispatch(
+        &self,
+        context: &ActorContext<AnyMessage>,
+        msg: AnyMessage,
+    ) -> Result<Handled<AnyMessage>, AnyMessage>;
+
+    /// The message types of the handlers, in their order.
+    fn message_types(&self) -> Vec<(TypeId, &'static str)>;
+}
+
+/// A behavior that hands every message to the handler of its type, created with [`Behaviors::handlers`].
+pub struct Handlers<S, Args> {
+    handlers: S,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl Behaviors {
+    /// A behavior for several unrelated message types, each with its own handler.
+    ///
+    /// The actor receives [`AnyMessage`]s and passes every message to the handler of its type,
+    /// messages of other types are unhandled.
+    ///
+    /// This trades the checks of the compiler for the convenience of a single ref: the ref accepts
+    /// messages of any type, so a message without a handler is only noticed as a dead letter when the
+    /// actor receives it, and every message has to be boxed. Prefer an enum of the messages, where
+    /// all types are known up front.
+    ///
+    /// # Panics
+    ///
+    /// If two handlers take messages of the same type, since the second one would never be called.
+    ///
+    /// ```rust
+    /// use elliot::{ActorSystem, AnyMessage, Behaviors};
+    ///
+    /// struct Greet(String);
+    /// struct Leave(String);
+    ///
+    /// # async fn example() {
+    /// let system = ActorSystem::new();
+    /// let greeter = system.spawn(
+    ///     "greeter",
+    ///     Behaviors::handlers((
+    ///         |Greet(name)| async move { println!("Hello, {name}") },
+    ///         |Leave(name)| async move { println!("Bye, {name}") },
+    ///     )),
+    /// );
+    /// let _ = greeter.tell(Box::new(Greet(String::from("Alice"))));
+    /// # }
+    /// ```
+    pub fn handlers<S, Args>(handlers: S) -> Handlers<S, Args>
+    where
+        S: HandlerSet<Args>,
+    {
+        let mut types = handlers.message_types();
+        types.sort_by_key(|(id, _)| *id);
+        if let Some(twice) = types.windows(2).find(|types| types[0].0 == types[1].0) {
+            panic!("more than one handler for messages of type {}", twice[0].1);
+        }
+        Handlers {
+            handlers,
+            _args: PhantomData,
+        }
+    }
+}
+
+impl<S, Args> Behavior<AnyMessage> for Handlers<S, Args>
+where
+    S: HandlerSet<Args>,
+    Args: 'static,
+{
+    type F = Handled<AnyMessage>;
+
+    fn receive(&self, context: &ActorContext<AnyMessage>, msg: AnyMessage) -> Self::F {
+        self.handlers
+            .dispatch(context, msg)
+            .unwrap_or_else(|_| Box::pin(async { Ok(Behaviors::Unhandled) }))
+    }
+}
+
+macro_rules! impl_handler {
+    ( $($ty:ident),* ) => {
+        impl<F, Fut, Res, M, $($ty,)*> Handler<($($ty,)* M,)> for F
+        where
+            F: Fn($($ty,)* M) -> Fut + Send + Sync + 'static,
+            Fut: Future<Output = Res> + Send + 'static,
+            Res: IntoResult<AnyMessage>,
+            M: Send + 'static,
+            $( $ty: FromContext<AnyMessage> + Send,)*
+        {
+            type Msg = M;
+
+            fn handle(&self, _context: &ActorContext<AnyMessage>, msg: M) -> Handled<AnyMessage> {
+                let handled = self($($ty::from_context(_context),)* msg);
+                Box::pin(async move { handled.await.into_result() })
+            }
+        }
+    };
+}
+
+impl_handler!();
+impl_handler!(T1);
+impl_handler!(T1, T2);
+
+macro_rules! impl_handler_set {
+    ( $($handler:ident $args:ident),+ ) => {
+        impl<$($handler, $args,)+> HandlerSet<($($args,)+)> for ($($handler,)+)
+        where
+            $( $handler: Handler<$args>, )+
+        {
+            #[allow(non_snake_case)]
+            fn dispatch(
+                &self,
+                context: &ActorContext<AnyMessage>,
+                msg: AnyMessage,
+            ) -> Result<Handled<AnyMessage>, AnyMessage> {
+                let ($($handler,)+) = self;
+                $(
+                    let msg = match msg.downcast::<<$handler as Handler<$args>>::Msg>() {
+                        Ok(msg) => return Ok($handler.handle(context, *msg)),
+                        Err(msg) => msg,
+                    };
+                )+
+                Err(msg)
+            }
+
+            fn message_types(&self) -> Vec<(TypeId, &'static str)> {
+                vec![$(
+                    (
+                        TypeId::of::<<$handler as Handler<$args>>::Msg>(),
+                        type_name::<<$handler as Handler<$args>>::Msg>(),
+                    ),
+                )+]
+            }
+        }
+    };
+}
+
+impl_handler_set!(H1 A1);
+impl_handler_set!(H1 A1, H2 A2);
+impl_handler_set!(H1 A1, H2 A2, H3 A3);
+impl_handler_set!(H1 A1, H2 A2, H3 A3, H4 A4);
+impl_handler_set!(H1 A1, H2 A2, H3 A3, H4 A4, H5 A5);
+impl_handler_set!(H1 A1, H2 A2, H3 A3, H4 A4, H5 A5, H6 A6);
+impl_handler_set!(H1 A1, H2 A2, H3 A3, H4 A4, H5 A5, H6 A6, H7 A7);
+impl_handler_set!(H1 A1, H2 A2, H3 A3, H4 A4, H5 A5, H6 A6, H7 A7, H8 A8);
+
+impl<S, Args> std::fmt::Debug for Handlers<S, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Handlers").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,91 @@
+//! A minimal JSON inspector for an [`ActorSystem`].
+//!
+//! Use [`to_json`] to mount the snapshot into an existing HTTP server,
+//! or [`serve`] to run a tiny standalone HTTP server.
+
+use crate::{ActorInfo, ActorState, ActorSystem};
+use std::{fmt::Write as _, io};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+/// Render a snapshot of all live actors of the system as JSON.
+///
+/// ```json
+/// {"actors":[{"path":"/user/ping","state":"running","mailbox_len":0,"uptime_ms":42,"dead_letters":0}],"dead_letters":0}
+/// ```
+pub fn to_json(system: &ActorSystem) -> String {
+    let actors = system.actors();
+    let dead_letters = actors.iter().map(|a| a.dead_letters).sum::<usize>();
+
+    let mut json = String::from(r#"{"actors":["#);
+    for (i, actor) in actors.iter().enumerate() {
+        if i > 0 {
+            json.push(',');
+        }
+        write_actor(&mut json, actor);
+    }
+    let _ = write!(json, r#"],"dead_letters":{dead_letters}}}"#);
+    json
+}
+
+/// Serve the inspector JSON on `GET /` until an error occurs while accepting connections.
+pub async fn serve(system: ActorSystem, addr: impl ToSocketAddrs) -> io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let system = system.clone();
+        let _handle = tokio::spawn(async move { respond(&system, stream).await });
+    }
+}
+
+async fn respond(system: &ActorSystem, mut stream: TcpStream) -> io::Result<()> {
+    let mut buf = [0; 1024];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+
+    let (status, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/")) => ("200 OK", to_json(system)),
+        _ => ("404 Not Found", String::from(r#"{"error":"not found"}"#)),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\ncontent-type: application/json\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn write_actor(json: &mut String, actor: &ActorInfo) {
+    json.push_str(r#"{"path":"#);
+    write_str(json, actor.path.as_str());
+    let state = match actor.state {
+        ActorState::Running => "running",
+        ActorState::Stopping => "stopping",
+    };
+    let _ = write!(
+        json,
+        r#","state":"{state}","mailbox_len":{},"uptime_ms":{},"dead_letters":{}}}"#,
+        actor.mailbox_len,
+        actor.uptime.as_millis(),
+        actor.dead_letters,
+    );
+}
+
+fn write_str(json: &mut String, s: &str) {
+    json.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => json.push_str(r#"\""#),
+            '\\' => json.push_str(r"\\"),
+            c if c.is_control() => {
+                let _ = write!(json, "\\u{:04x}", c as u32);
+            }
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+}
@@ -0,0 +1,463 @@
+//! Interceptors that run around every message of a behavior.
+//!
+//! An [`Intercepted`] behavior passes every message through its interceptors before the wrapped
+//! behavior handles it. Every interceptor decides whether and how to call the [`Next`] one,
+//! so that cross-cutting concerns like auditing live outside of the handlers.
+//!
+//! ```rust
+//! use elliot::{
+//!     intercept::{self, Intercepted, Next},
+//!     ActorContext, ActorSystem,
+//! };
+//!
+//! # async fn example() {
+//! let system = ActorSystem::new();
+//! let greeter = Intercepted::new(|name: String| async move { println!("Hello, {name}") })
+//!     .with(intercept::logging(|line| println!("{line}")))
+//!     .with(intercept::filter(|name: &String| name.is_empty() == false))
+//!     .with(|ctx: &ActorContext<String>, name: String, next: Next<String>| {
+//!         println!("{} is about to greet", ctx.name());
+//!         next.run(name)
+//!     });
+//! let greeter = system.spawn("greeter", greeter);
+//! # }
+//! ```
+
+use crate::{
+    runtime::Instant, ActorContext, ActorPath, Behavior, Behaviors, Error, HandlerTimeout,
+};
+use std::{
+    collections::{HashSet, VecDeque},
+    future::Future,
+    hash::Hash,
+    marker::PhantomData,
+    pin::Pin,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// The outcome of handling a message, as returned by [`Next::run`].
+pub type Handled<T> = Pin<Box<dyn Future<Output = Result<Behaviors, Error<T>>> + Send>>;
+
+/// Runs around every message of an [`Intercepted`] behavior.
+///
+/// Closures `Fn(&ActorContext<T>, T, Next<T>)` that return a future of the [`Handled`] output are interceptors.
+pub trait Interceptor<T>: Send + Sync + 'static {
+    /// Handle `msg`, usually by running `next` with it.
+    fn intercept(&self, context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T>;
+}
+
+impl<T, F, Fut> Interceptor<T> for F
+where
+    F: Fn(&ActorContext<T>, T, Next<T>) -> Fut + Send + Sync + 'static,
+    Fut: Future<Output = Result<Behaviors, Error<T>>> + Send + 'static,
+{
+    fn intercept(&self, context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T> {
+        Box::pin(self(context, msg, next))
+    }
+}
+
+type Receive<T> = Arc<dyn Fn(&ActorContext<T>, T) -> Handled<T> + Send + Sync>;
+
+struct Chain<T> {
+    interceptors: Vec<Arc<dyn Interceptor<T>>>,
+    behavior: Receive<T>,
+}
+
+impl<T> Clone for Chain<T> {
+    fn clone(&self) -> Self {
+        Self {
+            interceptors: self.interceptors.clone(),
+            behavior: Arc::clone(&self.behavior),
+        }
+    }
+}
+
+/// A behavior that runs its interceptors around every message of the wrapped behavior.
+///
+/// The interceptors run in the order in which they were added, the first one sees the message first.
+pub struct Intercepted<T> {
+    chain: Arc<Chain<T>>,
+}
+
+impl<T: Send + 'static> Intercepted<T> {
+    pub fn new<B, Args>(behavior: B) -> Self
+    where
+        B: Behavior<T, Args>,
+        Args: 'static,
+    {
+        let behavior: Receive<T> =
+            Arc::new(move |context, msg| Box::pin(behavior.receive(context, msg)));
+        Self {
+            chain: Arc::new(Chain {
+                interceptors: Vec::new(),
+                behavior,
+            }),
+        }
+    }
+
+    /// Run `interceptor` around every message, inside of the interceptors that were added before.
+    pub fn with<I: Interceptor<T>>(mut self, interceptor: I) -> Self {
+        // messages that are handled right now keep the chain that they started with
+        Arc::make_mut(&mut self.chain)
+            .interceptors
+            .push(Arc::new(interceptor));
+        self
+    }
+}
+
+impl<T: Send + 'static> Behavior<T> for Intercepted<T> {
+    type F = Handled<T>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        Next {
+            chain: Arc::clone(&self.chain),
+            index: 0,
+            context: context.clone(),
+        }
+        .run(msg)
+    }
+}
+
+/// The rest of the interceptors together with the wrapped behavior.
+pub struct Next<T> {
+    chain: Arc<Chain<T>>,
+    index: usize,
+    context: ActorContext<T>,
+}
+
+impl<T: 'static> Next<T> {
+    /// Pass `msg` on to the next interceptor, or to the wrapped behavior after the last one.
+    pub fn run(self, msg: T) -> Handled<T> {
+        match self.chain.interceptors.get(self.index) {
+            Some(interceptor) => {
+                let next = Self {
+                    chain: Arc::clone(&self.chain),
+                    index: self.index + 1,
+                    context: self.context.clone(),
+                };
+                interceptor.intercept(&self.context, msg, next)
+            }
+            None => (self.chain.behavior)(&self.context, msg),
+        }
+    }
+}
+
+/// Passes every message and how it was handled to a sink, see [`logging`].
+#[derive(Clone)]
+pub struct Logging {
+    sink: Arc<dyn Fn(String) + Send + Sync>,
+}
+
+/// Pass a line with every message and how it was handled to `sink`, e.g. to `log::info!`.
+pub fn logging<S>(sink: S) -> Logging
+where
+    S: Fn(String) + Send + Sync + 'static,
+{
+    Logging {
+        sink: Arc::new(sink),
+    }
+}
+
+impl<T: std::fmt::Debug + Send + 'static> Interceptor<T> for Logging {
+    fn intercept(&self, context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T> {
+        let path = context.path().clone();
+        let received = format!("{msg:?}");
+        let sink = Arc::clone(&self.sink);
+        Box::pin(async move {
+            let handled = next.run(msg).await;
+            match &handled {
+                Ok(behavior) => sink(format!("{path}: {received} -> {behavior:?}")),
+                Err(e) => sink(format!("{path}: {received} -> {e}")),
+            }
+            handled
+        })
+    }
+}
+
+type TimingSink = Arc<dyn Fn(&ActorPath, Duration) + Send + Sync>;
+
+/// Passes how long every message took to handle to a sink, see [`timing`].
+#[derive(Clone)]
+pub struct Timing {
+    sink: TimingSink,
+}
+
+/// Pass how long every message took to handle to `sink`, e.g. to record it in a histogram.
+///
+/// The time includes the interceptors that were added after this one.
+pub fn timing<S>(sink: S) -> Timing
+where
+    S: Fn(&ActorPath, Duration) + Send + Sync + 'static,
+{
+    Timing {
+        sink: Arc::new(sink),
+    }
+}
+
+impl<T: Send + 'static> Interceptor<T> for Timing {
+    fn intercept(&self, context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T> {
+        let path = context.path().clone();
+        let sink = Arc::clone(&self.sink);
+        Box::pin(async move {
+            let start = Instant::now();
+            let handled = next.run(msg).await;
+            sink(&path, start.elapsed());
+            handled
+        })
+    }
+}
+
+/// Only passes on the messages that match a predicate, see [`filter`].
+pub struct Filter<P, T> {
+    predicate: P,
+    _msg: PhantomData<fn(&T)>,
+}
+
+/// Only pass on the messages that match `predicate`, other messages are unhandled.
+pub fn filter<T, P>(predicate: P) -> Filter<P, T>
+where
+    P: Fn(&T) -> bool + Send + Sync + 'static,
+{
+    Filter {
+        predicate,
+        _msg: PhantomData,
+    }
+}
+
+impl<T, P> Interceptor<T> for Filter<P, T>
+where
+    T: Send + 'static,
+    P: Fn(&T) -> bool + Send + Sync + 'static,
+{
+    fn intercept(&self, _context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T> {
+        if (self.predicate)(&msg) {
+            next.run(msg)
+        } else {
+            Box::pin(async { Ok(Behaviors::Unhandled) })
+        }
+    }
+}
+
+/// Limits how long a message may take to handle, see [`with_timeout`].
+pub struct WithTimeout<T> {
+    timeout: Duration,
+    on_timeout: OnTimeout<T>,
+}
+
+enum OnTimeout<T> {
+    Crash,
+    Unhandled,
+    Signal(Arc<dyn Fn() -> T + Send + Sync>),
+}
+
+/// Stop handling a message that takes longer than `timeout`.
+///
+/// By default, the actor crashes with a [`HandlerTimeout`]. The handling of the message is cancelled in any case,
+/// including the interceptors that were added after this one.
+pub fn with_timeout<T>(timeout: Duration) -> WithTimeout<T> {
+    WithTimeout {
+        timeout,
+        on_timeout: OnTimeout::Crash,
+    }
+}
+
+impl<T> WithTimeout<T> {
+    /// Treat the message as unhandled and continue with the next one.
+    pub fn unhandled(mut self) -> Self {
+        self.on_timeout = OnTimeout::Unhandled;
+        self
+    }
+
+    /// Tell the actor the message returned by `signal` and continue with the next message.
+    pub fn signal<F>(mut self, signal: F) -> Self
+    where
+        F: Fn() -> T + Send + Sync + 'static,
+    {
+        self.on_timeout = OnTimeout::Signal(Arc::new(signal));
+        self
+    }
+}
+
+impl<T: Send + 'static> Interceptor<T> for WithTimeout<T> {
+    fn intercept(&self, context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T> {
+        let timeout = self.timeout;
+        let on_timeout = match &self.on_timeout {
+            OnTimeout::Crash => OnTimeout::Crash,
+            OnTimeout::Unhandled => OnTimeout::Unhandled,
+            OnTimeout::Signal(signal) => OnTimeout::Signal(Arc::clone(signal)),
+        };
+        let this = context.this();
+        Box::pin(async move {
+            let Ok(handled) = tokio::time::timeout(timeout, next.run(msg)).await else {
+                return match on_timeout {
+                    OnTimeout::Crash => Err(Error::Crashed(Box::new(HandlerTimeout(timeout)))),
+                    OnTimeout::Unhandled => Ok(Behaviors::Unhandled),
+                    OnTimeout::Signal(signal) => {
+                        let _ = this.tell(signal());
+                        Ok(Behaviors::Same)
+                    }
+                };
+            };
+            handled
+        })
+    }
+}
+
+/// Drops the messages whose idempotency key was seen recently, see [`dedup`].
+pub struct Dedup<T, K, F> {
+    window: Duration,
+    key: F,
+    on_duplicate: Option<Box<dyn Fn(T) + Send + Sync>>,
+    seen: Arc<Mutex<Seen<K>>>,
+}
+
+struct Seen<K> {
+    keys: HashSet<K>,
+    order: VecDeque<(Instant, K)>,
+}
+
+/// Drop every message whose idempotency key, as returned by `key`, was already passed on within `window`.
+///
+/// Messages without a key are always passed on. A key is remembered from the first message that carries it,
+/// duplicates do not extend its window. Dropped duplicates count as handled. A message that fails to be
+/// handled forgets its key again, so that it can be retried.
+pub fn dedup<T, K, F>(window: Duration, key: F) -> Dedup<T, K, F>
+where
+    K: Hash + Eq + Clone,
+    F: Fn(&T) -> Option<K> + Send + Sync + 'static,
+{
+    Dedup {
+        window,
+        key,
+        on_duplicate: None,
+        seen: Arc::new(Mutex::new(Seen {
+            keys: HashSet::new(),
+            order: VecDeque::new(),
+        })),
+    }
+}
+
+impl<T, K, F> Dedup<T, K, F> {
+    /// Pass every duplicate to `on_duplicate` instead of dropping it, e.g. to reply to its asker again.
+    pub fn on_duplicate<D>(mut self, on_duplicate: D) -> Self
+    where
+        D: Fn(T) + Send + Sync + 'static,
+    {
+        self.on_duplicate = Some(Box::new(on_duplicate));
+        self
+    }
+}
+
+impl<K: Hash + Eq + Clone> Seen<K> {
+    /// Remember `key` and return whether it was new.
+    fn insert(&mut self, key: K, window: Duration) -> bool {
+        let now = Instant::now();
+        while let Some((at, _)) = self.order.front() {
+            if now.duration_since(*at) < window {
+                break;
+            }
+            if let Some((_, expired)) = self.order.pop_front() {
+                let _ = self.keys.remove(&expired);
+            }
+        }
+        if self.keys.insert(key.clone()) == false {
+            return false;
+        }
+        self.order.push_back((now, key));
+        true
+    }
+
+    /// Forget `key`, so that the next message with it is passed on again.
+    fn remove(&mut self, key: &K) {
+        if self.keys.remove(key) {
+            self.order.retain(|(_, seen)| seen != key);
+        }
+    }
+}
+
+impl<T, K, F> Interceptor<T> for Dedup<T, K, F>
+where
+    T: Send + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    F: Fn(&T) -> Option<K> + Send + Sync + 'static,
+{
+    fn intercept(&self, _context: &ActorContext<T>, msg: T, next: Next<T>) -> Handled<T> {
+        let Some(key) = (self.key)(&msg) else {
+            return next.run(msg);
+        };
+        if self.seen.lock().unwrap().insert(key.clone(), self.window) {
+            let seen = Arc::clone(&self.seen);
+            let handled = next.run(msg);
+            return Box::pin(async move {
+                let handled = handled.await;
+                if handled.is_err() {
+                    seen.lock().unwrap().remove(&key);
+                }
+                handled
+            });
+        }
+        if let Some(on_duplicate) = &self.on_duplicate {
+            on_duplicate(msg);
+        }
+        Box::pin(async { Ok(Behaviors::Same) })
+    }
+}
+
+impl<T> std::fmt::Debug for Intercepted<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Intercepted")
+            .field("interceptors", &self.chain.interceptors.len())
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> std::fmt::Debug for Next<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Next")
+            .field("path", self.context.path())
+            .field("remaining", &(self.chain.interceptors.len() - self.index))
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> std::fmt::Debug for WithTimeout<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let on_timeout = match self.on_timeout {
+            OnTimeout::Crash => "crash",
+            OnTimeout::Unhandled => "unhandled",
+            OnTimeout::Signal(_) => "signal",
+        };
+        f.debug_struct("WithTimeout")
+            .field("timeout", &self.timeout)
+            .field("on_timeout", &on_timeout)
+            .finish()
+    }
+}
+
+impl std::fmt::Debug for Logging {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Logging").finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for Timing {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Timing").finish_non_exhaustive()
+    }
+}
+
+impl<P, T> std::fmt::Debug for Filter<P, T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Filter").finish_non_exhaustive()
+    }
+}
+
+impl<T, K, F> std::fmt::Debug for Dedup<T, K, F> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Dedup")
+            .field("window", &self.window)
+            .field("on_duplicate", &self.on_duplicate.is_some())
+            .finish_non_exhaustive()
+    }
+}
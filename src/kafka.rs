@@ -0,0 +1,333 @@
+use crate::{behavior, cell::ActorCell, ActorPath, ActorRef, ActorSystem, BoxErr, SnapshotStore};
+use rskafka::{
+    chrono::{DateTime, Utc},
+    client::partition::{Compression, OffsetAt, PartitionClient},
+    record::Record,
+};
+use std::{
+    collections::BTreeMap,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// How long to wait before a failed fetch or produce is tried again.
+const RETRY_AFTER: Duration = Duration::from_secs(1);
+
+/// A record that a [`KafkaConsumer`] delivers.
+///
+/// The offset of the record is committed once it is [acked](Ack::ack).
+#[derive(Debug)]
+pub struct KafkaRecord {
+    pub offset: i64,
+    pub key: Option<Vec<u8>>,
+    pub value: Option<Vec<u8>>,
+    pub headers: BTreeMap<String, Vec<u8>>,
+    pub timestamp: SystemTime,
+    pub ack: Ack,
+}
+
+/// Marks a [`KafkaRecord`] as processed.
+///
+/// Dropping the ack without acking makes the consumer deliver the record again.
+#[derive(Debug)]
+pub struct Ack(oneshot::Sender<()>);
+
+impl Ack {
+    pub fn ack(self) {
+        let _ = self.0.send(());
+    }
+}
+
+/// Delivers the records of one Kafka partition to an actor.
+///
+/// Records are delivered in order and the consumer commits the offset after the last record that
+/// has been acked to a [`SnapshotStore`], so that a restarted consumer continues from there.
+/// Records are delivered at least once, a record that is not acked is delivered again together with
+/// the records that followed it.
+pub struct KafkaConsumer {
+    client: PartitionClient,
+    offsets: Arc<dyn SnapshotStore<i64>>,
+    start_at: OffsetAt,
+    max_bytes: i32,
+    max_wait: Duration,
+}
+
+impl KafkaConsumer {
+    pub fn new<S: SnapshotStore<i64>>(client: PartitionClient, offsets: S) -> Self {
+        Self {
+            client,
+            offsets: Arc::new(offsets),
+            start_at: OffsetAt::Earliest,
+            max_bytes: 1024 * 1024,
+            max_wait: Duration::from_millis(500),
+        }
+    }
+
+    /// Where to start when no offset has been committed yet, defaults to [`OffsetAt::Earliest`].
+    pub fn start_at(mut self, start_at: OffsetAt) -> Self {
+        self.start_at = start_at;
+        self
+    }
+
+    /// How many bytes of records to fetch at once, defaults to 1 MiB.
+    pub fn max_bytes(mut self, max_bytes: i32) -> Self {
+        self.max_bytes = max_bytes.max(1);
+        self
+    }
+
+    /// How long the broker waits for new records before a fetch returns empty, defaults to 500ms.
+    pub fn max_wait(mut self, max_wait: Duration) -> Self {
+        self.max_wait = max_wait;
+        self
+    }
+
+    /// Start delivering records to `target`.
+    ///
+    /// The consumer stops when the target stops and keeps it alive until then.
+    /// Failed fetches are tried again.
+    pub fn spawn(self, target: &ActorRef<KafkaRecord>) {
+        let target = target.clone();
+        let _handle = tokio::spawn(async move {
+            tokio::select! {
+                () = self.consume(&target) => {}
+                () = target.wait_for_stop() => {}
+            }
+        });
+    }
+
+    fn persistence_id(&self) -> String {
+        format!("kafka-{}-{}", self.client.topic(), self.client.partition())
+    }
+
+    async fn consume(&self, target: &ActorRef<KafkaRecord>) {
+        let id = self.persistence_id();
+        let mut offset = loop {
+            match self.committed(&id).await {
+                Ok(offset) => break offset,
+                Err(_) => tokio::time::sleep(RETRY_AFTER).await,
+            }
+        };
+        let max_wait = i32::try_from(self.max_wait.as_millis()).unwrap_or(i32::MAX);
+
+        loop {
+            let records = match self
+                .client
+                .fetch_records(offset, 1..self.max_bytes, max_wait)
+                .await
+            {
+                Ok((records, _high_watermark)) => records,
+                Err(_) => {
+                    tokio::time::sleep(RETRY_AFTER).await;
+                    continue;
+                }
+            };
+
+            let mut acks = Vec::with_capacity(records.len());
+            for record in records {
+                let (tx, rx) = oneshot::channel();
+                let record_offset = record.offset;
+                let record = KafkaRecord {
+                    offset: record.offset,
+                    key: record.record.key,
+                    value: record.record.value,
+                    headers: record.record.headers,
+                    timestamp: system_time(record.record.timestamp),
+                    ack: Ack(tx),
+                };
+                if target.tell(record).is_err() {
+                    return;
+                }
+                acks.push((record_offset, rx));
+            }
+
+            for (record_offset, ack) in acks {
+                if ack.await.is_err() {
+                    // not acked, fetch again from this record
+                    break;
+                }
+                offset = record_offset + 1;
+                while self.offsets.save(&id, offset as u64, &offset).is_err() {
+                    tokio::time::sleep(RETRY_AFTER).await;
+                }
+            }
+        }
+    }
+
+    async fn committed(&self, id: &str) -> Result<i64, BoxErr> {
+        match self.offsets.load(id)? {
+            Some((_, offset)) => Ok(offset),
+            None => Ok(self.client.get_offset(self.start_at).await?),
+        }
+    }
+}
+
+/// A message for a [`KafkaProducer`].
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct KafkaMessage {
+    pub key: Option<Vec<u8>>,
+    pub value: Option<Vec<u8>>,
+    pub headers: BTreeMap<String, Vec<u8>>,
+}
+
+impl KafkaMessage {
+    pub fn new(value: impl Into<Vec<u8>>) -> Self {
+        Self {
+            value: Some(value.into()),
+            ..Self::default()
+        }
+    }
+
+    pub fn key(mut self, key: impl Into<Vec<u8>>) -> Self {
+        self.key = Some(key.into());
+        self
+    }
+
+    pub fn header(mut self, name: impl Into<String>, value: impl Into<Vec<u8>>) -> Self {
+        let _ = self.headers.insert(name.into(), value.into());
+        self
+    }
+}
+
+/// An actor that produces the messages told to it to one Kafka partition, in batches.
+///
+/// A batch is produced once it is full, or once the first message in it has waited for the linger time.
+/// Batches that fail to be produced are tried again, so messages are produced in the order they were told.
+/// The messages of a batch that still fails after the last retry are dead letters of the producer.
+pub struct KafkaProducer {
+    client: PartitionClient,
+    max_batch: usize,
+    linger: Duration,
+    compression: Compression,
+    max_retries: u32,
+}
+
+impl KafkaProducer {
+    pub fn new(client: PartitionClient) -> Self {
+        Self {
+            client,
+            max_batch: 100,
+            linger: Duration::from_millis(5),
+            compression: Compression::NoCompression,
+            max_retries: 10,
+        }
+    }
+
+    /// How many messages to produce at most in one batch, defaults to 100.
+    pub fn max_batch(mut self, max_batch: usize) -> Self {
+        self.max_batch = max_batch.max(1);
+        self
+    }
+
+    /// How long to wait for more messages before a batch that is not full is produced, defaults to 5ms.
+    pub fn linger(mut self, linger: Duration) -> Self {
+        self.linger = linger;
+        self
+    }
+
+    pub fn compression(mut self, compression: Compression) -> Self {
+        self.compression = compression;
+        self
+    }
+
+    /// How often a batch that fails to be produced is tried again, defaults to 10.
+    pub fn max_retries(mut self, max_retries: u32) -> Self {
+        self.max_retries = max_retries;
+        self
+    }
+
+    /// Spawn the producer as `name`.
+    ///
+    /// The messages that are waiting are still produced after the producer has stopped.
+    pub fn spawn<N: Into<Arc<str>>>(self, system: &ActorSystem, name: N) -> ActorRef<KafkaMessage> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let producer = behavior::actor_of(
+            system,
+            ActorPath::user(&name.into()),
+            move |msg: KafkaMessage| {
+                let _ = tx.send(msg);
+                async {}
+            },
+        );
+        let _handle = tokio::spawn(self.produce(rx, Arc::clone(&producer.cell)));
+        producer
+    }
+
+    async fn produce(self, mut rx: mpsc::UnboundedReceiver<KafkaMessage>, cell: Arc<ActorCell>) {
+        let mut batch = Vec::with_capacity(self.max_batch);
+        while let Some(first) = rx.recv().await {
+            batch.push(record(first));
+            let deadline = tokio::time::Instant::now() + self.linger;
+            while batch.len() < self.max_batch {
+                match tokio::time::timeout_at(deadline, rx.recv()).await {
+                    Ok(Some(msg)) => batch.push(record(msg)),
+                    Ok(None) | Err(_) => break,
+                }
+            }
+            let mut retries = 0;
+            while self
+                .client
+                .produce(batch.clone(), self.compression)
+                .await
+                .is_err()
+            {
+                if retries == self.max_retries {
+                    // the messages after the batch are still produced
+                    for record in batch.drain(..) {
+                        cell.dead_letter_with(KafkaMessage {
+                            key: record.key,
+                            value: record.value,
+                            headers: record.headers,
+                        });
+                    }
+                    break;
+                }
+                retries += 1;
+                tokio::time::sleep(RETRY_AFTER).await;
+            }
+            batch.clear();
+        }
+    }
+}
+
+fn record(msg: KafkaMessage) -> Record {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |d| i64::try_from(d.as_millis()).unwrap_or(i64::MAX));
+    Record {
+        key: msg.key,
+        value: msg.value,
+        headers: msg.headers,
+        timestamp: DateTime::from_timestamp_millis(now).unwrap_or_default(),
+    }
+}
+
+fn system_time(timestamp: DateTime<Utc>) -> SystemTime {
+    let millis = timestamp.timestamp_millis();
+    match u64::try_from(millis) {
+        Ok(millis) => UNIX_EPOCH + Duration::from_millis(millis),
+        Err(_) => UNIX_EPOCH - Duration::from_millis(millis.unsigned_abs()),
+    }
+}
+
+impl std::fmt::Debug for KafkaConsumer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaConsumer")
+            .field("topic", &self.client.topic())
+            .field("partition", &self.client.partition())
+            .field("start_at", &self.start_at)
+            .finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for KafkaProducer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("KafkaProducer")
+            .field("topic", &self.client.topic())
+            .field("partition", &self.client.partition())
+            .field("max_batch", &self.max_batch)
+            .field("linger", &self.linger)
+            .field("max_retries", &self.max_retries)
+            .finish_non_exhaustive()
+    }
+}
@@ -86,7 +86,6 @@ fn main() {
 
 #![warn(
     bad_style,
-    const_err,
     dead_code,
     explicit_outlives_requirements,
     improper_ctypes,
@@ -103,7 +102,6 @@ fn main() {
     overflowing_literals,
     path_statements,
     patterns_in_fns_without_body,
-    private_in_public,
     rust_2018_idioms,
     trivial_casts,
     trivial_numeric_casts,
@@ -131,31 +129,171 @@ fn main() {
     clippy::redundant_pub_crate
 )]
 
+// dev-dependencies of the integration tests and benchmarks
+#[cfg(test)]
+use criterion as _;
+#[cfg(all(test, unix))]
+use libc as _;
+
 mod actor;
+mod adapter;
+mod any_ref;
+mod ask;
 mod behavior;
+mod bus;
+mod cell;
+mod child;
+mod circuit_breaker;
+#[cfg(feature = "cluster")]
+mod cluster;
+mod codec;
+mod compose;
+#[cfg(feature = "config")]
+mod config;
+mod conflate;
+#[cfg(feature = "console")]
+mod console;
+mod crash_dump;
+mod entity;
+mod envelope;
 mod error;
+mod failure_detector;
+mod fsm;
+mod guardian;
+mod handlers;
+#[cfg(feature = "inspector")]
+pub mod inspector;
+pub mod intercept;
+#[cfg(feature = "kafka")]
+mod kafka;
+mod lifecycle;
+#[cfg(feature = "log")]
+mod logging;
+mod mailbox;
+#[cfg(feature = "mqtt")]
+mod mqtt;
+#[cfg(feature = "nats")]
+mod nats;
+#[cfg(feature = "net")]
+pub mod net;
+mod path;
+mod persistence;
+mod process_manager;
+#[cfg(feature = "prometheus")]
+pub mod prometheus;
+mod receptionist;
+#[cfg(feature = "redis")]
+mod redis;
+mod reliable;
+#[cfg(feature = "remote")]
+mod remote;
+mod replace;
+mod router;
+mod runtime;
+mod schedule;
+mod scope;
+mod selection;
+mod sequence;
+#[cfg(feature = "tower")]
+mod service;
+mod signal;
+#[cfg(feature = "stream")]
+mod stream;
+mod system;
+mod testkit;
+mod throttle;
+mod topic;
+mod work_pulling;
 
 pub use actor::*;
+pub use any_ref::{AnyActorRef, AnyMessage};
+pub use ask::{ask_all, ask_any, AskPool, ReplyTo};
 pub use behavior::{Behavior, Behaviors};
+pub use cell::{ActorInfo, ActorState};
+pub use child::ChildOptions;
+pub use circuit_breaker::{CircuitBreaker, CircuitBreakerTransition, CircuitState};
+#[cfg(feature = "cluster")]
+pub use cluster::{
+    Cluster, ClusterEvent, ClusterReceptionist, ClusterRouter, ClusterSettings, Consistency,
+    Counter, Crdt, CrdtCodec, DistributedTopic, Downing, LwwMap, Member, MemberStatus, OrSet,
+    Replicate, Sharding,
+};
+#[cfg(feature = "bincode")]
+pub use codec::BincodeCodec;
+#[cfg(feature = "json")]
+pub use codec::JsonCodec;
+#[cfg(feature = "prost")]
+pub use codec::ProstCodec;
+pub use codec::{MessageCodec, VersionedCodec};
+pub use compose::{OrElse, ReceiveIf, ReceiveMatch, Redeliver};
+#[cfg(feature = "config")]
+pub use config::{ActorConfig, ActorSystemConfig, RemoteConfig};
+pub use crash_dump::DebugMessages;
+pub use entity::EntityManager;
+pub use envelope::Meta;
 pub use error::*;
-
-#[allow(missing_copy_implementations)]
-#[derive(Debug)]
-pub struct ActorSystem {
-    // TODO: dead letters, system bus, actor paths,
-}
-
-impl ActorSystem {
-    #[allow(clippy::new_without_default)]
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    pub fn spawn<T: Send + 'static, N, A, Args>(&self, name: N, behavior: A) -> ActorRef<T>
-    where
-        N: Into<std::sync::Arc<str>>,
-        A: Behavior<T, Args>,
-    {
-        behavior::actor_of(name, behavior)
-    }
-}
+pub use failure_detector::{FailureDetector, PhiAccrualFailureDetector};
+pub use fsm::{Fsm, Transition};
+pub use handlers::{Handler, HandlerSet, Handlers};
+#[cfg(feature = "kafka")]
+pub use kafka::{Ack, KafkaConsumer, KafkaMessage, KafkaProducer, KafkaRecord};
+pub use lifecycle::*;
+#[cfg(feature = "log")]
+pub use logging::Log;
+#[cfg(feature = "mqtt")]
+pub use mqtt::{MqttBridge, MqttMessage};
+#[cfg(feature = "nats")]
+pub use nats::Nats;
+pub use path::ActorPath;
+#[cfg(feature = "file-journal")]
+pub use persistence::FileJournal;
+#[cfg(feature = "postgres")]
+pub use persistence::PostgresJournal;
+#[cfg(feature = "sqlite")]
+pub use persistence::SqliteJournal;
+pub use persistence::{
+    CodecJournal, CodecSnapshotStore, EventEnvelope, EventSink, EventSourced, EventsByTag,
+    InMemoryJournal, InMemoryOffsetStore, InMemorySnapshotStore, Journal, OffsetStore, Projection,
+    ReadJournal, SnapshotStore, TaggedJournal,
+};
+#[cfg(feature = "redis")]
+pub use persistence::{RedisJournal, RedisSnapshotStore};
+pub use process_manager::{Compensation, ProcessManager, ProcessStep};
+pub use receptionist::{Listing, ListingUpdate, Receptionist, ServiceKey};
+#[cfg(feature = "redis")]
+pub use redis::RedisBridge;
+pub use reliable::{Delivery, Outbox, OutboxSnapshot, ReliableRef};
+#[cfg(feature = "grpc")]
+pub use remote::GrpcTransport;
+#[cfg(feature = "quic")]
+pub use remote::QuicTransport;
+#[cfg(feature = "tls")]
+pub use remote::TlsTransport;
+#[cfg(feature = "remote")]
+pub use remote::{
+    Accepted, Authenticate, Connection, Credentials, Eviction, FailureDetection, Listener,
+    RemoteBuffer, TcpTransport, Transport,
+};
+pub use router::{Pool, Resizer, Strategy};
+pub use schedule::{
+    Cron, Reminder, Reminders, Schedule, ScheduleHandle, SchedulePersistence, ScheduledTell,
+    Scheduler,
+};
+pub use scope::ActorScope;
+pub use selection::ActorSelection;
+pub use sequence::{Sequenced, SequencedRef};
+#[cfg(feature = "tower")]
+pub use service::ActorService;
+pub use signal::{OnSignal, Signal};
+#[cfg(feature = "stream")]
+pub use stream::{ActorOutput, ActorStream, ReplyStream, StreamReplyTo};
+pub use system::{
+    ActorSystem, ActorSystemBuilder, DuplicateNames, Lifetime, SpawnOptions, Supervision,
+};
+pub use testkit::{BehaviorTestKit, Effect, TestInbox};
+pub use throttle::Rate;
+/// The TLS configs of the [`TlsTransport`] are built with this version of rustls.
+#[cfg(feature = "tls")]
+pub use tokio_rustls::rustls;
+pub use topic::Topic;
+pub use work_pulling::Work;
@@ -0,0 +1,204 @@
+use crate::{crash_dump::CrashDump, ActorPath, Error};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::{broadcast, mpsc};
+
+/// Events about actors starting and stopping, published on the [`ActorSystem`](crate::ActorSystem).
+#[derive(Clone, Debug)]
+pub enum LifecycleEvent {
+    Started(ActorStarted),
+    Stopped(ActorStopped),
+    Crashed(ActorCrashed),
+}
+
+#[derive(Clone, Debug)]
+pub struct ActorStarted {
+    pub path: ActorPath,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActorStopped {
+    pub path: ActorPath,
+    pub reason: StopReason,
+}
+
+#[derive(Clone, Debug)]
+pub struct ActorCrashed {
+    pub path: ActorPath,
+    /// The rendered error that crashed the actor.
+    pub reason: Arc<str>,
+    /// The last messages of the actor, oldest first, the last one crashed the actor.
+    ///
+    /// Empty unless enabled with [`ActorSystemBuilder::crash_dump`](crate::ActorSystemBuilder::crash_dump).
+    pub recent_messages: Arc<[Arc<str>]>,
+}
+
+/// A message that an actor did not handle, published on the [`ActorSystem`](crate::ActorSystem).
+///
+/// The dead letters of [`ActorSystem::dead_letters`](crate::ActorSystem::dead_letters) only tell
+/// the recipient, those of [`ActorSystem::dead_letters_of`](crate::ActorSystem::dead_letters_of)
+/// carry the message back.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct DeadLetter<T = ()> {
+    pub recipient: ActorPath,
+    pub message: T,
+}
+
+/// Sent to watchers once a watched actor has stopped, see [`ActorSystem::watch`](crate::ActorSystem::watch).
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Terminated {
+    pub path: ActorPath,
+    /// The actor is in another system that can no longer be reached, it might still be alive.
+    pub unreachable: bool,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum StopReason {
+    /// All actor refs have been dropped.
+    NoActorRef,
+    /// The actor returned `Behaviors::Stopped`.
+    Stopped,
+}
+
+impl LifecycleEvent {
+    pub fn path(&self) -> &ActorPath {
+        match self {
+            Self::Started(e) => &e.path,
+            Self::Stopped(e) => &e.path,
+            Self::Crashed(e) => &e.path,
+        }
+    }
+
+    pub(crate) fn terminated<T>(path: ActorPath, error: &Error<T>, crash_dump: &CrashDump) -> Self {
+        match error {
+            Error::NoActorRef(_) => Self::Stopped(ActorStopped {
+                path,
+                reason: StopReason::NoActorRef,
+            }),
+            Error::Stopped(_) => Self::Stopped(ActorStopped {
+                path,
+                reason: StopReason::Stopped,
+            }),
+            Error::Unhandled(_) | Error::Crashed(_) => Self::Crashed(ActorCrashed {
+                path,
+                reason: error.to_string().into(),
+                recent_messages: crash_dump.recent(),
+            }),
+        }
+    }
+}
+
+/// A subscription to the [`LifecycleEvent`]s of an [`ActorSystem`](crate::ActorSystem).
+///
+/// Only events that happen after subscribing are received.
+/// A subscriber that does not keep up will miss the oldest events.
+#[derive(Debug)]
+pub struct LifecycleEvents {
+    pub(crate) rx: broadcast::Receiver<LifecycleEvent>,
+}
+
+impl LifecycleEvents {
+    /// Receive the next event, or `None` if the system has been dropped.
+    pub async fn recv(&mut self) -> Option<LifecycleEvent> {
+        loop {
+            match self.rx.recv().await {
+                Ok(event) => return Some(event),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
+
+/// A subscription to the [`DeadLetter`]s with messages of type `T`,
+/// created with [`ActorSystem::dead_letters_of`](crate::ActorSystem::dead_letters_of).
+#[derive(Debug)]
+pub struct DeadLettersOf<T> {
+    rx: mpsc::UnboundedReceiver<DeadLetter<T>>,
+}
+
+impl<T> DeadLettersOf<T> {
+    /// Receive the next dead letter, or `None` if the system has been dropped or terminated.
+    pub async fn recv(&mut self) -> Option<DeadLetter<T>> {
+        self.rx.recv().await
+    }
+}
+
+/// The subscribers of [`DeadLettersOf`], keyed by the type of the messages.
+#[derive(Debug, Default)]
+pub(crate) struct DeadLetterValues {
+    subscribers: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+}
+
+type Subscribers<T> = Vec<mpsc::UnboundedSender<DeadLetter<T>>>;
+
+impl DeadLetterValues {
+    pub(crate) fn subscribe<T: Send + 'static>(&self) -> DeadLettersOf<T> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribers
+            .lock()
+            .unwrap()
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Subscribers::<T>::new()))
+            .downcast_mut::<Subscribers<T>>()
+            .expect("dead letter subscribers are keyed by their type")
+            .push(tx);
+        DeadLettersOf { rx }
+    }
+
+    /// End all subscriptions.
+    pub(crate) fn close(&self) {
+        let subscribers = std::mem::take(&mut *self.subscribers.lock().unwrap());
+        drop(subscribers);
+    }
+
+    /// Hand the message to the earliest subscriber that is still subscribed, if any.
+    pub(crate) fn offer<T: Send + 'static>(&self, recipient: &ActorPath, message: T) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let Some(subscribers) = subscribers
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|s| s.downcast_mut::<Subscribers<T>>())
+        else {
+            return;
+        };
+        let mut dead_letter = DeadLetter {
+            recipient: recipient.clone(),
+            message,
+        };
+        while let Some(subscriber) = subscribers.first() {
+            match subscriber.send(dead_letter) {
+                Ok(()) => return,
+                Err(e) => {
+                    dead_letter = e.0;
+                    let _ = subscribers.remove(0);
+                }
+            }
+        }
+    }
+}
+
+/// A subscription to the [`DeadLetter`]s of an [`ActorSystem`](crate::ActorSystem).
+///
+/// Only dead letters that happen after subscribing are received.
+/// A subscriber that does not keep up will miss the oldest dead letters.
+#[derive(Debug)]
+pub struct DeadLetters {
+    pub(crate) rx: broadcast::Receiver<DeadLetter>,
+}
+
+impl DeadLetters {
+    /// Receive the next dead letter, or `None` if the system has been dropped.
+    pub async fn recv(&mut self) -> Option<DeadLetter> {
+        loop {
+            match self.rx.recv().await {
+                Ok(dead_letter) => return Some(dead_letter),
+                Err(broadcast::error::RecvError::Lagged(_)) => continue,
+                Err(broadcast::error::RecvError::Closed) => return None,
+            }
+        }
+    }
+}
@@ -0,0 +1,62 @@
+use crate::{behavior::FromContext, ActorContext, ActorPath};
+use log::Level;
+use std::fmt::Display;
+
+/// A logger for handlers that tags every record with the actor path and the message type.
+///
+/// Records go through the [`log`] facade with the target `elliot::actor` and the keys
+/// `actor` and `message_type`, `tracing` subscribers receive them with `tracing-log`.
+///
+/// ```no_run
+/// # use elliot::{ActorSystem, Log};
+/// # let system = ActorSystem::new();
+/// let greeter = system.spawn("greeter", |log: Log, name: String| async move {
+///     log.info(format_args!("hello {name}"));
+/// });
+/// ```
+#[derive(Clone, Debug)]
+pub struct Log {
+    path: ActorPath,
+    message_type: &'static str,
+}
+
+impl Log {
+    pub fn error(&self, msg: impl Display) {
+        self.log(Level::Error, msg);
+    }
+
+    pub fn warn(&self, msg: impl Display) {
+        self.log(Level::Warn, msg);
+    }
+
+    pub fn info(&self, msg: impl Display) {
+        self.log(Level::Info, msg);
+    }
+
+    pub fn debug(&self, msg: impl Display) {
+        self.log(Level::Debug, msg);
+    }
+
+    pub fn trace(&self, msg: impl Display) {
+        self.log(Level::Trace, msg);
+    }
+
+    pub fn log(&self, level: Level, msg: impl Display) {
+        log::log!(
+            target: "elliot::actor",
+            level,
+            actor = self.path.as_str(),
+            message_type = self.message_type;
+            "{msg}"
+        );
+    }
+}
+
+impl<T> FromContext<T> for Log {
+    fn from_context(context: &ActorContext<T>) -> Self {
+        Self {
+            path: context.path().clone(),
+            message_type: std::any::type_name::<T>(),
+        }
+    }
+}
@@ -0,0 +1,405 @@
+use crate::{
+    adapter::Sender, cell::ActorCell, envelope::Envelope, replace::Replacement, runtime::Task,
+    ActorCrashed, ActorRef, Signal,
+};
+use std::{
+    any::Any,
+    future::poll_fn,
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, OnceLock,
+    },
+    task::{Context, Poll, Waker},
+    time::Duration,
+};
+use tokio::sync::{mpsc, oneshot};
+
+/// Where the messages of a drained mailbox are sent, see [`ActorRef::drain`].
+pub(crate) type Drained<T> = oneshot::Sender<Vec<Envelope<T>>>;
+
+/// Measures the approximate bytes of the messages of an actor, shared by all of its refs.
+pub(crate) type Sizer<T> = Arc<OnceLock<Box<dyn Fn(&T) -> usize + Send + Sync>>>;
+
+/// The lanes of a sharded mailbox, shared by all refs of the actor, `None` for a single lane.
+pub(crate) type Lanes<T> = Option<Arc<LaneSenders<T>>>;
+
+/// Create the mailbox of an actor together with its first ref.
+pub(crate) fn channel<T: Send + 'static>(cell: Arc<ActorCell>) -> (ActorRef<T>, Mailbox<T>) {
+    sharded(cell, 1)
+}
+
+/// Like [`channel`], but the mailbox has `lanes` senders, see [`SpawnOptions::lanes`](crate::SpawnOptions::lanes).
+pub(crate) fn sharded<T: Send + 'static>(
+    cell: Arc<ActorCell>,
+    lanes: usize,
+) -> (ActorRef<T>, Mailbox<T>) {
+    let (senders, receivers) = (0..lanes.max(1))
+        .map(|_| mpsc::unbounded_channel())
+        .unzip::<_, _, Vec<_>, Vec<_>>();
+    let tx = senders[0].clone();
+    let lanes = (senders.len() > 1).then(|| {
+        Arc::new(LaneSenders {
+            senders: senders.into_boxed_slice(),
+            next: AtomicUsize::new(1),
+        })
+    });
+    let (signal, signals) = mpsc::unbounded_channel();
+    cell.set_signals(signal);
+    cell.referenced();
+    let this = ActorRef {
+        tx: Sender::Lane(tx.clone()),
+        cell: Arc::clone(&cell),
+        sizer: Sizer::default(),
+        lanes,
+        reserved: false,
+    };
+    (
+        this,
+        Mailbox {
+            rx: Inbox {
+                lanes: receivers.into_boxed_slice(),
+                next: 0,
+            },
+            signals,
+            this: Some(tx),
+            cell,
+            handling: false,
+            undelivered: ActorCell::dead_letter_with::<T>,
+            drained: |drained, envelopes| {
+                if let Ok(drained) = drained.downcast::<Drained<T>>() {
+                    let _ = drained.send(envelopes);
+                }
+            },
+            replacement: |replacement| replacement.downcast().ok().map(|r| *r),
+        },
+    )
+}
+
+/// The senders of a sharded mailbox, new refs are spread over them.
+pub(crate) struct LaneSenders<T> {
+    senders: Box<[mpsc::UnboundedSender<Envelope<T>>]>,
+    next: AtomicUsize,
+}
+
+impl<T> LaneSenders<T> {
+    /// The sender for a new ref, the lanes take turns.
+    pub(crate) fn next(&self) -> mpsc::UnboundedSender<Envelope<T>> {
+        let lane = self.next.fetch_add(1, Ordering::Relaxed) % self.senders.len();
+        self.senders[lane].clone()
+    }
+
+    /// The first lane, which the mailbox keeps open while the actor is referenced, so that weak refs
+    /// are upgraded through it.
+    pub(crate) fn first(&self) -> &mpsc::UnboundedSender<Envelope<T>> {
+        &self.senders[0]
+    }
+}
+
+/// The receiving end of an actor, keeps the [`ActorCell`] statistics up to date.
+#[derive(Debug)]
+pub(crate) struct Mailbox<T> {
+    rx: Inbox<T>,
+    signals: mpsc::UnboundedReceiver<SystemMessage>,
+    /// Keeps the mailbox open after the last [`ActorRef`] is gone, until the waiting messages are
+    /// handled, so that the actor can still reach itself while it handles them.
+    this: Option<mpsc::UnboundedSender<Envelope<T>>>,
+    cell: Arc<ActorCell>,
+    /// Whether the last received message is being handled, until the next message is received.
+    handling: bool,
+    /// What happens to the messages that are left in the mailbox when it is dropped.
+    undelivered: fn(&ActorCell, T),
+    /// Sends the messages of a drained mailbox to a [`Drained`], which the system messages carry untyped.
+    drained: fn(Box<dyn Any + Send>, Vec<Envelope<T>>),
+    /// Restores the type of a [`Replacement`] from the system messages.
+    replacement: fn(Box<dyn Any + Send>) -> Option<Replacement<T>>,
+}
+
+/// The next message of `rx` if one is ready, without waiting.
+///
+/// Unlike `try_recv`, this does not block the thread while a sender is in the middle of sending.
+/// The waker of `rx` is replaced, so it must be polled again before the actor waits.
+fn ready<M>(rx: &mut mpsc::UnboundedReceiver<M>) -> Option<M> {
+    match rx.poll_recv(&mut Context::from_waker(Waker::noop())) {
+        Poll::Ready(msg) => msg,
+        Poll::Pending => None,
+    }
+}
+
+/// The receiving ends of the lanes of a mailbox, there is only one unless the actor is sharded.
+#[derive(Debug)]
+struct Inbox<T> {
+    lanes: Box<[mpsc::UnboundedReceiver<Envelope<T>>]>,
+    /// The lane that is asked first for the next message, so that a busy lane does not starve the others.
+    next: usize,
+}
+
+impl<T> Inbox<T> {
+    async fn recv(&mut self) -> Option<Envelope<T>> {
+        match &mut *self.lanes {
+            [lane] => lane.recv().await,
+            _ => poll_fn(|cx| self.poll_recv(cx)).await,
+        }
+    }
+
+    /// `None` once all lanes are closed and empty.
+    fn poll_recv(&mut self, cx: &mut Context<'_>) -> Poll<Option<Envelope<T>>> {
+        let len = self.lanes.len();
+        let mut closed = 0;
+        for i in 0..len {
+            let lane = (self.next + i) % len;
+            match self.lanes[lane].poll_recv(cx) {
+                Poll::Ready(Some(envelope)) => {
+                    self.next = (lane + 1) % len;
+                    return Poll::Ready(Some(envelope));
+                }
+                Poll::Ready(None) => closed += 1,
+                Poll::Pending => {}
+            }
+        }
+        if closed == len {
+            Poll::Ready(None)
+        } else {
+            Poll::Pending
+        }
+    }
+
+    /// The next message if one is ready, see [`ready`].
+    fn ready(&mut self) -> Option<Envelope<T>> {
+        match self.poll_recv(&mut Context::from_waker(Waker::noop())) {
+            Poll::Ready(envelope) => envelope,
+            Poll::Pending => None,
+        }
+    }
+
+    fn try_recv(&mut self) -> Result<Envelope<T>, mpsc::error::TryRecvError> {
+        let len = self.lanes.len();
+        let mut closed = 0;
+        for i in 0..len {
+            let lane = (self.next + i) % len;
+            match self.lanes[lane].try_recv() {
+                Ok(envelope) => {
+                    self.next = (lane + 1) % len;
+                    return Ok(envelope);
+                }
+                Err(mpsc::error::TryRecvError::Disconnected) => closed += 1,
+                Err(mpsc::error::TryRecvError::Empty) => {}
+            }
+        }
+        if closed == len {
+            Err(mpsc::error::TryRecvError::Disconnected)
+        } else {
+            Err(mpsc::error::TryRecvError::Empty)
+        }
+    }
+
+    fn close(&mut self) {
+        for lane in &mut *self.lanes {
+            lane.close();
+        }
+    }
+}
+
+impl<T> Mailbox<T> {
+    /// The next message if one is waiting, for mailboxes that no actor receives from.
+    pub(crate) fn try_recv(&mut self) -> Option<T> {
+        let envelope = self.rx.ready()?;
+        self.cell.dequeued(envelope.size);
+        Some(envelope.msg)
+    }
+
+    pub(crate) async fn recv(&mut self) -> Option<T> {
+        self.recv_envelope().await.map(|envelope| envelope.msg)
+    }
+
+    pub(crate) async fn recv_envelope(&mut self) -> Option<Envelope<T>> {
+        if std::mem::take(&mut self.handling) {
+            self.cell.done();
+        }
+        let msg = loop {
+            self.close_if_unreferenced();
+            match self.rx.try_recv() {
+                Ok(msg) => break Some(msg),
+                Err(mpsc::error::TryRecvError::Disconnected) => break None,
+                Err(_) => {}
+            }
+            // only the end of the refs matters without a behavior
+            tokio::select! {
+                biased;
+                Some(_) = self.signals.recv() => {}
+                msg = self.rx.recv() => break msg,
+            }
+        };
+        if let Some(envelope) = &msg {
+            // busy before the mailbox is empty, so that the actor never appears idle in between
+            self.cell.busy();
+            self.handling = true;
+            self.cell.dequeued(envelope.size);
+        }
+        msg
+    }
+
+    /// Receive the next system message or message, system messages first,
+    /// so that a flooded mailbox does not delay a stop request.
+    /// Messages that have expired are dead letters.
+    ///
+    /// The receive timeout of the cell is slept with `sleep`.
+    pub(crate) async fn recv_next(&mut self, sleep: impl FnOnce(Duration) -> Task) -> Next<T> {
+        if std::mem::take(&mut self.handling) {
+            self.cell.done();
+        }
+        // waiting messages are taken without setting up the receive timeout and the wakers
+        while self.cell.mailbox_len() > 0 {
+            self.close_if_unreferenced();
+            let next = if let Some(msg) = ready(&mut self.signals) {
+                match self.system_message(msg) {
+                    Some(next) => next,
+                    None => continue,
+                }
+            } else if let Some(envelope) = self.rx.ready() {
+                Next::Message(envelope)
+            } else {
+                break;
+            };
+            if let Some(next) = self.accept(next) {
+                return next;
+            }
+        }
+        let timeout = self.cell.receive_timeout();
+        let timeout = async {
+            match timeout {
+                Some(timeout) => sleep(timeout).await,
+                None => std::future::pending().await,
+            }
+        };
+        tokio::pin!(timeout);
+        loop {
+            self.close_if_unreferenced();
+            let next = tokio::select! {
+                biased;
+                Some(msg) = self.signals.recv() => match self.system_message(msg) {
+                    Some(next) => next,
+                    None => continue,
+                },
+                msg = self.rx.recv() => match msg {
+                    Some(msg) => Next::Message(msg),
+                    None => Next::Closed,
+                },
+                () = &mut timeout => Next::Signal(Signal::ReceiveTimeout),
+            };
+            if let Some(next) = self.accept(next) {
+                return next;
+            }
+        }
+    }
+
+    /// What the actor does next because of `msg`, `None` if there is nothing to do.
+    fn system_message(&mut self, msg: SystemMessage) -> Option<Next<T>> {
+        match msg {
+            SystemMessage::Unreferenced => None,
+            SystemMessage::Signal(signal) => Some(Next::Signal(signal)),
+            SystemMessage::Stop => Some(Next::Stop),
+            SystemMessage::Drain(drained) => {
+                self.drain(drained);
+                Some(Next::Stop)
+            }
+            SystemMessage::Replace(replacement) => {
+                (self.replacement)(replacement).map(Next::Replace)
+            }
+            SystemMessage::Escalate(crashed) => Some(Next::Escalate(crashed)),
+        }
+    }
+
+    /// Hand `next` to the actor, `None` for a stale message, which is a dead letter instead.
+    fn accept(&mut self, next: Next<T>) -> Option<Next<T>> {
+        match next {
+            // stale messages are not handled
+            Next::Message(envelope) if envelope.meta.is_expired() => {
+                self.cell.dequeued(envelope.size);
+                self.dead_letter(envelope.msg);
+                None
+            }
+            Next::Message(ref envelope) => {
+                self.cell.busy();
+                self.handling = true;
+                self.cell.dequeued(envelope.size);
+                Some(next)
+            }
+            next => Some(next),
+        }
+    }
+
+    /// Let the mailbox close once all refs are gone and the waiting messages are handled.
+    fn close_if_unreferenced(&mut self) {
+        if self.this.is_some() && self.cell.is_referenced() == false && self.cell.mailbox_len() == 0
+        {
+            self.this = None;
+        }
+    }
+
+    /// Close the mailbox and send the messages that are waiting to `drained`.
+    fn drain(&mut self, drained: Box<dyn Any + Send>) {
+        self.rx.close();
+        let mut envelopes = Vec::new();
+        while let Ok(envelope) = self.rx.try_recv() {
+            self.cell.dequeued(envelope.size);
+            if envelope.meta.is_expired() {
+                self.dead_letter(envelope.msg);
+            } else {
+                envelopes.push(envelope);
+            }
+        }
+        (self.drained)(drained, envelopes);
+    }
+
+    /// Count `msg` as a dead letter.
+    pub(crate) fn dead_letter(&self, msg: T) {
+        (self.undelivered)(&self.cell, msg);
+    }
+
+    pub(crate) fn cell(&self) -> &ActorCell {
+        &self.cell
+    }
+}
+
+/// What an actor receives next, see [`Mailbox::recv_next`].
+pub(crate) enum Next<T> {
+    Message(Envelope<T>),
+    Signal(Signal),
+    /// The actor is requested to stop, see [`ActorRef::stop`].
+    Stop,
+    /// Continue with another behavior, see [`ActorRef::replace_behavior`].
+    Replace(Replacement<T>),
+    /// Fail with the failure of a child.
+    Escalate(ActorCrashed),
+    /// All refs are gone and the mailbox is empty.
+    Closed,
+}
+
+/// Messages from the library to an actor, they overtake the messages in the mailbox.
+#[derive(Debug)]
+pub(crate) enum SystemMessage {
+    /// The last [`ActorRef`] is gone.
+    Unreferenced,
+    Signal(Signal),
+    Stop,
+    /// Stop and hand the waiting messages to a [`Drained`].
+    Drain(Box<dyn Any + Send>),
+    /// Replace the behavior with a [`Replacement`].
+    Replace(Box<dyn Any + Send>),
+    /// A child has failed too often, see [`ChildOptions::escalate_after`](crate::ChildOptions::escalate_after).
+    Escalate(ActorCrashed),
+}
+
+impl<T> Drop for Mailbox<T> {
+    fn drop(&mut self) {
+        if self.handling {
+            self.cell.done();
+        }
+        self.rx.close();
+        while let Ok(envelope) = self.rx.try_recv() {
+            self.cell.dequeued(envelope.size);
+            self.dead_letter(envelope.msg);
+        }
+        self.cell.stopped();
+    }
+}
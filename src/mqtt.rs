@@ -0,0 +1,166 @@
+use crate::{behavior, ActorPath, ActorRef, ActorSystem};
+use rumqttc::{AsyncClient, Event, MqttOptions, Packet, QoS};
+use std::{sync::Arc, time::Duration};
+
+/// How long to wait before the connection to the broker is tried again.
+const RECONNECT_AFTER: Duration = Duration::from_secs(1);
+
+/// A message that a [`MqttBridge`] publishes or receives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MqttMessage {
+    pub topic: String,
+    pub payload: Vec<u8>,
+    pub retain: bool,
+}
+
+impl MqttMessage {
+    pub fn new(topic: impl Into<String>, payload: impl Into<Vec<u8>>) -> Self {
+        Self {
+            topic: topic.into(),
+            payload: payload.into(),
+            retain: false,
+        }
+    }
+
+    pub fn retain(mut self, retain: bool) -> Self {
+        self.retain = retain;
+        self
+    }
+}
+
+struct Route {
+    filter: String,
+    qos: QoS,
+    target: ActorRef<MqttMessage>,
+}
+
+/// An MQTT client as an actor.
+///
+/// The messages that are published to the subscribed topics are told to the targets of the
+/// subscriptions, and every [`MqttMessage`] that is told to the bridge is published.
+/// The bridge reconnects and subscribes again when the connection to the broker fails.
+///
+/// ```rust,no_run
+/// use elliot::{ActorSystem, MqttBridge, MqttMessage};
+/// use rumqttc::{MqttOptions, QoS};
+///
+/// # async fn example() {
+/// let system = ActorSystem::new();
+/// let thermostat = system.spawn("thermostat", |msg: MqttMessage| async move {
+///     println!("{}: {:?}", msg.topic, msg.payload);
+/// });
+/// let mqtt = MqttBridge::new(MqttOptions::new("elliot", "localhost", 1883))
+///     .subscribe("home/+/temperature", QoS::AtLeastOnce, &thermostat)
+///     .spawn(&system, "mqtt");
+/// mqtt.tell(MqttMessage::new("home/heating", "on")).unwrap();
+/// # }
+/// ```
+pub struct MqttBridge {
+    options: MqttOptions,
+    routes: Vec<Route>,
+    qos: QoS,
+}
+
+impl MqttBridge {
+    pub fn new(options: MqttOptions) -> Self {
+        Self {
+            options,
+            routes: Vec::new(),
+            qos: QoS::AtLeastOnce,
+        }
+    }
+
+    /// Tell the messages of the topics that match `filter` to `target`.
+    ///
+    /// A message that matches several filters is told to all of their targets.
+    /// The bridge keeps the target alive.
+    pub fn subscribe(
+        mut self,
+        filter: impl Into<String>,
+        qos: QoS,
+        target: &ActorRef<MqttMessage>,
+    ) -> Self {
+        self.routes.push(Route {
+            filter: filter.into(),
+            qos,
+            target: target.clone(),
+        });
+        self
+    }
+
+    /// The quality of service for the messages that are published, defaults to [`QoS::AtLeastOnce`].
+    pub fn qos(mut self, qos: QoS) -> Self {
+        self.qos = qos;
+        self
+    }
+
+    /// Connect to the broker and spawn the bridge as `name`.
+    ///
+    /// The connection is closed once the bridge has stopped.
+    pub fn spawn<N: Into<Arc<str>>>(self, system: &ActorSystem, name: N) -> ActorRef<MqttMessage> {
+        let Self {
+            options,
+            routes,
+            qos,
+        } = self;
+        let (client, mut events) = AsyncClient::new(options, 64);
+
+        let publisher = client.clone();
+        let this = behavior::actor_of(
+            system,
+            ActorPath::user(&name.into()),
+            move |msg: MqttMessage| {
+                let client = publisher.clone();
+                // the request queue only fails when the event loop has stopped
+                async move {
+                    client
+                        .publish(msg.topic, qos, msg.retain, msg.payload)
+                        .await
+                }
+            },
+        );
+
+        let weak = this.downgrade();
+        let _handle = tokio::spawn(async move {
+            loop {
+                let event = tokio::select! {
+                    event = events.poll() => event,
+                    () = weak.cell.wait_for_stop() => break,
+                };
+                match event {
+                    // (re)connected, the subscriptions of a clean session are gone
+                    Ok(Event::Incoming(Packet::ConnAck(_))) => {
+                        for route in &routes {
+                            let _ = client.try_subscribe(route.filter.clone(), route.qos);
+                        }
+                    }
+                    Ok(Event::Incoming(Packet::Publish(publish))) => {
+                        let msg = MqttMessage {
+                            topic: publish.topic,
+                            payload: publish.payload.to_vec(),
+                            retain: publish.retain,
+                        };
+                        for route in &routes {
+                            if rumqttc::matches(&msg.topic, &route.filter) {
+                                let _ = route.target.tell(msg.clone());
+                            }
+                        }
+                    }
+                    Ok(_) => {}
+                    Err(_) => tokio::time::sleep(RECONNECT_AFTER).await,
+                }
+            }
+        });
+        this
+    }
+}
+
+impl std::fmt::Debug for MqttBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MqttBridge")
+            .field("client_id", &self.options.client_id())
+            .field("broker", &self.options.broker_address())
+            .field("qos", &self.qos)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,207 @@
+use crate::{
+    behavior, bus::Echoes, cell::ActorCell, mailbox, system::SystemInner, ActorPath, ActorRef,
+    ActorSystem, MessageCodec, NatsError, WeakActorRef,
+};
+use async_nats::{Client, HeaderMap, Subscriber};
+use futures_core::Stream;
+use std::{
+    future::poll_fn,
+    pin::Pin,
+    sync::{Arc, Weak},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// The header with the origin of bus events, so that a bridge ignores its own events.
+const ORIGIN: &str = "Elliot-Origin";
+
+/// Remoting and system bus events over NATS subjects, created with [`ActorSystem::nats`].
+///
+/// Actors are reachable at a subject made from the prefix and their path, with the slashes
+/// replaced by dots, e.g. `/user/greeter` with the prefix `node-a` is at `node-a.user.greeter`.
+/// Like [remoting](ActorSystem::bind), messages are encoded with the registered codec for
+/// their type and messages that cannot be published are lost.
+#[derive(Clone)]
+pub struct Nats {
+    client: Client,
+    prefix: Arc<str>,
+    origin: Arc<str>,
+    system: ActorSystem,
+}
+
+impl ActorSystem {
+    /// Use `client` to reach actors and share bus events with other systems.
+    ///
+    /// `prefix` is the first part of the subjects of the [exposed](Nats::expose) actors of this system.
+    pub fn nats(&self, client: Client, prefix: &str) -> Nats {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        Nats {
+            client,
+            prefix: prefix.trim_end_matches('.').into(),
+            origin: format!("{prefix}-{}-{nanos}", std::process::id()).into(),
+            system: self.clone(),
+        }
+    }
+}
+
+impl Nats {
+    /// The subject of an actor of this system.
+    pub fn subject(&self, path: &ActorPath) -> String {
+        format!("{}{}", self.prefix, path.as_str().replace('/', "."))
+    }
+
+    /// Deliver the messages that are published to the [subject](Nats::subject) of `actor`.
+    ///
+    /// The subscription ends when the actor stops.
+    pub async fn expose<T: Send + 'static>(&self, actor: &ActorRef<T>) -> Result<(), NatsError> {
+        let codec = self.codec::<T>()?;
+        let mut subscriber = self.client.subscribe(self.subject(actor.path())).await?;
+        let actor = actor.downgrade();
+        let _handle = tokio::spawn(async move {
+            loop {
+                let msg = tokio::select! {
+                    msg = next(&mut subscriber) => msg,
+                    () = actor.cell.wait_for_stop() => return,
+                };
+                let Some(msg) = msg else {
+                    return;
+                };
+                let Some(target) = actor.upgrade() else {
+                    return;
+                };
+                let Ok(msg) = codec.decode(&msg.payload) else {
+                    target.cell.dead_letter();
+                    continue;
+                };
+                if target.tell(msg).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok(())
+    }
+
+    /// A ref that publishes every message to `subject`, e.g. the subject of an actor that another system has exposed.
+    pub fn remote_ref<T: Send + 'static>(&self, subject: &str) -> Result<ActorRef<T>, NatsError> {
+        let codec = self.codec::<T>()?;
+        let subject = subject.to_owned();
+        let cell = ActorCell::unregistered(ActorPath::remote(&format!("nats://{subject}")));
+        let (this, mut mailbox) = mailbox::channel(Arc::new(cell));
+        let client = self.client.clone();
+        let _handle = tokio::spawn(async move {
+            while let Some(msg) = mailbox.recv().await {
+                let Ok(payload) = codec.encode(&msg) else {
+                    mailbox.cell().dead_letter();
+                    continue;
+                };
+                if client
+                    .publish(subject.clone(), payload.into())
+                    .await
+                    .is_err()
+                {
+                    mailbox.cell().dead_letter();
+                }
+            }
+        });
+        Ok(this)
+    }
+
+    /// Share the bus events of type `E` with all systems that bridge the same `subject`.
+    ///
+    /// Events that are published on the bus of this system are published to the subject,
+    /// and events from other systems are published on the bus of this system. Events that
+    /// came from the subject are not sent back to it.
+    pub async fn bridge<E: Clone + Send + 'static>(&self, subject: &str) -> Result<(), NatsError> {
+        let codec = self.codec::<E>()?;
+        let subscriber = self.client.subscribe(subject.to_owned()).await?;
+        let incoming = Arc::new(Echoes::default());
+
+        let client = self.client.clone();
+        let origin = Arc::clone(&self.origin);
+        let subject_name = subject.to_owned();
+        let codec_out = Arc::clone(&codec);
+        let echoes = Arc::clone(&incoming);
+        let path = ActorPath::system("nats").child(&subject.replace('.', "-"));
+        let outgoing = behavior::actor_of(&self.system, path, move |event: E| {
+            let payload = codec_out.encode(&event);
+            let client = client.clone();
+            let origin = Arc::clone(&origin);
+            let subject = subject_name.clone();
+            let echoes = Arc::clone(&echoes);
+            async move {
+                // events that cannot be encoded or published are lost, as with remote refs
+                let Ok(payload) = payload else {
+                    return;
+                };
+                if echoes.is_echo(&payload) {
+                    return;
+                }
+                let mut headers = HeaderMap::new();
+                headers.insert(ORIGIN, &*origin);
+                let _ = client
+                    .publish_with_headers(subject, headers, payload.into())
+                    .await;
+            }
+        });
+        self.system.subscribe(&outgoing);
+
+        let system = Arc::downgrade(&self.system.inner);
+        let origin = Arc::clone(&self.origin);
+        let outgoing = outgoing.downgrade();
+        let _handle = tokio::spawn(receive_events(
+            subscriber, system, origin, codec, incoming, outgoing,
+        ));
+        Ok(())
+    }
+
+    fn codec<T: 'static>(&self) -> Result<Arc<dyn MessageCodec<T>>, NatsError> {
+        self.system
+            .codec::<T>()
+            .ok_or(NatsError::NoCodec(std::any::type_name::<T>()))
+    }
+}
+
+async fn receive_events<E: Clone + Send + 'static>(
+    mut subscriber: Subscriber,
+    system: Weak<SystemInner>,
+    origin: Arc<str>,
+    codec: Arc<dyn MessageCodec<E>>,
+    incoming: Arc<Echoes>,
+    outgoing: WeakActorRef<E>,
+) {
+    while let Some(msg) = next(&mut subscriber).await {
+        let own = msg
+            .headers
+            .as_ref()
+            .and_then(|headers| headers.get(ORIGIN))
+            .is_some_and(|o| o.as_str() == &*origin);
+        if own {
+            continue;
+        }
+        let Ok(event) = codec.decode(&msg.payload) else {
+            continue;
+        };
+        let Some(system) = system.upgrade() else {
+            return;
+        };
+        // the bridge sees the event on the bus as well, it compares the encoded events
+        if let (Some(_), Ok(payload)) = (outgoing.upgrade(), codec.encode(&event)) {
+            incoming.expect(payload);
+        }
+        let _ = system.bus.publish(event);
+    }
+}
+
+async fn next(subscriber: &mut Subscriber) -> Option<async_nats::Message> {
+    poll_fn(|cx| Pin::new(&mut *subscriber).poll_next(cx)).await
+}
+
+impl std::fmt::Debug for Nats {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Nats")
+            .field("prefix", &self.prefix)
+            .field("origin", &self.origin)
+            .finish_non_exhaustive()
+    }
+}
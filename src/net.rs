@@ -0,0 +1,16 @@
+//! Actors for network connections.
+//!
+//! [`TcpListenerActor`] accepts TCP connections and spawns one actor per connection.
+//! [`UdpActor`] delivers the datagrams of a UDP socket to an actor.
+//! With the `websocket` feature, [`ActorSystem::spawn_websocket`](crate::ActorSystem::spawn_websocket)
+//! turns a WebSocket into an actor.
+
+mod tcp;
+mod udp;
+#[cfg(feature = "websocket")]
+mod websocket;
+
+pub use tcp::{Accepted, Framing, TcpEvent, TcpListenerActor};
+pub use udp::{Datagram, UdpActor};
+#[cfg(feature = "websocket")]
+pub use websocket::{OutFrame, WebSocketEvent};
@@ -0,0 +1,240 @@
+use crate::{behavior, ActorContext, ActorPath, ActorRef, ActorSystem, Behavior, Behaviors, Error};
+use std::{
+    future::{ready, Ready},
+    io,
+    net::SocketAddr,
+    sync::Arc,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::{tcp::OwnedReadHalf, TcpListener, TcpStream, ToSocketAddrs},
+    sync::Mutex,
+};
+
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+type SpawnFn = Box<
+    dyn Fn(&ActorSystem, ActorPath, SocketAddr, ActorRef<Vec<u8>>) -> ActorRef<TcpEvent>
+        + Send
+        + Sync,
+>;
+
+/// How the bytes of a connection are split into messages.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Framing {
+    /// Every frame is prefixed with its length as a big endian `u32`.
+    #[default]
+    LengthPrefixed,
+    /// Every frame ends with a `\n`, which is not part of the message.
+    Lines,
+}
+
+/// What the actor of a connection receives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TcpEvent {
+    /// One frame from the peer.
+    Received(Vec<u8>),
+    /// The peer closed the connection or it failed, there will be no more frames.
+    Closed,
+}
+
+/// A connection for a [`TcpListenerActor`].
+#[derive(Debug)]
+pub struct Accepted {
+    stream: TcpStream,
+    peer: SocketAddr,
+}
+
+impl Accepted {
+    /// Hand a connection that was accepted elsewhere to a listener.
+    pub fn new(stream: TcpStream) -> io::Result<Self> {
+        let peer = stream.peer_addr()?;
+        Ok(Self { stream, peer })
+    }
+}
+
+/// A behavior that spawns a child actor for every connection it accepts.
+///
+/// The child receives the frames of the connection as [`TcpEvent`]s. It is created with a ref to a
+/// writer for the connection, every message told to that writer is sent to the peer as one frame.
+/// The connection is closed once the child, and with it the writer, has stopped.
+///
+/// ```rust,no_run
+/// use elliot::{net::{TcpEvent, TcpListenerActor}, ActorSystem};
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let system = ActorSystem::new();
+/// let echo = TcpListenerActor::new(|_peer, writer| {
+///     move |event: TcpEvent| {
+///         if let TcpEvent::Received(frame) = event {
+///             let _ = writer.tell(frame);
+///         }
+///         async {}
+///     }
+/// });
+/// let (listener, addr) = echo.bind(&system, "echo", "127.0.0.1:7000").await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TcpListenerActor {
+    spawn: SpawnFn,
+    framing: Framing,
+}
+
+impl TcpListenerActor {
+    /// `connection` creates the behavior of the child for a new connection with the given peer.
+    pub fn new<F, A, Args>(connection: F) -> Self
+    where
+        F: Fn(SocketAddr, ActorRef<Vec<u8>>) -> A + Send + Sync + 'static,
+        A: Behavior<TcpEvent, Args>,
+    {
+        let spawn = move |system: &ActorSystem, path, peer, writer| {
+            behavior::actor_of(system, path, connection(peer, writer))
+        };
+        Self {
+            spawn: Box::new(spawn),
+            framing: Framing::default(),
+        }
+    }
+
+    /// How the bytes are split into frames, defaults to [`Framing::LengthPrefixed`].
+    pub fn framing(mut self, framing: Framing) -> Self {
+        self.framing = framing;
+        self
+    }
+
+    /// Spawn the listener as `name` and accept connections on `addr`, returns the address that is bound.
+    ///
+    /// No more connections are accepted when the returned ref is dropped,
+    /// the connections that are open stay open.
+    pub async fn bind(
+        self,
+        system: &ActorSystem,
+        name: &str,
+        addr: impl ToSocketAddrs,
+    ) -> io::Result<(ActorRef<Accepted>, SocketAddr)> {
+        let listener = TcpListener::bind(addr).await?;
+        let local_addr = listener.local_addr()?;
+        let this = system.spawn(name, self);
+        let weak = this.downgrade();
+        let _handle = tokio::spawn(async move {
+            while let Ok((stream, peer)) = listener.accept().await {
+                let Some(this) = weak.upgrade() else {
+                    return;
+                };
+                if this.tell(Accepted { stream, peer }).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok((this, local_addr))
+    }
+}
+
+impl Behavior<Accepted> for TcpListenerActor {
+    type F = Ready<Result<Behaviors, Error<Accepted>>>;
+
+    fn receive(&self, context: &ActorContext<Accepted>, msg: Accepted) -> Self::F {
+        let Accepted { stream, peer } = msg;
+        let _ = stream.set_nodelay(true);
+        let (reader, writer) = stream.into_split();
+        let path = context.path().child(&peer.to_string());
+
+        let framing = self.framing;
+        let writer = Arc::new(Mutex::new(writer));
+        let writer = behavior::actor_of(context.system(), path.child("writer"), {
+            move |frame: Vec<u8>| {
+                let writer = Arc::clone(&writer);
+                async move { write_frame(&mut *writer.lock().await, framing, &frame).await }
+            }
+        });
+        let connection = (self.spawn)(context.system(), path, peer, writer);
+        let _handle = tokio::spawn(read_frames(reader, framing, connection));
+
+        ready(Ok(Behaviors::Same))
+    }
+}
+
+async fn read_frames(reader: OwnedReadHalf, framing: Framing, connection: ActorRef<TcpEvent>) {
+    let mut reader = BufReader::new(reader);
+    while let Ok(Some(frame)) = read_frame(&mut reader, framing).await {
+        if connection.tell(TcpEvent::Received(frame)).is_err() {
+            return;
+        }
+    }
+    let _ = connection.tell(TcpEvent::Closed);
+}
+
+async fn read_frame(
+    reader: &mut BufReader<OwnedReadHalf>,
+    framing: Framing,
+) -> io::Result<Option<Vec<u8>>> {
+    match framing {
+        Framing::LengthPrefixed => {
+            let len = match reader.read_u32().await {
+                Ok(len) => len as usize,
+                Err(e) if e.kind() == io::ErrorKind::UnexpectedEof => return Ok(None),
+                Err(e) => return Err(e),
+            };
+            if len > MAX_FRAME_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "frame too large",
+                ));
+            }
+            let mut frame = vec![0; len];
+            let _ = reader.read_exact(&mut frame).await?;
+            Ok(Some(frame))
+        }
+        Framing::Lines => {
+            // one more byte than the largest frame, for its newline
+            let limit = MAX_FRAME_LEN as u64 + 1;
+            let mut frame = Vec::new();
+            if (&mut *reader)
+                .take(limit)
+                .read_until(b'\n', &mut frame)
+                .await?
+                == 0
+            {
+                return Ok(None);
+            }
+            if frame.last() == Some(&b'\n') {
+                let _ = frame.pop();
+            }
+            if frame.len() > MAX_FRAME_LEN {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidData,
+                    "frame too large",
+                ));
+            }
+            Ok(Some(frame))
+        }
+    }
+}
+
+async fn write_frame<W>(writer: &mut W, framing: Framing, frame: &[u8]) -> io::Result<()>
+where
+    W: AsyncWriteExt + Unpin,
+{
+    match framing {
+        Framing::LengthPrefixed => {
+            let len = u32::try_from(frame.len())
+                .map_err(|_| io::Error::new(io::ErrorKind::InvalidInput, "frame too large"))?;
+            writer.write_u32(len).await?;
+            writer.write_all(frame).await?;
+        }
+        Framing::Lines => {
+            writer.write_all(frame).await?;
+            writer.write_all(b"\n").await?;
+        }
+    }
+    writer.flush().await
+}
+
+impl std::fmt::Debug for TcpListenerActor {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TcpListenerActor")
+            .field("framing", &self.framing)
+            .finish_non_exhaustive()
+    }
+}
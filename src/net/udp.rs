@@ -0,0 +1,84 @@
+use crate::{behavior, ActorPath, ActorRef, ActorSystem, Behavior};
+use std::{io, net::SocketAddr, sync::Arc};
+use tokio::net::{ToSocketAddrs, UdpSocket};
+
+/// The largest payload of a UDP datagram.
+const MAX_DATAGRAM_LEN: usize = 65_535;
+
+/// A datagram with the address it came from or is sent to.
+pub type Datagram = (SocketAddr, Vec<u8>);
+
+/// An actor that receives the datagrams of a UDP socket.
+///
+/// The actor is created with a ref to a sender for the socket, every [`Datagram`] told to that sender
+/// is sent to its address. The socket is closed once the actor has stopped.
+///
+/// ```rust,no_run
+/// use elliot::{net::UdpActor, ActorSystem};
+///
+/// # async fn example() -> std::io::Result<()> {
+/// let system = ActorSystem::new();
+/// let (echo, addr) = UdpActor::bind(&system, "echo", "127.0.0.1:7000", |sender| {
+///     move |datagram| {
+///         let _ = sender.tell(datagram);
+///         async {}
+///     }
+/// })
+/// .await?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Copy, Clone, Debug)]
+pub struct UdpActor;
+
+impl UdpActor {
+    /// Bind a socket to `addr` and spawn an actor as `name` for it, returns the address that is bound.
+    ///
+    /// `datagrams` creates the behavior of the actor with the sender for the socket.
+    /// Datagrams that are larger than the buffer are truncated and errors while receiving are skipped,
+    /// failing to send a datagram crashes the sender.
+    pub async fn bind<N, F, A, Args>(
+        system: &ActorSystem,
+        name: N,
+        addr: impl ToSocketAddrs,
+        datagrams: F,
+    ) -> io::Result<(ActorRef<Datagram>, SocketAddr)>
+    where
+        N: Into<Arc<str>>,
+        F: FnOnce(ActorRef<Datagram>) -> A,
+        A: Behavior<Datagram, Args>,
+    {
+        let socket = Arc::new(UdpSocket::bind(addr).await?);
+        let local_addr = socket.local_addr()?;
+        let path = ActorPath::user(&name.into());
+
+        let sending = Arc::clone(&socket);
+        let sender =
+            behavior::actor_of(system, path.child("sender"), move |(to, data): Datagram| {
+                let socket = Arc::clone(&sending);
+                async move { socket.send_to(&data, to).await.map(drop) }
+            });
+        let this = behavior::actor_of(system, path, datagrams(sender));
+
+        let weak = this.downgrade();
+        let _handle = tokio::spawn(async move {
+            let mut buf = vec![0; MAX_DATAGRAM_LEN];
+            loop {
+                let received = tokio::select! {
+                    received = socket.recv_from(&mut buf) => received,
+                    () = weak.cell.wait_for_stop() => return,
+                };
+                let Ok((len, from)) = received else {
+                    continue;
+                };
+                let Some(actor) = weak.upgrade() else {
+                    return;
+                };
+                if actor.tell((from, buf[..len].to_vec())).is_err() {
+                    return;
+                }
+            }
+        });
+        Ok((this, local_addr))
+    }
+}
@@ -0,0 +1,124 @@
+use crate::{behavior, ActorPath, ActorRef, ActorSystem, Behavior};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::{future::poll_fn, pin::Pin, sync::Arc};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    sync::mpsc,
+};
+use tokio_tungstenite::{
+    tungstenite::{Error as WsError, Message},
+    WebSocketStream,
+};
+
+/// What the actor of a WebSocket receives.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum WebSocketEvent {
+    /// A text message from the peer.
+    Text(String),
+    /// A binary message from the peer.
+    Binary(Vec<u8>),
+    /// The connection is closed, there will be no more messages.
+    Closed,
+}
+
+/// What the actor of a WebSocket can send to the peer.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum OutFrame {
+    /// Send a text message.
+    Text(String),
+    /// Send a binary message.
+    Binary(Vec<u8>),
+    /// Start to close the connection, the actor is told [`WebSocketEvent::Closed`] once the peer has closed as well.
+    Close,
+}
+
+impl ActorSystem {
+    /// Spawn an actor as `name` that receives the messages of `socket`.
+    ///
+    /// `connection` creates the behavior of the actor with a ref that sends [`OutFrame`]s to the peer.
+    /// Pings are answered and close handshakes are completed without involving the actor.
+    /// The socket is closed once the actor, and with it the sending ref, has stopped.
+    ///
+    /// The socket can come from anything that does the handshake with `tokio-tungstenite`,
+    /// e.g. its `accept_async`, or `connect_async` for a client.
+    pub fn spawn_websocket<S, N, F, A, Args>(
+        &self,
+        name: N,
+        socket: WebSocketStream<S>,
+        connection: F,
+    ) -> ActorRef<WebSocketEvent>
+    where
+        S: AsyncRead + AsyncWrite + Unpin + Send + 'static,
+        N: Into<Arc<str>>,
+        F: FnOnce(ActorRef<OutFrame>) -> A,
+        A: Behavior<WebSocketEvent, Args>,
+    {
+        let path = ActorPath::user(&name.into());
+        let (tx, rx) = mpsc::unbounded_channel();
+        let out = behavior::actor_of(self, path.child("out"), move |frame: OutFrame| {
+            let _ = tx.send(frame);
+            async {}
+        });
+        let this = behavior::actor_of(self, path, connection(out));
+        let _handle = tokio::spawn(drive(socket, rx, this.clone()));
+        this
+    }
+}
+
+async fn drive<S>(
+    mut socket: WebSocketStream<S>,
+    mut out: mpsc::UnboundedReceiver<OutFrame>,
+    connection: ActorRef<WebSocketEvent>,
+) where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    let mut closing = false;
+    loop {
+        tokio::select! {
+            msg = next(&mut socket) => {
+                let event = match msg {
+                    Some(Ok(Message::Text(text))) => WebSocketEvent::Text(text.as_str().to_owned()),
+                    Some(Ok(Message::Binary(data))) => WebSocketEvent::Binary(data.to_vec()),
+                    // pings are answered and a close is replied to by the socket,
+                    // the stream ends once the close handshake is done
+                    Some(Ok(_)) => continue,
+                    Some(Err(_)) | None => break,
+                };
+                if connection.tell(event).is_err() {
+                    break;
+                }
+            }
+            frame = out.recv(), if closing == false => {
+                let msg = match frame {
+                    Some(OutFrame::Text(text)) => Message::text(text),
+                    Some(OutFrame::Binary(data)) => Message::binary(data),
+                    Some(OutFrame::Close) | None => {
+                        closing = true;
+                        Message::Close(None)
+                    }
+                };
+                if send(&mut socket, msg).await.is_err() {
+                    break;
+                }
+            }
+        }
+    }
+    let _ = connection.tell(WebSocketEvent::Closed);
+}
+
+async fn next<S>(socket: &mut WebSocketStream<S>) -> Option<Result<Message, WsError>>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    poll_fn(|cx| Pin::new(&mut *socket).poll_next(cx)).await
+}
+
+async fn send<S>(socket: &mut WebSocketStream<S>, msg: Message) -> Result<(), WsError>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    poll_fn(|cx| Pin::new(&mut *socket).poll_ready(cx)).await?;
+    Pin::new(&mut *socket).start_send(msg)?;
+    poll_fn(|cx| Pin::new(&mut *socket).poll_flush(cx)).await
+}
@@ -0,0 +1,75 @@
+use crate::InvalidActorPath;
+use std::{fmt::Display, str::FromStr, sync::Arc};
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+pub struct ActorPath(Arc<str>);
+
+impl ActorPath {
+    /// The path of the guardian of the user actors.
+    pub(crate) fn user_guardian() -> Self {
+        Self(Arc::from("/user"))
+    }
+
+    /// The path of a top-level user actor, a child of the [user guardian](Self::user_guardian).
+    pub(crate) fn user(name: &str) -> Self {
+        Self::user_guardian().child(name)
+    }
+
+    /// The path of the guardian of the internal actors.
+    pub(crate) fn system_guardian() -> Self {
+        Self(Arc::from("/system"))
+    }
+
+    /// The path of a top-level internal actor, a child of the [system guardian](Self::system_guardian).
+    pub(crate) fn system(name: &str) -> Self {
+        Self::system_guardian().child(name)
+    }
+
+    #[cfg(any(feature = "remote", feature = "nats"))]
+    pub(crate) fn remote(uri: &str) -> Self {
+        Self(uri.into())
+    }
+
+    pub(crate) fn child(&self, name: &str) -> Self {
+        Self(format!("{self}/{name}").into())
+    }
+
+    /// The last segment of the path, i.e. the name the actor was spawned with.
+    pub fn name(&self) -> &str {
+        self.0.rsplit_once('/').map_or(&*self.0, |(_, name)| name)
+    }
+
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+/// Parses the [`Display`] format of a local path, e.g. `/user/workers/1`.
+impl FromStr for ActorPath {
+    type Err = InvalidActorPath;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let valid = s.strip_prefix('/').is_some_and(|names| {
+            names
+                .split('/')
+                .all(|name| name.is_empty() == false && name.contains(char::is_whitespace) == false)
+        });
+        if valid {
+            Ok(Self(Arc::from(s)))
+        } else {
+            Err(InvalidActorPath(s.to_owned()))
+        }
+    }
+}
+
+impl Display for ActorPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&self.0)
+    }
+}
+
+impl std::fmt::Debug for ActorPath {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ActorPath").field(&&*self.0).finish()
+    }
+}
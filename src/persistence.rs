@@ -0,0 +1,835 @@
+use crate::{runtime, ActorContext, Behavior, Behaviors, BoxErr, Error, MessageCodec};
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+
+#[cfg(feature = "file-journal")]
+mod file;
+#[cfg(feature = "postgres")]
+mod postgres;
+mod projection;
+#[cfg(feature = "redis")]
+mod redis;
+#[cfg(feature = "sqlite")]
+mod sqlite;
+
+#[cfg(feature = "file-journal")]
+pub use file::FileJournal;
+#[cfg(feature = "postgres")]
+pub use postgres::PostgresJournal;
+pub use projection::{InMemoryOffsetStore, OffsetStore, Projection};
+#[cfg(feature = "redis")]
+pub use redis::{RedisJournal, RedisSnapshotStore};
+#[cfg(feature = "sqlite")]
+pub use sqlite::SqliteJournal;
+
+/// Storage for the events of event-sourced actors, see [`EventSourced`].
+///
+/// Every actor has its own stream of events, identified by its persistence id.
+/// Sequence numbers start at 1 and have no gaps.
+pub trait Journal<E>: Send + Sync + 'static {
+    /// Append `events` to the stream, the first event has the sequence number `sequence_nr`.
+    ///
+    /// Fails if the stream already contains an event with that sequence number.
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr>;
+
+    /// All events of the stream, starting at `from_sequence_nr`, in order.
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr>;
+
+    /// Like [`Journal::append`], with the tags of every event, by which a [`ReadJournal`] finds them.
+    ///
+    /// Journals that cannot be queried by tag ignore the tags, which is the default.
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let _ = tags;
+        self.append(persistence_id, sequence_nr, events)
+    }
+
+    /// Delete the events of the stream up to and including `to_sequence_nr`, e.g. once they are
+    /// covered by a snapshot.
+    ///
+    /// The last event of a stream is never deleted, so that its sequence numbers continue.
+    /// Journals that cannot delete keep the events, which is the default.
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        let _ = (persistence_id, to_sequence_nr);
+        Ok(())
+    }
+}
+
+/// The query side of a journal, for read models that follow the events of all actors.
+///
+/// Events are tagged when they are appended with [`Journal::append_tagged`], e.g. by a
+/// [`TaggedJournal`] or by the tagger of an [`InMemoryJournal`].
+pub trait ReadJournal<E>: Send + Sync + 'static {
+    /// Pass the events with `tag` after `offset` to `sink`, in the order they were appended,
+    /// followed by the events that are appended later, until `sink` returns `false`.
+    ///
+    /// Fails if the events that have already been appended cannot be read.
+    fn subscribe(&self, tag: &str, offset: u64, sink: EventSink<E>) -> Result<(), BoxErr>;
+
+    /// The events with `tag` after `offset`, in the order they were appended, followed by the events that are appended later.
+    fn events_by_tag_from(&self, tag: &str, offset: u64) -> Result<EventsByTag<E>, BoxErr>
+    where
+        E: Send + 'static,
+    {
+        let (tx, rx) = mpsc::unbounded_channel();
+        self.subscribe(
+            tag,
+            offset,
+            Box::new(move |envelope| tx.send(envelope).is_ok()),
+        )?;
+        Ok(EventsByTag { rx })
+    }
+
+    /// All events with `tag`, followed by the events that are appended later.
+    fn events_by_tag(&self, tag: &str) -> Result<EventsByTag<E>, BoxErr>
+    where
+        E: Send + 'static,
+    {
+        self.events_by_tag_from(tag, 0)
+    }
+}
+
+/// Receives the events of a query, see [`ReadJournal::subscribe`].
+pub type EventSink<E> = Box<dyn FnMut(EventEnvelope<E>) -> bool + Send>;
+
+/// A persisted event, as seen by a [`ReadJournal`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EventEnvelope<E> {
+    /// The position of the event in the journal, increasing with every appended event.
+    ///
+    /// Resuming a query from the offset of the last processed event skips all events up to it.
+    pub offset: u64,
+    pub persistence_id: Arc<str>,
+    pub sequence_nr: u64,
+    pub event: E,
+}
+
+impl<E> EventEnvelope<E> {
+    fn with_event<F>(self, event: F) -> EventEnvelope<F> {
+        EventEnvelope {
+            offset: self.offset,
+            persistence_id: self.persistence_id,
+            sequence_nr: self.sequence_nr,
+            event,
+        }
+    }
+}
+
+/// The sinks of the queries of a journal that follow the appended events, with their tags.
+pub(crate) struct Subscribers<E> {
+    sinks: Vec<(String, EventSink<E>)>,
+}
+
+impl<E: Clone> Subscribers<E> {
+    pub(crate) fn add(&mut self, tag: &str, sink: EventSink<E>) {
+        self.sinks.push((tag.to_owned(), sink));
+    }
+
+    #[cfg(feature = "file-journal")]
+    pub(crate) fn is_subscribed(&self, tag: &str) -> bool {
+        self.sinks.iter().any(|(subscribed, _)| subscribed == tag)
+    }
+
+    /// Pass the event to the sinks of `tag`, and forget the sinks that are done.
+    pub(crate) fn publish(&mut self, tag: &str, envelope: &EventEnvelope<E>) {
+        self.sinks
+            .retain_mut(|(subscribed, sink)| subscribed != tag || sink(envelope.clone()));
+    }
+}
+
+impl<E> Default for Subscribers<E> {
+    fn default() -> Self {
+        Self { sinks: Vec::new() }
+    }
+}
+
+impl<E> std::fmt::Debug for Subscribers<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Subscribers")
+            .field("sinks", &self.sinks.len())
+            .finish()
+    }
+}
+
+/// A sink of events that are stored as JSON, which ends at the first event that cannot be decoded.
+#[cfg(any(feature = "file-journal", feature = "postgres", feature = "sqlite"))]
+pub(crate) fn json_sink<E: serde::de::DeserializeOwned + 'static>(
+    mut sink: EventSink<E>,
+) -> EventSink<String> {
+    Box::new(
+        move |envelope: EventEnvelope<String>| match serde_json::from_str(&envelope.event) {
+            Ok(event) => sink(envelope.with_event(event)),
+            Err(_) => false,
+        },
+    )
+}
+
+/// The live events of a tag, see [`ReadJournal::events_by_tag`].
+///
+/// With the `stream` feature, this is also a `Stream` of the events.
+pub struct EventsByTag<E> {
+    pub(crate) rx: mpsc::UnboundedReceiver<EventEnvelope<E>>,
+}
+
+impl<E> EventsByTag<E> {
+    /// Receive the next event, or `None` if the journal has been dropped.
+    pub async fn recv(&mut self) -> Option<EventEnvelope<E>> {
+        self.rx.recv().await
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<E> futures_core::Stream for EventsByTag<E> {
+    type Item = EventEnvelope<E>;
+
+    fn poll_next(
+        mut self: Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl<E> std::fmt::Debug for EventsByTag<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventsByTag").finish_non_exhaustive()
+    }
+}
+
+type Tagger<E> = Box<dyn Fn(&E) -> Vec<String> + Send + Sync>;
+
+/// A journal that keeps all events in memory, meant for tests.
+///
+/// All clones share the same events.
+pub struct InMemoryJournal<E> {
+    inner: Arc<Mutex<Events<E>>>,
+}
+
+struct Events<E> {
+    /// The events of every stream with their sequence numbers.
+    streams: HashMap<String, Vec<(u64, E)>>,
+    tagger: Option<Tagger<E>>,
+    tagged: HashMap<String, Vec<EventEnvelope<E>>>,
+    /// The offset of the last appended event.
+    offset: u64,
+    subscribers: Subscribers<E>,
+}
+
+impl<E> InMemoryJournal<E> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Tag the events that are appended from now on with the tags that `tagger` returns,
+    /// so that they can be queried by tag.
+    pub fn tagged(self, tagger: impl Fn(&E) -> Vec<String> + Send + Sync + 'static) -> Self {
+        self.inner.lock().unwrap().tagger = Some(Box::new(tagger));
+        self
+    }
+}
+
+impl<E: Clone> InMemoryJournal<E> {
+    /// All events of the stream that have not been deleted, in order.
+    pub fn events(&self, persistence_id: &str) -> Vec<E> {
+        self.inner
+            .lock()
+            .unwrap()
+            .streams
+            .get(persistence_id)
+            .into_iter()
+            .flatten()
+            .map(|(_, event)| event.clone())
+            .collect()
+    }
+}
+
+impl<E> Default for InMemoryJournal<E> {
+    fn default() -> Self {
+        Self {
+            inner: Arc::new(Mutex::new(Events {
+                streams: HashMap::new(),
+                tagger: None,
+                tagged: HashMap::new(),
+                offset: 0,
+                subscribers: Subscribers::default(),
+            })),
+        }
+    }
+}
+
+impl<E> Clone for InMemoryJournal<E> {
+    fn clone(&self) -> Self {
+        Self {
+            inner: Arc::clone(&self.inner),
+        }
+    }
+}
+
+impl<E> std::fmt::Debug for InMemoryJournal<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let inner = self.inner.lock().unwrap();
+        f.debug_struct("InMemoryJournal")
+            .field("streams", &inner.streams.len())
+            .field("offset", &inner.offset)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<E: Clone + Send + 'static> Journal<E> for InMemoryJournal<E> {
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr> {
+        self.append_tagged(persistence_id, sequence_nr, events, &[])
+    }
+
+    /// The events are tagged with `tags` and with the tags of the tagger.
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let mut inner = self.inner.lock().unwrap();
+        let Events {
+            streams,
+            tagger,
+            tagged,
+            offset,
+            subscribers,
+        } = &mut *inner;
+        let stream = streams.entry(persistence_id.to_owned()).or_default();
+        let expected = stream.last().map_or(1, |(highest, _)| highest + 1);
+        if sequence_nr != expected {
+            return Err(format!(
+                "Expected sequence number {expected} for {persistence_id}, got {sequence_nr}"
+            )
+            .into());
+        }
+        stream.extend((sequence_nr..).zip(events.iter().cloned()));
+
+        let persistence_id = Arc::<str>::from(persistence_id);
+        for (i, (event, sequence_nr)) in events.iter().zip(sequence_nr..).enumerate() {
+            *offset += 1;
+            let mut event_tags = tags.get(i).cloned().unwrap_or_default();
+            if let Some(tagger) = tagger {
+                event_tags.extend(tagger(event));
+            }
+            for tag in event_tags {
+                let envelope = EventEnvelope {
+                    offset: *offset,
+                    persistence_id: Arc::clone(&persistence_id),
+                    sequence_nr,
+                    event: event.clone(),
+                };
+                subscribers.publish(&tag, &envelope);
+                tagged.entry(tag).or_default().push(envelope);
+            }
+        }
+        Ok(())
+    }
+
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr> {
+        let inner = self.inner.lock().unwrap();
+        let events = inner
+            .streams
+            .get(persistence_id)
+            .into_iter()
+            .flatten()
+            .filter(|(sequence_nr, _)| *sequence_nr >= from_sequence_nr)
+            .cloned()
+            .collect();
+        Ok(events)
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        let mut inner = self.inner.lock().unwrap();
+        if let Some(stream) = inner.streams.get_mut(persistence_id) {
+            let highest = stream.last().map_or(0, |(highest, _)| *highest);
+            let to_sequence_nr = to_sequence_nr.min(highest.saturating_sub(1));
+            stream.retain(|(sequence_nr, _)| *sequence_nr > to_sequence_nr);
+        }
+        Ok(())
+    }
+}
+
+impl<E: Clone + Send + 'static> ReadJournal<E> for InMemoryJournal<E> {
+    fn subscribe(&self, tag: &str, offset: u64, mut sink: EventSink<E>) -> Result<(), BoxErr> {
+        let mut inner = self.inner.lock().unwrap();
+        for envelope in inner.tagged.get(tag).into_iter().flatten() {
+            if envelope.offset > offset && sink(envelope.clone()) == false {
+                return Ok(());
+            }
+        }
+        inner.subscribers.add(tag, sink);
+        Ok(())
+    }
+}
+
+/// A journal that stores the events encoded with a codec, in a journal of bytes.
+///
+/// With a [`VersionedCodec`](crate::VersionedCodec), events that have been persisted by an
+/// older version are migrated when they are replayed.
+///
+/// Queries by tag end at the first event that cannot be decoded.
+pub struct CodecJournal<E, J> {
+    journal: J,
+    codec: Arc<dyn MessageCodec<E>>,
+}
+
+impl<E: 'static, J: Journal<Vec<u8>>> CodecJournal<E, J> {
+    pub fn new(journal: J, codec: impl MessageCodec<E>) -> Self {
+        Self {
+            journal,
+            codec: Arc::new(codec),
+        }
+    }
+}
+
+impl<E: 'static, J: Journal<Vec<u8>>> Journal<E> for CodecJournal<E, J> {
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr> {
+        let events = events
+            .iter()
+            .map(|event| self.codec.encode(event))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.journal.append(persistence_id, sequence_nr, &events)
+    }
+
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let events = events
+            .iter()
+            .map(|event| self.codec.encode(event))
+            .collect::<Result<Vec<_>, _>>()?;
+        self.journal
+            .append_tagged(persistence_id, sequence_nr, &events, tags)
+    }
+
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr> {
+        self.journal
+            .replay(persistence_id, from_sequence_nr)?
+            .into_iter()
+            .map(|(sequence_nr, bytes)| Ok((sequence_nr, self.codec.decode(&bytes)?)))
+            .collect()
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        self.journal.delete_to(persistence_id, to_sequence_nr)
+    }
+}
+
+impl<E, J> ReadJournal<E> for CodecJournal<E, J>
+where
+    E: 'static,
+    J: Journal<Vec<u8>> + ReadJournal<Vec<u8>>,
+{
+    fn subscribe(&self, tag: &str, offset: u64, mut sink: EventSink<E>) -> Result<(), BoxErr> {
+        let codec = Arc::clone(&self.codec);
+        self.journal.subscribe(
+            tag,
+            offset,
+            Box::new(
+                move |envelope: EventEnvelope<Vec<u8>>| match codec.decode(&envelope.event) {
+                    Ok(event) => sink(envelope.with_event(event)),
+                    Err(_) => false,
+                },
+            ),
+        )
+    }
+}
+
+impl<E, J: Clone> Clone for CodecJournal<E, J> {
+    fn clone(&self) -> Self {
+        Self {
+            journal: self.journal.clone(),
+            codec: Arc::clone(&self.codec),
+        }
+    }
+}
+
+impl<E, J: std::fmt::Debug> std::fmt::Debug for CodecJournal<E, J> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodecJournal")
+            .field("journal", &self.journal)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A journal that tags the events that are appended to it, so that they can be queried by tag
+/// with [`ReadJournal::events_by_tag`].
+pub struct TaggedJournal<E, J> {
+    journal: J,
+    tagger: Arc<Tagger<E>>,
+}
+
+impl<E, J: Journal<E>> TaggedJournal<E, J> {
+    /// Tag the events that are appended to `journal` with the tags that `tagger` returns.
+    pub fn new(journal: J, tagger: impl Fn(&E) -> Vec<String> + Send + Sync + 'static) -> Self {
+        Self {
+            journal,
+            tagger: Arc::new(Box::new(tagger)),
+        }
+    }
+}
+
+impl<E: 'static, J: Journal<E>> Journal<E> for TaggedJournal<E, J> {
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr> {
+        self.append_tagged(persistence_id, sequence_nr, events, &[])
+    }
+
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr> {
+        self.journal.replay(persistence_id, from_sequence_nr)
+    }
+
+    /// The events are tagged with `tags` and with the tags of the tagger.
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let tags = events
+            .iter()
+            .enumerate()
+            .map(|(i, event)| {
+                let mut event_tags = tags.get(i).cloned().unwrap_or_default();
+                event_tags.extend((self.tagger)(event));
+                event_tags
+            })
+            .collect::<Vec<_>>();
+        self.journal
+            .append_tagged(persistence_id, sequence_nr, events, &tags)
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        self.journal.delete_to(persistence_id, to_sequence_nr)
+    }
+}
+
+impl<E: 'static, J: Journal<E> + ReadJournal<E>> ReadJournal<E> for TaggedJournal<E, J> {
+    fn subscribe(&self, tag: &str, offset: u64, sink: EventSink<E>) -> Result<(), BoxErr> {
+        self.journal.subscribe(tag, offset, sink)
+    }
+}
+
+impl<E, J: Clone> Clone for TaggedJournal<E, J> {
+    fn clone(&self) -> Self {
+        Self {
+            journal: self.journal.clone(),
+            tagger: Arc::clone(&self.tagger),
+        }
+    }
+}
+
+impl<E, J: std::fmt::Debug> std::fmt::Debug for TaggedJournal<E, J> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaggedJournal")
+            .field("journal", &self.journal)
+            .finish_non_exhaustive()
+    }
+}
+
+/// Storage for snapshots of the state of event-sourced actors, see [`EventSourced::snapshot_every`].
+///
+/// Only the latest snapshot of every actor is needed for recovery.
+pub trait SnapshotStore<S>: Send + Sync + 'static {
+    /// Save the state after the event with the sequence number `sequence_nr` has been applied.
+    fn save(&self, persistence_id: &str, sequence_nr: u64, state: &S) -> Result<(), BoxErr>;
+
+    /// The latest snapshot and its sequence number, if there is one.
+    fn load(&self, persistence_id: &str) -> Result<Option<(u64, S)>, BoxErr>;
+}
+
+/// A snapshot store that keeps the latest snapshots in memory, meant for tests.
+///
+/// All clones share the same snapshots.
+#[derive(Debug)]
+pub struct InMemorySnapshotStore<S> {
+    snapshots: Arc<Mutex<HashMap<String, (u64, S)>>>,
+}
+
+impl<S> InMemorySnapshotStore<S> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<S> Default for InMemorySnapshotStore<S> {
+    fn default() -> Self {
+        Self {
+            snapshots: Arc::default(),
+        }
+    }
+}
+
+impl<S> Clone for InMemorySnapshotStore<S> {
+    fn clone(&self) -> Self {
+        Self {
+            snapshots: Arc::clone(&self.snapshots),
+        }
+    }
+}
+
+impl<S: Clone + Send + 'static> SnapshotStore<S> for InMemorySnapshotStore<S> {
+    fn save(&self, persistence_id: &str, sequence_nr: u64, state: &S) -> Result<(), BoxErr> {
+        let _ = self
+            .snapshots
+            .lock()
+            .unwrap()
+            .insert(persistence_id.to_owned(), (sequence_nr, state.clone()));
+        Ok(())
+    }
+
+    fn load(&self, persistence_id: &str) -> Result<Option<(u64, S)>, BoxErr> {
+        Ok(self.snapshots.lock().unwrap().get(persistence_id).cloned())
+    }
+}
+
+/// A snapshot store that stores the states encoded with a codec, in a snapshot store of bytes.
+pub struct CodecSnapshotStore<S, St> {
+    store: St,
+    codec: Arc<dyn MessageCodec<S>>,
+}
+
+impl<S: 'static, St: SnapshotStore<Vec<u8>>> CodecSnapshotStore<S, St> {
+    pub fn new(store: St, codec: impl MessageCodec<S>) -> Self {
+        Self {
+            store,
+            codec: Arc::new(codec),
+        }
+    }
+}
+
+impl<S: 'static, St: SnapshotStore<Vec<u8>>> SnapshotStore<S> for CodecSnapshotStore<S, St> {
+    fn save(&self, persistence_id: &str, sequence_nr: u64, state: &S) -> Result<(), BoxErr> {
+        let state = self.codec.encode(state)?;
+        self.store.save(persistence_id, sequence_nr, &state)
+    }
+
+    fn load(&self, persistence_id: &str) -> Result<Option<(u64, S)>, BoxErr> {
+        self.store
+            .load(persistence_id)?
+            .map(|(sequence_nr, state)| Ok((sequence_nr, self.codec.decode(&state)?)))
+            .transpose()
+    }
+}
+
+impl<S, St: Clone> Clone for CodecSnapshotStore<S, St> {
+    fn clone(&self) -> Self {
+        Self {
+            store: self.store.clone(),
+            codec: Arc::clone(&self.codec),
+        }
+    }
+}
+
+impl<S, St: std::fmt::Debug> std::fmt::Debug for CodecSnapshotStore<S, St> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CodecSnapshotStore")
+            .field("store", &self.store)
+            .finish_non_exhaustive()
+    }
+}
+
+type CommandHandler<C, E, S> = Box<dyn Fn(&S, C) -> Vec<E> + Send + Sync>;
+type EventHandler<E, S> = Box<dyn Fn(&mut S, &E) + Send + Sync>;
+
+/// A behavior whose state is derived from the events it has persisted.
+///
+/// The command handler does not change the state, it decides which events to emit.
+/// The events are appended to the journal and only then applied to the state by the
+/// event handler. Before the first command is handled, the state is recovered by
+/// replaying all events from the journal, or from the latest snapshot if snapshots are enabled.
+///
+/// Commands are handled on a blocking thread of the runtime of the actor, so that journals
+/// and snapshot stores can block on their I/O.
+///
+/// If the journal fails, the actor crashes.
+pub struct EventSourced<C, E, S> {
+    persistence_id: Arc<str>,
+    journal: Arc<dyn Journal<E>>,
+    command_handler: Arc<CommandHandler<C, E, S>>,
+    event_handler: Arc<EventHandler<E, S>>,
+    snapshots: Option<Snapshots<S>>,
+    entity: Arc<Mutex<Option<Entity<S>>>>,
+}
+
+struct Snapshots<S> {
+    store: Arc<dyn SnapshotStore<S>>,
+    every: u64,
+}
+
+impl<S> Clone for Snapshots<S> {
+    fn clone(&self) -> Self {
+        Self {
+            store: Arc::clone(&self.store),
+            every: self.every,
+        }
+    }
+}
+
+struct Entity<S> {
+    state: S,
+    sequence_nr: u64,
+    snapshot_sequence_nr: u64,
+}
+
+impl<C, E, S> EventSourced<C, E, S> {
+    pub fn new<J, CH, EH>(
+        persistence_id: impl Into<Arc<str>>,
+        journal: J,
+        command_handler: CH,
+        event_handler: EH,
+    ) -> Self
+    where
+        J: Journal<E>,
+        CH: Fn(&S, C) -> Vec<E> + Send + Sync + 'static,
+        EH: Fn(&mut S, &E) + Send + Sync + 'static,
+    {
+        Self {
+            persistence_id: persistence_id.into(),
+            journal: Arc::new(journal),
+            command_handler: Arc::new(Box::new(command_handler)),
+            event_handler: Arc::new(Box::new(event_handler)),
+            snapshots: None,
+            entity: Arc::default(),
+        }
+    }
+
+    /// Save a snapshot of the state to `store` after every `events` events.
+    ///
+    /// Recovery starts from the latest snapshot and replays only the newer events.
+    /// Failing to save a snapshot does not crash the actor, the snapshot is retried
+    /// after the next event.
+    pub fn snapshot_every(mut self, events: u64, store: impl SnapshotStore<S>) -> Self {
+        self.snapshots = Some(Snapshots {
+            store: Arc::new(store),
+            every: events.max(1),
+        });
+        self
+    }
+
+    pub fn persistence_id(&self) -> &str {
+        &self.persistence_id
+    }
+
+    /// Another handle to this behavior and its state, for the blocking thread.
+    fn share(&self) -> Self {
+        Self {
+            persistence_id: Arc::clone(&self.persistence_id),
+            journal: Arc::clone(&self.journal),
+            command_handler: Arc::clone(&self.command_handler),
+            event_handler: Arc::clone(&self.event_handler),
+            snapshots: self.snapshots.clone(),
+            entity: Arc::clone(&self.entity),
+        }
+    }
+}
+
+impl<C, E: 'static, S: Default + 'static> EventSourced<C, E, S> {
+    /// Recover the state before the first command, and handle `command`.
+    fn receive(&self, command: C) -> Result<(), BoxErr> {
+        let mut entity = self.entity.lock().unwrap();
+        if entity.is_none() {
+            *entity = Some(self.recover()?);
+        }
+        let entity = entity.as_mut().expect("entity is recovered");
+        self.handle(entity, command)
+    }
+
+    fn recover(&self) -> Result<Entity<S>, BoxErr> {
+        let snapshot = match &self.snapshots {
+            Some(snapshots) => snapshots.store.load(&self.persistence_id)?,
+            None => None,
+        };
+        let mut entity = match snapshot {
+            Some((sequence_nr, state)) => Entity {
+                state,
+                sequence_nr,
+                snapshot_sequence_nr: sequence_nr,
+            },
+            None => Entity {
+                state: S::default(),
+                sequence_nr: 0,
+                snapshot_sequence_nr: 0,
+            },
+        };
+        let from_sequence_nr = entity.sequence_nr + 1;
+        for (sequence_nr, event) in self
+            .journal
+            .replay(&self.persistence_id, from_sequence_nr)?
+        {
+            (self.event_handler)(&mut entity.state, &event);
+            entity.sequence_nr = sequence_nr;
+        }
+        Ok(entity)
+    }
+
+    fn handle(&self, entity: &mut Entity<S>, command: C) -> Result<(), BoxErr> {
+        let events = (self.command_handler)(&entity.state, command);
+        if events.is_empty() {
+            return Ok(());
+        }
+        self.journal
+            .append(&self.persistence_id, entity.sequence_nr + 1, &events)?;
+        for event in &events {
+            (self.event_handler)(&mut entity.state, event);
+        }
+        entity.sequence_nr += events.len() as u64;
+
+        if let Some(snapshots) = &self.snapshots {
+            if entity.sequence_nr - entity.snapshot_sequence_nr >= snapshots.every {
+                let store = &snapshots.store;
+                if store
+                    .save(&self.persistence_id, entity.sequence_nr, &entity.state)
+                    .is_ok()
+                {
+                    entity.snapshot_sequence_nr = entity.sequence_nr;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<C, E, S> Behavior<C> for EventSourced<C, E, S>
+where
+    C: Send + 'static,
+    E: 'static,
+    S: Default + Send + 'static,
+{
+    type F = Pin<Box<dyn Future<Output = Result<Behaviors, Error<C>>> + Send>>;
+
+    fn receive(&self, context: &ActorContext<C>, msg: C) -> Self::F {
+        let this = self.share();
+        let runtime = context.this.cell.runtime();
+        Box::pin(async move {
+            match runtime::unblock(&*runtime, move || this.receive(msg)).await {
+                Ok(()) => Ok(Behaviors::Same),
+                Err(e) => Err(Error::Crashed(e)),
+            }
+        })
+    }
+}
+
+impl<C, E, S> std::fmt::Debug for EventSourced<C, E, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EventSourced")
+            .field("persistence_id", &self.persistence_id)
+            .finish_non_exhaustive()
+    }
+}
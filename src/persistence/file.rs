@@ -0,0 +1,339 @@
+use super::{json_sink, EventEnvelope, EventSink, Subscribers};
+use crate::{BoxErr, Journal, ReadJournal};
+use serde::{de::DeserializeOwned, de::IgnoredAny, Deserialize, Serialize};
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    fs::{File, OpenOptions},
+    io::{self, BufRead, BufReader, Read, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex},
+};
+
+/// A journal that appends all events to a single file, one JSON record per line.
+///
+/// Appends are written immediately, but the file is only synced to disk after
+/// every [`FileJournal::sync_every`] appends, and when the last clone is dropped.
+/// A torn record at the end of the file, left behind by a crash during a write,
+/// is removed when the file is opened, and by the next append after a failed write.
+///
+/// Deleting events rewrites the whole file without them.
+///
+/// Every event gets an offset, and the journal keeps an index of the offsets and positions
+/// of the events of every tag, so that queries by tag read only their events.
+/// Only the events that are appended with this journal or its clones are followed live.
+///
+/// All clones share the same file.
+#[derive(Clone, Debug)]
+pub struct FileJournal {
+    inner: Arc<Mutex<Inner>>,
+}
+
+#[derive(Debug)]
+struct Inner {
+    path: PathBuf,
+    file: File,
+    highest_sequence_nrs: HashMap<String, u64>,
+    /// The offset of the last appended event.
+    offset: u64,
+    /// The offsets and positions in the file of the events of every tag.
+    tags: HashMap<String, Vec<(u64, u64)>>,
+    /// The length of the file, which is the position of the next event.
+    len: u64,
+    subscribers: Subscribers<String>,
+    sync_every: usize,
+    unsynced: usize,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Record<'a, E> {
+    id: Cow<'a, str>,
+    seq: u64,
+    #[serde(default)]
+    offset: u64,
+    #[serde(default, skip_serializing_if = "<[String]>::is_empty")]
+    tags: Cow<'a, [String]>,
+    event: E,
+}
+
+impl FileJournal {
+    /// Open the journal file, creating it if it does not exist.
+    pub fn open(path: impl AsRef<Path>) -> io::Result<Self> {
+        let path = path.as_ref().to_path_buf();
+        let mut file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .create(true)
+            .open(&path)?;
+
+        let mut contents = Vec::new();
+        let _ = file.read_to_end(&mut contents)?;
+        let complete = contents
+            .iter()
+            .rposition(|b| *b == b'\n')
+            .map_or(0, |pos| pos + 1);
+        if complete < contents.len() {
+            file.set_len(complete as u64)?;
+            file.sync_data()?;
+        }
+
+        let mut inner = Inner {
+            path,
+            file,
+            highest_sequence_nrs: HashMap::new(),
+            offset: 0,
+            tags: HashMap::new(),
+            len: 0,
+            subscribers: Subscribers::default(),
+            sync_every: 1,
+            unsynced: 0,
+        };
+        inner.index(&contents[..complete])?;
+        Ok(Self {
+            inner: Arc::new(Mutex::new(inner)),
+        })
+    }
+
+    /// Sync the file to disk only after every `appends` appends, defaults to 1.
+    ///
+    /// Events of the appends in between are lost if the machine crashes.
+    pub fn sync_every(self, appends: usize) -> Self {
+        self.inner.lock().unwrap().sync_every = appends.max(1);
+        self
+    }
+
+    /// Sync all appended events to disk.
+    pub fn sync(&self) -> io::Result<()> {
+        self.inner.lock().unwrap().sync()
+    }
+
+    /// The sequence number of the last event of the stream, or 0 if there are no events.
+    pub fn highest_sequence_nr(&self, persistence_id: &str) -> u64 {
+        self.inner
+            .lock()
+            .unwrap()
+            .highest_sequence_nrs
+            .get(persistence_id)
+            .copied()
+            .unwrap_or(0)
+    }
+}
+
+impl Inner {
+    /// Index the records of the file.
+    fn index(&mut self, contents: &[u8]) -> serde_json::Result<()> {
+        self.highest_sequence_nrs.clear();
+        self.tags.clear();
+        let mut position = 0;
+        for line in contents.split_inclusive(|b| *b == b'\n') {
+            let record: Record<'_, IgnoredAny> = serde_json::from_slice(line)?;
+            self.offset = self.offset.max(record.offset);
+            for tag in record.tags.iter() {
+                self.tags
+                    .entry(tag.clone())
+                    .or_default()
+                    .push((record.offset, position));
+            }
+            let _ = self
+                .highest_sequence_nrs
+                .insert(record.id.into_owned(), record.seq);
+            position += line.len() as u64;
+        }
+        self.len = contents.len() as u64;
+        Ok(())
+    }
+
+    fn sync(&mut self) -> io::Result<()> {
+        if self.unsynced > 0 {
+            self.file.sync_data()?;
+            self.unsynced = 0;
+        }
+        Ok(())
+    }
+}
+
+impl<E> Journal<E> for FileJournal
+where
+    E: Serialize + DeserializeOwned + 'static,
+{
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr> {
+        self.append_tagged(persistence_id, sequence_nr, events, &[])
+    }
+
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        if events.is_empty() {
+            return Ok(());
+        }
+        let mut inner = self.inner.lock().unwrap();
+        let expected = inner
+            .highest_sequence_nrs
+            .get(persistence_id)
+            .map_or(1, |highest| highest + 1);
+        if sequence_nr != expected {
+            return Err(format!(
+                "Expected sequence number {expected} for {persistence_id}, got {sequence_nr}"
+            )
+            .into());
+        }
+
+        let mut batch = Vec::new();
+        let mut indexed = Vec::new();
+        for (i, (event, seq)) in events.iter().zip(sequence_nr..).enumerate() {
+            let offset = inner.offset + 1 + i as u64;
+            let event_tags = tags.get(i).map_or(&[][..], Vec::as_slice);
+            let record = Record {
+                id: Cow::Borrowed(persistence_id),
+                seq,
+                offset,
+                tags: Cow::Borrowed(event_tags),
+                event,
+            };
+            let position = inner.len + batch.len() as u64;
+            serde_json::to_writer(&mut batch, &record)?;
+            batch.push(b'\n');
+            for tag in event_tags {
+                let live = match inner.subscribers.is_subscribed(tag) {
+                    true => Some(serde_json::to_string(event)?),
+                    false => None,
+                };
+                indexed.push((tag, offset, seq, position, live));
+            }
+        }
+        if let Err(e) = inner.file.write_all(&batch) {
+            // a partial write would end up in front of the next record
+            let _ = inner.file.set_len(inner.len);
+            return Err(e.into());
+        }
+        inner.offset += events.len() as u64;
+        inner.len += batch.len() as u64;
+
+        let highest = sequence_nr + events.len() as u64 - 1;
+        let _ = inner
+            .highest_sequence_nrs
+            .insert(persistence_id.to_owned(), highest);
+
+        let persistence_id = Arc::<str>::from(persistence_id);
+        for (tag, offset, sequence_nr, position, live) in indexed {
+            inner
+                .tags
+                .entry(tag.clone())
+                .or_default()
+                .push((offset, position));
+            if let Some(event) = live {
+                let envelope = EventEnvelope {
+                    offset,
+                    persistence_id: Arc::clone(&persistence_id),
+                    sequence_nr,
+                    event,
+                };
+                inner.subscribers.publish(tag, &envelope);
+            }
+        }
+        inner.unsynced += 1;
+        if inner.unsynced >= inner.sync_every {
+            inner.sync()?;
+        }
+        Ok(())
+    }
+
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr> {
+        let inner = self.inner.lock().unwrap();
+        let file = File::open(&inner.path)?;
+        let mut events = Vec::new();
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+            if line.is_empty() {
+                continue;
+            }
+            let record: Record<'_, serde_json::Value> = serde_json::from_str(&line)?;
+            if record.id == persistence_id && record.seq >= from_sequence_nr {
+                events.push((record.seq, serde_json::from_value(record.event)?));
+            }
+        }
+        Ok(events)
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        let mut inner = self.inner.lock().unwrap();
+        let highest = inner
+            .highest_sequence_nrs
+            .get(persistence_id)
+            .copied()
+            .unwrap_or(0);
+        let to_sequence_nr = to_sequence_nr.min(highest.saturating_sub(1));
+        if to_sequence_nr == 0 {
+            return Ok(());
+        }
+
+        let contents = std::fs::read(&inner.path)?;
+        let mut kept = Vec::with_capacity(contents.len());
+        for line in contents.split_inclusive(|b| *b == b'\n') {
+            let record: Record<'_, IgnoredAny> = serde_json::from_slice(line)?;
+            if record.id != persistence_id || record.seq > to_sequence_nr {
+                kept.extend_from_slice(line);
+            }
+        }
+        if kept.len() == contents.len() {
+            return Ok(());
+        }
+
+        // the old file stays complete until the new one replaces it
+        let mut compacted = inner.path.clone().into_os_string();
+        compacted.push(".compacting");
+        let mut file = File::create(&compacted)?;
+        file.write_all(&kept)?;
+        file.sync_all()?;
+        std::fs::rename(&compacted, &inner.path)?;
+        inner.file = OpenOptions::new()
+            .read(true)
+            .append(true)
+            .open(&inner.path)?;
+        inner.unsynced = 0;
+        inner.index(&kept)?;
+        Ok(())
+    }
+}
+
+impl<E> ReadJournal<E> for FileJournal
+where
+    E: Serialize + DeserializeOwned + 'static,
+{
+    fn subscribe(&self, tag: &str, offset: u64, mut sink: EventSink<E>) -> Result<(), BoxErr> {
+        let mut inner = self.inner.lock().unwrap();
+        let positions = inner.tags.get(tag).map_or(&[][..], Vec::as_slice);
+        let start = positions.partition_point(|(indexed, _)| *indexed <= offset);
+        if start < positions.len() {
+            let mut file = BufReader::new(File::open(&inner.path)?);
+            let mut line = String::new();
+            for (_, position) in &positions[start..] {
+                let _ = file.seek(SeekFrom::Start(*position))?;
+                line.clear();
+                let _ = file.read_line(&mut line)?;
+                let record: Record<'_, E> = serde_json::from_str(&line)?;
+                let envelope = EventEnvelope {
+                    offset: record.offset,
+                    persistence_id: record.id.into(),
+                    sequence_nr: record.seq,
+                    event: record.event,
+                };
+                if sink(envelope) == false {
+                    return Ok(());
+                }
+            }
+        }
+        inner.subscribers.add(tag, json_sink(sink));
+        Ok(())
+    }
+}
+
+impl Drop for Inner {
+    fn drop(&mut self) {
+        let _ = self.sync();
+    }
+}
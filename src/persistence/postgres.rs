@@ -0,0 +1,480 @@
+use super::{json_sink, EventEnvelope, EventSink, Subscribers};
+use crate::{BoxErr, Journal, OffsetStore, ReadJournal};
+use serde::{de::DeserializeOwned, Serialize};
+use sqlx::postgres::PgPool;
+use std::{
+    collections::HashMap,
+    future::Future,
+    sync::{mpsc as sync, Arc},
+    thread,
+};
+use tokio::{runtime::Handle, sync::mpsc};
+
+/// How many appends are written in one transaction at most.
+const MAX_BATCH: usize = 256;
+
+/// A journal that stores events as JSON in a Postgres database, with sqlx.
+///
+/// Appends that arrive while a batch is written are written together in the next transaction,
+/// with one insert for all of their events. Every append is still checked on its own, a conflicting
+/// append fails without failing the others of its batch. The primary key on persistence id and
+/// sequence number also rejects the conflicting appends of other journals.
+///
+/// Every event gets an offset. The batches of all journals take turns with a lock on the `journal`
+/// table, so the offsets have no gaps and are committed in order. The tags of the events are in the
+/// `journal_tags` table, deleted events are no longer found by tag. Only the events that are appended
+/// with this journal or its clones are followed live, a query that starts later also finds the events
+/// of other journals.
+///
+/// It is also an [`OffsetStore`] for the projections of its events, in the `projection_offsets` table.
+///
+/// The queries run on a runtime of their own, every call blocks until Postgres has answered.
+/// All clones share the same connection pool, which is closed with the last clone.
+#[derive(Clone)]
+pub struct PostgresJournal {
+    pool: PgPool,
+    runtime: Handle,
+    writes: mpsc::UnboundedSender<Write>,
+}
+
+impl PostgresJournal {
+    /// Connect to the database at `url`, e.g. `postgres://localhost/elliot`,
+    /// creating the `journal` table if it does not exist.
+    pub fn connect(url: &str) -> sqlx::Result<Self> {
+        let url = url.to_owned();
+        let (started, start) = sync::channel();
+        let _thread = thread::Builder::new()
+            .name("elliot-postgres".to_owned())
+            .spawn(move || {
+                let runtime = match tokio::runtime::Builder::new_current_thread()
+                    .enable_all()
+                    .build()
+                {
+                    Ok(runtime) => runtime,
+                    Err(e) => {
+                        let _ = started.send(Err(sqlx::Error::Io(e)));
+                        return;
+                    }
+                };
+                runtime.block_on(async move {
+                    let pool = match setup(&url).await {
+                        Ok(pool) => pool,
+                        Err(e) => {
+                            let _ = started.send(Err(e));
+                            return;
+                        }
+                    };
+                    let (writes, rx) = mpsc::unbounded_channel();
+                    let _ = started.send(Ok((pool.clone(), Handle::current(), writes)));
+                    // runs until the last clone is dropped
+                    writer(pool, rx).await;
+                });
+            })
+            .map_err(sqlx::Error::Io)?;
+        let (pool, runtime, writes) = start.recv().map_err(|_| sqlx::Error::PoolClosed)??;
+        Ok(Self {
+            pool,
+            runtime,
+            writes,
+        })
+    }
+
+    /// The sequence number of the last event of the stream, or 0 if there are no events.
+    pub fn highest_sequence_nr(&self, persistence_id: &str) -> sqlx::Result<u64> {
+        let pool = self.pool.clone();
+        let persistence_id = persistence_id.to_owned();
+        self.run(async move {
+            let highest: i64 = sqlx::query_scalar(
+                "SELECT COALESCE(MAX(sequence_nr), 0) FROM journal WHERE persistence_id = $1",
+            )
+            .bind(persistence_id)
+            .fetch_one(&pool)
+            .await?;
+            Ok(highest as u64)
+        })
+    }
+
+    /// Run `query` on the runtime of the journal and wait for its result.
+    fn run<T: Send + 'static>(
+        &self,
+        query: impl Future<Output = sqlx::Result<T>> + Send + 'static,
+    ) -> sqlx::Result<T> {
+        let (done, result) = sync::sync_channel(1);
+        let _handle = self.runtime.spawn(async move {
+            let _ = done.send(query.await);
+        });
+        result.recv().map_err(|_| sqlx::Error::PoolClosed)?
+    }
+
+    /// Hand `write` to the writer and wait until it is done.
+    fn write(
+        &self,
+        write: impl FnOnce(sync::SyncSender<Result<(), BoxErr>>) -> Write,
+    ) -> Result<(), BoxErr> {
+        let (done, result) = sync::sync_channel(1);
+        self.writes
+            .send(write(done))
+            .map_err(|_| sqlx::Error::PoolClosed)?;
+        result.recv().map_err(|_| sqlx::Error::PoolClosed)?
+    }
+}
+
+async fn setup(url: &str) -> sqlx::Result<PgPool> {
+    let pool = PgPool::connect(url).await?;
+    let _ = sqlx::raw_sql(
+        "CREATE TABLE IF NOT EXISTS journal (
+             persistence_id TEXT NOT NULL,
+             sequence_nr BIGINT NOT NULL,
+             ordering BIGINT NOT NULL UNIQUE,
+             event JSONB NOT NULL,
+             PRIMARY KEY (persistence_id, sequence_nr)
+         );
+         CREATE TABLE IF NOT EXISTS journal_tags (
+             tag TEXT NOT NULL,
+             ordering BIGINT NOT NULL,
+             PRIMARY KEY (tag, ordering)
+         );
+         CREATE TABLE IF NOT EXISTS projection_offsets (
+             projection TEXT PRIMARY KEY,
+             ordering BIGINT NOT NULL
+         );",
+    )
+    .execute(&pool)
+    .await?;
+    Ok(pool)
+}
+
+enum Write {
+    Append(Append),
+    Subscribe {
+        tag: String,
+        offset: u64,
+        sink: EventSink<String>,
+        done: sync::SyncSender<Result<(), BoxErr>>,
+    },
+}
+
+struct Append {
+    persistence_id: String,
+    sequence_nr: u64,
+    events: Vec<String>,
+    tags: Vec<Vec<String>>,
+    done: sync::SyncSender<Result<(), BoxErr>>,
+}
+
+/// Writes the appends in batches and follows the tags, in the order in which they arrive.
+async fn writer(pool: PgPool, mut writes: mpsc::UnboundedReceiver<Write>) {
+    let mut subscribers = Subscribers::default();
+    let mut batch = Vec::with_capacity(MAX_BATCH);
+    let mut appends = Vec::new();
+    while writes.recv_many(&mut batch, MAX_BATCH).await > 0 {
+        for write in batch.drain(..) {
+            match write {
+                Write::Append(append) => appends.push(append),
+                Write::Subscribe {
+                    tag,
+                    offset,
+                    sink,
+                    done,
+                } => {
+                    // the query finds the appends that arrived before, the later ones are followed live
+                    append(&pool, &mut appends, &mut subscribers).await;
+                    let subscribed = subscribe(&pool, &tag, offset, sink, &mut subscribers).await;
+                    let _ = done.send(subscribed);
+                }
+            }
+        }
+        append(&pool, &mut appends, &mut subscribers).await;
+    }
+}
+
+/// Write the appends in one transaction, and publish their tagged events.
+async fn append(pool: &PgPool, appends: &mut Vec<Append>, subscribers: &mut Subscribers<String>) {
+    if appends.is_empty() {
+        return;
+    }
+    let offsets = match insert(pool, appends).await {
+        Ok(offsets) => offsets,
+        Err(e) => {
+            let e = e.to_string();
+            for append in appends.drain(..) {
+                let _ = append.done.send(Err(e.clone().into()));
+            }
+            return;
+        }
+    };
+    for (append, offset) in appends.drain(..).zip(offsets) {
+        let first = match offset {
+            Ok(first) => first,
+            Err(e) => {
+                let _ = append.done.send(Err(e));
+                continue;
+            }
+        };
+        let persistence_id = Arc::<str>::from(append.persistence_id);
+        for (i, event) in append.events.into_iter().enumerate() {
+            for tag in append.tags.get(i).into_iter().flatten() {
+                let envelope = EventEnvelope {
+                    offset: first + i as u64,
+                    persistence_id: Arc::clone(&persistence_id),
+                    sequence_nr: append.sequence_nr + i as u64,
+                    event: event.clone(),
+                };
+                subscribers.publish(tag, &envelope);
+            }
+        }
+        let _ = append.done.send(Ok(()));
+    }
+}
+
+/// Insert the events of the appends that continue their streams, with one statement for all events
+/// and one for all tags. Returns the offset of the first event of every append, or why it was rejected.
+async fn insert(pool: &PgPool, appends: &[Append]) -> sqlx::Result<Vec<Result<u64, BoxErr>>> {
+    let mut transaction = pool.begin().await?;
+    // one batch at a time, also of other journals, so that the offsets have no gaps and are committed in order
+    let _ = sqlx::query("LOCK TABLE journal IN SHARE ROW EXCLUSIVE MODE")
+        .execute(&mut *transaction)
+        .await?;
+    let ids: Vec<&str> = appends.iter().map(|a| a.persistence_id.as_str()).collect();
+    let mut highest: HashMap<String, i64> = sqlx::query_as(
+        "SELECT persistence_id, MAX(sequence_nr) FROM journal
+         WHERE persistence_id = ANY($1)
+         GROUP BY persistence_id",
+    )
+    .bind(ids)
+    .fetch_all(&mut *transaction)
+    .await?
+    .into_iter()
+    .collect();
+    // the last event of a stream is never deleted, so the highest offset stays in the table
+    let mut offset: i64 = sqlx::query_scalar("SELECT COALESCE(MAX(ordering), 0) FROM journal")
+        .fetch_one(&mut *transaction)
+        .await?;
+
+    let mut firsts = Vec::with_capacity(appends.len());
+    let mut rows = (Vec::new(), Vec::new(), Vec::new(), Vec::new());
+    let mut tags = (Vec::new(), Vec::new());
+    for append in appends {
+        let highest = highest.entry(append.persistence_id.clone()).or_insert(0);
+        if append.sequence_nr != *highest as u64 + 1 {
+            firsts.push(Err(format!(
+                "Expected sequence number {} for {}, got {}",
+                *highest + 1,
+                append.persistence_id,
+                append.sequence_nr
+            )
+            .into()));
+            continue;
+        }
+        firsts.push(Ok(offset as u64 + 1));
+        for (i, (event, seq)) in append
+            .events
+            .iter()
+            .zip(append.sequence_nr as i64..)
+            .enumerate()
+        {
+            offset += 1;
+            *highest = seq;
+            rows.0.push(append.persistence_id.as_str());
+            rows.1.push(seq);
+            rows.2.push(offset);
+            rows.3.push(event.as_str());
+            for tag in append.tags.get(i).into_iter().flatten() {
+                tags.0.push(tag.as_str());
+                tags.1.push(offset);
+            }
+        }
+    }
+
+    if rows.0.is_empty() == false {
+        let _ = sqlx::query(
+            "INSERT INTO journal (persistence_id, sequence_nr, ordering, event)
+             SELECT persistence_id, sequence_nr, ordering, event::jsonb
+             FROM UNNEST($1::text[], $2::bigint[], $3::bigint[], $4::text[])
+                 AS appended (persistence_id, sequence_nr, ordering, event)",
+        )
+        .bind(rows.0)
+        .bind(rows.1)
+        .bind(rows.2)
+        .bind(rows.3)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    if tags.0.is_empty() == false {
+        let _ = sqlx::query(
+            "INSERT INTO journal_tags (tag, ordering)
+             SELECT * FROM UNNEST($1::text[], $2::bigint[])",
+        )
+        .bind(tags.0)
+        .bind(tags.1)
+        .execute(&mut *transaction)
+        .await?;
+    }
+    transaction.commit().await?;
+    Ok(firsts)
+}
+
+/// Pass the events of `tag` after `offset` to the sink, and then follow the tag.
+async fn subscribe(
+    pool: &PgPool,
+    tag: &str,
+    offset: u64,
+    mut sink: EventSink<String>,
+    subscribers: &mut Subscribers<String>,
+) -> Result<(), BoxErr> {
+    let rows: Vec<(i64, String, i64, String)> = sqlx::query_as(
+        "SELECT journal.ordering, journal.persistence_id, journal.sequence_nr, journal.event::text
+         FROM journal_tags JOIN journal ON journal.ordering = journal_tags.ordering
+         WHERE journal_tags.tag = $1 AND journal_tags.ordering > $2
+         ORDER BY journal_tags.ordering",
+    )
+    .bind(tag)
+    .bind(offset as i64)
+    .fetch_all(pool)
+    .await?;
+    for (offset, persistence_id, sequence_nr, event) in rows {
+        let envelope = EventEnvelope {
+            offset: offset as u64,
+            persistence_id: persistence_id.into(),
+            sequence_nr: sequence_nr as u64,
+            event,
+        };
+        if sink(envelope) == false {
+            return Ok(());
+        }
+    }
+    subscribers.add(tag, sink);
+    Ok(())
+}
+
+impl<E> Journal<E> for PostgresJournal
+where
+    E: Serialize + DeserializeOwned + 'static,
+{
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr> {
+        self.append_tagged(persistence_id, sequence_nr, events, &[])
+    }
+
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let events = events
+            .iter()
+            .map(serde_json::to_string)
+            .collect::<Result<_, _>>()?;
+        self.write(|done| {
+            Write::Append(Append {
+                persistence_id: persistence_id.to_owned(),
+                sequence_nr,
+                events,
+                tags: tags.to_vec(),
+                done,
+            })
+        })
+    }
+
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr> {
+        let pool = self.pool.clone();
+        let persistence_id = persistence_id.to_owned();
+        let rows: Vec<(i64, String)> = self.run(async move {
+            sqlx::query_as(
+                "SELECT sequence_nr, event::text FROM journal
+                 WHERE persistence_id = $1 AND sequence_nr >= $2
+                 ORDER BY sequence_nr",
+            )
+            .bind(persistence_id)
+            .bind(from_sequence_nr as i64)
+            .fetch_all(&pool)
+            .await
+        })?;
+        rows.into_iter()
+            .map(|(seq, event)| Ok((seq as u64, serde_json::from_str(&event)?)))
+            .collect()
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        let pool = self.pool.clone();
+        let persistence_id = persistence_id.to_owned();
+        self.run(async move {
+            let mut transaction = pool.begin().await?;
+            let _deleted = sqlx::query(
+                "DELETE FROM journal_tags WHERE ordering IN (
+                     SELECT ordering FROM journal
+                     WHERE persistence_id = $1 AND sequence_nr <= $2 AND sequence_nr < (
+                         SELECT MAX(sequence_nr) FROM journal WHERE persistence_id = $1
+                     )
+                 )",
+            )
+            .bind(&persistence_id)
+            .bind(to_sequence_nr as i64)
+            .execute(&mut *transaction)
+            .await?;
+            let _deleted = sqlx::query(
+                "DELETE FROM journal
+                 WHERE persistence_id = $1 AND sequence_nr <= $2 AND sequence_nr < (
+                     SELECT MAX(sequence_nr) FROM journal WHERE persistence_id = $1
+                 )",
+            )
+            .bind(&persistence_id)
+            .bind(to_sequence_nr as i64)
+            .execute(&mut *transaction)
+            .await?;
+            transaction.commit().await
+        })?;
+        Ok(())
+    }
+}
+
+impl<E> ReadJournal<E> for PostgresJournal
+where
+    E: Serialize + DeserializeOwned + 'static,
+{
+    fn subscribe(&self, tag: &str, offset: u64, sink: EventSink<E>) -> Result<(), BoxErr> {
+        self.write(|done| Write::Subscribe {
+            tag: tag.to_owned(),
+            offset,
+            sink: json_sink(sink),
+            done,
+        })
+    }
+}
+
+impl OffsetStore for PostgresJournal {
+    fn save(&self, projection: &str, offset: u64) -> Result<(), BoxErr> {
+        let pool = self.pool.clone();
+        let projection = projection.to_owned();
+        let _saved = self.run(async move {
+            sqlx::query(
+                "INSERT INTO projection_offsets (projection, ordering) VALUES ($1, $2)
+                 ON CONFLICT (projection) DO UPDATE SET ordering = excluded.ordering",
+            )
+            .bind(projection)
+            .bind(offset as i64)
+            .execute(&pool)
+            .await
+        })?;
+        Ok(())
+    }
+
+    fn load(&self, projection: &str) -> Result<Option<u64>, BoxErr> {
+        let pool = self.pool.clone();
+        let projection = projection.to_owned();
+        let offset: Option<i64> = self.run(async move {
+            sqlx::query_scalar("SELECT ordering FROM projection_offsets WHERE projection = $1")
+                .bind(projection)
+                .fetch_optional(&pool)
+                .await
+        })?;
+        Ok(offset.map(|offset| offset as u64))
+    }
+}
+
+impl std::fmt::Debug for PostgresJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("PostgresJournal").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,187 @@
+use super::{EventEnvelope, EventsByTag, ReadJournal};
+use crate::BoxErr;
+use std::{
+    collections::HashMap,
+    future::Future,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc::error::TryRecvError;
+
+type Query<E> = Box<dyn Fn(u64) -> Result<EventsByTag<E>, BoxErr> + Send + Sync>;
+type Handler<E> = Box<
+    dyn Fn(EventEnvelope<E>) -> Pin<Box<dyn Future<Output = Result<(), BoxErr>> + Send>>
+        + Send
+        + Sync,
+>;
+
+/// Storage for the offsets of projections, see [`Projection`].
+pub trait OffsetStore: Send + Sync + 'static {
+    /// Save the offset of the last event that the projection has handled.
+    fn save(&self, projection: &str, offset: u64) -> Result<(), BoxErr>;
+
+    /// The last saved offset of the projection, or `None` if it has not saved one yet.
+    fn load(&self, projection: &str) -> Result<Option<u64>, BoxErr>;
+}
+
+/// An offset store that keeps all offsets in memory, meant for tests.
+///
+/// All clones share the same offsets.
+#[derive(Debug, Default, Clone)]
+pub struct InMemoryOffsetStore {
+    offsets: Arc<Mutex<HashMap<String, u64>>>,
+}
+
+impl InMemoryOffsetStore {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl OffsetStore for InMemoryOffsetStore {
+    fn save(&self, projection: &str, offset: u64) -> Result<(), BoxErr> {
+        let _ = self
+            .offsets
+            .lock()
+            .unwrap()
+            .insert(projection.to_owned(), offset);
+        Ok(())
+    }
+
+    fn load(&self, projection: &str) -> Result<Option<u64>, BoxErr> {
+        Ok(self.offsets.lock().unwrap().get(projection).copied())
+    }
+}
+
+/// Feeds the events of a tag to a handler, e.g. to keep a read model in a database up to date.
+///
+/// The projection remembers how far it got in an [`OffsetStore`] under its name,
+/// and resumes after that offset when it runs again, e.g. after a restart.
+pub struct Projection<E> {
+    name: Arc<str>,
+    query: Query<E>,
+    offsets: Arc<dyn OffsetStore>,
+    handler: Handler<E>,
+    /// `None` if the handler saves the offsets.
+    save_every: Option<u64>,
+}
+
+impl<E: Send + 'static> Projection<E> {
+    /// Handle every event at least once, the offset is saved after the handler succeeded.
+    ///
+    /// Events that were handled, but whose offset was not saved when the projection stopped,
+    /// are handled again when it runs again.
+    pub fn at_least_once<H, F>(
+        name: impl Into<Arc<str>>,
+        journal: impl ReadJournal<E>,
+        tag: impl Into<String>,
+        offsets: impl OffsetStore,
+        handler: H,
+    ) -> Self
+    where
+        H: Fn(EventEnvelope<E>) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), BoxErr>> + Send + 'static,
+    {
+        let mut projection = Self::exactly_once(name, journal, tag, offsets, handler);
+        projection.save_every = Some(1);
+        projection
+    }
+
+    /// Handle every event exactly once, with a handler that saves the offset of every event
+    /// in the same transaction as its effects, e.g. in the database that it updates.
+    ///
+    /// `offsets` reads from where the handler saves the offsets, the projection only loads them.
+    pub fn exactly_once<H, F>(
+        name: impl Into<Arc<str>>,
+        journal: impl ReadJournal<E>,
+        tag: impl Into<String>,
+        offsets: impl OffsetStore,
+        handler: H,
+    ) -> Self
+    where
+        H: Fn(EventEnvelope<E>) -> F + Send + Sync + 'static,
+        F: Future<Output = Result<(), BoxErr>> + Send + 'static,
+    {
+        let tag = tag.into();
+        Self {
+            name: name.into(),
+            query: Box::new(move |offset| journal.events_by_tag_from(&tag, offset)),
+            offsets: Arc::new(offsets),
+            handler: Box::new(move |envelope| Box::pin(handler(envelope))),
+            save_every: None,
+        }
+    }
+
+    /// Save the offset only after every `events` events and when the projection has caught up,
+    /// which handles more events again after a restart, defaults to 1.
+    ///
+    /// Has no effect on projections that handle events exactly once.
+    pub fn save_every(mut self, events: u64) -> Self {
+        if self.save_every.is_some() {
+            self.save_every = Some(events.max(1));
+        }
+        self
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Handle the events after the saved offset, and then the events as they are persisted.
+    ///
+    /// Runs until the future is dropped or the events end, or fails as soon as the journal,
+    /// the handler or the offset store fails. The projection can run again, to retry the failed event.
+    pub async fn run(&self) -> Result<(), BoxErr> {
+        let offset = self.offsets.load(&self.name)?.unwrap_or(0);
+        let mut events = (self.query)(offset)?;
+        let mut unsaved = Unsaved { offset, events: 0 };
+        loop {
+            let envelope = match events.rx.try_recv() {
+                Ok(envelope) => envelope,
+                Err(TryRecvError::Empty) => {
+                    // caught up, until the next event is persisted
+                    self.save(&mut unsaved, 1)?;
+                    match events.recv().await {
+                        Some(envelope) => envelope,
+                        None => return Ok(()),
+                    }
+                }
+                Err(TryRecvError::Disconnected) => {
+                    self.save(&mut unsaved, 1)?;
+                    return Ok(());
+                }
+            };
+            let offset = envelope.offset;
+            (self.handler)(envelope).await?;
+            unsaved.offset = offset;
+            unsaved.events += 1;
+            if let Some(every) = self.save_every {
+                self.save(&mut unsaved, every)?;
+            }
+        }
+    }
+
+    fn save(&self, unsaved: &mut Unsaved, every: u64) -> Result<(), BoxErr> {
+        if self.save_every.is_none() || unsaved.events == 0 || unsaved.events < every {
+            return Ok(());
+        }
+        self.offsets.save(&self.name, unsaved.offset)?;
+        unsaved.events = 0;
+        Ok(())
+    }
+}
+
+/// The offset of the last handled event, and how many events were handled since it was saved.
+struct Unsaved {
+    offset: u64,
+    events: u64,
+}
+
+impl<E> std::fmt::Debug for Projection<E> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Projection")
+            .field("name", &self.name)
+            .field("save_every", &self.save_every)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,285 @@
+use super::{EventEnvelope, EventSink, Subscribers};
+use crate::{BoxErr, Journal, ReadJournal, SnapshotStore};
+use redis::{Client, Connection, RedisResult};
+use std::sync::{Arc, Mutex};
+
+/// Appends the events to the stream in `KEYS[1]`, if `ARGV[1]` is the next sequence number,
+/// and returns their offsets, which are counted in `KEYS[2]`.
+///
+/// `ARGV[2]` is the persistence id and `ARGV[3]` the prefix of the keys of the tags. Every event
+/// is followed by the number of its tags and the tags, it is also added to the stream of every tag.
+const APPEND: &str = r"
+local last = redis.call('XREVRANGE', KEYS[1], '+', '-', 'COUNT', 1)
+local highest = 0
+if #last > 0 then
+    highest = tonumber(string.match(last[1][1], '%-(%d+)$'))
+end
+local sequence_nr = tonumber(ARGV[1])
+if sequence_nr ~= highest + 1 then
+    return redis.error_reply('Expected sequence number ' .. (highest + 1) .. ', got ' .. ARGV[1])
+end
+local offsets = {}
+local i = 4
+while i <= #ARGV do
+    local event = ARGV[i]
+    local tags = tonumber(ARGV[i + 1])
+    local offset = redis.call('INCR', KEYS[2])
+    redis.call('XADD', KEYS[1], '0-' .. sequence_nr, 'event', event)
+    for t = 1, tags do
+        redis.call('XADD', ARGV[3] .. ARGV[i + 1 + t], '0-' .. offset,
+            'persistence_id', ARGV[2], 'sequence_nr', sequence_nr, 'event', event)
+    end
+    offsets[#offsets + 1] = offset
+    sequence_nr = sequence_nr + 1
+    i = i + 2 + tags
+end
+return offsets
+";
+
+/// Deletes the events up to `ARGV[1]` from the stream in `KEYS[1]`, except for the last one.
+const DELETE_TO: &str = r"
+local last = redis.call('XREVRANGE', KEYS[1], '+', '-', 'COUNT', 1)
+if #last == 0 then
+    return 0
+end
+local highest = tonumber(string.match(last[1][1], '%-(%d+)$'))
+local keep = math.min(tonumber(ARGV[1]) + 1, highest)
+return redis.call('XTRIM', KEYS[1], 'MINID', '0-' .. keep)
+";
+
+/// A journal that stores the events of every persistence id in a Redis stream, as bytes.
+///
+/// The stream ids are the sequence numbers, `0-<sequence_nr>`. Every append is a single script,
+/// which rejects conflicting appends. Wrap it in a [`CodecJournal`](crate::CodecJournal) for typed events.
+///
+/// Tagged events are also added to a stream for every tag, whose ids are the offsets of the events,
+/// `0-<offset>`. Deleted events stay in the streams of their tags. Only the events that are appended
+/// with this journal or its clones are followed live, a query that starts later also finds the events
+/// of other connections.
+///
+/// All clones share the same connection, every call blocks until Redis has answered.
+/// [`EventSourced`](crate::EventSourced) actors call it on a blocking thread.
+#[derive(Clone)]
+pub struct RedisJournal {
+    connection: Arc<Mutex<Connection>>,
+    subscribers: Arc<Mutex<Subscribers<Vec<u8>>>>,
+    prefix: Arc<str>,
+}
+
+impl RedisJournal {
+    pub fn connect(client: &Client) -> RedisResult<Self> {
+        Ok(Self {
+            connection: Arc::new(Mutex::new(client.get_connection()?)),
+            subscribers: Arc::default(),
+            prefix: "elliot".into(),
+        })
+    }
+
+    /// Prefix the keys of the streams, which are `<prefix>:journal:<persistence_id>` and
+    /// `<prefix>:tag:<tag>`, defaults to `elliot`.
+    pub fn prefix(mut self, prefix: impl Into<Arc<str>>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, persistence_id: &str) -> String {
+        format!("{}:journal:{persistence_id}", self.prefix)
+    }
+
+    fn tag_key(&self, tag: &str) -> String {
+        format!("{}:tag:{tag}", self.prefix)
+    }
+}
+
+impl Journal<Vec<u8>> for RedisJournal {
+    fn append(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[Vec<u8>],
+    ) -> Result<(), BoxErr> {
+        self.append_tagged(persistence_id, sequence_nr, events, &[])
+    }
+
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[Vec<u8>],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let mut append = redis::cmd("EVAL");
+        let _ = append
+            .arg(APPEND)
+            .arg(2)
+            .arg(self.key(persistence_id))
+            .arg(format!("{}:offset", self.prefix))
+            .arg(sequence_nr)
+            .arg(persistence_id)
+            .arg(self.tag_key(""));
+        for (i, event) in events.iter().enumerate() {
+            let event_tags = tags.get(i).map_or(&[][..], Vec::as_slice);
+            let _ = append
+                .arg(event.as_slice())
+                .arg(event_tags.len())
+                .arg(event_tags);
+        }
+        let mut connection = self.connection.lock().unwrap();
+        let offsets: Vec<u64> = append.query(&mut *connection)?;
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let persistence_id = Arc::<str>::from(persistence_id);
+        for (i, (event, offset)) in events.iter().zip(offsets).enumerate() {
+            for tag in tags.get(i).into_iter().flatten() {
+                let envelope = EventEnvelope {
+                    offset,
+                    persistence_id: Arc::clone(&persistence_id),
+                    sequence_nr: sequence_nr + i as u64,
+                    event: event.clone(),
+                };
+                subscribers.publish(tag, &envelope);
+            }
+        }
+        Ok(())
+    }
+
+    fn replay(
+        &self,
+        persistence_id: &str,
+        from_sequence_nr: u64,
+    ) -> Result<Vec<(u64, Vec<u8>)>, BoxErr> {
+        let entries: Vec<(String, Vec<Vec<u8>>)> = redis::cmd("XRANGE")
+            .arg(self.key(persistence_id))
+            .arg(format!("0-{from_sequence_nr}"))
+            .arg("+")
+            .query(&mut *self.connection.lock().unwrap())?;
+        entries
+            .into_iter()
+            .map(|(id, mut fields)| {
+                let sequence_nr = id
+                    .split_once('-')
+                    .and_then(|(_, sequence_nr)| sequence_nr.parse().ok())
+                    .ok_or_else(|| format!("Invalid stream id {id} for {persistence_id}"))?;
+                let event = fields
+                    .pop()
+                    .ok_or_else(|| format!("Missing event {id} for {persistence_id}"))?;
+                Ok((sequence_nr, event))
+            })
+            .collect()
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        let _deleted: u64 = redis::cmd("EVAL")
+            .arg(DELETE_TO)
+            .arg(1)
+            .arg(self.key(persistence_id))
+            .arg(to_sequence_nr)
+            .query(&mut *self.connection.lock().unwrap())?;
+        Ok(())
+    }
+}
+
+impl ReadJournal<Vec<u8>> for RedisJournal {
+    fn subscribe(
+        &self,
+        tag: &str,
+        offset: u64,
+        mut sink: EventSink<Vec<u8>>,
+    ) -> Result<(), BoxErr> {
+        // appends wait for the connection, so no event is missed between the query and the subscription
+        let mut connection = self.connection.lock().unwrap();
+        let entries: Vec<(String, Vec<Vec<u8>>)> = redis::cmd("XRANGE")
+            .arg(self.tag_key(tag))
+            .arg(format!("0-{}", offset + 1))
+            .arg("+")
+            .query(&mut *connection)?;
+        for (id, fields) in entries {
+            let invalid = || format!("Invalid entry {id} of tag {tag}");
+            let [_, persistence_id, _, sequence_nr, _, event] =
+                <[Vec<u8>; 6]>::try_from(fields).map_err(|_| invalid())?;
+            let envelope = EventEnvelope {
+                offset: id
+                    .split_once('-')
+                    .and_then(|(_, offset)| offset.parse().ok())
+                    .ok_or_else(invalid)?,
+                persistence_id: String::from_utf8(persistence_id)?.into(),
+                sequence_nr: std::str::from_utf8(&sequence_nr)?.parse()?,
+                event,
+            };
+            if sink(envelope) == false {
+                return Ok(());
+            }
+        }
+        self.subscribers.lock().unwrap().add(tag, sink);
+        Ok(())
+    }
+}
+
+impl std::fmt::Debug for RedisJournal {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisJournal")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A snapshot store that keeps the latest snapshot of every persistence id in a Redis hash, as bytes.
+///
+/// The sequence number and the state are written together. Wrap it in a
+/// [`CodecSnapshotStore`](crate::CodecSnapshotStore) for typed snapshots.
+///
+/// All clones share the same connection.
+#[derive(Clone)]
+pub struct RedisSnapshotStore {
+    connection: Arc<Mutex<Connection>>,
+    prefix: Arc<str>,
+}
+
+impl RedisSnapshotStore {
+    pub fn connect(client: &Client) -> RedisResult<Self> {
+        Ok(Self {
+            connection: Arc::new(Mutex::new(client.get_connection()?)),
+            prefix: "elliot".into(),
+        })
+    }
+
+    /// Prefix the keys of the hashes, which are `<prefix>:snapshot:<persistence_id>`, defaults to `elliot`.
+    pub fn prefix(mut self, prefix: impl Into<Arc<str>>) -> Self {
+        self.prefix = prefix.into();
+        self
+    }
+
+    fn key(&self, persistence_id: &str) -> String {
+        format!("{}:snapshot:{persistence_id}", self.prefix)
+    }
+}
+
+impl SnapshotStore<Vec<u8>> for RedisSnapshotStore {
+    fn save(&self, persistence_id: &str, sequence_nr: u64, state: &Vec<u8>) -> Result<(), BoxErr> {
+        redis::cmd("HSET")
+            .arg(self.key(persistence_id))
+            .arg("sequence_nr")
+            .arg(sequence_nr)
+            .arg("state")
+            .arg(state.as_slice())
+            .exec(&mut *self.connection.lock().unwrap())?;
+        Ok(())
+    }
+
+    fn load(&self, persistence_id: &str) -> Result<Option<(u64, Vec<u8>)>, BoxErr> {
+        let (sequence_nr, state): (Option<u64>, Option<Vec<u8>>) = redis::cmd("HMGET")
+            .arg(self.key(persistence_id))
+            .arg("sequence_nr")
+            .arg("state")
+            .query(&mut *self.connection.lock().unwrap())?;
+        Ok(sequence_nr.zip(state))
+    }
+}
+
+impl std::fmt::Debug for RedisSnapshotStore {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisSnapshotStore")
+            .field("prefix", &self.prefix)
+            .finish_non_exhaustive()
+    }
+}
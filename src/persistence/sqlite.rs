@@ -0,0 +1,247 @@
+use super::{json_sink, EventEnvelope, EventSink, Subscribers};
+use crate::{BoxErr, Journal, OffsetStore, ReadJournal};
+use rusqlite::{params, Connection, OptionalExtension};
+use serde::{de::DeserializeOwned, Serialize};
+use std::{
+    path::Path,
+    sync::{Arc, Mutex},
+};
+
+/// A journal that stores events as JSON in a SQLite database.
+///
+/// Every append is a single transaction, the database is in WAL mode.
+/// The primary key on persistence id and sequence number rejects conflicting appends.
+///
+/// Every event gets an offset, the tags of the events are in the `journal_tags` table,
+/// whose primary key is the index for the queries by tag. Deleted events are no longer found by tag.
+/// Only the events that are appended with this journal or its clones are followed live,
+/// a query that starts later also finds the events of other connections.
+///
+/// It is also an [`OffsetStore`] for the projections of its events, in the `projection_offsets` table.
+///
+/// All clones share the same connection.
+#[derive(Clone, Debug)]
+pub struct SqliteJournal {
+    connection: Arc<Mutex<Connection>>,
+    subscribers: Arc<Mutex<Subscribers<String>>>,
+}
+
+impl SqliteJournal {
+    /// Open the database, creating it and the `journal` table if they do not exist.
+    pub fn open(path: impl AsRef<Path>) -> rusqlite::Result<Self> {
+        Self::new(Connection::open(path)?)
+    }
+
+    /// A journal in a private in-memory database.
+    pub fn in_memory() -> rusqlite::Result<Self> {
+        Self::new(Connection::open_in_memory()?)
+    }
+
+    fn new(connection: Connection) -> rusqlite::Result<Self> {
+        let _mode: String =
+            connection.query_row("PRAGMA journal_mode = WAL", [], |row| row.get(0))?;
+        connection.execute_batch(
+            "PRAGMA synchronous = NORMAL;
+             CREATE TABLE IF NOT EXISTS journal (
+                 persistence_id TEXT NOT NULL,
+                 sequence_nr INTEGER NOT NULL,
+                 ordering INTEGER NOT NULL UNIQUE,
+                 event TEXT NOT NULL,
+                 PRIMARY KEY (persistence_id, sequence_nr)
+             );
+             CREATE TABLE IF NOT EXISTS journal_tags (
+                 tag TEXT NOT NULL,
+                 ordering INTEGER NOT NULL,
+                 PRIMARY KEY (tag, ordering)
+             ) WITHOUT ROWID;
+             CREATE TABLE IF NOT EXISTS projection_offsets (
+                 projection TEXT PRIMARY KEY,
+                 ordering INTEGER NOT NULL
+             );",
+        )?;
+        Ok(Self {
+            connection: Arc::new(Mutex::new(connection)),
+            subscribers: Arc::default(),
+        })
+    }
+
+    /// The sequence number of the last event of the stream, or 0 if there are no events.
+    pub fn highest_sequence_nr(&self, persistence_id: &str) -> rusqlite::Result<u64> {
+        self.connection.lock().unwrap().query_row(
+            "SELECT COALESCE(MAX(sequence_nr), 0) FROM journal WHERE persistence_id = ?1",
+            [persistence_id],
+            |row| row.get::<_, i64>(0).map(|highest| highest as u64),
+        )
+    }
+}
+
+impl<E> Journal<E> for SqliteJournal
+where
+    E: Serialize + DeserializeOwned + 'static,
+{
+    fn append(&self, persistence_id: &str, sequence_nr: u64, events: &[E]) -> Result<(), BoxErr> {
+        self.append_tagged(persistence_id, sequence_nr, events, &[])
+    }
+
+    fn append_tagged(
+        &self,
+        persistence_id: &str,
+        sequence_nr: u64,
+        events: &[E],
+        tags: &[Vec<String>],
+    ) -> Result<(), BoxErr> {
+        let mut connection = self.connection.lock().unwrap();
+        let highest: u64 = connection.query_row(
+            "SELECT COALESCE(MAX(sequence_nr), 0) FROM journal WHERE persistence_id = ?1",
+            [persistence_id],
+            |row| row.get::<_, i64>(0).map(|highest| highest as u64),
+        )?;
+        if sequence_nr != highest + 1 {
+            return Err(format!(
+                "Expected sequence number {} for {persistence_id}, got {sequence_nr}",
+                highest + 1
+            )
+            .into());
+        }
+
+        let transaction = connection.transaction()?;
+        // the last event of a stream is never deleted, so the highest offset stays in the table
+        let highest: u64 = transaction.query_row(
+            "SELECT COALESCE(MAX(ordering), 0) FROM journal",
+            [],
+            |row| row.get::<_, i64>(0).map(|highest| highest as u64),
+        )?;
+        let mut appended = Vec::with_capacity(events.len());
+        {
+            let mut insert = transaction.prepare_cached(
+                "INSERT INTO journal (persistence_id, sequence_nr, ordering, event)
+                 VALUES (?1, ?2, ?3, ?4)",
+            )?;
+            let mut insert_tag = transaction
+                .prepare_cached("INSERT INTO journal_tags (tag, ordering) VALUES (?1, ?2)")?;
+            for (i, (event, seq)) in events.iter().zip(sequence_nr..).enumerate() {
+                let offset = highest + 1 + i as u64;
+                let event = serde_json::to_string(event)?;
+                let _ =
+                    insert.execute(params![persistence_id, seq as i64, offset as i64, event])?;
+                let event_tags = tags.get(i).map_or(&[][..], Vec::as_slice);
+                for tag in event_tags {
+                    let _ = insert_tag.execute(params![tag, offset as i64])?;
+                }
+                appended.push((offset, seq, event, event_tags));
+            }
+        }
+        transaction.commit()?;
+
+        let mut subscribers = self.subscribers.lock().unwrap();
+        let persistence_id = Arc::<str>::from(persistence_id);
+        for (offset, sequence_nr, event, event_tags) in appended {
+            for tag in event_tags {
+                let envelope = EventEnvelope {
+                    offset,
+                    persistence_id: Arc::clone(&persistence_id),
+                    sequence_nr,
+                    event: event.clone(),
+                };
+                subscribers.publish(tag, &envelope);
+            }
+        }
+        Ok(())
+    }
+
+    fn replay(&self, persistence_id: &str, from_sequence_nr: u64) -> Result<Vec<(u64, E)>, BoxErr> {
+        let connection = self.connection.lock().unwrap();
+        let mut select = connection.prepare_cached(
+            "SELECT sequence_nr, event FROM journal
+             WHERE persistence_id = ?1 AND sequence_nr >= ?2
+             ORDER BY sequence_nr",
+        )?;
+        let rows = select.query_map(params![persistence_id, from_sequence_nr as i64], |row| {
+            Ok((row.get::<_, i64>(0)? as u64, row.get::<_, String>(1)?))
+        })?;
+        let mut events = Vec::new();
+        for row in rows {
+            let (seq, event) = row?;
+            events.push((seq, serde_json::from_str(&event)?));
+        }
+        Ok(events)
+    }
+
+    fn delete_to(&self, persistence_id: &str, to_sequence_nr: u64) -> Result<(), BoxErr> {
+        let mut connection = self.connection.lock().unwrap();
+        let transaction = connection.transaction()?;
+        let _deleted = transaction.execute(
+            "DELETE FROM journal_tags WHERE ordering IN (
+                 SELECT ordering FROM journal
+                 WHERE persistence_id = ?1 AND sequence_nr <= ?2 AND sequence_nr < (
+                     SELECT MAX(sequence_nr) FROM journal WHERE persistence_id = ?1
+                 )
+             )",
+            params![persistence_id, to_sequence_nr as i64],
+        )?;
+        let _deleted = transaction.execute(
+            "DELETE FROM journal
+             WHERE persistence_id = ?1 AND sequence_nr <= ?2 AND sequence_nr < (
+                 SELECT MAX(sequence_nr) FROM journal WHERE persistence_id = ?1
+             )",
+            params![persistence_id, to_sequence_nr as i64],
+        )?;
+        transaction.commit()?;
+        Ok(())
+    }
+}
+
+impl<E> ReadJournal<E> for SqliteJournal
+where
+    E: Serialize + DeserializeOwned + 'static,
+{
+    fn subscribe(&self, tag: &str, offset: u64, mut sink: EventSink<E>) -> Result<(), BoxErr> {
+        // appends wait for the connection, so no event is missed between the query and the subscription
+        let connection = self.connection.lock().unwrap();
+        let mut select = connection.prepare_cached(
+            "SELECT journal.ordering, journal.persistence_id, journal.sequence_nr, journal.event
+             FROM journal_tags JOIN journal ON journal.ordering = journal_tags.ordering
+             WHERE journal_tags.tag = ?1 AND journal_tags.ordering > ?2
+             ORDER BY journal_tags.ordering",
+        )?;
+        let mut rows = select.query(params![tag, offset as i64])?;
+        while let Some(row) = rows.next()? {
+            let envelope = EventEnvelope {
+                offset: row.get::<_, i64>(0)? as u64,
+                persistence_id: row.get::<_, String>(1)?.into(),
+                sequence_nr: row.get::<_, i64>(2)? as u64,
+                event: serde_json::from_str(&row.get::<_, String>(3)?)?,
+            };
+            if sink(envelope) == false {
+                return Ok(());
+            }
+        }
+        self.subscribers.lock().unwrap().add(tag, json_sink(sink));
+        Ok(())
+    }
+}
+
+impl OffsetStore for SqliteJournal {
+    fn save(&self, projection: &str, offset: u64) -> Result<(), BoxErr> {
+        let _saved = self.connection.lock().unwrap().execute(
+            "INSERT INTO projection_offsets (projection, ordering) VALUES (?1, ?2)
+             ON CONFLICT (projection) DO UPDATE SET ordering = excluded.ordering",
+            params![projection, offset as i64],
+        )?;
+        Ok(())
+    }
+
+    fn load(&self, projection: &str) -> Result<Option<u64>, BoxErr> {
+        let offset = self
+            .connection
+            .lock()
+            .unwrap()
+            .query_row(
+                "SELECT ordering FROM projection_offsets WHERE projection = ?1",
+                [projection],
+                |row| row.get::<_, i64>(0),
+            )
+            .optional()?;
+        Ok(offset.map(|offset| offset as u64))
+    }
+}
@@ -0,0 +1,230 @@
+use crate::{ActorContext, Behavior, Behaviors, Error};
+use std::{
+    collections::HashMap,
+    future::{ready, Ready},
+    hash::Hash,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+
+/// What a [`ProcessManager`] does with a process after handling one of its messages.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum ProcessStep {
+    /// Keep the process state and wait for the next message.
+    Continue,
+    /// The workflow has finished successfully.
+    Complete,
+    /// The workflow has failed and its previous steps need to be undone.
+    Compensate,
+}
+
+/// Why a process is being compensated.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Compensation {
+    /// The handler returned [`ProcessStep::Compensate`].
+    Requested,
+    /// The process did not complete within the timeout.
+    TimedOut,
+}
+
+type KeyFn<M, K> = Box<dyn Fn(&M) -> K + Send + Sync>;
+type HandleFn<M, S> = Box<dyn Fn(&mut S, M) -> ProcessStep + Send + Sync>;
+type CompleteFn<K, S> = Arc<dyn Fn(K, S) + Send + Sync>;
+type CompensateFn<K, S> = Arc<dyn Fn(K, S, Compensation) + Send + Sync>;
+
+/// A behavior that drives many instances of a multi-step workflow.
+///
+/// Every message is correlated to a process by its key. The first message for a key
+/// starts a new process with a default state, which is then passed to the handler
+/// for every message with that key, until the handler completes or compensates the process.
+///
+/// ```rust
+/// use elliot::{ActorSystem, ProcessManager, ProcessStep};
+/// use std::time::Duration;
+///
+/// enum Order {
+///     Paid { order: u32 },
+///     Shipped { order: u32 },
+///     Cancelled { order: u32 },
+/// }
+///
+/// # async fn example() {
+/// let system = ActorSystem::new();
+/// let orders = ProcessManager::new(
+///     |msg: &Order| match msg {
+///         Order::Paid { order } | Order::Shipped { order } | Order::Cancelled { order } => *order,
+///     },
+///     |steps: &mut usize, msg| match msg {
+///         Order::Cancelled { .. } => ProcessStep::Compensate,
+///         _ if *steps == 1 => ProcessStep::Complete,
+///         _ => {
+///             *steps += 1;
+///             ProcessStep::Continue
+///         }
+///     },
+/// )
+/// .timeout(Duration::from_secs(3600))
+/// .on_compensate(|order, _steps, _reason| println!("refund order {order}"));
+///
+/// let orders = system.spawn("orders", orders);
+/// # }
+/// ```
+pub struct ProcessManager<M, K, S> {
+    key: KeyFn<M, K>,
+    handle: HandleFn<M, S>,
+    on_complete: Option<CompleteFn<K, S>>,
+    on_compensate: Option<CompensateFn<K, S>>,
+    timeout: Option<Duration>,
+    processes: Arc<Mutex<Processes<K, S>>>,
+}
+
+struct Processes<K, S> {
+    next_id: u64,
+    running: HashMap<K, Process<S>>,
+}
+
+struct Process<S> {
+    id: u64,
+    state: S,
+}
+
+impl<M, K, S> ProcessManager<M, K, S>
+where
+    K: Hash + Eq,
+{
+    pub fn new<KF, HF>(key: KF, handle: HF) -> Self
+    where
+        KF: Fn(&M) -> K + Send + Sync + 'static,
+        HF: Fn(&mut S, M) -> ProcessStep + Send + Sync + 'static,
+    {
+        Self {
+            key: Box::new(key),
+            handle: Box::new(handle),
+            on_complete: None,
+            on_compensate: None,
+            timeout: None,
+            processes: Arc::new(Mutex::new(Processes {
+                next_id: 0,
+                running: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Compensate processes that have not completed within `timeout` after their first message.
+    pub fn timeout(mut self, timeout: Duration) -> Self {
+        self.timeout = Some(timeout);
+        self
+    }
+
+    pub fn on_complete<F>(mut self, on_complete: F) -> Self
+    where
+        F: Fn(K, S) + Send + Sync + 'static,
+    {
+        self.on_complete = Some(Arc::new(on_complete));
+        self
+    }
+
+    pub fn on_compensate<F>(mut self, on_compensate: F) -> Self
+    where
+        F: Fn(K, S, Compensation) + Send + Sync + 'static,
+    {
+        self.on_compensate = Some(Arc::new(on_compensate));
+        self
+    }
+}
+
+impl<M, K, S> ProcessManager<M, K, S>
+where
+    K: Hash + Eq + Clone + Send + 'static,
+    S: Send + 'static,
+{
+    fn start_timer(&self, context: &ActorContext<M>, key: K, id: u64, timeout: Duration) {
+        let processes = Arc::downgrade(&self.processes);
+        let on_compensate = self.on_compensate.clone();
+        let cell = &context.this.cell;
+        let sleep = cell.runtime().sleep(timeout);
+        cell.spawn_task(async move {
+            sleep.await;
+            time_out(&processes, key, id, on_compensate);
+        });
+    }
+}
+
+fn time_out<K, S>(
+    processes: &Weak<Mutex<Processes<K, S>>>,
+    key: K,
+    id: u64,
+    on_compensate: Option<CompensateFn<K, S>>,
+) where
+    K: Hash + Eq,
+{
+    let Some(processes) = processes.upgrade() else {
+        return;
+    };
+    let mut processes = processes.lock().unwrap();
+    // the process might have completed and a new one started with the same key
+    if processes.running.get(&key).is_some_and(|p| p.id == id) {
+        let process = processes.running.remove(&key).expect("process is running");
+        drop(processes);
+        if let Some(on_compensate) = on_compensate {
+            on_compensate(key, process.state, Compensation::TimedOut);
+        }
+    }
+}
+
+impl<M, K, S> Behavior<M> for ProcessManager<M, K, S>
+where
+    M: Send + 'static,
+    K: Hash + Eq + Clone + Send + 'static,
+    S: Default + Send + 'static,
+{
+    type F = Ready<Result<Behaviors, Error<M>>>;
+
+    fn receive(&self, context: &ActorContext<M>, msg: M) -> Self::F {
+        let key = (self.key)(&msg);
+        let mut processes = self.processes.lock().unwrap();
+
+        if processes.running.contains_key(&key) == false {
+            let id = processes.next_id;
+            processes.next_id += 1;
+            let process = Process {
+                id,
+                state: S::default(),
+            };
+            let _ = processes.running.insert(key.clone(), process);
+            if let Some(timeout) = self.timeout {
+                self.start_timer(context, key.clone(), id, timeout);
+            }
+        }
+
+        let process = processes.running.get_mut(&key).expect("process is running");
+        match (self.handle)(&mut process.state, msg) {
+            ProcessStep::Continue => {}
+            ProcessStep::Complete => {
+                let process = processes.running.remove(&key).expect("process is running");
+                drop(processes);
+                if let Some(on_complete) = &self.on_complete {
+                    on_complete(key, process.state);
+                }
+            }
+            ProcessStep::Compensate => {
+                let process = processes.running.remove(&key).expect("process is running");
+                drop(processes);
+                if let Some(on_compensate) = &self.on_compensate {
+                    on_compensate(key, process.state, Compensation::Requested);
+                }
+            }
+        }
+
+        ready(Ok(Behaviors::Same))
+    }
+}
+
+impl<M, K, S> std::fmt::Debug for ProcessManager<M, K, S> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ProcessManager")
+            .field("timeout", &self.timeout)
+            .field("running", &self.processes.lock().unwrap().running.len())
+            .finish_non_exhaustive()
+    }
+}
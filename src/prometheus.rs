@@ -0,0 +1,208 @@
+//! Actor metrics in the Prometheus text format.
+//!
+//! Use [`to_text`] to mount the metrics into an existing HTTP server,
+//! or [`serve`] to run a tiny standalone HTTP server for scraping.
+//! Every metric is labeled with the path of its actor.
+
+use crate::{cell::ActorCell, ActorSystem};
+use std::{
+    fmt::Write as _,
+    io,
+    sync::atomic::{AtomicU64, Ordering},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream, ToSocketAddrs},
+};
+
+/// The upper bounds of the buckets of the handling time histogram, in seconds.
+const BUCKETS: [f64; 10] = [
+    0.000_1, 0.000_5, 0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1.0, 5.0,
+];
+
+/// How long the messages of an actor took to handle.
+#[derive(Debug, Default)]
+pub(crate) struct ActorMetrics {
+    /// Not cumulative, the last entry counts the messages that are slower than all buckets.
+    buckets: [AtomicU64; BUCKETS.len() + 1],
+    sum_nanos: AtomicU64,
+}
+
+impl ActorMetrics {
+    pub(crate) fn handled(&self, took: Duration) {
+        let seconds = took.as_secs_f64();
+        let bucket = BUCKETS
+            .iter()
+            .position(|&le| seconds <= le)
+            .unwrap_or(BUCKETS.len());
+        let _ = self.buckets[bucket].fetch_add(1, Ordering::Relaxed);
+        let nanos = u64::try_from(took.as_nanos()).unwrap_or(u64::MAX);
+        let _ = self.sum_nanos.fetch_add(nanos, Ordering::Relaxed);
+    }
+}
+
+/// Render the metrics of all live actors of the system in the Prometheus text format.
+///
+/// ```text
+/// # TYPE elliot_actor_messages_total counter
+/// elliot_actor_messages_total{path="/user/ping"} 42
+/// ```
+pub fn to_text(system: &ActorSystem) -> String {
+    let cells = system.cells();
+    let mut text = String::new();
+
+    let info = cells.iter().map(|cell| cell.info()).collect::<Vec<_>>();
+    header(
+        &mut text,
+        "mailbox_len",
+        "Messages waiting in the mailbox",
+        "gauge",
+    );
+    for (cell, info) in cells.iter().zip(&info) {
+        sample(
+            &mut text,
+            "mailbox_len",
+            cell,
+            "",
+            &info.mailbox_len.to_string(),
+        );
+    }
+    header(
+        &mut text,
+        "uptime_seconds",
+        "Time since the actor was spawned",
+        "gauge",
+    );
+    for (cell, info) in cells.iter().zip(&info) {
+        let uptime = info.uptime.as_secs_f64();
+        sample(&mut text, "uptime_seconds", cell, "", &uptime.to_string());
+    }
+    header(
+        &mut text,
+        "dead_letters_total",
+        "Messages that were not handled",
+        "counter",
+    );
+    for (cell, info) in cells.iter().zip(&info) {
+        let dead_letters = info.dead_letters;
+        sample(
+            &mut text,
+            "dead_letters_total",
+            cell,
+            "",
+            &dead_letters.to_string(),
+        );
+    }
+    header(
+        &mut text,
+        "messages_total",
+        "Messages that were processed, handled or not",
+        "counter",
+    );
+    for cell in &cells {
+        sample(
+            &mut text,
+            "messages_total",
+            cell,
+            "",
+            &count(cell).to_string(),
+        );
+    }
+
+    header(
+        &mut text,
+        "handling_seconds",
+        "Time it took to handle a message",
+        "histogram",
+    );
+    for cell in &cells {
+        let metrics = &cell.metrics;
+        let mut cumulative = 0;
+        for (i, bucket) in metrics.buckets.iter().enumerate() {
+            cumulative += bucket.load(Ordering::Relaxed);
+            let le = BUCKETS
+                .get(i)
+                .map_or_else(|| String::from("+Inf"), f64::to_string);
+            let label = format!(r#",le="{le}""#);
+            sample(
+                &mut text,
+                "handling_seconds_bucket",
+                cell,
+                &label,
+                &cumulative.to_string(),
+            );
+        }
+        let sum = Duration::from_nanos(metrics.sum_nanos.load(Ordering::Relaxed));
+        sample(
+            &mut text,
+            "handling_seconds_sum",
+            cell,
+            "",
+            &sum.as_secs_f64().to_string(),
+        );
+        sample(
+            &mut text,
+            "handling_seconds_count",
+            cell,
+            "",
+            &cumulative.to_string(),
+        );
+    }
+    text
+}
+
+/// Serve the metrics on `GET /metrics` until an error occurs while accepting connections.
+pub async fn serve(system: ActorSystem, addr: impl ToSocketAddrs) -> io::Result<()> {
+    let listener = TcpListener::bind(addr).await?;
+    loop {
+        let (stream, _) = listener.accept().await?;
+        let system = system.clone();
+        let _handle = tokio::spawn(async move { respond(&system, stream).await });
+    }
+}
+
+async fn respond(system: &ActorSystem, mut stream: TcpStream) -> io::Result<()> {
+    let mut buf = [0; 1024];
+    let read = stream.read(&mut buf).await?;
+    let request = String::from_utf8_lossy(&buf[..read]);
+    let mut request_line = request.lines().next().unwrap_or_default().split(' ');
+
+    let (status, body) = match (request_line.next(), request_line.next()) {
+        (Some("GET"), Some("/metrics")) => ("200 OK", to_text(system)),
+        _ => ("404 Not Found", String::from("not found\n")),
+    };
+
+    let response = format!(
+        "HTTP/1.1 {status}\r\ncontent-type: text/plain; version=0.0.4\r\ncontent-length: {}\r\nconnection: close\r\n\r\n{body}",
+        body.len()
+    );
+    stream.write_all(response.as_bytes()).await?;
+    stream.shutdown().await
+}
+
+fn count(cell: &ActorCell) -> u64 {
+    cell.metrics
+        .buckets
+        .iter()
+        .map(|bucket| bucket.load(Ordering::Relaxed))
+        .sum()
+}
+
+fn header(text: &mut String, name: &str, help: &str, kind: &str) {
+    let _ = writeln!(text, "# HELP elliot_actor_{name} {help}");
+    let _ = writeln!(text, "# TYPE elliot_actor_{name} {kind}");
+}
+
+fn sample(text: &mut String, name: &str, cell: &ActorCell, labels: &str, value: &str) {
+    let _ = write!(text, r#"elliot_actor_{name}{{path=""#);
+    for c in cell.path.as_str().chars() {
+        match c {
+            '"' => text.push_str(r#"\""#),
+            '\\' => text.push_str(r"\\"),
+            '\n' => text.push_str(r"\n"),
+            c => text.push(c),
+        }
+    }
+    let _ = writeln!(text, r#""{labels}}} {value}"#);
+}
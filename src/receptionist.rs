@@ -0,0 +1,209 @@
+use crate::{cell::ActorCell, ActorPath, ActorRef, ActorSystem};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    marker::PhantomData,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+
+type Key = (Arc<str>, TypeId);
+
+/// The services of a system, keyed by the id of their [`ServiceKey`] and the type of their messages.
+#[derive(Debug, Default)]
+pub(crate) struct Registry {
+    listings: Mutex<HashMap<Key, Box<dyn Any + Send>>>,
+}
+
+/// The key that services for messages of type `T` are registered under, see [`ActorSystem::receptionist`].
+pub struct ServiceKey<T> {
+    id: Arc<str>,
+    _msg: PhantomData<fn(T)>,
+}
+
+impl<T> ServiceKey<T> {
+    pub fn new(id: impl Into<Arc<str>>) -> Self {
+        Self {
+            id: id.into(),
+            _msg: PhantomData,
+        }
+    }
+
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+}
+
+/// A change of the services that are registered under a [`ServiceKey`], see [`Receptionist::subscribe`].
+pub enum ListingUpdate<T> {
+    /// A service has been registered.
+    Registered(ActorRef<T>),
+    /// A service has been deregistered or has stopped.
+    Deregistered(ActorPath),
+}
+
+/// Finds the services that actors have registered under a [`ServiceKey`], within one system.
+///
+/// Services are deregistered when they stop.
+#[derive(Clone, Debug)]
+pub struct Receptionist {
+    system: ActorSystem,
+}
+
+/// The updates of the services of a [`ServiceKey`], see [`Receptionist::subscribe`].
+///
+/// With the `stream` feature, this is also a `Stream` of the updates.
+pub struct Listing<T> {
+    pub(crate) rx: mpsc::UnboundedReceiver<ListingUpdate<T>>,
+}
+
+type Shared<T> = Arc<Mutex<Services<T>>>;
+
+struct Services<T> {
+    services: Vec<ActorRef<T>>,
+    subscribers: Vec<mpsc::UnboundedSender<ListingUpdate<T>>>,
+}
+
+impl<T> Services<T> {
+    fn notify(&mut self, update: &ListingUpdate<T>) {
+        self.subscribers
+            .retain(|subscriber| subscriber.send(update.clone()).is_ok());
+    }
+}
+
+impl ActorSystem {
+    /// The receptionist of this system, to register and find services by their [`ServiceKey`].
+    pub fn receptionist(&self) -> Receptionist {
+        Receptionist {
+            system: self.clone(),
+        }
+    }
+}
+
+impl Receptionist {
+    /// Register `service` under `key`, until it is deregistered or stops.
+    pub fn register<T: Send + 'static>(&self, key: &ServiceKey<T>, service: &ActorRef<T>) {
+        let listing = self.listing(key);
+        let mut services = listing.lock().unwrap();
+        if service.is_alive() == false || services.services.iter().any(|s| s.same_actor(service)) {
+            return;
+        }
+        services.services.push(service.clone());
+        services.notify(&ListingUpdate::Registered(service.clone()));
+        drop(services);
+
+        // the watch must not keep the service alive
+        let stopped = Arc::clone(&service.cell);
+        let listing = Arc::downgrade(&listing);
+        self.system.watch_with(service, move |_| {
+            if let Some(listing) = listing.upgrade() {
+                deregister(&listing, &stopped);
+            }
+        });
+    }
+
+    pub fn deregister<T: Send + 'static>(&self, key: &ServiceKey<T>, service: &ActorRef<T>) {
+        deregister(&self.listing(key), &service.cell);
+    }
+
+    /// The services that are currently registered under `key`.
+    pub fn find<T: Send + 'static>(&self, key: &ServiceKey<T>) -> Vec<ActorRef<T>> {
+        self.listing(key).lock().unwrap().services.clone()
+    }
+
+    /// Receive the changes of the services under `key`, starting with the services that are currently registered.
+    pub fn subscribe<T: Send + 'static>(&self, key: &ServiceKey<T>) -> Listing<T> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let listing = self.listing(key);
+        let mut services = listing.lock().unwrap();
+        for service in &services.services {
+            let _ = tx.send(ListingUpdate::Registered(service.clone()));
+        }
+        services.subscribers.push(tx);
+        Listing { rx }
+    }
+
+    fn listing<T: Send + 'static>(&self, key: &ServiceKey<T>) -> Shared<T> {
+        let mut listings = self.system.inner.registry.listings.lock().unwrap();
+        let listing = listings
+            .entry((Arc::clone(&key.id), TypeId::of::<T>()))
+            .or_insert_with(|| {
+                Box::new(Shared::<T>::new(Mutex::new(Services {
+                    services: Vec::new(),
+                    subscribers: Vec::new(),
+                })))
+            })
+            .downcast_ref::<Shared<T>>()
+            .expect("services are keyed by their type");
+        Arc::clone(listing)
+    }
+}
+
+fn deregister<T>(listing: &Mutex<Services<T>>, service: &Arc<ActorCell>) {
+    let mut services = listing.lock().unwrap();
+    let before = services.services.len();
+    services
+        .services
+        .retain(|s| Arc::ptr_eq(&s.cell, service) == false);
+    if services.services.len() < before {
+        services.notify(&ListingUpdate::Deregistered(service.path.clone()));
+    }
+}
+
+impl<T> Listing<T> {
+    /// Receive the next update, or `None` if the system has been dropped.
+    pub async fn recv(&mut self) -> Option<ListingUpdate<T>> {
+        self.rx.recv().await
+    }
+}
+
+#[cfg(feature = "stream")]
+impl<T> futures_core::Stream for Listing<T> {
+    type Item = ListingUpdate<T>;
+
+    fn poll_next(
+        mut self: std::pin::Pin<&mut Self>,
+        cx: &mut std::task::Context<'_>,
+    ) -> std::task::Poll<Option<Self::Item>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+impl<T> Clone for ServiceKey<T> {
+    fn clone(&self) -> Self {
+        Self {
+            id: Arc::clone(&self.id),
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ServiceKey<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("ServiceKey").field(&self.id).finish()
+    }
+}
+
+impl<T> Clone for ListingUpdate<T> {
+    fn clone(&self) -> Self {
+        match self {
+            Self::Registered(service) => Self::Registered(service.clone()),
+            Self::Deregistered(path) => Self::Deregistered(path.clone()),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ListingUpdate<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Registered(service) => f.debug_tuple("Registered").field(service).finish(),
+            Self::Deregistered(path) => f.debug_tuple("Deregistered").field(path).finish(),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Listing<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Listing").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{
+    behavior, bus::Echoes, system::SystemInner, ActorPath, ActorSystem, MessageCodec,
+    RedisBridgeError, WeakActorRef,
+};
+use futures_core::Stream;
+use redis::{aio::MultiplexedConnection, Client, RedisResult};
+use std::{
+    future::poll_fn,
+    pin::{pin, Pin},
+    sync::{Arc, Weak},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+/// Mirrors bus events of selected types to Redis pub/sub channels, in both directions.
+///
+/// Every bridged type has its own channel, other processes that bridge the same channel
+/// see the events that are published on the bus of this system and the other way around.
+/// Events are encoded with the registered codec for their type. Events that cannot be
+/// published are lost and a bridge ends when its connection to Redis fails.
+#[derive(Clone)]
+pub struct RedisBridge {
+    client: Client,
+    connection: MultiplexedConnection,
+    origin: Arc<[u8]>,
+    system: ActorSystem,
+}
+
+impl RedisBridge {
+    pub async fn connect(system: &ActorSystem, client: Client) -> RedisResult<Self> {
+        let connection = client.get_multiplexed_async_connection().await?;
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |d| d.as_nanos());
+        let origin = format!("{}-{nanos}", std::process::id());
+        Ok(Self {
+            client,
+            connection,
+            origin: origin.as_bytes().into(),
+            system: system.clone(),
+        })
+    }
+
+    /// Share the bus events of type `E` over `channel`.
+    ///
+    /// Events that came from the channel are not sent back to it.
+    pub async fn bridge<E: Clone + Send + 'static>(
+        &self,
+        channel: &str,
+    ) -> Result<(), RedisBridgeError> {
+        let codec = self
+            .system
+            .codec::<E>()
+            .ok_or(RedisBridgeError::NoCodec(std::any::type_name::<E>()))?;
+        let mut pubsub = self.client.get_async_pubsub().await?;
+        pubsub.subscribe(channel).await?;
+
+        let incoming = Arc::new(Echoes::default());
+        let echoes = Arc::clone(&incoming);
+        let connection = self.connection.clone();
+        let origin = Arc::clone(&self.origin);
+        let channel_name = channel.to_owned();
+        let encoder = Arc::clone(&codec);
+        let path = ActorPath::system("redis").child(channel);
+        let outgoing = behavior::actor_of(&self.system, path, move |event: E| {
+            let payload = encoder.encode(&event);
+            let mut connection = connection.clone();
+            let origin = Arc::clone(&origin);
+            let channel = channel_name.clone();
+            let echoes = Arc::clone(&echoes);
+            async move {
+                let Ok(payload) = payload else {
+                    return;
+                };
+                if echoes.is_echo(&payload) {
+                    return;
+                }
+                let _ = redis::cmd("PUBLISH")
+                    .arg(channel)
+                    .arg(frame(&origin, &payload))
+                    .exec_async(&mut connection)
+                    .await;
+            }
+        });
+        self.system.subscribe(&outgoing);
+
+        let system = Arc::downgrade(&self.system.inner);
+        let origin = Arc::clone(&self.origin);
+        let outgoing = outgoing.downgrade();
+        let _handle = tokio::spawn(async move {
+            let messages = pin!(pubsub.into_on_message());
+            receive_events(messages, system, origin, codec, incoming, outgoing).await;
+        });
+        Ok(())
+    }
+}
+
+async fn receive_events<E: Clone + Send + 'static>(
+    mut messages: Pin<&mut impl Stream<Item = redis::Msg>>,
+    system: Weak<SystemInner>,
+    origin: Arc<[u8]>,
+    codec: Arc<dyn MessageCodec<E>>,
+    incoming: Arc<Echoes>,
+    outgoing: WeakActorRef<E>,
+) {
+    while let Some(msg) = poll_fn(|cx| messages.as_mut().poll_next(cx)).await {
+        let Some((from, payload)) = unframe(msg.get_payload_bytes()) else {
+            continue;
+        };
+        if from == &*origin {
+            continue;
+        }
+        let Ok(event) = codec.decode(payload) else {
+            continue;
+        };
+        let Some(system) = system.upgrade() else {
+            return;
+        };
+        // the bridge sees the event on the bus as well, it compares the encoded events
+        if let (Some(_), Ok(payload)) = (outgoing.upgrade(), codec.encode(&event)) {
+            incoming.expect(payload);
+        }
+        let _ = system.bus.publish(event);
+    }
+}
+
+/// Redis messages have no headers, the origin is prefixed with its length as a single byte.
+fn frame(origin: &[u8], payload: &[u8]) -> Vec<u8> {
+    let mut frame = Vec::with_capacity(1 + origin.len() + payload.len());
+    frame.push(origin.len() as u8);
+    frame.extend_from_slice(origin);
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn unframe(frame: &[u8]) -> Option<(&[u8], &[u8])> {
+    let (&len, rest) = frame.split_first()?;
+    (rest.len() >= usize::from(len)).then(|| rest.split_at(usize::from(len)))
+}
+
+impl std::fmt::Debug for RedisBridge {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("RedisBridge")
+            .field("origin", &String::from_utf8_lossy(&self.origin))
+            .finish_non_exhaustive()
+    }
+}
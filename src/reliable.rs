@@ -0,0 +1,385 @@
+use crate::{
+    runtime::{self, AbortHandle, Runtime},
+    ActorRef, BoxErr, Journal, SnapshotStore,
+};
+use std::{
+    collections::BTreeMap,
+    future::Future,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::sync::Mutex as AsyncMutex;
+
+/// The events that a [`ReliableRef`] persists in its outbox.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    any(feature = "file-journal", feature = "sqlite"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum Outbox<T> {
+    Sent { id: u64, msg: T },
+    Acked { id: u64 },
+}
+
+/// The unacked messages of a [`ReliableRef`], which replace the events before it on recovery,
+/// see [`ReliableRef::recover_with_snapshots`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    any(feature = "file-journal", feature = "sqlite"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub struct OutboxSnapshot<T> {
+    pub last_id: u64,
+    pub pending: Vec<(u64, T)>,
+}
+
+/// A ref that delivers messages at least once, even if the process restarts in between.
+///
+/// Every message is persisted in the journal before it is sent, and stays in the outbox until
+/// the target [acks](Delivery::ack) it. A new `ReliableRef` for the same persistence id picks up
+/// all unacked messages and sends them again. The target might see a message more than once,
+/// a plain [`ActorRef::tell`] delivers at most once.
+///
+/// The journal is read and written on a blocking thread of the runtime of the target.
+/// Only a durable journal, like the `FileJournal`, keeps the outbox across process crashes.
+pub struct ReliableRef<T> {
+    inner: Arc<Inner<T>>,
+}
+
+/// A message from a [`ReliableRef`], that is sent again until it is acked.
+pub struct Delivery<T> {
+    id: u64,
+    msg: T,
+    outbox: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    persistence_id: Arc<str>,
+    journal: Arc<dyn Journal<Outbox<T>>>,
+    snapshots: Option<Snapshots<T>>,
+    target: ActorRef<Delivery<T>>,
+    runtime: Arc<dyn Runtime>,
+    /// Held while the journal is written, so that the events are appended in order.
+    persisted: AsyncMutex<Persisted>,
+    state: Mutex<State<T>>,
+}
+
+struct Snapshots<T> {
+    store: Arc<dyn SnapshotStore<OutboxSnapshot<T>>>,
+    every: u64,
+}
+
+struct Persisted {
+    sequence_nr: u64,
+    snapshot_sequence_nr: u64,
+}
+
+struct State<T> {
+    last_id: u64,
+    pending: BTreeMap<u64, T>,
+    redelivery: Option<AbortHandle>,
+}
+
+impl<T: Clone + Send + 'static> ReliableRef<T> {
+    /// Recover all unacked messages for `target` from the journal and send them again.
+    ///
+    /// Every event of the outbox is replayed, see [`ReliableRef::recover_with_snapshots`]
+    /// to keep the outbox small.
+    pub async fn recover<J>(
+        persistence_id: impl Into<Arc<str>>,
+        journal: J,
+        target: &ActorRef<Delivery<T>>,
+    ) -> Result<Self, BoxErr>
+    where
+        J: Journal<Outbox<T>>,
+    {
+        Self::recover_from(persistence_id.into(), Arc::new(journal), None, target).await
+    }
+
+    /// Like [`ReliableRef::recover`], but save a snapshot of the outbox to `snapshots` after every
+    /// `snapshot_every` events, and [delete](Journal::delete_to) the events that it covers.
+    ///
+    /// Recovery starts from the latest snapshot and replays only the newer events.
+    /// Failing to save a snapshot does not fail the message, the snapshot is retried after the next one.
+    pub async fn recover_with_snapshots<J, S>(
+        persistence_id: impl Into<Arc<str>>,
+        journal: J,
+        snapshots: S,
+        snapshot_every: u64,
+        target: &ActorRef<Delivery<T>>,
+    ) -> Result<Self, BoxErr>
+    where
+        J: Journal<Outbox<T>>,
+        S: SnapshotStore<OutboxSnapshot<T>>,
+    {
+        let snapshots = Snapshots {
+            store: Arc::new(snapshots),
+            every: snapshot_every.max(1),
+        };
+        Self::recover_from(
+            persistence_id.into(),
+            Arc::new(journal),
+            Some(snapshots),
+            target,
+        )
+        .await
+    }
+
+    async fn recover_from(
+        persistence_id: Arc<str>,
+        journal: Arc<dyn Journal<Outbox<T>>>,
+        snapshots: Option<Snapshots<T>>,
+        target: &ActorRef<Delivery<T>>,
+    ) -> Result<Self, BoxErr> {
+        let runtime = target.cell.runtime();
+        let (snapshot, events) = {
+            let persistence_id = Arc::clone(&persistence_id);
+            let journal = Arc::clone(&journal);
+            let store = snapshots
+                .as_ref()
+                .map(|snapshots| Arc::clone(&snapshots.store));
+            runtime::unblock(&*runtime, move || -> Result<_, BoxErr> {
+                let snapshot = match store {
+                    Some(store) => store.load(&persistence_id)?,
+                    None => None,
+                };
+                let from_sequence_nr = snapshot.as_ref().map_or(0, |(seq, _)| *seq) + 1;
+                let events = journal.replay(&persistence_id, from_sequence_nr)?;
+                Ok((snapshot, events))
+            })
+            .await?
+        };
+
+        let (mut sequence_nr, mut last_id, mut pending) = match snapshot {
+            Some((sequence_nr, snapshot)) => (
+                sequence_nr,
+                snapshot.last_id,
+                snapshot.pending.into_iter().collect(),
+            ),
+            None => (0, 0, BTreeMap::new()),
+        };
+        let snapshot_sequence_nr = sequence_nr;
+        for (seq, event) in events {
+            sequence_nr = seq;
+            match event {
+                Outbox::Sent { id, msg } => {
+                    last_id = id;
+                    let _ = pending.insert(id, msg);
+                }
+                Outbox::Acked { id } => {
+                    let _ = pending.remove(&id);
+                }
+            }
+        }
+
+        let this = Self {
+            inner: Arc::new(Inner {
+                persistence_id,
+                journal,
+                snapshots,
+                target: target.clone(),
+                runtime,
+                persisted: AsyncMutex::new(Persisted {
+                    sequence_nr,
+                    snapshot_sequence_nr,
+                }),
+                state: Mutex::new(State {
+                    last_id,
+                    pending,
+                    redelivery: None,
+                }),
+            }),
+        };
+        let _ = this.redeliver();
+        Ok(this)
+    }
+
+    /// Send all unacked messages again every `interval`, in addition to when the outbox is recovered.
+    pub fn redeliver_every(self, interval: Duration) -> Self {
+        let inner = Arc::downgrade(&self.inner);
+        let runtime = Arc::clone(&self.inner.runtime);
+        let sleep = Arc::clone(&runtime);
+        let redelivery = runtime::spawn_abortable(&*runtime, "elliot-redelivery", async move {
+            loop {
+                sleep.sleep(interval).await;
+                let Some(inner) = inner.upgrade() else {
+                    return;
+                };
+                let _ = Inner::redeliver(&inner);
+            }
+        });
+        let previous = self
+            .inner
+            .state
+            .lock()
+            .unwrap()
+            .redelivery
+            .replace(redelivery);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+        self
+    }
+
+    /// Persist `msg` in the outbox and send it to the target.
+    ///
+    /// Once this returns `Ok`, the message is delivered eventually, even if the target is not alive right now.
+    pub async fn tell(&self, msg: T) -> Result<(), BoxErr> {
+        let inner = &self.inner;
+        let mut persisted = inner.persisted.lock().await;
+        let id = inner.state.lock().unwrap().last_id + 1;
+        inner
+            .persist(
+                &mut persisted,
+                Outbox::Sent {
+                    id,
+                    msg: msg.clone(),
+                },
+            )
+            .await?;
+        {
+            let mut state = inner.state.lock().unwrap();
+            state.last_id = id;
+            let _ = state.pending.insert(id, msg.clone());
+        }
+        let _ = inner.target.tell(Delivery {
+            id,
+            msg,
+            outbox: Arc::clone(inner),
+        });
+        inner.compact(&mut persisted).await;
+        Ok(())
+    }
+
+    /// Send all unacked messages again, in the order they were told, returns how many were sent.
+    pub fn redeliver(&self) -> usize {
+        Inner::redeliver(&self.inner)
+    }
+
+    /// The ids of all unacked messages, in the order they were told.
+    pub fn pending(&self) -> Vec<u64> {
+        let state = self.inner.state.lock().unwrap();
+        state.pending.keys().copied().collect()
+    }
+
+    pub fn target(&self) -> &ActorRef<Delivery<T>> {
+        &self.inner.target
+    }
+}
+
+impl<T: Clone + Send + 'static> Inner<T> {
+    async fn persist(&self, persisted: &mut Persisted, event: Outbox<T>) -> Result<(), BoxErr> {
+        let journal = Arc::clone(&self.journal);
+        let persistence_id = Arc::clone(&self.persistence_id);
+        let sequence_nr = persisted.sequence_nr + 1;
+        runtime::unblock(&*self.runtime, move || {
+            journal.append(&persistence_id, sequence_nr, &[event])
+        })
+        .await?;
+        persisted.sequence_nr = sequence_nr;
+        Ok(())
+    }
+
+    /// Snapshot the outbox if it is due, and delete the events that the snapshot covers.
+    async fn compact(&self, persisted: &mut Persisted) {
+        let Some(snapshots) = &self.snapshots else {
+            return;
+        };
+        let sequence_nr = persisted.sequence_nr;
+        if sequence_nr - persisted.snapshot_sequence_nr < snapshots.every {
+            return;
+        }
+        let snapshot = {
+            let state = self.state.lock().unwrap();
+            OutboxSnapshot {
+                last_id: state.last_id,
+                pending: state
+                    .pending
+                    .iter()
+                    .map(|(id, msg)| (*id, msg.clone()))
+                    .collect(),
+            }
+        };
+        let store = Arc::clone(&snapshots.store);
+        let journal = Arc::clone(&self.journal);
+        let persistence_id = Arc::clone(&self.persistence_id);
+        let saved = runtime::unblock(&*self.runtime, move || {
+            store.save(&persistence_id, sequence_nr, &snapshot)?;
+            journal.delete_to(&persistence_id, sequence_nr)
+        })
+        .await;
+        if saved.is_ok() {
+            persisted.snapshot_sequence_nr = sequence_nr;
+        }
+    }
+
+    fn redeliver(this: &Arc<Self>) -> usize {
+        let pending = this.state.lock().unwrap().pending.clone();
+        pending
+            .into_iter()
+            .filter(|(id, msg)| {
+                let delivery = Delivery {
+                    id: *id,
+                    msg: msg.clone(),
+                    outbox: Arc::clone(this),
+                };
+                this.target.tell(delivery).is_ok()
+            })
+            .count()
+    }
+}
+
+impl<T: Clone + Send + 'static> Delivery<T> {
+    /// The id of the message, the same for every time it is sent.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+
+    pub fn msg(&self) -> &T {
+        &self.msg
+    }
+
+    /// Remove the message from the outbox, so that it is not sent again.
+    ///
+    /// Acking a message more than once has no effect.
+    pub fn ack(&self) -> impl Future<Output = Result<(), BoxErr>> + Send + 'static {
+        let outbox = Arc::clone(&self.outbox);
+        let id = self.id;
+        async move {
+            let mut persisted = outbox.persisted.lock().await;
+            if outbox.state.lock().unwrap().pending.contains_key(&id) == false {
+                return Ok(());
+            }
+            outbox.persist(&mut persisted, Outbox::Acked { id }).await?;
+            let _ = outbox.state.lock().unwrap().pending.remove(&id);
+            outbox.compact(&mut persisted).await;
+            Ok(())
+        }
+    }
+}
+
+impl<T> Drop for ReliableRef<T> {
+    fn drop(&mut self) {
+        if let Some(redelivery) = &self.inner.state.lock().unwrap().redelivery {
+            redelivery.abort();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ReliableRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReliableRef")
+            .field("persistence_id", &self.inner.persistence_id)
+            .field("target", &self.inner.target)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T: std::fmt::Debug> std::fmt::Debug for Delivery<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Delivery")
+            .field("id", &self.id)
+            .field("msg", &self.msg)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,822 @@
+use crate::{
+    cell::ActorCell, failure_detector::NewDetector, mailbox, system::SystemInner, ActorPath,
+    ActorRef, ActorSystem, BoxErr, FailureDetector, Meta, RemoteError,
+};
+use std::{
+    collections::{HashMap, VecDeque},
+    io,
+    net::SocketAddr,
+    sync::{Arc, Mutex, Weak},
+    time::Duration,
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, BufReader, BufWriter, ReadHalf},
+    net::ToSocketAddrs,
+    sync::mpsc,
+    time::{Instant, Interval, MissedTickBehavior},
+};
+
+#[cfg(feature = "grpc")]
+mod grpc;
+#[cfg(feature = "quic")]
+mod quic;
+#[cfg(feature = "tls")]
+mod tls;
+mod transport;
+
+#[cfg(feature = "grpc")]
+pub use grpc::GrpcTransport;
+#[cfg(feature = "quic")]
+pub use quic::QuicTransport;
+#[cfg(feature = "tls")]
+pub use tls::TlsTransport;
+pub use transport::{Accepted, Connection, Listener, TcpTransport, Transport};
+
+const SCHEME: &str = "elliot://";
+const MAX_FRAME_LEN: usize = 16 * 1024 * 1024;
+
+const TELL: u8 = 0;
+const WATCH: u8 = 1;
+const TERMINATED: u8 = 2;
+const HEARTBEAT: u8 = 3;
+const HEARTBEAT_ACK: u8 = 4;
+/// The first frame of every connection, with the auth token as payload.
+const HELLO: u8 = 5;
+
+/// How connections to other systems are checked, see [`ActorSystem::set_failure_detection`].
+///
+/// A heartbeat is sent every `interval` and the connection is declared failed if nothing
+/// has been received from the other system for `timeout`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct FailureDetection {
+    interval: Duration,
+    timeout: Duration,
+}
+
+impl FailureDetection {
+    pub fn new(interval: Duration, timeout: Duration) -> Self {
+        Self { interval, timeout }
+    }
+}
+
+/// What a system that connects presents to be authenticated, see [`Authenticate`].
+#[derive(Copy, Clone, Debug)]
+pub struct Credentials<'a> {
+    /// The token that the other system has set with [`ActorSystem::set_auth_token`].
+    pub token: Option<&'a str>,
+    /// The DER certificate that the transport has verified the other system with, e.g. with mutual TLS.
+    pub certificate: Option<&'a [u8]>,
+}
+
+/// Decides which systems may connect, see [`ActorSystem::set_authenticator`].
+pub trait Authenticate: Send + Sync + 'static {
+    /// The identity of the other system, which is the [`Meta::peer`] of its messages,
+    /// or an error to close the connection.
+    fn authenticate(&self, credentials: &Credentials<'_>) -> Result<Arc<str>, BoxErr>;
+}
+
+impl<F> Authenticate for F
+where
+    F: Fn(&Credentials<'_>) -> Result<Arc<str>, BoxErr> + Send + Sync + 'static,
+{
+    fn authenticate(&self, credentials: &Credentials<'_>) -> Result<Arc<str>, BoxErr> {
+        self(credentials)
+    }
+}
+
+/// Which messages are dropped when the [`RemoteBuffer`] of a system is full.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Eviction {
+    /// Make room for the new message.
+    DropOldest,
+    /// Keep the buffered messages.
+    DropNewest,
+}
+
+/// Store-and-forward for messages to unreachable systems, see [`ActorSystem::set_remote_buffer`].
+///
+/// Messages that are sent while the connection to another system is down are kept, up to
+/// `capacity` per system, and sent in order once the connection is back. Reconnecting is
+/// tried every `retry_interval`, until `max_attempts` have failed. Messages that are dropped
+/// from a full buffer, or held for a system that does not come back, are dead letters.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct RemoteBuffer {
+    capacity: usize,
+    retry_interval: Duration,
+    max_attempts: Option<u32>,
+    eviction: Eviction,
+}
+
+impl RemoteBuffer {
+    /// Keep up to `capacity` messages per system, dropping the oldest and retrying every second by default.
+    pub fn new(capacity: usize) -> Self {
+        Self {
+            capacity,
+            retry_interval: Duration::from_secs(1),
+            max_attempts: None,
+            eviction: Eviction::DropOldest,
+        }
+    }
+
+    pub fn retry_interval(mut self, retry_interval: Duration) -> Self {
+        self.retry_interval = retry_interval;
+        self
+    }
+
+    /// Give up on a system after `attempts` failed reconnects, reconnecting is tried forever by default.
+    ///
+    /// The next message to the system tries to connect again.
+    pub fn max_attempts(mut self, attempts: u32) -> Self {
+        self.max_attempts = Some(attempts.max(1));
+        self
+    }
+
+    pub fn eviction(mut self, eviction: Eviction) -> Self {
+        self.eviction = eviction;
+        self
+    }
+
+    /// Buffer `frame`, returns `false` if it has been dropped.
+    ///
+    /// The sender of an evicted frame is told about the dead letter, the sender of `frame` is not.
+    fn push(&self, frames: &mut VecDeque<Buffered>, frame: Buffered) -> bool {
+        if frames.len() < self.capacity {
+            frames.push_back(frame);
+            return true;
+        }
+        match self.eviction {
+            Eviction::DropNewest => false,
+            Eviction::DropOldest => {
+                if let Some((_, Some(sender))) = frames.pop_front() {
+                    sender.dead_letter();
+                }
+                if self.capacity > 0 {
+                    frames.push_back(frame);
+                    return true;
+                }
+                false
+            }
+        }
+    }
+}
+
+/// Fails when nothing has been heard for longer than the timeout.
+struct Deadline {
+    timeout: Duration,
+    last_heartbeat: Option<Instant>,
+}
+
+impl FailureDetector for Deadline {
+    fn heartbeat(&mut self, now: Instant) {
+        self.last_heartbeat = Some(now);
+    }
+
+    fn is_available(&self, now: Instant) -> bool {
+        self.last_heartbeat
+            .is_none_or(|last| now.duration_since(last) <= self.timeout)
+    }
+}
+
+impl Default for FailureDetection {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(1), Duration::from_secs(5))
+    }
+}
+
+type Deliver = Box<dyn Fn(&[u8], Option<Arc<str>>) -> Result<bool, BoxErr> + Send + Sync>;
+type Notify = Box<dyn FnOnce(bool) + Send>;
+/// A frame that waits for a connection, with the ref that sent it if it is a message.
+type Buffered = (Vec<u8>, Option<Arc<ActorCell>>);
+type Frames = mpsc::UnboundedSender<Buffered>;
+type Outgoing = mpsc::UnboundedReceiver<Buffered>;
+type Detector = Arc<Mutex<Box<dyn FailureDetector>>>;
+/// The watchers of the actors of one remote system, by path.
+type Watchers = HashMap<String, Vec<Notify>>;
+
+/// The remoting state of a system.
+#[derive(Default)]
+pub(crate) struct Remote {
+    exposed: Mutex<HashMap<Arc<str>, Exposed>>,
+    connections: Mutex<HashMap<Arc<str>, Frames>>,
+    /// The systems that are down, with the frames that wait for them.
+    buffers: Mutex<HashMap<Arc<str>, VecDeque<Buffered>>>,
+    watchers: Mutex<HashMap<Arc<str>, Watchers>>,
+    failure_detection: Mutex<FailureDetection>,
+    /// `None` to check the connections with the timeout of the [`FailureDetection`].
+    failure_detector: Mutex<Option<NewDetector>>,
+    buffer: Mutex<Option<RemoteBuffer>>,
+    /// `None` for the [`TcpTransport`].
+    transport: Mutex<Option<Arc<dyn Transport>>>,
+    authenticator: Mutex<Option<Arc<dyn Authenticate>>>,
+    auth_token: Mutex<Option<Arc<str>>>,
+}
+
+/// Which system has opened a connection.
+enum Side {
+    /// We have, to the system at the authority.
+    Outbound(Arc<str>),
+    /// The other system has, with the certificate that the transport has verified it with.
+    Inbound(Option<Vec<u8>>),
+}
+
+struct Exposed {
+    deliver: Deliver,
+    cell: Arc<ActorCell>,
+}
+
+impl ActorSystem {
+    /// Like [`ActorSystem::bind`], on the address of the [`RemoteConfig`](crate::RemoteConfig),
+    /// `None` if the configuration has none.
+    #[cfg(feature = "config")]
+    pub async fn bind_configured(&self) -> io::Result<Option<SocketAddr>> {
+        match &self.inner.bind {
+            Some(addr) => self.bind(addr.as_str()).await.map(Some),
+            None => Ok(None),
+        }
+    }
+
+    /// Accept messages from other systems on `addr`, returns the address that is bound.
+    ///
+    /// Only actors that have been [exposed](ActorSystem::expose) can receive remote messages.
+    pub async fn bind(&self, addr: impl ToSocketAddrs) -> io::Result<SocketAddr> {
+        let transport = self.inner.remote.transport();
+        let mut bound = Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            "could not resolve to any address",
+        ));
+        for addr in tokio::net::lookup_host(addr).await? {
+            bound = transport.bind(addr).await;
+            if bound.is_ok() {
+                break;
+            }
+        }
+        let mut listener = bound?;
+        let local_addr = listener.local_addr()?;
+        let system = Arc::downgrade(&self.inner);
+        let _handle = tokio::spawn(async move {
+            while let Ok(accepted) = listener.accept().await {
+                if system.strong_count() == 0 {
+                    return;
+                }
+                let (frames, mut outgoing) = mpsc::unbounded_channel();
+                let system = Weak::clone(&system);
+                let _handle = tokio::spawn(async move {
+                    let connection = run_connection(
+                        accepted.connection,
+                        system,
+                        Side::Inbound(accepted.certificate),
+                        frames,
+                        &mut outgoing,
+                        None,
+                    );
+                    connection.await;
+                });
+            }
+        });
+        Ok(local_addr)
+    }
+
+    /// Allow other systems to send messages to `actor`, decoded with the registered codec for `T`.
+    ///
+    /// The actor is reachable at `elliot://<bound address><actor path>`.
+    pub fn expose<T: Send + 'static>(&self, actor: &ActorRef<T>) -> Result<(), RemoteError> {
+        let codec = self
+            .codec::<T>()
+            .ok_or(RemoteError::NoCodec(std::any::type_name::<T>()))?;
+        let path: Arc<str> = actor.path().as_str().into();
+        let cell = Arc::clone(&actor.cell);
+        let actor = actor.downgrade();
+        let deliver = move |bytes: &[u8], peer: Option<Arc<str>>| {
+            let Some(actor) = actor.upgrade() else {
+                return Ok(false);
+            };
+            let meta = Meta {
+                peer,
+                ..Meta::capture(None)
+            };
+            Ok(actor.deliver(codec.decode(bytes)?, meta).is_ok())
+        };
+        let exposed = Exposed {
+            deliver: Box::new(deliver),
+            cell,
+        };
+        let _ = self
+            .inner
+            .remote
+            .exposed
+            .lock()
+            .unwrap()
+            .insert(path, exposed);
+        Ok(())
+    }
+
+    /// A ref to an actor in another system, at `elliot://host:port/user/name`.
+    ///
+    /// Messages are encoded with the registered codec for `T`. The connection is
+    /// established on the first message and re-established if it fails; messages
+    /// that cannot be sent are [dead letters](ActorSystem::dead_letters).
+    pub fn remote_ref<T: Send + 'static>(&self, uri: &str) -> Result<ActorRef<T>, RemoteError> {
+        let (authority, path) =
+            parse_uri(uri).ok_or_else(|| RemoteError::InvalidUri(uri.to_owned()))?;
+        let codec = self
+            .codec::<T>()
+            .ok_or(RemoteError::NoCodec(std::any::type_name::<T>()))?;
+
+        let authority: Arc<str> = authority.into();
+        let path = path.to_owned();
+        // the messages that cannot be sent are published as dead letters of this system
+        let dead_letters = Some(self.inner.dead_letters.clone());
+        let cell = ActorCell::new(
+            u64::MAX,
+            ActorPath::remote(uri),
+            dead_letters,
+            None,
+            None,
+            0,
+        );
+        let (this, mut mailbox) = mailbox::channel(Arc::new(cell));
+        let system = Arc::clone(&self.inner);
+        let sender = Arc::clone(&this.cell);
+        let _handle = tokio::spawn(async move {
+            while let Some(msg) = mailbox.recv().await {
+                let Ok(payload) = codec.encode(&msg) else {
+                    mailbox.cell().dead_letter();
+                    continue;
+                };
+                let frame = encode_frame(TELL, &path, &payload);
+                if system
+                    .remote
+                    .send(&system, &authority, frame, Some(&sender))
+                    == false
+                {
+                    mailbox.cell().dead_letter();
+                }
+            }
+        });
+        Ok(this)
+    }
+
+    /// Change how connections to other systems are checked, this applies to new connections only.
+    pub fn set_failure_detection(&self, failure_detection: FailureDetection) {
+        *self.inner.remote.failure_detection.lock().unwrap() = failure_detection;
+    }
+
+    /// Check connections to other systems with a detector from `new` instead of the timeout of
+    /// the [`FailureDetection`], e.g. a [`PhiAccrualFailureDetector`](crate::PhiAccrualFailureDetector).
+    ///
+    /// The detectors receive a heartbeat for every frame from the other system.
+    /// This applies to new connections only.
+    pub fn set_failure_detector<D: FailureDetector + 'static>(
+        &self,
+        new: impl Fn() -> D + Send + Sync + 'static,
+    ) {
+        *self.inner.remote.failure_detector.lock().unwrap() =
+            Some(Arc::new(move || Box::new(new())));
+    }
+
+    /// Connect to other systems with `transport` instead of plain TCP.
+    ///
+    /// This applies to connections and [binds](ActorSystem::bind) that are made after it,
+    /// all systems of a cluster need to use the same transport.
+    pub fn set_transport(&self, transport: impl Transport) {
+        *self.inner.remote.transport.lock().unwrap() = Some(Arc::new(transport));
+    }
+
+    /// Only accept connections from systems that `authenticator` accepts, this applies to new connections only.
+    ///
+    /// The identity it returns is the [`Meta::peer`] of the messages from that system.
+    pub fn set_authenticator(&self, authenticator: impl Authenticate) {
+        *self.inner.remote.authenticator.lock().unwrap() = Some(Arc::new(authenticator));
+    }
+
+    /// Present `token` to the [authenticator](ActorSystem::set_authenticator) of the systems that this system connects to.
+    pub fn set_auth_token(&self, token: impl Into<Arc<str>>) {
+        *self.inner.remote.auth_token.lock().unwrap() = Some(token.into());
+    }
+
+    /// Keep the messages for other systems while they are unreachable, instead of dropping them.
+    ///
+    /// `None` turns buffering off, for systems that fail after that.
+    pub fn set_remote_buffer(&self, buffer: Option<RemoteBuffer>) {
+        *self.inner.remote.buffer.lock().unwrap() = buffer;
+    }
+}
+
+/// Split `elliot://host:port/path` into `host:port` and `/path`.
+pub(crate) fn parse_uri(uri: &str) -> Option<(&str, &str)> {
+    let rest = uri.strip_prefix(SCHEME)?;
+    let slash = rest.find('/')?;
+    let (authority, path) = rest.split_at(slash);
+    (authority.is_empty() == false && path.len() > 1).then_some((authority, path))
+}
+
+impl Remote {
+    fn transport(&self) -> Arc<dyn Transport> {
+        self.transport
+            .lock()
+            .unwrap()
+            .clone()
+            .unwrap_or_else(|| Arc::new(TcpTransport))
+    }
+
+    fn send(
+        &self,
+        system: &Arc<SystemInner>,
+        authority: &Arc<str>,
+        frame: Vec<u8>,
+        sender: Option<&Arc<ActorCell>>,
+    ) -> bool {
+        let mut connections = self.connections.lock().unwrap();
+        if let Some(frames) = self.buffers.lock().unwrap().get_mut(authority) {
+            let buffer = *self.buffer.lock().unwrap();
+            return buffer.is_some_and(|buffer| buffer.push(frames, (frame, sender.cloned())));
+        }
+        let connection = connections
+            .entry(Arc::clone(authority))
+            .or_insert_with(|| self.connect(system, authority));
+        if connection.is_closed() {
+            *connection = self.connect(system, authority);
+        }
+        connection.send((frame, sender.cloned())).is_ok()
+    }
+
+    fn connect(&self, system: &Arc<SystemInner>, authority: &Arc<str>) -> Frames {
+        let (frames, outgoing) = mpsc::unbounded_channel();
+        let replies = frames.clone();
+        let failure_detection = *self.failure_detection.lock().unwrap();
+        let failure_detector = self.failure_detector.lock().unwrap().clone();
+        let transport = self.transport();
+        let system = Arc::downgrade(system);
+        let authority = Arc::clone(authority);
+        let _handle = tokio::spawn(async move {
+            let (mut replies, mut outgoing) = (replies, outgoing);
+            let mut stream = transport.connect(&authority).await.ok();
+            loop {
+                if let Some(stream) = stream.take() {
+                    let connection = run_connection(
+                        stream,
+                        Weak::clone(&system),
+                        Side::Outbound(Arc::clone(&authority)),
+                        replies,
+                        &mut outgoing,
+                        Some((failure_detection, failure_detector.clone())),
+                    );
+                    connection.await;
+                }
+                let Some(strong) = system.upgrade() else {
+                    return;
+                };
+                // nothing more will be heard from the watched actors
+                strong.remote.unreachable(&authority);
+                let Some(buffer) = strong.remote.hold(&authority, outgoing) else {
+                    return;
+                };
+                drop(strong);
+
+                let mut attempts = 0;
+                let reconnected = loop {
+                    tokio::time::sleep(buffer.retry_interval).await;
+                    if system.strong_count() == 0 {
+                        return;
+                    }
+                    if let Ok(reconnected) = transport.connect(&authority).await {
+                        break reconnected;
+                    }
+                    attempts += 1;
+                    if buffer.max_attempts.is_some_and(|max| attempts >= max) {
+                        if let Some(strong) = system.upgrade() {
+                            strong.remote.give_up(&authority);
+                        }
+                        return;
+                    }
+                };
+                let Some(strong) = system.upgrade() else {
+                    return;
+                };
+                (replies, outgoing) = strong.remote.flush(&authority);
+                stream = Some(reconnected);
+            }
+        });
+        frames
+    }
+
+    /// Keep the frames that have not been sent to a system that is down, returns how to reconnect.
+    ///
+    /// Without a buffer, the messages are dead letters.
+    fn hold(&self, authority: &Arc<str>, mut outgoing: Outgoing) -> Option<RemoteBuffer> {
+        // no frames can be sent in between
+        let _connections = self.connections.lock().unwrap();
+        let Some(buffer) = *self.buffer.lock().unwrap() else {
+            while let Ok((_, sender)) = outgoing.try_recv() {
+                if let Some(sender) = sender {
+                    sender.dead_letter();
+                }
+            }
+            return None;
+        };
+        let mut buffers = self.buffers.lock().unwrap();
+        let frames = buffers.entry(Arc::clone(authority)).or_default();
+        while let Ok((frame, sender)) = outgoing.try_recv() {
+            if buffer.push(frames, (frame, sender.clone())) == false {
+                if let Some(sender) = sender {
+                    sender.dead_letter();
+                }
+            }
+        }
+        Some(buffer)
+    }
+
+    /// Drop the held frames of a system that did not come back, the messages are dead letters.
+    fn give_up(&self, authority: &Arc<str>) {
+        let _connections = self.connections.lock().unwrap();
+        let held = self.buffers.lock().unwrap().remove(authority);
+        for (_, sender) in held.into_iter().flatten() {
+            if let Some(sender) = sender {
+                sender.dead_letter();
+            }
+        }
+    }
+
+    /// Send the held frames over a new connection to a system that is back.
+    fn flush(&self, authority: &Arc<str>) -> (Frames, Outgoing) {
+        let mut connections = self.connections.lock().unwrap();
+        let (frames, outgoing) = mpsc::unbounded_channel();
+        let held = self.buffers.lock().unwrap().remove(authority);
+        for frame in held.into_iter().flatten() {
+            let _ = frames.send(frame);
+        }
+        let _ = connections.insert(Arc::clone(authority), frames.clone());
+        (frames, outgoing)
+    }
+
+    fn deliver(&self, path: &str, payload: &[u8], peer: Option<Arc<str>>) {
+        let mut exposed = self.exposed.lock().unwrap();
+        let delivered = exposed
+            .get(path)
+            .map(|exposed| (exposed.deliver)(payload, peer));
+        // the actor has stopped
+        if let Some(Ok(false)) = delivered {
+            let _ = exposed.remove(path);
+        }
+    }
+
+    pub(crate) fn watch(
+        &self,
+        system: &Arc<SystemInner>,
+        authority: &str,
+        path: &str,
+        notify: Notify,
+    ) {
+        let authority: Arc<str> = authority.into();
+        // the watch is not buffered, the system might not come back
+        if self.buffers.lock().unwrap().contains_key(&authority) {
+            notify(true);
+            return;
+        }
+        self.watchers
+            .lock()
+            .unwrap()
+            .entry(Arc::clone(&authority))
+            .or_default()
+            .entry(path.to_owned())
+            .or_default()
+            .push(notify);
+        if self.send(system, &authority, encode_frame(WATCH, path, &[]), None) == false {
+            self.unreachable(&authority);
+        }
+    }
+
+    /// Another system watches one of our actors.
+    fn watched(&self, path: &str, replies: Frames) {
+        let cell = self
+            .exposed
+            .lock()
+            .unwrap()
+            .get(path)
+            .map(|exposed| Arc::clone(&exposed.cell));
+        let path = path.to_owned();
+        let _handle = tokio::spawn(async move {
+            if let Some(cell) = cell {
+                cell.wait_for_stop().await;
+            }
+            let _ = replies.send((encode_frame(TERMINATED, &path, &[]), None));
+        });
+    }
+
+    fn terminated(&self, authority: &str, path: &str) {
+        let watchers = self
+            .watchers
+            .lock()
+            .unwrap()
+            .get_mut(authority)
+            .and_then(|watchers| watchers.remove(path));
+        for notify in watchers.into_iter().flatten() {
+            notify(false);
+        }
+    }
+
+    fn unreachable(&self, authority: &str) {
+        let watchers = self.watchers.lock().unwrap().remove(authority);
+        for notify in watchers
+            .into_iter()
+            .flat_map(HashMap::into_values)
+            .flatten()
+        {
+            notify(true);
+        }
+    }
+}
+
+/// Write outgoing frames and handle incoming frames until either side closes the connection.
+///
+/// Outbound connections start with a hello, inbound connections are authenticated by it.
+async fn run_connection(
+    stream: Box<dyn Connection>,
+    system: Weak<SystemInner>,
+    side: Side,
+    replies: Frames,
+    outgoing: &mut Outgoing,
+    failure_detection: Option<(FailureDetection, Option<NewDetector>)>,
+) {
+    let hello = match (&side, system.upgrade()) {
+        (Side::Outbound(_), Some(system)) => {
+            let token = system.remote.auth_token.lock().unwrap().clone();
+            Some(encode_frame(
+                HELLO,
+                "",
+                token.as_deref().unwrap_or("").as_bytes(),
+            ))
+        }
+        _ => None,
+    };
+    let (read, write) = tokio::io::split(stream);
+    let detector = failure_detection.as_ref().map(|(failure_detection, new)| {
+        let mut detector = match new {
+            Some(new) => new(),
+            None => Box::new(Deadline {
+                timeout: failure_detection.timeout,
+                last_heartbeat: None,
+            }),
+        };
+        detector.heartbeat(Instant::now());
+        Arc::new(Mutex::new(detector))
+    });
+    let mut reader = tokio::spawn(read_frames(read, system, side, replies, detector.clone()));
+    let mut heartbeat = failure_detection.map(|(failure_detection, _)| {
+        let mut interval = tokio::time::interval(failure_detection.interval);
+        interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        interval
+    });
+
+    let mut write = BufWriter::new(write);
+    if let Some(hello) = hello {
+        if write.write_all(&hello).await.is_err()
+            || (outgoing.is_empty() && write.flush().await.is_err())
+        {
+            reader.abort();
+            return;
+        }
+    }
+    loop {
+        let frame = tokio::select! {
+            frame = outgoing.recv() => frame.map(|(frame, _)| frame),
+            () = tick(&mut heartbeat) => {
+                let now = Instant::now();
+                if detector.as_ref().is_some_and(|d| d.lock().unwrap().is_available(now) == false) {
+                    break;
+                }
+                Some(encode_frame(HEARTBEAT, "", &[]))
+            }
+            _ = &mut reader => break,
+        };
+        let Some(frame) = frame else {
+            break;
+        };
+        if write.write_all(&frame).await.is_err() {
+            break;
+        }
+        if outgoing.is_empty() && write.flush().await.is_err() {
+            break;
+        }
+    }
+    reader.abort();
+}
+
+async fn tick(heartbeat: &mut Option<Interval>) {
+    match heartbeat {
+        Some(interval) => {
+            let _ = interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+async fn read_frames(
+    read: ReadHalf<Box<dyn Connection>>,
+    system: Weak<SystemInner>,
+    side: Side,
+    replies: Frames,
+    detector: Option<Detector>,
+) {
+    let mut authenticate = match &side {
+        Side::Inbound(certificate) => system
+            .upgrade()
+            .and_then(|system| system.remote.authenticator.lock().unwrap().clone())
+            .map(|authenticator| (authenticator, certificate.as_deref())),
+        Side::Outbound(_) => None,
+    };
+    let mut peer = None;
+    let mut read = BufReader::new(read);
+    let mut frame = Vec::new();
+    loop {
+        let Ok(len) = read.read_u32().await else {
+            return;
+        };
+        let len = len as usize;
+        if len > MAX_FRAME_LEN {
+            return;
+        }
+        frame.resize(len, 0);
+        if read.read_exact(&mut frame).await.is_err() {
+            return;
+        }
+        if let Some(detector) = &detector {
+            detector.lock().unwrap().heartbeat(Instant::now());
+        }
+
+        let Some(system) = system.upgrade() else {
+            return;
+        };
+        let decoded = decode_frame(&frame);
+        if let Some((authenticator, certificate)) = authenticate.take() {
+            let Some((HELLO, _, token)) = decoded else {
+                return;
+            };
+            let Ok(token) = std::str::from_utf8(token) else {
+                return;
+            };
+            let credentials = Credentials {
+                token: (token.is_empty() == false).then_some(token),
+                certificate,
+            };
+            match authenticator.authenticate(&credentials) {
+                Ok(identity) => peer = Some(identity),
+                Err(_) => return,
+            }
+            continue;
+        }
+        let Some((kind, path, payload)) = decoded else {
+            continue;
+        };
+        match kind {
+            TELL => system.remote.deliver(path, payload, peer.clone()),
+            WATCH => system.remote.watched(path, replies.clone()),
+            TERMINATED => {
+                if let Side::Outbound(authority) = &side {
+                    system.remote.terminated(authority, path);
+                }
+            }
+            HEARTBEAT => {
+                let _ = replies.send((encode_frame(HEARTBEAT_ACK, "", &[]), None));
+            }
+            _ => {}
+        }
+    }
+}
+
+/// `[len: u32][kind: u8][path len: u16][path][payload]`, where `len` counts everything after itself.
+fn encode_frame(kind: u8, path: &str, payload: &[u8]) -> Vec<u8> {
+    let len = 3 + path.len() + payload.len();
+    let mut frame = Vec::with_capacity(4 + len);
+    frame.extend_from_slice(&(len as u32).to_be_bytes());
+    frame.push(kind);
+    frame.extend_from_slice(&(path.len() as u16).to_be_bytes());
+    frame.extend_from_slice(path.as_bytes());
+    frame.extend_from_slice(payload);
+    frame
+}
+
+fn decode_frame(frame: &[u8]) -> Option<(u8, &str, &[u8])> {
+    let (kind, rest) = frame.split_first()?;
+    let (path_len, rest) = rest.split_first_chunk::<2>()?;
+    let path_len = u16::from_be_bytes(*path_len) as usize;
+    if rest.len() < path_len {
+        return None;
+    }
+    let (path, payload) = rest.split_at(path_len);
+    Some((*kind, std::str::from_utf8(path).ok()?, payload))
+}
+
+impl std::fmt::Debug for Remote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Remote")
+            .field("exposed", &self.exposed.lock().unwrap().len())
+            .field("connections", &self.connections.lock().unwrap().len())
+            .field("buffers", &self.buffers.lock().unwrap().len())
+            .field(
+                "failure_detection",
+                &*self.failure_detection.lock().unwrap(),
+            )
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,243 @@
+use super::transport::{Accepted, Connection, Io, Listener, Transport};
+use bytes::{Buf, BufMut, BytesMut};
+use std::{
+    convert::Infallible,
+    future::{ready, Ready},
+    io,
+    net::SocketAddr,
+    task::{Context, Poll},
+};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt, DuplexStream},
+    net::TcpListener,
+    sync::mpsc,
+    task::JoinHandle,
+};
+use tonic::{
+    body::Body,
+    codec::{Codec, DecodeBuf, Decoder, EncodeBuf, Encoder},
+    codegen::{
+        http::{self, uri::PathAndQuery},
+        tokio_stream::wrappers::ReceiverStream,
+        BoxFuture, Bytes, Service,
+    },
+    transport::{server::TcpIncoming, Endpoint, Server},
+    Request, Response, Status, Streaming,
+};
+
+/// The bidirectional streaming call that carries one connection.
+const CONNECT: &str = "/elliot.Remote/Connect";
+/// The largest chunk of a connection that is sent in one message.
+const CHUNK: usize = 64 * 1024;
+
+/// Connections as bidirectional streaming gRPC calls, for networks that only pass HTTP/2,
+/// e.g. service meshes and L7 load balancers.
+///
+/// Every connection to another system is one call over its own HTTP/2 connection.
+/// The calls are not encrypted, TLS is expected to be terminated by the mesh or the load balancer.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct GrpcTransport;
+
+impl Transport for GrpcTransport {
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>> {
+        Box::pin(async move {
+            let channel = Endpoint::from_shared(format!("http://{authority}"))
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?
+                .tcp_nodelay(true)
+                .connect()
+                .await
+                .map_err(|e| io::Error::new(io::ErrorKind::ConnectionRefused, e))?;
+            let mut client = tonic::client::Grpc::new(channel);
+            client.ready().await.map_err(io::Error::other)?;
+            let (outbound, requests) = mpsc::channel(1);
+            let inbound = client
+                .streaming(
+                    Request::new(ReceiverStream::new(requests)),
+                    PathAndQuery::from_static(CONNECT),
+                    Chunks,
+                )
+                .await
+                .map_err(io::Error::other)?
+                .into_inner();
+            let (connection, io) = tokio::io::duplex(CHUNK);
+            let _handle = tokio::spawn(pump(io, inbound, outbound, |chunk| chunk));
+            let connection: Box<dyn Connection> = Box::new(connection);
+            Ok(connection)
+        })
+    }
+
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>> {
+        Box::pin(async move {
+            let listener = TcpListener::bind(addr).await?;
+            let local_addr = listener.local_addr()?;
+            let (accepted, incoming) = mpsc::unbounded_channel();
+            let server = Server::builder().serve_with_incoming(
+                Connect { accepted },
+                TcpIncoming::from(listener).with_nodelay(Some(true)),
+            );
+            let listener: Box<dyn Listener> = Box::new(GrpcListener {
+                local_addr,
+                incoming,
+                server: tokio::spawn(server),
+            });
+            Ok(listener)
+        })
+    }
+}
+
+/// Serves the calls until it is dropped.
+struct GrpcListener {
+    local_addr: SocketAddr,
+    incoming: mpsc::UnboundedReceiver<Accepted>,
+    server: JoinHandle<Result<(), tonic::transport::Error>>,
+}
+
+impl Listener for GrpcListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.local_addr)
+    }
+
+    fn accept(&mut self) -> Io<'_, Accepted> {
+        Box::pin(async move {
+            let accepted = self
+                .incoming
+                .recv()
+                .await
+                .ok_or(io::ErrorKind::BrokenPipe)?;
+            Ok(accepted)
+        })
+    }
+}
+
+impl Drop for GrpcListener {
+    fn drop(&mut self) {
+        self.server.abort();
+    }
+}
+
+/// Routes the calls to [`CONNECT`], everything else is unimplemented.
+#[derive(Clone)]
+struct Connect {
+    accepted: mpsc::UnboundedSender<Accepted>,
+}
+
+impl Service<http::Request<Body>> for Connect {
+    type Response = http::Response<Body>;
+    type Error = Infallible;
+    type Future = BoxFuture<Self::Response, Self::Error>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: http::Request<Body>) -> Self::Future {
+        let accepted = self.accepted.clone();
+        Box::pin(async move {
+            if request.uri().path() != CONNECT {
+                return Ok(Status::unimplemented(request.uri().path()).into_http());
+            }
+            let mut server = tonic::server::Grpc::new(Chunks);
+            Ok(server.streaming(Accept(accepted), request).await)
+        })
+    }
+}
+
+/// Hands every call to the listener as a new connection.
+struct Accept(mpsc::UnboundedSender<Accepted>);
+
+impl Service<Request<Streaming<Bytes>>> for Accept {
+    type Response = Response<ReceiverStream<Result<Bytes, Status>>>;
+    type Error = Status;
+    type Future = Ready<Result<Self::Response, Self::Error>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn call(&mut self, request: Request<Streaming<Bytes>>) -> Self::Future {
+        let (connection, io) = tokio::io::duplex(CHUNK);
+        if self.0.send(Accepted::new(connection)).is_err() {
+            return ready(Err(Status::unavailable("the system is no longer bound")));
+        }
+        let (outbound, responses) = mpsc::channel(1);
+        let _handle = tokio::spawn(pump(io, request.into_inner(), outbound, Ok));
+        ready(Ok(Response::new(ReceiverStream::new(responses))))
+    }
+}
+
+/// Copies what is written to the connection into the outbound messages of the call,
+/// and the inbound messages of the call into what is read from the connection.
+async fn pump<T>(
+    io: DuplexStream,
+    mut inbound: Streaming<Bytes>,
+    outbound: mpsc::Sender<T>,
+    message: fn(Bytes) -> T,
+) {
+    let (mut reader, mut writer) = tokio::io::split(io);
+    let send = async move {
+        let mut chunk = BytesMut::new();
+        loop {
+            chunk.reserve(CHUNK);
+            match reader.read_buf(&mut chunk).await {
+                Ok(0) | Err(_) => break,
+                Ok(_) => {
+                    if outbound
+                        .send(message(chunk.split().freeze()))
+                        .await
+                        .is_err()
+                    {
+                        break;
+                    }
+                }
+            }
+        }
+    };
+    let receive = async move {
+        while let Ok(Some(chunk)) = inbound.message().await {
+            if writer.write_all(&chunk).await.is_err() {
+                break;
+            }
+        }
+        let _ = writer.shutdown().await;
+    };
+    let ((), ()) = tokio::join!(send, receive);
+}
+
+/// The messages are the raw chunks of the connection, the frames of the remoting protocol
+/// are not aligned to them.
+#[derive(Clone, Copy, Debug, Default)]
+struct Chunks;
+
+impl Codec for Chunks {
+    type Encode = Bytes;
+    type Decode = Bytes;
+    type Encoder = Self;
+    type Decoder = Self;
+
+    fn encoder(&mut self) -> Self::Encoder {
+        Self
+    }
+
+    fn decoder(&mut self) -> Self::Decoder {
+        Self
+    }
+}
+
+impl Encoder for Chunks {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn encode(&mut self, item: Bytes, dst: &mut EncodeBuf<'_>) -> Result<(), Status> {
+        dst.put(item);
+        Ok(())
+    }
+}
+
+impl Decoder for Chunks {
+    type Item = Bytes;
+    type Error = Status;
+
+    fn decode(&mut self, src: &mut DecodeBuf<'_>) -> Result<Option<Bytes>, Status> {
+        Ok(Some(src.copy_to_bytes(src.remaining())))
+    }
+}
@@ -0,0 +1,157 @@
+use super::{
+    tls::host,
+    transport::{Accepted, Connection, Io, Listener, Transport},
+};
+use quinn::{
+    crypto::rustls::{QuicClientConfig, QuicServerConfig},
+    Endpoint,
+};
+use std::{
+    collections::HashMap,
+    io,
+    net::{Ipv4Addr, Ipv6Addr, SocketAddr},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::{sync::mpsc, task::JoinSet};
+use tokio_rustls::rustls::{pki_types::CertificateDer, ClientConfig, ServerConfig};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connections as streams of QUIC connections, encrypted with TLS 1.3.
+///
+/// Every connection that remoting opens to another system is its own bidirectional stream,
+/// the streams to one system share a single QUIC connection. A lost packet only holds up
+/// the stream that it belongs to, not all traffic to the system.
+///
+/// Other systems are verified with `client` and systems that connect with `server`,
+/// like with the [`TlsTransport`](crate::TlsTransport).
+/// The verified client certificate is handed to the [`Authenticate`](crate::Authenticate) hook.
+#[derive(Clone)]
+pub struct QuicTransport {
+    client: quinn::ClientConfig,
+    server: quinn::ServerConfig,
+    connections: Arc<Mutex<HashMap<String, quinn::Connection>>>,
+}
+
+impl QuicTransport {
+    /// Fails if the configs do not support TLS 1.3, which QUIC requires.
+    pub fn new(client: Arc<ClientConfig>, server: Arc<ServerConfig>) -> io::Result<Self> {
+        let client = QuicClientConfig::try_from(client)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let server = QuicServerConfig::try_from(server)
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        Ok(Self {
+            client: quinn::ClientConfig::new(Arc::new(client)),
+            server: quinn::ServerConfig::with_crypto(Arc::new(server)),
+            connections: Arc::default(),
+        })
+    }
+
+    /// The open connection to `authority`, or a new one.
+    async fn connection(&self, authority: &str) -> io::Result<quinn::Connection> {
+        let open = self.connections.lock().unwrap().get(authority).cloned();
+        if let Some(connection) = open.filter(|c| c.close_reason().is_none()) {
+            return Ok(connection);
+        }
+        let addr = tokio::net::lookup_host(authority)
+            .await?
+            .next()
+            .ok_or(io::ErrorKind::AddrNotAvailable)?;
+        let local = match addr {
+            SocketAddr::V4(_) => SocketAddr::from((Ipv4Addr::UNSPECIFIED, 0)),
+            SocketAddr::V6(_) => SocketAddr::from((Ipv6Addr::UNSPECIFIED, 0)),
+        };
+        let endpoint = Endpoint::client(local)?;
+        let connecting = endpoint
+            .connect_with(self.client.clone(), addr, host(authority))
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let connection = tokio::time::timeout(HANDSHAKE_TIMEOUT, connecting)
+            .await
+            .map_err(|_| io::ErrorKind::TimedOut)??;
+        let _ = self
+            .connections
+            .lock()
+            .unwrap()
+            .insert(authority.to_owned(), connection.clone());
+        Ok(connection)
+    }
+}
+
+impl Transport for QuicTransport {
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>> {
+        Box::pin(async move {
+            let (send, recv) = self.connection(authority).await?.open_bi().await?;
+            let stream: Box<dyn Connection> = Box::new(tokio::io::join(recv, send));
+            Ok(stream)
+        })
+    }
+
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>> {
+        Box::pin(async move {
+            let (streams, incoming) = mpsc::unbounded_channel();
+            let listener: Box<dyn Listener> = Box::new(QuicListener {
+                endpoint: Endpoint::server(self.server.clone(), addr)?,
+                connections: JoinSet::new(),
+                streams,
+                incoming,
+            });
+            Ok(listener)
+        })
+    }
+}
+
+/// Accepts the streams of every connection concurrently, until it is dropped.
+struct QuicListener {
+    endpoint: Endpoint,
+    connections: JoinSet<()>,
+    streams: mpsc::UnboundedSender<Accepted>,
+    incoming: mpsc::UnboundedReceiver<Accepted>,
+}
+
+impl Listener for QuicListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.endpoint.local_addr()
+    }
+
+    fn accept(&mut self) -> Io<'_, Accepted> {
+        Box::pin(async move {
+            loop {
+                tokio::select! {
+                    Some(accepted) = self.incoming.recv() => return Ok(accepted),
+                    incoming = self.endpoint.accept() => {
+                        let incoming = incoming.ok_or(io::ErrorKind::BrokenPipe)?;
+                        let _ = self.connections.spawn(streams(incoming, self.streams.clone()));
+                    }
+                    Some(_) = self.connections.join_next() => {}
+                }
+            }
+        })
+    }
+}
+
+/// Accepts the streams of one connection, failed handshakes only close their own connection.
+async fn streams(incoming: quinn::Incoming, streams: mpsc::UnboundedSender<Accepted>) {
+    let Ok(Ok(connection)) = tokio::time::timeout(HANDSHAKE_TIMEOUT, incoming).await else {
+        return;
+    };
+    let certificate = connection
+        .peer_identity()
+        .and_then(|identity| identity.downcast::<Vec<CertificateDer<'static>>>().ok())
+        .and_then(|certificates| certificates.first().map(|c| c.to_vec()));
+    while let Ok((send, recv)) = connection.accept_bi().await {
+        let accepted = Accepted {
+            connection: Box::new(tokio::io::join(recv, send)),
+            certificate: certificate.clone(),
+        };
+        if streams.send(accepted).is_err() {
+            return;
+        }
+    }
+}
+
+impl std::fmt::Debug for QuicTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("QuicTransport").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,124 @@
+use super::transport::{Accepted, Connection, Io, Listener, Transport};
+use std::{io, net::SocketAddr, sync::Arc, time::Duration};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    task::JoinSet,
+};
+use tokio_rustls::{
+    rustls::{pki_types::ServerName, ClientConfig, ServerConfig},
+    TlsAcceptor, TlsConnector,
+};
+
+const HANDSHAKE_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Connections over TCP, encrypted with TLS.
+///
+/// Other systems are verified with `client` when connecting to them, by the host of their address.
+/// Systems that connect are verified with `server`, which can require client certificates for mutual TLS.
+/// The verified client certificate is handed to the [`Authenticate`](crate::Authenticate) hook.
+#[derive(Clone)]
+pub struct TlsTransport {
+    connector: TlsConnector,
+    acceptor: TlsAcceptor,
+}
+
+impl TlsTransport {
+    pub fn new(client: Arc<ClientConfig>, server: Arc<ServerConfig>) -> Self {
+        Self {
+            connector: TlsConnector::from(client),
+            acceptor: TlsAcceptor::from(server),
+        }
+    }
+}
+
+impl Transport for TlsTransport {
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>> {
+        Box::pin(async move {
+            let name = ServerName::try_from(host(authority).to_owned())
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let stream = TcpStream::connect(authority).await?;
+            let _ = stream.set_nodelay(true);
+            let handshake = self.connector.connect(name, stream);
+            let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+                .await
+                .map_err(|_| io::ErrorKind::TimedOut)??;
+            let stream: Box<dyn Connection> = Box::new(stream);
+            Ok(stream)
+        })
+    }
+
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>> {
+        Box::pin(async move {
+            let listener: Box<dyn Listener> = Box::new(TlsListener {
+                listener: TcpListener::bind(addr).await?,
+                acceptor: self.acceptor.clone(),
+                handshakes: JoinSet::new(),
+            });
+            Ok(listener)
+        })
+    }
+}
+
+/// Runs the handshakes concurrently, so that a slow client does not hold up the others.
+struct TlsListener {
+    listener: TcpListener,
+    acceptor: TlsAcceptor,
+    handshakes: JoinSet<io::Result<Accepted>>,
+}
+
+impl Listener for TlsListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.listener.local_addr()
+    }
+
+    fn accept(&mut self) -> Io<'_, Accepted> {
+        Box::pin(async move {
+            loop {
+                tokio::select! {
+                    accepted = self.listener.accept() => {
+                        let (stream, _) = accepted?;
+                        let _ = stream.set_nodelay(true);
+                        let handshake = self.acceptor.accept(stream);
+                        let _ = self.handshakes.spawn(async move {
+                            let stream = tokio::time::timeout(HANDSHAKE_TIMEOUT, handshake)
+                                .await
+                                .map_err(|_| io::ErrorKind::TimedOut)??;
+                            let certificate = stream
+                                .get_ref()
+                                .1
+                                .peer_certificates()
+                                .and_then(|certificates| certificates.first())
+                                .map(|certificate| certificate.to_vec());
+                            Ok(Accepted {
+                                connection: Box::new(stream),
+                                certificate,
+                            })
+                        });
+                    }
+                    Some(handshake) = self.handshakes.join_next() => {
+                        // failed handshakes only close their own connection
+                        if let Ok(Ok(accepted)) = handshake {
+                            return Ok(accepted);
+                        }
+                    }
+                }
+            }
+        })
+    }
+}
+
+/// The host of `host:port`, without the brackets of IPv6 addresses.
+pub(super) fn host(authority: &str) -> &str {
+    let host = authority
+        .rsplit_once(':')
+        .map_or(authority, |(host, _)| host);
+    host.strip_prefix('[')
+        .and_then(|host| host.strip_suffix(']'))
+        .unwrap_or(host)
+}
+
+impl std::fmt::Debug for TlsTransport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TlsTransport").finish_non_exhaustive()
+    }
+}
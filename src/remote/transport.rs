@@ -0,0 +1,92 @@
+use std::{future::Future, io, net::SocketAddr, pin::Pin};
+use tokio::{
+    io::{AsyncRead, AsyncWrite},
+    net::{TcpListener, TcpStream},
+};
+
+pub(super) type Io<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// A byte stream between two systems, opened by a [`Transport`].
+pub trait Connection: AsyncRead + AsyncWrite + Send + Unpin + 'static {}
+
+impl<C: AsyncRead + AsyncWrite + Send + Unpin + 'static> Connection for C {}
+
+/// How systems connect to each other, see [`ActorSystem::set_transport`](crate::ActorSystem::set_transport).
+///
+/// Remoting writes its frames to the connections and reads them back, the transport decides
+/// how the bytes get to the other system. The default is plain TCP, with the [`TcpTransport`].
+pub trait Transport: Send + Sync + 'static {
+    /// Open a connection to the system at `authority`, given as `host:port`.
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>>;
+
+    /// Accept connections from other systems on `addr`.
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>>;
+}
+
+/// The connections that other systems open to a bound [`Transport`].
+pub trait Listener: Send + 'static {
+    fn local_addr(&self) -> io::Result<SocketAddr>;
+
+    fn accept(&mut self) -> Io<'_, Accepted>;
+}
+
+/// A connection that another system has opened.
+pub struct Accepted {
+    pub connection: Box<dyn Connection>,
+    /// The DER certificate that the transport has verified the other system with, e.g. with mutual TLS.
+    pub certificate: Option<Vec<u8>>,
+}
+
+impl Accepted {
+    pub fn new(connection: impl Connection) -> Self {
+        Self {
+            connection: Box::new(connection),
+            certificate: None,
+        }
+    }
+}
+
+impl std::fmt::Debug for Accepted {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Accepted")
+            .field("certificate", &self.certificate.is_some())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Connections over plain TCP, with Nagle's algorithm turned off.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct TcpTransport;
+
+impl Transport for TcpTransport {
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>> {
+        Box::pin(async move {
+            let stream = TcpStream::connect(authority).await?;
+            let _ = stream.set_nodelay(true);
+            let stream: Box<dyn Connection> = Box::new(stream);
+            Ok(stream)
+        })
+    }
+
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>> {
+        Box::pin(async move {
+            let listener = TcpListener::bind(addr).await?;
+            let listener: Box<dyn Listener> = Box::new(listener);
+            Ok(listener)
+        })
+    }
+}
+
+impl Listener for TcpListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        TcpListener::local_addr(self)
+    }
+
+    fn accept(&mut self) -> Io<'_, Accepted> {
+        Box::pin(async move {
+            let (stream, _) = TcpListener::accept(self).await?;
+            let _ = stream.set_nodelay(true);
+            Ok(Accepted::new(stream))
+        })
+    }
+}
@@ -0,0 +1,66 @@
+use crate::{intercept::Handled, ActorContext, Behavior, Signal};
+use std::{marker::PhantomData, sync::Arc};
+
+/// The behavior that an actor continues with, see [`ActorRef::replace_behavior`](crate::ActorRef::replace_behavior).
+pub(crate) struct Replacement<T>(Box<dyn DynBehavior<T>>);
+
+/// The object safe part of [`Behavior`], so that behaviors of any type can replace each other.
+trait DynBehavior<T>: Send + Sync + 'static {
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Handled<T>;
+
+    fn receive_signal(&self, context: &ActorContext<T>, signal: Signal) -> Option<Handled<T>>;
+
+    fn describe(&self, msg: &T) -> Option<Arc<str>>;
+}
+
+struct Boxed<B, Args> {
+    behavior: B,
+    _args: PhantomData<fn(Args)>,
+}
+
+pub(crate) fn replacement<T, B, Args>(behavior: B) -> Replacement<T>
+where
+    T: 'static,
+    B: Behavior<T, Args>,
+    Args: 'static,
+{
+    Replacement(Box::new(Boxed {
+        behavior,
+        _args: PhantomData,
+    }))
+}
+
+impl<T, B, Args> DynBehavior<T> for Boxed<B, Args>
+where
+    T: 'static,
+    B: Behavior<T, Args>,
+    Args: 'static,
+{
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Handled<T> {
+        Box::pin(self.behavior.receive(context, msg))
+    }
+
+    fn receive_signal(&self, context: &ActorContext<T>, signal: Signal) -> Option<Handled<T>> {
+        self.behavior.receive_signal(context, signal)
+    }
+
+    fn describe(&self, msg: &T) -> Option<Arc<str>> {
+        self.behavior.describe(msg)
+    }
+}
+
+impl<T: 'static> Behavior<T> for Replacement<T> {
+    type F = Handled<T>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        self.0.receive(context, msg)
+    }
+
+    fn receive_signal(&self, context: &ActorContext<T>, signal: Signal) -> Option<Handled<T>> {
+        self.0.receive_signal(context, signal)
+    }
+
+    fn describe(&self, msg: &T) -> Option<Arc<str>> {
+        self.0.describe(msg)
+    }
+}
@@ -0,0 +1,302 @@
+use crate::{
+    behavior,
+    runtime::{self, AbortHandle},
+    ActorContext, ActorPath, ActorRef, ActorRefGone, ActorSystem, Behavior, Behaviors, Error,
+};
+use std::{
+    collections::hash_map::RandomState,
+    future::{ready, Ready},
+    hash::{BuildHasher, Hasher},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+/// How a [`Pool`] picks the routee for each message.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Strategy {
+    /// Send to every routee in turn.
+    RoundRobin,
+    /// Send to a randomly chosen routee.
+    Random,
+    /// Send to the routee with the fewest messages waiting in its mailbox.
+    SmallestMailbox,
+}
+
+/// Grows or shrinks a [`Pool`] based on the mailbox pressure of its routees.
+///
+/// Every `interval`, the pool grows by one routee if the routees have on average at least
+/// `pressure_threshold` messages waiting, and shrinks by one routee if no routee
+/// has any messages waiting.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Resizer {
+    lower: usize,
+    upper: usize,
+    pressure_threshold: usize,
+    interval: Duration,
+}
+
+impl Resizer {
+    pub fn new(lower: usize, upper: usize) -> Self {
+        Self {
+            lower,
+            upper: upper.max(lower),
+            pressure_threshold: 1,
+            interval: Duration::from_millis(100),
+        }
+    }
+
+    pub fn pressure_threshold(mut self, pressure_threshold: usize) -> Self {
+        self.pressure_threshold = pressure_threshold.max(1);
+        self
+    }
+
+    pub fn interval(mut self, interval: Duration) -> Self {
+        self.interval = interval;
+        self
+    }
+}
+
+/// A router in front of a pool of identical routees.
+///
+/// The router is an actor itself, messages are sent to it and then forwarded to one of the routees.
+pub struct Pool<T> {
+    router: ActorRef<T>,
+    routees: Arc<Mutex<Routees<T>>>,
+}
+
+type SpawnRoutee<T> = Box<dyn Fn(usize) -> ActorRef<T> + Send + Sync>;
+
+struct Routees<T> {
+    routees: Vec<ActorRef<T>>,
+    selector: Selector,
+    spawn: SpawnRoutee<T>,
+    spawned: usize,
+    resizer: Option<AbortHandle>,
+}
+
+struct Router<T> {
+    routees: Arc<Mutex<Routees<T>>>,
+}
+
+/// Picks routees by a [`Strategy`].
+#[derive(Debug)]
+pub(crate) struct Selector {
+    strategy: Strategy,
+    next: usize,
+    rng: u64,
+}
+
+impl ActorSystem {
+    /// Spawn a router named `name` with `size` routees that all run a clone of `behavior`.
+    pub fn spawn_pool<T, N, A, Args>(
+        &self,
+        name: N,
+        size: usize,
+        strategy: Strategy,
+        behavior: A,
+    ) -> Pool<T>
+    where
+        T: Send + 'static,
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args> + Clone,
+    {
+        let path = ActorPath::user(&name.into());
+        let system = self.clone();
+        let routee_path = path.clone();
+        let spawn = move |i: usize| {
+            let path = routee_path.child(&format!("$routee-{i}"));
+            behavior::actor_of(&system, path, behavior.clone())
+        };
+        let mut routees = Routees {
+            routees: Vec::with_capacity(size),
+            selector: Selector::new(strategy),
+            spawn: Box::new(spawn),
+            spawned: 0,
+            resizer: None,
+        };
+        routees.resize(size);
+        let routees = Arc::new(Mutex::new(routees));
+        let router = Router {
+            routees: Arc::clone(&routees),
+        };
+        let router = behavior::actor_of(self, path, router);
+        Pool { router, routees }
+    }
+}
+
+impl<T> Pool<T> {
+    pub fn tell(&self, msg: T) -> Result<(), ActorRefGone<T>> {
+        self.router.tell(msg)
+    }
+
+    /// The ref of the router actor, messages sent to it are forwarded to the routees.
+    pub fn router(&self) -> ActorRef<T> {
+        self.router.clone()
+    }
+
+    pub fn routees(&self) -> Vec<ActorRef<T>> {
+        self.routees.lock().unwrap().routees.clone()
+    }
+
+    pub fn size(&self) -> usize {
+        self.routees.lock().unwrap().routees.len()
+    }
+
+    pub fn strategy(&self) -> Strategy {
+        self.routees.lock().unwrap().selector.strategy
+    }
+
+    /// Spawn or remove routees until the pool has `size` routees.
+    ///
+    /// The most recently spawned routees are removed first. Removed routees no longer
+    /// receive new messages, but handle the messages that are already in their mailbox
+    /// and then stop, unless there are other refs to them.
+    pub fn resize(&self, size: usize) {
+        self.routees.lock().unwrap().resize(size);
+    }
+}
+
+impl<T: Send + 'static> Pool<T> {
+    /// Resize the pool automatically until the router stops, replacing any previous resizer.
+    pub fn auto_resize(&self, resizer: Resizer) {
+        let router = self.router.downgrade();
+        let routees = Arc::clone(&self.routees);
+        let runtime = self.router.cell.runtime();
+        let sleep = Arc::clone(&runtime);
+        let handle = runtime::spawn_abortable(&*runtime, "elliot-resizer", async move {
+            loop {
+                sleep.sleep(resizer.interval).await;
+                if router.upgrade().is_none() {
+                    return;
+                }
+                routees.lock().unwrap().auto_resize(&resizer);
+            }
+        });
+
+        let previous = self.routees.lock().unwrap().resizer.replace(handle);
+        if let Some(previous) = previous {
+            previous.abort();
+        }
+    }
+}
+
+impl<T> Routees<T> {
+    fn resize(&mut self, size: usize) {
+        while self.routees.len() < size {
+            self.routees.push((self.spawn)(self.spawned));
+            self.spawned += 1;
+        }
+        self.routees.truncate(size);
+    }
+
+    fn auto_resize(&mut self, resizer: &Resizer) {
+        let size = self.routees.len();
+        let waiting = self
+            .routees
+            .iter()
+            .map(|routee| routee.cell.mailbox_len())
+            .sum::<usize>();
+        let size = if size < resizer.lower {
+            resizer.lower
+        } else if waiting >= resizer.pressure_threshold * size.max(1) {
+            (size + 1).min(resizer.upper)
+        } else if waiting == 0 {
+            size.saturating_sub(1).max(resizer.lower)
+        } else {
+            size
+        };
+        self.resize(size);
+    }
+
+    fn route(&mut self, msg: T) -> Result<(), T> {
+        self.selector.route(&mut self.routees, msg)
+    }
+}
+
+impl Selector {
+    pub(crate) fn new(strategy: Strategy) -> Self {
+        Self {
+            strategy,
+            next: 0,
+            rng: RandomState::new().build_hasher().finish() | 1,
+        }
+    }
+
+    fn select<T>(&mut self, routees: &[ActorRef<T>]) -> usize {
+        let len = routees.len();
+        match self.strategy {
+            Strategy::RoundRobin => {
+                self.next = (self.next + 1) % len;
+                self.next
+            }
+            Strategy::Random => {
+                // xorshift64
+                self.rng ^= self.rng << 13;
+                self.rng ^= self.rng >> 7;
+                self.rng ^= self.rng << 17;
+                (self.rng % len as u64) as usize
+            }
+            Strategy::SmallestMailbox => routees
+                .iter()
+                .enumerate()
+                .min_by_key(|(_, routee)| routee.cell.mailbox_len())
+                .map_or(0, |(i, _)| i),
+        }
+    }
+
+    /// Forward the message to a routee, removing routees that have stopped.
+    pub(crate) fn route<T>(&mut self, routees: &mut Vec<ActorRef<T>>, mut msg: T) -> Result<(), T> {
+        while routees.is_empty() == false {
+            let routee = self.select(routees);
+            match routees[routee].tell(msg) {
+                Ok(()) => return Ok(()),
+                Err(ActorRefGone(returned)) => {
+                    msg = returned;
+                    let _ = routees.remove(routee);
+                }
+            }
+        }
+        Err(msg)
+    }
+}
+
+impl<T: Send + 'static> Behavior<T> for Router<T> {
+    type F = Ready<Result<Behaviors, Error<T>>>;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        let routed = self.routees.lock().unwrap().route(msg);
+        // without routees, the message can be recovered from the dead letters of the router
+        if let Err(msg) = routed {
+            context.this.cell.dead_letter_with(msg);
+        }
+        ready(Ok(Behaviors::Same))
+    }
+}
+
+impl<T> Drop for Routees<T> {
+    fn drop(&mut self) {
+        if let Some(resizer) = self.resizer.take() {
+            resizer.abort();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Pool<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let routees = self.routees.lock().unwrap();
+        f.debug_struct("Pool")
+            .field("router", &self.router)
+            .field("strategy", &routees.selector.strategy)
+            .field("routees", &routees.routees)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<T> Clone for Pool<T> {
+    fn clone(&self) -> Self {
+        Self {
+            router: self.router.clone(),
+            routees: Arc::clone(&self.routees),
+        }
+    }
+}
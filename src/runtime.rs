@@ -0,0 +1,208 @@
+use std::{fmt::Debug, future::Future, pin::Pin, sync::Arc, time::Duration};
+use tokio::{runtime::Handle, sync::watch};
+
+#[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+pub(crate) use std::time::Instant;
+/// `std::time::Instant` panics in the browser.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+pub(crate) use web_time::Instant;
+
+pub(crate) type Task = Pin<Box<dyn Future<Output = ()> + Send + 'static>>;
+
+pub(crate) type LocalTask = Pin<Box<dyn Future<Output = ()> + 'static>>;
+
+pub(crate) type BlockingTask = Box<dyn FnOnce() + Send + 'static>;
+
+/// The executor that runs the actors and the tasks of a system.
+///
+/// The channels of elliot work on any executor, only spawning and sleeping need one.
+pub(crate) trait Runtime: Send + Sync + Debug + 'static {
+    /// Spawn `task` in the background, `name` identifies the task in diagnostics.
+    fn spawn(&self, name: &str, task: Task);
+
+    fn sleep(&self, duration: Duration) -> Task;
+
+    /// Run `task` on a thread where it may block, e.g. for file or database I/O.
+    fn spawn_blocking(&self, task: BlockingTask);
+}
+
+/// Run `f` with [`Runtime::spawn_blocking`] and wait for its result.
+///
+/// # Panics
+///
+/// If `f` panics.
+pub(crate) async fn unblock<R: Send + 'static>(
+    runtime: &dyn Runtime,
+    f: impl FnOnce() -> R + Send + 'static,
+) -> R {
+    let (tx, rx) = tokio::sync::oneshot::channel();
+    runtime.spawn_blocking(Box::new(move || {
+        let _ = tx.send(f());
+    }));
+    rx.await.expect("the blocking task panicked")
+}
+
+/// The runtime of systems that are not configured with one.
+///
+/// In the browser that is the event loop, elsewhere the tokio runtime that builds the system, if any.
+pub(crate) fn default() -> Arc<dyn Runtime> {
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    return Arc::new(Wasm);
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    Arc::new(Tokio(Handle::try_current().ok()))
+}
+
+/// Aborts a task of [`spawn_abortable`], dropping the handle does not abort the task.
+#[derive(Debug)]
+pub(crate) struct AbortHandle(watch::Sender<bool>);
+
+impl AbortHandle {
+    pub(crate) fn abort(&self) {
+        let _ = self.0.send_replace(true);
+    }
+}
+
+/// Like [`Runtime::spawn`], but the task can be aborted.
+pub(crate) fn spawn_abortable(
+    runtime: &dyn Runtime,
+    name: &str,
+    task: impl Future<Output = ()> + Send + 'static,
+) -> AbortHandle {
+    let (handle, mut aborted) = watch::channel(false);
+    runtime.spawn(
+        name,
+        Box::pin(async move {
+            tokio::select! {
+                () = task => {}
+                // a dropped handle fails the wait, which leaves the task running
+                Ok(_) = aborted.wait_for(|aborted| *aborted) => {}
+            }
+        }),
+    );
+    AbortHandle(handle)
+}
+
+/// Spawn `task` on the current thread.
+pub(crate) fn spawn_local(name: &str, task: LocalTask) {
+    let _ = name;
+    #[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+    wasm_bindgen_futures::spawn_local(task);
+    #[cfg(not(all(feature = "wasm", target_arch = "wasm32")))]
+    let _handle = tokio::task::spawn_local(task);
+}
+
+/// Runs on the given tokio runtime or else on the current one.
+#[derive(Debug)]
+pub(crate) struct Tokio(pub(crate) Option<Handle>);
+
+impl Runtime for Tokio {
+    /// With `--cfg tokio_unstable` and the `tracing` feature, the task is named
+    /// so that it can be identified in tokio-console.
+    fn spawn(&self, name: &str, task: Task) {
+        #[cfg(all(tokio_unstable, feature = "tracing"))]
+        {
+            let builder = tokio::task::Builder::new().name(name);
+            let _handle = match &self.0 {
+                Some(runtime) => builder.spawn_on(task, runtime),
+                None => builder.spawn(task),
+            }
+            .expect("failed to spawn task");
+        }
+
+        #[cfg(not(all(tokio_unstable, feature = "tracing")))]
+        {
+            let _ = name;
+            let _handle = match &self.0 {
+                Some(runtime) => runtime.spawn(task),
+                None => tokio::spawn(task),
+            };
+        }
+    }
+
+    fn sleep(&self, duration: Duration) -> Task {
+        // the timer registers with the runtime that is entered at creation
+        let _guard = self.0.as_ref().map(Handle::enter);
+        Box::pin(tokio::time::sleep(duration))
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        let _handle = match &self.0 {
+            Some(runtime) => runtime.spawn_blocking(task),
+            None => tokio::task::spawn_blocking(task),
+        };
+    }
+}
+
+/// Runs on the global executor of smol.
+#[cfg(feature = "smol")]
+#[derive(Debug)]
+pub(crate) struct Smol;
+
+#[cfg(feature = "smol")]
+impl Runtime for Smol {
+    fn spawn(&self, _name: &str, task: Task) {
+        smol::spawn(task).detach();
+    }
+
+    fn sleep(&self, duration: Duration) -> Task {
+        Box::pin(async move {
+            let _ = smol::Timer::after(duration).await;
+        })
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        smol::unblock(task).detach();
+    }
+}
+
+/// Runs on the global executor of async-std.
+#[cfg(feature = "async-std")]
+#[derive(Debug)]
+pub(crate) struct AsyncStd;
+
+#[cfg(feature = "async-std")]
+impl Runtime for AsyncStd {
+    fn spawn(&self, name: &str, task: Task) {
+        let _handle = async_std::task::Builder::new()
+            .name(name.to_owned())
+            .spawn(task)
+            .expect("failed to spawn task");
+    }
+
+    fn sleep(&self, duration: Duration) -> Task {
+        Box::pin(async_std::task::sleep(duration))
+    }
+
+    fn spawn_blocking(&self, task: BlockingTask) {
+        let _handle = async_std::task::spawn_blocking(task);
+    }
+}
+
+/// Runs on the event loop of the browser.
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+#[derive(Debug)]
+pub(crate) struct Wasm;
+
+#[cfg(all(feature = "wasm", target_arch = "wasm32"))]
+impl Runtime for Wasm {
+    fn spawn(&self, _name: &str, task: Task) {
+        wasm_bindgen_futures::spawn_local(task);
+    }
+
+    fn sleep(&self, duration: Duration) -> Task {
+        // the timers of gloo are not `Send`, they are driven by a local task instead
+        let (elapsed, sleeping) = tokio::sync::oneshot::channel();
+        wasm_bindgen_futures::spawn_local(async move {
+            gloo_timers::future::sleep(duration).await;
+            let _ = elapsed.send(());
+        });
+        Box::pin(async move {
+            let _ = sleeping.await;
+        })
+    }
+
+    /// There are no threads to block in the browser, `task` runs right away.
+    fn spawn_blocking(&self, task: BlockingTask) {
+        task();
+    }
+}
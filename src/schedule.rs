@@ -0,0 +1,367 @@
+use crate::{
+    cell::ActorCell,
+    runtime::{self, AbortHandle, Instant, Runtime},
+    ActorRef, InvalidSchedule,
+};
+use std::{
+    collections::HashMap,
+    fmt::Display,
+    str::FromStr,
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::{Duration, SystemTime},
+};
+use tokio::sync::oneshot;
+
+mod cron;
+mod reminders;
+
+pub use cron::Cron;
+pub use reminders::{Reminder, Reminders};
+
+/// A message that will be delivered to an actor after a delay.
+///
+/// Dropping the handle does not cancel the delivery.
+#[derive(Debug)]
+pub struct ScheduledTell<T> {
+    msg: Arc<Mutex<Option<T>>>,
+    /// `None` if the delivery is recorded by a [`BehaviorTestKit`](crate::BehaviorTestKit).
+    handle: Option<AbortHandle>,
+}
+
+impl<T> ScheduledTell<T> {
+    /// Cancel the delivery.
+    ///
+    /// Returns the message if it has not been delivered yet.
+    pub fn cancel(self) -> Option<T> {
+        if let Some(handle) = &self.handle {
+            handle.abort();
+        }
+        self.msg.lock().unwrap().take()
+    }
+
+    /// Returns `true` if the message has been delivered or the delivery has been cancelled.
+    pub fn is_finished(&self) -> bool {
+        self.msg.lock().unwrap().is_none()
+    }
+}
+
+pub(crate) fn tell_after<T: Send + 'static>(
+    target: &ActorRef<T>,
+    msg: T,
+    delay: Duration,
+) -> ScheduledTell<T> {
+    let msg = Arc::new(Mutex::new(Some(msg)));
+    let pending = Arc::clone(&msg);
+    if let Some(effects) = target.cell.effects() {
+        effects.scheduled(target, pending, delay);
+        return ScheduledTell { msg, handle: None };
+    }
+    let target = target.clone();
+    target.cell.busy();
+    let delayed = Delayed(Arc::clone(&target.cell));
+    let runtime = target.cell.runtime();
+    let sleep = runtime.sleep(delay);
+    let handle = runtime::spawn_abortable(&*runtime, "elliot-tell-after", async move {
+        let _delayed = delayed;
+        sleep.await;
+        let msg = pending.lock().unwrap().take();
+        if let Some(msg) = msg {
+            let _ = target.tell(msg);
+        }
+    });
+    ScheduledTell {
+        msg,
+        handle: Some(handle),
+    }
+}
+
+/// Keeps the target of a delayed message busy until the message is delivered or cancelled.
+struct Delayed(Arc<ActorCell>);
+
+impl Drop for Delayed {
+    fn drop(&mut self) {
+        self.0.done();
+    }
+}
+
+/// When a [`Scheduler`] delivers a recurring message.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Schedule {
+    /// Deliver the message every period, starting one period from now.
+    /// If deliveries fall behind, the missed ones are delivered as fast as possible.
+    FixedRate(Duration),
+    /// Wait for the period between deliveries, starting one period from now.
+    /// If deliveries fall behind, the following ones are pushed back.
+    FixedDelay(Duration),
+    /// Deliver the message whenever the cron expression matches.
+    Cron(Cron),
+}
+
+/// Hooks to persist registered schedules, so that they can be re-registered after a restart.
+///
+/// Schedules are identified by their key; the targets and messages are not persisted
+/// and need to be provided again when re-registering the schedule.
+pub trait SchedulePersistence: Send + Sync + 'static {
+    /// A schedule has been registered, possibly replacing a previous one with the same key.
+    fn registered(&self, key: &str, schedule: &Schedule);
+
+    /// A schedule has been cancelled explicitly.
+    ///
+    /// This is not called when a schedule ends because its target actor has stopped.
+    fn cancelled(&self, key: &str);
+}
+
+/// Delivers recurring messages to actors, owned by the [`ActorSystem`](crate::ActorSystem).
+#[derive(Clone)]
+pub struct Scheduler {
+    inner: Arc<SchedulerInner>,
+}
+
+/// A handle to a registered schedule, dropping it does not cancel the schedule.
+#[derive(Debug)]
+pub struct ScheduleHandle {
+    key: Arc<str>,
+    scheduler: Scheduler,
+}
+
+struct SchedulerInner {
+    /// Delays are rounded up to a multiple of the resolution.
+    resolution: Duration,
+    runtime: Arc<dyn Runtime>,
+    next_id: AtomicU64,
+    schedules: Mutex<HashMap<Arc<str>, Entry>>,
+    persistence: Mutex<Option<Arc<dyn SchedulePersistence>>>,
+}
+
+#[derive(Debug)]
+struct Entry {
+    id: u64,
+    schedule: Schedule,
+    /// Dropping the sender ends the task of the schedule.
+    _cancel: oneshot::Sender<()>,
+}
+
+impl Scheduler {
+    pub(crate) fn new(resolution: Duration, runtime: Arc<dyn Runtime>) -> Self {
+        Self {
+            inner: Arc::new(SchedulerInner {
+                resolution: resolution.max(Duration::from_millis(1)),
+                runtime,
+                next_id: AtomicU64::new(0),
+                schedules: Mutex::default(),
+                persistence: Mutex::default(),
+            }),
+        }
+    }
+
+    /// Install the hooks that are called when schedules are registered or cancelled.
+    pub fn set_persistence(&self, persistence: impl SchedulePersistence) {
+        *self.inner.persistence.lock().unwrap() = Some(Arc::new(persistence));
+    }
+
+    /// Deliver a clone of `msg` to `target` according to the schedule.
+    ///
+    /// An existing schedule with the same key is replaced.
+    /// The schedule ends when it is cancelled or when the target actor has stopped.
+    pub fn schedule<T, K>(
+        &self,
+        key: K,
+        schedule: Schedule,
+        target: &ActorRef<T>,
+        msg: T,
+    ) -> ScheduleHandle
+    where
+        T: Clone + Send + 'static,
+        K: Into<Arc<str>>,
+    {
+        let key = key.into();
+        let id = self.inner.next_id.fetch_add(1, Ordering::Relaxed);
+        let target = target.clone();
+        let task = run(schedule.clone(), Arc::clone(&self.inner), target, msg);
+        let this = self.clone();
+        let task_key = Arc::clone(&key);
+        let (cancel, cancelled) = oneshot::channel::<()>();
+        self.inner.runtime.spawn(
+            "elliot-schedule",
+            Box::pin(async move {
+                tokio::select! {
+                    () = task => this.remove(&task_key, id),
+                    _ = cancelled => {}
+                }
+            }),
+        );
+
+        let previous = self.inner.schedules.lock().unwrap().insert(
+            Arc::clone(&key),
+            Entry {
+                id,
+                schedule: schedule.clone(),
+                _cancel: cancel,
+            },
+        );
+        drop(previous);
+        if let Some(persistence) = self.persistence() {
+            persistence.registered(&key, &schedule);
+        }
+
+        ScheduleHandle {
+            key,
+            scheduler: self.clone(),
+        }
+    }
+
+    /// Cancel the schedule with the given key, returns `false` if there was no such schedule.
+    pub fn cancel(&self, key: &str) -> bool {
+        let entry = self.inner.schedules.lock().unwrap().remove(key);
+        if entry.is_none() {
+            return false;
+        }
+        if let Some(persistence) = self.persistence() {
+            persistence.cancelled(key);
+        }
+        true
+    }
+
+    /// All currently active schedules.
+    pub fn schedules(&self) -> Vec<(Arc<str>, Schedule)> {
+        let schedules = self.inner.schedules.lock().unwrap();
+        let mut schedules = schedules
+            .iter()
+            .map(|(key, entry)| (Arc::clone(key), entry.schedule.clone()))
+            .collect::<Vec<_>>();
+        schedules.sort_by(|a, b| a.0.cmp(&b.0));
+        schedules
+    }
+
+    /// Cancel all schedules, without calling the [`SchedulePersistence`], so that they are re-registered after a restart.
+    pub(crate) fn cancel_all(&self) {
+        let schedules = std::mem::take(&mut *self.inner.schedules.lock().unwrap());
+        drop(schedules);
+    }
+
+    fn persistence(&self) -> Option<Arc<dyn SchedulePersistence>> {
+        self.inner.persistence.lock().unwrap().clone()
+    }
+
+    fn remove(&self, key: &str, id: u64) {
+        let mut schedules = self.inner.schedules.lock().unwrap();
+        if schedules.get(key).is_some_and(|entry| entry.id == id) {
+            let _ = schedules.remove(key);
+        }
+    }
+}
+
+impl ScheduleHandle {
+    pub fn key(&self) -> &str {
+        &self.key
+    }
+
+    /// Cancel the schedule, returns `false` if it has already ended or was replaced.
+    pub fn cancel(self) -> bool {
+        self.scheduler.cancel(&self.key)
+    }
+}
+
+async fn run<T: Clone>(
+    schedule: Schedule,
+    scheduler: Arc<SchedulerInner>,
+    target: ActorRef<T>,
+    msg: T,
+) {
+    let resolution = scheduler.resolution;
+    let runtime = &scheduler.runtime;
+    match schedule {
+        Schedule::FixedRate(period) => {
+            // missed deliveries are caught up on, because the deadlines do not move
+            let period = round_up(period, resolution);
+            let mut deadline = Instant::now();
+            loop {
+                deadline += period;
+                runtime
+                    .sleep(deadline.saturating_duration_since(Instant::now()))
+                    .await;
+                if target.tell(msg.clone()).is_err() {
+                    return;
+                }
+            }
+        }
+        Schedule::FixedDelay(period) => {
+            let period = round_up(period, resolution);
+            loop {
+                runtime.sleep(period).await;
+                if target.tell(msg.clone()).is_err() {
+                    return;
+                }
+            }
+        }
+        Schedule::Cron(cron) => {
+            while let Some(next) = cron.next_after(SystemTime::now()) {
+                let delay = next.duration_since(SystemTime::now()).unwrap_or_default();
+                runtime.sleep(round_up(delay, resolution)).await;
+                if target.tell(msg.clone()).is_err() {
+                    return;
+                }
+            }
+        }
+    }
+}
+
+fn round_up(delay: Duration, resolution: Duration) -> Duration {
+    let ticks = delay.as_nanos().div_ceil(resolution.as_nanos()).max(1);
+    resolution * u32::try_from(ticks).unwrap_or(u32::MAX)
+}
+
+impl Display for Schedule {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::FixedRate(period) => write!(f, "rate {}ms", period.as_millis()),
+            Self::FixedDelay(period) => write!(f, "delay {}ms", period.as_millis()),
+            Self::Cron(cron) => write!(f, "cron {cron}"),
+        }
+    }
+}
+
+/// Parses the [`Display`] format of a schedule, i.e. `rate 500ms`, `delay 10s`, or `cron */5 * * * *`.
+impl FromStr for Schedule {
+    type Err = InvalidSchedule;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (kind, value) = s
+            .trim()
+            .split_once(' ')
+            .ok_or_else(|| InvalidSchedule(format!("invalid schedule `{s}`")))?;
+        let period = || {
+            let value = value.trim();
+            let period = match value.strip_suffix("ms") {
+                Some(millis) => millis.parse().map(Duration::from_millis),
+                None => value
+                    .strip_suffix('s')
+                    .unwrap_or(value)
+                    .parse()
+                    .map(Duration::from_secs),
+            };
+            match period {
+                Ok(period) if !period.is_zero() => Ok(period),
+                _ => Err(InvalidSchedule(format!("invalid period `{value}`"))),
+            }
+        };
+        match kind {
+            "rate" => Ok(Self::FixedRate(period()?)),
+            "delay" => Ok(Self::FixedDelay(period()?)),
+            "cron" => Ok(Self::Cron(value.parse()?)),
+            _ => Err(InvalidSchedule(format!("unknown schedule kind `{kind}`"))),
+        }
+    }
+}
+
+impl std::fmt::Debug for Scheduler {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Scheduler")
+            .field("resolution", &self.inner.resolution)
+            .field("schedules", &self.schedules())
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,164 @@
+use crate::InvalidSchedule;
+use std::{
+    fmt::Display,
+    str::FromStr,
+    sync::Arc,
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+/// A cron expression with the five fields `minute hour day-of-month month day-of-week`.
+///
+/// Every field supports `*`, single values, ranges (`1-5`), steps (`*/15`, `0-30/10`)
+/// and lists of those (`1,15,30`). Day-of-week is `0-7`, where both `0` and `7` are Sunday.
+/// If both day fields are restricted, a day matches if either field matches.
+///
+/// All times are evaluated in UTC.
+#[derive(Clone, PartialEq, Eq)]
+pub struct Cron {
+    source: Arc<str>,
+    minutes: Field,
+    hours: Field,
+    days: Field,
+    months: Field,
+    weekdays: Field,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+struct Field {
+    bits: u64,
+    any: bool,
+}
+
+const SECS_PER_DAY: u64 = 86_400;
+/// Do not look further than this into the future when searching for the next match.
+const MAX_LOOKAHEAD_DAYS: u64 = 5 * 366;
+
+impl Cron {
+    /// The next point in time after `after` that matches this expression.
+    ///
+    /// Returns `None` if there is no match within the next five years, e.g. for `0 0 30 2 *`.
+    pub fn next_after(&self, after: SystemTime) -> Option<SystemTime> {
+        let now = after.duration_since(UNIX_EPOCH).ok()?.as_secs();
+        let limit = now + MAX_LOOKAHEAD_DAYS * SECS_PER_DAY;
+        let mut t = (now / 60 + 1) * 60;
+
+        while t < limit {
+            let days = t / SECS_PER_DAY;
+            let (month, day) = month_and_day(days);
+            let weekday = (days + 4) % 7;
+            if !self.months.contains(month) || !self.day_matches(day, weekday) {
+                t = (days + 1) * SECS_PER_DAY;
+                continue;
+            }
+            if !self.hours.contains((t % SECS_PER_DAY) / 3600) {
+                t = (t / 3600 + 1) * 3600;
+                continue;
+            }
+            if !self.minutes.contains((t % 3600) / 60) {
+                t += 60;
+                continue;
+            }
+            return Some(UNIX_EPOCH + Duration::from_secs(t));
+        }
+        None
+    }
+
+    fn day_matches(&self, day: u64, weekday: u64) -> bool {
+        let day_matches = self.days.contains(day);
+        // 7 is an alias for Sunday
+        let weekday_matches =
+            self.weekdays.contains(weekday) || (weekday == 0 && self.weekdays.contains(7));
+        match (self.days.any, self.weekdays.any) {
+            (true, true) => true,
+            (false, true) => day_matches,
+            (true, false) => weekday_matches,
+            (false, false) => day_matches || weekday_matches,
+        }
+    }
+}
+
+/// Converts days since the unix epoch to the month (1-12) and day of month (1-31).
+fn month_and_day(days: u64) -> (u64, u64) {
+    // see http://howardhinnant.github.io/date_algorithms.html#civil_from_days
+    let z = days + 719_468;
+    let doe = z % 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    (month, day)
+}
+
+impl Field {
+    fn contains(self, value: u64) -> bool {
+        self.bits & (1 << value) != 0
+    }
+
+    fn parse(field: &str, name: &str, min: u64, max: u64) -> Result<Self, InvalidSchedule> {
+        let invalid = || InvalidSchedule(format!("invalid {name} field `{field}`"));
+        let number = |s: &str| s.parse::<u64>().map_err(|_| invalid());
+
+        let mut bits = 0;
+        for item in field.split(',') {
+            let (range, step) = match item.split_once('/') {
+                Some((range, step)) => (range, number(step)?),
+                None => (item, 1),
+            };
+            let (from, to) = match range.split_once('-') {
+                Some((from, to)) => (number(from)?, number(to)?),
+                None if range == "*" => (min, max),
+                None if step > 1 => (number(range)?, max),
+                None => {
+                    let value = number(range)?;
+                    (value, value)
+                }
+            };
+            if step == 0 || from < min || to > max || from > to {
+                return Err(invalid());
+            }
+            bits |= (from..=to)
+                .step_by(step as usize)
+                .fold(0, |b, v| b | 1 << v);
+        }
+
+        Ok(Self {
+            bits,
+            any: field == "*",
+        })
+    }
+}
+
+impl FromStr for Cron {
+    type Err = InvalidSchedule;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let fields = s.split_whitespace().collect::<Vec<_>>();
+        let [minutes, hours, days, months, weekdays] = fields[..] else {
+            return Err(InvalidSchedule(format!(
+                "expected 5 fields in cron expression `{s}`, got {}",
+                fields.len()
+            )));
+        };
+        Ok(Self {
+            source: fields.join(" ").into(),
+            minutes: Field::parse(minutes, "minute", 0, 59)?,
+            hours: Field::parse(hours, "hour", 0, 23)?,
+            days: Field::parse(days, "day-of-month", 1, 31)?,
+            months: Field::parse(months, "month", 1, 12)?,
+            weekdays: Field::parse(weekdays, "day-of-week", 0, 7)?,
+        })
+    }
+}
+
+impl Display for Cron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(&self.source)
+    }
+}
+
+impl std::fmt::Debug for Cron {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_tuple("Cron").field(&&*self.source).finish()
+    }
+}
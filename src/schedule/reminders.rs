@@ -0,0 +1,204 @@
+use crate::{
+    runtime::{self, AbortHandle},
+    ActorRef, BoxErr, Journal,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime},
+};
+
+/// The events that [`Reminders`] persist in their journal.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(
+    any(feature = "file-journal", feature = "sqlite"),
+    derive(serde::Serialize, serde::Deserialize)
+)]
+pub enum Reminder<T> {
+    Scheduled {
+        key: String,
+        due: SystemTime,
+        msg: T,
+    },
+    Cancelled {
+        key: String,
+    },
+    Delivered {
+        key: String,
+    },
+}
+
+/// Durable reminders for a single actor, that are delivered even if the process restarts in between.
+///
+/// Every reminder is persisted in the journal and removed once it has been delivered.
+/// A new `Reminders` for the same persistence id picks up all pending reminders and
+/// delivers overdue ones immediately. Reminders are delivered at least once; a reminder
+/// might be delivered again if the process stops right after delivering it.
+///
+/// Dropping the `Reminders` stops all timers, but keeps the reminders in the journal.
+pub struct Reminders<T> {
+    inner: Arc<Inner<T>>,
+}
+
+struct Inner<T> {
+    persistence_id: Arc<str>,
+    journal: Arc<dyn Journal<Reminder<T>>>,
+    target: ActorRef<T>,
+    state: Mutex<State>,
+}
+
+struct State {
+    sequence_nr: u64,
+    next_id: u64,
+    pending: HashMap<String, (u64, AbortHandle)>,
+}
+
+impl<T: Send + 'static> Reminders<T> {
+    /// Recover all pending reminders for `target` from the journal and restart their timers.
+    pub fn recover<J>(
+        persistence_id: impl Into<Arc<str>>,
+        journal: J,
+        target: &ActorRef<T>,
+    ) -> Result<Self, BoxErr>
+    where
+        J: Journal<Reminder<T>>,
+    {
+        let persistence_id = persistence_id.into();
+        let mut sequence_nr = 0;
+        let mut pending = HashMap::new();
+        for (seq, event) in journal.replay(&persistence_id, 1)? {
+            sequence_nr = seq;
+            match event {
+                Reminder::Scheduled { key, due, msg } => {
+                    let _ = pending.insert(key, (due, msg));
+                }
+                Reminder::Cancelled { key } | Reminder::Delivered { key } => {
+                    let _ = pending.remove(&key);
+                }
+            }
+        }
+
+        let inner = Arc::new(Inner {
+            persistence_id,
+            journal: Arc::new(journal),
+            target: target.clone(),
+            state: Mutex::new(State {
+                sequence_nr,
+                next_id: 0,
+                pending: HashMap::new(),
+            }),
+        });
+        {
+            let mut state = inner.state.lock().unwrap();
+            for (key, (due, msg)) in pending {
+                Inner::start_timer(&inner, &mut state, key, due, msg);
+            }
+        }
+        Ok(Self { inner })
+    }
+
+    /// Deliver `msg` to the target after `delay`, replacing any pending reminder with the same key.
+    pub fn remind(&self, key: impl Into<String>, delay: Duration, msg: T) -> Result<(), BoxErr>
+    where
+        T: Clone,
+    {
+        let key = key.into();
+        let due = SystemTime::now() + delay;
+        let mut state = self.inner.state.lock().unwrap();
+        self.inner.append(
+            &mut state,
+            Reminder::Scheduled {
+                key: key.clone(),
+                due,
+                msg: msg.clone(),
+            },
+        )?;
+        Inner::start_timer(&self.inner, &mut state, key, due, msg);
+        Ok(())
+    }
+
+    /// Cancel the pending reminder, returns `false` if there was none.
+    pub fn cancel(&self, key: &str) -> Result<bool, BoxErr> {
+        let mut state = self.inner.state.lock().unwrap();
+        if state.pending.contains_key(key) == false {
+            return Ok(false);
+        }
+        self.inner.append(
+            &mut state,
+            Reminder::Cancelled {
+                key: key.to_owned(),
+            },
+        )?;
+        if let Some((_, timer)) = state.pending.remove(key) {
+            timer.abort();
+        }
+        Ok(true)
+    }
+
+    /// The keys of all pending reminders, sorted.
+    pub fn pending(&self) -> Vec<String> {
+        let state = self.inner.state.lock().unwrap();
+        let mut keys = state.pending.keys().cloned().collect::<Vec<_>>();
+        keys.sort();
+        keys
+    }
+}
+
+impl<T: Send + 'static> Inner<T> {
+    fn append(&self, state: &mut State, event: Reminder<T>) -> Result<(), BoxErr> {
+        self.journal
+            .append(&self.persistence_id, state.sequence_nr + 1, &[event])?;
+        state.sequence_nr += 1;
+        Ok(())
+    }
+
+    fn start_timer(this: &Arc<Self>, state: &mut State, key: String, due: SystemTime, msg: T) {
+        let id = state.next_id;
+        state.next_id += 1;
+        let delay = due
+            .duration_since(SystemTime::now())
+            .unwrap_or(Duration::ZERO);
+        let inner = Arc::clone(this);
+        let timer_key = key.clone();
+        let runtime = this.target.cell.runtime();
+        let sleep = runtime.sleep(delay);
+        let timer = runtime::spawn_abortable(&*runtime, "elliot-reminder", async move {
+            sleep.await;
+            inner.deliver(timer_key, id, msg);
+        });
+        if let Some((_, previous)) = state.pending.insert(key, (id, timer)) {
+            previous.abort();
+        }
+    }
+
+    fn deliver(&self, key: String, id: u64, msg: T) {
+        let mut state = self.state.lock().unwrap();
+        // the reminder has been replaced or cancelled in the meantime
+        if state.pending.get(&key).map(|(pending, _)| *pending) != Some(id) {
+            return;
+        }
+        let _ = state.pending.remove(&key);
+        // if the delivery cannot be recorded, the reminder is delivered again after a restart
+        if self.target.tell(msg).is_ok() {
+            let _ = self.append(&mut state, Reminder::Delivered { key });
+        }
+    }
+}
+
+impl<T> Drop for Reminders<T> {
+    fn drop(&mut self) {
+        let state = self.inner.state.lock().unwrap();
+        for (_, timer) in state.pending.values() {
+            timer.abort();
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Reminders<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Reminders")
+            .field("persistence_id", &self.inner.persistence_id)
+            .field("target", &self.inner.target)
+            .finish_non_exhaustive()
+    }
+}
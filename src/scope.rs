@@ -0,0 +1,107 @@
+use crate::{behavior, cell::ActorCell, ActorContext, ActorPath, ActorRef, ActorSystem, Behavior};
+use std::sync::{Arc, Mutex};
+
+/// Actors that live no longer than the scope, e.g. the actors that serve one request.
+///
+/// When the scope is [closed](ActorScope::close), its actors are stopped and awaited.
+/// Dropping the scope stops its actors without waiting for them.
+///
+/// ```rust
+/// # use elliot::ActorSystem;
+/// # async fn example(system: ActorSystem) {
+/// let scope = system.scope();
+/// let worker = scope.spawn_anonymous(|job: String| async move { println!("{job}") });
+/// worker.tell(String::from("work")).unwrap();
+/// scope.close().await;
+/// assert!(!worker.is_alive());
+/// # }
+/// ```
+pub struct ActorScope {
+    system: ActorSystem,
+    /// The actor whose children are spawned, `None` for user actors.
+    parent: Option<ActorPath>,
+    cells: Mutex<Vec<Arc<ActorCell>>>,
+}
+
+impl ActorSystem {
+    /// A scope for user actors.
+    pub fn scope(&self) -> ActorScope {
+        ActorScope::new(self.clone(), None)
+    }
+}
+
+impl<T> ActorContext<T> {
+    /// A scope for children of this actor.
+    pub fn scope(&self) -> ActorScope {
+        ActorScope::new(self.system().clone(), Some(self.path().clone()))
+    }
+}
+
+impl ActorScope {
+    fn new(system: ActorSystem, parent: Option<ActorPath>) -> Self {
+        Self {
+            system,
+            parent,
+            cells: Mutex::default(),
+        }
+    }
+
+    /// Spawn an actor in this scope, see [`ActorSystem::spawn`].
+    ///
+    /// # Panics
+    ///
+    /// In scopes of the system, if the name is taken and the system rejects duplicate names.
+    pub fn spawn<T: Send + 'static, A, Args>(&self, name: &str, behavior: A) -> ActorRef<T>
+    where
+        A: Behavior<T, Args>,
+    {
+        let actor = match &self.parent {
+            Some(parent) => behavior::actor_of(&self.system, parent.child(name), behavior),
+            None => self.system.spawn(name, behavior),
+        };
+        self.adopt(actor)
+    }
+
+    /// Spawn an actor with a generated unique name in this scope, see [`ActorSystem::spawn_anonymous`].
+    pub fn spawn_anonymous<T: Send + 'static, A, Args>(&self, behavior: A) -> ActorRef<T>
+    where
+        A: Behavior<T, Args>,
+    {
+        let name = self.system.inner.anonymous_name();
+        self.spawn(&name, behavior)
+    }
+
+    fn adopt<T>(&self, actor: ActorRef<T>) -> ActorRef<T> {
+        let mut cells = self.cells.lock().unwrap();
+        cells.retain(|cell| cell.is_stopped() == false);
+        cells.push(Arc::clone(&actor.cell));
+        actor
+    }
+
+    /// Stop the actors of this scope and wait until they have stopped.
+    pub async fn close(self) {
+        let cells = std::mem::take(&mut *self.cells.lock().unwrap());
+        for cell in &cells {
+            cell.request_stop();
+        }
+        for cell in cells {
+            cell.wait_for_stop().await;
+        }
+    }
+}
+
+impl Drop for ActorScope {
+    fn drop(&mut self) {
+        for cell in self.cells.get_mut().unwrap().drain(..) {
+            cell.request_stop();
+        }
+    }
+}
+
+impl std::fmt::Debug for ActorScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorScope")
+            .field("parent", &self.parent)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,90 @@
+use crate::{ActorRef, ActorSystem};
+use std::{marker::PhantomData, sync::Arc};
+
+/// The actors of a system whose paths match a pattern, created with [`ActorSystem::select`].
+///
+/// The pattern is resolved whenever the selection is used, so it covers the actors that are alive at that time.
+pub struct ActorSelection<T> {
+    system: ActorSystem,
+    pattern: Arc<str>,
+    _msg: PhantomData<fn(T)>,
+}
+
+impl<T: Send + 'static> ActorSelection<T> {
+    pub(crate) fn new(system: ActorSystem, pattern: Arc<str>) -> Self {
+        Self {
+            system,
+            pattern,
+            _msg: PhantomData,
+        }
+    }
+
+    pub fn pattern(&self) -> &str {
+        &self.pattern
+    }
+
+    /// The live actors that match the pattern and handle messages of type `T`, sorted by their path.
+    pub fn resolve(&self) -> Vec<ActorRef<T>> {
+        self.system
+            .inner
+            .actors_of(|path| matches(&self.pattern, path))
+    }
+
+    /// Tell a clone of `msg` to all actors that match the pattern, returns the number of actors that received it.
+    pub fn tell(&self, msg: T) -> usize
+    where
+        T: Clone,
+    {
+        self.resolve()
+            .into_iter()
+            .filter(|actor| actor.tell(msg.clone()).is_ok())
+            .count()
+    }
+}
+
+/// Whether `path` matches `pattern` segment by segment, where `*` in a segment of the pattern
+/// matches any part of a name, e.g. `/user/workers/*` or `/user/worker-*`.
+fn matches(pattern: &str, path: &str) -> bool {
+    let mut patterns = pattern.split('/');
+    let mut names = path.split('/');
+    loop {
+        match (patterns.next(), names.next()) {
+            (Some(pattern), Some(name)) if glob(pattern, name) => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn glob(pattern: &str, name: &str) -> bool {
+    let Some((prefix, rest)) = pattern.split_once('*') else {
+        return pattern == name;
+    };
+    let Some(name) = name.strip_prefix(prefix) else {
+        return false;
+    };
+    if rest.is_empty() {
+        return true;
+    }
+    (0..=name.len())
+        .filter(|&start| name.is_char_boundary(start))
+        .any(|start| glob(rest, &name[start..]))
+}
+
+impl<T> Clone for ActorSelection<T> {
+    fn clone(&self) -> Self {
+        Self {
+            system: self.system.clone(),
+            pattern: Arc::clone(&self.pattern),
+            _msg: PhantomData,
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for ActorSelection<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorSelection")
+            .field("pattern", &self.pattern)
+            .finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,178 @@
+use crate::{cell::ActorCell, mailbox, ActorRef, ActorRefGone};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+};
+
+static SENDERS: AtomicU64 = AtomicU64::new(0);
+
+/// The senders that have not been dropped yet, so that the receivers can forget the others.
+static LIVE_SENDERS: Mutex<BTreeSet<u64>> = Mutex::new(BTreeSet::new());
+
+/// A message stamped with the sequence number of its sender, see [`SequencedRef`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Sequenced<T> {
+    /// The sender that stamped the message.
+    pub sender: u64,
+    /// The position of the message among the messages of its sender, starting at 0.
+    pub seq: u64,
+    /// The message itself.
+    pub msg: T,
+}
+
+/// A ref that stamps every message with the next sequence number of this sender.
+///
+/// The receiving side is a ref created with [`ActorRef::sequenced`], which passes on the
+/// messages of every sender in the order of their stamps, no matter in which order they arrive,
+/// e.g. through a [`Pool`](crate::Pool) or after a retry. Clones are the same sender.
+///
+/// Once all clones of a sender are dropped and its messages have been passed on, the receiving side
+/// forgets the sender, so retries of its messages must arrive before that.
+pub struct SequencedRef<T> {
+    actor: ActorRef<Sequenced<T>>,
+    sender: Arc<Sender>,
+}
+
+/// Shared by the clones of a [`SequencedRef`].
+struct Sender {
+    id: u64,
+    next: AtomicU64,
+}
+
+impl Drop for Sender {
+    fn drop(&mut self) {
+        let _ = LIVE_SENDERS.lock().unwrap().remove(&self.id);
+    }
+}
+
+fn is_live(sender: u64) -> bool {
+    LIVE_SENDERS.lock().unwrap().contains(&sender)
+}
+
+impl<T> SequencedRef<T> {
+    /// A new sender to `actor`.
+    pub fn new(actor: ActorRef<Sequenced<T>>) -> Self {
+        let id = SENDERS.fetch_add(1, Ordering::Relaxed);
+        let _ = LIVE_SENDERS.lock().unwrap().insert(id);
+        Self {
+            actor,
+            sender: Arc::new(Sender {
+                id,
+                next: AtomicU64::new(0),
+            }),
+        }
+    }
+
+    /// Stamp `msg` with the next sequence number, to send it on another way than [`SequencedRef::tell`].
+    ///
+    /// Every stamp must eventually arrive, a missing stamp holds back the later messages of this sender.
+    /// Retries of a stamped message can be sent any number of times, only the first one is passed on.
+    pub fn stamp(&self, msg: T) -> Sequenced<T> {
+        Sequenced {
+            sender: self.sender.id,
+            seq: self.sender.next.fetch_add(1, Ordering::Relaxed),
+            msg,
+        }
+    }
+
+    /// Stamp `msg` and send it.
+    pub fn tell(&self, msg: T) -> Result<(), ActorRefGone<Sequenced<T>>> {
+        self.actor.tell(self.stamp(msg))
+    }
+
+    /// The ref that receives the stamped messages.
+    pub fn actor(&self) -> &ActorRef<Sequenced<T>> {
+        &self.actor
+    }
+}
+
+/// The messages of one sender that wait for the ones that were stamped before them.
+struct Reorder<T> {
+    next: u64,
+    pending: BTreeMap<u64, T>,
+}
+
+impl<T> Default for Reorder<T> {
+    fn default() -> Self {
+        Self {
+            next: 0,
+            pending: BTreeMap::new(),
+        }
+    }
+}
+
+impl<T> Reorder<T> {
+    fn accept(&mut self, seq: u64, msg: T) {
+        // a message that was already passed on is a retry
+        if seq >= self.next {
+            let _ = self.pending.entry(seq).or_insert(msg);
+        }
+    }
+
+    fn ready(&mut self) -> Option<T> {
+        let msg = self.pending.remove(&self.next)?;
+        self.next += 1;
+        Some(msg)
+    }
+}
+
+/// Forget the senders that are gone and wait for nothing, once there are twice as many as
+/// after the last time, so that the cost is spread over the messages.
+fn prune<T>(senders: &mut HashMap<u64, Reorder<T>>, kept: &mut usize) {
+    if senders.len() < *kept * 2 {
+        return;
+    }
+    senders.retain(|sender, reorder| reorder.pending.is_empty() == false || is_live(*sender));
+    *kept = senders.len().max(16);
+}
+
+pub(crate) fn sequenced<T: Send + 'static>(target: &ActorRef<T>) -> ActorRef<Sequenced<T>> {
+    let cell = ActorCell::unregistered(target.path().child("$sequence"));
+    cell.set_runtime(target.cell.runtime());
+    let (this, mut mailbox) = mailbox::channel(Arc::new(cell));
+    let target = target.clone();
+    this.cell.spawn_task(async move {
+        let mut senders = HashMap::<u64, Reorder<T>>::new();
+        let mut kept = 16;
+        loop {
+            let msg = tokio::select! {
+                msg = mailbox.recv() => msg,
+                () = target.wait_for_stop() => None,
+            };
+            let Some(Sequenced { sender, seq, msg }) = msg else {
+                return;
+            };
+            let reorder = senders.entry(sender).or_default();
+            reorder.accept(seq, msg);
+            while let Some(msg) = reorder.ready() {
+                if target.tell(msg).is_err() {
+                    return;
+                }
+            }
+            prune(&mut senders, &mut kept);
+        }
+    });
+    this
+}
+
+impl<T> Clone for SequencedRef<T> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: self.actor.clone(),
+            sender: Arc::clone(&self.sender),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for SequencedRef<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("SequencedRef")
+            .field("actor", &self.actor)
+            .field("sender", &self.sender.id)
+            .field("next", &self.sender.next.load(Ordering::Relaxed))
+            .finish()
+    }
+}
@@ -0,0 +1,73 @@
+use crate::{ActorRef, AskError, ReplyTo};
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::Arc,
+    task::{Context, Poll},
+};
+
+type Request<Req, Res, T> = Arc<dyn Fn(Req, ReplyTo<Res>) -> T + Send + Sync>;
+
+/// A [`tower_service::Service`] that asks an actor for every request.
+///
+/// Created with [`ActorRef::service`], this lets an actor sit behind tower middleware
+/// or be used as a hyper or tonic service. The service is ready for as long as the actor is alive.
+pub struct ActorService<T, Req, Res> {
+    actor: ActorRef<T>,
+    request: Request<Req, Res, T>,
+}
+
+impl<T> ActorRef<T> {
+    /// A service that turns every request into a message with `request` and waits for the reply.
+    pub fn service<Req, Res, F>(&self, request: F) -> ActorService<T, Req, Res>
+    where
+        F: Fn(Req, ReplyTo<Res>) -> T + Send + Sync + 'static,
+    {
+        ActorService {
+            actor: self.clone(),
+            request: Arc::new(request),
+        }
+    }
+}
+
+impl<T, Req, Res> tower_service::Service<Req> for ActorService<T, Req, Res>
+where
+    T: Send + 'static,
+    Req: Send + 'static,
+    Res: Send + 'static,
+{
+    type Response = Res;
+    type Error = AskError;
+    type Future = Pin<Box<dyn Future<Output = Result<Res, AskError>> + Send>>;
+
+    fn poll_ready(&mut self, _cx: &mut Context<'_>) -> Poll<Result<(), AskError>> {
+        if self.actor.is_alive() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(AskError::ActorRefGone))
+        }
+    }
+
+    fn call(&mut self, req: Req) -> Self::Future {
+        let actor = self.actor.clone();
+        let request = Arc::clone(&self.request);
+        Box::pin(async move { actor.ask(|reply_to| request(req, reply_to)).await })
+    }
+}
+
+impl<T, Req, Res> Clone for ActorService<T, Req, Res> {
+    fn clone(&self) -> Self {
+        Self {
+            actor: self.actor.clone(),
+            request: Arc::clone(&self.request),
+        }
+    }
+}
+
+impl<T, Req, Res> std::fmt::Debug for ActorService<T, Req, Res> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorService")
+            .field("actor", &self.actor)
+            .finish_non_exhaustive()
+    }
+}
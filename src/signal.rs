@@ -0,0 +1,67 @@
+use crate::{
+    behavior::IntoResult, intercept::Handled, ActorContext, ActorCrashed, Behavior, Terminated,
+};
+use std::{future::Future, marker::PhantomData, sync::Arc};
+
+/// Notifications from the library, delivered apart from the messages of an actor.
+///
+/// Signals are ignored unless a handler is attached with [`Behavior::on_signal`].
+#[derive(Clone, Debug)]
+pub enum Signal {
+    /// An actor that is watched with [`ActorContext::watch`] has stopped.
+    Terminated(Terminated),
+    /// A child has crashed, i.e. an actor whose path is below the path of this actor.
+    ChildFailed(ActorCrashed),
+    /// No message has arrived within the timeout of [`ActorContext::set_receive_timeout`].
+    ReceiveTimeout,
+    /// The actor has stopped and will not receive messages or signals anymore.
+    PostStop,
+}
+
+/// A behavior with a handler for [`Signal`]s, created with [`Behavior::on_signal`].
+pub struct OnSignal<B, H, Args> {
+    behavior: B,
+    handler: H,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl<B, H, Args> OnSignal<B, H, Args> {
+    pub(crate) fn new(behavior: B, handler: H) -> Self {
+        Self {
+            behavior,
+            handler,
+            _args: PhantomData,
+        }
+    }
+}
+
+impl<T, B, H, Args, Fut> Behavior<T> for OnSignal<B, H, Args>
+where
+    T: Send + 'static,
+    B: Behavior<T, Args>,
+    H: Fn(&ActorContext<T>, Signal) -> Fut + Send + Sync + 'static,
+    Fut: Future + Send + 'static,
+    Fut::Output: IntoResult<T>,
+    Args: 'static,
+{
+    type F = B::F;
+
+    fn receive(&self, context: &ActorContext<T>, msg: T) -> Self::F {
+        self.behavior.receive(context, msg)
+    }
+
+    fn receive_signal(&self, context: &ActorContext<T>, signal: Signal) -> Option<Handled<T>> {
+        let handled = (self.handler)(context, signal);
+        Some(Box::pin(async move { handled.await.into_result() }))
+    }
+
+    fn describe(&self, msg: &T) -> Option<Arc<str>> {
+        self.behavior.describe(msg)
+    }
+}
+
+impl<B, H, Args> std::fmt::Debug for OnSignal<B, H, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("OnSignal").finish_non_exhaustive()
+    }
+}
@@ -0,0 +1,320 @@
+use crate::{
+    behavior::FromContext,
+    cell::{ActorCell, Reserved},
+    ActorContext, ActorRef, ActorRefGone, ActorSystem, WeakActorRef,
+};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    future::poll_fn,
+    marker::PhantomData,
+    pin::{pin, Pin},
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+use tokio::sync::mpsc;
+
+/// Items of a source that can wait in the mailbox of the target before the source is paused.
+const SOURCE_BUFFER: usize = 16;
+
+/// Items of a streamed reply that can wait for the asker before the actor has to wait.
+const REPLY_BUFFER: usize = 16;
+
+impl ActorSystem {
+    /// Forward every item of `stream` to `target`.
+    ///
+    /// The stream is only polled while the mailbox of the target has room for more items.
+    /// Forwarding stops when the stream ends or when the target stops, the source keeps
+    /// the target alive until then.
+    pub fn spawn_source<S>(&self, stream: S, target: &ActorRef<S::Item>)
+    where
+        S: Stream + Send + 'static,
+        S::Item: Send + 'static,
+    {
+        let weak = target.downgrade();
+        let source = target.clone();
+        target.cell.spawn_task(async move {
+            forward(stream, weak, |item| item).await;
+            drop(source);
+        });
+    }
+}
+
+impl<T: Send + 'static> ActorContext<T> {
+    /// Forward every item of `stream` to this actor, after turning it into a message with `map`.
+    ///
+    /// Unlike [`ActorSystem::spawn_source`], the stream does not keep this actor alive.
+    pub fn pipe_stream<S, F>(&self, stream: S, map: F)
+    where
+        S: Stream + Send + 'static,
+        F: Fn(S::Item) -> T + Send + 'static,
+    {
+        self.this
+            .cell
+            .spawn_task(forward(stream, self.this.clone(), map));
+    }
+}
+
+async fn forward<S, T, F>(stream: S, target: WeakActorRef<T>, map: F)
+where
+    S: Stream,
+    F: Fn(S::Item) -> T,
+{
+    let mut stream = pin!(stream);
+    loop {
+        let next = async {
+            let capacity = target.cell.capacity().min(SOURCE_BUFFER);
+            poll_fn(|cx| target.cell.poll_enqueue(capacity, 0, cx)).await;
+            let reserved = Reserved::new(&target.cell);
+            let item = poll_fn(|cx| stream.as_mut().poll_next(cx)).await;
+            (item, reserved)
+        };
+        let (item, reserved) = tokio::select! {
+            next = next => next,
+            () = target.cell.wait_for_stop() => return,
+        };
+        let Some(item) = item else {
+            return;
+        };
+        let Some(actor) = target.upgrade() else {
+            return;
+        };
+        reserved.keep();
+        if actor.deliver_reserved(map(item)).is_err() {
+            return;
+        }
+    }
+}
+
+/// Emit items from within an actor, to everyone who listens to the [output](ActorRef::output) of the actor.
+///
+/// ```rust
+/// use elliot::{ActorStream, ActorSystem};
+///
+/// # async fn example() {
+/// let system = ActorSystem::new();
+/// let doubler = system.spawn("doubler", |out: ActorStream<u32>, n: u32| async move {
+///     let _ = out.emit(n * 2);
+/// });
+/// let doubled = doubler.output::<u32>();
+/// # }
+/// ```
+pub struct ActorStream<O> {
+    cell: Arc<ActorCell>,
+    _item: PhantomData<fn(O)>,
+}
+
+impl<O: Clone + Send + 'static> ActorStream<O> {
+    /// Send `item` to all outputs of type `O`, returns the number of outputs that received it.
+    ///
+    /// Items that are emitted while nobody listens are dropped.
+    pub fn emit(&self, item: O) -> usize {
+        self.cell.outputs.emit(item)
+    }
+}
+
+impl<T, O> FromContext<T> for ActorStream<O> {
+    fn from_context(context: &ActorContext<T>) -> Self {
+        Self {
+            cell: Arc::clone(&context.this.cell),
+            _item: PhantomData,
+        }
+    }
+}
+
+/// The items that an actor [emits](ActorStream::emit), as a [`Stream`].
+///
+/// The stream ends when the actor stops. Items are buffered without a bound until they are polled.
+pub struct ActorOutput<O> {
+    rx: mpsc::UnboundedReceiver<O>,
+}
+
+impl<T> ActorRef<T> {
+    /// Listen to the items of type `O` that this actor emits from now on.
+    pub fn output<O: Send + 'static>(&self) -> ActorOutput<O> {
+        ActorOutput {
+            rx: self.cell.outputs.listen(&self.cell),
+        }
+    }
+}
+
+impl<O> Stream for ActorOutput<O> {
+    type Item = O;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<O>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// The reply channel of [`ActorRef::ask_stream`], for replies of many items,
+/// e.g. the pages of a query or the updates of a subscription.
+///
+/// The stream of the asker ends when this is dropped or [completed](StreamReplyTo::complete).
+#[derive(Debug)]
+pub struct StreamReplyTo<R> {
+    tx: mpsc::Sender<R>,
+}
+
+impl<R> StreamReplyTo<R> {
+    /// Send the next item, waiting while the asker has not taken the earlier items yet.
+    /// Returns the item if the asker is no longer listening.
+    pub async fn send(&self, item: R) -> Result<(), R> {
+        self.tx.send(item).await.map_err(|e| e.0)
+    }
+
+    /// End the stream of the asker after the items that were sent.
+    pub fn complete(self) {}
+
+    /// Returns `true` if the asker is no longer listening.
+    pub fn is_closed(&self) -> bool {
+        self.tx.is_closed()
+    }
+
+    /// Wait until the asker is no longer listening, so that producing the items can be abandoned.
+    pub async fn closed(&self) {
+        self.tx.closed().await;
+    }
+}
+
+/// The items of a reply to [`ActorRef::ask_stream`], as a [`Stream`].
+pub struct ReplyStream<R> {
+    rx: mpsc::Receiver<R>,
+}
+
+impl<T> ActorRef<T> {
+    /// Send a message that contains a [`StreamReplyTo`] and receive the items of the reply.
+    ///
+    /// The stream ends once the actor completes the reply or drops the [`StreamReplyTo`],
+    /// or right away if the actor has stopped. Dropping the stream tells the actor that
+    /// nobody is listening anymore.
+    pub fn ask_stream<R, F>(&self, msg: F) -> ReplyStream<R>
+    where
+        F: FnOnce(StreamReplyTo<R>) -> T,
+    {
+        let (tx, rx) = mpsc::channel(REPLY_BUFFER);
+        let _ = self.tell(msg(StreamReplyTo { tx }));
+        ReplyStream { rx }
+    }
+}
+
+impl<R> Stream for ReplyStream<R> {
+    type Item = R;
+
+    fn poll_next(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<R>> {
+        self.rx.poll_recv(cx)
+    }
+}
+
+/// Forward streams into an actor, waiting while the mailbox of a [bounded](ActorSystem::spawn_bounded) actor is full.
+///
+/// Items are in the mailbox as soon as they are sent, so flushing and closing do nothing.
+impl<T> Sink<T> for ActorRef<T> {
+    type Error = ActorRefGone<()>;
+
+    fn poll_ready(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        let this = self.get_mut();
+        if this.is_alive() == false {
+            return Poll::Ready(Err(ActorRefGone(())));
+        }
+        if this.reserved == false {
+            if this
+                .cell
+                .poll_enqueue(this.cell.capacity(), 0, cx)
+                .is_pending()
+            {
+                return Poll::Pending;
+            }
+            this.reserved = true;
+        }
+        Poll::Ready(Ok(()))
+    }
+
+    fn start_send(self: Pin<&mut Self>, item: T) -> Result<(), Self::Error> {
+        let this = self.get_mut();
+        if std::mem::take(&mut this.reserved) {
+            this.deliver_reserved(item)
+        } else {
+            this.tell(item)
+        }
+        .map_err(|_| ActorRefGone(()))
+    }
+
+    fn poll_flush(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+
+    fn poll_close(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Result<(), Self::Error>> {
+        Poll::Ready(Ok(()))
+    }
+}
+
+/// The listeners of the outputs of an actor, keyed by the type of the items.
+#[derive(Default)]
+pub(crate) struct Outputs {
+    listeners: Mutex<HashMap<TypeId, Box<dyn Any + Send>>>,
+}
+
+impl Outputs {
+    fn listen<O: Send + 'static>(&self, cell: &ActorCell) -> mpsc::UnboundedReceiver<O> {
+        let (tx, rx) = mpsc::unbounded_channel();
+        let mut listeners = self.listeners.lock().unwrap();
+        // the actor has stopped already, the stream ends right away
+        if cell.is_stopped() {
+            return rx;
+        }
+        listeners
+            .entry(TypeId::of::<O>())
+            .or_insert_with(|| Box::new(Vec::<mpsc::UnboundedSender<O>>::new()))
+            .downcast_mut::<Vec<mpsc::UnboundedSender<O>>>()
+            .expect("outputs are keyed by their type")
+            .push(tx);
+        rx
+    }
+
+    fn emit<O: Clone + Send + 'static>(&self, item: O) -> usize {
+        let mut listeners = self.listeners.lock().unwrap();
+        let Some(listeners) = listeners
+            .get_mut(&TypeId::of::<O>())
+            .and_then(|l| l.downcast_mut::<Vec<mpsc::UnboundedSender<O>>>())
+        else {
+            return 0;
+        };
+        listeners.retain(|l| l.send(item.clone()).is_ok());
+        listeners.len()
+    }
+
+    /// End all outputs.
+    pub(crate) fn close(&self) {
+        self.listeners.lock().unwrap().clear();
+    }
+}
+
+impl<O> std::fmt::Debug for ActorStream<O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorStream")
+            .field("path", &self.cell.path)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<R> std::fmt::Debug for ReplyStream<R> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ReplyStream").finish_non_exhaustive()
+    }
+}
+
+impl<O> std::fmt::Debug for ActorOutput<O> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ActorOutput").finish_non_exhaustive()
+    }
+}
+
+impl std::fmt::Debug for Outputs {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Outputs")
+            .field("types", &self.listeners.lock().unwrap().len())
+            .finish()
+    }
+}
@@ -0,0 +1,1051 @@
+use crate::{
+    behavior::{self, IntoResult},
+    bus::Bus,
+    cell::{Activity, ActorCell, Reserved},
+    child::Supervisor,
+    codec::Codecs,
+    guardian::{self, Guarded, Guardian, Internals},
+    lifecycle::DeadLetterValues,
+    receptionist::Registry,
+    runtime::{self, Runtime},
+    selection::ActorSelection,
+    topic::Topics,
+    ActorCrashed, ActorInfo, ActorPath, ActorRef, Behavior, DeadLetter, DeadLetters, DeadLettersOf,
+    LifecycleEvent, LifecycleEvents, NameTaken, Scheduler, Signal, Terminated, WeakActorRef,
+};
+use std::{
+    any::Any,
+    collections::HashMap,
+    convert::Infallible,
+    future::{poll_fn, Future},
+    sync::{
+        atomic::{AtomicU64, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+use tokio::{
+    runtime::Handle,
+    sync::{broadcast, mpsc},
+};
+
+const LIFECYCLE_EVENTS_CAPACITY: usize = 256;
+
+#[derive(Clone, Debug)]
+pub struct ActorSystem {
+    pub(crate) inner: Arc<SystemInner>,
+}
+
+/// What happens to an actor when handling a message fails.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "config",
+    derive(serde::Deserialize),
+    serde(rename_all = "lowercase")
+)]
+pub enum Supervision {
+    /// The actor stops, its lifecycle event has the error.
+    #[default]
+    Stop,
+    /// The message that failed is a dead letter and the actor continues with the next message.
+    Resume,
+}
+
+/// What happens when an actor is spawned with the name of a live actor.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum DuplicateNames {
+    /// The name gets the first free suffix, e.g. `worker-1`.
+    #[default]
+    Suffix,
+    /// [`ActorSystem::try_spawn`] fails with [`NameTaken`], [`ActorSystem::spawn`] panics.
+    Reject,
+}
+
+/// How long an actor lives, when it is not stopped by its behavior or by a failure.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum Lifetime {
+    /// The actor stops with [`NoActorRef`](crate::NoActorRef) once all of its [`ActorRef`]s are gone.
+    #[default]
+    RefCounted,
+    /// The actor stays alive until its behavior returns [`Behaviors::Stopped`](crate::Behaviors::Stopped),
+    /// e.g. for tickers or listeners that are driven by themselves.
+    Detached,
+}
+
+/// How an actor is spawned with [`ActorSystem::spawn_with`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct SpawnOptions {
+    pub(crate) lifetime: Lifetime,
+    pub(crate) lanes: usize,
+}
+
+impl Default for SpawnOptions {
+    fn default() -> Self {
+        Self {
+            lifetime: Lifetime::default(),
+            lanes: 1,
+        }
+    }
+}
+
+impl SpawnOptions {
+    /// How long the actor lives, see [`Lifetime`].
+    pub fn lifetime(mut self, lifetime: Lifetime) -> Self {
+        self.lifetime = lifetime;
+        self
+    }
+
+    /// Shard the mailbox into `lanes` channels that the actor takes turns to receive from,
+    /// for actors with thousands of concurrent senders that would all contend for a single channel.
+    ///
+    /// Every ref sends to one lane, clones of a ref are spread over the lanes. Messages that are
+    /// told through the same ref keep their order, but messages that are told through different
+    /// refs can overtake each other, even if one task tells them one after the other.
+    /// Give each sender its own clone and keep related messages on one ref.
+    pub fn lanes(mut self, lanes: usize) -> Self {
+        self.lanes = lanes.max(1);
+        self
+    }
+}
+
+/// Configures an [`ActorSystem`], created with [`ActorSystem::builder`].
+#[derive(Debug)]
+pub struct ActorSystemBuilder {
+    name: Arc<str>,
+    mailbox_capacity: Option<usize>,
+    supervision: Supervision,
+    duplicate_names: DuplicateNames,
+    dead_letter_capacity: usize,
+    crash_dump: usize,
+    scheduler_resolution: Duration,
+    runtime: Option<Arc<dyn Runtime>>,
+    #[cfg(feature = "config")]
+    pub(crate) actors: std::collections::BTreeMap<String, crate::ActorConfig>,
+    #[cfg(feature = "config")]
+    pub(crate) dispatchers: HashMap<String, Handle>,
+    #[cfg(all(feature = "config", feature = "remote"))]
+    pub(crate) bind: Option<String>,
+}
+
+impl ActorSystemBuilder {
+    /// The name of the system, defaults to `elliot`.
+    pub fn name(mut self, name: impl Into<Arc<str>>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Give every spawned actor a mailbox that holds at most `capacity` messages,
+    /// like [`ActorSystem::spawn_bounded`]. By default, mailboxes are unbounded.
+    pub fn mailbox_capacity(mut self, capacity: usize) -> Self {
+        self.mailbox_capacity = Some(capacity.max(1));
+        self
+    }
+
+    /// What happens to actors when handling a message fails, defaults to [`Supervision::Stop`].
+    pub fn supervision(mut self, supervision: Supervision) -> Self {
+        self.supervision = supervision;
+        self
+    }
+
+    /// What happens when an actor is spawned with a name that is taken,
+    /// defaults to [`DuplicateNames::Suffix`].
+    pub fn duplicate_names(mut self, duplicate_names: DuplicateNames) -> Self {
+        self.duplicate_names = duplicate_names;
+        self
+    }
+
+    /// How many dead letters are buffered for [`ActorSystem::dead_letters`] subscribers
+    /// that are falling behind, defaults to 256.
+    pub fn dead_letter_capacity(mut self, capacity: usize) -> Self {
+        self.dead_letter_capacity = capacity.max(1);
+        self
+    }
+
+    /// Keep the last `messages` of every actor for the report when it crashes,
+    /// see [`ActorCrashed::recent_messages`]. Defaults to 0, which keeps none.
+    ///
+    /// The messages are named by their type, unless the behavior renders them with
+    /// [`Behavior::debug_messages`].
+    pub fn crash_dump(mut self, messages: usize) -> Self {
+        self.crash_dump = messages;
+        self
+    }
+
+    /// The delays of the [`Scheduler`] are rounded up to a multiple of `resolution`,
+    /// so that timers that are close to each other fire together. Defaults to 1ms.
+    pub fn scheduler_resolution(mut self, resolution: Duration) -> Self {
+        self.scheduler_resolution = resolution;
+        self
+    }
+
+    /// Run the actors and the tasks of the system on `runtime`.
+    ///
+    /// Defaults to the runtime that builds the system. Systems that are built outside of a runtime
+    /// use the runtime that spawns each actor.
+    pub fn runtime(mut self, runtime: Handle) -> Self {
+        self.runtime = Some(Arc::new(runtime::Tokio(Some(runtime))));
+        self
+    }
+
+    /// Run the actors and the tasks of the system on the global executor of smol.
+    ///
+    /// The integrations still require a tokio runtime.
+    #[cfg(feature = "smol")]
+    pub fn smol(mut self) -> Self {
+        self.runtime = Some(Arc::new(runtime::Smol));
+        self
+    }
+
+    /// Run the actors and the tasks of the system on the global executor of async-std.
+    ///
+    /// The integrations still require a tokio runtime.
+    #[cfg(feature = "async-std")]
+    pub fn async_std(mut self) -> Self {
+        self.runtime = Some(Arc::new(runtime::AsyncStd));
+        self
+    }
+
+    /// # Panics
+    ///
+    /// With the `config` feature, if an actor is configured with a dispatcher that has not been
+    /// registered, see [`ActorSystemBuilder::try_build`].
+    pub fn build(self) -> ActorSystem {
+        #[cfg(feature = "config")]
+        return self.try_build().unwrap_or_else(|e| panic!("{e}"));
+        #[cfg(not(feature = "config"))]
+        self.finish()
+    }
+
+    /// Like [`ActorSystemBuilder::build`], but fails if an actor is configured with a dispatcher
+    /// that has not been registered.
+    #[cfg(feature = "config")]
+    pub fn try_build(mut self) -> Result<ActorSystem, crate::UnknownDispatcher> {
+        let actor_settings = std::mem::take(&mut self.actors)
+            .into_iter()
+            .map(|(path, config)| {
+                let runtime = match config.dispatcher {
+                    Some(dispatcher) => match self.dispatchers.get(&dispatcher) {
+                        Some(runtime) => Some(runtime.clone()),
+                        None => return Err(crate::UnknownDispatcher { dispatcher, path }),
+                    },
+                    None => None,
+                };
+                let settings = ActorSettings {
+                    mailbox_capacity: config.mailbox_capacity,
+                    runtime,
+                };
+                Ok((path, settings))
+            })
+            .collect::<Result<_, _>>()?;
+        Ok(self.finish(actor_settings))
+    }
+
+    fn finish(
+        self,
+        #[cfg(feature = "config")] actor_settings: HashMap<String, ActorSettings>,
+    ) -> ActorSystem {
+        let (lifecycle, _) = broadcast::channel(LIFECYCLE_EVENTS_CAPACITY);
+        let (dead_letters, _) = broadcast::channel(self.dead_letter_capacity);
+        let runtime = self.runtime.unwrap_or_else(runtime::default);
+        ActorSystem {
+            inner: Arc::new(SystemInner {
+                name: self.name,
+                mailbox_capacity: self.mailbox_capacity,
+                supervision: self.supervision,
+                duplicate_names: self.duplicate_names,
+                crash_dump: self.crash_dump,
+                runtime: Arc::clone(&runtime),
+                #[cfg(feature = "config")]
+                actor_settings,
+                lifecycle,
+                dead_letters,
+                dead_letter_values: Arc::default(),
+                next_id: AtomicU64::new(0),
+                next_anonymous: AtomicU64::new(0),
+                activity: Arc::default(),
+                actors: Mutex::default(),
+                refs: Mutex::default(),
+                names: Mutex::default(),
+                scheduler: Scheduler::new(self.scheduler_resolution, runtime),
+                bus: Bus::default(),
+                topics: Topics::default(),
+                registry: Registry::default(),
+                codecs: Codecs::default(),
+                user_guardian: Mutex::new(None),
+                system_guardian: Mutex::new(None),
+                internals: Internals::default(),
+                #[cfg(feature = "remote")]
+                remote: crate::remote::Remote::default(),
+                #[cfg(all(feature = "config", feature = "remote"))]
+                bind: self.bind,
+            }),
+        }
+    }
+}
+
+#[cfg(feature = "config")]
+#[derive(Debug)]
+struct ActorSettings {
+    mailbox_capacity: Option<usize>,
+    runtime: Option<Handle>,
+}
+
+/// The id of the cell and a [`WeakActorRef`] of a named actor.
+type Named = (u64, Box<dyn NamedActor>);
+
+trait NamedActor: Send + Sync + std::fmt::Debug + 'static {
+    fn as_any(&self) -> &dyn Any;
+
+    /// Whether the actor can still receive messages, its name is free otherwise.
+    fn is_alive(&self) -> bool;
+}
+
+impl<T: Send + 'static> NamedActor for WeakActorRef<T> {
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn is_alive(&self) -> bool {
+        self.upgrade().is_some_and(|actor| actor.is_alive())
+    }
+}
+
+/// A path that is reserved with [`SystemInner::claim`] until an actor is spawned at it,
+/// the reservation is released if the spawn does not complete, e.g. because it panics.
+struct Claim<'a> {
+    system: &'a SystemInner,
+    path: ActorPath,
+    spawned: bool,
+}
+
+impl Claim<'_> {
+    /// Make the actor that is spawned at the claimed path findable.
+    fn named<T: Send + 'static>(mut self, actor: &ActorRef<T>) {
+        self.system.named(actor);
+        self.spawned = true;
+    }
+}
+
+impl Drop for Claim<'_> {
+    fn drop(&mut self) {
+        if self.spawned {
+            return;
+        }
+        // not unwrap, this runs while a panic unwinds
+        if let Ok(mut names) = self.system.names.lock() {
+            if matches!(names.get(&self.path), Some(None)) {
+                let _ = names.remove(&self.path);
+            }
+        }
+    }
+}
+
+#[derive(Debug)]
+pub(crate) struct SystemInner {
+    name: Arc<str>,
+    mailbox_capacity: Option<usize>,
+    pub(crate) supervision: Supervision,
+    duplicate_names: DuplicateNames,
+    crash_dump: usize,
+    runtime: Arc<dyn Runtime>,
+    /// The configured settings of single actors, keyed by their path.
+    #[cfg(feature = "config")]
+    actor_settings: HashMap<String, ActorSettings>,
+    lifecycle: broadcast::Sender<LifecycleEvent>,
+    pub(crate) dead_letters: broadcast::Sender<DeadLetter>,
+    dead_letter_values: Arc<DeadLetterValues>,
+    next_id: AtomicU64,
+    next_anonymous: AtomicU64,
+    activity: Arc<Activity>,
+    actors: Mutex<HashMap<u64, Arc<ActorCell>>>,
+    /// The [`WeakActorRef`]s of the actors, by the id of their cell, for [`ActorSystem::select`].
+    refs: Mutex<HashMap<u64, Box<dyn NamedActor>>>,
+    /// The actors that are spawned with a name, by the id of their cell,
+    /// `None` while the actor is being spawned.
+    names: Mutex<HashMap<ActorPath, Option<Named>>>,
+    scheduler: Scheduler,
+    pub(crate) bus: Bus,
+    pub(crate) topics: Topics,
+    pub(crate) registry: Registry,
+    pub(crate) codecs: Codecs,
+    /// Spawned with the first user actor, and again with the first one after a terminate.
+    user_guardian: Mutex<Option<Guarded>>,
+    /// Spawned with the first internal actor, and again with the first one after a terminate.
+    system_guardian: Mutex<Option<Guarded>>,
+    internals: Internals,
+    #[cfg(feature = "remote")]
+    pub(crate) remote: crate::remote::Remote,
+    /// The address of the configured [`RemoteConfig`](crate::RemoteConfig).
+    #[cfg(all(feature = "config", feature = "remote"))]
+    pub(crate) bind: Option<String>,
+}
+
+impl ActorSystem {
+    /// A system with the default configuration, see [`ActorSystem::builder`].
+    #[allow(clippy::new_without_default)]
+    pub fn new() -> Self {
+        Self::builder().build()
+    }
+
+    pub fn builder() -> ActorSystemBuilder {
+        ActorSystemBuilder {
+            name: Arc::from("elliot"),
+            mailbox_capacity: None,
+            supervision: Supervision::Stop,
+            duplicate_names: DuplicateNames::Suffix,
+            dead_letter_capacity: 256,
+            crash_dump: 0,
+            scheduler_resolution: Duration::from_millis(1),
+            runtime: None,
+            #[cfg(feature = "config")]
+            actors: std::collections::BTreeMap::new(),
+            #[cfg(feature = "config")]
+            dispatchers: HashMap::new(),
+            #[cfg(all(feature = "config", feature = "remote"))]
+            bind: None,
+        }
+    }
+
+    pub fn name(&self) -> &str {
+        &self.inner.name
+    }
+
+    /// # Panics
+    ///
+    /// If the name is taken and the system [rejects duplicate names](DuplicateNames::Reject).
+    pub fn spawn<T: Send + 'static, N, A, Args>(&self, name: N, behavior: A) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        self.try_spawn(name, behavior)
+            .unwrap_or_else(|taken| panic!("{taken}"))
+    }
+
+    /// Like [`ActorSystem::spawn`], but fails if the name is taken and the system
+    /// [rejects duplicate names](DuplicateNames::Reject).
+    pub fn try_spawn<T: Send + 'static, N, A, Args>(
+        &self,
+        name: N,
+        behavior: A,
+    ) -> Result<ActorRef<T>, NameTaken>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        let claim = self.inner.claim(&name.into())?;
+        let actor = behavior::actor_of(self, claim.path.clone(), behavior);
+        claim.named(&actor);
+        Ok(actor)
+    }
+
+    /// The live actor that was spawned with `name`, if it handles messages of type `T`.
+    pub fn find<T: Send + 'static>(&self, name: &str) -> Option<ActorRef<T>> {
+        let names = self.inner.names.lock().unwrap();
+        let (_, actor) = names.get(&ActorPath::user(name))?.as_ref()?;
+        actor.as_any().downcast_ref::<WeakActorRef<T>>()?.upgrade()
+    }
+
+    /// Like [`ActorSystem::spawn`], but the actor stays alive without refs, see [`Lifetime::Detached`].
+    ///
+    /// # Panics
+    ///
+    /// If the name is taken and the system [rejects duplicate names](DuplicateNames::Reject).
+    pub fn spawn_detached<T: Send + 'static, N, A, Args>(&self, name: N, behavior: A) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        self.spawn_with_lifetime(name, Lifetime::Detached, behavior)
+    }
+
+    /// Like [`ActorSystem::spawn`], but with the given [`Lifetime`].
+    ///
+    /// # Panics
+    ///
+    /// If the name is taken and the system [rejects duplicate names](DuplicateNames::Reject).
+    pub fn spawn_with_lifetime<T: Send + 'static, N, A, Args>(
+        &self,
+        name: N,
+        lifetime: Lifetime,
+        behavior: A,
+    ) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        self.spawn_with(name, SpawnOptions::default().lifetime(lifetime), behavior)
+    }
+
+    /// Like [`ActorSystem::spawn`], but with the given [`SpawnOptions`].
+    ///
+    /// # Panics
+    ///
+    /// If the name is taken and the system [rejects duplicate names](DuplicateNames::Reject).
+    pub fn spawn_with<T: Send + 'static, N, A, Args>(
+        &self,
+        name: N,
+        options: SpawnOptions,
+        behavior: A,
+    ) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        let claim = self
+            .inner
+            .claim(&name.into())
+            .unwrap_or_else(|taken| panic!("{taken}"));
+        let path = claim.path.clone();
+        let actor = behavior::actor_on(self, None, options, Supervisor::system(), path, behavior);
+        claim.named(&actor);
+        actor
+    }
+
+    /// The actors that handle messages of type `T` and whose paths match `pattern`,
+    /// where `*` matches any part of a name, e.g. `/user/workers/*` for all workers.
+    pub fn select<T: Send + 'static>(&self, pattern: &str) -> ActorSelection<T> {
+        ActorSelection::new(self.clone(), Arc::from(pattern))
+    }
+
+    /// Like [`ActorSystem::spawn`], but with a generated unique name like `$a-42`.
+    pub fn spawn_anonymous<T: Send + 'static, A, Args>(&self, behavior: A) -> ActorRef<T>
+    where
+        A: Behavior<T, Args>,
+    {
+        let path = ActorPath::user(&self.inner.anonymous_name());
+        behavior::actor_of(self, path, behavior)
+    }
+
+    /// Like [`ActorSystem::spawn`], but the actor runs on the current thread, so that `handler`
+    /// can hold state that is not `Send`, such as UI handles in the browser.
+    ///
+    /// In the browser, with the `wasm` feature, the actor is spawned with
+    /// `wasm_bindgen_futures::spawn_local`. Elsewhere, this must be called within a
+    /// [`tokio::task::LocalSet`].
+    pub fn spawn_local<T, N, F, Fut>(&self, name: N, handler: F) -> ActorRef<T>
+    where
+        T: Send + 'static,
+        N: Into<Arc<str>>,
+        F: FnMut(T) -> Fut + 'static,
+        Fut: Future + 'static,
+        Fut::Output: IntoResult<T>,
+    {
+        let claim = self
+            .inner
+            .claim(&name.into())
+            .unwrap_or_else(|taken| panic!("{taken}"));
+        let actor = behavior::local_actor_of(self, claim.path.clone(), handler);
+        claim.named(&actor);
+        actor
+    }
+
+    /// Like [`ActorSystem::spawn`], but the actor runs on `runtime`, which can be a current-thread runtime.
+    pub fn spawn_on<T: Send + 'static, N, A, Args>(
+        &self,
+        runtime: &Handle,
+        name: N,
+        behavior: A,
+    ) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        let claim = self
+            .inner
+            .claim(&name.into())
+            .unwrap_or_else(|taken| panic!("{taken}"));
+        let actor = behavior::actor_on(
+            self,
+            Some(runtime.clone()),
+            SpawnOptions::default(),
+            Supervisor::system(),
+            claim.path.clone(),
+            behavior,
+        );
+        claim.named(&actor);
+        actor
+    }
+
+    /// Like [`ActorSystem::spawn`], but with a mailbox that holds at most `capacity` messages.
+    ///
+    /// Senders that use [`ActorRef::send`] wait while the mailbox is full,
+    /// [`ActorRef::tell`] never waits and ignores the bound.
+    pub fn spawn_bounded<T: Send + 'static, N, A, Args>(
+        &self,
+        name: N,
+        capacity: usize,
+        behavior: A,
+    ) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        let actor = self.spawn(name, behavior);
+        actor.cell.set_capacity(capacity.max(1));
+        actor
+    }
+
+    /// Like [`ActorSystem::spawn`], but with a mailbox that holds messages of at most `budget` bytes,
+    /// as approximated by `size`, e.g. `|_| std::mem::size_of::<T>()` or the length of a buffer.
+    ///
+    /// Senders that use [`ActorRef::send`] wait while the next message does not fit into the budget,
+    /// [`ActorRef::try_send`] rejects it, and [`ActorRef::tell`] never waits and ignores the bound.
+    /// A message always fits into an empty mailbox, even if it is larger than the budget.
+    pub fn spawn_bounded_by_size<T: Send + 'static, N, S, A, Args>(
+        &self,
+        name: N,
+        budget: usize,
+        size: S,
+        behavior: A,
+    ) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        S: Fn(&T) -> usize + Send + Sync + 'static,
+        A: Behavior<T, Args>,
+    {
+        let actor = self.spawn(name, behavior);
+        let _ = actor.sizer.set(Box::new(size));
+        actor.cell.set_byte_budget(budget);
+        actor
+    }
+
+    /// Like [`ActorSystem::spawn`], but the actor also handles the messages from an existing `receiver`.
+    ///
+    /// The messages of the receiver are delivered in order, interleaved with the messages that are
+    /// told to the returned ref. The receiver keeps the actor alive until all of its senders are gone.
+    /// No more messages of the receiver wait in the mailbox than fit into the receiver, so that its
+    /// senders still wait while the actor is busy.
+    pub fn adopt<T: Send + 'static, N, A, Args>(
+        &self,
+        name: N,
+        mut receiver: mpsc::Receiver<T>,
+        behavior: A,
+    ) -> ActorRef<T>
+    where
+        N: Into<Arc<str>>,
+        A: Behavior<T, Args>,
+    {
+        let actor = self.spawn(name, behavior);
+        let target = actor.clone();
+        self.inner.spawn_task(async move {
+            let capacity = target.cell.capacity().min(receiver.max_capacity());
+            loop {
+                let next = async {
+                    poll_fn(|cx| target.cell.poll_enqueue(capacity, 0, cx)).await;
+                    let reserved = Reserved::new(&target.cell);
+                    (receiver.recv().await, reserved)
+                };
+                let (msg, reserved) = tokio::select! {
+                    next = next => next,
+                    () = target.wait_for_stop() => return,
+                };
+                let Some(msg) = msg else {
+                    return;
+                };
+                reserved.keep();
+                if target.deliver_reserved(msg).is_err() {
+                    return;
+                }
+            }
+        });
+        actor
+    }
+
+    /// A snapshot of all actors that are currently alive in this system.
+    pub fn actors(&self) -> Vec<ActorInfo> {
+        self.cells().iter().map(|cell| cell.info()).collect()
+    }
+
+    /// The cells of all actors that are currently alive, sorted by their path.
+    pub(crate) fn cells(&self) -> Vec<Arc<ActorCell>> {
+        self.inner.cells()
+    }
+
+    /// The scheduler of the system, owned by the internal actor `/system/scheduler`,
+    /// whose stop cancels all schedules.
+    pub fn scheduler(&self) -> &Scheduler {
+        &self.inner.scheduler
+    }
+
+    /// Publish an event on the system bus, returns the number of subscribers that received it.
+    pub fn publish<E: Clone + Send + 'static>(&self, event: E) -> usize {
+        self.inner.bus.publish(event)
+    }
+
+    /// Subscribe an actor to all events of type `E` on the system bus.
+    ///
+    /// The subscription ends when the actor stops, or when the internal actor `/system/bus` stops.
+    pub fn subscribe<E: Send + 'static>(&self, subscriber: &ActorRef<E>) {
+        self.inner.bus.subscribe(subscriber.clone());
+    }
+
+    pub fn unsubscribe<E: Send + 'static>(&self, subscriber: &ActorRef<E>) {
+        self.inner.bus.unsubscribe(subscriber);
+    }
+
+    /// Tell `watcher` the message returned by `on_terminated` once `target` has stopped.
+    ///
+    /// Watching keeps neither actor alive. If `target` has already stopped, the message is sent immediately.
+    /// Remote actors are watched through the connection to their system, if that connection fails,
+    /// the watcher is told that the target is unreachable.
+    pub fn watch<T, M, F>(&self, target: &ActorRef<T>, watcher: &ActorRef<M>, on_terminated: F)
+    where
+        M: Send + 'static,
+        F: FnOnce(Terminated) -> M + Send + 'static,
+    {
+        let watcher = watcher.downgrade();
+        self.watch_with(target, move |terminated| {
+            if let Some(watcher) = watcher.upgrade() {
+                let _ = watcher.tell(on_terminated(terminated));
+            }
+        });
+    }
+
+    /// Call `on_terminated` once `target` has stopped, see [`ActorSystem::watch`].
+    pub(crate) fn watch_with<T>(
+        &self,
+        target: &ActorRef<T>,
+        on_terminated: impl FnOnce(Terminated) + Send + 'static,
+    ) {
+        let path = target.path().clone();
+        let notify = move |unreachable: bool| on_terminated(Terminated { path, unreachable });
+
+        #[cfg(feature = "remote")]
+        if let Some((authority, path)) = crate::remote::parse_uri(target.path().as_str()) {
+            self.inner
+                .remote
+                .watch(&self.inner, authority, path, Box::new(notify));
+            return;
+        }
+
+        let cell = Arc::clone(&target.cell);
+        self.inner.spawn_task(async move {
+            cell.wait_for_stop().await;
+            notify(false);
+        });
+    }
+
+    /// Wait until no actor has messages that are waiting, being handled, or delayed with
+    /// [`ActorRef::tell_after`], so that tests can assert the final state without sleeping.
+    ///
+    /// The recurring schedules of the [`Scheduler`] are not waited for.
+    pub async fn run_until_idle(&self) {
+        self.inner
+            .activity
+            .wait_until(|| {
+                let cells = self.cells();
+                let activity = cells.iter().map(|cell| cell.activity()).sum::<u64>();
+                cells.iter().all(|cell| cell.is_idle())
+                    && cells.iter().map(|cell| cell.activity()).sum::<u64>() == activity
+            })
+            .await;
+    }
+
+    /// Stop all user actors, i.e. the actors below `/user`, and then the internal actors below `/system`,
+    /// and wait until they have stopped.
+    ///
+    /// The actors are stopped by their guardian, which stops afterwards. Actors that are spawned
+    /// in the meantime or afterwards are below a new guardian, which the next call stops.
+    pub async fn terminate(&self) {
+        for guardian in [&self.inner.user_guardian, &self.inner.system_guardian] {
+            let guarded = guardian.lock().unwrap().take();
+            if let Some(Guarded { guardian, .. }) = guarded {
+                let _ = guardian.tell(Guardian::Terminate);
+                guardian.wait_for_stop().await;
+            }
+        }
+    }
+
+    /// Subscribe to the lifecycle events of all actors in this system.
+    pub fn lifecycle_events(&self) -> LifecycleEvents {
+        LifecycleEvents {
+            rx: self.inner.lifecycle.subscribe(),
+        }
+    }
+
+    /// Subscribe to the messages that the actors of this system did not handle.
+    ///
+    /// The subscription ends when the internal actor `/system/dead-letters` stops.
+    pub fn dead_letters(&self) -> DeadLetters {
+        DeadLetters {
+            rx: self.inner.dead_letters.subscribe(),
+        }
+    }
+
+    /// Subscribe to the messages of type `T` that the actors of this system did not handle,
+    /// e.g. to route undeliverable work elsewhere.
+    ///
+    /// These are the messages that are told to an actor that no longer handles messages, e.g. after
+    /// [`Behaviors::Empty`](crate::Behaviors::Empty), the messages that are still waiting in the
+    /// mailbox when an actor stops, and the messages that a behavior hands back with
+    /// [`Error::Unhandled`](crate::Error::Unhandled) while the actor is resumed or restarted,
+    /// as well as the messages to a pool without routees.
+    /// Every message goes to one subscriber, the earliest one that is still subscribed.
+    ///
+    /// Messages that a behavior has taken are gone, those for which it returns
+    /// [`Behaviors::Unhandled`](crate::Behaviors::Unhandled) or fails otherwise are only counted
+    /// and published without the message on [`ActorSystem::dead_letters`].
+    pub fn dead_letters_of<T: Send + 'static>(&self) -> DeadLettersOf<T> {
+        self.inner.dead_letter_values.subscribe()
+    }
+}
+
+impl SystemInner {
+    pub(crate) fn register(self: &Arc<Self>, path: ActorPath) -> Arc<ActorCell> {
+        let guardian = self.guardian_below(&path);
+        let id = self.next_id.fetch_add(1, Ordering::Relaxed);
+        let cell = Arc::new(ActorCell::new(
+            id,
+            path,
+            Some(self.dead_letters.clone()),
+            Some(Arc::clone(&self.dead_letter_values)),
+            Some(Arc::clone(&self.activity)),
+            self.crash_dump,
+        ));
+        let mut started = false;
+        if let Some((guardian, guardian_path)) = guardian {
+            let mut guarded = guardian.lock().unwrap();
+            started = guarded.is_none() && guardian_path == ActorPath::user_guardian();
+            guarded
+                .get_or_insert_with(|| guardian::guardian(self, guardian_path))
+                .adopt(&cell);
+        }
+        let _ = self.actors.lock().unwrap().insert(id, Arc::clone(&cell));
+        if started {
+            self.spawn_internals();
+        }
+        cell
+    }
+
+    pub(crate) fn cells(&self) -> Vec<Arc<ActorCell>> {
+        let actors = self.actors.lock().unwrap();
+        let mut cells = actors.values().cloned().collect::<Vec<_>>();
+        cells.sort_by(|a, b| a.path.as_str().cmp(b.path.as_str()));
+        cells
+    }
+
+    /// The guardian of the actor at `path` and its path, if it is a top-level actor below `/user` or `/system`.
+    fn guardian_of(&self, path: &ActorPath) -> Option<&Mutex<Option<Guarded>>> {
+        let (parent, _) = path.as_str().rsplit_once('/')?;
+        self.guardians()
+            .find(|(_, guardian)| guardian.as_str() == parent)
+            .map(|(guarded, _)| guarded)
+    }
+
+    /// The guardian that stops the actor at `path` and its path, if it is any actor below `/user` or `/system`.
+    fn guardian_below(&self, path: &ActorPath) -> Option<(&Mutex<Option<Guarded>>, ActorPath)> {
+        self.guardians().find(|(_, guardian)| {
+            path.as_str()
+                .strip_prefix(guardian.as_str())
+                .is_some_and(|below| below.starts_with('/'))
+        })
+    }
+
+    fn guardians(&self) -> impl Iterator<Item = (&Mutex<Option<Guarded>>, ActorPath)> {
+        [
+            (&self.user_guardian, ActorPath::user_guardian()),
+            (&self.system_guardian, ActorPath::system_guardian()),
+        ]
+        .into_iter()
+    }
+
+    /// Spawn the internal actors that own the components of the system, unless they are running.
+    ///
+    /// They are spawned with the first user actor rather than when the system is built, so that
+    /// a system can be built outside of a runtime, e.g. for a [`BehaviorTestKit`](crate::BehaviorTestKit).
+    fn spawn_internals(self: &Arc<Self>) {
+        self.internal(&self.internals.scheduler, "scheduler", |system| {
+            system.scheduler.cancel_all();
+        });
+        self.internal(&self.internals.bus, "bus", |system| system.bus.clear());
+        self.internal(&self.internals.dead_letters, "dead-letters", |system| {
+            system.dead_letter_values.close();
+        });
+    }
+
+    /// Spawn the internal actor `name` into `actor` unless it is running already.
+    fn internal(
+        self: &Arc<Self>,
+        actor: &Mutex<Option<ActorRef<Infallible>>>,
+        name: &str,
+        stop: fn(&Self),
+    ) {
+        let mut actor = actor.lock().unwrap();
+        if actor.as_ref().is_some_and(ActorRef::is_alive) == false {
+            *actor = Some(guardian::internal(self, name, stop));
+        }
+    }
+
+    /// Reserve the path for a user actor named `name`, or for the first free suffix of `name`.
+    fn claim(&self, name: &str) -> Result<Claim<'_>, NameTaken> {
+        let mut names = self.names.lock().unwrap();
+        let mut path = ActorPath::user(name);
+        let mut suffix = 0;
+        // actors without refs are still stopping, but they can no longer be reached by their name
+        while let Some(named) = names.get(&path) {
+            if named
+                .as_ref()
+                .is_some_and(|(_, actor)| actor.is_alive() == false)
+            {
+                break;
+            }
+            if self.duplicate_names == DuplicateNames::Reject {
+                return Err(NameTaken(name.to_owned()));
+            }
+            suffix += 1;
+            path = ActorPath::user(&format!("{name}-{suffix}"));
+        }
+        let _ = names.insert(path.clone(), None);
+        Ok(Claim {
+            system: self,
+            path,
+            spawned: false,
+        })
+    }
+
+    /// Make the actor selectable by its path, see [`ActorSystem::select`].
+    pub(crate) fn selectable<T: Send + 'static>(&self, actor: &ActorRef<T>) {
+        let mut refs = self.refs.lock().unwrap();
+        let _ = refs.insert(actor.cell.id, Box::new(actor.downgrade()));
+    }
+
+    /// The live actors of type `T` whose path matches, sorted by their path.
+    pub(crate) fn actors_of<T: Send + 'static>(
+        &self,
+        matches: impl Fn(&str) -> bool,
+    ) -> Vec<ActorRef<T>> {
+        let refs = self.refs.lock().unwrap();
+        let mut actors = refs
+            .values()
+            .filter_map(|actor| actor.as_any().downcast_ref::<WeakActorRef<T>>())
+            .filter(|actor| matches(actor.path().as_str()))
+            .filter_map(WeakActorRef::upgrade)
+            .filter(ActorRef::is_alive)
+            .collect::<Vec<_>>();
+        drop(refs);
+        actors.sort_by(|a, b| a.path().as_str().cmp(b.path().as_str()));
+        actors
+    }
+
+    /// Make the actor at a claimed path findable.
+    fn named<T: Send + 'static>(&self, actor: &ActorRef<T>) {
+        let mut names = self.names.lock().unwrap();
+        // the actor may have already stopped and released its name
+        if let Some(entry) = names.get_mut(actor.path()) {
+            *entry = Some((actor.cell.id, Box::new(actor.downgrade())));
+        }
+    }
+
+    /// A name that no other anonymous actor of this system has.
+    pub(crate) fn anonymous_name(&self) -> String {
+        let id = self.next_anonymous.fetch_add(1, Ordering::Relaxed);
+        format!("$a-{id}")
+    }
+
+    /// Spawn a task of the system, like [`tokio::spawn`] but on the runtime of the system.
+    pub(crate) fn spawn_task(&self, task: impl Future<Output = ()> + Send + 'static) {
+        self.runtime.spawn("elliot-system", Box::pin(task));
+    }
+
+    /// Like [`SystemInner::spawn_task`], but the task can be aborted.
+    #[cfg(feature = "cluster")]
+    pub(crate) fn spawn_abortable(
+        &self,
+        task: impl Future<Output = ()> + Send + 'static,
+    ) -> runtime::AbortHandle {
+        runtime::spawn_abortable(&*self.runtime, "elliot-system", task)
+    }
+
+    #[cfg(feature = "cluster")]
+    pub(crate) fn runtime(&self) -> &Arc<dyn Runtime> {
+        &self.runtime
+    }
+
+    /// Spawn the task driving the actor of `cell`, on `runtime` if given,
+    /// or else on the configured dispatcher of the actor or the runtime of the system.
+    pub(crate) fn spawn_actor(
+        &self,
+        runtime: Option<Handle>,
+        cell: &ActorCell,
+        task: impl Future<Output = ()> + Send + 'static,
+    ) {
+        #[cfg(feature = "config")]
+        let runtime = runtime.or_else(|| self.settings(&cell.path).and_then(|s| s.runtime.clone()));
+        let runtime: Arc<dyn Runtime> = match runtime {
+            Some(runtime) => Arc::new(runtime::Tokio(Some(runtime))),
+            None => Arc::clone(&self.runtime),
+        };
+        runtime.spawn(cell.path.as_str(), Box::pin(task));
+        cell.set_runtime(runtime);
+    }
+
+    /// The bound of the mailbox for the actor at `path`, `None` for an unbounded mailbox.
+    ///
+    /// The system-wide capacity applies to user actors only.
+    pub(crate) fn mailbox_capacity_for(&self, path: &ActorPath) -> Option<usize> {
+        #[cfg(feature = "config")]
+        if let Some(capacity) = self.settings(path).and_then(|s| s.mailbox_capacity) {
+            return Some(capacity.max(1));
+        }
+        self.mailbox_capacity
+            .filter(|_| path.as_str().starts_with("/user/"))
+    }
+
+    #[cfg(feature = "config")]
+    fn settings(&self, path: &ActorPath) -> Option<&ActorSettings> {
+        self.actor_settings.get(path.as_str())
+    }
+
+    pub(crate) fn deregister(&self, cell: &ActorCell) {
+        let _ = self.actors.lock().unwrap().remove(&cell.id);
+        if let Some((guardian, _)) = self.guardian_below(&cell.path) {
+            if let Some(guarded) = guardian.lock().unwrap().as_ref() {
+                guarded.release(cell);
+            }
+        }
+        self.activity.changed();
+        let _ = self.refs.lock().unwrap().remove(&cell.id);
+        let mut names = self.names.lock().unwrap();
+        // other actors at the same path are not named, e.g. the actors of a pool
+        if matches!(names.get(&cell.path), Some(Some((id, _))) if *id == cell.id) {
+            let _ = names.remove(&cell.path);
+        }
+    }
+
+    /// Send [`Signal::ChildFailed`] to the parent of the crashed actor, if it is alive.
+    pub(crate) fn signal_parent(&self, crashed: &ActorCrashed) {
+        if let Some(parent) = self.parent_of(&crashed.path) {
+            parent.signal(Signal::ChildFailed(crashed.clone()));
+        }
+    }
+
+    /// Fail the parent of the crashed actor, see [`ChildOptions::escalate_after`](crate::ChildOptions::escalate_after).
+    pub(crate) fn escalate(&self, crashed: &ActorCrashed) {
+        if let Some(parent) = self.parent_of(&crashed.path) {
+            parent.escalate(crashed.clone());
+        }
+    }
+
+    fn parent_of(&self, path: &ActorPath) -> Option<Arc<ActorCell>> {
+        if let Some(guardian) = self.guardian_of(path) {
+            let guarded = guardian.lock().unwrap();
+            return Some(Arc::clone(&guarded.as_ref()?.guardian.cell));
+        }
+        let (parent, _) = path.as_str().rsplit_once('/')?;
+        let actors = self.actors.lock().unwrap();
+        actors
+            .values()
+            .find(|cell| cell.path.as_str() == parent)
+            .cloned()
+    }
+
+    pub(crate) fn sleep(&self, duration: Duration) -> runtime::Task {
+        self.runtime.sleep(duration)
+    }
+
+    pub(crate) fn publish_lifecycle(&self, event: LifecycleEvent) {
+        // no subscribers is not an error
+        let _ = self.lifecycle.send(event);
+    }
+}
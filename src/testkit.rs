@@ -0,0 +1,334 @@
+use crate::{
+    ask::block_until,
+    cell::ActorCell,
+    mailbox::{self, Mailbox},
+    ActorContext, ActorPath, ActorRef, ActorSystem, Behavior, Behaviors, Error, Signal,
+};
+use std::{
+    any::Any,
+    collections::HashMap,
+    future::Future,
+    marker::PhantomData,
+    sync::{Arc, Mutex, Weak},
+    time::{Duration, Instant},
+};
+
+/// How long a handler may take before the test kit gives up on it.
+const WAIT: Duration = Duration::from_secs(3);
+
+/// Something that a behavior did with its [`ActorContext`], recorded by a [`BehaviorTestKit`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Effect {
+    /// A child was spawned, its messages wait in [`BehaviorTestKit::child_inbox`].
+    Spawned(ActorPath),
+    /// An actor was watched with [`ActorContext::watch`].
+    Watched(ActorPath),
+    /// A message was scheduled with [`ActorRef::tell_after`], it is delivered by
+    /// [`BehaviorTestKit::fire_timers`].
+    Scheduled { target: ActorPath, delay: Duration },
+    /// The receive timeout was changed with [`ActorContext::set_receive_timeout`].
+    ReceiveTimeout(Option<Duration>),
+}
+
+/// Runs a behavior on the current thread without spawning it, for fast and deterministic
+/// tests of its handlers.
+///
+/// Messages are handled one at a time with [`BehaviorTestKit::run`], which returns what the
+/// behavior returned. What the behavior does with its context is recorded as [`Effect`]s instead:
+/// children are not spawned, their messages wait in a [`TestInbox`], and so do the messages that
+/// the behavior tells itself. Timers of [`ActorRef::tell_after`] to any of the inboxes wait for
+/// [`BehaviorTestKit::fire_timers`].
+///
+/// The behavior is not supervised, and handlers that wait for other actors, e.g. for the answer
+/// of an ask, do not finish.
+///
+/// Only the context and the refs of the kit are intercepted, everything else is live:
+/// tells to other refs are delivered, the [`Scheduler`](crate::Scheduler) of the system schedules
+/// real timers, and [throttled](ActorRef::throttled), [conflated](ActorRef::conflated) and
+/// [sequenced](ActorRef::sequenced) refs run their own tasks. Those need a runtime, without one
+/// they panic.
+pub struct BehaviorTestKit<T, A, Args = ()> {
+    behavior: A,
+    context: ActorContext<T>,
+    inbox: TestInbox<T>,
+    effects: Arc<Effects>,
+    stopped: bool,
+    _args: PhantomData<fn(Args)>,
+}
+
+impl<T, A, Args> BehaviorTestKit<T, A, Args>
+where
+    T: Send + 'static,
+    A: Behavior<T, Args>,
+{
+    /// Test `behavior` as the actor `/user/test`.
+    pub fn new(behavior: A) -> Self {
+        Self::named("test", behavior)
+    }
+
+    /// Test `behavior` as the actor `/user/<name>`.
+    pub fn named(name: &str, behavior: A) -> Self {
+        let effects = Effects::new();
+        let inbox = effects.inbox(ActorPath::user(name));
+        let context = ActorContext::new(inbox.this.downgrade(), ActorSystem::new());
+        Self {
+            behavior,
+            context,
+            inbox,
+            effects,
+            stopped: false,
+            _args: PhantomData,
+        }
+    }
+
+    /// Handle `msg` and return what the behavior returned.
+    ///
+    /// # Panics
+    ///
+    /// If the behavior has stopped or the handler does not finish.
+    pub fn run(&mut self, msg: T) -> Result<Behaviors, Error<T>> {
+        self.assert_running();
+        let handled = self.behavior.receive(&self.context, msg);
+        self.handled(handled)
+    }
+
+    /// Handle the messages that the behavior has told itself, until there are no more.
+    ///
+    /// # Panics
+    ///
+    /// Like [`BehaviorTestKit::run`].
+    pub fn run_inbox(&mut self) -> Vec<Result<Behaviors, Error<T>>> {
+        let mut handled = Vec::new();
+        while self.stopped == false {
+            match self.inbox.try_receive() {
+                Some(msg) => handled.push(self.run(msg)),
+                None => break,
+            }
+        }
+        handled
+    }
+
+    /// Handle `signal`, `None` if the behavior ignores signals.
+    ///
+    /// # Panics
+    ///
+    /// Like [`BehaviorTestKit::run`].
+    pub fn signal(&mut self, signal: Signal) -> Option<Result<Behaviors, Error<T>>> {
+        self.assert_running();
+        let handled = self.behavior.receive_signal(&self.context, signal)?;
+        Some(self.handled(handled))
+    }
+
+    /// Take the effects that were recorded since the last call, in the order in which they happened.
+    pub fn effects(&self) -> Vec<Effect> {
+        std::mem::take(&mut *self.effects.recorded.lock().unwrap())
+    }
+
+    /// Deliver the scheduled messages that have not been cancelled, the shortest delay first.
+    pub fn fire_timers(&self) {
+        let mut timers = std::mem::take(&mut *self.effects.timers.lock().unwrap());
+        timers.sort_by_key(|timer| timer.delay);
+        for timer in timers {
+            (timer.deliver)();
+        }
+    }
+
+    /// The messages that the behavior has told itself.
+    pub fn self_inbox(&self) -> &TestInbox<T> {
+        &self.inbox
+    }
+
+    /// The messages of the child `name`, `None` if it has not been spawned or has another message type.
+    pub fn child_inbox<U: Send + 'static>(&self, name: &str) -> Option<TestInbox<U>> {
+        let children = self.effects.children.lock().unwrap();
+        children.get(name)?.downcast_ref().cloned()
+    }
+
+    /// An inbox at `/user/<name>`, e.g. to receive the replies of the behavior.
+    pub fn inbox<U: Send + 'static>(&self, name: &str) -> TestInbox<U> {
+        self.effects.inbox(ActorPath::user(name))
+    }
+
+    /// A ref to the tested actor, its messages wait in [`BehaviorTestKit::self_inbox`].
+    pub fn this(&self) -> ActorRef<T> {
+        self.inbox.this.clone()
+    }
+
+    pub fn context(&self) -> &ActorContext<T> {
+        &self.context
+    }
+
+    /// The behavior, e.g. to look at its state.
+    pub fn behavior(&self) -> &A {
+        &self.behavior
+    }
+
+    /// Returns `false` once the behavior has returned [`Behaviors::Stopped`].
+    pub fn is_alive(&self) -> bool {
+        self.stopped == false
+    }
+
+    fn assert_running(&self) {
+        assert!(
+            self.stopped == false,
+            "the behavior of {} has stopped",
+            self.context.path()
+        );
+    }
+
+    fn handled<F>(&mut self, handled: F) -> Result<Behaviors, Error<T>>
+    where
+        F: Future<Output = Result<Behaviors, Error<T>>>,
+    {
+        let handled = block_until(Box::pin(handled), Instant::now() + WAIT).unwrap_or_else(|| {
+            panic!(
+                "the behavior of {} did not finish within {WAIT:?}, it cannot wait for other actors",
+                self.context.path()
+            )
+        });
+        if let Ok(Behaviors::Stopped) = handled {
+            self.stopped = true;
+        }
+        handled
+    }
+}
+
+impl<T, A, Args> std::fmt::Debug for BehaviorTestKit<T, A, Args> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BehaviorTestKit")
+            .field("path", self.context.path())
+            .field("stopped", &self.stopped)
+            .finish_non_exhaustive()
+    }
+}
+
+/// A ref whose messages are not handled by an actor, but wait until the test takes them.
+///
+/// All clones share the same messages.
+pub struct TestInbox<T> {
+    this: ActorRef<T>,
+    mailbox: Arc<Mutex<Mailbox<T>>>,
+}
+
+impl<T> TestInbox<T> {
+    pub fn this(&self) -> ActorRef<T> {
+        self.this.clone()
+    }
+
+    pub fn path(&self) -> &ActorPath {
+        self.this.path()
+    }
+
+    /// Take the oldest message, `None` if there is none.
+    pub fn try_receive(&self) -> Option<T> {
+        self.mailbox.lock().unwrap().try_recv()
+    }
+
+    /// Take all messages, the oldest first.
+    pub fn receive_all(&self) -> Vec<T> {
+        std::iter::from_fn(|| self.try_receive()).collect()
+    }
+}
+
+impl<T> Clone for TestInbox<T> {
+    fn clone(&self) -> Self {
+        Self {
+            this: self.this.clone(),
+            mailbox: Arc::clone(&self.mailbox),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for TestInbox<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TestInbox")
+            .field("path", self.path())
+            .finish_non_exhaustive()
+    }
+}
+
+/// What the actors of a [`BehaviorTestKit`] did, shared by the cells of its inboxes.
+pub(crate) struct Effects {
+    this: Weak<Effects>,
+    recorded: Mutex<Vec<Effect>>,
+    timers: Mutex<Vec<Timer>>,
+    /// The [`TestInbox`]es of the spawned children, by name.
+    children: Mutex<HashMap<String, Box<dyn Any + Send>>>,
+}
+
+/// A scheduled message that waits for [`BehaviorTestKit::fire_timers`].
+struct Timer {
+    delay: Duration,
+    deliver: Box<dyn FnOnce() + Send>,
+}
+
+impl Effects {
+    fn new() -> Arc<Self> {
+        Arc::new_cyclic(|this| Self {
+            this: Weak::clone(this),
+            recorded: Mutex::default(),
+            timers: Mutex::default(),
+            children: Mutex::default(),
+        })
+    }
+
+    fn inbox<T: Send + 'static>(&self, path: ActorPath) -> TestInbox<T> {
+        let cell = Arc::new(ActorCell::unregistered(path));
+        cell.set_effects(Weak::clone(&self.this));
+        let (this, mailbox) = mailbox::channel(cell);
+        TestInbox {
+            this,
+            mailbox: Arc::new(Mutex::new(mailbox)),
+        }
+    }
+
+    pub(crate) fn record(&self, effect: Effect) {
+        self.recorded.lock().unwrap().push(effect);
+    }
+
+    /// Record a spawned child, whose messages wait in an inbox instead.
+    pub(crate) fn spawned<T: Send + 'static>(&self, path: ActorPath) -> ActorRef<T> {
+        let inbox = self.inbox::<T>(path.clone());
+        let this = inbox.this();
+        let _ = self
+            .children
+            .lock()
+            .unwrap()
+            .insert(path.name().to_owned(), Box::new(inbox));
+        self.record(Effect::Spawned(path));
+        this
+    }
+
+    /// Record a message of [`ActorRef::tell_after`], which is delivered unless `msg` is taken before.
+    pub(crate) fn scheduled<T: Send + 'static>(
+        &self,
+        target: &ActorRef<T>,
+        msg: Arc<Mutex<Option<T>>>,
+        delay: Duration,
+    ) {
+        self.record(Effect::Scheduled {
+            target: target.path().clone(),
+            delay,
+        });
+        let target = target.downgrade();
+        let deliver = move || {
+            let msg = msg.lock().unwrap().take();
+            if let (Some(msg), Some(target)) = (msg, target.upgrade()) {
+                let _ = target.tell(msg);
+            }
+        };
+        self.timers.lock().unwrap().push(Timer {
+            delay,
+            deliver: Box::new(deliver),
+        });
+    }
+}
+
+impl std::fmt::Debug for Effects {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Effects")
+            .field("recorded", &self.recorded)
+            .finish_non_exhaustive()
+    }
+}
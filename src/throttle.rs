@@ -0,0 +1,130 @@
+use crate::{
+    cell::ActorCell,
+    mailbox,
+    runtime::{Instant, Runtime},
+    ActorRef,
+};
+use std::{sync::Arc, time::Duration};
+
+/// The rate that is enforced by [`ActorRef::throttled`].
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub struct Rate {
+    messages: u32,
+    per: Duration,
+    burst: u32,
+    drop_excess: bool,
+}
+
+impl Rate {
+    /// Allow `messages` messages for every `per` duration.
+    ///
+    /// # Panics
+    ///
+    /// If `messages` or `per` is zero.
+    pub fn new(messages: u32, per: Duration) -> Self {
+        assert!(messages > 0, "rate must allow at least one message");
+        assert!(!per.is_zero(), "rate period must not be zero");
+        Self {
+            messages,
+            per,
+            burst: messages,
+            drop_excess: false,
+        }
+    }
+
+    pub fn per_second(messages: u32) -> Self {
+        Self::new(messages, Duration::from_secs(1))
+    }
+
+    /// How many messages can be delivered at once after a quiet period, the default is `messages`.
+    pub fn burst(mut self, burst: u32) -> Self {
+        self.burst = burst.max(1);
+        self
+    }
+
+    /// Drop messages that exceed the rate instead of buffering them until they can be delivered.
+    pub fn drop_excess(mut self) -> Self {
+        self.drop_excess = true;
+        self
+    }
+}
+
+/// A token bucket that starts full.
+#[derive(Debug)]
+struct Bucket {
+    rate: Rate,
+    tokens: f64,
+    last_refill: Instant,
+}
+
+impl Bucket {
+    fn new(rate: Rate) -> Self {
+        Self {
+            rate,
+            tokens: f64::from(rate.burst),
+            last_refill: Instant::now(),
+        }
+    }
+
+    fn refill(&mut self) {
+        let now = Instant::now();
+        let elapsed = now.duration_since(self.last_refill).as_secs_f64();
+        let refill = elapsed * f64::from(self.rate.messages) / self.rate.per.as_secs_f64();
+        self.tokens = (self.tokens + refill).min(f64::from(self.rate.burst));
+        self.last_refill = now;
+    }
+
+    fn try_acquire(&mut self) -> bool {
+        self.refill();
+        if self.tokens >= 1.0 {
+            self.tokens -= 1.0;
+            true
+        } else {
+            false
+        }
+    }
+
+    /// Wait until a token is available and take it.
+    async fn acquire(&mut self, runtime: &dyn Runtime) {
+        while !self.try_acquire() {
+            let missing = 1.0 - self.tokens;
+            let wait = self
+                .rate
+                .per
+                .mul_f64(missing / f64::from(self.rate.messages));
+            runtime.sleep(wait).await;
+        }
+    }
+}
+
+pub(crate) fn throttled<T: Send + 'static>(target: &ActorRef<T>, rate: Rate) -> ActorRef<T> {
+    let cell = ActorCell::unregistered(target.path().child("$throttle"));
+    cell.set_runtime(target.cell.runtime());
+    let (this, mut mailbox) = mailbox::channel(Arc::new(cell));
+    let target = target.clone();
+    let runtime = this.cell.runtime();
+    this.cell.spawn_task(async move {
+        let mut bucket = Bucket::new(rate);
+        loop {
+            let msg = tokio::select! {
+                msg = mailbox.recv() => msg,
+                () = target.wait_for_stop() => None,
+            };
+            let Some(msg) = msg else {
+                return;
+            };
+            if rate.drop_excess {
+                if !bucket.try_acquire() {
+                    mailbox.cell().dead_letter();
+                    continue;
+                }
+            } else {
+                bucket.acquire(&*runtime).await;
+            }
+            if target.tell(msg).is_err() {
+                return;
+            }
+        }
+    });
+    this
+}
@@ -0,0 +1,100 @@
+use crate::{ActorRef, ActorSystem};
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+type Subscribers<T> = Arc<Mutex<Vec<ActorRef<T>>>>;
+
+type Key = (Arc<str>, TypeId);
+
+/// The topics of a system, keyed by their name and the type of their messages.
+#[derive(Debug, Default)]
+pub(crate) struct Topics {
+    topics: Mutex<HashMap<Key, Box<dyn Any + Send>>>,
+}
+
+/// A named channel for messages of type `T` within one system, see [`ActorSystem::topic`].
+///
+/// Unlike the system bus, there can be any number of topics for the same type.
+/// Subscriptions end when the subscriber stops.
+pub struct Topic<T> {
+    name: Arc<str>,
+    subscribers: Subscribers<T>,
+}
+
+impl ActorSystem {
+    /// The topic `name` for messages of type `T`.
+    ///
+    /// Every call with the same name and type returns a handle to the same topic.
+    pub fn topic<T: Clone + Send + 'static>(&self, name: &str) -> Topic<T> {
+        let name = Arc::<str>::from(name);
+        let mut topics = self.inner.topics.topics.lock().unwrap();
+        let subscribers = topics
+            .entry((Arc::clone(&name), TypeId::of::<T>()))
+            .or_insert_with(|| Box::new(Subscribers::<T>::default()))
+            .downcast_ref::<Subscribers<T>>()
+            .expect("topics are keyed by their type");
+        Topic {
+            name,
+            subscribers: Arc::clone(subscribers),
+        }
+    }
+}
+
+impl<T: Clone + Send + 'static> Topic<T> {
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Send `msg` to all subscribers, returns the number of subscribers that received it.
+    pub fn publish(&self, msg: T) -> usize {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(|s| s.tell(msg.clone()).is_ok());
+        subscribers.len()
+    }
+
+    /// Receive the messages that are published on this topic, once per subscriber.
+    pub fn subscribe(&self, subscriber: &ActorRef<T>) {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(ActorRef::is_alive);
+        if subscribers
+            .iter()
+            .all(|s| s.same_actor(subscriber) == false)
+        {
+            subscribers.push(subscriber.clone());
+        }
+    }
+
+    pub fn unsubscribe(&self, subscriber: &ActorRef<T>) {
+        self.subscribers
+            .lock()
+            .unwrap()
+            .retain(|s| s.same_actor(subscriber) == false);
+    }
+
+    /// The number of subscribers that are still alive.
+    pub fn subscribers(&self) -> usize {
+        let mut subscribers = self.subscribers.lock().unwrap();
+        subscribers.retain(ActorRef::is_alive);
+        subscribers.len()
+    }
+}
+
+impl<T> Clone for Topic<T> {
+    fn clone(&self) -> Self {
+        Self {
+            name: Arc::clone(&self.name),
+            subscribers: Arc::clone(&self.subscribers),
+        }
+    }
+}
+
+impl<T> std::fmt::Debug for Topic<T> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Topic")
+            .field("name", &self.name)
+            .finish_non_exhaustive()
+    }
+}
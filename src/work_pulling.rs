@@ -0,0 +1,181 @@
+use crate::{ActorContext, ActorRef, ActorSystem, Behavior, Behaviors, Error};
+use pin_project_lite::pin_project;
+use std::{
+    collections::VecDeque,
+    future::{ready, Future, Ready},
+    pin::Pin,
+    sync::{Arc, Mutex},
+    task::{Context, Poll},
+};
+
+/// Messages for a work-pulling producer, see [`ActorSystem::spawn_producer`].
+pub enum Work<W> {
+    /// Queue a work item until a worker asks for it.
+    Submit(W),
+    /// The worker is ready to receive that many more work items.
+    Demand(ActorRef<W>, usize),
+}
+
+impl ActorSystem {
+    /// Spawn a producer that hands out submitted work only to workers that have asked for it.
+    ///
+    /// Work is queued until there is demand, and workers never receive more items than
+    /// they have asked for. Workers are served round-robin.
+    pub fn spawn_producer<W, N>(&self, name: N) -> ActorRef<Work<W>>
+    where
+        W: Send + 'static,
+        N: Into<Arc<str>>,
+    {
+        self.spawn(name, Producer::default())
+    }
+
+    /// Spawn a worker that pulls its work from a producer.
+    ///
+    /// The worker asks for `demand` items initially and for another one
+    /// after each work item has been handled.
+    pub fn spawn_worker<W, N, A, Args>(
+        &self,
+        name: N,
+        producer: &ActorRef<Work<W>>,
+        demand: usize,
+        behavior: A,
+    ) -> ActorRef<W>
+    where
+        W: Send + 'static,
+        N: Into<Arc<str>>,
+        A: Behavior<W, Args>,
+        Args: 'static,
+    {
+        let worker = self.spawn(
+            name,
+            Worker {
+                producer: producer.clone(),
+                behavior,
+            },
+        );
+        let _ = producer.tell(Work::Demand(worker.clone(), demand));
+        worker
+    }
+}
+
+struct Producer<W> {
+    state: Mutex<ProducerState<W>>,
+}
+
+struct ProducerState<W> {
+    work: VecDeque<W>,
+    workers: VecDeque<(ActorRef<W>, usize)>,
+}
+
+impl<W> Default for Producer<W> {
+    fn default() -> Self {
+        Self {
+            state: Mutex::new(ProducerState {
+                work: VecDeque::new(),
+                workers: VecDeque::new(),
+            }),
+        }
+    }
+}
+
+impl<W> ProducerState<W> {
+    fn dispatch(&mut self) {
+        while let Some(work) = self.work.pop_front() {
+            let Some((worker, demand)) = self.workers.pop_front() else {
+                self.work.push_front(work);
+                return;
+            };
+            match worker.tell(work) {
+                Ok(()) if demand > 1 => self.workers.push_back((worker, demand - 1)),
+                Ok(()) => {}
+                // the worker is gone, give the work to the next one
+                Err(gone) => self.work.push_front(gone.0),
+            }
+        }
+    }
+}
+
+impl<W: Send + 'static> Behavior<Work<W>> for Producer<W> {
+    type F = Ready<Result<Behaviors, Error<Work<W>>>>;
+
+    fn receive(&self, _context: &ActorContext<Work<W>>, msg: Work<W>) -> Self::F {
+        let mut state = self.state.lock().unwrap();
+        match msg {
+            Work::Submit(work) => state.work.push_back(work),
+            Work::Demand(worker, demand) => {
+                match state
+                    .workers
+                    .iter_mut()
+                    .find(|(w, _)| w.same_actor(&worker))
+                {
+                    Some((_, existing)) => *existing += demand,
+                    None if demand > 0 => state.workers.push_back((worker, demand)),
+                    None => {}
+                }
+            }
+        }
+        state.dispatch();
+        ready(Ok(Behaviors::Same))
+    }
+}
+
+struct Worker<W, B> {
+    producer: ActorRef<Work<W>>,
+    behavior: B,
+}
+
+impl<W, B, Args> Behavior<W, Args> for Worker<W, B>
+where
+    W: Send + 'static,
+    B: Behavior<W, Args>,
+    Args: 'static,
+{
+    type F = RequestMore<B::F, W>;
+
+    fn receive(&self, context: &ActorContext<W>, msg: W) -> Self::F {
+        RequestMore {
+            inner: self.behavior.receive(context, msg),
+            producer: self.producer.clone(),
+            this: context.this(),
+        }
+    }
+}
+
+pin_project! {
+    pub struct RequestMore<F, W> {
+        #[pin]
+        inner: F,
+        producer: ActorRef<Work<W>>,
+        this: ActorRef<W>,
+    }
+}
+
+impl<F, W> Future for RequestMore<F, W>
+where
+    F: Future<Output = Result<Behaviors, Error<W>>>,
+{
+    type Output = F::Output;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let this = self.project();
+        let result = match this.inner.poll(cx) {
+            Poll::Ready(result) => result,
+            Poll::Pending => return Poll::Pending,
+        };
+        if matches!(result, Ok(Behaviors::Same | Behaviors::Unhandled)) {
+            let _ = this.producer.tell(Work::Demand(this.this.clone(), 1));
+        }
+        Poll::Ready(result)
+    }
+}
+
+impl<W> std::fmt::Debug for Work<W> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Submit(_) => f.debug_tuple("Submit").finish_non_exhaustive(),
+            Self::Demand(worker, demand) => {
+                f.debug_tuple("Demand").field(worker).field(demand).finish()
+            }
+        }
+    }
+}
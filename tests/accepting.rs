@@ -0,0 +1,79 @@
+use elliot::{ActorSystem, Behaviors, ReplyTo};
+use std::sync::{Arc, Mutex};
+
+/// The narrow messages, as owned by their producers.
+#[derive(Debug, PartialEq)]
+struct Deposit(u32);
+struct Balance(ReplyTo<u32>);
+
+enum Command {
+    Deposit(Deposit),
+    Balance(Balance),
+    Close,
+}
+
+impl From<Deposit> for Command {
+    fn from(deposit: Deposit) -> Self {
+        Self::Deposit(deposit)
+    }
+}
+
+impl From<Balance> for Command {
+    fn from(balance: Balance) -> Self {
+        Self::Balance(balance)
+    }
+}
+
+async fn accepting() {
+    let system = ActorSystem::new();
+    let balance = Arc::new(Mutex::new(0));
+    let account = Arc::clone(&balance);
+    let actor = system.spawn("account", move |command: Command| {
+        let stop = match command {
+            Command::Deposit(Deposit(amount)) => {
+                *account.lock().unwrap() += amount;
+                false
+            }
+            Command::Balance(Balance(reply)) => {
+                let _ = reply.reply(*account.lock().unwrap());
+                false
+            }
+            Command::Close => true,
+        };
+        async move {
+            if stop {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    let deposits = actor.accepting::<Deposit>();
+    let queries = actor.accepting::<Balance>();
+    assert_eq!(deposits.path(), actor.path());
+
+    // the narrow refs keep the actor alive, also through weak refs
+    let weak = deposits.downgrade();
+    drop(actor);
+    deposits.tell(Deposit(10)).unwrap();
+    weak.upgrade().unwrap().tell(Deposit(5)).unwrap();
+    assert_eq!(queries.ask(Balance).await, Ok(15));
+
+    // the messages of all refs share the mailbox
+    let actor = system.find::<Command>("account").unwrap();
+    actor.tell(Command::Close).unwrap();
+    deposits.wait_for_stop().await;
+    assert!(!queries.is_alive());
+    assert_eq!(deposits.tell(Deposit(1)).unwrap_err().0, Deposit(1));
+    assert_eq!(*balance.lock().unwrap(), 15);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(accepting());
+}
@@ -0,0 +1,74 @@
+use elliot::{ActorSystem, LifecycleEvent, StopReason};
+use std::sync::Arc;
+use tokio::sync::{mpsc, Notify};
+
+async fn adopt() {
+    let system = ActorSystem::new();
+    let mut events = system.lifecycle_events();
+    let (tx, rx) = mpsc::channel(4);
+    let (results, mut received) = mpsc::unbounded_channel();
+
+    let worker = system.adopt("worker", rx, move |n: u32| {
+        let _ = results.send(n * 2);
+        async {}
+    });
+
+    // the existing senders keep working
+    for n in 0..10 {
+        tx.send(n).await.unwrap();
+    }
+    for n in 0..10 {
+        assert_eq!(received.recv().await, Some(n * 2));
+    }
+
+    // and so does the ref of the actor
+    worker.tell(21).unwrap();
+    assert_eq!(received.recv().await, Some(42));
+
+    // the receiver keeps the actor alive
+    drop(worker);
+    tx.send(50).await.unwrap();
+    assert_eq!(received.recv().await, Some(100));
+
+    drop(tx);
+    assert_eq!(received.recv().await, None);
+    loop {
+        match events.recv().await {
+            Some(LifecycleEvent::Stopped(e)) => {
+                assert_eq!(e.path.as_str(), "/user/worker");
+                assert_eq!(e.reason, StopReason::NoActorRef);
+                break;
+            }
+            Some(LifecycleEvent::Started(_)) => {}
+            e => panic!("expected stopped event, got {e:?}"),
+        }
+    }
+
+    // the senders wait while the actor is busy
+    let gate = Arc::new(Notify::new());
+    let wait = Arc::clone(&gate);
+    let (tx, rx) = mpsc::channel(2);
+    let _busy = system.adopt("busy", rx, move |(): ()| {
+        let wait = Arc::clone(&wait);
+        async move { wait.notified().await }
+    });
+    let mut sent = 0;
+    for _ in 0..100 {
+        if tx.try_send(()).is_ok() {
+            sent += 1;
+        }
+        tokio::task::yield_now().await;
+    }
+    // one message is handled, two wait in the mailbox and two in the receiver
+    assert!(sent <= 5, "{sent} messages sent");
+    gate.notify_one();
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(adopt());
+}
@@ -0,0 +1,44 @@
+use elliot::{ActorContext, ActorSystem};
+use tokio::sync::oneshot;
+
+type Reply = oneshot::Sender<String>;
+
+async fn anonymous() {
+    let system = ActorSystem::new();
+
+    let first = system.spawn_anonymous(|(): ()| async {});
+    let second = system.spawn_anonymous(|(): ()| async {});
+    assert!(first.path().name().starts_with("$a-"));
+    assert_ne!(first.path(), second.path());
+
+    // every request is handled by a new worker that is a child of the actor
+    let parent = system.spawn(
+        "parent",
+        |ctx: ActorContext<Reply>, reply: Reply| async move {
+            let worker = ctx.spawn_anonymous(|ctx: ActorContext<Reply>, reply: Reply| async move {
+                let _ = reply.send(ctx.path().to_string());
+            });
+            worker.tell(reply).unwrap();
+        },
+    );
+
+    let (tx, rx) = oneshot::channel();
+    parent.tell(tx).unwrap();
+    let worker = rx.await.unwrap();
+    let (tx, rx) = oneshot::channel();
+    parent.tell(tx).unwrap();
+    let other = rx.await.unwrap();
+
+    assert!(worker.starts_with("/user/parent/$a-"), "{worker}");
+    assert!(other.starts_with("/user/parent/$a-"), "{other}");
+    assert_ne!(worker, other);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(anonymous());
+}
@@ -0,0 +1,77 @@
+use elliot::{ActorSystem, AnyActorRef, AnyMessage, AnyTellError};
+use std::sync::{Arc, Mutex};
+
+async fn any_ref() {
+    let system = ActorSystem::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let seen = Arc::clone(&received);
+    let numbers = system.spawn("numbers", move |n: u32| {
+        seen.lock().unwrap().push(format!("number {n}"));
+        async {}
+    });
+    // this actor opts into messages of any type
+    let seen = Arc::clone(&received);
+    let anything = system.spawn("anything", move |msg: AnyMessage| {
+        let msg = match msg.downcast::<String>() {
+            Ok(s) => format!("string {s}"),
+            Err(msg) => match msg.downcast::<u32>() {
+                Ok(n) => format!("any number {n}"),
+                Err(_) => String::from("unknown"),
+            },
+        };
+        seen.lock().unwrap().push(msg);
+        async {}
+    });
+
+    // refs of different types in one registry
+    let registry: Vec<AnyActorRef> = vec![numbers.to_any(), anything.into()];
+    assert!(registry[0].accepts::<u32>());
+    assert!(!registry[0].accepts::<String>());
+    assert!(registry[1].accepts::<String>());
+    assert_eq!(registry[0].message_type(), "u32");
+
+    for actor in &registry {
+        let _ = actor.tell(Box::new(7_u32));
+        if let Err(rejected) = actor.tell(Box::new(String::from("hi"))) {
+            assert!(matches!(rejected, AnyTellError::WrongType(_)));
+            assert_eq!(*rejected.into_inner().downcast::<String>().unwrap(), "hi");
+        }
+        let _ = actor.tell(Box::new(1.5_f64));
+    }
+
+    // the typed ref can be recovered
+    let typed = registry[0].downcast::<u32>().unwrap();
+    assert!(registry[0].downcast::<String>().is_none());
+    typed.tell(8).unwrap();
+
+    while received.lock().unwrap().len() < 5 {
+        tokio::task::yield_now().await;
+    }
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(
+        received,
+        [
+            "any number 7",
+            "number 7",
+            "number 8",
+            "string hi",
+            "unknown"
+        ]
+    );
+
+    registry[0].stop();
+    typed.wait_for_stop().await;
+    let gone = registry[0].tell(Box::new(9_u32));
+    assert!(matches!(gone, Err(AnyTellError::ActorRefGone(_))));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(any_ref());
+}
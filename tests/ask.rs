@@ -0,0 +1,98 @@
+use elliot::{ask_all, ask_any, ActorSystem, AskError, Behaviors, ReplyTo};
+use std::time::Duration;
+
+struct Query {
+    value: u32,
+    reply: ReplyTo<u32>,
+}
+
+async fn double(query: Query) {
+    let _ = query.reply.reply(query.value * 2);
+}
+
+async fn silent(query: Query) {
+    drop(query);
+}
+
+async fn slow(query: Query) {
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let _ = query.reply.reply(query.value);
+}
+
+async fn stop(_query: Query) -> Behaviors {
+    Behaviors::Stopped
+}
+
+async fn ask() {
+    let system = ActorSystem::new();
+    let doubler = system.spawn("double", double);
+    let silent = system.spawn("silent", silent);
+    let slow = system.spawn("slow", slow);
+    let stopped = system.spawn("stopped", stop);
+    let reply = stopped.ask(|reply| Query { value: 0, reply }).await;
+    assert_eq!(reply, Err(AskError::NoReply));
+    stopped.wait_for_stop().await;
+
+    let reply = doubler.ask(|reply| Query { value: 21, reply }).await;
+    assert_eq!(reply, Ok(42));
+    let reply = silent.ask(|reply| Query { value: 1, reply }).await;
+    assert_eq!(reply, Err(AskError::NoReply));
+    let timeout = Duration::from_millis(10);
+    let reply = slow
+        .ask_timeout(|reply| Query { value: 1, reply }, timeout)
+        .await;
+    assert_eq!(reply, Err(AskError::Timeout));
+
+    let all = [&doubler, &silent, &slow, &stopped];
+    let replies = ask_all(all, |reply| Query { value: 2, reply }, timeout).await;
+    assert_eq!(
+        replies,
+        [
+            Ok(4),
+            Err(AskError::NoReply),
+            Err(AskError::Timeout),
+            Err(AskError::ActorRefGone)
+        ]
+    );
+
+    let timeout = Duration::from_secs(5);
+    let first = ask_any([&slow, &silent], |reply| Query { value: 3, reply }, timeout).await;
+    assert_eq!(first, Ok(3));
+    let none = ask_any([&silent], |reply| Query { value: 3, reply }, timeout).await;
+    assert_eq!(none, Err(AskError::NoReply));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(ask());
+}
+
+#[test]
+fn ask_blocking() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap();
+    let guard = runtime.enter();
+    let system = ActorSystem::new();
+    let doubler = system.spawn("double", double);
+    let silent = system.spawn("silent", silent);
+    let slow = system.spawn("slow", slow);
+    drop(guard);
+
+    // called from a thread that is not driven by the runtime
+    let timeout = Duration::from_secs(5);
+    let reply = doubler.ask_blocking(|reply| Query { value: 21, reply }, timeout);
+    assert_eq!(reply, Ok(42));
+    let reply = silent.ask_blocking(|reply| Query { value: 1, reply }, timeout);
+    assert_eq!(reply, Err(AskError::NoReply));
+    let timeout = Duration::from_millis(10);
+    let reply = slow.ask_blocking(|reply| Query { value: 1, reply }, timeout);
+    assert_eq!(reply, Err(AskError::Timeout));
+}
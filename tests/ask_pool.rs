@@ -0,0 +1,91 @@
+use elliot::{ActorSystem, AskError, AskPool, ReplyTo};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+struct Query {
+    value: u32,
+    reply: ReplyTo<u32>,
+}
+
+async fn ask_pool() {
+    let system = ActorSystem::new();
+    // the actor hands the queries out, so that the test decides when they are answered
+    let (held, mut queries) = mpsc::unbounded_channel();
+    let actor = system.spawn("held", move |query: Query| {
+        let _ = held.send(query);
+        async {}
+    });
+    let pool = AskPool::new(actor, 2).with_max_queued(1);
+
+    let first = tokio::spawn({
+        let pool = pool.clone();
+        async move { pool.ask(|reply| Query { value: 1, reply }).await }
+    });
+    let second = tokio::spawn({
+        let pool = pool.clone();
+        async move { pool.ask(|reply| Query { value: 2, reply }).await }
+    });
+    let q1 = queries.recv().await.unwrap();
+    let q2 = queries.recv().await.unwrap();
+    assert_eq!(pool.in_flight(), 2);
+
+    // the third ask waits for its turn
+    let third = pool.ask(|reply| Query { value: 3, reply });
+    tokio::pin!(third);
+    assert!(tokio::time::timeout(Duration::from_millis(10), &mut third)
+        .await
+        .is_err());
+
+    // the fourth does not fit into the queue
+    let fourth = pool.ask(|reply| Query { value: 4, reply }).await;
+    assert_eq!(fourth, Err(AskError::Overloaded));
+
+    // once an ask is answered, the waiting one is sent
+    q1.reply.reply(q1.value * 10).unwrap();
+    assert_eq!(first.await.unwrap(), Ok(10));
+    let (reply, ()) = tokio::join!(&mut third, async {
+        let q3 = queries.recv().await.unwrap();
+        q3.reply.reply(q3.value * 10).unwrap();
+    });
+    assert_eq!(reply, Ok(30));
+
+    q2.reply.reply(q2.value * 10).unwrap();
+    assert_eq!(second.await.unwrap(), Ok(20));
+    assert_eq!(pool.in_flight(), 0);
+
+    // an ask that gives up while it waits leaves the queue
+    let pool = AskPool::new(pool.actor().clone(), 1).with_max_queued(1);
+    let busy = tokio::spawn({
+        let pool = pool.clone();
+        async move { pool.ask(|reply| Query { value: 5, reply }).await }
+    });
+    let q5 = queries.recv().await.unwrap();
+    let gave_up = pool
+        .ask_timeout(|reply| Query { value: 6, reply }, Duration::from_millis(10))
+        .await;
+    assert_eq!(gave_up, Err(AskError::Timeout));
+    let waiting = pool.ask(|reply| Query { value: 7, reply });
+    tokio::pin!(waiting);
+    assert!(
+        tokio::time::timeout(Duration::from_millis(10), &mut waiting)
+            .await
+            .is_err()
+    );
+    q5.reply.reply(q5.value * 10).unwrap();
+    assert_eq!(busy.await.unwrap(), Ok(50));
+    let (reply, ()) = tokio::join!(&mut waiting, async {
+        let q7 = queries.recv().await.unwrap();
+        q7.reply.reply(q7.value * 10).unwrap();
+    });
+    assert_eq!(reply, Ok(70));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(ask_pool());
+}
@@ -0,0 +1,94 @@
+use elliot::{ActorSystem, Behaviors};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+use tokio::sync::Notify;
+
+async fn bounded() {
+    let system = ActorSystem::new();
+    let received = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&received);
+    let slow = system.spawn_bounded("slow", 2, move |n: usize| {
+        let _ = count.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+            if n == 19 {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    // send waits while the mailbox is full
+    for n in 0..10 {
+        slow.send(n).await.unwrap();
+        let in_mailbox = system
+            .actors()
+            .into_iter()
+            .find(|a| a.path.name() == "slow")
+            .unwrap()
+            .mailbox_len;
+        assert!(in_mailbox <= 2, "{in_mailbox} messages in the mailbox");
+    }
+
+    // tell ignores the bound
+    for n in 10..20 {
+        slow.tell(n).unwrap();
+    }
+    assert!(
+        system
+            .actors()
+            .into_iter()
+            .find(|a| a.path.name() == "slow")
+            .unwrap()
+            .mailbox_len
+            > 2
+    );
+
+    slow.wait_for_stop().await;
+    assert_eq!(received.load(Ordering::SeqCst), 20);
+    assert_eq!(slow.send(20).await.unwrap_err().0, 20);
+
+    // concurrent senders do not overshoot the bound
+    let started = Arc::new(Notify::new());
+    let gate = Arc::new(Notify::new());
+    let (start, wait) = (Arc::clone(&started), Arc::clone(&gate));
+    let blocked = system.spawn_bounded("blocked", 4, move |n: usize| {
+        let (start, wait) = (Arc::clone(&start), Arc::clone(&wait));
+        async move {
+            if n == 0 {
+                start.notify_one();
+                wait.notified().await;
+            }
+        }
+    });
+    blocked.tell(0).unwrap();
+    started.notified().await;
+    let senders = (0..8)
+        .map(|_| {
+            let blocked = blocked.clone();
+            tokio::spawn(async move { (0..50).filter(|_| blocked.try_send(1).is_ok()).count() })
+        })
+        .collect::<Vec<_>>();
+    let mut sent = 0;
+    for sender in senders {
+        sent += sender.await.unwrap();
+    }
+    assert_eq!(sent, 4);
+    gate.notify_one();
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(bounded());
+}
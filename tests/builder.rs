@@ -0,0 +1,98 @@
+use elliot::{ActorRefGone, ActorSystem, Schedule, Supervision};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::{Duration, Instant},
+};
+use tokio::sync::mpsc;
+
+async fn builder(runtime: tokio::runtime::Handle) {
+    let system = ActorSystem::builder()
+        .name("configured")
+        .mailbox_capacity(2)
+        .supervision(Supervision::Resume)
+        .scheduler_resolution(Duration::from_millis(50))
+        .runtime(runtime)
+        .build();
+    assert_eq!(system.name(), "configured");
+    let mut dead_letters = system.dead_letters();
+
+    // actors run on the configured runtime
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let reporter = system.spawn("reporter", move |(): ()| {
+        let _ = tx.send(std::thread::current().name().map(String::from));
+        async {}
+    });
+    reporter.tell(()).unwrap();
+    assert_eq!(rx.recv().await.unwrap().as_deref(), Some("actors"));
+
+    // failing messages are dead letters, the actor keeps going
+    let handled = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&handled);
+    let this = reporter.clone();
+    let flaky = system.spawn("flaky", move |fail: bool| {
+        let _ = count.fetch_add(1, Ordering::SeqCst);
+        let this = this.clone();
+        async move {
+            if fail {
+                Err(ActorRefGone(()))
+            } else {
+                this.tell(())
+            }
+        }
+    });
+    for fail in [false, true, false] {
+        flaky.send(fail).await.unwrap();
+    }
+    assert_eq!(rx.recv().await.unwrap().as_deref(), Some("actors"));
+    assert_eq!(rx.recv().await.unwrap().as_deref(), Some("actors"));
+    let dead_letter = dead_letters.recv().await.unwrap();
+    assert_eq!(dead_letter.recipient.as_str(), "/user/flaky");
+    assert_eq!(handled.load(Ordering::SeqCst), 3);
+
+    // every mailbox is bounded
+    let slow = system.spawn("slow", |(): ()| async {
+        tokio::time::sleep(Duration::from_millis(2)).await;
+    });
+    for _ in 0..10 {
+        slow.send(()).await.unwrap();
+        let info = system
+            .actors()
+            .into_iter()
+            .find(|a| a.path.name() == "slow")
+            .unwrap();
+        assert!(
+            info.mailbox_len <= 2,
+            "{} messages in the mailbox",
+            info.mailbox_len
+        );
+    }
+
+    // schedules are rounded up to the resolution
+    let start = Instant::now();
+    let _schedule = system.scheduler().schedule(
+        "tick",
+        Schedule::FixedDelay(Duration::from_millis(1)),
+        &reporter,
+        (),
+    );
+    let _ = rx.recv().await.unwrap();
+    assert!(start.elapsed() >= Duration::from_millis(50));
+}
+
+#[test]
+fn test() {
+    let actors = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("actors")
+        .enable_time()
+        .build()
+        .unwrap();
+    tokio::runtime::Builder::new_current_thread()
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(builder(actors.handle().clone()));
+}
@@ -0,0 +1,57 @@
+#![cfg(feature = "cancellation")]
+
+use elliot::{ActorSystem, AskError, ReplyTo};
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio_util::sync::CancellationToken;
+
+async fn cancellation() {
+    let system = ActorSystem::new();
+    let (tx, mut abandoned) = mpsc::unbounded_channel();
+
+    // the work takes far longer than the asker is willing to wait
+    let actor = system.spawn("slow", move |mut reply: ReplyTo<u32>| {
+        let tx = tx.clone();
+        async move {
+            tokio::select! {
+                () = tokio::time::sleep(Duration::from_secs(60)) => {
+                    let _ = reply.reply(42);
+                }
+                () = reply.closed() => {
+                    let _ = tx.send(());
+                }
+            }
+        }
+    });
+
+    let token = CancellationToken::new();
+    let asks = (0..3)
+        .map(|_| {
+            let (actor, token) = (actor.clone(), token.clone());
+            tokio::spawn(async move { actor.ask_cancellable(|reply| reply, &token).await })
+        })
+        .collect::<Vec<_>>();
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    token.cancel();
+
+    for ask in asks {
+        assert_eq!(ask.await.unwrap(), Err(AskError::Cancelled));
+    }
+    for _ in 0..3 {
+        abandoned.recv().await.unwrap();
+    }
+
+    // a cancelled token fails new asks right away
+    let reply = actor.ask_cancellable(|reply| reply, &token).await;
+    assert_eq!(reply, Err(AskError::Cancelled));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(cancellation());
+}
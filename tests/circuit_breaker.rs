@@ -0,0 +1,87 @@
+use elliot::{
+    ActorSystem, Behaviors, CircuitBreaker, CircuitBreakerError, CircuitBreakerTransition,
+    CircuitState,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+async fn fail() -> Result<(), &'static str> {
+    Err("boom")
+}
+
+async fn succeed() -> Result<u32, &'static str> {
+    Ok(42)
+}
+
+async fn circuit_breaker() {
+    let system = ActorSystem::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let recorder = Arc::clone(&seen);
+    let subscriber = system.spawn("transitions", move |t: CircuitBreakerTransition| {
+        let seen = Arc::clone(&recorder);
+        async move {
+            seen.lock().unwrap().push((t.from, t.to));
+            if t.to == CircuitState::Closed {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+    system.subscribe(&subscriber);
+
+    let breaker = CircuitBreaker::new("api", 2, Duration::from_millis(20))
+        .with_call_timeout(Duration::from_secs(5))
+        .publish_transitions(&system);
+
+    assert!(matches!(
+        breaker.call(fail()).await,
+        Err(CircuitBreakerError::Failed("boom"))
+    ));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+    assert!(matches!(
+        breaker.call(fail()).await,
+        Err(CircuitBreakerError::Failed(_))
+    ));
+    assert_eq!(breaker.state(), CircuitState::Open);
+    assert!(matches!(
+        breaker.call(succeed()).await,
+        Err(CircuitBreakerError::Open)
+    ));
+
+    tokio::time::sleep(Duration::from_millis(25)).await;
+    assert!(breaker.allow());
+    assert_eq!(breaker.state(), CircuitState::HalfOpen);
+    assert!(!breaker.allow());
+    breaker.failure();
+    assert_eq!(breaker.state(), CircuitState::Open);
+
+    tokio::time::sleep(Duration::from_millis(25)).await;
+    assert!(matches!(breaker.call(succeed()).await, Ok(42)));
+    assert_eq!(breaker.state(), CircuitState::Closed);
+
+    subscriber.wait_for_stop().await;
+    assert_eq!(
+        *seen.lock().unwrap(),
+        [
+            (CircuitState::Closed, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Open),
+            (CircuitState::Open, CircuitState::HalfOpen),
+            (CircuitState::HalfOpen, CircuitState::Closed),
+        ]
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(circuit_breaker());
+}
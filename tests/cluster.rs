@@ -0,0 +1,184 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{
+    ActorSystem, Behaviors, BoxErr, Cluster, ClusterEvent, ClusterSettings, MemberStatus,
+    MessageCodec,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+fn settings(seed: &str) -> ClusterSettings {
+    ClusterSettings::new([seed])
+        .gossip_interval(Duration::from_millis(20))
+        .remove_after(Duration::from_millis(200))
+}
+
+async fn converged(clusters: &[&Cluster], members: usize) {
+    while clusters.iter().any(|cluster| {
+        let view = cluster.members();
+        view.len() != members || view.iter().any(|m| m.status != MemberStatus::Up)
+    }) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+async fn wait_for(events: &Mutex<Vec<ClusterEvent>>, event: &ClusterEvent) {
+    while !events.lock().unwrap().contains(event) {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+async fn cluster() {
+    let first = ActorSystem::new();
+    let events = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&events);
+    let listener = first.spawn("listener", move |event: ClusterEvent| {
+        log.lock().unwrap().push(event);
+        async { Behaviors::Same }
+    });
+    first.subscribe(&listener);
+
+    let seed = first
+        .join_cluster(
+            "127.0.0.1:0",
+            ClusterSettings::new(Vec::<String>::new())
+                .gossip_interval(Duration::from_millis(20))
+                .remove_after(Duration::from_millis(200)),
+        )
+        .await
+        .unwrap();
+    let seed_address = seed.address().to_owned();
+
+    let second = ActorSystem::new();
+    let second = second
+        .join_cluster("127.0.0.1:0", settings(&seed_address))
+        .await
+        .unwrap();
+    let third = ActorSystem::new();
+    let third = third
+        .join_cluster("127.0.0.1:0", settings(&seed_address))
+        .await
+        .unwrap();
+
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        converged(&[&seed, &second, &third], 3),
+    )
+    .await
+    .unwrap();
+
+    let second_address: Arc<str> = second.address().into();
+    let third_address: Arc<str> = third.address().into();
+    wait_for(
+        &events,
+        &ClusterEvent::MemberUp(Arc::clone(&second_address)),
+    )
+    .await;
+    wait_for(&events, &ClusterEvent::MemberUp(Arc::clone(&third_address))).await;
+
+    // a crashed node is detected as unreachable and then removed
+    drop(third);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        wait_for(
+            &events,
+            &ClusterEvent::MemberUnreachable(Arc::clone(&third_address)),
+        )
+        .await;
+        wait_for(
+            &events,
+            &ClusterEvent::MemberRemoved(Arc::clone(&third_address)),
+        )
+        .await;
+    })
+    .await
+    .unwrap();
+    tokio::time::timeout(Duration::from_secs(5), converged(&[&seed, &second], 2))
+        .await
+        .unwrap();
+
+    // a node that leaves is removed right away
+    let before_leave = events.lock().unwrap().len();
+    second.leave();
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        wait_for(
+            &events,
+            &ClusterEvent::MemberRemoved(Arc::clone(&second_address)),
+        ),
+    )
+    .await
+    .unwrap();
+    assert!(!events.lock().unwrap()[before_leave..]
+        .contains(&ClusterEvent::MemberUnreachable(second_address)));
+    assert_eq!(seed.members().len(), 1);
+}
+
+/// Gossip as sent by nodes from before the gossip was tagged with its version.
+struct LegacyGossip(Vec<u8>);
+
+struct Raw;
+
+impl MessageCodec<LegacyGossip> for Raw {
+    fn encode(&self, msg: &LegacyGossip) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.0.clone())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<LegacyGossip, BoxErr> {
+        Ok(LegacyGossip(bytes.to_vec()))
+    }
+}
+
+async fn legacy_gossip() {
+    let seed = ActorSystem::new()
+        .join_cluster("127.0.0.1:0", settings("127.0.0.1:1"))
+        .await
+        .unwrap();
+    let old = ActorSystem::new();
+    old.register_codec(Raw);
+    let gossip = old
+        .remote_ref::<LegacyGossip>(&format!("elliot://{}/system/cluster", seed.address()))
+        .unwrap();
+
+    // one member, with its heartbeat, that is not removed
+    let address = b"127.0.0.1:2";
+    let mut bytes = 1_u32.to_be_bytes().to_vec();
+    bytes.extend_from_slice(&(address.len() as u16).to_be_bytes());
+    bytes.extend_from_slice(address);
+    bytes.extend_from_slice(&1_u64.to_be_bytes());
+    bytes.push(0);
+    gossip.tell(LegacyGossip(bytes)).unwrap();
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while !seed
+            .members()
+            .iter()
+            .any(|member| &*member.address == "127.0.0.1:2")
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(cluster());
+}
+
+#[test]
+fn test_legacy_gossip() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(legacy_gossip());
+}
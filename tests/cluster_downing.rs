@@ -0,0 +1,215 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{
+    Accepted, ActorSystem, BoxErr, Cluster, ClusterSettings, Connection, Downing, Listener,
+    MemberStatus, MessageCodec, Sharding, TcpTransport, Transport,
+};
+use std::{
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
+
+type Io<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+
+/// TCP that can be cut, to partition a node from the others.
+#[derive(Clone, Default)]
+struct Cuttable {
+    cut: Arc<AtomicBool>,
+}
+
+struct CutListener(Box<dyn Listener>, Arc<AtomicBool>);
+
+struct Link(Box<dyn Connection>, Arc<AtomicBool>);
+
+impl Transport for Cuttable {
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>> {
+        Box::pin(async move {
+            if self.cut.load(Ordering::SeqCst) {
+                return Err(io::ErrorKind::ConnectionRefused.into());
+            }
+            let stream = TcpTransport.connect(authority).await?;
+            let stream: Box<dyn Connection> = Box::new(Link(stream, Arc::clone(&self.cut)));
+            Ok(stream)
+        })
+    }
+
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>> {
+        Box::pin(async move {
+            let listener = TcpTransport.bind(addr).await?;
+            let listener: Box<dyn Listener> =
+                Box::new(CutListener(listener, Arc::clone(&self.cut)));
+            Ok(listener)
+        })
+    }
+}
+
+impl Listener for CutListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        self.0.local_addr()
+    }
+
+    fn accept(&mut self) -> Io<'_, Accepted> {
+        Box::pin(async move {
+            loop {
+                let accepted = self.0.accept().await?;
+                if !self.1.load(Ordering::SeqCst) {
+                    return Ok(Accepted::new(Link(
+                        accepted.connection,
+                        Arc::clone(&self.1),
+                    )));
+                }
+            }
+        })
+    }
+}
+
+impl Link {
+    fn check(&self) -> io::Result<()> {
+        if self.1.load(Ordering::SeqCst) {
+            return Err(io::ErrorKind::BrokenPipe.into());
+        }
+        Ok(())
+    }
+}
+
+impl AsyncRead for Link {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<io::Result<()>> {
+        self.check()?;
+        Pin::new(&mut self.0).poll_read(cx, buf)
+    }
+}
+
+impl AsyncWrite for Link {
+    fn poll_write(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &[u8],
+    ) -> Poll<io::Result<usize>> {
+        self.check()?;
+        Pin::new(&mut self.0).poll_write(cx, buf)
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        self.check()?;
+        Pin::new(&mut self.0).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.0).poll_shutdown(cx)
+    }
+}
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn node(seeds: &[&str], downing: Downing) -> (Cluster, Cuttable, ActorSystem) {
+    let system = ActorSystem::new();
+    let transport = Cuttable::default();
+    system.set_transport(transport.clone());
+    let settings = ClusterSettings::new(seeds.iter().copied())
+        .gossip_interval(Duration::from_millis(20))
+        .remove_after(Duration::from_millis(200))
+        .downing(downing);
+    let cluster = system.join_cluster("127.0.0.1:0", settings).await.unwrap();
+    // the members that join later are younger
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    (cluster, transport, system)
+}
+
+async fn three_nodes(downing: Downing) -> Vec<(Cluster, Cuttable, ActorSystem)> {
+    let seed = node(&[], downing).await;
+    let address = seed.0.address().to_owned();
+    let nodes = vec![
+        seed,
+        node(&[&address], downing).await,
+        node(&[&address], downing).await,
+    ];
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while nodes.iter().any(|(cluster, ..)| {
+            let view = cluster.members();
+            view.len() != 3 || view.iter().any(|m| m.status != MemberStatus::Up)
+        }) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    nodes
+}
+
+/// Wait until the partition is resolved, the surviving nodes only see each other.
+async fn resolved(survivors: &[&Cluster], downed: &[&Cluster]) {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while downed.iter().any(|cluster| !cluster.is_down())
+            || survivors
+                .iter()
+                .any(|cluster| cluster.members().len() != survivors.len())
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+}
+
+async fn downing() {
+    // the isolated node is the minority
+    let nodes = three_nodes(Downing::KeepMajority).await;
+    nodes[2].1.cut.store(true, Ordering::SeqCst);
+    resolved(&[&nodes[0].0, &nodes[1].0], &[&nodes[2].0]).await;
+    assert!(!nodes[0].0.is_down() && !nodes[1].0.is_down());
+    assert!(nodes[2].0.members().is_empty());
+
+    // a node that is down has no shards, their messages are dead letters
+    nodes[2].2.register_codec(Utf8);
+    let region = Sharding::new("counters", String::clone, |_| |_: String| async {})
+        .start(&nodes[2].0)
+        .unwrap();
+    let mut dead_letters = nodes[2].2.dead_letters_of::<String>();
+    region.tell("a".to_owned()).unwrap();
+    let dead_letter = tokio::time::timeout(Duration::from_secs(5), dead_letters.recv());
+    assert_eq!(dead_letter.await.unwrap().unwrap().message, "a");
+
+    // the isolated node is the oldest, which survives on its own
+    let nodes = three_nodes(Downing::KeepOldest).await;
+    nodes[0].1.cut.store(true, Ordering::SeqCst);
+    resolved(&[&nodes[0].0], &[&nodes[1].0, &nodes[2].0]).await;
+    assert!(!nodes[0].0.is_down());
+
+    // without a quorum, every side downs itself
+    let nodes = three_nodes(Downing::StaticQuorum(3)).await;
+    nodes[2].1.cut.store(true, Ordering::SeqCst);
+    resolved(&[], &[&nodes[0].0, &nodes[1].0, &nodes[2].0]).await;
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(downing());
+}
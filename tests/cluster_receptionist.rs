@@ -0,0 +1,107 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{
+    ActorSystem, BoxErr, Cluster, ClusterSettings, ListingUpdate, MessageCodec, ServiceKey,
+};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn node(seeds: &[String]) -> (ActorSystem, Cluster) {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let settings = ClusterSettings::new(seeds.to_vec())
+        .gossip_interval(Duration::from_millis(20))
+        .remove_after(Duration::from_millis(200));
+    let cluster = system.join_cluster("127.0.0.1:0", settings).await.unwrap();
+    (system, cluster)
+}
+
+async fn cluster_receptionist() {
+    let (first_system, first) = node(&[]).await;
+    let (second_system, second) = node(&[first.address().to_owned()]).await;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while first.members().len() != 2 || second.members().len() != 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let key = ServiceKey::<String>::new("greeters");
+    let first_receptionist = first.receptionist(&key).unwrap();
+    let second_receptionist = second.receptionist(&key).unwrap();
+    // the other node needs a codec for the messages
+    assert!(first.receptionist(&ServiceKey::<u32>::new("ids")).is_err());
+
+    let (tx, mut greeted) = mpsc::unbounded_channel();
+    let greeter = |tx: mpsc::UnboundedSender<String>| {
+        move |msg: String| {
+            let _ = tx.send(msg);
+            async {}
+        }
+    };
+    let alice = first_system.spawn("alice", greeter(tx.clone()));
+    let bob = second_system.spawn("bob", greeter(tx));
+    first_receptionist.register(&alice);
+    // registering with the local receptionist shares the service as well
+    second_system.receptionist().register(&key, &bob);
+
+    let mut listing = first_receptionist.subscribe();
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while first_receptionist.find().len() != 2 || second_receptionist.find().len() != 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    // remote services are found by their uri and can be told like local ones
+    let bob_uri = format!("elliot://{}/user/bob", second.address());
+    let found = first_receptionist.find();
+    let paths = found.iter().map(|s| s.path().as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, ["/user/alice", bob_uri.as_str()]);
+    found[1].tell("hello".to_owned()).unwrap();
+    let greeting = tokio::time::timeout(Duration::from_secs(5), greeted.recv())
+        .await
+        .unwrap();
+    assert_eq!(greeting.as_deref(), Some("hello"));
+
+    // services of a crashed node are deregistered
+    drop(second);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            match listing.recv().await.unwrap() {
+                ListingUpdate::Deregistered(path) if path.as_str() == bob_uri => break,
+                ListingUpdate::Deregistered(path) => panic!("unexpected deregistration of {path}"),
+                ListingUpdate::Registered(_) => {}
+            }
+        }
+    })
+    .await
+    .unwrap();
+    let found = first_receptionist.find();
+    let paths = found.iter().map(|s| s.path().as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, ["/user/alice"]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(cluster_receptionist());
+}
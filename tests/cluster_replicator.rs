@@ -0,0 +1,243 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{
+    ActorRef, ActorSystem, BoxErr, Cluster, ClusterSettings, Consistency, ConsistencyTimeout,
+    Counter, Crdt, CrdtCodec, LwwMap, MemberStatus, MessageCodec, OrSet, RemoteError, Replicate,
+};
+use std::time::Duration;
+
+const TIMEOUT: Duration = Duration::from_secs(5);
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn node(seeds: &[String]) -> Cluster {
+    let system = ActorSystem::new();
+    system.register_codec::<Counter>(CrdtCodec::new(()));
+    system.register_codec::<OrSet<String>>(CrdtCodec::new(Utf8));
+    system.register_codec::<LwwMap<String, String>>(CrdtCodec::new(Utf8));
+    let settings = ClusterSettings::new(seeds.to_vec())
+        .gossip_interval(Duration::from_millis(20))
+        .remove_after(Duration::from_millis(200));
+    system.join_cluster("127.0.0.1:0", settings).await.unwrap()
+}
+
+async fn three_nodes() -> Vec<Cluster> {
+    let seed = node(&[]).await;
+    let address = [seed.address().to_owned()];
+    let nodes = vec![seed, node(&address).await, node(&address).await];
+    tokio::time::timeout(TIMEOUT, async {
+        while nodes.iter().any(|cluster| {
+            let view = cluster.members();
+            view.len() != 3 || view.iter().any(|m| m.status != MemberStatus::Up)
+        }) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    nodes
+}
+
+async fn get<D: Crdt>(
+    replicator: &ActorRef<Replicate<D>>,
+    key: &str,
+    consistency: Consistency,
+) -> Result<Option<D>, ConsistencyTimeout> {
+    replicator
+        .ask(|reply| Replicate::get(key, consistency, reply))
+        .await
+        .unwrap()
+}
+
+async fn update<D: Crdt>(
+    replicator: &ActorRef<Replicate<D>>,
+    key: &str,
+    consistency: Consistency,
+    modify: impl FnOnce(&mut D, &str) + Send + 'static,
+) -> Result<D, ConsistencyTimeout> {
+    replicator
+        .ask(|reply| Replicate::update(key, consistency, modify, reply))
+        .await
+        .unwrap()
+}
+
+/// Wait until a local read on every replicator satisfies `check`.
+async fn converged<D: Crdt>(
+    replicators: &[ActorRef<Replicate<D>>],
+    key: &str,
+    check: impl Fn(&D) -> bool,
+) {
+    tokio::time::timeout(TIMEOUT, async {
+        for replicator in replicators {
+            while get(replicator, key, Consistency::Local)
+                .await
+                .unwrap()
+                .is_none_or(|value| !check(&value))
+            {
+                tokio::time::sleep(Duration::from_millis(5)).await;
+            }
+        }
+    })
+    .await
+    .unwrap();
+}
+
+async fn replicator() {
+    let nodes = three_nodes().await;
+    let counters = nodes
+        .iter()
+        .map(|cluster| cluster.replicator::<Counter>("counters").unwrap())
+        .collect::<Vec<_>>();
+
+    // local updates on every node converge
+    for (i, counter) in counters.iter().enumerate() {
+        let value = update(counter, "hits", Consistency::Local, move |c, node| {
+            c.increment(node, 10);
+            c.decrement(node, i as u64);
+        })
+        .await
+        .unwrap();
+        assert!(value.value() >= 10 - i as i64);
+    }
+    converged(&counters, "hits", |c| c.value() == 27).await;
+
+    // a majority write and a majority read always overlap
+    let majority = Consistency::Majority(TIMEOUT);
+    let _ = update(&counters[0], "visits", majority, |c, node| {
+        c.increment(node, 1)
+    })
+    .await
+    .unwrap();
+    let visits = get(&counters[1], "visits", majority).await.unwrap();
+    assert_eq!(visits.map(|c| c.value()), Some(1));
+    let all = Consistency::All(TIMEOUT);
+    assert_eq!(get(&counters[2], "unknown", all).await, Ok(None));
+
+    // concurrent adds win over removes
+    let sets = nodes
+        .iter()
+        .map(|cluster| cluster.replicator::<OrSet<String>>("sets").unwrap())
+        .collect::<Vec<_>>();
+    let _ = update(&sets[0], "tags", all, |s, node| {
+        s.add(node, "a".to_owned());
+        s.add(node, "b".to_owned());
+    })
+    .await
+    .unwrap();
+    let _ = update(&sets[1], "tags", Consistency::Local, |s, _| {
+        assert!(s.remove(&"a".to_owned()));
+    })
+    .await
+    .unwrap();
+    let _ = update(&sets[2], "tags", Consistency::Local, |s, node| {
+        s.add(node, "a".to_owned());
+        assert!(s.remove(&"b".to_owned()));
+    })
+    .await
+    .unwrap();
+    converged(&sets, "tags", |s| s.iter().eq(["a"])).await;
+
+    // the last write wins
+    let maps = nodes
+        .iter()
+        .map(|cluster| {
+            cluster
+                .replicator::<LwwMap<String, String>>("maps")
+                .unwrap()
+        })
+        .collect::<Vec<_>>();
+    let _ = update(&maps[0], "config", all, |m, node| {
+        m.insert(node, "mode".to_owned(), "fast".to_owned());
+        m.insert(node, "level".to_owned(), "1".to_owned());
+    })
+    .await
+    .unwrap();
+    let _ = update(&maps[1], "config", Consistency::Local, |m, node| {
+        m.insert(node, "mode".to_owned(), "safe".to_owned());
+        m.remove(node, "level".to_owned());
+    })
+    .await
+    .unwrap();
+    converged(&maps, "config", |m| {
+        m.get(&"mode".to_owned()).map(String::as_str) == Some("safe") && m.len() == 1
+    })
+    .await;
+
+    // without answers from the other replicators, the consistency cannot be reached
+    let mut counters = counters.into_iter();
+    let counter = counters.next().unwrap();
+    drop(counters);
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    let short = Duration::from_millis(100);
+    let read = get(&counter, "hits", Consistency::Majority(short)).await;
+    assert_eq!(read, Err(ConsistencyTimeout(short)));
+    let write = update(&counter, "hits", Consistency::All(short), |c, node| {
+        c.increment(node, 1)
+    })
+    .await;
+    assert_eq!(write, Err(ConsistencyTimeout(short)));
+    // the write is not undone
+    let hits = get(&counter, "hits", Consistency::Local).await.unwrap();
+    assert_eq!(hits.map(|c| c.value()), Some(28));
+
+    // the value type needs a codec
+    let without_codec = nodes[0].replicator::<OrSet<u32>>("numbers");
+    assert!(matches!(without_codec, Err(RemoteError::NoCodec(_))));
+}
+
+/// A replicator that starts late is brought up to date by the digests of the others.
+async fn digests() {
+    let nodes = three_nodes().await;
+    let early = nodes[..2]
+        .iter()
+        .map(|cluster| cluster.replicator::<Counter>("late").unwrap())
+        .collect::<Vec<_>>();
+    for key in ["a", "b"] {
+        let _ = update(&early[0], key, Consistency::Local, |c, node| {
+            c.increment(node, 2)
+        })
+        .await
+        .unwrap();
+    }
+    converged(&early, "b", |c| c.value() == 2).await;
+
+    let late = nodes[2].replicator::<Counter>("late").unwrap();
+    let _ = update(&late, "a", Consistency::Local, |c, node| {
+        c.increment(node, 1)
+    })
+    .await
+    .unwrap();
+    let all = [early, vec![late]].concat();
+    converged(&all, "a", |c| c.value() == 3).await;
+    converged(&all, "b", |c| c.value() == 2).await;
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(replicator());
+}
+
+#[test]
+fn test_digests() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(digests());
+}
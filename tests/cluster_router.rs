@@ -0,0 +1,121 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{
+    ActorSystem, BoxErr, Cluster, ClusterRouter, ClusterSettings, MessageCodec, ServiceKey,
+    Strategy,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn node(seeds: &[String], role: &str) -> (ActorSystem, Cluster) {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let settings = ClusterSettings::new(seeds.to_vec())
+        .gossip_interval(Duration::from_millis(20))
+        .remove_after(Duration::from_millis(200))
+        .roles([role]);
+    let cluster = system.join_cluster("127.0.0.1:0", settings).await.unwrap();
+    (system, cluster)
+}
+
+async fn received(
+    rx: &mut mpsc::UnboundedReceiver<(&'static str, String)>,
+    n: usize,
+) -> Vec<&'static str> {
+    let mut workers = Vec::new();
+    for _ in 0..n {
+        let (worker, _) = tokio::time::timeout(Duration::from_secs(5), rx.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        workers.push(worker);
+    }
+    workers.sort_unstable();
+    workers
+}
+
+async fn cluster_router() {
+    let (first_system, first) = node(&[], "frontend").await;
+    let (second_system, second) = node(&[first.address().to_owned()], "backend").await;
+    let key = ServiceKey::<String>::new("workers");
+    let first_receptionist = first.receptionist(&key).unwrap();
+    let _second_receptionist = second.receptionist(&key).unwrap();
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let worker = |name: &'static str, tx: mpsc::UnboundedSender<(&'static str, String)>| {
+        move |msg: String| {
+            let _ = tx.send((name, msg));
+            async {}
+        }
+    };
+    let alice = first_system.spawn("alice", worker("alice", tx.clone()));
+    let bob = second_system.spawn("bob", worker("bob", tx));
+    first_system.receptionist().register(&key, &alice);
+    second_system.receptionist().register(&key, &bob);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while first_receptionist.find().len() != 2 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    let roles = first
+        .members()
+        .into_iter()
+        .map(|member| member.roles)
+        .collect::<Vec<_>>();
+    assert!(roles.contains(&vec![Arc::from("backend")]));
+
+    // all services are routees
+    let router = ClusterRouter::new(Strategy::RoundRobin).start(&first_receptionist);
+    for i in 0..4 {
+        router.tell(format!("job {i}")).unwrap();
+    }
+    assert_eq!(received(&mut rx, 4).await, ["alice", "alice", "bob", "bob"]);
+
+    // only the services on members with the role are routees
+    let backend = ClusterRouter::new(Strategy::Random)
+        .role("backend")
+        .start(&first_receptionist);
+    for i in 0..4 {
+        backend.tell(format!("job {i}")).unwrap();
+    }
+    assert_eq!(received(&mut rx, 4).await, ["bob"; 4]);
+
+    // the routees of removed members are dropped
+    drop(second);
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while first.members().len() != 1 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    for i in 0..2 {
+        router.tell(format!("job {i}")).unwrap();
+    }
+    assert_eq!(received(&mut rx, 2).await, ["alice", "alice"]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(cluster_router());
+}
@@ -0,0 +1,164 @@
+use elliot::{
+    ActorSystem, BoxErr, CodecJournal, InMemoryJournal, Journal, MessageCodec, VersionError,
+    VersionedCodec,
+};
+
+struct Decimal;
+
+impl MessageCodec<u32> for Decimal {
+    fn encode(&self, msg: &u32) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.to_string().into_bytes())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<u32, BoxErr> {
+        Ok(std::str::from_utf8(bytes)?.parse()?)
+    }
+}
+
+fn roundtrip<T, C: MessageCodec<T> + ?Sized>(codec: &C, msg: &T) -> T {
+    codec.decode(&codec.encode(msg).unwrap()).unwrap()
+}
+
+#[test]
+fn registration() {
+    let system = ActorSystem::new();
+    assert!(system.codec::<u32>().is_none());
+
+    system.register_codec(Decimal);
+    let codec = system.codec::<u32>().unwrap();
+    assert_eq!(codec.encode(&42).unwrap(), b"42");
+    assert_eq!(roundtrip(&*codec, &42), 42);
+    assert!(codec.decode(b"forty-two").is_err());
+
+    // codecs are registered per message type
+    assert!(system.codec::<u64>().is_none());
+}
+
+#[derive(Debug, PartialEq)]
+struct Celsius(f64);
+
+struct Float;
+
+impl MessageCodec<Celsius> for Float {
+    fn encode(&self, msg: &Celsius) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.0.to_be_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<Celsius, BoxErr> {
+        Ok(Celsius(f64::from_be_bytes(bytes.try_into()?)))
+    }
+}
+
+#[test]
+fn versioned() {
+    // version 1 had whole degrees only
+    let v1 = VersionedCodec::new("temperature", 1, Decimal).unwrap();
+    let v2 = VersionedCodec::new("temperature", 2, Float)
+        .unwrap()
+        .upcaster(1, Decimal, |degrees: u32| Celsius(f64::from(degrees)));
+
+    let old = v1.encode(&21).unwrap();
+    assert_eq!(old, b"\0\x0btemperature\0\0\0\x0121");
+    assert_eq!(v2.decode(&old).unwrap(), Celsius(21.0));
+    assert_eq!(roundtrip(&v2, &Celsius(21.5)), Celsius(21.5));
+
+    // older nodes cannot read newer messages
+    let new = v2.encode(&Celsius(21.5)).unwrap();
+    let error = v1.decode(&new).unwrap_err();
+    assert_eq!(
+        error.downcast_ref::<VersionError>(),
+        Some(&VersionError::UnknownVersion {
+            name: "temperature".into(),
+            version: 2
+        })
+    );
+    let other = VersionedCodec::new("pressure", 1, Decimal).unwrap();
+    assert!(matches!(
+        other.decode(&old).unwrap_err().downcast_ref(),
+        Some(VersionError::WrongType { .. })
+    ));
+    assert!(matches!(
+        v2.decode(b"21").unwrap_err().downcast_ref(),
+        Some(VersionError::Untagged)
+    ));
+
+    // the name has to fit into the tag
+    let name = "t".repeat(usize::from(u16::MAX) + 1);
+    assert_eq!(
+        VersionedCodec::new(name, 1, Decimal).unwrap_err(),
+        VersionError::NameTooLong(65536)
+    );
+}
+
+#[test]
+fn persisted_events_are_upcast() {
+    let bytes = InMemoryJournal::<Vec<u8>>::new();
+    let v1 = CodecJournal::new(
+        bytes.clone(),
+        VersionedCodec::new("temperature", 1, Decimal).unwrap(),
+    );
+    v1.append("sensor", 1, &[18, 19]).unwrap();
+
+    let v2 = CodecJournal::new(
+        bytes,
+        VersionedCodec::new("temperature", 2, Float)
+            .unwrap()
+            .upcaster(1, Decimal, |degrees: u32| Celsius(f64::from(degrees))),
+    );
+    v2.append("sensor", 3, &[Celsius(19.5)]).unwrap();
+    assert_eq!(
+        v2.replay("sensor", 2).unwrap(),
+        [(2, Celsius(19.0)), (3, Celsius(19.5))]
+    );
+}
+
+#[cfg(any(feature = "json", feature = "bincode"))]
+#[derive(Debug, PartialEq, serde::Serialize, serde::Deserialize)]
+struct Order {
+    id: u64,
+    items: Vec<String>,
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn json() {
+    let order = Order {
+        id: 1,
+        items: vec!["tea".into()],
+    };
+    let codec = elliot::JsonCodec;
+    assert_eq!(
+        MessageCodec::<Order>::encode(&codec, &order).unwrap(),
+        br#"{"id":1,"items":["tea"]}"#
+    );
+    assert_eq!(roundtrip(&codec, &order), order);
+}
+
+#[cfg(feature = "bincode")]
+#[test]
+fn bincode() {
+    let order = Order {
+        id: 1,
+        items: vec!["tea".into(), "cake".into()],
+    };
+    assert_eq!(roundtrip(&elliot::BincodeCodec, &order), order);
+}
+
+#[cfg(feature = "prost")]
+#[derive(Clone, PartialEq, prost::Message)]
+struct Ping {
+    #[prost(uint32, tag = "1")]
+    seq: u32,
+    #[prost(string, tag = "2")]
+    from: String,
+}
+
+#[cfg(feature = "prost")]
+#[test]
+fn prost() {
+    let ping = Ping {
+        seq: 7,
+        from: "node-1".into(),
+    };
+    assert_eq!(roundtrip(&elliot::ProstCodec, &ping), ping);
+}
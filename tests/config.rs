@@ -0,0 +1,99 @@
+#![cfg(feature = "config")]
+
+use elliot::{ActorSystem, ActorSystemConfig, ConfigError, Supervision, UnknownDispatcher};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+const CONFIG: &str = r#"
+name = "orders"
+mailbox_capacity = 1000
+supervision = "resume"
+scheduler_resolution_ms = 10
+
+[remote]
+bind = "127.0.0.1:0"
+
+[actors."/user/importer"]
+mailbox_capacity = 2
+dispatcher = "blocking"
+"#;
+
+async fn config(blocking: tokio::runtime::Handle) {
+    let path = std::env::temp_dir().join(format!("elliot-{}.toml", std::process::id()));
+    std::fs::write(&path, CONFIG).unwrap();
+    let config = ActorSystemConfig::from_path(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+
+    assert_eq!(config.name.as_deref(), Some("orders"));
+    assert_eq!(config.supervision, Some(Supervision::Resume));
+    assert_eq!(config.dead_letter_capacity, None);
+    assert_eq!(config.remote.bind.as_deref(), Some("127.0.0.1:0"));
+    let importer = &config.actors["/user/importer"];
+    assert_eq!(importer.mailbox_capacity, Some(2));
+    assert_eq!(importer.dispatcher.as_deref(), Some("blocking"));
+
+    // the dispatchers must be registered
+    let unregistered = ActorSystem::builder().config(config.clone()).try_build();
+    assert_eq!(
+        unregistered.err(),
+        Some(UnknownDispatcher {
+            dispatcher: "blocking".into(),
+            path: "/user/importer".into(),
+        })
+    );
+
+    let system = ActorSystem::builder()
+        .config(config)
+        .dispatcher("blocking", blocking)
+        .build();
+    assert_eq!(system.name(), "orders");
+    #[cfg(feature = "remote")]
+    {
+        let bound = system.bind_configured().await.unwrap().unwrap();
+        assert!(bound.ip().is_loopback());
+        assert_ne!(bound.port(), 0);
+    }
+
+    // the importer runs on its dispatcher with its own mailbox bound
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let importer = system.spawn("importer", move |(): ()| {
+        let _ = tx.send(std::thread::current().name().map(String::from));
+        async {
+            tokio::time::sleep(Duration::from_millis(2)).await;
+        }
+    });
+    for _ in 0..10 {
+        importer.send(()).await.unwrap();
+        assert!(
+            system
+                .actors()
+                .into_iter()
+                .find(|a| a.path.name() == "importer")
+                .unwrap()
+                .mailbox_len
+                <= 2
+        );
+    }
+    assert_eq!(rx.recv().await.unwrap().as_deref(), Some("blocking"));
+
+    let unknown = "mailbox_size = 10".parse::<ActorSystemConfig>();
+    assert!(matches!(unknown, Err(ConfigError::Parse(_))));
+    let missing = ActorSystemConfig::from_path("does/not/exist.toml");
+    assert!(matches!(missing, Err(ConfigError::Io(_))));
+}
+
+#[test]
+fn test() {
+    let blocking = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("blocking")
+        .enable_time()
+        .build()
+        .unwrap();
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(config(blocking.handle().clone()));
+}
@@ -0,0 +1,53 @@
+use elliot::{ActorSystem, Behaviors};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+async fn conflate() {
+    let system = ActorSystem::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let recorder = Arc::clone(&seen);
+    let actor = system.spawn("recorder", move |msg: u32| {
+        let seen = Arc::clone(&recorder);
+        async move {
+            seen.lock().unwrap().push(msg);
+            if msg == 0 {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    let latest = actor.conflated(Duration::from_millis(20));
+    for msg in 1..=5 {
+        let _ = latest.tell(msg);
+    }
+
+    let summed = actor.conflated_with(Duration::from_millis(20), |a, b| a + b);
+    for msg in 10..=12 {
+        let _ = summed.tell(msg);
+    }
+
+    while seen.lock().unwrap().len() < 2 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    let _ = actor.tell(0);
+    actor.wait_for_stop().await;
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_unstable();
+    assert_eq!(seen, [0, 5, 33]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(conflate());
+}
@@ -0,0 +1,46 @@
+#![cfg(feature = "console")]
+
+use elliot::{ActorSystem, Behaviors};
+use std::sync::{Arc, Mutex};
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+async fn console() {
+    let system = ActorSystem::new();
+    let (output, written) = tokio::io::duplex(64);
+    let mut written = BufReader::new(written).lines();
+    let stdout = system.spawn_writer::<usize, _, _>("stdout", output);
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&received);
+    let repl = system.spawn("repl", move |line: String| {
+        log.lock().unwrap().push(line.clone());
+        let behavior = if line == "quit" {
+            Behaviors::Stopped
+        } else {
+            stdout.tell(line.len()).unwrap();
+            Behaviors::Same
+        };
+        async move { behavior }
+    });
+
+    let (mut input, lines) = tokio::io::duplex(64);
+    system.spawn_lines(BufReader::new(lines), &repl);
+    drop(repl);
+    input.write_all(b"one\nthree\r\n").await.unwrap();
+    assert_eq!(written.next_line().await.unwrap().as_deref(), Some("3"));
+    assert_eq!(written.next_line().await.unwrap().as_deref(), Some("5"));
+
+    // the input keeps the actor alive until it stops by itself
+    input.write_all(b"quit\nignored\n").await.unwrap();
+    assert_eq!(written.next_line().await.unwrap(), None);
+    assert_eq!(*received.lock().unwrap(), ["one", "three", "quit"]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(console());
+}
@@ -0,0 +1,38 @@
+use elliot::{ActorContext, ActorSystem};
+use tokio::sync::oneshot;
+
+type Echo = (oneshot::Receiver<()>, oneshot::Sender<bool>);
+
+async fn context_ref() {
+    let system = ActorSystem::new();
+    let echo = system.spawn(
+        "echo",
+        |ctx: ActorContext<Echo>, (gate, reply): Echo| async move {
+            let _ = gate.await;
+            let _ = reply.send(ctx.this().is_alive());
+        },
+    );
+    let weak = echo.downgrade();
+
+    // the waiting messages can still reach the actor through its context
+    let (open, gate) = oneshot::channel();
+    let (reply, alive) = oneshot::channel();
+    echo.tell((gate, reply)).unwrap();
+    drop(echo);
+    open.send(()).unwrap();
+    assert!(alive.await.unwrap());
+
+    // once they are handled, the actor stops
+    while weak.upgrade().is_some() {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(context_ref());
+}
@@ -0,0 +1,71 @@
+use elliot::{ActorCrashed, ActorSystem, Behavior, LifecycleEvent, LifecycleEvents};
+use std::{io, sync::Arc};
+
+#[derive(Debug)]
+enum Job {
+    Work(u32),
+    Fail,
+}
+
+async fn job(job: Job) -> io::Result<()> {
+    match job {
+        Job::Work(n) => {
+            assert!(n > 0);
+            Ok(())
+        }
+        Job::Fail => Err(io::Error::other("boom")),
+    }
+}
+
+async fn crashed(events: &mut LifecycleEvents, name: &str) -> ActorCrashed {
+    loop {
+        match events.recv().await {
+            Some(LifecycleEvent::Crashed(e)) if e.path.name() == name => return e,
+            Some(_) => {}
+            None => panic!("no crash event"),
+        }
+    }
+}
+
+fn strings(messages: &[Arc<str>]) -> Vec<&str> {
+    messages.iter().map(|m| &**m).collect()
+}
+
+async fn crash_dump() {
+    let system = ActorSystem::builder().crash_dump(2).build();
+    let mut events = system.lifecycle_events();
+
+    let named = system.spawn("named", job);
+    let debugged = system.spawn("debugged", job.debug_messages());
+    for actor in [&named, &debugged] {
+        actor.tell(Job::Work(1)).unwrap();
+        actor.tell(Job::Work(2)).unwrap();
+        actor.tell(Job::Fail).unwrap();
+    }
+
+    // only the last two messages are kept
+    let e = crashed(&mut events, "named").await;
+    let name = std::any::type_name::<Job>();
+    assert_eq!(strings(&e.recent_messages), [name, name]);
+    let e = crashed(&mut events, "debugged").await;
+    assert_eq!(strings(&e.recent_messages), ["Work(2)", "Fail"]);
+
+    // nothing is kept by default
+    let system = ActorSystem::new();
+    let mut events = system.lifecycle_events();
+    let actor = system.spawn("default", job.debug_messages());
+    actor.tell(Job::Fail).unwrap();
+    assert!(crashed(&mut events, "default")
+        .await
+        .recent_messages
+        .is_empty());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(crash_dump());
+}
@@ -0,0 +1,57 @@
+use elliot::{ActorSystem, Behaviors};
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+async fn dead_letters() {
+    let system = ActorSystem::new();
+    let mut jobs = system.dead_letters_of::<u32>();
+    let mut later = system.dead_letters_of::<u32>();
+    let mut untyped = system.dead_letters();
+
+    // the actor handles only its first message
+    let once = system.spawn("once", |_: u32| async { Behaviors::Empty });
+    once.tell(1).unwrap();
+    once.tell(2).unwrap();
+    let dead_letter = jobs.recv().await.unwrap();
+    assert_eq!(dead_letter.recipient, *once.path());
+    assert_eq!(dead_letter.message, 2);
+    assert_eq!(untyped.recv().await.unwrap().recipient, *once.path());
+
+    // messages that wait behind a stop request are handed back
+    let (started, wait) = oneshot::channel();
+    let (release, released) = oneshot::channel::<()>();
+    let gate = Mutex::new(Some((started, released)));
+    let stopped = system.spawn("stopped", move |_: u32| {
+        let gate = gate.lock().unwrap().take();
+        async move {
+            if let Some((started, released)) = gate {
+                let _ = started.send(());
+                let _ = released.await;
+            }
+        }
+    });
+    stopped.tell(10).unwrap();
+    wait.await.unwrap();
+    stopped.tell(11).unwrap();
+    stopped.tell(12).unwrap();
+    stopped.stop();
+    release.send(()).unwrap();
+    assert_eq!(jobs.recv().await.unwrap().message, 11);
+    assert_eq!(jobs.recv().await.unwrap().message, 12);
+
+    // every message goes to one subscriber, the earliest one
+    drop(jobs);
+    let once = system.spawn("again", |_: u32| async { Behaviors::Empty });
+    once.tell(20).unwrap();
+    once.tell(21).unwrap();
+    assert_eq!(later.recv().await.unwrap().message, 21);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(dead_letters());
+}
@@ -0,0 +1,51 @@
+use elliot::{ActorContext, ActorSystem, Behaviors};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+async fn detached() {
+    let system = ActorSystem::new();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // a ticker that drives itself, nobody else holds a ref to it
+    let ticker = system.spawn_detached("ticker", move |ctx: ActorContext<u32>, n: u32| {
+        let tx = tx.clone();
+        async move {
+            let _ = tx.send(n);
+            if n == 3 {
+                return Behaviors::Stopped;
+            }
+            let _ = ctx.this().tell_after(n + 1, Duration::from_millis(1));
+            Behaviors::Same
+        }
+    });
+    ticker.tell(1).unwrap();
+    let weak = ticker.downgrade();
+    drop(ticker);
+
+    for n in 1..=3 {
+        assert_eq!(rx.recv().await, Some(n));
+    }
+    assert_eq!(rx.recv().await, None);
+    while weak.upgrade().is_some() {
+        tokio::task::yield_now().await;
+    }
+
+    // by default, the actor stops once its refs are gone
+    let counted = system.spawn("counted", |(): ()| async {});
+    let weak = counted.downgrade();
+    drop(counted);
+    while system.find::<()>("counted").is_some() {
+        tokio::task::yield_now().await;
+    }
+    assert!(weak.upgrade().is_none());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(detached());
+}
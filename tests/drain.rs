@@ -0,0 +1,62 @@
+use elliot::{ActorRef, ActorSystem};
+use std::sync::Mutex;
+use tokio::sync::{mpsc, oneshot};
+
+/// An actor that blocks on its first message until the returned sender is used.
+async fn blocked(system: &ActorSystem, name: &str) -> (ActorRef<u32>, oneshot::Sender<()>) {
+    let (started, wait) = oneshot::channel();
+    let (release, released) = oneshot::channel::<()>();
+    let gate = Mutex::new(Some((started, released)));
+    let actor = system.spawn(name, move |_: u32| {
+        let gate = gate.lock().unwrap().take();
+        async move {
+            if let Some((started, released)) = gate {
+                let _ = started.send(());
+                let _ = released.await;
+            }
+        }
+    });
+    actor.tell(0).unwrap();
+    wait.await.unwrap();
+    (actor, release)
+}
+
+async fn drain() {
+    let system = ActorSystem::new();
+
+    let (actor, release) = blocked(&system, "drained").await;
+    actor.tell(1).unwrap();
+    actor.tell(2).unwrap();
+    let drained = actor.drain();
+    release.send(()).unwrap();
+    assert_eq!(drained.await, [1, 2]);
+    actor.wait_for_stop().await;
+    assert!(actor.tell(3).is_err());
+    assert!(actor.drain().await.is_empty());
+
+    // a new version of the actor takes over the waiting messages in order
+    let (old, release) = blocked(&system, "v1").await;
+    for n in 1..=3 {
+        old.tell(n).unwrap();
+    }
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let new = system.spawn("v2", move |n: u32| {
+        let _ = tx.send(n);
+        async {}
+    });
+    let handed_over = old.hand_over(&new);
+    release.send(()).unwrap();
+    assert!(handed_over.await.is_ok());
+    for n in 1..=3 {
+        assert_eq!(rx.recv().await, Some(n));
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(drain());
+}
@@ -0,0 +1,111 @@
+use elliot::{ActorSystem, Behaviors, EntityManager};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[derive(Debug)]
+struct Msg {
+    user: &'static str,
+    text: &'static str,
+}
+
+async fn entity_manager() {
+    let system = ActorSystem::new();
+    let spawned = Arc::new(Mutex::new(Vec::new()));
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let spawned_log = Arc::clone(&spawned);
+    let received_log = Arc::clone(&received);
+    let users = EntityManager::new(
+        |msg: &Msg| msg.user.to_owned(),
+        move |user: &String| {
+            spawned_log.lock().unwrap().push(user.clone());
+            let received = Arc::clone(&received_log);
+            move |msg: Msg| {
+                received.lock().unwrap().push((msg.user, msg.text));
+                let stop = msg.text == "bye";
+                async move {
+                    if stop {
+                        Behaviors::Stopped
+                    } else {
+                        Behaviors::Same
+                    }
+                }
+            }
+        },
+    )
+    .passivate_after(Duration::from_millis(50));
+    let users = system.spawn("users", users);
+
+    for (user, text) in [("alice", "hi"), ("bob", "hi"), ("alice", "hello")] {
+        users.tell(Msg { user, text }).unwrap();
+    }
+    while received.lock().unwrap().len() < 3 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(*spawned.lock().unwrap(), ["alice", "bob"]);
+    let mut children = system
+        .actors()
+        .into_iter()
+        .map(|actor| actor.path.as_str().to_owned())
+        .filter(|path| path.starts_with("/user/users/"))
+        .collect::<Vec<_>>();
+    children.sort();
+    assert_eq!(children, ["/user/users/alice", "/user/users/bob"]);
+
+    // idle entities are passivated and spawned again on the next message
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    users
+        .tell(Msg {
+            user: "bob",
+            text: "back",
+        })
+        .unwrap();
+    while spawned.lock().unwrap().len() < 3 {
+        tokio::task::yield_now().await;
+    }
+
+    // an entity that stopped by itself is spawned again as well
+    users
+        .tell(Msg {
+            user: "bob",
+            text: "bye",
+        })
+        .unwrap();
+    while received.lock().unwrap().len() < 5 {
+        tokio::task::yield_now().await;
+    }
+    tokio::time::sleep(Duration::from_millis(10)).await;
+    users
+        .tell(Msg {
+            user: "bob",
+            text: "again",
+        })
+        .unwrap();
+    while received.lock().unwrap().len() < 6 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(*spawned.lock().unwrap(), ["alice", "bob", "bob", "bob"]);
+    let messages = |user| {
+        received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(u, _)| *u == user)
+            .map(|(_, text)| *text)
+            .collect::<Vec<_>>()
+    };
+    assert_eq!(messages("alice"), ["hi", "hello"]);
+    assert_eq!(messages("bob"), ["hi", "back", "bye", "again"]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(entity_manager());
+}
@@ -0,0 +1,85 @@
+use elliot::{ActorSystem, FailureDetector, PhiAccrualFailureDetector, ReplyTo};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+use tokio::time::Instant;
+
+enum Monitor {
+    Heartbeat(Instant),
+    Available(Instant, ReplyTo<bool>),
+}
+
+/// Any detector can watch the heartbeats of an external system.
+fn monitor(
+    detector: impl FailureDetector + 'static,
+) -> impl Fn(Monitor) -> std::future::Ready<()> + Clone {
+    let detector = Arc::new(Mutex::new(Box::new(detector) as Box<dyn FailureDetector>));
+    move |msg| {
+        let mut detector = detector.lock().unwrap();
+        match msg {
+            Monitor::Heartbeat(at) => detector.heartbeat(at),
+            Monitor::Available(at, reply) => {
+                let _ = reply.reply(detector.is_available(at));
+            }
+        }
+        std::future::ready(())
+    }
+}
+
+async fn failure_detector() {
+    let system = ActorSystem::new();
+    let detector = PhiAccrualFailureDetector::new(Duration::from_millis(100), Duration::ZERO);
+    let monitor = system.spawn("monitor", monitor(detector));
+
+    let start = Instant::now();
+    // nothing is known before the first heartbeat
+    assert!(monitor
+        .ask(|reply| Monitor::Available(start, reply))
+        .await
+        .unwrap());
+    for beat in 0..10 {
+        let at = start + Duration::from_millis(100) * beat;
+        monitor.tell(Monitor::Heartbeat(at)).unwrap();
+    }
+    let last = start + Duration::from_millis(900);
+    let available = |after| monitor.ask(move |reply| Monitor::Available(last + after, reply));
+    assert!(available(Duration::from_millis(100)).await.unwrap());
+    assert!(!available(Duration::from_millis(300)).await.unwrap());
+    // a heartbeat brings the system back
+    monitor
+        .tell(Monitor::Heartbeat(last + Duration::from_millis(400)))
+        .unwrap();
+    assert!(available(Duration::from_millis(450)).await.unwrap());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(failure_detector());
+}
+
+#[test]
+fn phi_accrual() {
+    let mut detector = PhiAccrualFailureDetector::new(Duration::from_millis(100), Duration::ZERO);
+    let start = Instant::now();
+    assert_eq!(detector.phi(start), 0.0);
+    for beat in 0..10 {
+        detector.heartbeat(start + Duration::from_millis(100) * beat);
+    }
+    let last = start + Duration::from_millis(900);
+    assert!(detector.phi(last + Duration::from_millis(50)) < 1.0);
+    assert!(detector.is_available(last + Duration::from_millis(100), 8.0));
+    assert!(!detector.is_available(last + Duration::from_millis(300), 8.0));
+    assert!(!detector.is_available(last + Duration::from_millis(150), 1.0));
+    // as a failure detector, it uses the threshold it was built with
+    let detector = detector.threshold(1.0);
+    assert!(!FailureDetector::is_available(
+        &detector,
+        last + Duration::from_millis(150)
+    ));
+}
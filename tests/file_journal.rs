@@ -0,0 +1,149 @@
+#![cfg(feature = "file-journal")]
+
+use elliot::{
+    ActorSystem, EventSourced, FileJournal, Journal, ReadJournal, ReplyTo, TaggedJournal,
+};
+use serde::{Deserialize, Serialize};
+use std::{fs::OpenOptions, io::Write};
+
+#[derive(Debug)]
+enum Command {
+    Add(String),
+    Items(ReplyTo<Vec<String>>),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+enum Event {
+    Added(String),
+}
+
+fn cart(journal: &FileJournal) -> EventSourced<Command, Event, Vec<String>> {
+    EventSourced::new(
+        "cart-1",
+        journal.clone(),
+        |items: &Vec<String>, command| match command {
+            Command::Add(item) => vec![Event::Added(item)],
+            Command::Items(reply) => {
+                let _ = reply.reply(items.clone());
+                vec![]
+            }
+        },
+        |items: &mut Vec<String>, Event::Added(item)| items.push(item.clone()),
+    )
+}
+
+async fn file_journal() {
+    let path = std::env::temp_dir().join(format!("elliot-journal-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let system = ActorSystem::new();
+    let journal = FileJournal::open(&path).unwrap().sync_every(2);
+    let first = system.spawn("cart", cart(&journal));
+    let _ = first.tell(Command::Add("apples".into()));
+    let _ = first.tell(Command::Add("pears".into()));
+    assert_eq!(
+        first.ask(Command::Items).await.unwrap(),
+        ["apples", "pears"]
+    );
+    first.stop();
+    first.wait_for_stop().await;
+    drop(first);
+    drop(journal);
+
+    // a crash in the middle of a write leaves a torn record behind
+    let mut file = OpenOptions::new().append(true).open(&path).unwrap();
+    file.write_all(br#"{"id":"cart-1","seq":3,"ev"#).unwrap();
+    drop(file);
+
+    // a new process recovers from the file
+    let journal = FileJournal::open(&path).unwrap();
+    assert_eq!(journal.highest_sequence_nr("cart-1"), 2);
+    let second = system.spawn("cart", cart(&journal));
+    let _ = second.tell(Command::Add("plums".into()));
+    assert_eq!(
+        second.ask(Command::Items).await.unwrap(),
+        ["apples", "pears", "plums"]
+    );
+
+    let events: Vec<(u64, Event)> = journal.replay("cart-1", 3).unwrap();
+    assert_eq!(events, [(3, Event::Added("plums".into()))]);
+    assert!(Journal::append(&journal, "cart-1", 3, &[Event::Added("figs".into())]).is_err());
+
+    // deleting rewrites the file, the last event is kept so that the sequence numbers continue
+    Journal::<Event>::delete_to(&journal, "cart-1", 10).unwrap();
+    let events: Vec<(u64, Event)> = journal.replay("cart-1", 1).unwrap();
+    assert_eq!(events, [(3, Event::Added("plums".into()))]);
+    Journal::append(&journal, "cart-1", 4, &[Event::Added("figs".into())]).unwrap();
+    drop(second);
+    drop(journal);
+    let journal = FileJournal::open(&path).unwrap();
+    assert_eq!(journal.highest_sequence_nr("cart-1"), 4);
+
+    let _ = std::fs::remove_file(&path);
+}
+
+async fn tags() {
+    let path = std::env::temp_dir().join(format!("elliot-tags-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let tagged = |journal| {
+        TaggedJournal::new(journal, |Event::Added(item): &Event| {
+            vec![format!("{}-items", item.len())]
+        })
+    };
+    let journal = tagged(FileJournal::open(&path).unwrap());
+    let added = |item: &str| Event::Added(item.into());
+    journal
+        .append("cart-1", 1, &[added("figs"), added("apples")])
+        .unwrap();
+    journal.append("cart-2", 1, &[added("kiwi")]).unwrap();
+
+    let mut short = journal.events_by_tag("4-items").unwrap();
+    let mut envelope = short.recv().await.unwrap();
+    assert_eq!((envelope.offset, &*envelope.persistence_id), (1, "cart-1"));
+    envelope = short.recv().await.unwrap();
+    assert_eq!((envelope.offset, envelope.event), (3, added("kiwi")));
+
+    // appended events are followed live
+    journal.append("cart-2", 2, &[added("plum")]).unwrap();
+    envelope = short.recv().await.unwrap();
+    assert_eq!((envelope.offset, envelope.sequence_nr), (4, 2));
+
+    // the index is rebuilt from the file
+    drop(short);
+    drop(journal);
+    let journal = tagged(FileJournal::open(&path).unwrap());
+    journal.append("cart-1", 3, &[added("pear")]).unwrap();
+    let mut resumed = journal.events_by_tag_from("4-items", 3).unwrap();
+    assert_eq!(resumed.recv().await.unwrap().event, added("plum"));
+    assert_eq!(resumed.recv().await.unwrap().offset, 5);
+    let mut long = journal.events_by_tag("6-items").unwrap();
+    assert_eq!(long.recv().await.unwrap().event, added("apples"));
+
+    // and after deleting events
+    journal.delete_to("cart-1", 2).unwrap();
+    let mut remaining = journal.events_by_tag("4-items").unwrap();
+    for offset in [3, 4, 5] {
+        assert_eq!(remaining.recv().await.unwrap().offset, offset);
+    }
+
+    let _ = std::fs::remove_file(&path);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(file_journal());
+}
+
+#[test]
+fn test_tags() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(tags());
+}
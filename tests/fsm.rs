@@ -0,0 +1,76 @@
+use elliot::{ActorSystem, Fsm, Transition};
+use std::sync::{Arc, Mutex};
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Light {
+    Red,
+    Green,
+    Yellow,
+}
+
+#[derive(Debug)]
+enum Signal {
+    Next,
+    Hold,
+    Off,
+}
+
+async fn fsm() {
+    let system = ActorSystem::new();
+    let log = Arc::new(Mutex::new(Vec::new()));
+
+    let record = |entry: &'static str| {
+        let log = Arc::clone(&log);
+        move || log.lock().unwrap().push(entry)
+    };
+    let next = |state: Light| {
+        move |msg: Signal| match msg {
+            Signal::Next => Transition::To(state),
+            Signal::Hold => Transition::Stay,
+            Signal::Off => Transition::Stop,
+        }
+    };
+
+    let light = Fsm::new(Light::Red)
+        .when(Light::Red, next(Light::Green))
+        .when(Light::Green, next(Light::Yellow))
+        .when(Light::Yellow, next(Light::Red))
+        .on_enter(Light::Red, record("enter red"))
+        .on_exit(Light::Red, record("exit red"))
+        .on_enter(Light::Green, record("enter green"))
+        .on_exit(Light::Green, record("exit green"))
+        .on_enter(Light::Yellow, record("enter yellow"));
+    let light = system.spawn("light", light);
+
+    for signal in [
+        Signal::Next,
+        Signal::Hold,
+        Signal::Next,
+        Signal::Next,
+        Signal::Off,
+    ] {
+        let _ = light.tell(signal);
+    }
+    light.wait_for_stop().await;
+
+    assert_eq!(
+        *log.lock().unwrap(),
+        [
+            "exit red",
+            "enter green",
+            "exit green",
+            "enter yellow",
+            "enter red",
+            "exit red"
+        ]
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(fsm());
+}
@@ -0,0 +1,53 @@
+#![cfg(feature = "grpc")]
+
+use elliot::{ActorSystem, BoxErr, GrpcTransport, MessageCodec};
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn grpc() {
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    server.set_transport(GrpcTransport);
+    let addr = server.bind("127.0.0.1:0").await.unwrap();
+
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let inbox = server.spawn("inbox", move |msg: String| {
+        let _ = tx.send(msg);
+        async {}
+    });
+    server.expose(&inbox).unwrap();
+
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_transport(GrpcTransport);
+    let remote = client
+        .remote_ref::<String>(&format!("elliot://{addr}/user/inbox"))
+        .unwrap();
+    remote.tell("over grpc".to_owned()).unwrap();
+    // larger than one chunk of the call
+    let large = "a".repeat(200 * 1024);
+    remote.tell(large.clone()).unwrap();
+    assert_eq!(received.recv().await.unwrap(), "over grpc");
+    assert_eq!(received.recv().await.unwrap(), large);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(grpc());
+}
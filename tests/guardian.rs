@@ -0,0 +1,122 @@
+use elliot::{ActorContext, ActorRef, ActorSystem, Behavior, Schedule, Signal};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+/// Asks the parent for its child.
+type SpawnChild = oneshot::Sender<ActorRef<()>>;
+
+async fn guardian() {
+    let system = ActorSystem::new();
+    // nothing to stop yet
+    system.terminate().await;
+
+    let detached = system.spawn_detached("detached", |(): ()| async {});
+    let counted = system.spawn("counted", |(): ()| async {});
+    let anonymous = system.spawn_anonymous(|(): ()| async {});
+    let parent = system.spawn(
+        "parent",
+        |ctx: ActorContext<SpawnChild>, reply: SpawnChild| {
+            let _ = reply.send(ctx.spawn_child("child", |(): ()| async {}));
+            async {}
+        },
+    );
+    let (reply, child) = oneshot::channel();
+    parent.tell(reply).unwrap();
+    let child = child.await.unwrap();
+    assert_eq!(child.path().as_str(), "/user/parent/child");
+    // and the three internal actors
+    assert_eq!(system.actors().len(), 8);
+
+    // the guardian stops the top-level actors and their children
+    system.terminate().await;
+    for actor in [&detached, &counted, &anonymous, &child] {
+        assert!(!actor.is_alive(), "{}", actor.path());
+    }
+    assert!(!parent.is_alive());
+    while !system.actors().is_empty() {
+        tokio::task::yield_now().await;
+    }
+
+    // the guardian is gone as well
+    system.terminate().await;
+
+    // actors that are spawned afterwards get a new guardian
+    let later = system.spawn_detached("later", |(): ()| async {});
+    system.terminate().await;
+    assert!(!later.is_alive());
+}
+
+async fn system_guardian() {
+    let system = ActorSystem::new();
+    let (tx, mut alive) = mpsc::unbounded_channel();
+    let counted = system.spawn(
+        "counted",
+        (|(): ()| async {}).on_signal(move |ctx: &ActorContext<()>, signal| {
+            // the internal actors outlive the user actors
+            if let Signal::PostStop = signal {
+                let internals = ctx
+                    .system()
+                    .actors()
+                    .into_iter()
+                    .filter(|a| a.path.as_str().starts_with("/system/"))
+                    .count();
+                let _ = tx.send(internals);
+            }
+            async {}
+        }),
+    );
+
+    // the components of the system are internal actors
+    let _ = system.scheduler().schedule(
+        "tick",
+        Schedule::FixedRate(Duration::from_secs(60)),
+        &counted,
+        (),
+    );
+    system.subscribe(&counted);
+    let mut dead_letters = system.dead_letters_of::<u32>();
+    let paths = system
+        .actors()
+        .into_iter()
+        .map(|a| a.path.as_str().to_owned())
+        .collect::<Vec<_>>();
+    assert_eq!(
+        paths,
+        [
+            "/system/bus",
+            "/system/dead-letters",
+            "/system/scheduler",
+            "/user/counted"
+        ]
+    );
+
+    // the internal actors stop last and shut down their components
+    system.terminate().await;
+    assert_eq!(alive.recv().await, Some(3));
+    assert!(!counted.is_alive());
+    assert!(system.scheduler().schedules().is_empty());
+    assert_eq!(system.publish(()), 0);
+    assert!(dead_letters.recv().await.is_none());
+    while !system.actors().is_empty() {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(guardian());
+}
+
+#[test]
+fn system() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(system_guardian());
+}
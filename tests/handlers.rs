@@ -0,0 +1,82 @@
+use elliot::{ActorContext, ActorSystem, AnyMessage, Behaviors, ReplyTo};
+use std::sync::{Arc, Mutex};
+
+struct Deposit(u32);
+struct Withdraw(u32);
+struct Balance(ReplyTo<u32>);
+struct Close;
+
+async fn handlers() {
+    let system = ActorSystem::new();
+    let balance = Arc::new(Mutex::new(0));
+    let (deposits, withdrawals, queries) = (
+        Arc::clone(&balance),
+        Arc::clone(&balance),
+        Arc::clone(&balance),
+    );
+    let account = system.spawn(
+        "account",
+        Behaviors::handlers((
+            move |Deposit(amount)| {
+                *deposits.lock().unwrap() += amount;
+                async {}
+            },
+            move |ctx: ActorContext<AnyMessage>, Withdraw(amount)| {
+                let mut balance = withdrawals.lock().unwrap();
+                let overdrawn = amount > *balance;
+                if !overdrawn {
+                    *balance -= amount;
+                }
+                let this = ctx.this();
+                async move {
+                    if overdrawn {
+                        let _ = this.tell(Box::new(Close));
+                    }
+                }
+            },
+            move |Balance(reply)| {
+                let _ = reply.reply(*queries.lock().unwrap());
+                async {}
+            },
+            |Close| async { Behaviors::Stopped },
+        )),
+    );
+
+    account.tell(Box::new(Deposit(50))).unwrap();
+    account.tell(Box::new(Withdraw(20))).unwrap();
+    // a message without a handler is unhandled
+    account.tell(Box::new(String::from("hi"))).unwrap();
+    let answer = account
+        .ask(|reply| Box::new(Balance(reply)) as AnyMessage)
+        .await;
+    assert_eq!(answer, Ok(30));
+    let info = system
+        .actors()
+        .into_iter()
+        .find(|a| a.path.name() == "account")
+        .unwrap();
+    assert_eq!(info.dead_letters, 1);
+
+    account.tell(Box::new(Withdraw(40))).unwrap();
+    account.wait_for_stop().await;
+    assert_eq!(*balance.lock().unwrap(), 30);
+}
+
+#[test]
+#[should_panic(expected = "more than one handler for messages of type handlers::Deposit")]
+fn duplicate_handlers() {
+    let _ = Behaviors::handlers((
+        |Deposit(_)| async {},
+        |Close| async {},
+        |Deposit(_)| async {},
+    ));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(handlers());
+}
@@ -0,0 +1,51 @@
+use elliot::{ActorRef, ActorSystem};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+async fn idle() {
+    let system = ActorSystem::new();
+    let total = Arc::new(AtomicUsize::new(0));
+
+    let sum = Arc::clone(&total);
+    let sink = system.spawn("sink", move |n: usize| {
+        let _ = sum.fetch_add(n, Ordering::SeqCst);
+        async {
+            tokio::task::yield_now().await;
+        }
+    });
+    // every message fans out to a delayed and an immediate message
+    let fan_out = system.spawn("fan-out", move |n: usize| {
+        let sink: ActorRef<usize> = sink.clone();
+        async move {
+            let _ = sink.tell_after(n, Duration::from_millis(20));
+            sink.tell(n).unwrap();
+        }
+    });
+
+    for n in 1..=10 {
+        fan_out.tell(n).unwrap();
+    }
+    system.run_until_idle().await;
+    assert_eq!(total.load(Ordering::SeqCst), 110);
+
+    // cancelled delays are not waited for
+    let cancelled = fan_out.tell_after(1000, Duration::from_secs(60));
+    cancelled.cancel();
+    system.run_until_idle().await;
+    assert_eq!(total.load(Ordering::SeqCst), 110);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(idle());
+}
@@ -0,0 +1,63 @@
+#![cfg(feature = "inspector")]
+
+use elliot::{inspector, ActorSystem, Behaviors};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+async fn unhandled(_msg: ()) -> Behaviors {
+    Behaviors::Unhandled
+}
+
+async fn inspect() {
+    let system = ActorSystem::new();
+    let actor = system.spawn("quote\"d", unhandled);
+    let _ = actor.tell(());
+
+    while system
+        .actors()
+        .into_iter()
+        .find(|a| a.path.name() == "quote\"d")
+        .unwrap()
+        .dead_letters
+        == 0
+    {
+        tokio::task::yield_now().await;
+    }
+
+    let json = inspector::to_json(&system);
+    // the internal actors come first
+    assert!(json.starts_with(r#"{"actors":[{"path":"/system/"#));
+    assert!(json.contains(r#"{"path":"/user/quote\"d","state":"running","#));
+    let summary = r#""dead_letters":1}],"dead_letters":1}"#;
+    assert!(json.ends_with(summary));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let _server = tokio::spawn(inspector::serve(system, addr));
+
+    let mut stream = loop {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::task::yield_now().await,
+        }
+    };
+    stream.write_all(b"GET / HTTP/1.1\r\n\r\n").await.unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.ends_with(summary));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .unwrap()
+        .block_on(inspect());
+}
@@ -0,0 +1,297 @@
+use elliot::{
+    intercept::{self, Intercepted, Next},
+    ActorContext, ActorSystem, Behaviors, LifecycleEvent, ReplyTo, Supervision,
+};
+use std::{
+    io,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+async fn intercept() {
+    let system = ActorSystem::new();
+    let audit = Arc::new(Mutex::new(Vec::new()));
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let lines = Arc::new(Mutex::new(Vec::new()));
+    let timings = Arc::new(Mutex::new(Vec::new()));
+
+    let audited = Arc::clone(&audit);
+    let seen = Arc::clone(&handled);
+    let logged = Arc::clone(&lines);
+    let timed = Arc::clone(&timings);
+    let behavior = Intercepted::new(move |n: u32| {
+        seen.lock().unwrap().push(n);
+        async {}
+    })
+    .with(intercept::timing(move |path, _| {
+        timed.lock().unwrap().push(path.name().to_owned());
+    }))
+    .with(move |ctx: &ActorContext<u32>, n: u32, next: Next<u32>| {
+        audited
+            .lock()
+            .unwrap()
+            .push(format!("{} got {n}", ctx.name()));
+        next.run(n)
+    })
+    .with(intercept::filter(|n: &u32| n.is_multiple_of(2)))
+    .with(intercept::logging(move |line| {
+        logged.lock().unwrap().push(line)
+    }))
+    .with(
+        |_: &ActorContext<u32>, n: u32, next: Next<u32>| async move {
+            if n == 42 {
+                Ok(Behaviors::Stopped)
+            } else {
+                next.run(n).await
+            }
+        },
+    );
+
+    let actor = system.spawn("counter", behavior);
+    for n in [1, 2, 3, 4, 42, 6] {
+        actor.tell(n).unwrap();
+    }
+    actor.wait_for_stop().await;
+
+    // every message passes the audit, the odd ones are filtered and 42 stops the actor
+    assert_eq!(
+        *audit.lock().unwrap(),
+        [
+            "counter got 1",
+            "counter got 2",
+            "counter got 3",
+            "counter got 4",
+            "counter got 42"
+        ]
+    );
+    assert_eq!(*handled.lock().unwrap(), [2, 4]);
+    assert_eq!(
+        *lines.lock().unwrap(),
+        [
+            "/user/counter: 2 -> Same",
+            "/user/counter: 4 -> Same",
+            "/user/counter: 42 -> Stopped"
+        ]
+    );
+    assert_eq!(timings.lock().unwrap().len(), 5);
+}
+
+async fn unhandled() {
+    let system = ActorSystem::new();
+    let behavior = Intercepted::new(|_: u32| async {}).with(intercept::filter(|n: &u32| *n > 10));
+    let actor = system.spawn("picky", behavior);
+    for n in [5, 15, 7] {
+        actor.tell(n).unwrap();
+    }
+    loop {
+        let info = system
+            .actors()
+            .into_iter()
+            .find(|a| a.path.name() == "picky")
+            .unwrap();
+        if info.mailbox_len == 0 && info.dead_letters == 2 {
+            break;
+        }
+        tokio::task::yield_now().await;
+    }
+}
+
+#[derive(Debug)]
+enum Job {
+    Sleep(u64),
+    TimedOut,
+}
+
+async fn timeouts() {
+    let system = ActorSystem::new();
+    let slow = |job: Job| async move {
+        if let Job::Sleep(ms) = job {
+            tokio::time::sleep(Duration::from_millis(ms)).await;
+        }
+    };
+
+    // the actor is told a signal and keeps going
+    let log = Arc::new(Mutex::new(Vec::new()));
+    let jobs = Arc::clone(&log);
+    let behavior = Intercepted::new(move |job: Job| {
+        jobs.lock().unwrap().push(format!("{job:?}"));
+        slow(job)
+    })
+    .with(intercept::with_timeout(Duration::from_millis(50)).signal(|| Job::TimedOut));
+    let actor = system.spawn("signal", behavior);
+    actor.tell(Job::Sleep(1000)).unwrap();
+    actor.tell(Job::Sleep(1)).unwrap();
+    while log.lock().unwrap().len() < 3 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert_eq!(
+        *log.lock().unwrap(),
+        ["Sleep(1000)", "Sleep(1)", "TimedOut"]
+    );
+
+    // the message is unhandled
+    let actor = system.spawn(
+        "unhandled",
+        Intercepted::new(slow).with(intercept::with_timeout(Duration::from_millis(20)).unhandled()),
+    );
+    actor.tell(Job::Sleep(1000)).unwrap();
+    actor.tell(Job::Sleep(1)).unwrap();
+    loop {
+        let info = system
+            .actors()
+            .into_iter()
+            .find(|a| a.path.name() == "unhandled")
+            .unwrap();
+        if info.mailbox_len == 0 && info.dead_letters == 1 {
+            break;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // the actor crashes
+    let mut events = system.lifecycle_events();
+    let actor = system.spawn(
+        "crash",
+        Intercepted::new(slow).with(intercept::with_timeout(Duration::from_millis(20))),
+    );
+    actor.tell(Job::Sleep(1000)).unwrap();
+    actor.wait_for_stop().await;
+    loop {
+        match events.recv().await {
+            Some(LifecycleEvent::Crashed(e)) => {
+                assert_eq!(e.path.name(), "crash");
+                assert!(e.reason.contains("not handled within 20ms"), "{}", e.reason);
+                break;
+            }
+            Some(_) => {}
+            None => panic!("no crash event"),
+        }
+    }
+}
+
+struct Deposit {
+    id: Option<u32>,
+    amount: u32,
+    reply: ReplyTo<u32>,
+}
+
+async fn deduplicated() {
+    let system = ActorSystem::new();
+    let balance = Arc::new(Mutex::new(0));
+    let account = Arc::clone(&balance);
+    let behavior = Intercepted::new(move |deposit: Deposit| {
+        let mut balance = account.lock().unwrap();
+        *balance += deposit.amount;
+        let _ = deposit.reply.reply(*balance);
+        async {}
+    })
+    .with(
+        intercept::dedup(Duration::from_millis(50), |deposit: &Deposit| deposit.id).on_duplicate(
+            |deposit: Deposit| {
+                let _ = deposit.reply.reply(0);
+            },
+        ),
+    );
+    let actor = system.spawn("account", behavior);
+    let deposit = |id, amount| {
+        let actor = actor.clone();
+        async move {
+            actor
+                .ask(|reply| Deposit { id, amount, reply })
+                .await
+                .unwrap()
+        }
+    };
+
+    // a redelivered deposit is only booked once, messages without a key always pass
+    assert_eq!(deposit(Some(1), 10).await, 10);
+    assert_eq!(deposit(Some(1), 10).await, 0);
+    assert_eq!(deposit(Some(2), 5).await, 15);
+    assert_eq!(deposit(None, 1).await, 16);
+    assert_eq!(deposit(None, 1).await, 17);
+    assert_eq!(deposit(Some(2), 5).await, 0);
+
+    // the key is forgotten after the window
+    tokio::time::sleep(Duration::from_millis(60)).await;
+    assert_eq!(deposit(Some(1), 10).await, 27);
+    assert_eq!(*balance.lock().unwrap(), 27);
+
+    // a deposit that failed can be retried with the same key
+    let system = ActorSystem::builder()
+        .supervision(Supervision::Resume)
+        .build();
+    let available = Arc::new(Mutex::new(false));
+    let booking = Arc::clone(&available);
+    let behavior = Intercepted::new(move |deposit: Deposit| {
+        let available = *booking.lock().unwrap();
+        if available {
+            let _ = deposit.reply.reply(deposit.amount);
+        }
+        async move {
+            match available {
+                true => Ok(()),
+                false => Err(io::Error::other("unavailable")),
+            }
+        }
+    })
+    .with(intercept::dedup(
+        Duration::from_secs(60),
+        |deposit: &Deposit| deposit.id,
+    ));
+    let actor = system.spawn("flaky", behavior);
+    let failed = actor
+        .ask(|reply| Deposit {
+            id: Some(1),
+            amount: 10,
+            reply,
+        })
+        .await;
+    assert!(failed.is_err());
+    *available.lock().unwrap() = true;
+    let retried = actor
+        .ask(|reply| Deposit {
+            id: Some(1),
+            amount: 10,
+            reply,
+        })
+        .await;
+    assert_eq!(retried, Ok(10));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(intercept());
+}
+
+#[test]
+fn filter() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(unhandled());
+}
+
+#[test]
+fn timeout() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(timeouts());
+}
+
+#[test]
+fn dedup() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(deduplicated());
+}
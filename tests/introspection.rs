@@ -0,0 +1,51 @@
+use elliot::{ActorState, ActorSystem, Behaviors};
+
+async fn stop_on_first(_msg: ()) -> Behaviors {
+    Behaviors::Stopped
+}
+
+async fn introspection() {
+    let system = ActorSystem::new();
+    let a = system.spawn("a", stop_on_first);
+    let b = system.spawn("b", stop_on_first);
+
+    let actors = system.actors();
+    let paths = actors.iter().map(|a| a.path.as_str()).collect::<Vec<_>>();
+    // the internal actors are spawned with the first actor
+    assert_eq!(
+        paths,
+        [
+            "/system/bus",
+            "/system/dead-letters",
+            "/system/scheduler",
+            "/user/a",
+            "/user/b"
+        ]
+    );
+    assert!(actors.iter().all(|a| a.state == ActorState::Running));
+
+    let _ = a.tell(());
+    a.wait_for_stop().await;
+
+    let users = || {
+        system
+            .actors()
+            .into_iter()
+            .filter(|a| a.path.as_str().starts_with("/user/"))
+            .collect::<Vec<_>>()
+    };
+    while users().len() != 1 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(users()[0].path.name(), "b");
+    assert!(b.is_alive());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(introspection());
+}
@@ -0,0 +1,78 @@
+#![cfg(feature = "kafka")]
+
+use elliot::{
+    ActorSystem, InMemorySnapshotStore, KafkaConsumer, KafkaMessage, KafkaProducer, KafkaRecord,
+    SnapshotStore,
+};
+use rskafka::client::{partition::UnknownTopicHandling, ClientBuilder};
+use std::{
+    sync::atomic::{AtomicUsize, Ordering},
+    time::{SystemTime, UNIX_EPOCH},
+};
+use tokio::sync::mpsc;
+
+/// Runs against the brokers in `KAFKA_BROKERS`, e.g. `localhost:9092`.
+async fn kafka() {
+    let brokers = std::env::var("KAFKA_BROKERS").expect("KAFKA_BROKERS is not set");
+    let client = ClientBuilder::new(brokers.split(',').map(String::from).collect())
+        .build()
+        .await
+        .unwrap();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let topic = format!("elliot-{nanos}");
+    client
+        .controller_client()
+        .unwrap()
+        .create_topic(&topic, 1, 1, 5_000)
+        .await
+        .unwrap();
+    let partition = || client.partition_client(&topic, 0, UnknownTopicHandling::Retry);
+
+    let system = ActorSystem::new();
+    let producer = KafkaProducer::new(partition().await.unwrap()).spawn(&system, "producer");
+    for value in ["one", "two", "three"] {
+        producer.tell(KafkaMessage::new(value).key(value)).unwrap();
+    }
+
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let deliveries = AtomicUsize::new(0);
+    let consumer = system.spawn("consumer", move |record: KafkaRecord| {
+        let value = String::from_utf8(record.value.unwrap()).unwrap();
+        // the first delivery of "two" is not acked
+        if deliveries.fetch_add(1, Ordering::SeqCst) != 1 {
+            record.ack.ack();
+        }
+        let _ = tx.send(value);
+        async {}
+    });
+    let offsets = InMemorySnapshotStore::new();
+    KafkaConsumer::new(partition().await.unwrap(), offsets.clone()).spawn(&consumer);
+
+    let mut values = Vec::new();
+    while values.len() < 5 {
+        values.push(received.recv().await.unwrap());
+    }
+    assert_eq!(values, ["one", "two", "three", "two", "three"]);
+    while offsets
+        .load(&format!("kafka-{topic}-0"))
+        .unwrap()
+        .map(|(_, o)| o)
+        != Some(3)
+    {
+        tokio::task::yield_now().await;
+    }
+}
+
+#[test]
+#[ignore = "needs KAFKA_BROKERS"]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(kafka());
+}
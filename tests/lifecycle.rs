@@ -0,0 +1,35 @@
+use elliot::{ActorSystem, Behaviors, LifecycleEvent, StopReason};
+
+async fn stop_on_first(_msg: ()) -> Behaviors {
+    Behaviors::Stopped
+}
+
+async fn lifecycle() {
+    let system = ActorSystem::new();
+    let mut events = system.lifecycle_events();
+
+    let actor = system.spawn("stopper", stop_on_first);
+    let _ = actor.tell(());
+    actor.wait_for_stop().await;
+
+    match events.recv().await {
+        Some(LifecycleEvent::Started(e)) => assert_eq!(e.path.name(), "stopper"),
+        e => panic!("expected started event, got {e:?}"),
+    }
+    match events.recv().await {
+        Some(LifecycleEvent::Stopped(e)) => {
+            assert_eq!(e.path.as_str(), "/user/stopper");
+            assert_eq!(e.reason, StopReason::Stopped);
+        }
+        e => panic!("expected stopped event, got {e:?}"),
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(lifecycle());
+}
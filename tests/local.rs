@@ -0,0 +1,51 @@
+use elliot::{ActorSystem, Behaviors};
+use std::{cell::RefCell, rc::Rc};
+use tokio::{sync::oneshot, task::LocalSet};
+
+enum Msg {
+    Add(u32),
+    Get(oneshot::Sender<u32>),
+    Stop,
+}
+
+async fn local() {
+    let system = ActorSystem::new();
+
+    // the state is not `Send`
+    let total = Rc::new(RefCell::new(0));
+    let counter = system.spawn_local("counter", move |msg: Msg| {
+        let total = Rc::clone(&total);
+        async move {
+            match msg {
+                Msg::Add(n) => *total.borrow_mut() += n,
+                Msg::Get(reply) => {
+                    let _ = reply.send(*total.borrow());
+                }
+                Msg::Stop => return Behaviors::Stopped,
+            }
+            Behaviors::Same
+        }
+    });
+
+    counter.tell(Msg::Add(40)).unwrap();
+    counter.tell(Msg::Add(2)).unwrap();
+    let (tx, rx) = oneshot::channel();
+    counter.tell(Msg::Get(tx)).unwrap();
+    assert_eq!(rx.await.unwrap(), 42);
+
+    counter.tell(Msg::Stop).unwrap();
+    counter.wait_for_stop().await;
+    assert!(system
+        .actors()
+        .iter()
+        .all(|a| a.path.as_str().starts_with("/system/")));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(LocalSet::new().run_until(local()));
+}
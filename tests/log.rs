@@ -0,0 +1,87 @@
+#![cfg(feature = "log")]
+
+use elliot::{ActorSystem, Log};
+use log::{
+    kv::{Key, Value, VisitSource},
+    Level, Metadata, Record,
+};
+use std::sync::Mutex;
+use tokio::sync::oneshot;
+
+type Captured = (Level, String, Vec<(String, String)>);
+
+static RECORDS: Mutex<Vec<Captured>> = Mutex::new(Vec::new());
+
+struct Capture;
+
+impl log::Log for Capture {
+    fn enabled(&self, _: &Metadata<'_>) -> bool {
+        true
+    }
+
+    fn log(&self, record: &Record<'_>) {
+        struct Keys(Vec<(String, String)>);
+        impl<'kvs> VisitSource<'kvs> for Keys {
+            fn visit_pair(
+                &mut self,
+                key: Key<'kvs>,
+                value: Value<'kvs>,
+            ) -> Result<(), log::kv::Error> {
+                self.0.push((key.to_string(), value.to_string()));
+                Ok(())
+            }
+        }
+
+        if record.target() == "elliot::actor" {
+            let mut keys = Keys(Vec::new());
+            record.key_values().visit(&mut keys).unwrap();
+            let msg = record.args().to_string();
+            RECORDS.lock().unwrap().push((record.level(), msg, keys.0));
+        }
+    }
+
+    fn flush(&self) {}
+}
+
+async fn logging() {
+    log::set_logger(&Capture).unwrap();
+    log::set_max_level(log::LevelFilter::Trace);
+
+    let system = ActorSystem::new();
+    let greeter = system.spawn(
+        "greeter",
+        |log: Log, (name, done): (String, oneshot::Sender<()>)| async move {
+            log.info(format_args!("hello {name}"));
+            log.warn("done");
+            let _ = done.send(());
+        },
+    );
+    let (tx, rx) = oneshot::channel();
+    greeter.tell(("elliot".to_owned(), tx)).unwrap();
+    rx.await.unwrap();
+
+    let records = RECORDS.lock().unwrap();
+    let keys = vec![
+        ("actor".to_owned(), "/user/greeter".to_owned()),
+        (
+            "message_type".to_owned(),
+            std::any::type_name::<(String, oneshot::Sender<()>)>().to_owned(),
+        ),
+    ];
+    assert_eq!(
+        *records,
+        [
+            (Level::Info, "hello elliot".to_owned(), keys.clone()),
+            (Level::Warn, "done".to_owned(), keys),
+        ]
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(logging());
+}
@@ -0,0 +1,73 @@
+use elliot::{ActorSystem, TrySendError};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::{mpsc, Semaphore};
+
+async fn memory_bounded() {
+    let system = ActorSystem::new();
+    let (tx, mut received) = mpsc::unbounded_channel();
+    // every message waits for a permit, so that the mailbox fills up
+    let permits = Arc::new(Semaphore::new(0));
+    let gate = Arc::clone(&permits);
+    let actor = system.spawn_bounded_by_size("buffers", 100, Vec::len, move |buf: Vec<u8>| {
+        let _ = tx.send(buf.len());
+        let gate = Arc::clone(&gate);
+        async move { gate.acquire().await.unwrap().forget() }
+    });
+    let bytes = || {
+        system
+            .actors()
+            .into_iter()
+            .find(|a| a.path.name() == "buffers")
+            .unwrap()
+            .mailbox_bytes
+    };
+
+    actor.tell(vec![0; 10]).unwrap();
+    assert_eq!(received.recv().await, Some(10));
+    assert_eq!(bytes(), 0);
+
+    actor.try_send(vec![0; 60]).unwrap();
+    let rejected = actor.try_send(vec![0; 60]).unwrap_err();
+    assert!(matches!(rejected, TrySendError::Full(_)));
+    assert_eq!(rejected.into_inner().len(), 60);
+    actor.try_send(vec![0; 40]).unwrap();
+    assert_eq!(bytes(), 100);
+
+    // send waits until the mailbox has room for the message
+    let sender = actor.clone();
+    let mut send = tokio::spawn(async move { sender.send(vec![0; 30]).await.is_ok() });
+    assert!(tokio::time::timeout(Duration::from_millis(20), &mut send)
+        .await
+        .is_err());
+    permits.add_permits(1);
+    assert_eq!(received.recv().await, Some(60));
+    assert!(send.await.unwrap());
+    assert_eq!(bytes(), 70);
+
+    permits.add_permits(3);
+    assert_eq!(received.recv().await, Some(40));
+    assert_eq!(received.recv().await, Some(30));
+    assert_eq!(bytes(), 0);
+
+    // a message that exceeds the budget still fits into an empty mailbox
+    actor.try_send(vec![0; 500]).unwrap();
+    assert_eq!(received.recv().await, Some(500));
+    permits.add_permits(1);
+
+    actor.stop();
+    actor.wait_for_stop().await;
+    assert!(matches!(
+        actor.try_send(Vec::new()),
+        Err(TrySendError::ActorRefGone(_))
+    ));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(memory_bounded());
+}
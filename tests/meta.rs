@@ -0,0 +1,42 @@
+use elliot::{ActorSystem, Meta};
+use std::time::Instant;
+use tokio::sync::mpsc;
+
+async fn meta() {
+    let system = ActorSystem::new();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let last = system.spawn("last", move |meta: Meta, msg: &'static str| {
+        let _ = tx.send((msg, meta));
+        async {}
+    });
+    let target = last.clone();
+    let forward = system.spawn("forward", move |msg: &'static str| {
+        let _ = target.tell(msg);
+        async {}
+    });
+
+    let before = Instant::now();
+    last.tell("outside").unwrap();
+    let (msg, meta) = rx.recv().await.unwrap();
+    assert_eq!(msg, "outside");
+    assert!(meta.sender.is_none());
+    assert!(meta.correlation_id.is_none());
+    assert!(meta.enqueued_at >= before);
+
+    // the correlation id travels along with the messages that are told while handling
+    forward.tell_correlated("hop", "request-1").unwrap();
+    let (msg, meta) = rx.recv().await.unwrap();
+    assert_eq!(msg, "hop");
+    assert_eq!(meta.sender.unwrap().as_str(), "/user/forward");
+    assert_eq!(meta.correlation_id.as_deref(), Some("request-1"));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(meta());
+}
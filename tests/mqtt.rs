@@ -0,0 +1,52 @@
+#![cfg(feature = "mqtt")]
+
+use elliot::{ActorSystem, MqttBridge, MqttMessage};
+use rumqttc::{MqttOptions, QoS};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+/// Runs against the broker in `MQTT_BROKER`, e.g. `localhost:1883`.
+async fn mqtt() {
+    let broker = std::env::var("MQTT_BROKER").expect("MQTT_BROKER is not set");
+    let (host, port) = broker.rsplit_once(':').unwrap();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let prefix = format!("elliot-{nanos}");
+
+    let system = ActorSystem::new();
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let devices = system.spawn("devices", move |msg: MqttMessage| {
+        let _ = tx.send(msg);
+        async {}
+    });
+    let options = MqttOptions::new(&prefix, host, port.parse().unwrap());
+    let mqtt = MqttBridge::new(options)
+        .subscribe(format!("{prefix}/+/state"), QoS::AtLeastOnce, &devices)
+        .spawn(&system, "mqtt");
+
+    // the bridge receives what it publishes itself, once it has subscribed
+    let state = format!("{prefix}/lamp/state");
+    let msg = loop {
+        mqtt.tell(MqttMessage::new(&state, "on")).unwrap();
+        mqtt.tell(MqttMessage::new(format!("{prefix}/lamp/other"), "ignored"))
+            .unwrap();
+        let timeout = std::time::Duration::from_millis(200);
+        if let Ok(msg) = tokio::time::timeout(timeout, received.recv()).await {
+            break msg.unwrap();
+        }
+    };
+    assert_eq!(msg, MqttMessage::new(state, "on"));
+}
+
+#[test]
+#[ignore = "needs MQTT_BROKER"]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(mqtt());
+}
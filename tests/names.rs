@@ -0,0 +1,70 @@
+use elliot::{ActorSystem, DuplicateNames};
+use std::panic::{self, AssertUnwindSafe};
+use tokio::sync::oneshot;
+
+async fn unique() {
+    let system = ActorSystem::builder()
+        .duplicate_names(DuplicateNames::Reject)
+        .build();
+
+    let echo = system.spawn("echo", |reply: oneshot::Sender<u32>| async move {
+        let _ = reply.send(42);
+    });
+    let taken = system.try_spawn("echo", |(): ()| async {});
+    assert_eq!(taken.unwrap_err().0, "echo");
+
+    // lookup by name and message type
+    let found = system.find::<oneshot::Sender<u32>>("echo").unwrap();
+    let (tx, rx) = oneshot::channel();
+    found.tell(tx).unwrap();
+    assert_eq!(rx.await.unwrap(), 42);
+    assert!(system.find::<String>("echo").is_none());
+    assert!(system.find::<oneshot::Sender<u32>>("missing").is_none());
+
+    // the name is free again once the actor has stopped
+    drop(found);
+    drop(echo);
+    while system.find::<oneshot::Sender<u32>>("echo").is_some() {
+        tokio::task::yield_now().await;
+    }
+    while system.try_spawn("echo", |(): ()| async {}).is_err() {
+        tokio::task::yield_now().await;
+    }
+
+    // and once a spawn has panicked, here a local actor outside of a LocalSet
+    let panicked = panic::catch_unwind(AssertUnwindSafe(|| {
+        system.spawn_local("local", |(): ()| async {})
+    }));
+    assert!(panicked.is_err());
+    assert!(system.try_spawn("local", |(): ()| async {}).is_ok());
+}
+
+async fn suffix() {
+    // taken names are suffixed by default
+    let system = ActorSystem::new();
+    let first = system.spawn("worker", |(): ()| async {});
+    let second = system.spawn("worker", |(): ()| async {});
+    let third = system.spawn("worker", |(): ()| async {});
+    assert_eq!(first.path().as_str(), "/user/worker");
+    assert_eq!(second.path().as_str(), "/user/worker-1");
+    assert_eq!(third.path().as_str(), "/user/worker-2");
+    assert!(system.find::<()>("worker-1").is_some());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(unique());
+}
+
+#[test]
+fn suffixed() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(suffix());
+}
@@ -0,0 +1,83 @@
+#![cfg(feature = "nats")]
+
+use elliot::{ActorSystem, BoxErr, MessageCodec};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+fn system(tx: mpsc::UnboundedSender<(&'static str, String)>, name: &'static str) -> ActorSystem {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let events = system.spawn("events", move |event: String| {
+        let _ = tx.send((name, event));
+        async {}
+    });
+    system.subscribe(&events);
+    system
+}
+
+/// Runs against the server in `NATS_URL`, e.g. `nats://localhost:4222`.
+async fn nats() {
+    let url = std::env::var("NATS_URL").expect("NATS_URL is not set");
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let first = system(tx.clone(), "first");
+    let second = system(tx.clone(), "second");
+    let first_nats = first.nats(async_nats::connect(&url).await.unwrap(), "first");
+    let second_nats = second.nats(async_nats::connect(&url).await.unwrap(), "second");
+
+    // tell an exposed actor of another system
+    let greeter = first.spawn("greeter", move |name: String| {
+        let _ = tx.send(("greeter", name));
+        async {}
+    });
+    first_nats.expose(&greeter).await.unwrap();
+    let subject = first_nats.subject(greeter.path());
+    assert_eq!(subject, "first.user.greeter");
+    let remote = second_nats.remote_ref::<String>(&subject).unwrap();
+    remote.tell("Alice".to_owned()).unwrap();
+    assert_eq!(received.recv().await, Some(("greeter", "Alice".to_owned())));
+
+    // bus events are shared in both directions, and not sent back
+    first_nats.bridge::<String>("elliot.events").await.unwrap();
+    second_nats.bridge::<String>("elliot.events").await.unwrap();
+    let _ = first.publish("from first".to_owned());
+    let _ = second.publish("from second".to_owned());
+    let mut events = Vec::new();
+    while let Ok(Some(event)) =
+        tokio::time::timeout(Duration::from_millis(500), received.recv()).await
+    {
+        events.push(event);
+    }
+    events.sort();
+    assert_eq!(
+        events,
+        [
+            ("first", "from first".to_owned()),
+            ("first", "from second".to_owned()),
+            ("second", "from first".to_owned()),
+            ("second", "from second".to_owned()),
+        ]
+    );
+}
+
+#[test]
+#[ignore = "needs NATS_URL"]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(nats());
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "net")]
+
+use elliot::{
+    net::{Framing, TcpEvent, TcpListenerActor},
+    ActorSystem, Behaviors,
+};
+use tokio::{
+    io::{AsyncBufReadExt, AsyncReadExt, AsyncWriteExt, BufReader},
+    net::TcpStream,
+    sync::mpsc,
+};
+
+async fn echo() {
+    let system = ActorSystem::new();
+    let (tx, mut events) = mpsc::unbounded_channel();
+    let echo = TcpListenerActor::new(move |_peer, writer| {
+        let tx = tx.clone();
+        move |event: TcpEvent| {
+            let _ = tx.send(event.clone());
+            let behavior = match event {
+                TcpEvent::Received(frame) if frame == b"bye" => Behaviors::Stopped,
+                TcpEvent::Received(frame) => {
+                    writer.tell(frame).unwrap();
+                    Behaviors::Same
+                }
+                TcpEvent::Closed => Behaviors::Stopped,
+            };
+            async move { behavior }
+        }
+    });
+    let (_listener, addr) = echo.bind(&system, "echo", "127.0.0.1:0").await.unwrap();
+
+    let mut client = TcpStream::connect(addr).await.unwrap();
+    for frame in [&b"hello"[..], b"world"] {
+        client.write_u32(frame.len() as u32).await.unwrap();
+        client.write_all(frame).await.unwrap();
+        let mut echoed = vec![0; client.read_u32().await.unwrap() as usize];
+        let _ = client.read_exact(&mut echoed).await.unwrap();
+        assert_eq!(echoed, frame);
+    }
+    assert_eq!(
+        events.recv().await,
+        Some(TcpEvent::Received(b"hello".to_vec()))
+    );
+    assert_eq!(
+        events.recv().await,
+        Some(TcpEvent::Received(b"world".to_vec()))
+    );
+
+    // the connection closes when the actor stops
+    client.write_u32(3).await.unwrap();
+    client.write_all(b"bye").await.unwrap();
+    assert_eq!(client.read(&mut [0; 4]).await.unwrap(), 0);
+
+    // the actor is told when the peer closes
+    let client = TcpStream::connect(addr).await.unwrap();
+    drop(client);
+    assert_eq!(
+        events.recv().await,
+        Some(TcpEvent::Received(b"bye".to_vec()))
+    );
+    assert_eq!(events.recv().await, Some(TcpEvent::Closed));
+}
+
+async fn lines() {
+    let system = ActorSystem::new();
+    let upper = TcpListenerActor::new(|_peer, writer| {
+        move |event: TcpEvent| {
+            if let TcpEvent::Received(line) = event {
+                writer.tell(line.to_ascii_uppercase()).unwrap();
+            }
+            async {}
+        }
+    })
+    .framing(Framing::Lines);
+    let (_listener, addr) = upper.bind(&system, "upper", "127.0.0.1:0").await.unwrap();
+
+    let mut client = BufReader::new(TcpStream::connect(addr).await.unwrap());
+    client.write_all(b"one\ntwo\n").await.unwrap();
+    let mut received = String::new();
+    for _ in 0..2 {
+        let _ = client.read_line(&mut received).await.unwrap();
+    }
+    assert_eq!(received, "ONE\nTWO\n");
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .unwrap()
+        .block_on(echo());
+}
+
+#[test]
+fn line_framing() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .unwrap()
+        .block_on(lines());
+}
@@ -0,0 +1,55 @@
+#![cfg(feature = "opentelemetry")]
+
+use elliot::ActorSystem;
+use opentelemetry::{
+    trace::{SpanContext, SpanId, TraceContextExt, TraceFlags, TraceId, TraceState},
+    Context,
+};
+use tokio::sync::mpsc;
+
+async fn propagation() {
+    let system = ActorSystem::new();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let last = system.spawn("last", move |(): ()| {
+        let _ = tx.send(Context::current().span().span_context().clone());
+        async {}
+    });
+    let first = system.spawn("first", move |(): ()| {
+        let last = last.clone();
+        async move {
+            tokio::task::yield_now().await;
+            // the context is still current after the handler was suspended
+            let _ = last.tell(());
+        }
+    });
+
+    let trace_id = TraceId::from(0x4bf9_2f35_77b3_4da6_a3ce_929d_0e0e_4736);
+    let span_context = SpanContext::new(
+        trace_id,
+        SpanId::from(0x00f0_67aa_0ba9_02b7),
+        TraceFlags::SAMPLED,
+        true,
+        TraceState::default(),
+    );
+    {
+        let _guard = Context::current()
+            .with_remote_span_context(span_context.clone())
+            .attach();
+        first.tell(()).unwrap();
+    }
+    // without a current context, there is nothing to propagate
+    first.tell(()).unwrap();
+
+    assert_eq!(rx.recv().await.unwrap(), span_context);
+    assert!(!rx.recv().await.unwrap().is_valid());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(propagation());
+}
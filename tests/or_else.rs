@@ -0,0 +1,61 @@
+use elliot::{ActorRefGone, ActorSystem, Behavior, Behaviors};
+use tokio::sync::mpsc;
+
+type Reply = mpsc::UnboundedSender<&'static str>;
+
+#[derive(Clone, Debug)]
+enum Msg {
+    Ping(Reply),
+    Count(Reply),
+    Other,
+    Invalid,
+}
+
+async fn or_else() {
+    let system = ActorSystem::new();
+    let mut dead_letters = system.dead_letters();
+
+    let pings = |msg: Msg| async move {
+        match msg {
+            Msg::Ping(reply) => {
+                let _ = reply.send("pong");
+                Behaviors::Same
+            }
+            _ => Behaviors::Unhandled,
+        }
+    };
+    let counts = |msg: Msg| async move {
+        match msg {
+            Msg::Count(reply) => {
+                let _ = reply.send("counted");
+                Ok(Behaviors::Same)
+            }
+            Msg::Invalid => Err(ActorRefGone(msg)),
+            _ => Ok(Behaviors::Unhandled),
+        }
+    };
+    let actor = system.spawn("layered", pings.or_else(counts));
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    actor.tell(Msg::Ping(tx.clone())).unwrap();
+    actor.tell(Msg::Count(tx)).unwrap();
+    assert_eq!(rx.recv().await, Some("pong"));
+    assert_eq!(rx.recv().await, Some("counted"));
+
+    // unhandled by both layers, the last one decides what happens
+    actor.tell(Msg::Other).unwrap();
+    let dead_letter = dead_letters.recv().await.unwrap();
+    assert_eq!(dead_letter.recipient.as_str(), "/user/layered");
+
+    actor.tell(Msg::Invalid).unwrap();
+    actor.wait_for_stop().await;
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(or_else());
+}
@@ -0,0 +1,197 @@
+use elliot::{
+    ActorSystem, EventEnvelope, EventSourced, EventsByTag, InMemoryJournal, InMemorySnapshotStore,
+    Journal, ReadJournal, ReplyTo, SnapshotStore,
+};
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+#[derive(Debug)]
+enum Command {
+    Deposit(u64),
+    Withdraw(u64),
+    Balance(ReplyTo<u64>),
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Event {
+    Deposited(u64),
+    Withdrawn(u64),
+}
+
+fn account(journal: &InMemoryJournal<Event>) -> EventSourced<Command, Event, u64> {
+    EventSourced::new(
+        "account-1",
+        journal.clone(),
+        |balance: &u64, command| match command {
+            Command::Deposit(amount) => vec![Event::Deposited(amount)],
+            Command::Withdraw(amount) if amount <= *balance => vec![Event::Withdrawn(amount)],
+            Command::Withdraw(_) => vec![],
+            Command::Balance(reply) => {
+                let _ = reply.reply(*balance);
+                vec![]
+            }
+        },
+        |balance: &mut u64, event| match event {
+            Event::Deposited(amount) => *balance += amount,
+            Event::Withdrawn(amount) => *balance -= amount,
+        },
+    )
+}
+
+async fn persistence() {
+    let system = ActorSystem::new();
+    let journal = InMemoryJournal::new();
+
+    let first = system.spawn("account", account(&journal));
+    let _ = first.tell(Command::Deposit(100));
+    let _ = first.tell(Command::Withdraw(30));
+    let _ = first.tell(Command::Withdraw(500));
+    assert_eq!(first.ask(Command::Balance).await, Ok(70));
+    drop(first);
+
+    assert_eq!(
+        journal.events("account-1"),
+        vec![Event::Deposited(100), Event::Withdrawn(30)]
+    );
+
+    // a new incarnation recovers its state from the journal
+    let second = system.spawn("account", account(&journal));
+    assert_eq!(second.ask(Command::Balance).await, Ok(70));
+    let _ = second.tell(Command::Deposit(5));
+    assert_eq!(second.ask(Command::Balance).await, Ok(75));
+    assert_eq!(journal.events("account-1").len(), 3);
+}
+
+async fn snapshots() {
+    let system = ActorSystem::new();
+    let journal = InMemoryJournal::new();
+    let snapshots = InMemorySnapshotStore::new();
+    let applied = Arc::new(AtomicUsize::new(0));
+
+    let account = || {
+        let applied = Arc::clone(&applied);
+        EventSourced::new(
+            "account-2",
+            journal.clone(),
+            |_: &u64, command| match command {
+                Command::Deposit(amount) => vec![Event::Deposited(amount)],
+                _ => vec![],
+            },
+            move |balance: &mut u64, event| {
+                let _ = applied.fetch_add(1, Ordering::SeqCst);
+                if let Event::Deposited(amount) = event {
+                    *balance += amount;
+                }
+            },
+        )
+        .snapshot_every(3, snapshots.clone())
+    };
+
+    let first = system.spawn("account", account());
+    for amount in 1..=7 {
+        let _ = first.tell(Command::Deposit(amount));
+    }
+    let _ = first.tell(Command::Withdraw(0));
+    drop(first);
+    while applied.load(Ordering::SeqCst) < 7 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(snapshots.load("account-2").unwrap(), Some((6, 21)));
+
+    // recovery starts at the snapshot and replays only the seventh event
+    applied.store(0, Ordering::SeqCst);
+    let second = system.spawn("account", account());
+    let _ = second.tell(Command::Deposit(0));
+    while applied.load(Ordering::SeqCst) < 2 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(applied.load(Ordering::SeqCst), 2);
+    assert_eq!(journal.events("account-2").len(), 8);
+}
+
+async fn next(events: &mut EventsByTag<Event>) -> (u64, String, u64, Event) {
+    let EventEnvelope {
+        offset,
+        persistence_id,
+        sequence_nr,
+        event,
+    } = tokio::time::timeout(Duration::from_secs(5), events.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    (offset, persistence_id.to_string(), sequence_nr, event)
+}
+
+async fn events_by_tag() {
+    let system = ActorSystem::new();
+    let journal = InMemoryJournal::new().tagged(|event: &Event| match event {
+        Event::Deposited(_) => vec!["deposit".to_owned(), "money".to_owned()],
+        Event::Withdrawn(_) => vec!["money".to_owned()],
+    });
+
+    journal.append("other", 1, &[Event::Deposited(1)]).unwrap();
+    let mut deposits = journal.events_by_tag("deposit").unwrap();
+    let mut money = journal.events_by_tag("money").unwrap();
+    assert_eq!(
+        next(&mut deposits).await,
+        (1, "other".into(), 1, Event::Deposited(1))
+    );
+
+    // events that are persisted later are streamed live
+    let account = system.spawn("account", account(&journal));
+    let _ = account.tell(Command::Deposit(100));
+    let _ = account.tell(Command::Withdraw(30));
+    assert_eq!(
+        next(&mut deposits).await,
+        (2, "account-1".into(), 1, Event::Deposited(100))
+    );
+    assert_eq!(next(&mut money).await.0, 1);
+    assert_eq!(next(&mut money).await.0, 2);
+    assert_eq!(
+        next(&mut money).await,
+        (3, "account-1".into(), 2, Event::Withdrawn(30))
+    );
+
+    // a query resumes after the offset of the last processed event
+    let mut resumed = journal.events_by_tag_from("money", 2).unwrap();
+    assert_eq!(next(&mut resumed).await.3, Event::Withdrawn(30));
+    let _ = account.tell(Command::Deposit(5));
+    assert_eq!(next(&mut resumed).await.0, 4);
+
+    let mut unknown = journal.events_by_tag("unknown").unwrap();
+    let nothing = tokio::time::timeout(Duration::from_millis(50), unknown.recv()).await;
+    assert!(nothing.is_err());
+}
+
+#[test]
+fn tags() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(events_by_tag());
+}
+
+#[test]
+fn snapshot() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(snapshots());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(persistence());
+}
@@ -0,0 +1,133 @@
+#![cfg(feature = "postgres")]
+
+use elliot::{
+    ActorSystem, EventEnvelope, EventSourced, Journal, OffsetStore, PostgresJournal, ReadJournal,
+    ReplyTo, TaggedJournal,
+};
+use serde::{Deserialize, Serialize};
+use std::{
+    sync::Arc,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+#[derive(Debug)]
+enum Command {
+    Increment,
+    Count(ReplyTo<u32>),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Incremented;
+
+fn counter(
+    persistence_id: &str,
+    journal: &PostgresJournal,
+) -> EventSourced<Command, Incremented, u32> {
+    EventSourced::new(
+        persistence_id,
+        journal.clone(),
+        |count: &u32, command| match command {
+            Command::Increment => vec![Incremented],
+            Command::Count(reply) => {
+                let _ = reply.reply(*count);
+                vec![]
+            }
+        },
+        |count: &mut u32, Incremented| *count += 1,
+    )
+}
+
+/// The tables are shared, so the persistence ids and tags are unique to this run.
+fn unique(name: &str) -> String {
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    format!("elliot-test-{}-{nanos}-{name}", std::process::id())
+}
+
+async fn postgres_journal(url: &str) {
+    let id = unique("counter");
+    let system = ActorSystem::new();
+    let journal = PostgresJournal::connect(url).unwrap();
+    let first = system.spawn("counter", counter(&id, &journal));
+    for _ in 0..3 {
+        let _ = first.tell(Command::Increment);
+    }
+    assert_eq!(first.ask(Command::Count).await, Ok(3));
+    first.stop();
+    first.wait_for_stop().await;
+    drop(journal);
+
+    let journal = PostgresJournal::connect(url).unwrap();
+    assert_eq!(journal.highest_sequence_nr(&id).unwrap(), 3);
+    let second = system.spawn("counter", counter(&id, &journal));
+    let _ = second.tell(Command::Increment);
+    assert_eq!(second.ask(Command::Count).await, Ok(4));
+
+    let events: Vec<(u64, Incremented)> = journal.replay(&id, 4).unwrap();
+    assert_eq!(events, [(4, Incremented)]);
+    assert!(Journal::append(&journal, &id, 2, &[Incremented]).is_err());
+
+    // the last event is kept, so that the sequence numbers continue
+    Journal::<Incremented>::delete_to(&journal, &id, 10).unwrap();
+    let events: Vec<(u64, Incremented)> = journal.replay(&id, 1).unwrap();
+    assert_eq!(events, [(4, Incremented)]);
+    assert!(Journal::append(&journal, &id, 5, &[Incremented]).is_ok());
+}
+
+/// Concurrent appends are written in batches, a conflicting append only fails itself.
+fn batches(url: &str) {
+    let journal = PostgresJournal::connect(url).unwrap();
+    let tag = unique("numbers");
+    let tagger = tag.clone();
+    let tagged = Arc::new(TaggedJournal::new(journal.clone(), move |_: &u64| {
+        vec![tagger.clone()]
+    }));
+    let ids: Vec<String> = (0..8).map(|i| unique(&format!("numbers-{i}"))).collect();
+    std::thread::scope(|scope| {
+        for id in &ids {
+            let tagged = Arc::clone(&tagged);
+            let _handle = scope.spawn(move || {
+                for n in 1..=10 {
+                    tagged.append(id, n, &[n]).unwrap();
+                    assert!(tagged.append(id, n, &[n]).is_err());
+                }
+            });
+        }
+    });
+
+    // the offsets have no gaps
+    let (tx, rx) = std::sync::mpsc::channel();
+    let sink = Box::new(move |envelope: EventEnvelope<u64>| {
+        tx.send((envelope.offset, envelope.event)).is_ok()
+    });
+    tagged.subscribe(&tag, 0, sink).unwrap();
+    let events: Vec<(u64, u64)> = rx.try_iter().collect();
+    assert_eq!(events.len(), 80);
+    assert!(events.windows(2).all(|w| w[1].0 == w[0].0 + 1));
+
+    // appended events are followed live
+    let last = events[79].0;
+    tagged.append(&ids[0], 11, &[11]).unwrap();
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), [(last + 1, 11)]);
+    assert_eq!(journal.replay(&ids[0], 10).unwrap(), [(10, 10), (11, 11)]);
+
+    let projection = unique("projection");
+    assert_eq!(journal.load(&projection).unwrap(), None);
+    journal.save(&projection, last).unwrap();
+    assert_eq!(journal.load(&projection).unwrap(), Some(last));
+}
+
+/// Runs against the database in `DATABASE_URL`, e.g. `postgres://localhost/elliot`.
+#[test]
+#[ignore = "needs DATABASE_URL"]
+fn test() {
+    let url = std::env::var("DATABASE_URL").expect("DATABASE_URL is not set");
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(postgres_journal(&url));
+    batches(&url);
+}
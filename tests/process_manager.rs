@@ -0,0 +1,115 @@
+use elliot::{ActorSystem, Compensation, ProcessManager, ProcessStep};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+#[derive(Debug)]
+enum Booking {
+    Flight(u32),
+    Hotel(u32),
+    Failed(u32),
+}
+
+impl Booking {
+    fn trip(&self) -> u32 {
+        match self {
+            Self::Flight(trip) | Self::Hotel(trip) | Self::Failed(trip) => *trip,
+        }
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq)]
+struct Trip {
+    flight: bool,
+    hotel: bool,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Outcome {
+    Completed(u32, Trip),
+    Compensated(u32, Trip, Compensation),
+}
+
+async fn process_manager() {
+    let system = ActorSystem::new();
+    let outcomes = Arc::new(Mutex::new(Vec::new()));
+
+    let completed = Arc::clone(&outcomes);
+    let compensated = Arc::clone(&outcomes);
+    let trips = ProcessManager::new(Booking::trip, |trip: &mut Trip, msg| {
+        match msg {
+            Booking::Flight(_) => trip.flight = true,
+            Booking::Hotel(_) => trip.hotel = true,
+            Booking::Failed(_) => return ProcessStep::Compensate,
+        }
+        if trip.flight && trip.hotel {
+            ProcessStep::Complete
+        } else {
+            ProcessStep::Continue
+        }
+    })
+    .timeout(Duration::from_millis(50))
+    .on_complete(move |key, trip| {
+        completed
+            .lock()
+            .unwrap()
+            .push(Outcome::Completed(key, trip));
+    })
+    .on_compensate(move |key, trip, reason| {
+        compensated
+            .lock()
+            .unwrap()
+            .push(Outcome::Compensated(key, trip, reason));
+    });
+    let trips = system.spawn("trips", trips);
+
+    let _ = trips.tell(Booking::Flight(1));
+    let _ = trips.tell(Booking::Flight(2));
+    let _ = trips.tell(Booking::Hotel(3));
+    let _ = trips.tell(Booking::Hotel(1));
+    let _ = trips.tell(Booking::Failed(2));
+
+    // trip 3 never gets a flight and times out
+    tokio::time::sleep(Duration::from_millis(200)).await;
+
+    let outcomes = std::mem::take(&mut *outcomes.lock().unwrap());
+    assert_eq!(
+        outcomes,
+        vec![
+            Outcome::Completed(
+                1,
+                Trip {
+                    flight: true,
+                    hotel: true
+                }
+            ),
+            Outcome::Compensated(
+                2,
+                Trip {
+                    flight: true,
+                    hotel: false
+                },
+                Compensation::Requested
+            ),
+            Outcome::Compensated(
+                3,
+                Trip {
+                    flight: false,
+                    hotel: true
+                },
+                Compensation::TimedOut
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(process_manager());
+}
@@ -0,0 +1,204 @@
+use elliot::{
+    BoxErr, EventEnvelope, InMemoryJournal, InMemoryOffsetStore, Journal, OffsetStore, Projection,
+};
+use std::{
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    time::Duration,
+};
+
+fn journal() -> InMemoryJournal<u64> {
+    let journal = InMemoryJournal::new().tagged(|_: &u64| vec!["numbers".to_owned()]);
+    journal.append("first", 1, &[1, 2, 3]).unwrap();
+    journal.append("second", 1, &[4]).unwrap();
+    journal
+}
+
+/// Fails the first time that it sees `fail`.
+fn fail_once(fail: u64) -> impl Fn(u64) -> Result<(), BoxErr> {
+    let failed = AtomicBool::new(false);
+    move |event| {
+        if event == fail && !failed.swap(true, Ordering::SeqCst) {
+            return Err("handler failed".into());
+        }
+        Ok(())
+    }
+}
+
+/// Wait until `handled` contains `n` events.
+async fn handled_events<T: Clone>(handled: &Mutex<Vec<T>>, n: usize) -> Vec<T> {
+    tokio::time::timeout(Duration::from_secs(5), async {
+        loop {
+            let handled = handled.lock().unwrap().clone();
+            if handled.len() >= n {
+                return handled;
+            }
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap()
+}
+
+async fn at_least_once() {
+    let journal = journal();
+    let offsets = InMemoryOffsetStore::new();
+    let handled = Arc::new(Mutex::new(Vec::new()));
+    let fail = Arc::new(fail_once(3));
+    let projection = |save_every| {
+        let (handled, fail) = (Arc::clone(&handled), Arc::clone(&fail));
+        Projection::at_least_once(
+            "numbers",
+            journal.clone(),
+            "numbers",
+            offsets.clone(),
+            move |envelope: EventEnvelope<u64>| {
+                let result = fail(envelope.event);
+                if result.is_ok() {
+                    handled.lock().unwrap().push(envelope.event);
+                }
+                async move { result }
+            },
+        )
+        .save_every(save_every)
+    };
+
+    // the offset of the events before the failure is not saved yet
+    assert!(projection(10).run().await.is_err());
+    assert_eq!(*handled.lock().unwrap(), [1, 2]);
+    assert_eq!(offsets.load("numbers").unwrap(), None);
+
+    // they are handled again, and the projection follows the new events
+    let retry = projection(1);
+    let running = tokio::spawn(async move { retry.run().await });
+    journal.append("second", 2, &[5]).unwrap();
+    assert_eq!(handled_events(&handled, 7).await, [1, 2, 1, 2, 3, 4, 5]);
+    running.abort();
+    assert_eq!(offsets.load("numbers").unwrap(), Some(5));
+}
+
+/// A database that stores its rows and the offset in one transaction.
+#[derive(Clone, Default)]
+struct Database(Arc<Mutex<(Vec<u64>, Option<u64>)>>);
+
+impl OffsetStore for Database {
+    fn save(&self, _: &str, offset: u64) -> Result<(), BoxErr> {
+        self.0.lock().unwrap().1 = Some(offset);
+        Ok(())
+    }
+
+    fn load(&self, _: &str) -> Result<Option<u64>, BoxErr> {
+        Ok(self.0.lock().unwrap().1)
+    }
+}
+
+async fn exactly_once() {
+    let journal = journal();
+    let database = Database::default();
+    let fail = Arc::new(fail_once(3));
+    let projection = || {
+        let (database, fail) = (database.clone(), Arc::clone(&fail));
+        Projection::exactly_once(
+            "numbers",
+            journal.clone(),
+            "numbers",
+            database.clone(),
+            move |envelope: EventEnvelope<u64>| {
+                let result = fail(envelope.event).map(|()| {
+                    let mut transaction = database.0.lock().unwrap();
+                    transaction.0.push(envelope.event);
+                    transaction.1 = Some(envelope.offset);
+                });
+                async move { result }
+            },
+        )
+    };
+
+    assert!(projection().run().await.is_err());
+    assert_eq!(database.load("numbers").unwrap(), Some(2));
+
+    // the retry resumes after the last committed event
+    let retry = projection();
+    let running = tokio::spawn(async move { retry.run().await });
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while database.load("numbers").unwrap() != Some(4) {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    running.abort();
+    assert_eq!(database.0.lock().unwrap().0, [1, 2, 3, 4]);
+}
+
+/// The projection resumes from the offset that it saved in the database of the journal.
+#[cfg(feature = "sqlite")]
+async fn durable() {
+    let path = std::env::temp_dir().join(format!("elliot-projection-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+    let open = || {
+        let journal = elliot::SqliteJournal::open(&path).unwrap();
+        let tagged =
+            elliot::TaggedJournal::new(journal.clone(), |_: &u64| vec!["numbers".to_owned()]);
+        (tagged, journal)
+    };
+    let run = |(journal, offsets): (
+        elliot::TaggedJournal<u64, elliot::SqliteJournal>,
+        elliot::SqliteJournal,
+    )| {
+        let handled = Arc::new(Mutex::new(Vec::new()));
+        let projection = Projection::at_least_once("numbers", journal, "numbers", offsets, {
+            let handled = Arc::clone(&handled);
+            move |envelope: EventEnvelope<u64>| {
+                handled.lock().unwrap().push(envelope.event);
+                async { Ok(()) }
+            }
+        });
+        (tokio::spawn(async move { projection.run().await }), handled)
+    };
+
+    let (journal, offsets) = open();
+    journal.append("first", 1, &[1, 2]).unwrap();
+    let (running, handled) = run((journal.clone(), offsets.clone()));
+    assert_eq!(handled_events(&handled, 2).await, [1, 2]);
+    running.abort();
+    let _ = running.await;
+    assert_eq!(offsets.load("numbers").unwrap(), Some(2));
+    drop((journal, offsets));
+
+    // after a restart, only the new events are handled
+    let (journal, offsets) = open();
+    journal.append("first", 3, &[3]).unwrap();
+    let (running, handled) = run((journal.clone(), offsets));
+    journal.append("second", 1, &[4]).unwrap();
+    assert_eq!(handled_events(&handled, 2).await, [3, 4]);
+    running.abort();
+
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+}
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(at_least_once());
+    runtime.block_on(exactly_once());
+}
+
+#[cfg(feature = "sqlite")]
+#[test]
+fn test_durable() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(durable());
+}
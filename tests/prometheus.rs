@@ -0,0 +1,78 @@
+#![cfg(feature = "prometheus")]
+
+use elliot::{prometheus, ActorSystem, Behaviors};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    net::{TcpListener, TcpStream},
+};
+
+async fn handle(msg: bool) -> Behaviors {
+    if msg {
+        Behaviors::Same
+    } else {
+        Behaviors::Unhandled
+    }
+}
+
+async fn metrics() {
+    let system = ActorSystem::new();
+    let actor = system.spawn("quote\"d", handle);
+    for msg in [true, true, false] {
+        let _ = actor.tell(msg);
+    }
+
+    while system
+        .actors()
+        .into_iter()
+        .find(|a| a.path.name() == "quote\"d")
+        .unwrap()
+        .dead_letters
+        == 0
+    {
+        tokio::task::yield_now().await;
+    }
+
+    let text = prometheus::to_text(&system);
+    let path = r#"{path="/user/quote\"d""#;
+    assert!(text.contains("# TYPE elliot_actor_messages_total counter\n"));
+    assert!(text.contains(&format!("elliot_actor_messages_total{path}}} 3\n")));
+    assert!(text.contains(&format!("elliot_actor_dead_letters_total{path}}} 1\n")));
+    assert!(text.contains(&format!("elliot_actor_mailbox_len{path}}} 0\n")));
+    assert!(text.contains("# TYPE elliot_actor_handling_seconds histogram\n"));
+    assert!(text.contains(&format!(
+        "elliot_actor_handling_seconds_bucket{path},le=\"+Inf\"}} 3\n"
+    )));
+    assert!(text.contains(&format!("elliot_actor_handling_seconds_count{path}}} 3\n")));
+
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    drop(listener);
+    let _server = tokio::spawn(prometheus::serve(system, addr));
+
+    let mut stream = loop {
+        match TcpStream::connect(addr).await {
+            Ok(stream) => break stream,
+            Err(_) => tokio::task::yield_now().await,
+        }
+    };
+    stream
+        .write_all(b"GET /metrics HTTP/1.1\r\n\r\n")
+        .await
+        .unwrap();
+    let mut response = String::new();
+    let _ = stream.read_to_string(&mut response).await.unwrap();
+
+    assert!(response.starts_with("HTTP/1.1 200 OK"));
+    assert!(response.contains("content-type: text/plain; version=0.0.4"));
+    assert!(response.ends_with(&format!("elliot_actor_handling_seconds_count{path}}} 3\n")));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .unwrap()
+        .block_on(metrics());
+}
@@ -0,0 +1,105 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{ActorRef, ActorSystem, Behaviors, BoxErr, Cluster, ClusterSettings, MessageCodec};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn node(seeds: &[String]) -> (ActorSystem, Cluster) {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let settings = ClusterSettings::new(seeds.to_vec()).gossip_interval(Duration::from_millis(20));
+    let cluster = system.join_cluster("127.0.0.1:0", settings).await.unwrap();
+    (system, cluster)
+}
+
+type Received = Arc<Mutex<Vec<(&'static str, String)>>>;
+
+fn subscriber(system: &ActorSystem, name: &'static str, received: &Received) -> ActorRef<String> {
+    let received = Arc::clone(received);
+    system.spawn(name, move |event: String| {
+        received.lock().unwrap().push((name, event));
+        async { Behaviors::Same }
+    })
+}
+
+async fn pubsub() {
+    let (first_system, first) = node(&[]).await;
+    let seeds = [first.address().to_owned()];
+    let (second_system, second) = node(&seeds).await;
+    let (_third_system, third) = node(&seeds).await;
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while [&first, &second, &third]
+            .iter()
+            .any(|cluster| cluster.members().len() != 3)
+        {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+
+    let received = Received::default();
+    let first_topic = first.topic::<String>("news").unwrap();
+    let second_topic = second.topic::<String>("news").unwrap();
+    let third_topic = third.topic::<String>("news").unwrap();
+    let other_topic = second.topic::<String>("sports").unwrap();
+
+    let alice = subscriber(&first_system, "alice", &received);
+    let bob = subscriber(&second_system, "bob", &received);
+    let carol = subscriber(&second_system, "carol", &received);
+    first_topic.subscribe(&alice);
+    second_topic.subscribe(&bob);
+    second_topic.subscribe(&carol);
+    other_topic.subscribe(&carol);
+
+    // the third node has no subscribers, but can still publish
+    third_topic.publish("hello".to_owned());
+    first_topic.publish("world".to_owned());
+
+    tokio::time::timeout(Duration::from_secs(5), async {
+        while received.lock().unwrap().len() < 6 {
+            tokio::time::sleep(Duration::from_millis(5)).await;
+        }
+    })
+    .await
+    .unwrap();
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort();
+    assert_eq!(
+        received,
+        [
+            ("alice", "hello".to_owned()),
+            ("alice", "world".to_owned()),
+            ("bob", "hello".to_owned()),
+            ("bob", "world".to_owned()),
+            ("carol", "hello".to_owned()),
+            ("carol", "world".to_owned()),
+        ]
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(pubsub());
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "quic")]
+
+use elliot::{
+    rustls::{
+        crypto::{ring, CryptoProvider},
+        pki_types::{CertificateDer, PrivateKeyDer},
+        server::WebPkiClientVerifier,
+        ClientConfig, RootCertStore, ServerConfig,
+    },
+    ActorSystem, BoxErr, Credentials, MessageCodec, Meta, QuicTransport, Transport,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::{
+    io::{AsyncReadExt, AsyncWriteExt},
+    sync::mpsc,
+};
+
+const CA: &[u8] = include_bytes!("tls/ca.der");
+const SERVER: &[u8] = include_bytes!("tls/server.der");
+const SERVER_KEY: &[u8] = include_bytes!("tls/server.key.der");
+const CLIENT: &[u8] = include_bytes!("tls/client.der");
+const CLIENT_KEY: &[u8] = include_bytes!("tls/client.key.der");
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+/// Mutual TLS, every system is verified with a certificate from the test CA.
+fn transport() -> QuicTransport {
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from(CA)).unwrap();
+    let roots = Arc::new(roots);
+
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::clone(&roots), provider())
+        .build()
+        .unwrap();
+    let server = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![CertificateDer::from(SERVER.to_vec())],
+            PrivateKeyDer::Pkcs8(SERVER_KEY.to_vec().into()),
+        )
+        .unwrap();
+    let client = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_client_auth_cert(
+            vec![CertificateDer::from(CLIENT.to_vec())],
+            PrivateKeyDer::Pkcs8(CLIENT_KEY.to_vec().into()),
+        )
+        .unwrap();
+    QuicTransport::new(Arc::new(client), Arc::new(server)).unwrap()
+}
+
+fn authenticate(credentials: &Credentials<'_>) -> Result<Arc<str>, BoxErr> {
+    match credentials.certificate {
+        Some(CLIENT) => Ok("client.elliot.test".into()),
+        _ => Err("unknown certificate".into()),
+    }
+}
+
+async fn quic() {
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    server.set_transport(transport());
+    server.set_authenticator(authenticate);
+    let addr = server.bind("127.0.0.1:0").await.unwrap();
+
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let inbox = server.spawn("inbox", move |meta: Meta, msg: String| {
+        let _ = tx.send((meta.peer, msg));
+        async {}
+    });
+    server.expose(&inbox).unwrap();
+
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_transport(transport());
+    let remote = client
+        .remote_ref::<String>(&format!("elliot://{addr}/user/inbox"))
+        .unwrap();
+    remote.tell("over quic".to_owned()).unwrap();
+    let (peer, msg) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(peer.as_deref(), Some("client.elliot.test"));
+    assert_eq!(msg, "over quic");
+}
+
+/// The connections to one system are streams of the same QUIC connection.
+async fn streams() {
+    let transport = transport();
+    let mut listener = transport
+        .bind("127.0.0.1:0".parse().unwrap())
+        .await
+        .unwrap();
+    let authority = listener.local_addr().unwrap().to_string();
+
+    // the listener has to accept for the handshake to complete
+    let echo = tokio::spawn(async move {
+        for _ in 0..2 {
+            let mut accepted = listener.accept().await.unwrap();
+            assert_eq!(accepted.certificate.as_deref(), Some(CLIENT));
+            let _handle = tokio::spawn(async move {
+                let mut buf = [0; 6];
+                let n = accepted.connection.read(&mut buf).await.unwrap();
+                accepted.connection.write_all(&buf[..n]).await.unwrap();
+            });
+        }
+        listener
+    });
+
+    let mut first = transport.connect(&authority).await.unwrap();
+    let mut second = transport.connect(&authority).await.unwrap();
+    second.write_all(b"second").await.unwrap();
+    first.write_all(b"first").await.unwrap();
+    let _listener = echo.await.unwrap();
+
+    let mut buf = [0; 6];
+    first.read_exact(&mut buf[..5]).await.unwrap();
+    assert_eq!(&buf[..5], b"first");
+    second.read_exact(&mut buf).await.unwrap();
+    assert_eq!(&buf, b"second");
+}
+
+#[test]
+fn test() {
+    let runtime = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap();
+    runtime.block_on(quic());
+    runtime.block_on(streams());
+}
@@ -0,0 +1,62 @@
+use elliot::{ActorSystem, Behavior, Behaviors};
+use tokio::sync::mpsc;
+
+type Reply = mpsc::UnboundedSender<String>;
+
+#[derive(Clone, Debug)]
+enum Msg {
+    Greet(String, Reply),
+    Add(u32, u32, Reply),
+    Shout(String, Reply),
+}
+
+async fn receive_match() {
+    let system = ActorSystem::new();
+    let mut dead_letters = system.dead_letters();
+
+    let greetings = Behaviors::receive_match(|msg: Msg| match msg {
+        Msg::Greet(name, reply) => Some(async move {
+            let _ = reply.send(format!("Hello, {name}"));
+        }),
+        _ => None,
+    });
+    let sums = Behaviors::receive_match(|msg: Msg| match msg {
+        Msg::Add(a, b, reply) => Some(async move {
+            let _ = reply.send((a + b).to_string());
+        }),
+        _ => None,
+    });
+    // only short messages can be shouted
+    let shouts = Behaviors::receive_if(
+        |msg: &Msg| matches!(msg, Msg::Shout(text, _) if text.len() < 10),
+        |msg: Msg| async move {
+            if let Msg::Shout(text, reply) = msg {
+                let _ = reply.send(text.to_uppercase());
+            }
+        },
+    );
+    let actor = system.spawn("protocols", greetings.or_else(sums).or_else(shouts));
+
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    actor.tell(Msg::Greet("Alice".into(), tx.clone())).unwrap();
+    actor.tell(Msg::Add(40, 2, tx.clone())).unwrap();
+    actor.tell(Msg::Shout("hey".into(), tx.clone())).unwrap();
+    assert_eq!(rx.recv().await.unwrap(), "Hello, Alice");
+    assert_eq!(rx.recv().await.unwrap(), "42");
+    assert_eq!(rx.recv().await.unwrap(), "HEY");
+
+    actor
+        .tell(Msg::Shout("way too long to shout".into(), tx))
+        .unwrap();
+    let dead_letter = dead_letters.recv().await.unwrap();
+    assert_eq!(dead_letter.recipient.as_str(), "/user/protocols");
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(receive_match());
+}
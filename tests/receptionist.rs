@@ -0,0 +1,59 @@
+use elliot::{ActorSystem, ListingUpdate, ServiceKey};
+
+async fn receptionist() {
+    let system = ActorSystem::new();
+    let receptionist = system.receptionist();
+    let key = ServiceKey::<u32>::new("workers");
+    assert!(receptionist.find(&key).is_empty());
+
+    let first = system.spawn("first", |_: u32| async {});
+    receptionist.register(&key, &first);
+    // registering twice is ignored
+    receptionist.register(&key, &first);
+    // the same id for another message type is another key
+    receptionist.register(
+        &ServiceKey::new("workers"),
+        &system.spawn("other", |(): ()| async {}),
+    );
+    assert_eq!(receptionist.find(&key).len(), 1);
+
+    // the subscription starts with the current services
+    let mut listing = receptionist.subscribe(&key);
+    let Some(ListingUpdate::Registered(service)) = listing.recv().await else {
+        panic!("expected the first service");
+    };
+    assert_eq!(service.path(), first.path());
+
+    let second = system.spawn("second", |_: u32| async {});
+    receptionist.register(&key, &second);
+    let Some(ListingUpdate::Registered(service)) = listing.recv().await else {
+        panic!("expected the second service");
+    };
+    assert_eq!(service.path(), second.path());
+    let found = receptionist.find(&key);
+    let paths = found.iter().map(|s| s.path().as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, ["/user/first", "/user/second"]);
+
+    receptionist.deregister(&key, &first);
+    let Some(ListingUpdate::Deregistered(path)) = listing.recv().await else {
+        panic!("expected the first service to be deregistered");
+    };
+    assert_eq!(path, *first.path());
+
+    // stopped services are deregistered
+    second.stop();
+    let Some(ListingUpdate::Deregistered(path)) = listing.recv().await else {
+        panic!("expected the second service to be deregistered");
+    };
+    assert_eq!(path, *second.path());
+    assert!(receptionist.find(&key).is_empty());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(receptionist());
+}
@@ -0,0 +1,74 @@
+use elliot::{ActorSystem, Behavior, LifecycleEvent, Meta, Supervision};
+use std::io;
+use tokio::sync::mpsc;
+
+async fn redeliver() {
+    let system = ActorSystem::builder()
+        .supervision(Supervision::Resume)
+        .build();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // fails the first two deliveries of every message
+    let flaky = move |meta: Meta, msg: &'static str| {
+        let _ = tx.send((msg, meta.redeliveries));
+        async move {
+            if meta.redeliveries < 2 {
+                Err(io::Error::other("blip"))
+            } else {
+                Ok(())
+            }
+        }
+    };
+    let actor = system.spawn("flaky", flaky.redeliver(2));
+    let mut dead_letters = system.dead_letters();
+    actor.tell("first").unwrap();
+    actor.tell("second").unwrap();
+
+    let mut received = Vec::new();
+    for _ in 0..6 {
+        received.push(rx.recv().await.unwrap());
+    }
+    for msg in ["first", "second"] {
+        let redeliveries = received
+            .iter()
+            .filter(|(m, _)| *m == msg)
+            .map(|(_, redeliveries)| *redeliveries)
+            .collect::<Vec<_>>();
+        assert_eq!(redeliveries, [0, 1, 2]);
+    }
+
+    // without enough redeliveries the message ends up with the supervision
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let flaky = move |meta: Meta, (): ()| {
+        let _ = tx.send(meta.redeliveries);
+        async { Err::<(), _>(io::Error::other("down")) }
+    };
+    let actor = system.spawn("down", flaky.redeliver(1));
+    actor.tell(()).unwrap();
+    assert_eq!(rx.recv().await, Some(0));
+    assert_eq!(rx.recv().await, Some(1));
+    assert_eq!(dead_letters.recv().await.unwrap().recipient, *actor.path());
+
+    // under the default supervision, the actor crashes once the redeliveries are used up
+    let system = ActorSystem::new();
+    let mut events = system.lifecycle_events();
+    let crash = |(): ()| async { Err::<(), _>(io::Error::other("down")) };
+    let actor = system.spawn("crash", crash.redeliver(3));
+    actor.tell(()).unwrap();
+    loop {
+        match events.recv().await {
+            Some(LifecycleEvent::Crashed(e)) => break assert_eq!(e.path, *actor.path()),
+            Some(_) => {}
+            None => panic!("no crash event"),
+        }
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(redeliver());
+}
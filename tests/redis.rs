@@ -0,0 +1,131 @@
+#![cfg(feature = "redis")]
+
+use elliot::{
+    ActorSystem, BoxErr, CodecJournal, CodecSnapshotStore, EventEnvelope, Journal, MessageCodec,
+    ReadJournal, RedisBridge, RedisJournal, RedisSnapshotStore, SnapshotStore, TaggedJournal,
+};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+struct Number;
+
+impl MessageCodec<u64> for Number {
+    fn encode(&self, msg: &u64) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.to_be_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<u64, BoxErr> {
+        Ok(u64::from_be_bytes(bytes.try_into()?))
+    }
+}
+
+async fn bridged(
+    url: &str,
+    tx: mpsc::UnboundedSender<(&'static str, String)>,
+    name: &'static str,
+) -> ActorSystem {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let events = system.spawn("events", move |event: String| {
+        let _ = tx.send((name, event));
+        async {}
+    });
+    system.subscribe(&events);
+    let client = redis::Client::open(url).unwrap();
+    let bridge = RedisBridge::connect(&system, client).await.unwrap();
+    bridge.bridge::<String>("elliot-events").await.unwrap();
+    system
+}
+
+/// Runs against the server in `REDIS_URL`, e.g. `redis://localhost`.
+async fn redis_bridge() {
+    let url = std::env::var("REDIS_URL").expect("REDIS_URL is not set");
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let first = bridged(&url, tx.clone(), "first").await;
+    let second = bridged(&url, tx, "second").await;
+
+    // events are shared in both directions, and not sent back
+    let _ = first.publish("from first".to_owned());
+    let _ = second.publish("from second".to_owned());
+    let mut events = Vec::new();
+    while let Ok(Some(event)) =
+        tokio::time::timeout(Duration::from_millis(500), received.recv()).await
+    {
+        events.push(event);
+    }
+    events.sort();
+    assert_eq!(
+        events,
+        [
+            ("first", "from first".to_owned()),
+            ("first", "from second".to_owned()),
+            ("second", "from first".to_owned()),
+            ("second", "from second".to_owned()),
+        ]
+    );
+}
+
+/// Runs against the server in `REDIS_URL`, with keys that are unique to this run.
+#[test]
+#[ignore = "needs REDIS_URL"]
+fn persistence() {
+    let url = std::env::var("REDIS_URL").expect("REDIS_URL is not set");
+    let client = redis::Client::open(url).unwrap();
+    let nanos = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap()
+        .as_nanos();
+    let prefix = format!("elliot-test-{}-{nanos}", std::process::id());
+
+    let journal = RedisJournal::connect(&client).unwrap().prefix(&*prefix);
+    let journal = CodecJournal::new(journal, Number);
+    journal.append("counter", 1, &[10, 20]).unwrap();
+    journal.append("counter", 3, &[30]).unwrap();
+    assert!(journal.append("counter", 3, &[31]).is_err());
+    assert!(journal.append("counter", 5, &[50]).is_err());
+    assert_eq!(journal.replay("counter", 2).unwrap(), [(2, 20), (3, 30)]);
+    assert_eq!(journal.replay("other", 1).unwrap(), []);
+
+    // every event has an offset, tagged events can be queried and are followed live
+    let tagged = TaggedJournal::new(journal.clone(), |_: &u64| vec!["numbers".to_owned()]);
+    tagged.append("tagged", 1, &[1, 2]).unwrap();
+    let (tx, rx) = std::sync::mpsc::channel();
+    let sink = Box::new(move |envelope: EventEnvelope<u64>| {
+        tx.send((envelope.offset, envelope.event)).is_ok()
+    });
+    tagged.subscribe("numbers", 4, sink).unwrap();
+    tagged.append("tagged", 3, &[3]).unwrap();
+    assert_eq!(rx.try_iter().collect::<Vec<_>>(), [(5, 2), (6, 3)]);
+
+    let snapshots = RedisSnapshotStore::connect(&client)
+        .unwrap()
+        .prefix(&*prefix);
+    let snapshots = CodecSnapshotStore::new(snapshots, Number);
+    assert_eq!(snapshots.load("counter").unwrap(), None);
+    snapshots.save("counter", 2, &30).unwrap();
+    snapshots.save("counter", 3, &60).unwrap();
+    assert_eq!(snapshots.load("counter").unwrap(), Some((3, 60)));
+}
+
+#[test]
+#[ignore = "needs REDIS_URL"]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(redis_bridge());
+}
@@ -0,0 +1,136 @@
+use elliot::{
+    ActorSystem, Delivery, InMemoryJournal, InMemorySnapshotStore, Outbox, OutboxSnapshot,
+    ReliableRef, SnapshotStore,
+};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+async fn reliable() {
+    let system = ActorSystem::new();
+    let journal = InMemoryJournal::new();
+    let (tx, mut received) = mpsc::unbounded_channel();
+
+    // this worker fails to ack the odd jobs
+    let worker = system.spawn("worker", move |job: Delivery<u32>| {
+        let tx = tx.clone();
+        async move {
+            if job.msg().is_multiple_of(2) {
+                job.ack().await.unwrap();
+            }
+            let _ = tx.send(*job.msg());
+        }
+    });
+
+    let jobs = ReliableRef::recover("jobs", journal.clone(), &worker)
+        .await
+        .unwrap();
+    for job in 1..=4 {
+        jobs.tell(job).await.unwrap();
+    }
+    for job in 1..=4 {
+        assert_eq!(received.recv().await, Some(job));
+    }
+    assert_eq!(jobs.pending(), [1, 3]);
+
+    // the outbox survives a restart and the unacked jobs are sent again
+    drop(jobs);
+    let jobs = ReliableRef::recover("jobs", journal.clone(), &worker)
+        .await
+        .unwrap();
+    assert_eq!(jobs.pending(), [1, 3]);
+    assert_eq!(received.recv().await, Some(1));
+    assert_eq!(received.recv().await, Some(3));
+
+    // jobs that are told while the worker is gone are kept as well
+    worker.stop();
+    worker.wait_for_stop().await;
+    jobs.tell(5).await.unwrap();
+    assert_eq!(jobs.pending(), [1, 3, 5]);
+
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let worker = system.spawn("worker", move |job: Delivery<u32>| {
+        let tx = tx.clone();
+        async move {
+            job.ack().await.unwrap();
+            let _ = tx.send(*job.msg());
+        }
+    });
+    let jobs = ReliableRef::recover("jobs", journal.clone(), &worker)
+        .await
+        .unwrap()
+        .redeliver_every(Duration::from_millis(10));
+    for job in [1, 3, 5] {
+        assert_eq!(received.recv().await, Some(job));
+    }
+    while !jobs.pending().is_empty() {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    tokio::time::sleep(Duration::from_millis(30)).await;
+    assert!(received.try_recv().is_err());
+
+    let events = journal.events("jobs");
+    assert_eq!(events[0], Outbox::Sent { id: 1, msg: 1 });
+    assert_eq!(events.last(), Some(&Outbox::Acked { id: 5 }));
+}
+
+/// Snapshots replace the events of the outbox, which are deleted from the journal.
+async fn snapshots() {
+    let system = ActorSystem::new();
+    let journal = InMemoryJournal::new();
+    let snapshots = InMemorySnapshotStore::new();
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let worker = system.spawn("worker", move |job: Delivery<u32>| {
+        let tx = tx.clone();
+        async move {
+            if job.msg().is_multiple_of(2) {
+                job.ack().await.unwrap();
+            }
+            let _ = tx.send(*job.msg());
+        }
+    });
+
+    let jobs =
+        ReliableRef::recover_with_snapshots("jobs", journal.clone(), snapshots.clone(), 4, &worker)
+            .await
+            .unwrap();
+    for job in 1..=100 {
+        jobs.tell(job).await.unwrap();
+        assert_eq!(received.recv().await, Some(job));
+    }
+    assert_eq!(jobs.pending().len(), 50);
+    // the 150 events are covered by snapshots, up to the last one
+    assert!(journal.events("jobs").len() < 4);
+    let (_, snapshot) = SnapshotStore::<OutboxSnapshot<u32>>::load(&snapshots, "jobs")
+        .unwrap()
+        .unwrap();
+    assert_eq!(snapshot.last_id, 99);
+    drop(jobs);
+
+    let jobs = ReliableRef::recover_with_snapshots("jobs", journal, snapshots, 4, &worker)
+        .await
+        .unwrap();
+    assert_eq!(jobs.pending(), (1..=100).step_by(2).collect::<Vec<_>>());
+    assert_eq!(received.recv().await, Some(1));
+    jobs.tell(101).await.unwrap();
+    assert_eq!(jobs.pending().last(), Some(&101));
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(reliable());
+}
+
+#[test]
+fn test_snapshots() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(snapshots());
+}
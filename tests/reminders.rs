@@ -0,0 +1,57 @@
+use elliot::{ActorSystem, Behaviors, InMemoryJournal, Reminders};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+async fn reminders() {
+    let system = ActorSystem::new();
+    let journal = InMemoryJournal::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+
+    let log = Arc::clone(&received);
+    let entity = system.spawn("entity", move |msg: &'static str| {
+        log.lock().unwrap().push(msg);
+        async { Behaviors::Same }
+    });
+
+    let reminders = Reminders::recover("entity", journal.clone(), &entity).unwrap();
+    reminders
+        .remind("soon", Duration::from_millis(10), "soon")
+        .unwrap();
+    reminders
+        .remind("later", Duration::from_millis(100), "later")
+        .unwrap();
+    reminders
+        .remind("never", Duration::from_millis(10), "never")
+        .unwrap();
+    assert!(reminders.cancel("never").unwrap());
+    assert!(!reminders.cancel("never").unwrap());
+
+    tokio::time::sleep(Duration::from_millis(50)).await;
+    assert_eq!(*received.lock().unwrap(), ["soon"]);
+    assert_eq!(reminders.pending(), ["later"]);
+
+    // the timers stop, as if the process had stopped
+    drop(reminders);
+
+    let reminders = Reminders::recover("entity", journal.clone(), &entity).unwrap();
+    assert_eq!(reminders.pending(), ["later"]);
+    tokio::time::sleep(Duration::from_millis(100)).await;
+    assert_eq!(*received.lock().unwrap(), ["soon", "later"]);
+    assert!(reminders.pending().is_empty());
+    drop(reminders);
+
+    let reminders = Reminders::recover("entity", journal, &entity).unwrap();
+    assert!(reminders.pending().is_empty());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(reminders());
+}
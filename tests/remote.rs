@@ -0,0 +1,157 @@
+#![cfg(feature = "remote")]
+
+use elliot::{
+    ActorRef, ActorSystem, Behaviors, BoxErr, FailureDetection, MessageCodec, RemoteError,
+    Terminated,
+};
+use std::{
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn remote() {
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    let addr = server.bind("127.0.0.1:0").await.unwrap();
+
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&received);
+    let greeter = server.spawn("greeter", move |msg: String| {
+        log.lock().unwrap().push(msg);
+        async { Behaviors::Same }
+    });
+    server.expose(&greeter).unwrap();
+
+    let client = ActorSystem::new();
+    assert_eq!(
+        client
+            .remote_ref::<String>(&format!("elliot://{addr}/user/greeter"))
+            .unwrap_err(),
+        RemoteError::NoCodec("alloc::string::String")
+    );
+    client.register_codec(Utf8);
+    assert_eq!(
+        client.remote_ref::<String>("tcp://localhost").unwrap_err(),
+        RemoteError::InvalidUri("tcp://localhost".into())
+    );
+
+    let uri = format!("elliot://{addr}/user/greeter");
+    let remote = client.remote_ref::<String>(&uri).unwrap();
+    assert_eq!(remote.path().as_str(), uri);
+    for name in ["Alice", "Bob", "Carol"] {
+        remote.tell(format!("Hello, {name}")).unwrap();
+    }
+
+    while received.lock().unwrap().len() < 3 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(
+        *received.lock().unwrap(),
+        ["Hello, Alice", "Hello, Bob", "Hello, Carol"]
+    );
+}
+
+fn watcher(system: &ActorSystem) -> (ActorRef<Terminated>, Arc<Mutex<Vec<Terminated>>>) {
+    let terminated = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&terminated);
+    let watcher = system.spawn("watcher", move |msg: Terminated| {
+        log.lock().unwrap().push(msg);
+        async { Behaviors::Same }
+    });
+    (watcher, terminated)
+}
+
+async fn next(terminated: &Mutex<Vec<Terminated>>) -> Terminated {
+    loop {
+        if let Some(t) = terminated.lock().unwrap().pop() {
+            return t;
+        }
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+async fn death_watch() {
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    let addr = server.bind("127.0.0.1:0").await.unwrap();
+    let worker = server.spawn("worker", |msg: String| async move {
+        if msg == "stop" {
+            Behaviors::Stopped
+        } else {
+            Behaviors::Same
+        }
+    });
+    server.expose(&worker).unwrap();
+
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_failure_detection(FailureDetection::new(
+        Duration::from_millis(10),
+        Duration::from_millis(100),
+    ));
+    let (watcher, terminated) = watcher(&client);
+
+    // the remote actor stops
+    let uri = format!("elliot://{addr}/user/worker");
+    let remote = client.remote_ref::<String>(&uri).unwrap();
+    client.watch(&remote, &watcher, |t| t);
+    remote.tell("stop".into()).unwrap();
+    let t = next(&terminated).await;
+    assert_eq!(t.path.as_str(), uri);
+    assert!(!t.unreachable);
+
+    // nothing is listening
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let closed_addr = closed.local_addr().unwrap();
+    drop(closed);
+    let uri = format!("elliot://{closed_addr}/user/worker");
+    let remote = client.remote_ref::<String>(&uri).unwrap();
+    client.watch(&remote, &watcher, |t| t);
+    assert!(next(&terminated).await.unreachable);
+
+    // the other side accepts the connection but never answers heartbeats
+    let silent = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let silent_addr = silent.local_addr().unwrap();
+    let _accepted = tokio::spawn(async move {
+        let (_stream, _) = silent.accept().await.unwrap();
+        std::future::pending::<()>().await;
+    });
+    let uri = format!("elliot://{silent_addr}/user/worker");
+    let remote = client.remote_ref::<String>(&uri).unwrap();
+    client.watch(&remote, &watcher, |t| t);
+    let t = next(&terminated).await;
+    assert_eq!(t.path.as_str(), uri);
+    assert!(t.unreachable);
+}
+
+#[test]
+fn watch() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(death_watch());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(remote());
+}
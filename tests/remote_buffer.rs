@@ -0,0 +1,122 @@
+#![cfg(feature = "remote")]
+
+use elliot::{ActorSystem, BoxErr, Eviction, MessageCodec, RemoteBuffer};
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+async fn next(received: &mut mpsc::UnboundedReceiver<String>) -> String {
+    let msg = tokio::time::timeout(Duration::from_secs(5), received.recv()).await;
+    msg.unwrap().unwrap()
+}
+
+async fn remote_buffer() {
+    // nothing is listening yet
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = closed.local_addr().unwrap();
+    drop(closed);
+
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_remote_buffer(Some(
+        RemoteBuffer::new(2)
+            .retry_interval(Duration::from_millis(20))
+            .eviction(Eviction::DropOldest),
+    ));
+    let mut dead_letters = client.dead_letters();
+    let remote = client
+        .remote_ref::<String>(&format!("elliot://{addr}/user/inbox"))
+        .unwrap();
+    for msg in ["first", "second", "third"] {
+        remote.tell(msg.to_owned()).unwrap();
+    }
+    tokio::time::sleep(Duration::from_millis(50)).await;
+
+    // the messages are kept until the other system is up
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let inbox = server.spawn("inbox", move |msg: String| {
+        let _ = tx.send(msg);
+        async {}
+    });
+    server.expose(&inbox).unwrap();
+    let _ = server.bind(addr).await.unwrap();
+
+    // the oldest message did not fit into the buffer
+    assert_eq!(next(&mut received).await, "second");
+    assert_eq!(next(&mut received).await, "third");
+    assert!(dead_letters.recv().await.is_some());
+    remote.tell("fourth".to_owned()).unwrap();
+    assert_eq!(next(&mut received).await, "fourth");
+}
+
+/// The messages for a system that does not come back are dead letters.
+async fn give_up() {
+    let closed = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+    let addr = closed.local_addr().unwrap();
+    drop(closed);
+
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_remote_buffer(Some(
+        RemoteBuffer::new(10)
+            .retry_interval(Duration::from_millis(10))
+            .max_attempts(3),
+    ));
+    let mut dead_letters = client.dead_letters();
+    let remote = client
+        .remote_ref::<String>(&format!("elliot://{addr}/user/inbox"))
+        .unwrap();
+    for msg in ["first", "second"] {
+        remote.tell(msg.to_owned()).unwrap();
+    }
+    for _ in 0..2 {
+        let dead_letter = tokio::time::timeout(Duration::from_secs(5), dead_letters.recv());
+        assert!(dead_letter.await.unwrap().is_some());
+    }
+
+    // the next message tries again
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let inbox = server.spawn("inbox", move |msg: String| {
+        let _ = tx.send(msg);
+        async {}
+    });
+    server.expose(&inbox).unwrap();
+    let _ = server.bind(addr).await.unwrap();
+    remote.tell("third".to_owned()).unwrap();
+    assert_eq!(next(&mut received).await, "third");
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(remote_buffer());
+}
+
+#[test]
+fn test_give_up() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(give_up());
+}
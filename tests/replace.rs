@@ -0,0 +1,59 @@
+use elliot::{ActorContext, ActorSystem, Behavior, Signal};
+use std::sync::Mutex;
+use tokio::sync::{mpsc, oneshot};
+
+type Reply = (u32, mpsc::UnboundedSender<String>);
+
+async fn replace() {
+    let system = ActorSystem::new();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    // the first message blocks the actor until it is released
+    let (started, wait) = oneshot::channel();
+    let (release, released) = oneshot::channel::<()>();
+    let gate = Mutex::new(Some((started, released)));
+    let actor = system.spawn("versioned", move |(n, reply): Reply| {
+        let gate = gate.lock().unwrap().take();
+        async move {
+            if let Some((started, released)) = gate {
+                let _ = started.send(());
+                let _ = released.await;
+            }
+            let _ = reply.send(format!("v1: {n}"));
+        }
+    });
+    actor.tell((1, tx.clone())).unwrap();
+    actor.tell((2, tx.clone())).unwrap();
+    wait.await.unwrap();
+
+    // the waiting message is handled by the replacement, which has its own signals
+    let (stopped, post_stop) = oneshot::channel();
+    let stopped = Mutex::new(Some(stopped));
+    let v2 = |ctx: ActorContext<Reply>, (n, reply): Reply| async move {
+        let _ = reply.send(format!("v2: {n} at {}", ctx.name()));
+    };
+    actor.replace_behavior(v2.on_signal(move |_: &ActorContext<Reply>, signal| {
+        if let (Signal::PostStop, Some(stopped)) = (signal, stopped.lock().unwrap().take()) {
+            let _ = stopped.send(());
+        }
+        async {}
+    }));
+    release.send(()).unwrap();
+
+    assert_eq!(rx.recv().await.unwrap(), "v1: 1");
+    assert_eq!(rx.recv().await.unwrap(), "v2: 2 at versioned");
+    actor.tell((3, tx)).unwrap();
+    assert_eq!(rx.recv().await.unwrap(), "v2: 3 at versioned");
+
+    drop(actor);
+    post_stop.await.unwrap();
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(replace());
+}
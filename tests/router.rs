@@ -0,0 +1,127 @@
+use elliot::{ActorContext, ActorSystem, Resizer, Strategy};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+type Seen = Arc<Mutex<HashMap<String, usize>>>;
+
+async fn route(strategy: Strategy) -> HashMap<String, usize> {
+    let system = ActorSystem::new();
+    let seen = Seen::default();
+
+    let recorder = Arc::clone(&seen);
+    let behavior = move |ctx: ActorContext<u32>, _msg: u32| {
+        let seen = Arc::clone(&recorder);
+        async move {
+            *seen
+                .lock()
+                .unwrap()
+                .entry(ctx.path().to_string())
+                .or_default() += 1;
+        }
+    };
+    let pool = system.spawn_pool("pool", 3, strategy, behavior);
+    assert_eq!(pool.size(), 3);
+    assert_eq!(pool.strategy(), strategy);
+    assert_eq!(pool.routees()[2].path().as_str(), "/user/pool/$routee-2");
+
+    for msg in 0..30 {
+        let _ = pool.tell(msg);
+    }
+    while seen.lock().unwrap().values().sum::<usize>() < 30 {
+        tokio::task::yield_now().await;
+    }
+
+    let seen = seen.lock().unwrap().clone();
+    seen
+}
+
+async fn router() {
+    let seen = route(Strategy::RoundRobin).await;
+    assert_eq!(seen.len(), 3);
+    assert!(seen.values().all(|&count| count == 10));
+
+    let seen = route(Strategy::Random).await;
+    assert!(seen
+        .keys()
+        .all(|path| path.starts_with("/user/pool/$routee-")));
+
+    let seen = route(Strategy::SmallestMailbox).await;
+    assert!(seen
+        .keys()
+        .all(|path| path.starts_with("/user/pool/$routee-")));
+}
+
+async fn slow(_msg: u32) {
+    tokio::time::sleep(Duration::from_millis(5)).await;
+}
+
+async fn resize() {
+    let system = ActorSystem::new();
+    let pool = system.spawn_pool("pool", 1, Strategy::RoundRobin, slow);
+
+    pool.resize(3);
+    let routees = pool.routees();
+    assert_eq!(routees.len(), 3);
+
+    for msg in 0..6 {
+        let _ = pool.tell(msg);
+    }
+    pool.resize(1);
+    assert_eq!(pool.size(), 1);
+
+    // removed routees drain their mailbox and stop
+    let removed = routees[1..]
+        .iter()
+        .map(|r| r.downgrade())
+        .collect::<Vec<_>>();
+    drop(routees);
+    for routee in removed {
+        while routee.upgrade().is_some() {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+        }
+    }
+    while system
+        .actors()
+        .iter()
+        .filter(|a| a.path.as_str().starts_with("/user/"))
+        .count()
+        != 2
+    {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+
+    pool.auto_resize(Resizer::new(1, 4).interval(Duration::from_millis(5)));
+    for msg in 0..100 {
+        let _ = pool.tell(msg);
+    }
+    while pool.size() < 4 {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    while pool.size() > 1 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+
+    // without routees, the messages are dead letters
+    let mut dead_letters = system.dead_letters_of::<u32>();
+    let empty = system.spawn_pool("empty", 0, Strategy::RoundRobin, slow);
+    empty.tell(7).unwrap();
+    let dead_letter = dead_letters.recv().await.unwrap();
+    assert_eq!(
+        (dead_letter.recipient.as_str(), dead_letter.message),
+        ("/user/empty", 7)
+    );
+}
+
+#[test]
+fn test() {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap();
+    rt.block_on(router());
+    rt.block_on(resize());
+}
@@ -0,0 +1,54 @@
+use elliot::ActorSystem;
+use tokio::sync::oneshot;
+
+type Reply = oneshot::Sender<Option<String>>;
+
+async fn thread_name(reply: Reply) {
+    let _ = reply.send(std::thread::current().name().map(String::from));
+}
+
+async fn spawn_on(other: tokio::runtime::Handle) {
+    let system = ActorSystem::new();
+    let here = system.spawn("here", thread_name);
+    let there = system.spawn_on(&other, "there", thread_name);
+
+    let (tx, rx) = oneshot::channel();
+    here.tell(tx).unwrap();
+    assert_eq!(rx.await.unwrap().as_deref(), Some("main"));
+
+    let (tx, rx) = oneshot::channel();
+    there.tell(tx).unwrap();
+    assert_eq!(rx.await.unwrap().as_deref(), Some("other"));
+}
+
+#[test]
+fn test() {
+    let other = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(1)
+        .thread_name("other")
+        .build()
+        .unwrap();
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .thread_name("main")
+        .build()
+        .unwrap()
+        .block_on(spawn_on(other.handle().clone()));
+}
+
+#[test]
+fn current_thread() {
+    let runtime = tokio::runtime::Builder::new_current_thread()
+        .build()
+        .unwrap();
+    let system = ActorSystem::builder()
+        .runtime(runtime.handle().clone())
+        .build();
+
+    // spawning outside of a runtime is fine, the actor runs once the runtime is driven
+    let actor = system.spawn("outside", thread_name);
+    let (tx, rx) = oneshot::channel();
+    actor.tell(tx).unwrap();
+    let name = runtime.block_on(rx).unwrap();
+    assert_eq!(name, std::thread::current().name().map(String::from));
+}
@@ -0,0 +1,109 @@
+use elliot::{ActorSystem, Behaviors, Cron, Schedule, SchedulePersistence};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, SystemTime, UNIX_EPOCH},
+};
+
+async fn stop_on_first(_msg: u32) -> Behaviors {
+    Behaviors::Stopped
+}
+
+#[derive(Clone, Default)]
+struct Recorder(Arc<Mutex<Vec<String>>>);
+
+impl SchedulePersistence for Recorder {
+    fn registered(&self, key: &str, schedule: &Schedule) {
+        self.0.lock().unwrap().push(format!("+{key} {schedule}"));
+    }
+
+    fn cancelled(&self, key: &str) {
+        self.0.lock().unwrap().push(format!("-{key}"));
+    }
+}
+
+async fn tell_after() {
+    let system = ActorSystem::new();
+    let actor = system.spawn("delayed", stop_on_first);
+
+    let cancelled = actor.tell_after(1, Duration::from_secs(3600));
+    assert_eq!(cancelled.cancel(), Some(1));
+
+    let delivered = actor.tell_after(2, Duration::from_millis(10));
+    actor.wait_for_stop().await;
+    assert_eq!(delivered.cancel(), None);
+}
+
+async fn scheduler() {
+    let system = ActorSystem::new();
+    let recorder = Recorder::default();
+    system.scheduler().set_persistence(recorder.clone());
+
+    let actor = system.spawn("scheduled", stop_on_first);
+    let schedule = Schedule::FixedRate(Duration::from_millis(5));
+    let _handle = system.scheduler().schedule("tick", schedule, &actor, 3);
+    actor.wait_for_stop().await;
+
+    let hourly = "cron 0 * * * *".parse::<Schedule>().unwrap();
+    let handle = system.scheduler().schedule("hourly", hourly, &actor, 1);
+    assert_eq!(handle.key(), "hourly");
+    assert!(handle.cancel());
+    assert!(!system.scheduler().cancel("hourly"));
+
+    assert_eq!(
+        *recorder.0.lock().unwrap(),
+        ["+tick rate 5ms", "+hourly cron 0 * * * *", "-hourly"]
+    );
+}
+
+async fn fixed_delay() {
+    let system = ActorSystem::new();
+    let actor = system.spawn("scheduled", stop_on_first);
+
+    let schedule = "delay 1ms".parse().unwrap();
+    let _handle = system.scheduler().schedule("delay", schedule, &actor, 1);
+    actor.wait_for_stop().await;
+
+    // the schedule ends with the next delivery to the stopped actor
+    while !system.scheduler().schedules().is_empty() {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+}
+
+#[test]
+fn cron() {
+    let at = |secs| UNIX_EPOCH + Duration::from_secs(secs);
+    // 2021-06-15 10:17:30 UTC, a Tuesday
+    let now = at(1_623_752_250);
+
+    let every_quarter = "*/15 * * * *".parse::<Cron>().unwrap();
+    assert_eq!(every_quarter.next_after(now), Some(at(1_623_753_000)));
+
+    let weekdays_at_nine = "0 9 * * 1-5".parse::<Cron>().unwrap();
+    assert_eq!(weekdays_at_nine.next_after(now), Some(at(1_623_834_000)));
+
+    let sundays = "30 8 * * 7".parse::<Cron>().unwrap();
+    assert_eq!(sundays.next_after(now), Some(at(1_624_177_800)));
+
+    let new_year = "0 0 1 1 *".parse::<Cron>().unwrap();
+    assert_eq!(new_year.next_after(now), Some(at(1_640_995_200)));
+
+    let never = "0 0 30 2 *".parse::<Cron>().unwrap();
+    assert_eq!(never.next_after(SystemTime::now()), None);
+
+    assert!("* * *".parse::<Cron>().is_err());
+    assert!("60 * * * *".parse::<Cron>().is_err());
+    assert!("*/0 * * * *".parse::<Cron>().is_err());
+    assert!("5-1 * * * *".parse::<Cron>().is_err());
+}
+
+#[test]
+fn test() {
+    let rt = tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap();
+    rt.block_on(tell_after());
+    rt.block_on(scheduler());
+    rt.block_on(fixed_delay());
+}
@@ -0,0 +1,47 @@
+use elliot::{ActorContext, ActorRef, ActorSystem, ReplyTo};
+
+type Request = (u32, ReplyTo<ActorRef<()>>);
+
+async fn scope() {
+    let system = ActorSystem::new();
+
+    // the actors of a closed scope have stopped, even though their refs are still around
+    let scope = system.scope();
+    let named = scope.spawn("scoped", |(): ()| async {});
+    let anonymous = scope.spawn_anonymous(|(): ()| async {});
+    assert_eq!(named.path().as_str(), "/user/scoped");
+    assert!(anonymous.path().name().starts_with("$a-"));
+    scope.close().await;
+    assert!(!named.is_alive());
+    assert!(!anonymous.is_alive());
+    assert!(named.tell(()).is_err());
+
+    // children that serve a single request do not outlive it
+    let server = system.spawn(
+        "server",
+        |ctx: ActorContext<Request>, (n, reply): Request| async move {
+            let scope = ctx.scope();
+            let worker = scope.spawn(&format!("request-{n}"), |(): ()| async {});
+            worker.tell(()).unwrap();
+            let _ = reply.reply(worker);
+        },
+    );
+    let worker = server.ask(|reply| (1, reply)).await.unwrap();
+    assert_eq!(worker.path().as_str(), "/user/server/request-1");
+    worker.wait_for_stop().await;
+
+    // dropping a scope stops its actors too
+    let scope = system.scope();
+    let actor = scope.spawn_anonymous(|(): ()| async {});
+    drop(scope);
+    actor.wait_for_stop().await;
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(scope());
+}
@@ -0,0 +1,79 @@
+use elliot::{ActorContext, ActorPath, ActorRef, ActorSystem};
+use std::sync::{Arc, Mutex};
+use tokio::sync::mpsc;
+
+#[derive(Clone)]
+struct Flush;
+
+async fn selection() {
+    let system = ActorSystem::new();
+    let (tx, mut flushed) = mpsc::unbounded_channel();
+    let cache = |tx: mpsc::UnboundedSender<ActorPath>| {
+        move |ctx: ActorContext<Flush>, Flush| {
+            let _ = tx.send(ctx.path().clone());
+            async {}
+        }
+    };
+
+    // the workers keep their caches as children
+    let children = Arc::new(Mutex::new(Vec::<ActorRef<Flush>>::new()));
+    let spawn_cache = cache(tx.clone());
+    let workers = system.spawn("workers", move |ctx: ActorContext<&'static str>, name| {
+        let child = ctx.spawn_child(name, spawn_cache.clone());
+        children.lock().unwrap().push(child);
+        async {}
+    });
+    for name in ["1", "2", "3"] {
+        workers.tell(name).unwrap();
+    }
+    let first = system.spawn("cache-1", cache(tx.clone()));
+    let second = system.spawn("cache-2", cache(tx.clone()));
+    // a different message type is not selected
+    let _counter = system.spawn("cache-3", |_: u32| async {});
+
+    let caches = system.select::<Flush>("/user/workers/*");
+    assert_eq!(caches.pattern(), "/user/workers/*");
+    while caches.resolve().len() != 3 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(caches.tell(Flush), 3);
+    let mut paths = Vec::new();
+    for _ in 0..3 {
+        paths.push(flushed.recv().await.unwrap().to_string());
+    }
+    paths.sort();
+    assert_eq!(
+        paths,
+        ["/user/workers/1", "/user/workers/2", "/user/workers/3"]
+    );
+
+    let top_level = system.select::<Flush>("/user/cache-*");
+    let paths = top_level.resolve();
+    let paths = paths.iter().map(|a| a.path().as_str()).collect::<Vec<_>>();
+    assert_eq!(paths, ["/user/cache-1", "/user/cache-2"]);
+
+    // the selection is resolved whenever it is used
+    first.stop();
+    first.wait_for_stop().await;
+    assert_eq!(top_level.tell(Flush), 1);
+    assert_eq!(flushed.recv().await.unwrap(), *second.path());
+    assert_eq!(system.select::<Flush>("/user/*").tell(Flush), 1);
+    assert_eq!(system.select::<Flush>("/user/workers").tell(Flush), 0);
+
+    // paths are parsed from their display format
+    let path = "/user/workers/1".parse::<ActorPath>().unwrap();
+    assert_eq!(path.to_string(), "/user/workers/1");
+    assert_eq!(path.name(), "1");
+    for invalid in ["", "/", "user/workers", "/user//1", "/user/", "/user/a b"] {
+        assert!(invalid.parse::<ActorPath>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(selection());
+}
@@ -0,0 +1,66 @@
+use elliot::{ActorSystem, SequencedRef};
+use std::sync::{Arc, Mutex};
+
+async fn sequenced() {
+    let system = ActorSystem::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&received);
+    let actor = system.spawn("sink", move |msg: (char, u32)| {
+        seen.lock().unwrap().push(msg);
+        async {}
+    });
+    let front = actor.sequenced();
+
+    // the stamped messages of two senders arrive out of order and with a retry
+    let a = SequencedRef::new(front.clone());
+    let b = SequencedRef::new(front.clone());
+    let a = (0..3).map(|n| a.stamp(('a', n))).collect::<Vec<_>>();
+    let b = (0..2).map(|n| b.stamp(('b', n))).collect::<Vec<_>>();
+    for msg in [&a[2], &b[1], &a[0], &a[0], &b[0], &a[1], &a[2]] {
+        front.tell(msg.clone()).unwrap();
+    }
+
+    // told messages keep their order as well
+    let c = SequencedRef::new(front.clone());
+    for n in 0..3 {
+        c.tell(('c', n)).unwrap();
+    }
+
+    // senders that are gone are forgotten, without losing the messages that wait for others
+    for _ in 0..100 {
+        let d = SequencedRef::new(front.clone());
+        let (first, second) = (d.stamp(('d', 0)), d.stamp(('d', 1)));
+        drop(d);
+        front.tell(second).unwrap();
+        front.tell(first).unwrap();
+    }
+
+    while received.lock().unwrap().len() < 208 {
+        tokio::task::yield_now().await;
+    }
+    let received = received.lock().unwrap();
+    let ds = received
+        .iter()
+        .filter(|(s, _)| *s == 'd')
+        .collect::<Vec<_>>();
+    assert_eq!(ds.len(), 200);
+    assert!(ds.chunks(2).all(|d| d[0].1 == 0 && d[1].1 == 1));
+    for sender in ['a', 'b', 'c'] {
+        let ns = received
+            .iter()
+            .filter(|(s, _)| *s == sender)
+            .map(|(_, n)| *n)
+            .collect::<Vec<_>>();
+        let expected = if sender == 'b' { 2 } else { 3 };
+        assert_eq!(ns, (0..expected).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(sequenced());
+}
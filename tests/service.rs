@@ -0,0 +1,54 @@
+#![cfg(feature = "tower")]
+
+use elliot::{ActorSystem, AskError, Behaviors, ReplyTo};
+use std::future::poll_fn;
+use tower_service::Service;
+
+struct Greet {
+    name: String,
+    reply_to: ReplyTo<String>,
+}
+
+async fn service() {
+    let system = ActorSystem::new();
+    let greeter = system.spawn("greeter", |msg: Greet| {
+        let stop = msg.name == "stop";
+        let _ = msg.reply_to.reply(format!("Hello, {}", msg.name));
+        async move {
+            if stop {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    let mut service = greeter.service(|name, reply_to| Greet { name, reply_to });
+    for name in ["Alice", "Bob"] {
+        poll_fn(|cx| service.poll_ready(cx)).await.unwrap();
+        assert_eq!(
+            service.call(name.to_owned()).await.unwrap(),
+            format!("Hello, {name}")
+        );
+    }
+
+    // clones share the actor
+    let mut other = service.clone();
+    poll_fn(|cx| other.poll_ready(cx)).await.unwrap();
+    assert_eq!(other.call("stop".to_owned()).await.unwrap(), "Hello, stop");
+
+    greeter.wait_for_stop().await;
+    assert_eq!(
+        poll_fn(|cx| service.poll_ready(cx)).await.unwrap_err(),
+        AskError::ActorRefGone
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(service());
+}
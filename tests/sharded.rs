@@ -0,0 +1,64 @@
+use elliot::{ActorContext, ActorSystem, SpawnOptions};
+use std::sync::{Arc, Mutex};
+
+async fn sharded() {
+    let system = ActorSystem::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let seen = Arc::clone(&received);
+    let sink = system.spawn_with(
+        "sink",
+        SpawnOptions::default().lanes(4),
+        move |ctx: ActorContext<(usize, usize)>, msg: (usize, usize)| {
+            seen.lock().unwrap().push(msg);
+            // the actor reaches itself through its context
+            assert!(ctx.this().is_alive());
+            async {}
+        },
+    );
+
+    // many senders, each with its own clone
+    let senders = (0..16)
+        .map(|sender| {
+            let sink = sink.clone();
+            tokio::spawn(async move {
+                for n in 0..100 {
+                    sink.tell((sender, n)).unwrap();
+                }
+            })
+        })
+        .collect::<Vec<_>>();
+    for sender in senders {
+        sender.await.unwrap();
+    }
+
+    // the actor stops once its refs are gone and the lanes are empty
+    let weak = sink.downgrade();
+    drop(sink);
+    while weak.upgrade().is_some() {
+        tokio::task::yield_now().await;
+    }
+    while system.find::<(usize, usize)>("sink").is_some() {
+        tokio::task::yield_now().await;
+    }
+
+    // all messages arrive, the messages of one ref in order
+    let received = received.lock().unwrap();
+    assert_eq!(received.len(), 16 * 100);
+    for sender in 0..16 {
+        let ns = received
+            .iter()
+            .filter(|(s, _)| *s == sender)
+            .map(|(_, n)| *n)
+            .collect::<Vec<_>>();
+        assert_eq!(ns, (0..100).collect::<Vec<_>>());
+    }
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(sharded());
+}
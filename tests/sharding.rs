@@ -0,0 +1,196 @@
+#![cfg(feature = "cluster")]
+
+use elliot::{
+    ActorRef, ActorSystem, Behaviors, BoxErr, Cluster, ClusterSettings, MessageCodec, Sharding,
+};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+    time::Duration,
+};
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+/// Messages are `entity:payload`.
+fn entity_id(msg: &str) -> String {
+    msg.split_once(':').map_or(msg, |(id, _)| id).to_owned()
+}
+
+/// (node, entity, msg) for every received message
+type Received = Arc<Mutex<Vec<(usize, String, String)>>>;
+
+async fn node(node: usize, seeds: &[String], received: &Received) -> (Cluster, ActorRef<String>) {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let settings = ClusterSettings::new(seeds.to_vec())
+        .gossip_interval(Duration::from_millis(20))
+        .remove_after(Duration::from_millis(200));
+    let cluster = system.join_cluster("127.0.0.1:0", settings).await.unwrap();
+    let received = Arc::clone(received);
+    let region = Sharding::new(
+        "counter",
+        |msg: &String| entity_id(msg),
+        move |id: &str| {
+            let id = id.to_owned();
+            let received = Arc::clone(&received);
+            move |msg: String| {
+                received.lock().unwrap().push((node, id.clone(), msg));
+                async { Behaviors::Same }
+            }
+        },
+    )
+    .shards(16)
+    .passivate_after(Duration::from_millis(100))
+    .start(&cluster)
+    .unwrap();
+    (cluster, region)
+}
+
+async fn converged(clusters: &[&Cluster]) {
+    while clusters
+        .iter()
+        .any(|cluster| cluster.members().len() != clusters.len())
+    {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+/// The node that every entity received its messages on, all messages of an entity must be on the same node.
+async fn placement(received: &Received, round: &str, entities: usize) -> HashMap<String, usize> {
+    loop {
+        let placement = received
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|(_, _, msg)| msg.ends_with(round))
+            .map(|(node, id, _)| (id.clone(), *node))
+            .collect::<Vec<_>>();
+        if placement.len() == entities * 2 {
+            let mut nodes = HashMap::new();
+            for (id, node) in placement {
+                assert_eq!(*nodes.entry(id).or_insert(node), node);
+            }
+            return nodes;
+        }
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+}
+
+async fn sharding() {
+    let received = Received::default();
+    let (first, first_region) = node(0, &[], &received).await;
+    let seeds = [first.address().to_owned()];
+    let (second, second_region) = node(1, &seeds, &received).await;
+    tokio::time::timeout(Duration::from_secs(5), converged(&[&first, &second]))
+        .await
+        .unwrap();
+
+    let entities = 20;
+    for id in 0..entities {
+        first_region.tell(format!("e{id}:one")).unwrap();
+        second_region.tell(format!("e{id}:one")).unwrap();
+    }
+    let before = tokio::time::timeout(
+        Duration::from_secs(5),
+        placement(&received, "one", entities),
+    )
+    .await
+    .unwrap();
+    assert!(before.values().any(|node| *node == 0));
+    assert!(before.values().any(|node| *node == 1));
+
+    // a joining node takes over some shards, the others stay where they are
+    let (third, third_region) = node(2, &seeds, &received).await;
+    tokio::time::timeout(
+        Duration::from_secs(5),
+        converged(&[&first, &second, &third]),
+    )
+    .await
+    .unwrap();
+    for id in 0..entities {
+        first_region.tell(format!("e{id}:two")).unwrap();
+        third_region.tell(format!("e{id}:two")).unwrap();
+    }
+    let after = tokio::time::timeout(
+        Duration::from_secs(5),
+        placement(&received, "two", entities),
+    )
+    .await
+    .unwrap();
+    assert!(after.values().any(|node| *node == 2));
+    for (id, node) in &after {
+        assert!(
+            *node == 2 || *node == before[id],
+            "{id} moved from {} to {node}",
+            before[id]
+        );
+    }
+}
+
+async fn passivation() {
+    let system = ActorSystem::new();
+    system.register_codec(Utf8);
+    let cluster = system
+        .join_cluster("127.0.0.1:0", ClusterSettings::new(Vec::<String>::new()))
+        .await
+        .unwrap();
+
+    let spawned = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&spawned);
+    let region = Sharding::new(
+        "session",
+        |msg: &String| entity_id(msg),
+        move |id: &str| {
+            log.lock().unwrap().push(id.to_owned());
+            |_: String| async { Behaviors::Same }
+        },
+    )
+    .passivate_after(Duration::from_millis(50))
+    .start(&cluster)
+    .unwrap();
+
+    region.tell("alice:1".into()).unwrap();
+    region.tell("alice:2".into()).unwrap();
+    region.tell("bob:1".into()).unwrap();
+    while spawned.lock().unwrap().len() < 2 {
+        tokio::task::yield_now().await;
+    }
+
+    // idle entities are stopped and spawned again on the next message
+    tokio::time::sleep(Duration::from_millis(200)).await;
+    region.tell("alice:3".into()).unwrap();
+    while spawned.lock().unwrap().len() < 3 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(*spawned.lock().unwrap(), ["alice", "bob", "alice"]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(sharding());
+}
+
+#[test]
+fn passivate() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(passivation());
+}
@@ -0,0 +1,73 @@
+use elliot::{ActorContext, ActorRef, ActorSystem, Behavior, Signal};
+use std::{io, time::Duration};
+use tokio::sync::mpsc;
+
+enum Cmd {
+    Watch(ActorRef<()>),
+    SpawnFailing,
+    Timeout,
+}
+
+async fn signals() {
+    let system = ActorSystem::new();
+    let (tx, mut rx) = mpsc::unbounded_channel();
+
+    let behavior = |ctx: ActorContext<Cmd>, cmd: Cmd| async move {
+        match cmd {
+            Cmd::Watch(target) => ctx.watch(&target),
+            Cmd::SpawnFailing => {
+                let child =
+                    ctx.spawn_anonymous(|(): ()| async { Err::<(), _>(io::Error::other("boom")) });
+                child.tell(()).unwrap();
+            }
+            Cmd::Timeout => ctx.set_receive_timeout(Duration::from_millis(10)),
+        }
+    };
+    let parent = system.spawn(
+        "parent",
+        behavior.on_signal(move |ctx: &ActorContext<Cmd>, signal| {
+            if let Signal::ReceiveTimeout = signal {
+                ctx.set_receive_timeout(None);
+            }
+            let _ = tx.send(signal);
+            async {}
+        }),
+    );
+
+    // a watched actor that stops on its first message
+    let watched = system.spawn("watched", |(): ()| async { None::<()> });
+    parent.tell(Cmd::Watch(watched.clone())).unwrap();
+    watched.tell(()).unwrap();
+    match rx.recv().await.unwrap() {
+        Signal::Terminated(terminated) => assert_eq!(terminated.path, *watched.path()),
+        signal => panic!("unexpected {signal:?}"),
+    }
+
+    parent.tell(Cmd::SpawnFailing).unwrap();
+    match rx.recv().await.unwrap() {
+        Signal::ChildFailed(crashed) => {
+            assert!(crashed.path.as_str().starts_with("/user/parent/$a-"));
+            assert!(crashed.reason.contains("boom"), "{}", crashed.reason);
+        }
+        signal => panic!("unexpected {signal:?}"),
+    }
+
+    // the timeout fires once, the handler disables it again
+    parent.tell(Cmd::Timeout).unwrap();
+    assert!(matches!(rx.recv().await, Some(Signal::ReceiveTimeout)));
+
+    drop(parent);
+    assert!(matches!(rx.recv().await, Some(Signal::PostStop)));
+    // the handler is dropped together with the actor
+    assert!(rx.recv().await.is_none());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(signals());
+}
@@ -0,0 +1,59 @@
+#![cfg(feature = "smol")]
+
+use elliot::{ask_all, ActorSystem, AskError, Rate, ReplyTo, Schedule};
+use std::time::Duration;
+use tokio::sync::{mpsc, oneshot};
+
+async fn smol_executor() {
+    let system = ActorSystem::builder().smol().build();
+
+    let echo = system.spawn("echo", |reply: oneshot::Sender<&'static str>| async move {
+        let _ = reply.send("pong");
+    });
+    let (tx, rx) = oneshot::channel();
+    echo.tell(tx).unwrap();
+    assert_eq!(rx.await.unwrap(), "pong");
+
+    // the scheduler sleeps on the executor of the system
+    let (tx, mut rx) = mpsc::unbounded_channel();
+    let ticks = system.spawn("ticks", move |(): ()| {
+        let _ = tx.send(());
+        async {}
+    });
+    let schedule = system.scheduler().schedule(
+        "tick",
+        Schedule::FixedRate(Duration::from_millis(5)),
+        &ticks,
+        (),
+    );
+    rx.recv().await.unwrap();
+    rx.recv().await.unwrap();
+    assert!(schedule.cancel());
+
+    // and so do delayed and throttled messages
+    let _ = ticks.tell_after((), Duration::from_millis(5));
+    rx.recv().await.unwrap();
+    let throttled = ticks.throttled(Rate::new(100, Duration::from_secs(1)));
+    for _ in 0..3 {
+        throttled.tell(()).unwrap();
+    }
+    for _ in 0..3 {
+        rx.recv().await.unwrap();
+    }
+
+    // and so do the timeouts of asks
+    let silent = system.spawn("silent", |reply: ReplyTo<()>| async move {
+        let _ = smol::Timer::after(Duration::from_secs(1)).await;
+        drop(reply);
+    });
+    let timeout = Duration::from_millis(10);
+    let reply = silent.ask_timeout(|reply| reply, timeout).await;
+    assert!(matches!(reply, Err(AskError::Timeout)));
+    let replies = ask_all([&silent], |reply| reply, timeout).await;
+    assert!(matches!(replies[..], [Err(AskError::Timeout)]));
+}
+
+#[test]
+fn test() {
+    smol::block_on(smol_executor());
+}
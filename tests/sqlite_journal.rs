@@ -0,0 +1,124 @@
+#![cfg(feature = "sqlite")]
+
+use elliot::{
+    ActorSystem, EventSourced, Journal, ReadJournal, ReplyTo, SqliteJournal, TaggedJournal,
+};
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug)]
+enum Command {
+    Increment,
+    Count(ReplyTo<u32>),
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Incremented;
+
+fn counter(journal: &SqliteJournal) -> EventSourced<Command, Incremented, u32> {
+    EventSourced::new(
+        "counter-1",
+        journal.clone(),
+        |count: &u32, command| match command {
+            Command::Increment => vec![Incremented],
+            Command::Count(reply) => {
+                let _ = reply.reply(*count);
+                vec![]
+            }
+        },
+        |count: &mut u32, Incremented| *count += 1,
+    )
+}
+
+async fn sqlite_journal() {
+    let path = std::env::temp_dir().join(format!("elliot-journal-{}.db", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let system = ActorSystem::new();
+    let journal = SqliteJournal::open(&path).unwrap();
+    let first = system.spawn("counter", counter(&journal));
+    for _ in 0..3 {
+        let _ = first.tell(Command::Increment);
+    }
+    assert_eq!(first.ask(Command::Count).await, Ok(3));
+    first.stop();
+    first.wait_for_stop().await;
+    drop(first);
+    drop(journal);
+
+    let journal = SqliteJournal::open(&path).unwrap();
+    assert_eq!(journal.highest_sequence_nr("counter-1").unwrap(), 3);
+    let second = system.spawn("counter", counter(&journal));
+    let _ = second.tell(Command::Increment);
+    assert_eq!(second.ask(Command::Count).await, Ok(4));
+
+    let events: Vec<(u64, Incremented)> = journal.replay("counter-1", 4).unwrap();
+    assert_eq!(events, [(4, Incremented)]);
+    assert!(Journal::append(&journal, "counter-1", 2, &[Incremented]).is_err());
+    assert!(Journal::append(&journal, "counter-2", 1, &[Incremented]).is_ok());
+
+    // the last event is kept, so that the sequence numbers continue
+    Journal::<Incremented>::delete_to(&journal, "counter-1", 10).unwrap();
+    let events: Vec<(u64, Incremented)> = journal.replay("counter-1", 1).unwrap();
+    assert_eq!(events, [(4, Incremented)]);
+    assert!(Journal::append(&journal, "counter-1", 5, &[Incremented]).is_ok());
+    assert_eq!(journal.replay("counter-2", 1).unwrap(), [(1, Incremented)]);
+
+    drop(second);
+    drop(journal);
+    for suffix in ["", "-wal", "-shm"] {
+        let _ = std::fs::remove_file(format!("{}{suffix}", path.display()));
+    }
+}
+
+async fn tags() {
+    let journal = TaggedJournal::new(SqliteJournal::in_memory().unwrap(), |n: &u32| {
+        vec![if n.is_multiple_of(2) { "even" } else { "odd" }.to_owned()]
+    });
+    journal.append("numbers-1", 1, &[1, 2, 3]).unwrap();
+    journal.append("numbers-2", 1, &[4]).unwrap();
+
+    let mut odd = journal.events_by_tag("odd").unwrap();
+    let mut envelope = odd.recv().await.unwrap();
+    assert_eq!(
+        (envelope.offset, &*envelope.persistence_id),
+        (1, "numbers-1")
+    );
+    envelope = odd.recv().await.unwrap();
+    assert_eq!(
+        (envelope.offset, envelope.sequence_nr, envelope.event),
+        (3, 3, 3)
+    );
+
+    // appended events are followed live
+    journal.append("numbers-2", 2, &[5]).unwrap();
+    assert_eq!(odd.recv().await.unwrap().offset, 5);
+
+    let mut even = journal.events_by_tag_from("even", 2).unwrap();
+    assert_eq!(even.recv().await.unwrap().event, 4);
+
+    // deleted events are no longer found
+    Journal::<u32>::delete_to(&journal, "numbers-1", 2).unwrap();
+    let mut remaining = journal.events_by_tag("odd").unwrap();
+    assert_eq!(remaining.recv().await.unwrap().offset, 3);
+    assert_eq!(remaining.recv().await.unwrap().offset, 5);
+    journal.append("numbers-1", 4, &[7]).unwrap();
+    assert_eq!(remaining.recv().await.unwrap().offset, 6);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(sqlite_journal());
+}
+
+#[test]
+fn test_tags() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(tags());
+}
@@ -0,0 +1,41 @@
+use elliot::ActorSystem;
+use std::{
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc,
+    },
+    time::Duration,
+};
+
+async fn stop() {
+    let system = ActorSystem::new();
+    let handled = Arc::new(AtomicUsize::new(0));
+
+    let counter = Arc::clone(&handled);
+    let actor = system.spawn("flooded", move |(): ()| {
+        let _ = counter.fetch_add(1, Ordering::SeqCst);
+        tokio::time::sleep(Duration::from_millis(1))
+    });
+    for _ in 0..1000 {
+        actor.tell(()).unwrap();
+    }
+
+    // the request does not wait behind the flood
+    actor.stop();
+    tokio::time::timeout(Duration::from_secs(1), actor.wait_for_stop())
+        .await
+        .unwrap();
+    assert!(handled.load(Ordering::SeqCst) < 10);
+    assert!(!actor.is_alive());
+    assert!(actor.tell(()).is_err());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(stop());
+}
@@ -0,0 +1,300 @@
+#![cfg(feature = "stream")]
+
+use elliot::{ActorContext, ActorStream, ActorSystem, Behaviors, StreamReplyTo};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::{
+    future::poll_fn,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, AtomicUsize, Ordering},
+        Arc, Mutex,
+    },
+    task::{Context, Poll},
+    time::Duration,
+};
+
+/// Counts up from 0, until `end` if there is one.
+struct Counter {
+    next: usize,
+    end: Option<usize>,
+    polled: Arc<AtomicUsize>,
+    dropped: Arc<AtomicBool>,
+}
+
+impl Counter {
+    fn new(end: Option<usize>) -> Self {
+        Self {
+            next: 0,
+            end,
+            polled: Arc::default(),
+            dropped: Arc::default(),
+        }
+    }
+}
+
+impl Stream for Counter {
+    type Item = usize;
+
+    fn poll_next(mut self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<Option<usize>> {
+        if self.end == Some(self.next) {
+            return Poll::Ready(None);
+        }
+        let _ = self.polled.fetch_add(1, Ordering::SeqCst);
+        self.next += 1;
+        Poll::Ready(Some(self.next - 1))
+    }
+}
+
+impl Drop for Counter {
+    fn drop(&mut self) {
+        self.dropped.store(true, Ordering::SeqCst);
+    }
+}
+
+async fn source() {
+    let system = ActorSystem::new();
+    let received = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&received);
+    let slow = system.spawn("slow", move |n: usize| {
+        let _ = count.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            if n == 49 {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    let counter = Counter::new(None);
+    let polled = Arc::clone(&counter.polled);
+    let dropped = Arc::clone(&counter.dropped);
+    system.spawn_source(counter, &slow);
+    drop(slow);
+
+    // the infinite stream is only polled as fast as the actor takes the items
+    while received.load(Ordering::SeqCst) < 10 {
+        tokio::task::yield_now().await;
+    }
+    assert!(polled.load(Ordering::SeqCst) <= received.load(Ordering::SeqCst) + 17);
+
+    // the source stops when the actor stops
+    while !dropped.load(Ordering::SeqCst) {
+        tokio::time::sleep(Duration::from_millis(1)).await;
+    }
+    assert_eq!(received.load(Ordering::SeqCst), 50);
+    assert!(polled.load(Ordering::SeqCst) <= 50 + 17);
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum Msg {
+    Start,
+    Item(usize),
+}
+
+async fn pipe() {
+    let system = ActorSystem::new();
+    let received = Arc::new(Mutex::new(Vec::new()));
+    let log = Arc::clone(&received);
+    let actor = system.spawn("pipe", move |ctx: ActorContext<Msg>, msg: Msg| {
+        if msg == Msg::Start {
+            ctx.pipe_stream(Counter::new(Some(3)), Msg::Item);
+        }
+        log.lock().unwrap().push(msg);
+        async { Behaviors::Same }
+    });
+    actor.tell(Msg::Start).unwrap();
+
+    // the stream ends and the actor keeps running
+    while received.lock().unwrap().len() < 4 {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(
+        *received.lock().unwrap(),
+        [Msg::Start, Msg::Item(0), Msg::Item(1), Msg::Item(2)]
+    );
+    assert!(actor.is_alive());
+}
+
+async fn next<S: Stream + Unpin>(stream: &mut S) -> Option<S::Item> {
+    poll_fn(|cx| Pin::new(&mut *stream).poll_next(cx)).await
+}
+
+async fn output() {
+    let system = ActorSystem::new();
+    let doubler = system.spawn("doubler", |out: ActorStream<usize>, n: usize| {
+        let emitted = out.emit(n * 2);
+        async move {
+            if n == 0 {
+                return Behaviors::Stopped;
+            }
+            assert_eq!(emitted, 2);
+            Behaviors::Same
+        }
+    });
+
+    let mut first = doubler.output::<usize>();
+    let mut second = doubler.output::<usize>();
+    for n in [1, 2, 3, 0] {
+        doubler.tell(n).unwrap();
+    }
+    for expected in [2, 4, 6, 0] {
+        assert_eq!(next(&mut first).await, Some(expected));
+        assert_eq!(next(&mut second).await, Some(expected));
+    }
+
+    // the outputs end with the actor
+    assert_eq!(next(&mut first).await, None);
+    assert_eq!(next(&mut second).await, None);
+    doubler.wait_for_stop().await;
+    assert_eq!(next(&mut doubler.output::<usize>()).await, None);
+}
+
+/// What `StreamExt::forward` does.
+async fn forward<S: Stream + Unpin, K: Sink<S::Item> + Unpin>(
+    mut stream: S,
+    mut sink: K,
+) -> Result<(), K::Error> {
+    while let Some(item) = next(&mut stream).await {
+        poll_fn(|cx| Pin::new(&mut sink).poll_ready(cx)).await?;
+        Pin::new(&mut sink).start_send(item)?;
+    }
+    poll_fn(|cx| Pin::new(&mut sink).poll_close(cx)).await
+}
+
+async fn sink() {
+    let system = ActorSystem::new();
+    let received = Arc::new(AtomicUsize::new(0));
+    let count = Arc::clone(&received);
+    let slow = system.spawn_bounded("slow", 4, move |n: usize| {
+        let _ = count.fetch_add(1, Ordering::SeqCst);
+        async move {
+            tokio::time::sleep(Duration::from_millis(1)).await;
+            if n == 99 {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    let counter = Counter::new(Some(30));
+    let polled = Arc::clone(&counter.polled);
+    let forwarding = tokio::spawn(forward(counter, slow.clone()));
+    while received.load(Ordering::SeqCst) < 10 {
+        tokio::task::yield_now().await;
+        // the stream is polled no faster than the actor takes the items
+        assert!(polled.load(Ordering::SeqCst) <= received.load(Ordering::SeqCst) + 6);
+    }
+    forwarding.await.unwrap().unwrap();
+    slow.tell(99).unwrap();
+    slow.wait_for_stop().await;
+    assert_eq!(received.load(Ordering::SeqCst), 31);
+
+    // the sink fails once the actor has stopped
+    assert!(forward(Counter::new(Some(1)), slow).await.is_err());
+}
+
+/// Asks for the pages of `0..total`, `size` items at a time.
+struct Pages {
+    total: usize,
+    size: usize,
+    reply: StreamReplyTo<Vec<usize>>,
+}
+
+async fn reply_stream() {
+    let system = ActorSystem::new();
+    let pager = system.spawn("pager", |pages: Pages| async move {
+        for start in (0..pages.total).step_by(pages.size) {
+            let page = (start..pages.total.min(start + pages.size)).collect();
+            if pages.reply.send(page).await.is_err() {
+                return;
+            }
+        }
+        pages.reply.complete();
+    });
+
+    let mut pages = pager.ask_stream(|reply| Pages {
+        total: 5,
+        size: 2,
+        reply,
+    });
+    assert_eq!(next(&mut pages).await, Some(vec![0, 1]));
+    assert_eq!(next(&mut pages).await, Some(vec![2, 3]));
+    assert_eq!(next(&mut pages).await, Some(vec![4]));
+    assert_eq!(next(&mut pages).await, None);
+
+    // the actor learns that nobody listens anymore
+    let (closed_tx, mut closed) = tokio::sync::mpsc::unbounded_channel();
+    let watcher = system.spawn("watcher", move |reply: StreamReplyTo<usize>| {
+        let closed_tx = closed_tx.clone();
+        async move {
+            let _ = reply.send(1).await;
+            reply.closed().await;
+            let _ = closed_tx.send(reply.is_closed());
+        }
+    });
+    let mut stream = watcher.ask_stream(|reply| reply);
+    assert_eq!(next(&mut stream).await, Some(1));
+    drop(stream);
+    assert_eq!(closed.recv().await, Some(true));
+
+    // the stream of a stopped actor ends right away
+    pager.stop();
+    pager.wait_for_stop().await;
+    let mut pages = pager.ask_stream(|reply| Pages {
+        total: 5,
+        size: 2,
+        reply,
+    });
+    assert_eq!(next(&mut pages).await, None);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(source());
+}
+
+#[test]
+fn pipe_stream() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(pipe());
+}
+
+#[test]
+fn actor_stream() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(output());
+}
+
+#[test]
+fn actor_sink() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(sink());
+}
+
+#[test]
+fn ask_stream() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(reply_stream());
+}
@@ -0,0 +1,143 @@
+use elliot::{
+    ActorContext, ActorRef, ActorSystem, Behavior, Behaviors, ChildOptions, Error, LifecycleEvent,
+    ReplyTo,
+};
+use std::{
+    future::{ready, Ready},
+    io,
+    sync::atomic::{AtomicU32, Ordering},
+};
+use tokio::sync::mpsc;
+
+enum Msg {
+    Add(u32),
+    Fail,
+}
+
+/// Adds up the numbers and reports the sum, clones start from the sum of the original.
+struct Tally {
+    sum: AtomicU32,
+    reports: mpsc::UnboundedSender<u32>,
+}
+
+impl Clone for Tally {
+    fn clone(&self) -> Self {
+        Self {
+            sum: AtomicU32::new(self.sum.load(Ordering::SeqCst)),
+            reports: self.reports.clone(),
+        }
+    }
+}
+
+impl Behavior<Msg> for Tally {
+    type F = Ready<Result<Behaviors, Error<Msg>>>;
+
+    fn receive(&self, _context: &ActorContext<Msg>, msg: Msg) -> Self::F {
+        ready(match msg {
+            Msg::Add(n) => {
+                let sum = self.sum.fetch_add(n, Ordering::SeqCst) + n;
+                let _ = self.reports.send(sum);
+                Ok(Behaviors::Same)
+            }
+            Msg::Fail => Err(Error::Crashed(Box::new(io::Error::other("boom")))),
+        })
+    }
+}
+
+struct Spawn(&'static str, ChildOptions, ReplyTo<ActorRef<Msg>>);
+
+async fn supervision() {
+    let system = ActorSystem::new();
+    let mut events = system.lifecycle_events();
+    let (reports, mut sums) = mpsc::unbounded_channel();
+    let tally = Tally {
+        sum: AtomicU32::new(0),
+        reports,
+    };
+    let parent = system.spawn(
+        "parent",
+        move |ctx: ActorContext<Spawn>, Spawn(name, options, reply): Spawn| {
+            let _ = reply.reply(ctx.spawn_child_with(name, tally.clone(), options));
+            async {}
+        },
+    );
+    let spawn = |name, options| parent.ask(move |reply| Spawn(name, options, reply));
+
+    // a resumed child keeps its state
+    let resumed = spawn("resumed", ChildOptions::resume_on_failure())
+        .await
+        .unwrap();
+    assert_eq!(resumed.path().as_str(), "/user/parent/resumed");
+    for msg in [Msg::Add(1), Msg::Fail, Msg::Add(2)] {
+        resumed.tell(msg).unwrap();
+    }
+    assert_eq!(sums.recv().await, Some(1));
+    assert_eq!(sums.recv().await, Some(3));
+
+    // a restarted child starts over
+    let restarted = spawn("restarted", ChildOptions::restart_on_failure())
+        .await
+        .unwrap();
+    for msg in [Msg::Add(1), Msg::Fail, Msg::Add(2)] {
+        restarted.tell(msg).unwrap();
+    }
+    assert_eq!(sums.recv().await, Some(1));
+    assert_eq!(sums.recv().await, Some(2));
+
+    // a stopped child stops
+    let stopped = spawn("stopped", ChildOptions::stop_on_failure())
+        .await
+        .unwrap();
+    stopped.tell(Msg::Fail).unwrap();
+    stopped.wait_for_stop().await;
+    assert!(parent.is_alive());
+
+    // the second failure in a row of this child fails the parent
+    let escalating = spawn(
+        "escalating",
+        ChildOptions::restart_on_failure().escalate_after(2),
+    )
+    .await
+    .unwrap();
+    for msg in [
+        Msg::Fail,
+        Msg::Add(5),
+        Msg::Fail,
+        Msg::Add(1),
+        Msg::Fail,
+        Msg::Fail,
+    ] {
+        escalating.tell(msg).unwrap();
+    }
+    assert_eq!(sums.recv().await, Some(5));
+    assert_eq!(sums.recv().await, Some(1));
+    escalating.wait_for_stop().await;
+    assert!(escalating.tell(Msg::Add(5)).is_err());
+    parent.wait_for_stop().await;
+    loop {
+        match events.recv().await {
+            Some(LifecycleEvent::Crashed(crashed)) if crashed.path == *parent.path() => {
+                assert!(
+                    crashed
+                        .reason
+                        .contains("Child /user/parent/escalating failed"),
+                    "{}",
+                    crashed.reason
+                );
+                break;
+            }
+            Some(_) => {}
+            None => panic!("the parent did not crash"),
+        }
+    }
+    assert!(sums.try_recv().is_err());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(supervision());
+}
@@ -0,0 +1,134 @@
+use elliot::{ActorContext, ActorRef, Behavior, BehaviorTestKit, Behaviors, Effect, Error, Signal};
+use std::{
+    future::{ready, Ready},
+    io,
+    sync::Mutex,
+    time::Duration,
+};
+
+enum Cmd {
+    /// Spawn a worker for the job.
+    Start(&'static str, u32),
+    /// Start the job again after a second.
+    Retry(&'static str, u32),
+    Idle(Option<Duration>),
+    Done(u32, ActorRef<u32>),
+    Fail,
+    Stop,
+}
+
+/// Hands out jobs to workers and remembers which workers it started.
+#[derive(Default)]
+struct Jobs {
+    started: Mutex<Vec<&'static str>>,
+}
+
+impl Behavior<Cmd> for Jobs {
+    type F = Ready<Result<Behaviors, Error<Cmd>>>;
+
+    fn receive(&self, ctx: &ActorContext<Cmd>, cmd: Cmd) -> Self::F {
+        ready(match cmd {
+            Cmd::Start(name, job) => {
+                let worker = ctx.spawn_child(name, |job: u32| async move {
+                    assert!(job > 0);
+                });
+                ctx.watch(&worker);
+                worker.tell(job).unwrap();
+                self.started.lock().unwrap().push(name);
+                Ok(Behaviors::Same)
+            }
+            Cmd::Retry(name, job) => {
+                let _ = ctx
+                    .this()
+                    .tell_after(Cmd::Start(name, job), Duration::from_secs(1));
+                Ok(Behaviors::Same)
+            }
+            Cmd::Idle(timeout) => {
+                ctx.set_receive_timeout(timeout);
+                Ok(Behaviors::Same)
+            }
+            Cmd::Done(result, reply) => {
+                reply.tell(result).unwrap();
+                ctx.this().tell(Cmd::Stop).unwrap();
+                Ok(Behaviors::Same)
+            }
+            Cmd::Fail => Err(Error::Crashed(Box::new(io::Error::other("boom")))),
+            Cmd::Stop => Ok(Behaviors::Stopped),
+        })
+    }
+}
+
+/// Runs without a runtime, the behavior is driven on the test thread.
+#[test]
+fn testkit() {
+    let mut kit = BehaviorTestKit::named("jobs", Jobs::default());
+    assert_eq!(kit.context().path().as_str(), "/user/jobs");
+
+    // the components of the system can be used without a runtime
+    let system = kit.context().system();
+    assert!(system.scheduler().schedules().is_empty());
+    let _ = system.dead_letters();
+    system.subscribe(&kit.this());
+    assert!(system.actors().is_empty());
+
+    // the child is not spawned, its messages wait in an inbox
+    assert!(matches!(kit.run(Cmd::Start("a", 1)), Ok(Behaviors::Same)));
+    let worker = kit.child_inbox::<u32>("a").unwrap();
+    let a = worker.path().clone();
+    assert_eq!(a.as_str(), "/user/jobs/a");
+    assert_eq!(
+        kit.effects(),
+        [Effect::Spawned(a.clone()), Effect::Watched(a)]
+    );
+    assert_eq!(worker.receive_all(), [1]);
+    assert!(kit.child_inbox::<String>("a").is_none());
+    assert!(kit.child_inbox::<u32>("b").is_none());
+    assert_eq!(*kit.behavior().started.lock().unwrap(), ["a"]);
+
+    // timers wait until they are fired
+    let _ = kit.run(Cmd::Retry("b", 2));
+    let _ = kit.run(Cmd::Retry("c", 3));
+    let retry = Effect::Scheduled {
+        target: kit.context().path().clone(),
+        delay: Duration::from_secs(1),
+    };
+    assert_eq!(kit.effects(), [retry.clone(), retry]);
+    assert!(kit.self_inbox().try_receive().is_none());
+    kit.fire_timers();
+    assert_eq!(kit.run_inbox().len(), 2);
+    assert_eq!(*kit.behavior().started.lock().unwrap(), ["a", "b", "c"]);
+    assert_eq!(kit.effects().len(), 4);
+    assert_eq!(kit.child_inbox::<u32>("c").unwrap().receive_all(), [3]);
+
+    // cancelled timers do not fire
+    let scheduled = kit.this().tell_after(Cmd::Stop, Duration::from_millis(10));
+    assert!(!scheduled.is_finished());
+    assert!(scheduled.cancel().is_some());
+    kit.fire_timers();
+    assert!(kit.self_inbox().try_receive().is_none());
+    let _ = kit.effects();
+
+    let _ = kit.run(Cmd::Idle(Some(Duration::from_secs(5))));
+    let _ = kit.run(Cmd::Idle(None));
+    assert_eq!(
+        kit.effects(),
+        [
+            Effect::ReceiveTimeout(Some(Duration::from_secs(5))),
+            Effect::ReceiveTimeout(None)
+        ]
+    );
+
+    // failures are returned, the behavior is not supervised
+    assert!(matches!(kit.run(Cmd::Fail), Err(Error::Crashed(_))));
+    assert!(kit.signal(Signal::ReceiveTimeout).is_none());
+    assert!(kit.is_alive());
+
+    // replies wait in an inbox, the behavior stops itself with its next message
+    let replies = kit.inbox::<u32>("replies");
+    let _ = kit.run(Cmd::Done(42, replies.this()));
+    assert_eq!(replies.try_receive(), Some(42));
+    assert!(kit.is_alive());
+    let handled = kit.run_inbox();
+    assert!(matches!(handled[..], [Ok(Behaviors::Stopped)]));
+    assert!(!kit.is_alive());
+}
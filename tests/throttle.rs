@@ -0,0 +1,56 @@
+use elliot::{ActorSystem, Behaviors, Rate};
+use std::{
+    sync::{Arc, Mutex},
+    time::{Duration, Instant},
+};
+
+async fn throttle() {
+    let system = ActorSystem::new();
+    let seen = Arc::new(Mutex::new(Vec::new()));
+
+    let recorder = Arc::clone(&seen);
+    let actor = system.spawn("recorder", move |msg: u32| {
+        let seen = Arc::clone(&recorder);
+        async move {
+            seen.lock().unwrap().push(msg);
+            if msg == 0 {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    });
+
+    let start = Instant::now();
+    let buffered = actor.throttled(Rate::per_second(100).burst(1));
+    for msg in 1..=5 {
+        let _ = buffered.tell(msg);
+    }
+
+    let dropping = actor.throttled(Rate::new(1, Duration::from_secs(3600)).drop_excess());
+    for msg in 10..20 {
+        let _ = dropping.tell(msg);
+    }
+
+    while seen.lock().unwrap().len() < 6 {
+        tokio::time::sleep(Duration::from_millis(5)).await;
+    }
+    assert!(start.elapsed() >= Duration::from_millis(40));
+
+    let _ = actor.tell(0);
+    actor.wait_for_stop().await;
+
+    let mut seen = seen.lock().unwrap().clone();
+    seen.sort_unstable();
+    assert_eq!(seen, [0, 1, 2, 3, 4, 5, 10]);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(throttle());
+}
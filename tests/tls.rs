@@ -0,0 +1,130 @@
+#![cfg(feature = "tls")]
+
+use elliot::{
+    rustls::{
+        crypto::{ring, CryptoProvider},
+        pki_types::{CertificateDer, PrivateKeyDer},
+        server::WebPkiClientVerifier,
+        ClientConfig, RootCertStore, ServerConfig,
+    },
+    ActorSystem, BoxErr, Credentials, MessageCodec, Meta, TlsTransport,
+};
+use std::{sync::Arc, time::Duration};
+use tokio::sync::mpsc;
+
+const CA: &[u8] = include_bytes!("tls/ca.der");
+const SERVER: &[u8] = include_bytes!("tls/server.der");
+const SERVER_KEY: &[u8] = include_bytes!("tls/server.key.der");
+const CLIENT: &[u8] = include_bytes!("tls/client.der");
+const CLIENT_KEY: &[u8] = include_bytes!("tls/client.key.der");
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+fn provider() -> Arc<CryptoProvider> {
+    Arc::new(ring::default_provider())
+}
+
+/// Mutual TLS, every system is verified with a certificate from the test CA.
+fn transport() -> TlsTransport {
+    let mut roots = RootCertStore::empty();
+    roots.add(CertificateDer::from(CA)).unwrap();
+    let roots = Arc::new(roots);
+
+    let verifier = WebPkiClientVerifier::builder_with_provider(Arc::clone(&roots), provider())
+        .build()
+        .unwrap();
+    let server = ServerConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_client_cert_verifier(verifier)
+        .with_single_cert(
+            vec![CertificateDer::from(SERVER.to_vec())],
+            PrivateKeyDer::Pkcs8(SERVER_KEY.to_vec().into()),
+        )
+        .unwrap();
+    let client = ClientConfig::builder_with_provider(provider())
+        .with_safe_default_protocol_versions()
+        .unwrap()
+        .with_root_certificates(roots)
+        .with_client_auth_cert(
+            vec![CertificateDer::from(CLIENT.to_vec())],
+            PrivateKeyDer::Pkcs8(CLIENT_KEY.to_vec().into()),
+        )
+        .unwrap();
+    TlsTransport::new(Arc::new(client), Arc::new(server))
+}
+
+fn authenticate(credentials: &Credentials<'_>) -> Result<Arc<str>, BoxErr> {
+    if credentials.token != Some("secret") {
+        return Err("wrong token".into());
+    }
+    match credentials.certificate {
+        Some(CLIENT) => Ok("client.elliot.test".into()),
+        _ => Err("unknown certificate".into()),
+    }
+}
+
+async fn client(uri: &str, token: &str) -> ActorSystem {
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_transport(transport());
+    client.set_auth_token(token);
+    let remote = client.remote_ref::<String>(uri).unwrap();
+    remote.tell(format!("from {token}")).unwrap();
+    client
+}
+
+async fn tls() {
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    server.set_transport(transport());
+    server.set_authenticator(authenticate);
+    let addr = server.bind("127.0.0.1:0").await.unwrap();
+
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let inbox = server.spawn("inbox", move |meta: Meta, msg: String| {
+        let _ = tx.send((meta.peer, msg));
+        async {}
+    });
+    server.expose(&inbox).unwrap();
+    let uri = format!("elliot://{addr}/user/inbox");
+
+    // rejected systems are disconnected, their messages are lost
+    let _rejected = client(&uri, "guess").await;
+    let nothing = tokio::time::timeout(Duration::from_millis(200), received.recv()).await;
+    assert!(nothing.is_err());
+
+    // the actor sees who sent the message
+    let _accepted = client(&uri, "secret").await;
+    let (peer, msg) = tokio::time::timeout(Duration::from_secs(5), received.recv())
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(peer.as_deref(), Some("client.elliot.test"));
+    assert_eq!(msg, "from secret");
+
+    // local messages have no peer
+    inbox.tell("local".to_owned()).unwrap();
+    let (peer, _) = received.recv().await.unwrap();
+    assert_eq!(peer, None);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(tls());
+}
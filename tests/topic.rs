@@ -0,0 +1,76 @@
+use elliot::{ActorRef, ActorSystem, Behaviors};
+use std::sync::{Arc, Mutex};
+
+#[derive(Clone, Debug, PartialEq)]
+struct PriceUpdate(&'static str, u32);
+
+type Received = Arc<Mutex<Vec<(&'static str, PriceUpdate)>>>;
+
+fn subscriber(
+    system: &ActorSystem,
+    name: &'static str,
+    received: &Received,
+) -> ActorRef<PriceUpdate> {
+    let received = Arc::clone(received);
+    system.spawn(name, move |update: PriceUpdate| {
+        received.lock().unwrap().push((name, update.clone()));
+        async move {
+            if update.1 == 0 {
+                Behaviors::Stopped
+            } else {
+                Behaviors::Same
+            }
+        }
+    })
+}
+
+async fn topics() {
+    let system = ActorSystem::new();
+    let received = Received::default();
+    let alice = subscriber(&system, "alice", &received);
+    let bob = subscriber(&system, "bob", &received);
+
+    // topics of the same type are separate, handles of the same name are shared
+    let prices = system.topic::<PriceUpdate>("prices");
+    let other = system.topic::<PriceUpdate>("other");
+    prices.subscribe(&alice);
+    prices.subscribe(&alice);
+    system.topic::<PriceUpdate>("prices").subscribe(&bob);
+    other.subscribe(&bob);
+    assert_eq!(prices.subscribers(), 2);
+    assert_eq!(prices.name(), "prices");
+
+    assert_eq!(prices.publish(PriceUpdate("ACME", 42)), 2);
+    assert_eq!(other.publish(PriceUpdate("INIT", 7)), 1);
+    while received.lock().unwrap().len() < 3 {
+        tokio::task::yield_now().await;
+    }
+
+    // the subscription ends with the subscriber
+    prices.unsubscribe(&bob);
+    let _ = prices.publish(PriceUpdate("ACME", 0));
+    alice.wait_for_stop().await;
+    assert_eq!(prices.subscribers(), 0);
+    assert_eq!(prices.publish(PriceUpdate("ACME", 43)), 0);
+
+    let mut received = received.lock().unwrap().clone();
+    received.sort_by_key(|(name, update)| (*name, update.1));
+    assert_eq!(
+        received,
+        [
+            ("alice", PriceUpdate("ACME", 0)),
+            ("alice", PriceUpdate("ACME", 42)),
+            ("bob", PriceUpdate("INIT", 7)),
+            ("bob", PriceUpdate("ACME", 42)),
+        ]
+    );
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(topics());
+}
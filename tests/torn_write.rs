@@ -0,0 +1,43 @@
+#![cfg(all(feature = "file-journal", unix))]
+
+use elliot::{FileJournal, Journal};
+
+/// Limit the size of the files that this process writes, writes beyond it fail.
+fn limit_file_size(bytes: libc::rlim_t) {
+    let mut limit = libc::rlimit {
+        rlim_cur: 0,
+        rlim_max: 0,
+    };
+    // SAFETY: plain system calls, a write beyond the limit fails with EFBIG instead of
+    // terminating the process once SIGXFSZ is ignored
+    unsafe {
+        let _ = libc::signal(libc::SIGXFSZ, libc::SIG_IGN);
+        assert_eq!(libc::getrlimit(libc::RLIMIT_FSIZE, &mut limit), 0);
+        limit.rlim_cur = bytes.min(limit.rlim_max);
+        assert_eq!(libc::setrlimit(libc::RLIMIT_FSIZE, &limit), 0);
+    }
+}
+
+#[test]
+fn test() {
+    let path = std::env::temp_dir().join(format!("elliot-torn-{}.jsonl", std::process::id()));
+    let _ = std::fs::remove_file(&path);
+
+    let journal = FileJournal::open(&path).unwrap();
+    journal.append("cart-1", 1, &["figs".to_owned()]).unwrap();
+    let len = std::fs::metadata(&path).unwrap().len();
+
+    // the second record is cut off by the limit
+    limit_file_size(len + 10);
+    assert!(journal.append("cart-1", 2, &["apples".repeat(10)]).is_err());
+    limit_file_size(libc::RLIM_INFINITY);
+    assert_eq!(std::fs::metadata(&path).unwrap().len(), len);
+
+    journal.append("cart-1", 2, &["kiwi".to_owned()]).unwrap();
+    drop(journal);
+    let journal = FileJournal::open(&path).unwrap();
+    let events: Vec<(u64, String)> = journal.replay("cart-1", 1).unwrap();
+    assert_eq!(events, [(1, "figs".into()), (2, "kiwi".into())]);
+
+    let _ = std::fs::remove_file(&path);
+}
@@ -0,0 +1,112 @@
+#![cfg(feature = "remote")]
+
+use elliot::{Accepted, ActorSystem, BoxErr, Connection, Listener, MessageCodec, Transport};
+use std::{
+    collections::HashMap,
+    future::Future,
+    io,
+    net::SocketAddr,
+    pin::Pin,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::mpsc;
+
+struct Utf8;
+
+impl MessageCodec<String> for Utf8 {
+    fn encode(&self, msg: &String) -> Result<Vec<u8>, BoxErr> {
+        Ok(msg.as_bytes().to_vec())
+    }
+
+    fn decode(&self, bytes: &[u8]) -> Result<String, BoxErr> {
+        Ok(String::from_utf8(bytes.to_vec())?)
+    }
+}
+
+type Io<'a, T> = Pin<Box<dyn Future<Output = io::Result<T>> + Send + 'a>>;
+type Accepts = mpsc::UnboundedSender<tokio::io::DuplexStream>;
+
+/// Connects the systems of one process through in-memory pipes.
+#[derive(Clone, Default)]
+struct Pipes {
+    listeners: Arc<Mutex<HashMap<SocketAddr, Accepts>>>,
+}
+
+struct PipeListener(SocketAddr, mpsc::UnboundedReceiver<tokio::io::DuplexStream>);
+
+impl Transport for Pipes {
+    fn connect<'a>(&'a self, authority: &'a str) -> Io<'a, Box<dyn Connection>> {
+        Box::pin(async move {
+            let addr = authority
+                .parse::<SocketAddr>()
+                .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+            let listeners = self.listeners.lock().unwrap();
+            let accepts = listeners
+                .get(&addr)
+                .ok_or(io::ErrorKind::ConnectionRefused)?;
+            let (local, remote) = tokio::io::duplex(64 * 1024);
+            accepts
+                .send(remote)
+                .map_err(|_| io::ErrorKind::ConnectionRefused)?;
+            let local: Box<dyn Connection> = Box::new(local);
+            Ok(local)
+        })
+    }
+
+    fn bind(&self, addr: SocketAddr) -> Io<'_, Box<dyn Listener>> {
+        Box::pin(async move {
+            let (accepts, incoming) = mpsc::unbounded_channel();
+            let _ = self.listeners.lock().unwrap().insert(addr, accepts);
+            let listener: Box<dyn Listener> = Box::new(PipeListener(addr, incoming));
+            Ok(listener)
+        })
+    }
+}
+
+impl Listener for PipeListener {
+    fn local_addr(&self) -> io::Result<SocketAddr> {
+        Ok(self.0)
+    }
+
+    fn accept(&mut self) -> Io<'_, Accepted> {
+        Box::pin(async move {
+            let stream = self.1.recv().await.ok_or(io::ErrorKind::BrokenPipe)?;
+            Ok(Accepted::new(stream))
+        })
+    }
+}
+
+async fn transport() {
+    let pipes = Pipes::default();
+    let server = ActorSystem::new();
+    server.register_codec(Utf8);
+    server.set_transport(pipes.clone());
+    // nothing is bound on the network
+    let addr = server.bind("127.0.0.1:1").await.unwrap();
+
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let inbox = server.spawn("inbox", move |msg: String| {
+        let _ = tx.send(msg);
+        async {}
+    });
+    server.expose(&inbox).unwrap();
+
+    let client = ActorSystem::new();
+    client.register_codec(Utf8);
+    client.set_transport(pipes);
+    let remote = client
+        .remote_ref::<String>(&format!("elliot://{addr}/user/inbox"))
+        .unwrap();
+    remote.tell("through the pipe".to_owned()).unwrap();
+    assert_eq!(received.recv().await.unwrap(), "through the pipe");
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_all()
+        .build()
+        .unwrap()
+        .block_on(transport());
+}
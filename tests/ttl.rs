@@ -0,0 +1,47 @@
+use elliot::ActorSystem;
+use std::{sync::Mutex, time::Duration};
+use tokio::sync::{mpsc, oneshot};
+
+async fn ttl() {
+    let system = ActorSystem::new();
+    let mut stale = system.dead_letters_of::<u32>();
+    let (tx, mut handled) = mpsc::unbounded_channel();
+
+    // the first message blocks the actor until it is released
+    let (release, released) = oneshot::channel::<()>();
+    let gate = Mutex::new(Some(released));
+    let actor = system.spawn("slow", move |n: u32| {
+        let _ = tx.send(n);
+        let gate = gate.lock().unwrap().take();
+        async move {
+            if let Some(released) = gate {
+                let _ = released.await;
+            }
+        }
+    });
+    actor.tell(1).unwrap();
+    assert_eq!(handled.recv().await, Some(1));
+
+    actor.tell_with_ttl(2, Duration::from_millis(10)).unwrap();
+    actor.tell_with_ttl(3, Duration::from_secs(60)).unwrap();
+    actor.tell(4).unwrap();
+    tokio::time::sleep(Duration::from_millis(20)).await;
+    release.send(()).unwrap();
+
+    // the stale message is skipped
+    assert_eq!(handled.recv().await, Some(3));
+    assert_eq!(handled.recv().await, Some(4));
+    let dead_letter = stale.recv().await.unwrap();
+    assert_eq!(dead_letter.message, 2);
+    assert_eq!(dead_letter.recipient, *actor.path());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_time()
+        .build()
+        .unwrap()
+        .block_on(ttl());
+}
@@ -0,0 +1,43 @@
+#![cfg(feature = "net")]
+
+use elliot::{net::UdpActor, ActorSystem};
+use tokio::{net::UdpSocket, sync::mpsc};
+
+async fn udp() {
+    let system = ActorSystem::new();
+    let (tx, mut received) = mpsc::unbounded_channel();
+    let (echo, addr) = UdpActor::bind(&system, "echo", "127.0.0.1:0", |sender| {
+        move |(from, data): (_, Vec<u8>)| {
+            let _ = tx.send((from, data.clone()));
+            sender.tell((from, data.to_ascii_uppercase())).unwrap();
+            async {}
+        }
+    })
+    .await
+    .unwrap();
+
+    let client = UdpSocket::bind("127.0.0.1:0").await.unwrap();
+    let client_addr = client.local_addr().unwrap();
+    let mut buf = [0; 16];
+    for data in [&b"ping"[..], b"pong"] {
+        let _ = client.send_to(data, addr).await.unwrap();
+        let (len, from) = client.recv_from(&mut buf).await.unwrap();
+        assert_eq!(from, addr);
+        assert_eq!(&buf[..len], data.to_ascii_uppercase());
+        assert_eq!(received.recv().await, Some((client_addr, data.to_vec())));
+    }
+
+    // the actor stops with its last ref, and the socket is closed with it
+    drop(echo);
+    assert_eq!(received.recv().await, None);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .unwrap()
+        .block_on(udp());
+}
@@ -0,0 +1,58 @@
+use elliot::{ActorSystem, Behaviors, Terminated};
+use std::sync::{Arc, Mutex};
+
+async fn watch() {
+    let system = ActorSystem::new();
+    let terminated = Arc::new(Mutex::new(Vec::new()));
+
+    let log = Arc::clone(&terminated);
+    let watcher = system.spawn("watcher", move |msg: Terminated| {
+        log.lock().unwrap().push(msg);
+        async { Behaviors::Same }
+    });
+    let worker = system.spawn("worker", |stop: bool| async move {
+        if stop {
+            Behaviors::Stopped
+        } else {
+            Behaviors::Same
+        }
+    });
+
+    // watching does not keep the worker alive
+    system.watch(&worker, &watcher, |t| t);
+    let weak = worker.downgrade();
+    let _ = worker.tell(false);
+    let _ = worker.tell(true);
+    drop(worker);
+
+    while terminated.lock().unwrap().is_empty() {
+        tokio::task::yield_now().await;
+    }
+    assert!(weak.upgrade().is_none());
+    assert_eq!(
+        terminated.lock().unwrap().pop().unwrap(),
+        Terminated {
+            path: weak.path().clone(),
+            unreachable: false
+        }
+    );
+
+    // watching a stopped actor notifies immediately
+    let gone = system.spawn("gone", |_: ()| async { Behaviors::Stopped });
+    let _ = gone.tell(());
+    gone.wait_for_stop().await;
+    system.watch(&gone, &watcher, |t| t);
+    while terminated.lock().unwrap().is_empty() {
+        tokio::task::yield_now().await;
+    }
+    assert_eq!(terminated.lock().unwrap()[0].path.as_str(), "/user/gone");
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(watch());
+}
@@ -0,0 +1,104 @@
+#![cfg(feature = "websocket")]
+
+use elliot::{
+    net::{OutFrame, WebSocketEvent},
+    ActorSystem, Behaviors,
+};
+use futures_core::Stream;
+use futures_sink::Sink;
+use std::{future::poll_fn, pin::Pin};
+use tokio::{
+    net::{TcpListener, TcpStream},
+    sync::mpsc,
+};
+use tokio_tungstenite::{
+    tungstenite::{Error, Message},
+    WebSocketStream,
+};
+
+async fn next(client: &mut WebSocketStream<TcpStream>) -> Option<Message> {
+    poll_fn(|cx| Pin::new(&mut *client).poll_next(cx))
+        .await
+        .map(Result::unwrap)
+}
+
+async fn send(client: &mut WebSocketStream<TcpStream>, msg: Message) -> Result<(), Error> {
+    poll_fn(|cx| Pin::new(&mut *client).poll_ready(cx)).await?;
+    Pin::new(&mut *client).start_send(msg)?;
+    poll_fn(|cx| Pin::new(&mut *client).poll_flush(cx)).await
+}
+
+async fn echo() {
+    let system = ActorSystem::new();
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let addr = listener.local_addr().unwrap();
+    let (tx, mut events) = mpsc::unbounded_channel();
+
+    let server = tokio::spawn(async move {
+        let (stream, _) = listener.accept().await.unwrap();
+        let socket = tokio_tungstenite::accept_async(stream).await.unwrap();
+        system.spawn_websocket("echo", socket, move |out| {
+            move |event: WebSocketEvent| {
+                let _ = tx.send(event.clone());
+                match event {
+                    WebSocketEvent::Text(text) if text == "bye" => out.tell(OutFrame::Close),
+                    WebSocketEvent::Text(text) => out.tell(OutFrame::Text(text)),
+                    WebSocketEvent::Binary(data) => out.tell(OutFrame::Binary(data)),
+                    WebSocketEvent::Closed => Ok(()),
+                }
+                .unwrap();
+                async { Behaviors::Same }
+            }
+        })
+    });
+
+    let stream = TcpStream::connect(addr).await.unwrap();
+    let (mut client, _) = tokio_tungstenite::client_async(format!("ws://{addr}/"), stream)
+        .await
+        .unwrap();
+    let echo = server.await.unwrap();
+
+    send(&mut client, Message::text("hello")).await.unwrap();
+    assert_eq!(next(&mut client).await, Some(Message::text("hello")));
+    send(&mut client, Message::binary(vec![1, 2, 3]))
+        .await
+        .unwrap();
+    assert_eq!(
+        next(&mut client).await,
+        Some(Message::binary(vec![1, 2, 3]))
+    );
+
+    // pings are answered by the adapter
+    send(&mut client, Message::Ping(vec![42].into()))
+        .await
+        .unwrap();
+    assert_eq!(
+        next(&mut client).await,
+        Some(Message::Pong(vec![42].into()))
+    );
+
+    // the actor closes the connection
+    send(&mut client, Message::text("bye")).await.unwrap();
+    assert!(matches!(next(&mut client).await, Some(Message::Close(_))));
+    assert_eq!(next(&mut client).await, None);
+
+    for expected in [
+        WebSocketEvent::Text("hello".into()),
+        WebSocketEvent::Binary(vec![1, 2, 3]),
+        WebSocketEvent::Text("bye".into()),
+        WebSocketEvent::Closed,
+    ] {
+        assert_eq!(events.recv().await, Some(expected));
+    }
+    assert!(echo.is_alive());
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .enable_io()
+        .build()
+        .unwrap()
+        .block_on(echo());
+}
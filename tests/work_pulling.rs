@@ -0,0 +1,50 @@
+use elliot::{ActorSystem, Behaviors, Work};
+use std::sync::{
+    atomic::{AtomicUsize, Ordering},
+    Arc,
+};
+
+async fn work_pulling() {
+    let system = ActorSystem::new();
+    let producer = system.spawn_producer("producer");
+    let handled = Arc::new(AtomicUsize::new(0));
+
+    let workers = ["worker-a", "worker-b"].map(|name| {
+        let handled = Arc::clone(&handled);
+        system.spawn_worker(name, &producer, 2, move |item: u32| {
+            let handled = Arc::clone(&handled);
+            async move {
+                let _ = handled.fetch_add(1, Ordering::SeqCst);
+                if item == u32::MAX {
+                    Behaviors::Stopped
+                } else {
+                    Behaviors::Same
+                }
+            }
+        })
+    });
+
+    for item in 0..100 {
+        let _ = producer.tell(Work::Submit(item));
+    }
+    while handled.load(Ordering::SeqCst) < 100 {
+        tokio::task::yield_now().await;
+    }
+
+    // a stopped worker does not ask for more work
+    let _ = producer.tell(Work::Submit(u32::MAX));
+    let _ = producer.tell(Work::Submit(u32::MAX));
+    for worker in &workers {
+        worker.wait_for_stop().await;
+    }
+    assert_eq!(handled.load(Ordering::SeqCst), 102);
+}
+
+#[test]
+fn test() {
+    tokio::runtime::Builder::new_multi_thread()
+        .worker_threads(2)
+        .build()
+        .unwrap()
+        .block_on(work_pulling());
+}